@@ -0,0 +1,411 @@
+//! The `appctl` command line: global flags and subcommands.
+
+use crate::cmd::{BackupAction, CredentialsAction, HistoryAction, ProfileAction, QueueAction};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(
+    name = "appctl",
+    version,
+    about = "CLI test harness for the Tauri template app",
+    arg_required_else_help = true
+)]
+pub struct Cli {
+    /// Skip network probes, LLM calls, and update checks instead of
+    /// letting them time out (also $APP__OFFLINE=1).
+    #[arg(long, global = true)]
+    pub offline: bool,
+    /// Validate without side effects: file writes, deletes, and links
+    /// report what they would do, other mutating commands are skipped
+    /// (also $APP__DRY_RUN=1).
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Run id for the (first) result; later results get `-<n>` suffixes
+    /// (also $APP__RUN_ID).
+    #[arg(long, global = true)]
+    pub run_id: Option<String>,
+    /// Seed for generated run ids and other randomness, for reproducible
+    /// runs (also $APP__SEED).
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+    /// App identifier the keychain service, autostart entry, and profiles
+    /// use; match the GUI's `identifier` (also $APP__APP_ID).
+    #[arg(long, global = true)]
+    pub app_id: Option<String>,
+    /// Print the engine's version, git commit, build date, cargo features,
+    /// and rustc version as JSON, then exit.
+    #[arg(long)]
+    pub build_info: bool,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Collect environment facts and emit an env summary.
+    Doctor {
+        /// Output as JSON instead of human-readable text.
+        #[arg(long)]
+        json: bool,
+        /// Write result JSON to this path.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Invoke a backend command by name with JSON args.
+    Call {
+        /// Command name (e.g. "ping", "read_file", "write_file").
+        cmd: String,
+        /// JSON args to pass to the command.
+        #[arg(long, default_value = "{}")]
+        args: String,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Timeout duration (e.g. "30s", "5000ms"). Currently informational.
+        #[arg(long)]
+        timeout: Option<String>,
+        /// Directory for artifacts output.
+        #[arg(long)]
+        artifacts: Option<PathBuf>,
+    },
+
+    /// Targeted capability check (`--list` shows the available probes).
+    Probe {
+        /// Probe name (e.g. "filesystem", "network", "clipboard"), or a probe
+        /// suite: "all", "desktop", "headless-ci", or one from $APP__PROBE_SUITES.
+        #[arg(required_unless_present = "list")]
+        target: Option<String>,
+        /// List probes with their capabilities, platforms, and arguments.
+        #[arg(long, conflicts_with = "target")]
+        list: bool,
+        /// JSON args to pass to the probe.
+        #[arg(long, default_value = "null")]
+        args: String,
+        /// Endpoint URL for the network probe; repeat for several. Replaces
+        /// $APP__NETWORK_ENDPOINTS.
+        #[arg(long = "endpoint")]
+        endpoints: Vec<String>,
+        /// Also measure latency percentiles and download bandwidth in the
+        /// network probe (reported only; set budgets in a scenario step).
+        #[arg(long)]
+        throughput: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Directory for artifacts output.
+        #[arg(long)]
+        artifacts: Option<PathBuf>,
+    },
+
+    /// Run a scripted scenario from a YAML file.
+    RunScenario {
+        /// Path to the scenario YAML file.
+        #[arg(required_unless_present = "builtin")]
+        file: Option<PathBuf>,
+        /// Run a scenario compiled into appctl instead of a file: "smoke"
+        /// (ping, filesystem and network probes, doctor).
+        #[arg(long, conflicts_with = "file")]
+        builtin: Option<String>,
+        /// Directory for artifacts output.
+        #[arg(long)]
+        artifacts: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Pause before each step, show it with its resolved args, and ask
+        /// whether to run it, skip it, go back, or abort. Without a TTY,
+        /// answers are read from stdin one per line.
+        #[arg(long)]
+        interactive: bool,
+        /// Print the steps that would run, with every default filled in,
+        /// without running them.
+        #[arg(long, conflicts_with = "interactive")]
+        dry_run: bool,
+        /// Run only steps tagged with one of these (comma-separated).
+        /// Steps inherit the scenario's own tags.
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// Leave out steps tagged with any of these (comma-separated).
+        #[arg(long, value_delimiter = ',')]
+        skip_tags: Vec<String>,
+    },
+
+    /// Run every *.yaml/*.yml scenario in a directory and report totals.
+    RunScenarios {
+        /// Directory of scenario files (not searched recursively).
+        dir: PathBuf,
+        /// Scenarios to run at once.
+        #[arg(long, default_value_t = 1)]
+        jobs: usize,
+        /// Directory for artifacts output.
+        #[arg(long)]
+        artifacts: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Run only steps tagged with one of these (comma-separated).
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// Leave out steps tagged with any of these (comma-separated).
+        #[arg(long, value_delimiter = ',')]
+        skip_tags: Vec<String>,
+    },
+
+    /// Run a scenario on another machine over SSH and pull back its
+    /// artifacts. Copies this appctl binary unless --remote-bin is given.
+    RunRemote {
+        /// SSH destination: `user@host` or a `Host` alias.
+        #[arg(long)]
+        host: String,
+        /// Path to the scenario YAML file (read locally).
+        #[arg(long)]
+        scenario: PathBuf,
+        /// appctl already installed on the remote, instead of copying this
+        /// binary (needed when the remote OS or architecture differs).
+        #[arg(long)]
+        remote_bin: Option<String>,
+        /// Extra ssh/scp option, as for `-o` (repeatable), e.g. `Port=2222`.
+        #[arg(long = "ssh-option")]
+        ssh_options: Vec<String>,
+        /// Keep the remote scratch directory instead of removing it.
+        #[arg(long)]
+        keep_remote: bool,
+        /// Directory the remote run's artifacts are copied into.
+        #[arg(long)]
+        artifacts: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Classify this host as supported, degraded, or unsupported from the
+    /// compatibility rules. Exits 1 when unsupported.
+    Compatibility {
+        /// Rules YAML (default: $APP__COMPAT_RULES, else the built-in rules).
+        #[arg(long)]
+        rules: Option<PathBuf>,
+        /// Exit 1 when degraded as well.
+        #[arg(long)]
+        strict: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Run the GUI's first-run checks headlessly, with the same progress
+    /// events and report. Exits 1 when a required check fails.
+    FirstRun {
+        /// Checks YAML (default: $APP__FIRST_RUN_CHECKS, else the built-in
+        /// checks).
+        #[arg(long)]
+        checks: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Run the GUI's launch readiness sequence (config, migrations,
+    /// required first-run probes) headlessly, with the same progress
+    /// events. Exits 1 when a stage fails.
+    ReadyCheck {
+        /// Checks YAML whose required probes gate readiness (default:
+        /// $APP__FIRST_RUN_CHECKS, else the built-in checks).
+        #[arg(long)]
+        checks: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Bring the app data directory up to this build's version (what the
+    /// GUI does at launch); `--dry-run` lists the changes instead.
+    Migrate {
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check the release manifest for a newer version. Never installs;
+    /// `--download` additionally fetches and verifies the artifact.
+    UpdateCheck {
+        /// Manifest URL (default: $APP__UPDATE_MANIFEST_URL).
+        #[arg(long)]
+        manifest_url: Option<String>,
+        /// Version to compare against (default: this build's version).
+        #[arg(long)]
+        current_version: Option<String>,
+        /// Minisign public key, base64 (default: $APP__UPDATE_PUBKEY).
+        #[arg(long)]
+        pubkey: Option<String>,
+        /// Download and verify the update into this directory.
+        #[arg(long)]
+        download: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Upload pending opt-in telemetry counts (`telemetry_status` /
+    /// `telemetry_set` via `call` show and change consent).
+    TelemetryFlush {
+        /// Upload even if fewer than `batch_size` runs are pending.
+        #[arg(long)]
+        force: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Start daemon mode over a Unix socket or TCP.
+    Serve {
+        /// Path for the Unix domain socket.
+        #[arg(long, required_unless_present = "listen")]
+        socket: Option<PathBuf>,
+        /// Listen on this TCP address instead (e.g. 0.0.0.0:7400), for
+        /// `fleet run` across machines. There is no authentication: bind
+        /// to a private network only.
+        #[arg(long, conflicts_with = "socket")]
+        listen: Option<String>,
+        /// Also serve Prometheus metrics at http://<addr>/metrics
+        /// (e.g. 0.0.0.0:9464).
+        #[arg(long)]
+        metrics_addr: Option<String>,
+        /// Refuse requests longer than this many bytes (also
+        /// $APP__MAX_REQUEST_BYTES; default 16 MiB).
+        #[arg(long)]
+        max_request_bytes: Option<usize>,
+    },
+
+    /// Serve a directory over HTTP on a random localhost port until
+    /// killed, for hermetic network and download tests. Prints the URL
+    /// (with --json, `{"port", "url", "root"}`) once listening.
+    ServeHttpFixture {
+        dir: PathBuf,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check appctl itself: command dispatch, artifact writing, a daemon
+    /// round-trip, and the scenario engine.
+    SelfTest {
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Push an artifacts run directory (`<dir>/<run_id>`) to the targets in
+    /// $APP__EXPORT. Runs with --artifacts are exported automatically.
+    Export {
+        run_dir: PathBuf,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Provision secrets into the OS credential store under the app's
+    /// identifier, where the GUI reads them (e.g. `--namespace llm openai`
+    /// for an API key).
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsAction,
+    },
+
+    /// Back up the app data directory to a zip with integrity hashes, list
+    /// backups, or restore one (`--dry-run` shows what would change).
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
+    /// Export the user's settings (no secrets) as a signed profile, or
+    /// import one with schema, signature, and conflict checks.
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
+    /// List, retry, or run the jobs in the durable task queue
+    /// (`<data_dir>/queue.json`) that the GUI works through in the
+    /// background.
+    Queue {
+        #[command(subcommand)]
+        action: QueueAction,
+    },
+
+    /// Query and prune the run history every execution is recorded in
+    /// (`<data_dir>/history.jsonl`, or $APP__HISTORY).
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+
+    /// Run scenarios across several daemons (`serve --socket`/`--listen`).
+    Fleet {
+        #[command(subcommand)]
+        action: FleetAction,
+    },
+
+    /// Talk to a running daemon (`serve --socket`/`--listen`).
+    Client {
+        #[command(subcommand)]
+        action: ClientAction,
+    },
+
+    /// Emit a desktop event (skeleton – returns UNIMPLEMENTED).
+    Emit {
+        /// Event type: tray-click | deep-link | file-drop | app-focus
+        event: String,
+        /// Optional event payload as JSON.
+        #[arg(long, default_value = "{}")]
+        payload: String,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum FleetAction {
+    /// Run a scenario on every target at once and report a step-by-target
+    /// matrix plus per-OS statuses.
+    Run {
+        /// Targets file: `targets: [{name, socket | tcp}]`.
+        #[arg(long)]
+        targets: PathBuf,
+        /// Path to the scenario YAML file (read locally).
+        #[arg(long)]
+        scenario: PathBuf,
+        /// Give up on a target that has not answered after this many seconds.
+        #[arg(long)]
+        timeout_secs: Option<u64>,
+        /// Directory for artifacts output.
+        #[arg(long)]
+        artifacts: Option<PathBuf>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ClientAction {
+    /// Check a daemon is healthy: its protocol version and engine build
+    /// (from the `hello` handshake) and round-trip latency of `ping` calls.
+    Ping {
+        /// Unix socket the daemon listens on.
+        #[arg(long, required_unless_present = "tcp")]
+        socket: Option<PathBuf>,
+        /// TCP address the daemon listens on instead (e.g. 10.0.0.7:7400).
+        #[arg(long, conflicts_with = "socket")]
+        tcp: Option<String>,
+        /// Number of pings.
+        #[arg(long, default_value_t = 4)]
+        count: u32,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
@@ -0,0 +1,114 @@
+//! Writing results, events, and exports to an artifacts directory.
+
+use crate::events::EventRecorder;
+use engine::types::*;
+use engine::{AppContext, CommandResult};
+
+pub fn write_result_file(path: &std::path::Path, result: &CommandResult) {
+    let j = serde_json::to_string_pretty(result).unwrap_or_default();
+    if let Err(e) = std::fs::write(path, &j) {
+        eprintln!(
+            "warning: failed to write result to {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+pub fn write_artifacts(
+    dir: &std::path::Path,
+    result: &CommandResult,
+    recorder: Option<EventRecorder>,
+) {
+    let art_dir = dir.join(&result.run_id);
+    if let Err(e) = std::fs::create_dir_all(&art_dir) {
+        eprintln!(
+            "warning: failed to create artifacts dir {}: {}",
+            art_dir.display(),
+            e
+        );
+        return;
+    }
+
+    // result.json
+    let result_path = art_dir.join("result.json");
+    let j = serde_json::to_string_pretty(result).unwrap_or_default();
+    let _ = std::fs::write(&result_path, &j);
+
+    // events.jsonl: engine events, then the result itself
+    write_events(&art_dir, recorder, std::slice::from_ref(result));
+}
+
+/// Write the outputs scenario steps kept with `save_artifacts`.
+pub fn write_step_artifacts(art_dir: &std::path::Path, result: &ScenarioResult) {
+    for artifact in &result.captured {
+        let path = art_dir.join(&artifact.path);
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, &artifact.bytes));
+        if let Err(e) = written {
+            eprintln!(
+                "warning: failed to write step artifact {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Push `run_dir` to the configured export targets, if any; the outcome
+/// goes to stderr and never changes the exit code.
+pub async fn export_artifacts(ctx: &AppContext, run_dir: &std::path::Path) {
+    if ctx.export.targets.is_empty() {
+        return;
+    }
+    let exporters = engine::export::exporters(ctx);
+    match engine::export::export_run(ctx, &exporters, run_dir).await {
+        Ok(reports) => {
+            for r in reports {
+                match r.error {
+                    None => eprintln!("exported {} files to {}", r.files.len(), r.target),
+                    Some(e) => eprintln!("warning: export to {} failed: {}", r.target, e),
+                }
+            }
+        }
+        Err(e) => eprintln!("warning: export of {} failed: {}", run_dir.display(), e),
+    }
+}
+
+/// Write `events.jsonl` in `art_dir`: the engine events recorded during the
+/// run (if any), followed by one line per entry of `records`.
+pub fn write_events<T: serde::Serialize>(
+    art_dir: &std::path::Path,
+    recorder: Option<EventRecorder>,
+    records: &[T],
+) {
+    use std::io::Write;
+
+    if let Some(recorder) = recorder {
+        if let Err(e) = recorder.finish(art_dir) {
+            eprintln!("warning: failed to save recorded events: {}", e);
+        }
+    }
+    let events_path = art_dir.join("events.jsonl");
+    let mut lines = String::new();
+    for record in records {
+        if let Ok(line) = serde_json::to_string(record) {
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+    }
+    let appended = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&events_path)
+        .and_then(|mut f| f.write_all(lines.as_bytes()));
+    if let Err(e) = appended {
+        eprintln!(
+            "warning: failed to write events to {}: {}",
+            events_path.display(),
+            e
+        );
+    }
+}
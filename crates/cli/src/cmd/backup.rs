@@ -0,0 +1,57 @@
+//! `backup`: archive and restore the data directory.
+
+use super::output::output_result;
+use clap::Subcommand;
+use engine::{AppContext, CommandRegistry};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum BackupAction {
+    /// Archive the data directory into the backup directory, keeping the
+    /// newest `backup.keep`.
+    Create {
+        /// Write the backup here instead (no retention applies).
+        #[arg(long)]
+        dest_dir: Option<PathBuf>,
+        /// A note stored in the backup's manifest.
+        #[arg(long)]
+        label: Option<String>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Replace the data directory's files with a backup's, after checking
+    /// its hashes and saving the current data as a backup.
+    Restore {
+        path: PathBuf,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the backups in the backup directory, newest first.
+    List {
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn cmd_backup(action: BackupAction, ctx: &AppContext, registry: &CommandRegistry) {
+    let (cmd, args, json) = match action {
+        BackupAction::Create {
+            dest_dir,
+            label,
+            json,
+        } => (
+            "backup_create",
+            serde_json::json!({ "dest_dir": dest_dir, "label": label }),
+            json,
+        ),
+        BackupAction::Restore { path, json } => {
+            ("backup_restore", serde_json::json!({ "path": path }), json)
+        }
+        BackupAction::List { json } => ("backup_list", serde_json::json!({}), json),
+    };
+    let result = registry.execute(cmd, args, ctx);
+    output_result(ctx, &result, json);
+}
@@ -0,0 +1,204 @@
+//! `doctor`, `call`, `probe`, and `emit`: one engine command or probe per
+//! invocation.
+
+use super::artifacts::{export_artifacts, write_artifacts, write_events, write_result_file};
+use super::output::output_result;
+use crate::events::EventRecorder;
+use engine::types::*;
+use engine::{AppContext, CommandRegistry, CommandResult, ProbeRegistry};
+use std::path::PathBuf;
+
+pub async fn cmd_doctor(json: bool, out: Option<PathBuf>, ctx: &AppContext) {
+    let result = engine::doctor::run_doctor(ctx);
+    if let Some(ref path) = out {
+        write_result_file(path, &result);
+    }
+    output_result(ctx, &result, json);
+}
+
+pub async fn cmd_call(
+    cmd: &str,
+    args_str: &str,
+    json: bool,
+    artifacts: Option<PathBuf>,
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+) {
+    let args: serde_json::Value = match serde_json::from_str(args_str) {
+        Ok(v) => v,
+        Err(e) => {
+            let r = result_err(
+                "call",
+                cmd,
+                &ctx.new_run_id(),
+                0,
+                ErrorCode::InvalidInput,
+                format!("invalid JSON args: {}", e),
+            );
+            output_result(ctx, &r, json);
+            return;
+        }
+    };
+
+    let recorder = artifacts
+        .as_deref()
+        .and_then(|dir| EventRecorder::start(ctx, dir));
+    let result = registry.execute(cmd, args, ctx);
+    if let Some(ref dir) = artifacts {
+        write_artifacts(dir, &result, recorder);
+        export_artifacts(ctx, &dir.join(&result.run_id)).await;
+    }
+    output_result(ctx, &result, json);
+}
+
+pub async fn cmd_probe(
+    target: &str,
+    args_str: &str,
+    json: bool,
+    artifacts: Option<PathBuf>,
+    ctx: &AppContext,
+    probes: &ProbeRegistry,
+) {
+    let args: serde_json::Value = match serde_json::from_str(args_str) {
+        Ok(v) => v,
+        Err(e) => {
+            let r = result_err(
+                "probe",
+                target,
+                &ctx.new_run_id(),
+                0,
+                ErrorCode::InvalidInput,
+                format!("invalid JSON args: {}", e),
+            );
+            output_result(ctx, &r, json);
+            return;
+        }
+    };
+
+    let recorder = artifacts
+        .as_deref()
+        .and_then(|dir| EventRecorder::start(ctx, dir));
+    let result = probes.run(target, args, ctx).await;
+    if let Some(ref dir) = artifacts {
+        write_artifacts(dir, &result, recorder);
+        export_artifacts(ctx, &dir.join(&result.run_id)).await;
+    }
+    output_result(ctx, &result, json);
+}
+
+/// Run a probe suite; exits 1 when a critical probe failed.
+pub async fn cmd_probe_suite(
+    name: &str,
+    json: bool,
+    artifacts: Option<PathBuf>,
+    ctx: &AppContext,
+    probes: &ProbeRegistry,
+) {
+    // Reserved first, so a fixed --run-id names the artifacts directory.
+    let run_id = artifacts.as_ref().map(|_| ctx.new_run_id());
+    let recorder = artifacts
+        .as_deref()
+        .and_then(|dir| EventRecorder::start(ctx, dir));
+    let Some(suite_result) = engine::suites::run_suite(name, ctx, probes).await else {
+        return;
+    };
+    engine::history::record_results(
+        ctx,
+        "cli",
+        Some(&format!("suite:{}", name)),
+        &suite_result.step_results,
+    );
+
+    if json {
+        let j = serde_json::to_string_pretty(&suite_result).unwrap_or_default();
+        println!("{}", j);
+    } else {
+        println!("Suite: {}", suite_result.name);
+        println!("Overall: {:?}", suite_result.overall_status);
+        for (line, r) in suite_result.summary.iter().zip(&suite_result.step_results) {
+            println!(
+                "  {}{} -> {:?} ({}ms)",
+                line.probe,
+                if line.critical { " [critical]" } else { "" },
+                line.status,
+                r.timing_ms.total
+            );
+        }
+    }
+
+    if let (Some(dir), Some(run_id)) = (&artifacts, run_id) {
+        let art_dir = dir.join(&run_id);
+        let _ = std::fs::create_dir_all(&art_dir);
+        let j = serde_json::to_string_pretty(&suite_result).unwrap_or_default();
+        let _ = std::fs::write(art_dir.join("result.json"), j);
+        write_events(&art_dir, recorder, &suite_result.step_results);
+        export_artifacts(ctx, &art_dir).await;
+    }
+
+    if suite_result.overall_status == Status::Fail {
+        std::process::exit(1);
+    }
+}
+
+pub fn cmd_probe_list(json: bool, ctx: &AppContext, probes: &ProbeRegistry) {
+    let list = probes.list();
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&list).unwrap_or_default()
+        );
+        return;
+    }
+    for info in list {
+        println!("{:<16} {}", info.name, info.description);
+        if !info.capabilities.is_empty() {
+            println!("{:<16}   uses: {}", "", info.capabilities.join(", "));
+        }
+        if !info.platforms.is_empty() {
+            println!("{:<16}   only on: {}", "", info.platforms.join(", "));
+        }
+        for (arg, about) in &info.args {
+            println!("{:<16}   --args {}: {}", "", arg, about);
+        }
+    }
+    let suites: Vec<&str> = ctx.probe_suites.keys().map(String::as_str).collect();
+    println!("\nsuites: {}", suites.join(", "));
+}
+
+pub async fn cmd_emit(event: &str, json: bool, ctx: &AppContext) {
+    let run_id = ctx.new_run_id();
+    let headless = detect_headless();
+
+    let (status, code, msg) = if headless {
+        (
+            Status::Skip,
+            ErrorCode::Unsupported,
+            format!("event '{}' unsupported in headless environment", event),
+        )
+    } else {
+        (
+            Status::Skip,
+            ErrorCode::Unimplemented,
+            format!("event '{}' is not yet implemented (skeleton)", event),
+        )
+    };
+
+    let result = CommandResult {
+        run_id,
+        command: "emit".to_string(),
+        target: event.to_string(),
+        status,
+        error: Some(ErrorInfo {
+            code,
+            message: msg,
+            details: serde_json::Value::Null,
+        }),
+        timing_ms: TimingInfo::default(),
+        artifacts: vec![],
+        env_summary: EnvSummary::default(),
+        data: None,
+        cached_age_ms: None,
+        deprecation: None,
+    };
+    output_result(ctx, &result, json);
+}
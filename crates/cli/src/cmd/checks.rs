@@ -0,0 +1,250 @@
+//! `compatibility`, `first-run`, `ready-check`, `migrate`, and `update-check`.
+
+use super::output::output_result;
+use engine::types::*;
+use engine::{AppContext, CommandRegistry, ProbeRegistry};
+use std::path::PathBuf;
+
+pub async fn cmd_compatibility(
+    rules: Option<PathBuf>,
+    strict: bool,
+    json: bool,
+    ctx: &AppContext,
+    probes: &ProbeRegistry,
+) {
+    let rules = match rules {
+        Some(path) => {
+            let loaded = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read rules file: {}", e))
+                .and_then(|yaml| engine::compat::load(&yaml));
+            match loaded {
+                Ok(rules) => rules,
+                Err(e) => {
+                    let target = path.display().to_string();
+                    let r = result_err(
+                        "compatibility",
+                        &target,
+                        &ctx.new_run_id(),
+                        0,
+                        ErrorCode::InvalidInput,
+                        e,
+                    );
+                    output_result(ctx, &r, json);
+                    return;
+                }
+            }
+        }
+        None => ctx.compat_rules.clone(),
+    };
+    let mut result = engine::compat::run_compatibility(&rules, ctx, probes).await;
+    if strict && result.target == "degraded" {
+        result.status = Status::Fail;
+    }
+    if json {
+        output_result(ctx, &result, true);
+        return;
+    }
+
+    engine::history::record_results(ctx, "cli", None, std::slice::from_ref(&result));
+    let report: Option<CompatReport> = result
+        .data
+        .clone()
+        .and_then(|d| serde_json::from_value(d).ok());
+    if let Some(report) = report {
+        println!("Tier: {:?}", report.tier);
+        println!(
+            "Host: {} {}",
+            report.os,
+            report.os_version.as_deref().unwrap_or("(version unknown)")
+        );
+        for reason in &report.reasons {
+            println!("  [{:?}] {}", reason.tier, reason.message);
+        }
+        for check in &report.checks {
+            println!(
+                "  {}{} -> {:?}",
+                check.probe,
+                if check.critical { " [required]" } else { "" },
+                check.status
+            );
+        }
+    }
+    if result.status == Status::Fail {
+        std::process::exit(1);
+    }
+}
+
+/// Replace the context's first-run checks with those in `path`, if given.
+/// `false` (after reporting the error) if the file is unusable.
+pub fn load_first_run_checks(
+    path: Option<PathBuf>,
+    command: &str,
+    ctx: &mut AppContext,
+    json: bool,
+) -> bool {
+    let Some(path) = path else {
+        return true;
+    };
+    let loaded = std::fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read checks file: {}", e))
+        .and_then(|yaml| engine::first_run::load(&yaml));
+    match loaded {
+        Ok(checks) => {
+            ctx.first_run_checks = checks;
+            true
+        }
+        Err(e) => {
+            let target = path.display().to_string();
+            let r = result_err(
+                command,
+                &target,
+                &ctx.new_run_id(),
+                0,
+                ErrorCode::InvalidInput,
+                e,
+            );
+            output_result(ctx, &r, json);
+            false
+        }
+    }
+}
+
+pub async fn cmd_first_run(json: bool, ctx: &AppContext, probes: &ProbeRegistry) {
+    if json {
+        let result = engine::first_run::run_checks(ctx, probes).await;
+        output_result(ctx, &result, true);
+        return;
+    }
+
+    // The progress a splash window would show.
+    let sub = ctx.events().subscribe(|ev| {
+        let p = &ev.payload;
+        let step = format!(
+            "[{}/{}]",
+            p["index"].as_u64().unwrap_or(0) + 1,
+            p["total"].as_u64().unwrap_or(0)
+        );
+        match ev.topic.as_str() {
+            "first_run:checking" => {
+                eprintln!("{} {}...", step, p["title"].as_str().unwrap_or_default())
+            }
+            "first_run:checked" => {
+                let status = p["status"].as_str().unwrap_or_default().to_uppercase();
+                let required = if p["required"] == true {
+                    ""
+                } else {
+                    " (optional)"
+                };
+                eprintln!("{} {}{}", step, status, required);
+                if let Some(message) = p["message"].as_str() {
+                    eprintln!("      {}", message);
+                }
+                if let Some(link) = p["remediation"].as_str() {
+                    eprintln!("      fix: {}", link);
+                }
+            }
+            _ => {}
+        }
+    });
+    let result = engine::first_run::run_checks(ctx, probes).await;
+    ctx.events().unsubscribe(sub);
+    engine::history::record_results(ctx, "cli", None, std::slice::from_ref(&result));
+    match &result.error {
+        None => println!("First run: passed"),
+        Some(e) => println!("First run: failed – {}", e.message),
+    }
+    if result.status == Status::Fail {
+        std::process::exit(1);
+    }
+}
+
+pub async fn cmd_ready_check(
+    json: bool,
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) {
+    if json {
+        let result = engine::readiness::run_ready_check(ctx, registry, probes).await;
+        output_result(ctx, &result, true);
+        return;
+    }
+
+    // The progress a splash window would show.
+    let sub = ctx.events().subscribe(|ev| {
+        let p = &ev.payload;
+        let step = format!(
+            "[{}/{}]",
+            p["index"].as_u64().unwrap_or(0) + 1,
+            p["total"].as_u64().unwrap_or(0)
+        );
+        match ev.topic.as_str() {
+            "ready:checking" => {
+                eprintln!("{} {}...", step, p["title"].as_str().unwrap_or_default())
+            }
+            "ready:checked" => {
+                let status = p["status"].as_str().unwrap_or_default().to_uppercase();
+                eprintln!("{} {} ({} ms)", step, status, p["duration_ms"]);
+                if let Some(message) = p["message"].as_str() {
+                    eprintln!("      {}", message);
+                }
+            }
+            _ => {}
+        }
+    });
+    let result = engine::readiness::run_ready_check(ctx, registry, probes).await;
+    ctx.events().unsubscribe(sub);
+    engine::history::record_results(ctx, "cli", None, std::slice::from_ref(&result));
+    match &result.error {
+        None => println!("Ready"),
+        Some(e) => println!("Not ready – {}", e.message),
+    }
+    if result.status == Status::Fail {
+        std::process::exit(1);
+    }
+}
+
+pub fn cmd_migrate(json: bool, ctx: &AppContext, registry: &CommandRegistry) {
+    let result = registry.execute("migrate", serde_json::json!({}), ctx);
+    if json || result.error.is_some() {
+        output_result(ctx, &result, json);
+        return;
+    }
+    let report: engine::migrations::MigrationReport = result
+        .data
+        .and_then(|d| serde_json::from_value(d).ok())
+        .unwrap_or_default();
+    if report.migrations.is_empty() {
+        println!("App data is up to date (version {})", report.from_version);
+        return;
+    }
+    for migration in &report.migrations {
+        println!("{:>4}  {}", migration.version, migration.name);
+        for action in &migration.actions {
+            println!("        {}", action);
+        }
+    }
+    let verb = if report.dry_run {
+        "Would migrate"
+    } else {
+        "Migrated"
+    };
+    println!(
+        "{} app data from version {} to {}",
+        verb, report.from_version, report.to_version
+    );
+}
+
+pub async fn cmd_update_check(
+    args: serde_json::Value,
+    download: bool,
+    json: bool,
+    ctx: &AppContext,
+) {
+    let result = if download {
+        engine::updates::run_download(args, ctx).await
+    } else {
+        engine::updates::run_check(args, ctx).await
+    };
+    output_result(ctx, &result, json);
+}
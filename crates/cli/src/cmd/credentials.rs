@@ -0,0 +1,105 @@
+//! `credentials`: the secret store.
+
+use super::output::output_result;
+use clap::Subcommand;
+use engine::{AppContext, CommandRegistry};
+
+#[derive(Subcommand)]
+pub enum CredentialsAction {
+    /// Store a secret, read from stdin unless --value is given.
+    Set {
+        key: String,
+        #[arg(long, default_value = engine::credentials::DEFAULT_NAMESPACE)]
+        namespace: String,
+        /// The secret itself. Visible in the process list and shell
+        /// history; prefer piping it in.
+        #[arg(long)]
+        value: Option<String>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report whether a secret is stored (length and SHA-256 only).
+    Get {
+        key: String,
+        #[arg(long, default_value = engine::credentials::DEFAULT_NAMESPACE)]
+        namespace: String,
+        /// Print the secret itself on stdout instead; exits 1 if unset.
+        #[arg(long, conflicts_with = "json")]
+        reveal: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Remove a secret.
+    Delete {
+        key: String,
+        #[arg(long, default_value = engine::credentials::DEFAULT_NAMESPACE)]
+        namespace: String,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn cmd_credentials(action: CredentialsAction, ctx: &AppContext, registry: &CommandRegistry) {
+    let (cmd, args, json) = match action {
+        CredentialsAction::Set {
+            key,
+            namespace,
+            value,
+            json,
+        } => {
+            let secret = value.unwrap_or_else(|| {
+                let mut secret = String::new();
+                if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut secret) {
+                    eprintln!("error: cannot read the secret from stdin: {}", e);
+                    std::process::exit(2);
+                }
+                secret.trim_end_matches(['\r', '\n']).to_string()
+            });
+            let args = serde_json::json!({ "namespace": namespace, "key": key, "secret": secret });
+            ("credential_set", args, json)
+        }
+        CredentialsAction::Get {
+            key,
+            namespace,
+            reveal: true,
+            ..
+        } => match engine::credentials::get_secret(ctx, &namespace, &key) {
+            Ok(Some(secret)) => {
+                println!("{}", secret);
+                return;
+            }
+            Ok(None) => {
+                eprintln!("error: no secret stored for {}/{}", namespace, key);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(2);
+            }
+        },
+        CredentialsAction::Get {
+            key,
+            namespace,
+            json,
+            ..
+        } => (
+            "credential_get",
+            serde_json::json!({ "namespace": namespace, "key": key }),
+            json,
+        ),
+        CredentialsAction::Delete {
+            key,
+            namespace,
+            json,
+        } => (
+            "credential_delete",
+            serde_json::json!({ "namespace": namespace, "key": key }),
+            json,
+        ),
+    };
+    let result = registry.execute(cmd, args, ctx);
+    output_result(ctx, &result, json);
+}
@@ -0,0 +1,24 @@
+//! `serve-http-fixture`: serve a directory over HTTP until killed.
+
+pub async fn cmd_serve_http_fixture(dir: &std::path::Path, json: bool) {
+    let fixture = match engine::http_fixture::HttpFixture::start(dir).await {
+        Ok(fixture) => fixture,
+        Err(e) => {
+            eprintln!("error: cannot serve {}", e);
+            std::process::exit(2);
+        }
+    };
+    if json {
+        let info = serde_json::json!({
+            "port": fixture.port(),
+            "url": fixture.url(),
+            "root": fixture.root(),
+        });
+        println!("{}", info);
+    } else {
+        println!("Serving {} at {}", fixture.root().display(), fixture.url());
+    }
+    use std::io::Write;
+    let _ = std::io::stdout().flush();
+    std::future::pending::<()>().await
+}
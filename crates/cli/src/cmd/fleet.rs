@@ -0,0 +1,259 @@
+//! `run-remote`, `fleet run`, and `client ping`: scenarios and checks against
+//! other machines.
+
+use super::artifacts::{export_artifacts, write_events};
+use super::output::output_result;
+use super::scenario::{load_scenario_file, print_scenario_result};
+use crate::remote;
+use engine::types::*;
+use engine::AppContext;
+use std::path::PathBuf;
+
+/// `run-remote`: the scenario runs on `remote.host` under a run id issued
+/// here, so the pulled artifacts land in `<artifacts>/<run_id>`.
+pub async fn cmd_run_remote(
+    remote: &remote::Remote<'_>,
+    file: &std::path::Path,
+    artifacts: Option<PathBuf>,
+    json: bool,
+    ctx: &AppContext,
+) {
+    let run_id = ctx.new_run_id();
+    let target = format!("{}:{}", remote.host, file.display());
+    let outcome = load_scenario_file(file).and_then(|scenario| {
+        tokio::task::block_in_place(|| {
+            remote::run(
+                remote,
+                &scenario,
+                &run_id,
+                ctx.offline,
+                ctx.dry_run,
+                artifacts.as_deref(),
+            )
+        })
+        .map_err(|e| (ErrorCode::IoError, e))
+    });
+    let scenario_result = match outcome {
+        Ok(r) => r,
+        Err((code, message)) => {
+            let r = result_err("run-remote", &target, &run_id, 0, code, message);
+            output_result(ctx, &r, json);
+            return;
+        }
+    };
+    let parent = format!(
+        "remote:{}:{}",
+        remote.host,
+        scenario_result.name.as_deref().unwrap_or("<unnamed>")
+    );
+    engine::history::record_results(ctx, "cli", Some(&parent), &scenario_result.step_results);
+    print_scenario_result(&scenario_result, json);
+
+    if let Some(dir) = &artifacts {
+        let art_dir = dir.join(&run_id);
+        if art_dir.is_dir() {
+            export_artifacts(ctx, &art_dir).await;
+        }
+    }
+    if scenario_result.overall_status == Status::Fail {
+        std::process::exit(1);
+    }
+}
+
+/// `fleet run`: artifacts hold the report in `<run_id>/result.json` and
+/// each target's scenario result under `<run_id>/<target>/`.
+pub async fn cmd_fleet_run(
+    targets: &std::path::Path,
+    file: &std::path::Path,
+    timeout: Option<std::time::Duration>,
+    artifacts: Option<PathBuf>,
+    json: bool,
+    ctx: &AppContext,
+) {
+    let run_id = ctx.new_run_id();
+    let loaded = std::fs::read_to_string(targets)
+        .map_err(|e| {
+            (
+                ErrorCode::IoError,
+                format!("cannot read targets file: {}", e),
+            )
+        })
+        .and_then(|yaml| {
+            engine::fleet::load_targets(&yaml).map_err(|e| (ErrorCode::InvalidInput, e))
+        })
+        .and_then(|t| load_scenario_file(file).map(|s| (t, s)));
+    let (targets, scenario) = match loaded {
+        Ok(loaded) => loaded,
+        Err((code, message)) => {
+            let target = file.display().to_string();
+            let r = result_err("fleet-run", &target, &run_id, 0, code, message);
+            output_result(ctx, &r, json);
+            return;
+        }
+    };
+
+    let report = engine::fleet::run_fleet(&targets, &scenario, timeout).await;
+    for t in &report.targets {
+        if let Some(result) = &t.result {
+            let name = result.name.as_deref().unwrap_or("<unnamed>");
+            let parent = format!("fleet:{}:{}", t.target, name);
+            engine::history::record_results(ctx, "cli", Some(&parent), &result.step_results);
+        }
+    }
+
+    if json {
+        let j = serde_json::to_string_pretty(&report).unwrap_or_default();
+        println!("{}", j);
+    } else {
+        print_fleet_report(&report);
+    }
+
+    if let Some(dir) = &artifacts {
+        let art_dir = dir.join(&run_id);
+        let _ = std::fs::create_dir_all(&art_dir);
+        let j = serde_json::to_string_pretty(&report).unwrap_or_default();
+        let _ = std::fs::write(art_dir.join("result.json"), j);
+        for t in &report.targets {
+            let Some(result) = &t.result else { continue };
+            let target_dir = art_dir.join(&t.target);
+            let _ = std::fs::create_dir_all(&target_dir);
+            let j = serde_json::to_string_pretty(result).unwrap_or_default();
+            let _ = std::fs::write(target_dir.join("result.json"), j);
+            write_events(&target_dir, None, &result.step_results);
+        }
+        export_artifacts(ctx, &art_dir).await;
+    }
+
+    if report.overall_status == Status::Fail {
+        std::process::exit(1);
+    }
+}
+
+pub async fn cmd_client_ping(
+    socket: Option<&std::path::Path>,
+    tcp: Option<&str>,
+    count: u32,
+    json: bool,
+    ctx: &AppContext,
+) {
+    let run_id = ctx.new_run_id();
+    let target = tcp.map_or_else(
+        || socket.map(|s| s.display().to_string()).unwrap_or_default(),
+        String::from,
+    );
+    let started = ctx.stopwatch();
+    let outcome = async {
+        let mut client = engine::client::DaemonClient::connect(socket, tcp).await?;
+        let hello = client
+            .hello()
+            .await?
+            .ok_or("daemon does not know hello (older than the handshake)")?;
+        let mut samples_ms = Vec::new();
+        for _ in 0..count {
+            let sent = ctx.stopwatch();
+            let response = client
+                .request("call", serde_json::json!({ "cmd": "ping" }))
+                .await?;
+            let reply = response.result.ok_or("ping answered without a result")?;
+            if let Some(e) = reply.error {
+                return Err(format!("ping failed: {}: {}", e.code, e.message));
+            }
+            samples_ms.push(sent.elapsed().as_secs_f64() * 1000.0);
+        }
+        Ok::<_, String>((hello, samples_ms))
+    }
+    .await;
+    let total_ms = started.elapsed_ms();
+
+    let result = match outcome {
+        Ok((hello, samples_ms)) => {
+            let mut r = result_ok("client-ping", &target, &run_id, total_ms);
+            let round = |ms: f64| (ms * 1000.0).round() / 1000.0;
+            let mut sorted = samples_ms.clone();
+            sorted.sort_by(f64::total_cmp);
+            let avg = samples_ms.iter().sum::<f64>() / samples_ms.len().max(1) as f64;
+            r.data = Some(serde_json::json!({
+                "protocol_version": hello.protocol_version,
+                "engine_version": hello.engine.engine_version,
+                "capabilities": hello.capabilities,
+                "samples_ms": samples_ms.iter().copied().map(round).collect::<Vec<_>>(),
+                "min_ms": sorted.first().copied().map(round),
+                "avg_ms": (!sorted.is_empty()).then(|| round(avg)),
+                "max_ms": sorted.last().copied().map(round),
+            }));
+            r
+        }
+        Err(e) => result_err(
+            "client-ping",
+            &target,
+            &run_id,
+            total_ms,
+            ErrorCode::NetworkError,
+            e,
+        ),
+    };
+    output_result(ctx, &result, json);
+}
+
+fn print_fleet_report(report: &FleetReport) {
+    let status =
+        |s: Option<Status>| s.map_or("-".to_string(), |s| format!("{:?}", s).to_lowercase());
+    println!(
+        "Fleet: {}",
+        report.scenario.as_deref().unwrap_or("<unnamed>")
+    );
+    println!("Overall: {:?}", report.overall_status);
+    let width = report
+        .targets
+        .iter()
+        .map(|t| t.target.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    for t in &report.targets {
+        let platform = match (&t.os, &t.arch) {
+            (Some(os), Some(arch)) => format!("{}/{}", os, arch),
+            _ => "-".to_string(),
+        };
+        println!(
+            "  {:<width$}  {:<16} {:<5}  {}ms{}",
+            t.target,
+            platform,
+            status(Some(t.status)),
+            t.timing_ms,
+            t.error
+                .as_deref()
+                .map(|e| format!("  {}", e))
+                .unwrap_or_default(),
+        );
+    }
+    let by_os: Vec<String> = report
+        .by_os
+        .iter()
+        .map(|(os, s)| format!("{} {}", os, status(Some(*s))))
+        .collect();
+    println!("By OS: {}", by_os.join(", "));
+
+    let labels: Vec<String> = report
+        .matrix
+        .iter()
+        .map(|row| format!("{} {}", row.step, row.label))
+        .collect();
+    let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+    let header: Vec<String> = report
+        .targets
+        .iter()
+        .map(|t| format!("{:<w$}", t.target, w = t.target.len().max(6)))
+        .collect();
+    println!();
+    println!("  {:<label_width$}  {}", "", header.join("  ").trim_end());
+    for (row, label) in report.matrix.iter().zip(&labels) {
+        let cells: Vec<String> = row
+            .statuses
+            .iter()
+            .zip(&report.targets)
+            .map(|(s, t)| format!("{:<w$}", status(*s), w = t.target.len().max(6)))
+            .collect();
+        println!("  {:<label_width$}  {}", label, cells.join("  ").trim_end());
+    }
+}
@@ -0,0 +1,199 @@
+//! `history`: recorded results.
+
+use super::output::print_human;
+use clap::Subcommand;
+use engine::types::*;
+use engine::AppContext;
+
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// List recorded results, newest first.
+    List {
+        /// Only this command (`call`, `probe`, `doctor`, ...).
+        #[arg(long)]
+        command: Option<String>,
+        /// Only this target (command or probe name).
+        #[arg(long)]
+        target: Option<String>,
+        /// Only this status: pass, fail, skip, or error.
+        #[arg(long)]
+        status: Option<String>,
+        /// Recorded at or after: `7d`, `12h`, `YYYY-MM-DD`, or Unix seconds.
+        #[arg(long)]
+        since: Option<String>,
+        /// Recorded before, in the same forms as --since.
+        #[arg(long)]
+        until: Option<String>,
+        /// Show at most this many entries.
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a recorded result in full.
+    Show {
+        run_id: String,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rank checks whose status alternates across recent runs on the same
+    /// environment (OS, arch, host, VM, sandbox).
+    Flaky {
+        /// Consider the last N runs of each check.
+        #[arg(long, default_value_t = 30)]
+        window: usize,
+        /// Ignore checks with fewer runs than this.
+        #[arg(long, default_value_t = 3)]
+        min_runs: usize,
+        /// Show at most this many checks.
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete old entries.
+    #[command(group(clap::ArgGroup::new("rule").required(true).multiple(true)))]
+    Prune {
+        /// Delete entries recorded before: `30d`, `YYYY-MM-DD`, or Unix seconds.
+        #[arg(long, group = "rule")]
+        older_than: Option<String>,
+        /// Keep only the newest N entries.
+        #[arg(long, group = "rule")]
+        keep: Option<usize>,
+    },
+}
+
+pub fn cmd_history(action: HistoryAction, ctx: &AppContext) {
+    use engine::history;
+
+    let Some(path) = ctx.history_path.as_deref() else {
+        eprintln!("error: run history is off (${}=off)", history::HISTORY_ENV);
+        std::process::exit(2);
+    };
+    let fail = |e: String| -> ! {
+        eprintln!("error: {}", e);
+        std::process::exit(2);
+    };
+    let now = ctx
+        .clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let time = |s: Option<String>| {
+        s.map(|s| history::parse_time(&s, now))
+            .transpose()
+            .unwrap_or_else(|e| fail(e))
+    };
+
+    match action {
+        HistoryAction::List {
+            command,
+            target,
+            status,
+            since,
+            until,
+            limit,
+            json,
+        } => {
+            let status = status.map(|s| {
+                serde_json::from_value::<Status>(serde_json::Value::String(s.clone()))
+                    .unwrap_or_else(|_| fail(format!("invalid status: {}", s)))
+            });
+            let filter = history::HistoryFilter {
+                command,
+                target,
+                status,
+                since: time(since),
+                until: time(until),
+            };
+            let entries = history::query(path, &filter, Some(limit))
+                .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+            if json {
+                let j = serde_json::to_string_pretty(&entries).unwrap_or_default();
+                println!("{}", j);
+                return;
+            }
+            for e in &entries {
+                println!(
+                    "{}  {:<5}  {} {}  {}{}",
+                    history::format_time(e.recorded_at),
+                    format!("{:?}", e.result.status).to_uppercase(),
+                    e.result.command,
+                    e.result.target,
+                    e.result.run_id,
+                    e.parent
+                        .as_deref()
+                        .map(|p| format!("  ({})", p))
+                        .unwrap_or_default()
+                );
+            }
+        }
+        HistoryAction::Show { run_id, json } => {
+            let entry = history::find(path, &run_id)
+                .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)))
+                .unwrap_or_else(|| fail(format!("no run {} in {}", run_id, path.display())));
+            if json {
+                let j = serde_json::to_string_pretty(&entry).unwrap_or_default();
+                println!("{}", j);
+            } else {
+                println!(
+                    "recorded: {} by {}{}",
+                    history::format_time(entry.recorded_at),
+                    entry.source,
+                    entry
+                        .parent
+                        .as_deref()
+                        .map(|p| format!(" in {}", p))
+                        .unwrap_or_default()
+                );
+                print_human(&entry.result);
+            }
+        }
+        HistoryAction::Flaky {
+            window,
+            min_runs,
+            limit,
+            json,
+        } => {
+            let entries =
+                history::load(path).unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+            let mut checks = history::flaky(&entries, window, min_runs);
+            checks.truncate(limit);
+            if json {
+                let j = serde_json::to_string_pretty(&checks).unwrap_or_default();
+                println!("{}", j);
+                return;
+            }
+            if checks.is_empty() {
+                println!("no flaky checks in the last {} runs", window);
+            }
+            for c in &checks {
+                println!(
+                    "{:>3.0}%  {} {}{}  ({} runs: {} pass, {} fail, {} flips; last {:?})",
+                    c.flake_rate * 100.0,
+                    c.command,
+                    c.target,
+                    c.parent
+                        .as_deref()
+                        .map(|p| format!(" in {}", p))
+                        .unwrap_or_default(),
+                    c.runs,
+                    c.passes,
+                    c.failures,
+                    c.flips,
+                    c.last_status
+                );
+                println!("      on {}", c.fingerprint);
+            }
+        }
+        HistoryAction::Prune { older_than, keep } => {
+            let removed = history::prune(path, time(older_than), keep)
+                .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+            println!("removed {} entries from {}", removed, path.display());
+        }
+    }
+}
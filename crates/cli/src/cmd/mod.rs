@@ -0,0 +1,40 @@
+//! Subcommand implementations, one module per subcommand or family.
+
+mod artifacts;
+mod backup;
+mod call;
+mod checks;
+mod credentials;
+mod fixture;
+mod fleet;
+mod history;
+mod output;
+mod profile;
+mod queue;
+mod scenario;
+
+pub use artifacts::write_artifacts;
+pub use backup::{cmd_backup, BackupAction};
+pub use call::{cmd_call, cmd_doctor, cmd_emit, cmd_probe, cmd_probe_list, cmd_probe_suite};
+pub use checks::{
+    cmd_compatibility, cmd_first_run, cmd_migrate, cmd_ready_check, cmd_update_check,
+    load_first_run_checks,
+};
+pub use credentials::{cmd_credentials, CredentialsAction};
+pub use fixture::cmd_serve_http_fixture;
+pub use fleet::{cmd_client_ping, cmd_fleet_run, cmd_run_remote};
+pub use history::{cmd_history, HistoryAction};
+pub use output::output_result;
+pub use profile::{cmd_profile, ProfileAction};
+pub use queue::{cmd_queue, QueueAction};
+pub use scenario::{cmd_run_scenario, cmd_run_scenarios, ScenarioOptions, ScenarioSource};
+
+use engine::AppContext;
+
+/// Publish sleep/wake and lock/unlock on the event bus for long-running
+/// subcommands, so they show up in scenario results and daemon frames.
+pub fn forward_session_events(ctx: &AppContext) {
+    if let Err(e) = engine::session::forward(ctx) {
+        tracing::debug!("power/session events unavailable: {}", e);
+    }
+}
@@ -0,0 +1,91 @@
+//! Printing results as JSON or for people, and recording them in the history.
+
+use engine::types::*;
+use engine::{AppContext, CommandResult};
+
+pub fn output_result(ctx: &AppContext, result: &CommandResult, json: bool) {
+    engine::history::record_results(ctx, "cli", None, std::slice::from_ref(result));
+    if json {
+        let j = serde_json::to_string_pretty(result).unwrap_or_default();
+        println!("{}", j);
+    } else {
+        print_human(result);
+    }
+
+    // Exit with non-zero status on error/fail
+    match result.status {
+        Status::Pass | Status::Skip => {}
+        Status::Fail => std::process::exit(1),
+        Status::Error => std::process::exit(2),
+    }
+}
+
+pub fn print_human(r: &CommandResult) {
+    let status_icon = match r.status {
+        Status::Pass => "PASS",
+        Status::Fail => "FAIL",
+        Status::Skip => "SKIP",
+        Status::Error => "ERROR",
+    };
+
+    println!("[{}] {} {}", status_icon, r.command, r.target);
+    println!("  run_id: {}", r.run_id);
+    println!("  timing: {}ms", r.timing_ms.total);
+
+    if !r.timing_ms.steps.is_empty() {
+        for (step, ms) in &r.timing_ms.steps {
+            println!("    {}: {}ms", step, ms);
+        }
+    }
+
+    if let Some(ref err) = r.error {
+        println!("  error:  {} – {}", err.code, err.message);
+    }
+
+    if let Some(ref data) = r.data {
+        // Print compact data for human output
+        if let Ok(s) = serde_json::to_string_pretty(data) {
+            // Indent each line
+            for line in s.lines() {
+                println!("  {}", line);
+            }
+        }
+    }
+
+    println!(
+        "  env: os={} arch={} headless={}",
+        r.env_summary.os, r.env_summary.arch, r.env_summary.headless
+    );
+    println!(
+        "  build: engine {} ({})",
+        r.env_summary.build.engine_version, r.env_summary.build.git_commit
+    );
+    let env = &r.env_summary;
+    println!(
+        "  host: {} session={}{}{}",
+        env.hostname.as_deref().unwrap_or("unknown"),
+        env.session_id,
+        env.vm
+            .as_deref()
+            .map(|v| format!(" vm={}", v))
+            .unwrap_or_default(),
+        env.ci
+            .as_deref()
+            .map(|c| format!(" ci={}", c))
+            .unwrap_or_default()
+    );
+    if !r.env_summary.sandbox.is_empty() {
+        let kinds: Vec<String> = r
+            .env_summary
+            .sandbox
+            .iter()
+            .map(|k| {
+                serde_json::to_value(k)
+                    .ok()
+                    .and_then(|v| v.as_str().map(String::from))
+                    .unwrap_or_default()
+            })
+            .collect();
+        println!("  sandbox: {}", kinds.join(", "));
+    }
+}
@@ -0,0 +1,70 @@
+//! `profile`: export and import settings profiles.
+
+use super::output::output_result;
+use clap::Subcommand;
+use engine::{AppContext, CommandRegistry};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// Write this machine's settings to a profile signed with
+    /// $APP__PROFILE_KEY (or the profile/signing_key credential).
+    Export {
+        /// Profile file to write.
+        out: PathBuf,
+        /// Sections to include (default: all).
+        #[arg(long, value_delimiter = ',')]
+        sections: Vec<String>,
+        /// Skip signing (when no key is set up).
+        #[arg(long)]
+        unsigned: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Apply a profile's settings, overwriting differing local values
+    /// unless --keep.
+    Import {
+        path: PathBuf,
+        /// Keep local values that differ from the profile.
+        #[arg(long)]
+        keep: bool,
+        /// Accept a profile without a signature.
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn cmd_profile(action: ProfileAction, ctx: &AppContext, registry: &CommandRegistry) {
+    let (cmd, args, json) = match action {
+        ProfileAction::Export {
+            out,
+            sections,
+            unsigned,
+            json,
+        } => (
+            "profile_export",
+            serde_json::json!({ "path": out, "sections": sections, "unsigned": unsigned }),
+            json,
+        ),
+        ProfileAction::Import {
+            path,
+            keep,
+            allow_unsigned,
+            json,
+        } => (
+            "profile_import",
+            serde_json::json!({
+                "path": path,
+                "on_conflict": if keep { "keep" } else { "overwrite" },
+                "allow_unsigned": allow_unsigned,
+            }),
+            json,
+        ),
+    };
+    let result = registry.execute(cmd, args, ctx);
+    output_result(ctx, &result, json);
+}
@@ -0,0 +1,116 @@
+//! `queue`: list, retry, and run queued jobs.
+
+use super::output::output_result;
+use clap::Subcommand;
+use engine::types::*;
+use engine::{AppContext, CommandRegistry, CommandResult};
+
+#[derive(Subcommand)]
+pub enum QueueAction {
+    /// List queued jobs, oldest first.
+    List {
+        /// Only jobs with this status (pending, running, done, failed).
+        #[arg(long)]
+        status: Option<String>,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Put a failed job back in line with its attempts reset.
+    Retry {
+        id: String,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Resume interrupted jobs and run every due one, then exit (while the
+    /// app is closed, or on a headless machine). With --dry-run, list the
+    /// due jobs instead.
+    Run {
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub async fn cmd_queue(action: QueueAction, ctx: &AppContext, registry: &CommandRegistry) {
+    let (cmd, args, json) = match action {
+        QueueAction::List { status, json } => {
+            ("queue_list", serde_json::json!({ "status": status }), json)
+        }
+        QueueAction::Retry { id, json } => ("queue_retry", serde_json::json!({ "id": id }), json),
+        QueueAction::Run { json } => {
+            let result = run_queue(ctx, registry).await;
+            output_result(ctx, &result, json);
+            return;
+        }
+    };
+    let result = registry.execute(cmd, args, ctx);
+    output_result(ctx, &result, json);
+}
+
+/// `appctl queue run`: the GUI worker's recovery and one pass over the
+/// due jobs. Fails if any job ran and failed.
+async fn run_queue(ctx: &AppContext, registry: &CommandRegistry) -> CommandResult {
+    use engine::queue::{self, JobStatus};
+
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    let err = |e: engine::commands::CommandError| {
+        result_err(
+            "queue",
+            "run",
+            &run_id,
+            start.elapsed_ms(),
+            e.error_code(),
+            e.to_string(),
+        )
+    };
+    if ctx.dry_run {
+        let due = match queue::list(ctx) {
+            Ok(jobs) => jobs,
+            Err(e) => return err(e),
+        };
+        let due: Vec<_> = due
+            .into_iter()
+            .filter(|j| matches!(j.status, JobStatus::Pending | JobStatus::Running))
+            .collect();
+        let mut r = result_ok("queue", "run", &run_id, start.elapsed_ms());
+        r.data = Some(serde_json::json!({ "dry_run": true, "jobs": due }));
+        return r;
+    }
+    let recovered = match queue::recover(ctx) {
+        Ok(n) => n,
+        Err(e) => return err(e),
+    };
+    let jobs = match queue::run_pending(ctx, registry).await {
+        Ok(jobs) => jobs,
+        Err(e) => return err(e),
+    };
+    let failed: Vec<_> = jobs
+        .iter()
+        .filter(|j| j.status != JobStatus::Done)
+        .collect();
+    let mut r = match failed.first().and_then(|j| j.error.as_ref()) {
+        None => result_ok("queue", "run", &run_id, start.elapsed_ms()),
+        Some(first) => {
+            let mut r = result_err(
+                "queue",
+                "run",
+                &run_id,
+                start.elapsed_ms(),
+                first.code,
+                format!(
+                    "{} of {} job(s) failed; first: {}",
+                    failed.len(),
+                    jobs.len(),
+                    first.message
+                ),
+            );
+            r.status = Status::Fail;
+            r
+        }
+    };
+    r.data = Some(serde_json::json!({ "recovered": recovered, "jobs": jobs }));
+    r
+}
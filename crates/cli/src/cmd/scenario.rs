@@ -0,0 +1,352 @@
+//! `run-scenario` and `run-scenarios`, including the interactive prompts.
+
+use super::artifacts::{export_artifacts, write_events, write_step_artifacts};
+use super::output::output_result;
+use crate::events::EventRecorder;
+use engine::types::*;
+use engine::{AppContext, CommandRegistry, CommandResult, ProbeRegistry};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where `run-scenario` gets its scenario from.
+pub enum ScenarioSource<'a> {
+    File(&'a std::path::Path),
+    /// Compiled in (see `engine::scenario::BUILTIN`).
+    Builtin(&'a str),
+}
+
+/// A scenario file with its `data:` rows expanded (relative to the file).
+pub fn load_scenario_file(file: &std::path::Path) -> Result<Scenario, (ErrorCode, String)> {
+    let yaml = std::fs::read_to_string(file).map_err(|e| {
+        (
+            ErrorCode::IoError,
+            format!("cannot read scenario file: {}", e),
+        )
+    })?;
+    let base = file.parent().unwrap_or(std::path::Path::new(""));
+    engine::scenario::load_scenario(&yaml)
+        .and_then(|s| engine::scenario::expand_data(s, base))
+        .map_err(|e| (ErrorCode::InvalidInput, e))
+}
+
+/// How `run-scenario` runs and reports.
+pub struct ScenarioOptions {
+    pub json: bool,
+    pub interactive: bool,
+    pub dry_run: bool,
+    pub artifacts: Option<PathBuf>,
+    pub tags: engine::scenario::TagFilter,
+}
+
+pub async fn cmd_run_scenario(
+    source: ScenarioSource<'_>,
+    options: ScenarioOptions,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) {
+    let ScenarioOptions {
+        json,
+        interactive,
+        dry_run,
+        artifacts,
+        tags,
+    } = options;
+    let (label, loaded) = match source {
+        ScenarioSource::Builtin(name) => (
+            format!("builtin:{}", name),
+            engine::scenario::builtin(name).map_err(|e| (ErrorCode::InvalidInput, e)),
+        ),
+        ScenarioSource::File(file) => (file.display().to_string(), load_scenario_file(file)),
+    };
+    let scenario = match loaded {
+        Ok(s) if dry_run => {
+            print_plan(&engine::scenario::plan(&s, &tags), json);
+            return;
+        }
+        Ok(s) if tags.is_empty() => s,
+        Ok(s) => engine::scenario::select_steps(&s, &tags),
+        Err((code, message)) => {
+            let r = result_err("run-scenario", &label, &ctx.new_run_id(), 0, code, message);
+            output_result(ctx, &r, json);
+            return;
+        }
+    };
+
+    // Reserved first, so a fixed --run-id names the artifacts directory.
+    let run_id = artifacts.as_ref().map(|_| ctx.new_run_id());
+    let recorder = artifacts
+        .as_deref()
+        .and_then(|dir| EventRecorder::start(ctx, dir));
+    let scenario_result = if interactive {
+        engine::scenario::run_scenario_interactive(
+            &scenario,
+            ctx,
+            registry,
+            probes,
+            |idx, total, label, step, can_go_back| {
+                use engine::scenario::StepChoice;
+
+                // block_in_place tells Tokio this closure will block on TTY I/O,
+                // so it can move async tasks off this worker thread.
+                tokio::task::block_in_place(|| {
+                    eprintln!("\n--- Step {}/{}: {} ---", idx + 1, total, label);
+                    // The step as it will run, defaults, data rows, and
+                    // fixture placeholders resolved, so the operator
+                    // approves what actually runs.
+                    if let Ok(step) = serde_json::to_string_pretty(step) {
+                        eprintln!("{}", step);
+                    }
+
+                    let mut choices = vec![
+                        ("run", "Run", Some(StepChoice::Run)),
+                        ("skip", "Skip", Some(StepChoice::Skip)),
+                    ];
+                    if can_go_back {
+                        choices.push(("back", "\u{2190} Go back", Some(StepChoice::GoBack)));
+                    }
+                    choices.push(("abort", "Abort scenario", None));
+                    choose("Run this step?", &choices).flatten()
+                })
+            },
+            |idx, total, label| {
+                use engine::scenario::FailureChoice;
+
+                tokio::task::block_in_place(|| {
+                    eprintln!("\n--- Step {}/{}: {} FAILED ---", idx + 1, total, label);
+                    choose(
+                        "Step failed. What would you like to do?",
+                        &[
+                            ("continue", "Continue to next step", FailureChoice::Continue),
+                            ("abort", "Abort scenario", FailureChoice::Abort),
+                        ],
+                    )
+                })
+            },
+        )
+        .await
+    } else {
+        engine::scenario::run_scenario(&scenario, ctx, registry, probes).await
+    };
+    let parent = format!(
+        "scenario:{}",
+        scenario_result
+            .name
+            .clone()
+            .unwrap_or_else(|| label.clone())
+    );
+    engine::history::record_results(ctx, "cli", Some(&parent), &scenario_result.step_results);
+
+    print_scenario_result(&scenario_result, json);
+
+    if let (Some(dir), Some(run_id)) = (&artifacts, run_id) {
+        let art_dir = dir.join(&run_id);
+        let _ = std::fs::create_dir_all(&art_dir);
+        let result_path = art_dir.join("result.json");
+        let j = serde_json::to_string_pretty(&scenario_result).unwrap_or_default();
+        let _ = std::fs::write(&result_path, j);
+
+        // Engine events, then per-step results
+        write_events(&art_dir, recorder, &scenario_result.step_results);
+        write_step_artifacts(&art_dir, &scenario_result);
+        export_artifacts(ctx, &art_dir).await;
+    }
+}
+
+/// Ask `prompt` with a menu on a terminal. Otherwise read answers from
+/// stdin, one per line (a choice's key or label, e.g. `run` or `abort`), so
+/// they can be piped in. `None` when the prompt is cancelled, stdin ends,
+/// or the terminal fails.
+fn choose<T: Clone>(prompt: &str, choices: &[(&str, &str, T)]) -> Option<T> {
+    if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        let labels: Vec<&str> = choices.iter().map(|(_, label, _)| *label).collect();
+        return match dialoguer::Select::new()
+            .with_prompt(prompt)
+            .items(&labels)
+            .default(0)
+            .interact_opt()
+        {
+            Ok(selection) => selection.map(|i| choices[i].2.clone()),
+            Err(e) => {
+                eprintln!("error: interactive prompt failed: {e}");
+                None
+            }
+        };
+    }
+    let keys: Vec<&str> = choices.iter().map(|(key, _, _)| *key).collect();
+    eprintln!("{} [{}]", prompt, keys.join("/"));
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("error: cannot read an answer: {e}");
+                return None;
+            }
+        }
+        let answer = line.trim();
+        if let Some((_, _, value)) = choices
+            .iter()
+            .find(|(key, label, _)| answer == *key || answer.eq_ignore_ascii_case(label))
+        {
+            return Some(value.clone());
+        }
+        eprintln!("expected one of: {}", keys.join(", "));
+    }
+}
+
+pub fn print_scenario_result(result: &ScenarioResult, json: bool) {
+    if json {
+        let j = serde_json::to_string_pretty(result).unwrap_or_default();
+        println!("{}", j);
+        return;
+    }
+    println!(
+        "Scenario: {}",
+        result.name.as_deref().unwrap_or("<unnamed>")
+    );
+    println!("Overall: {:?}", result.overall_status);
+    for (i, sr) in result.step_results.iter().enumerate() {
+        let marker = match result.xfail.iter().find(|x| x.step == i) {
+            Some(x) if x.outcome == XfailOutcome::Xpass => {
+                format!(" [XPASS: {}]", x.reason)
+            }
+            Some(x) => format!(" [xfail: {}]", x.reason),
+            None => String::new(),
+        };
+        println!(
+            "  Step {}: {} -> {:?} ({}ms){}",
+            i, sr.target, sr.status, sr.timing_ms.total, marker
+        );
+    }
+    if let Some(step) = result.stopped_at {
+        println!("Stopped after step {} (on_failure: stop)", step);
+    }
+}
+
+fn print_plan(plan: &ScenarioPlan, json: bool) {
+    if json {
+        let j = serde_json::to_string_pretty(plan).unwrap_or_default();
+        println!("{}", j);
+        return;
+    }
+    println!("Plan: {}", plan.name.as_deref().unwrap_or("<unnamed>"));
+    for (name, value) in &plan.env {
+        match engine::env::env_value(value) {
+            Some(value) => println!("  env: {}={}", name, value),
+            None => println!("  env: unset {}", name),
+        }
+    }
+    for step in &plan.steps {
+        let tags = if step.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", step.tags.join(", "))
+        };
+        match (step.run_index, &step.skipped) {
+            (Some(i), _) => println!("  Step {}: {}{}", i, step.label, tags),
+            (None, reason) => println!(
+                "  (not run) {}{}: {}",
+                step.label,
+                tags,
+                reason.as_deref().unwrap_or("skipped")
+            ),
+        }
+        if step.run_index.is_some() {
+            println!(
+                "    {}",
+                serde_json::to_string(&step.spec).unwrap_or_default()
+            );
+        }
+    }
+}
+
+/// `run-scenarios`: every scenario in `dir`, `jobs` at a time. Artifacts
+/// hold the combined report in `<run_id>/result.json` and each scenario's
+/// results under `<run_id>/<file>/`.
+pub async fn cmd_run_scenarios(
+    dir: &std::path::Path,
+    jobs: usize,
+    options: ScenarioOptions,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) {
+    let ScenarioOptions {
+        json,
+        artifacts,
+        tags,
+        ..
+    } = options;
+    // Reserved first, so a fixed --run-id names the artifacts directory.
+    let run_id = artifacts.as_ref().map(|_| ctx.new_run_id());
+    let recorder = artifacts
+        .as_deref()
+        .and_then(|dir| EventRecorder::start(ctx, dir));
+    let dir_result =
+        match engine::scenario_dir::run_dir(dir, jobs, &tags, ctx, registry, probes).await {
+            Ok(r) => r,
+            Err(e) => {
+                let r = result_err(
+                    "run-scenarios",
+                    &dir.display().to_string(),
+                    &ctx.new_run_id(),
+                    0,
+                    ErrorCode::IoError,
+                    format!("cannot list scenario directory: {}", e),
+                );
+                output_result(ctx, &r, json);
+                return;
+            }
+        };
+    for s in &dir_result.scenarios {
+        if let Some(result) = &s.result {
+            let name = result.name.as_deref().unwrap_or(&s.file);
+            let parent = format!("scenario:{}", name);
+            engine::history::record_results(ctx, "cli", Some(&parent), &result.step_results);
+        }
+    }
+
+    if json {
+        let j = serde_json::to_string_pretty(&dir_result).unwrap_or_default();
+        println!("{}", j);
+    } else {
+        let t = &dir_result.totals;
+        println!("Scenarios: {}", dir_result.dir);
+        println!("Overall: {:?}", dir_result.overall_status);
+        for s in &dir_result.scenarios {
+            match &s.error {
+                Some(e) => println!("  {} -> {:?}: {}", s.file, s.status, e),
+                None => println!("  {} -> {:?} ({}ms)", s.file, s.status, s.timing_ms),
+            }
+        }
+        println!(
+            "{} scenarios: {} passed, {} failed, {} skipped, {} errors; {} steps ({} xfailed, {} xpassed)",
+            t.scenarios, t.passed, t.failed, t.skipped, t.errors, t.steps, t.xfailed, t.xpassed
+        );
+    }
+
+    if let (Some(dir), Some(run_id)) = (&artifacts, run_id) {
+        let art_dir = dir.join(&run_id);
+        let _ = std::fs::create_dir_all(&art_dir);
+        let j = serde_json::to_string_pretty(&dir_result).unwrap_or_default();
+        let _ = std::fs::write(art_dir.join("result.json"), j);
+        write_events::<CommandResult>(&art_dir, recorder, &[]);
+        for s in &dir_result.scenarios {
+            let Some(result) = &s.result else { continue };
+            let file_dir = art_dir.join(&s.file);
+            let _ = std::fs::create_dir_all(&file_dir);
+            let j = serde_json::to_string_pretty(result).unwrap_or_default();
+            let _ = std::fs::write(file_dir.join("result.json"), j);
+            write_events(&file_dir, None, &result.step_results);
+            write_step_artifacts(&file_dir, result);
+        }
+        export_artifacts(ctx, &art_dir).await;
+    }
+
+    if dir_result.overall_status == Status::Fail {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,85 @@
+//! Engine event recording for `--artifacts` runs.
+//!
+//! Events are appended to a staging file in the artifacts directory as they
+//! are published, then moved to `<artifacts>/<run_id>/events.jsonl` once the
+//! run (and so its id) is complete.
+
+use engine::events::{EventSink, SubscriptionId};
+use engine::types::EngineEvent;
+use engine::AppContext;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Writes each event as one JSON line.
+pub struct JsonlEventSink {
+    file: Mutex<File>,
+}
+
+impl JsonlEventSink {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: Mutex::new(File::create(path)?),
+        })
+    }
+}
+
+impl EventSink for JsonlEventSink {
+    fn send(&self, event: &EngineEvent) {
+        let Ok(line) = serde_json::to_string(event) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Records the events published on a context while it is alive.
+pub struct EventRecorder<'a> {
+    ctx: &'a AppContext,
+    staging: PathBuf,
+    subscription: SubscriptionId,
+}
+
+impl<'a> EventRecorder<'a> {
+    /// Start recording into `artifacts`; warns and returns `None` if the
+    /// staging file cannot be created.
+    pub fn start(ctx: &'a AppContext, artifacts: &Path) -> Option<Self> {
+        let staging = artifacts.join(format!(".events-{}.jsonl", std::process::id()));
+        let sink =
+            std::fs::create_dir_all(artifacts).and_then(|_| JsonlEventSink::create(&staging));
+        match sink {
+            Ok(sink) => Some(Self {
+                ctx,
+                subscription: ctx.events().attach(Arc::new(sink)),
+                staging,
+            }),
+            Err(e) => {
+                eprintln!(
+                    "warning: failed to record events to {}: {}",
+                    staging.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Stop recording and move the events to `events.jsonl` in `run_dir`.
+    pub fn finish(self, run_dir: &Path) -> io::Result<PathBuf> {
+        // Detaching drops the sink, closing the file before it is moved.
+        self.ctx.events().unsubscribe(self.subscription);
+        let path = run_dir.join("events.jsonl");
+        std::fs::rename(&self.staging, &path)?;
+        Ok(path)
+    }
+}
+
+impl Drop for EventRecorder<'_> {
+    fn drop(&mut self) {
+        // No-ops after `finish`; otherwise discard the partial recording.
+        self.ctx.events().unsubscribe(self.subscription);
+        let _ = std::fs::remove_file(&self.staging);
+    }
+}
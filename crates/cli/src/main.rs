@@ -3,108 +3,19 @@
 //! Runs the same engine logic that powers the GUI, but without a window
 //! server. Designed for VM-based compatibility testing on macOS + Linux.
 
+mod cli;
+mod cmd;
+mod events;
+mod remote;
+mod selftest;
 mod serve;
 
-use clap::{Parser, Subcommand};
-use engine::types::*;
-use engine::{AppContext, CommandRegistry, CommandResult};
-use std::path::PathBuf;
-
-// ===========================================================================
-// CLI definition
-// ===========================================================================
-
-#[derive(Parser)]
-#[command(
-    name = "appctl",
-    version,
-    about = "CLI test harness for the Tauri template app"
-)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// Collect environment facts and emit an env summary.
-    Doctor {
-        /// Output as JSON instead of human-readable text.
-        #[arg(long)]
-        json: bool,
-        /// Write result JSON to this path.
-        #[arg(long)]
-        out: Option<PathBuf>,
-    },
-
-    /// Invoke a backend command by name with JSON args.
-    Call {
-        /// Command name (e.g. "ping", "read_file", "write_file").
-        cmd: String,
-        /// JSON args to pass to the command.
-        #[arg(long, default_value = "{}")]
-        args: String,
-        /// Output as JSON.
-        #[arg(long)]
-        json: bool,
-        /// Timeout duration (e.g. "30s", "5000ms"). Currently informational.
-        #[arg(long)]
-        timeout: Option<String>,
-        /// Directory for artifacts output.
-        #[arg(long)]
-        artifacts: Option<PathBuf>,
-    },
-
-    /// Targeted capability check: filesystem, network, or clipboard.
-    Probe {
-        /// Probe target: filesystem | network | clipboard
-        target: String,
-        /// Output as JSON.
-        #[arg(long)]
-        json: bool,
-        /// Directory for artifacts output.
-        #[arg(long)]
-        artifacts: Option<PathBuf>,
-    },
-
-    /// Run a scripted scenario from a YAML file.
-    RunScenario {
-        /// Path to the scenario YAML file.
-        file: PathBuf,
-        /// Directory for artifacts output.
-        #[arg(long)]
-        artifacts: Option<PathBuf>,
-        /// Output as JSON.
-        #[arg(long)]
-        json: bool,
-        /// Run interactively with go-back navigation.
-        #[arg(long)]
-        interactive: bool,
-    },
-
-    /// Start daemon mode over a Unix socket.
-    Serve {
-        /// Path for the Unix domain socket.
-        #[arg(long)]
-        socket: PathBuf,
-    },
-
-    /// Emit a desktop event (skeleton – returns UNIMPLEMENTED).
-    Emit {
-        /// Event type: tray-click | deep-link | file-drop | app-focus
-        event: String,
-        /// Optional event payload as JSON.
-        #[arg(long, default_value = "{}")]
-        payload: String,
-        /// Output as JSON.
-        #[arg(long)]
-        json: bool,
-    },
-}
-
-// ===========================================================================
-// Main
-// ===========================================================================
+use clap::Parser;
+use cli::{Cli, ClientAction, Commands, FleetAction};
+use cmd::*;
+use engine::endpoints::{NetworkEndpoint, ThroughputConfig};
+use engine::{AppContext, CommandRegistry, ProbeRegistry};
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
@@ -117,12 +28,37 @@ async fn main() {
         .with_writer(std::io::stderr)
         .init();
 
+    engine::host::set_app_version(env!("CARGO_PKG_VERSION"));
     let cli = Cli::parse();
-    let ctx = AppContext::default_platform();
+    if cli.build_info {
+        let info = engine::build_info::current();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&info).unwrap_or_default()
+        );
+        return;
+    }
+    let Some(command) = cli.command else {
+        let _ = <Cli as clap::CommandFactory>::command().print_help();
+        std::process::exit(2);
+    };
+    let mut ctx = AppContext::default_platform();
+    ctx.offline |= cli.offline;
+    ctx.dry_run |= cli.dry_run;
+    if let Some(seed) = cli.seed {
+        ctx = ctx.with_seed(seed);
+    }
+    if let Some(run_id) = cli.run_id {
+        ctx = ctx.with_run_id(run_id);
+    }
+    if let Some(app_id) = cli.app_id {
+        ctx.app_id = app_id;
+    }
     let registry = CommandRegistry::new();
+    let probes = ProbeRegistry::new();
 
-    match cli.command {
-        Commands::Doctor { json, out } => cmd_doctor(json, out).await,
+    match command {
+        Commands::Doctor { json, out } => cmd_doctor(json, out, &ctx).await,
         Commands::Call {
             cmd,
             args,
@@ -130,359 +66,196 @@ async fn main() {
             timeout: _,
             artifacts,
         } => cmd_call(&cmd, &args, json, artifacts, &ctx, &registry).await,
+        Commands::Probe {
+            list: true, json, ..
+        } => cmd_probe_list(json, &ctx, &probes),
         Commands::Probe {
             target,
+            list: _,
+            args,
+            endpoints,
+            throughput,
             json,
             artifacts,
-        } => cmd_probe(&target, json, artifacts, &ctx).await,
+        } => {
+            if !endpoints.is_empty() {
+                ctx.network_probe.endpoints =
+                    endpoints.into_iter().map(NetworkEndpoint::new).collect();
+            }
+            if throughput {
+                ctx.network_probe.throughput = Some(ThroughputConfig::default());
+            }
+            let target = target.unwrap_or_default();
+            if probes.get(&target).is_none() && ctx.probe_suites.contains_key(&target) {
+                cmd_probe_suite(&target, json, artifacts, &ctx, &probes).await
+            } else {
+                cmd_probe(&target, &args, json, artifacts, &ctx, &probes).await
+            }
+        }
         Commands::RunScenario {
             file,
+            builtin,
             artifacts,
             json,
             interactive,
-        } => cmd_run_scenario(&file, json, interactive, artifacts, &ctx, &registry).await,
-        Commands::Serve { socket } => serve::run_daemon(socket, ctx, registry).await,
-        Commands::Emit {
-            event,
-            payload: _,
+            dry_run,
+            tags,
+            skip_tags,
+        } => {
+            forward_session_events(&ctx);
+            let source = match (&builtin, &file) {
+                (Some(name), _) => ScenarioSource::Builtin(name),
+                (None, Some(file)) => ScenarioSource::File(file),
+                (None, None) => unreachable!("clap requires a file or --builtin"),
+            };
+            let options = ScenarioOptions {
+                json,
+                interactive,
+                dry_run,
+                artifacts,
+                tags: engine::scenario::TagFilter {
+                    include: tags,
+                    exclude: skip_tags,
+                },
+            };
+            cmd_run_scenario(source, options, &Arc::new(ctx), &registry, &probes).await
+        }
+        Commands::RunScenarios {
+            dir,
+            jobs,
+            artifacts,
             json,
-        } => cmd_emit(&event, json).await,
-    }
-}
-
-// ===========================================================================
-// Subcommand implementations
-// ===========================================================================
-
-async fn cmd_doctor(json: bool, out: Option<PathBuf>) {
-    let result = engine::doctor::run_doctor();
-    if let Some(ref path) = out {
-        write_result_file(path, &result);
-    }
-    output_result(&result, json);
-}
-
-async fn cmd_call(
-    cmd: &str,
-    args_str: &str,
-    json: bool,
-    artifacts: Option<PathBuf>,
-    ctx: &AppContext,
-    registry: &CommandRegistry,
-) {
-    let args: serde_json::Value = match serde_json::from_str(args_str) {
-        Ok(v) => v,
-        Err(e) => {
-            let r = result_err(
-                "call",
-                cmd,
-                &new_run_id(),
-                0,
-                ErrorCode::InvalidInput,
-                format!("invalid JSON args: {}", e),
-            );
-            output_result(&r, json);
-            return;
+            tags,
+            skip_tags,
+        } => {
+            forward_session_events(&ctx);
+            let options = ScenarioOptions {
+                json,
+                interactive: false,
+                dry_run: false,
+                artifacts,
+                tags: engine::scenario::TagFilter {
+                    include: tags,
+                    exclude: skip_tags,
+                },
+            };
+            cmd_run_scenarios(&dir, jobs, options, &Arc::new(ctx), &registry, &probes).await
         }
-    };
-
-    let result = registry.execute(cmd, args, ctx);
-    if let Some(ref dir) = artifacts {
-        write_artifacts(dir, &result);
-    }
-    output_result(&result, json);
-}
-
-async fn cmd_probe(target: &str, json: bool, artifacts: Option<PathBuf>, ctx: &AppContext) {
-    let result = engine::probes::run_probe(target, ctx).await;
-    if let Some(ref dir) = artifacts {
-        write_artifacts(dir, &result);
-    }
-    output_result(&result, json);
-}
-
-async fn cmd_run_scenario(
-    file: &PathBuf,
-    json: bool,
-    interactive: bool,
-    artifacts: Option<PathBuf>,
-    ctx: &AppContext,
-    registry: &CommandRegistry,
-) {
-    let yaml = match std::fs::read_to_string(file) {
-        Ok(s) => s,
-        Err(e) => {
-            let r = result_err(
-                "run-scenario",
-                &file.display().to_string(),
-                &new_run_id(),
-                0,
-                ErrorCode::IoError,
-                format!("cannot read scenario file: {}", e),
-            );
-            output_result(&r, json);
-            return;
+        Commands::RunRemote {
+            host,
+            scenario,
+            remote_bin,
+            ssh_options,
+            keep_remote,
+            artifacts,
+            json,
+        } => {
+            let remote = remote::Remote {
+                host: &host,
+                bin: remote_bin.as_deref(),
+                options: &ssh_options,
+                keep: keep_remote,
+            };
+            cmd_run_remote(&remote, &scenario, artifacts, json, &ctx).await
         }
-    };
-
-    let scenario = match engine::scenario::load_scenario(&yaml) {
-        Ok(s) => s,
-        Err(e) => {
-            let r = result_err(
-                "run-scenario",
-                &file.display().to_string(),
-                &new_run_id(),
-                0,
-                ErrorCode::InvalidInput,
-                e,
-            );
-            output_result(&r, json);
-            return;
+        Commands::Compatibility {
+            rules,
+            strict,
+            json,
+        } => cmd_compatibility(rules, strict, json, &ctx, &probes).await,
+        Commands::FirstRun { checks, json } => {
+            if !load_first_run_checks(checks, "first-run", &mut ctx, json) {
+                return;
+            }
+            cmd_first_run(json, &ctx, &probes).await
         }
-    };
-
-    let scenario_result = if interactive {
-        if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
-            eprintln!("error: --interactive requires a TTY (stdin is not a terminal)");
-            std::process::exit(1);
+        Commands::ReadyCheck { checks, json } => {
+            if !load_first_run_checks(checks, "ready-check", &mut ctx, json) {
+                return;
+            }
+            cmd_ready_check(json, &ctx, &registry, &probes).await
         }
-        engine::scenario::run_scenario_interactive(
-            &scenario,
-            ctx,
-            registry,
-            |idx, total, label, can_go_back| {
-                use engine::scenario::StepChoice;
-
-                // block_in_place tells Tokio this closure will block on TTY I/O,
-                // so it can move async tasks off this worker thread.
-                tokio::task::block_in_place(|| {
-                    eprintln!("\n--- Step {}/{}: {} ---", idx + 1, total, label);
-
-                    let mut choices = vec!["Run", "Skip"];
-                    if can_go_back {
-                        choices.push("\u{2190} Go back");
-                    }
-
-                    let selection = match dialoguer::Select::new()
-                        .with_prompt("Run this step?")
-                        .items(&choices)
-                        .default(0)
-                        .interact_opt()
-                    {
-                        Ok(Some(s)) => s,
-                        Ok(None) => return None,
-                        Err(e) => {
-                            eprintln!("error: interactive prompt failed: {e}");
-                            return None;
-                        }
-                    };
-
-                    Some(match choices[selection] {
-                        "Run" => StepChoice::Run,
-                        "Skip" => StepChoice::Skip,
-                        _ => StepChoice::GoBack,
-                    })
-                })
-            },
-            |idx, total, label| {
-                use engine::scenario::FailureChoice;
-
-                tokio::task::block_in_place(|| {
-                    eprintln!("\n--- Step {}/{}: {} FAILED ---", idx + 1, total, label);
-
-                    let choices = ["Continue to next step", "Abort scenario"];
-                    let selection = match dialoguer::Select::new()
-                        .with_prompt("Step failed. What would you like to do?")
-                        .items(choices)
-                        .default(0)
-                        .interact_opt()
-                    {
-                        Ok(Some(s)) => s,
-                        Ok(None) => return None,
-                        Err(e) => {
-                            eprintln!("error: interactive prompt failed: {e}");
-                            return None;
-                        }
-                    };
-
-                    Some(match choices[selection] {
-                        "Continue to next step" => FailureChoice::Continue,
-                        _ => FailureChoice::Abort,
-                    })
-                })
-            },
-        )
-        .await
-    } else {
-        engine::scenario::run_scenario(&scenario, ctx, registry).await
-    };
-
-    if json {
-        let j = serde_json::to_string_pretty(&scenario_result).unwrap_or_default();
-        println!("{}", j);
-    } else {
-        println!(
-            "Scenario: {}",
-            scenario_result.name.as_deref().unwrap_or("<unnamed>")
-        );
-        println!("Overall: {:?}", scenario_result.overall_status);
-        for (i, sr) in scenario_result.step_results.iter().enumerate() {
-            println!(
-                "  Step {}: {} -> {:?} ({}ms)",
-                i, sr.target, sr.status, sr.timing_ms.total
-            );
+        Commands::Migrate { json } => cmd_migrate(json, &ctx, &registry),
+        Commands::UpdateCheck {
+            manifest_url,
+            current_version,
+            pubkey,
+            download,
+            json,
+        } => {
+            let args = serde_json::json!({
+                "manifest_url": manifest_url,
+                "current_version": current_version,
+                "pubkey": pubkey,
+                "dest_dir": download.as_ref().map(|d| d.display().to_string()),
+            });
+            ctx.update_settings.allow_overrides = true;
+            cmd_update_check(args, download.is_some(), json, &ctx).await
         }
-    }
-
-    if let Some(ref dir) = artifacts {
-        let run_id = new_run_id();
-        let art_dir = dir.join(&run_id);
-        let _ = std::fs::create_dir_all(&art_dir);
-        let result_path = art_dir.join("result.json");
-        let j = serde_json::to_string_pretty(&scenario_result).unwrap_or_default();
-        let _ = std::fs::write(&result_path, j);
-
-        // Write per-step results as events.jsonl
-        let events_path = art_dir.join("events.jsonl");
-        let mut lines = String::new();
-        for sr in &scenario_result.step_results {
-            if let Ok(line) = serde_json::to_string(sr) {
-                lines.push_str(&line);
-                lines.push('\n');
-            }
+        Commands::TelemetryFlush { force, json } => {
+            let result = engine::telemetry::flush(&ctx, force).await;
+            output_result(&ctx, &result, json);
         }
-        let _ = std::fs::write(&events_path, lines);
-    }
-}
-
-async fn cmd_emit(event: &str, json: bool) {
-    let run_id = new_run_id();
-    let headless = detect_headless();
-
-    let (status, code, msg) = if headless {
-        (
-            Status::Skip,
-            ErrorCode::Unsupported,
-            format!("event '{}' unsupported in headless environment", event),
-        )
-    } else {
-        (
-            Status::Skip,
-            ErrorCode::Unimplemented,
-            format!("event '{}' is not yet implemented (skeleton)", event),
-        )
-    };
-
-    let result = CommandResult {
-        run_id,
-        command: "emit".to_string(),
-        target: event.to_string(),
-        status,
-        error: Some(ErrorInfo {
-            code,
-            message: msg,
-            details: serde_json::Value::Null,
-        }),
-        timing_ms: TimingInfo::default(),
-        artifacts: vec![],
-        env_summary: EnvSummary::default(),
-        data: None,
-    };
-    output_result(&result, json);
-}
-
-// ===========================================================================
-// Output helpers
-// ===========================================================================
-
-fn output_result(result: &CommandResult, json: bool) {
-    if json {
-        let j = serde_json::to_string_pretty(result).unwrap_or_default();
-        println!("{}", j);
-    } else {
-        print_human(result);
-    }
-
-    // Exit with non-zero status on error/fail
-    match result.status {
-        Status::Pass | Status::Skip => {}
-        Status::Fail => std::process::exit(1),
-        Status::Error => std::process::exit(2),
-    }
-}
-
-fn print_human(r: &CommandResult) {
-    let status_icon = match r.status {
-        Status::Pass => "PASS",
-        Status::Fail => "FAIL",
-        Status::Skip => "SKIP",
-        Status::Error => "ERROR",
-    };
-
-    println!("[{}] {} {}", status_icon, r.command, r.target);
-    println!("  run_id: {}", r.run_id);
-    println!("  timing: {}ms", r.timing_ms.total);
-
-    if !r.timing_ms.steps.is_empty() {
-        for (step, ms) in &r.timing_ms.steps {
-            println!("    {}: {}ms", step, ms);
+        Commands::Serve {
+            socket,
+            listen,
+            metrics_addr,
+            max_request_bytes,
+        } => {
+            forward_session_events(&ctx);
+            let max_request_bytes =
+                max_request_bytes.unwrap_or_else(engine::framing::max_request_bytes_from_env);
+            serve::run_daemon(
+                socket,
+                listen,
+                metrics_addr,
+                max_request_bytes,
+                ctx,
+                registry,
+                probes,
+            )
+            .await
         }
-    }
-
-    if let Some(ref err) = r.error {
-        println!("  error:  {} – {}", err.code, err.message);
-    }
-
-    if let Some(ref data) = r.data {
-        // Print compact data for human output
-        if let Ok(s) = serde_json::to_string_pretty(data) {
-            // Indent each line
-            for line in s.lines() {
-                println!("  {}", line);
-            }
+        Commands::ServeHttpFixture { dir, json } => cmd_serve_http_fixture(&dir, json).await,
+        Commands::SelfTest { json } => {
+            let result = selftest::run_self_test(&ctx).await;
+            output_result(&ctx, &result, json);
         }
-    }
-
-    println!(
-        "  env: os={} arch={} headless={}",
-        r.env_summary.os, r.env_summary.arch, r.env_summary.headless
-    );
-}
-
-// ===========================================================================
-// Artifact helpers
-// ===========================================================================
-
-fn write_result_file(path: &std::path::Path, result: &CommandResult) {
-    let j = serde_json::to_string_pretty(result).unwrap_or_default();
-    if let Err(e) = std::fs::write(path, &j) {
-        eprintln!(
-            "warning: failed to write result to {}: {}",
-            path.display(),
-            e
-        );
-    }
-}
-
-fn write_artifacts(dir: &std::path::Path, result: &CommandResult) {
-    let art_dir = dir.join(&result.run_id);
-    if let Err(e) = std::fs::create_dir_all(&art_dir) {
-        eprintln!(
-            "warning: failed to create artifacts dir {}: {}",
-            art_dir.display(),
-            e
-        );
-        return;
-    }
-
-    // result.json
-    let result_path = art_dir.join("result.json");
-    let j = serde_json::to_string_pretty(result).unwrap_or_default();
-    let _ = std::fs::write(&result_path, &j);
-
-    // events.jsonl (single event for non-scenario)
-    let events_path = art_dir.join("events.jsonl");
-    if let Ok(line) = serde_json::to_string(result) {
-        let _ = std::fs::write(&events_path, format!("{}\n", line));
+        Commands::Export { run_dir, json } => {
+            let result = engine::export::run_export(&ctx, &run_dir).await;
+            output_result(&ctx, &result, json);
+        }
+        Commands::Credentials { action } => cmd_credentials(action, &ctx, &registry),
+        Commands::Backup { action } => cmd_backup(action, &ctx, &registry),
+        Commands::Profile { action } => cmd_profile(action, &ctx, &registry),
+        Commands::Queue { action } => cmd_queue(action, &ctx, &registry).await,
+        Commands::History { action } => cmd_history(action, &ctx),
+        Commands::Fleet {
+            action:
+                FleetAction::Run {
+                    targets,
+                    scenario,
+                    timeout_secs,
+                    artifacts,
+                    json,
+                },
+        } => {
+            let timeout = timeout_secs.map(std::time::Duration::from_secs);
+            cmd_fleet_run(&targets, &scenario, timeout, artifacts, json, &ctx).await
+        }
+        Commands::Client {
+            action:
+                ClientAction::Ping {
+                    socket,
+                    tcp,
+                    count,
+                    json,
+                },
+        } => cmd_client_ping(socket.as_deref(), tcp.as_deref(), count, json, &ctx).await,
+        Commands::Emit {
+            event,
+            payload: _,
+            json,
+        } => cmd_emit(&event, json, &ctx).await,
     }
 }
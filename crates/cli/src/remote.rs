@@ -0,0 +1,228 @@
+//! `appctl run-remote` – run a scenario on another machine over SSH: copy
+//! this appctl there (or use one already installed), run the scenario in a
+//! scratch directory, and pull the artifacts back. Uses the system `ssh`
+//! and `scp`, so keys, agents, and `~/.ssh/config` apply as usual.
+
+use engine::types::{CommandResult, Scenario, ScenarioResult};
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Where and how to reach the remote machine.
+pub struct Remote<'a> {
+    /// `user@host`, or a `Host` alias from `~/.ssh/config`.
+    pub host: &'a str,
+    /// appctl already installed on the remote; `None` copies this binary.
+    pub bin: Option<&'a str>,
+    /// Extra `-o` options for both ssh and scp (`Port=2222`).
+    pub options: &'a [String],
+    /// Leave the remote scratch directory in place.
+    pub keep: bool,
+}
+
+/// Run `scenario` on the remote as `run_id`. With `artifacts`, the remote
+/// run's artifacts directory is copied to `<artifacts>/<run_id>`.
+pub fn run(
+    remote: &Remote,
+    scenario: &Scenario,
+    run_id: &str,
+    offline: bool,
+    dry_run: bool,
+    artifacts: Option<&Path>,
+) -> Result<ScenarioResult, String> {
+    let out = ssh(remote, "mktemp -d \"${TMPDIR:-/tmp}/appctl.XXXXXX\"")?;
+    let dir = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if dir.is_empty() {
+        return Err("mktemp on the remote printed no directory".into());
+    }
+
+    let result = run_in(remote, &dir, scenario, run_id, offline, dry_run, artifacts);
+    if remote.keep {
+        eprintln!("remote files kept in {}:{}", remote.host, dir);
+    } else if let Err(e) = ssh(remote, &format!("rm -rf {}", quote(&dir))) {
+        eprintln!("warning: failed to remove {}:{}: {}", remote.host, dir, e);
+    }
+    result
+}
+
+fn run_in(
+    remote: &Remote,
+    dir: &str,
+    scenario: &Scenario,
+    run_id: &str,
+    offline: bool,
+    dry_run: bool,
+    artifacts: Option<&Path>,
+) -> Result<ScenarioResult, String> {
+    let bin = match remote.bin {
+        Some(bin) => bin.to_string(),
+        None => {
+            let out = ssh(remote, "uname -sm")?;
+            let uname = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            if !matches_local(&uname) {
+                return Err(format!(
+                    "remote is {:?} but this appctl is built for {} {}; \
+                     pass --remote-bin with an appctl installed there",
+                    uname,
+                    std::env::consts::OS,
+                    std::env::consts::ARCH
+                ));
+            }
+            let exe = std::env::current_exe()
+                .map_err(|e| format!("cannot locate this appctl binary: {}", e))?;
+            let bin = format!("{}/appctl", dir);
+            scp(
+                remote,
+                &exe.display().to_string(),
+                &remote_path(remote, &bin),
+            )?;
+            bin
+        }
+    };
+
+    // Data rows are already expanded, so the scenario travels as one file;
+    // JSON is valid YAML, so `run-scenario` reads it as is.
+    let json = serde_json::to_string(scenario).map_err(|e| e.to_string())?;
+    let local = std::env::temp_dir().join(format!("appctl-remote-{}.json", run_id));
+    std::fs::write(&local, json).map_err(|e| format!("cannot write scenario copy: {}", e))?;
+    let file = format!("{}/scenario.json", dir);
+    let copied = scp(
+        remote,
+        &local.display().to_string(),
+        &remote_path(remote, &file),
+    );
+    let _ = std::fs::remove_file(&local);
+    copied?;
+
+    let mut command = vec![quote(&bin)];
+    if offline {
+        command.push("--offline".into());
+    }
+    if dry_run {
+        command.push("--dry-run".into());
+    }
+    command.extend(["--run-id".into(), quote(run_id), "run-scenario".into()]);
+    command.extend([quote(&file), "--json".into()]);
+    let remote_artifacts = format!("{}/artifacts", dir);
+    if artifacts.is_some() {
+        command.extend(["--artifacts".into(), quote(&remote_artifacts)]);
+    }
+    let out = ssh(remote, &command.join(" "))?;
+    let result = parse_output(&out)?;
+
+    if let Some(local) = artifacts {
+        let from = remote_path(remote, &format!("{}/{}", remote_artifacts, run_id));
+        let pulled = std::fs::create_dir_all(local)
+            .map_err(|e| e.to_string())
+            .and_then(|_| scp_dir(remote, &from, local));
+        match pulled {
+            Ok(()) => eprintln!("artifacts in {}", local.join(run_id).display()),
+            Err(e) => eprintln!("warning: failed to pull artifacts: {}", e),
+        }
+    }
+    Ok(result)
+}
+
+/// The scenario result `run-scenario --json` printed, or why there is none
+/// (a load error comes back as a single command result).
+fn parse_output(out: &Output) -> Result<ScenarioResult, String> {
+    if let Ok(result) = serde_json::from_slice::<ScenarioResult>(&out.stdout) {
+        return Ok(result);
+    }
+    if let Ok(r) = serde_json::from_slice::<CommandResult>(&out.stdout) {
+        if let Some(e) = r.error {
+            return Err(format!("remote run-scenario: {}", e.message));
+        }
+    }
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    Err(format!(
+        "remote run-scenario exited with {} without a result: {}",
+        out.status,
+        stderr.trim()
+    ))
+}
+
+/// Whether `uname -sm` output names the platform this binary runs on.
+fn matches_local(uname: &str) -> bool {
+    let mut parts = uname.split_whitespace();
+    let (Some(os), Some(arch)) = (parts.next(), parts.next()) else {
+        return false;
+    };
+    let os = match os {
+        "Linux" => "linux",
+        "Darwin" => "macos",
+        other => other,
+    };
+    let arch = match arch {
+        "arm64" => "aarch64",
+        "amd64" => "x86_64",
+        other => other,
+    };
+    os == std::env::consts::OS && arch == std::env::consts::ARCH
+}
+
+fn remote_path(remote: &Remote, path: &str) -> String {
+    format!("{}:{}", remote.host, path)
+}
+
+/// `s` single-quoted for the remote shell.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn options(remote: &Remote) -> Vec<String> {
+    let mut args = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+    for option in remote.options {
+        args.extend(["-o".to_string(), option.clone()]);
+    }
+    args
+}
+
+/// Run `command` on the remote; a non-zero exit is an error unless it
+/// still printed something (`run-scenario` reports its own failures).
+fn ssh(remote: &Remote, command: &str) -> Result<Output, String> {
+    let out = Command::new("ssh")
+        .args(options(remote))
+        .arg(remote.host)
+        .arg("--")
+        .arg(command)
+        .output()
+        .map_err(|e| format!("cannot run ssh: {}", e))?;
+    if !out.status.success() && out.stdout.is_empty() {
+        return Err(format!(
+            "ssh {} failed ({}): {}",
+            remote.host,
+            out.status,
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(out)
+}
+
+fn scp(remote: &Remote, from: &str, to: &str) -> Result<(), String> {
+    copy(remote, &[], from, to)
+}
+
+fn scp_dir(remote: &Remote, from: &str, to: &Path) -> Result<(), String> {
+    copy(remote, &["-r"], from, &to.display().to_string())
+}
+
+fn copy(remote: &Remote, flags: &[&str], from: &str, to: &str) -> Result<(), String> {
+    let out = Command::new("scp")
+        .arg("-q")
+        .args(flags)
+        .args(options(remote))
+        .arg(from)
+        .arg(to)
+        .output()
+        .map_err(|e| format!("cannot run scp: {}", e))?;
+    if !out.status.success() {
+        return Err(format!(
+            "scp {} -> {} failed ({}): {}",
+            from,
+            to,
+            out.status,
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! `appctl self-test` – exercises appctl's own machinery (command dispatch,
+//! artifact writing, a daemon round-trip, the scenario engine) on a headless
+//! context, so a broken build is caught before the VM gets blamed.
+
+use engine::types::*;
+use engine::{AppContext, CommandRegistry, CommandResult, ProbeRegistry};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+const SCENARIO: &str = r#"
+name: "self-test"
+steps:
+  - call: "ping"
+    expect_status: "pass"
+  - call: "no_such_command"
+    expect_status: "error"
+  - probe: "filesystem"
+"#;
+
+/// Run every check; passes only if all of them do.
+pub async fn run_self_test(ctx: &AppContext) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    let work_dir = ctx
+        .fs()
+        .temp_dir()
+        .join(format!("appctl_self_test_{:08x}", ctx.random_u64() as u32));
+
+    let mut steps = std::collections::HashMap::new();
+    let mut checks = Vec::new();
+    let mut failed = Vec::new();
+    for name in ["registry", "artifacts", "daemon", "scenario"] {
+        let t = ctx.stopwatch();
+        let outcome = match name {
+            "registry" => check_registry(),
+            "artifacts" => check_artifacts(&work_dir),
+            "daemon" => check_daemon(&work_dir).await,
+            _ => check_scenario().await,
+        };
+        steps.insert(name.to_string(), t.elapsed_ms());
+        if let Err(e) = &outcome {
+            failed.push(name);
+            tracing::warn!("self-test {} failed: {}", name, e);
+        }
+        checks.push(serde_json::json!({
+            "check": name,
+            "ok": outcome.is_ok(),
+            "error": outcome.err(),
+        }));
+    }
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    let mut r = if failed.is_empty() {
+        result_ok("self-test", "appctl", &run_id, start.elapsed_ms())
+    } else {
+        result_err(
+            "self-test",
+            "appctl",
+            &run_id,
+            start.elapsed_ms(),
+            ErrorCode::InternalError,
+            format!("appctl is broken: {} failed", failed.join(", ")),
+        )
+    };
+    r.timing_ms.steps = steps;
+    r.data = Some(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "checks": checks,
+    }));
+    r
+}
+
+fn check_registry() -> Result<(), String> {
+    let ctx = AppContext::default_headless();
+    let registry = CommandRegistry::new();
+    let r = registry.execute("ping", serde_json::json!({}), &ctx);
+    if r.status != Status::Pass || r.data.as_ref().map(|d| &d["pong"]) != Some(&true.into()) {
+        return Err(format!("ping returned {:?}", r.status));
+    }
+    let r = registry.execute("no_such_command", serde_json::json!({}), &ctx);
+    match r.error {
+        Some(e) if e.code == ErrorCode::InvalidInput => {}
+        _ => return Err("unknown command was not rejected".into()),
+    }
+    if ProbeRegistry::new().get("filesystem").is_none() {
+        return Err("built-in probes are not registered".into());
+    }
+    Ok(())
+}
+
+fn check_artifacts(work_dir: &Path) -> Result<(), String> {
+    let ctx = AppContext::default_headless();
+    let result = result_ok("self-test", "artifacts", &ctx.new_run_id(), 0);
+    let dir = work_dir.join("artifacts");
+    crate::cmd::write_artifacts(&dir, &result, None);
+
+    let run_dir = dir.join(&result.run_id);
+    let written = std::fs::read_to_string(run_dir.join("result.json"))
+        .map_err(|e| format!("result.json: {}", e))?;
+    let parsed: CommandResult =
+        serde_json::from_str(&written).map_err(|e| format!("result.json: {}", e))?;
+    if parsed.run_id != result.run_id {
+        return Err("result.json holds a different run".into());
+    }
+    let events = std::fs::read_to_string(run_dir.join("events.jsonl"))
+        .map_err(|e| format!("events.jsonl: {}", e))?;
+    if events.lines().count() != 1 {
+        return Err(format!(
+            "events.jsonl has {} lines, expected 1",
+            events.lines().count()
+        ));
+    }
+    Ok(())
+}
+
+async fn check_daemon(work_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(work_dir).map_err(|e| e.to_string())?;
+    let socket = work_dir.join("daemon.sock");
+    let listener = UnixListener::bind(&socket).map_err(|e| format!("bind: {}", e))?;
+    let mut ctx = AppContext::default_headless();
+    ctx.history_path = None;
+    let metrics = std::sync::Arc::new(engine::metrics::DaemonMetrics::new(0));
+    let server = crate::serve::serve(
+        crate::serve::Listener::Unix(listener),
+        engine::framing::DEFAULT_MAX_REQUEST_BYTES,
+        ctx,
+        CommandRegistry::new(),
+        ProbeRegistry::new(),
+        metrics,
+    );
+
+    let client = async {
+        let stream = UnixStream::connect(&socket)
+            .await
+            .map_err(|e| format!("connect: {}", e))?;
+        let (reader, mut writer) = stream.into_split();
+        let request = r#"{"id":"self-test","method":"call","params":{"cmd":"ping"}}"#;
+        writer
+            .write_all(format!("{}\n", request).as_bytes())
+            .await
+            .map_err(|e| format!("write: {}", e))?;
+        let mut lines = BufReader::new(reader).lines();
+        // Progress frames come first; the response carries `result`.
+        while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
+            let frame: serde_json::Value =
+                serde_json::from_str(&line).map_err(|e| format!("bad frame: {}", e))?;
+            if frame.get("progress").is_some() {
+                continue;
+            }
+            if frame["id"] != "self-test" || frame["result"]["data"]["pong"] != true {
+                return Err(format!("unexpected response: {}", line));
+            }
+            return Ok(());
+        }
+        Err("daemon closed the connection".to_string())
+    };
+
+    let outcome = tokio::select! {
+        _ = server => Err("daemon stopped".to_string()),
+        r = tokio::time::timeout(Duration::from_secs(5), client) => {
+            r.unwrap_or_else(|_| Err("no response within 5s".into()))
+        }
+    };
+    let _ = std::fs::remove_file(&socket);
+    outcome
+}
+
+async fn check_scenario() -> Result<(), String> {
+    let ctx = std::sync::Arc::new(AppContext::default_headless());
+    let scenario = engine::scenario::load_scenario(SCENARIO)?;
+    let r = engine::scenario::run_scenario(
+        &scenario,
+        &ctx,
+        &CommandRegistry::new(),
+        &ProbeRegistry::new(),
+    )
+    .await;
+    if r.overall_status != Status::Pass {
+        let failed: Vec<String> = r
+            .step_results
+            .iter()
+            .filter(|s| s.status != Status::Pass)
+            .map(|s| format!("{} ({:?})", s.target, s.status))
+            .collect();
+        return Err(format!("embedded scenario failed: {}", failed.join(", ")));
+    }
+    Ok(())
+}
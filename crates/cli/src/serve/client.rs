@@ -0,0 +1,179 @@
+//! What each connection sets up for itself: a session and event
+//! subscriptions.
+
+use super::{send_frame, Daemon, Outbox};
+use engine::daemon_sessions::DaemonSession;
+use engine::events::{self, SubscriptionId};
+use engine::types::*;
+use engine::AppContext;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+/// What one connection has set up for itself.
+pub(super) struct Client {
+    pub(super) subscriptions: Subscriptions,
+    /// From `open_session`; applied around each of the connection's
+    /// requests until `close_session` or disconnect.
+    pub(super) session: Option<DaemonSession>,
+}
+
+impl Client {
+    pub(super) fn open_session(
+        &mut self,
+        params: &serde_json::Value,
+        daemon: &Daemon,
+    ) -> CommandResult {
+        let run_id = daemon.ctx.new_run_id();
+        if let Some(open) = &self.session {
+            return result_err(
+                "open_session",
+                &open.id,
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                format!("session {} is already open on this connection", open.id),
+            );
+        }
+        let id = format!(
+            "sess-{}",
+            daemon.next_session.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        match DaemonSession::open(id.clone(), params) {
+            Ok(session) => {
+                let mut r = result_ok("open_session", &id, &run_id, 0);
+                r.data = Some(session.describe());
+                self.session = Some(session);
+                r
+            }
+            Err(e) => result_err("open_session", "", &run_id, 0, ErrorCode::InvalidInput, e),
+        }
+    }
+
+    pub(super) fn close_session(&mut self, ctx: &AppContext) -> CommandResult {
+        let run_id = ctx.new_run_id();
+        match self.session.take() {
+            Some(session) => result_ok("close_session", &session.id, &run_id, 0),
+            None => result_err(
+                "close_session",
+                "",
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                "no session is open on this connection",
+            ),
+        }
+    }
+}
+
+/// A connection's `subscribe` calls. Matching events are pushed as
+/// [`DaemonEvent`] frames until unsubscribed or the client disconnects.
+pub(super) struct Subscriptions {
+    out: Outbox,
+    active: HashMap<String, SubscriptionId>,
+}
+
+impl Subscriptions {
+    pub(super) fn new(out: Outbox) -> Self {
+        Self {
+            out,
+            active: HashMap::new(),
+        }
+    }
+
+    /// `{"topics": ["command:*", "probe:finished"]}`; see
+    /// [`engine::events::topic_matches`] for the patterns. A `session`
+    /// needs a `subscribe:<topic>` grant covering each topic.
+    pub(super) fn subscribe(
+        &mut self,
+        params: &serde_json::Value,
+        gzip: bool,
+        daemon: &Daemon,
+        session: Option<&DaemonSession>,
+    ) -> CommandResult {
+        let run_id = daemon.ctx.new_run_id();
+        let topics: Option<Vec<String>> = params
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .filter(|t| !t.is_empty())
+            .and_then(|t| t.iter().map(|p| p.as_str().map(String::from)).collect());
+        let Some(topics) = topics else {
+            return result_err(
+                "subscribe",
+                "",
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                "expected params {\"topics\": [\"pattern\", ...]}",
+            );
+        };
+        if let Some(session) = session {
+            if let Some(topic) = topics
+                .iter()
+                .find(|t| !session.permits(&format!("subscribe:{}", t)))
+            {
+                return result_err(
+                    "subscribe",
+                    topic,
+                    &run_id,
+                    0,
+                    ErrorCode::PermissionDenied,
+                    format!(
+                        "session {} has no grant for subscribe:{}",
+                        session.id, topic
+                    ),
+                );
+            }
+        }
+        let id = format!(
+            "sub-{}",
+            daemon.next_subscription.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let (out, subscription, patterns) = (self.out.clone(), id.clone(), topics.clone());
+        let handle = daemon.ctx.events().subscribe(move |ev| {
+            if patterns.iter().any(|p| events::topic_matches(p, &ev.topic)) {
+                let frame = DaemonEvent {
+                    subscription: subscription.clone(),
+                    event: ev.clone(),
+                };
+                send_frame(&out, &frame, &subscription, gzip);
+            }
+        });
+        self.active.insert(id.clone(), handle);
+        let mut r = result_ok("subscribe", &topics.join(","), &run_id, 0);
+        r.data = Some(serde_json::json!({ "subscription": id, "topics": topics }));
+        r
+    }
+
+    /// `{"subscription": "sub-1"}`.
+    pub(super) fn unsubscribe(
+        &mut self,
+        params: &serde_json::Value,
+        ctx: &AppContext,
+    ) -> CommandResult {
+        let run_id = ctx.new_run_id();
+        let id = params
+            .get("subscription")
+            .and_then(|s| s.as_str())
+            .unwrap_or("");
+        match self.active.remove(id) {
+            Some(handle) => {
+                ctx.events().unsubscribe(handle);
+                result_ok("unsubscribe", id, &run_id, 0)
+            }
+            None => result_err(
+                "unsubscribe",
+                id,
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                format!("no subscription {:?} on this connection", id),
+            ),
+        }
+    }
+
+    pub(super) fn clear(&mut self, ctx: &AppContext) {
+        for (_, handle) in self.active.drain() {
+            ctx.events().unsubscribe(handle);
+        }
+    }
+}
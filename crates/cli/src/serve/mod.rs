@@ -0,0 +1,303 @@
+//! Daemon mode – minimal JSON-RPC-ish protocol over a Unix socket or TCP.
+
+mod client;
+mod request;
+
+use client::{Client, Subscriptions};
+use engine::framing::{self, Frame};
+use engine::metrics::DaemonMetrics;
+use engine::types::*;
+use engine::{AppContext, CommandRegistry, ProbeRegistry};
+use request::{handle_request, peek_method, peek_request_id, takes_turn};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::mpsc;
+
+/// Where the daemon accepts connections.
+pub enum Listener {
+    Unix(UnixListener),
+    Tcp(TcpListener),
+}
+
+type Connection = (
+    Box<dyn AsyncRead + Unpin + Send>,
+    Box<dyn AsyncWrite + Unpin + Send>,
+);
+
+impl Listener {
+    async fn accept(&self) -> std::io::Result<Connection> {
+        match self {
+            Listener::Unix(l) => {
+                let (reader, writer) = l.accept().await?.0.into_split();
+                Ok((Box::new(reader), Box::new(writer)))
+            }
+            Listener::Tcp(l) => {
+                let (reader, writer) = l.accept().await?.0.into_split();
+                Ok((Box::new(reader), Box::new(writer)))
+            }
+        }
+    }
+}
+
+/// Listen on `socket_path`, or on the TCP address `listen` if given.
+/// Requests longer than `max_request_bytes` are refused.
+pub async fn run_daemon(
+    socket_path: Option<PathBuf>,
+    listen: Option<String>,
+    metrics_addr: Option<String>,
+    max_request_bytes: usize,
+    ctx: AppContext,
+    registry: CommandRegistry,
+    probes: ProbeRegistry,
+) {
+    let (listener, address) = match (listen, socket_path) {
+        (Some(addr), _) => match TcpListener::bind(&addr).await {
+            Ok(l) => (Listener::Tcp(l), addr),
+            Err(e) => {
+                eprintln!("error: cannot bind address {}: {}", addr, e);
+                std::process::exit(2);
+            }
+        },
+        (None, Some(socket_path)) => {
+            // Remove stale socket if it exists
+            let _ = std::fs::remove_file(&socket_path);
+            match UnixListener::bind(&socket_path) {
+                Ok(l) => (Listener::Unix(l), socket_path.display().to_string()),
+                Err(e) => {
+                    eprintln!("error: cannot bind socket {}: {}", socket_path.display(), e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        (None, None) => unreachable!("clap requires --socket or --listen"),
+    };
+
+    eprintln!("appctl daemon listening on {}", address);
+    let started_at = ctx
+        .clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let metrics = Arc::new(DaemonMetrics::new(started_at));
+    if let Some(addr) = metrics_addr {
+        let scrape = match TcpListener::bind(&addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("error: cannot bind metrics address {}: {}", addr, e);
+                std::process::exit(2);
+            }
+        };
+        eprintln!("metrics on http://{}/metrics", addr);
+        tokio::spawn(serve_metrics(scrape, metrics.clone()));
+    }
+    serve(listener, max_request_bytes, ctx, registry, probes, metrics).await
+}
+
+/// Answer `GET /metrics` with the Prometheus text format; 404 otherwise.
+async fn serve_metrics(listener: TcpListener, metrics: Arc<DaemonMetrics>) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            // The request line is all that matters; read until the headers end.
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < 8192 {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => head.extend_from_slice(&buf[..n]),
+                }
+            }
+            let request_line = String::from_utf8_lossy(&head);
+            let mut parts = request_line.split_whitespace();
+            let (status, body) = match (parts.next(), parts.next()) {
+                (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+                _ => ("404 Not Found", "not found\n".to_string()),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// State every connection shares.
+struct Daemon {
+    // Shared with command workers, which outlive a timed-out call.
+    ctx: Arc<AppContext>,
+    registry: CommandRegistry,
+    probes: ProbeRegistry,
+    metrics: Arc<DaemonMetrics>,
+    max_request_bytes: usize,
+    /// Held while a request runs, so requests from different clients take
+    /// turns and each request's progress frames are its own events only.
+    /// Requests that don't take a turn (see [`takes_turn`]) don't wait for
+    /// it.
+    turn: tokio::sync::Mutex<()>,
+    next_subscription: AtomicU64,
+    next_session: AtomicU64,
+}
+
+/// Answer requests on `listener` until the process exits. Each client gets
+/// its own task; one may hold subscriptions open while others make calls.
+pub async fn serve(
+    listener: Listener,
+    max_request_bytes: usize,
+    ctx: AppContext,
+    registry: CommandRegistry,
+    probes: ProbeRegistry,
+    metrics: Arc<DaemonMetrics>,
+) {
+    let daemon = Arc::new(Daemon {
+        ctx: Arc::new(ctx),
+        registry,
+        probes,
+        metrics,
+        max_request_bytes,
+        turn: tokio::sync::Mutex::new(()),
+        next_subscription: AtomicU64::new(0),
+        next_session: AtomicU64::new(0),
+    });
+    loop {
+        match listener.accept().await {
+            Ok((reader, writer)) => {
+                tokio::spawn(connection(reader, writer, daemon.clone()));
+            }
+            Err(e) => {
+                eprintln!("accept error: {}", e);
+            }
+        }
+    }
+}
+
+/// Frames queued for a connection's writer task. Responses, progress and
+/// pushed events all go through it, so frames never interleave.
+type Outbox = mpsc::UnboundedSender<Vec<u8>>;
+
+async fn connection(
+    reader: Box<dyn AsyncRead + Unpin + Send>,
+    mut writer: Box<dyn AsyncWrite + Unpin + Send>,
+    daemon: Arc<Daemon>,
+) {
+    let (out, mut outgoing) = mpsc::unbounded_channel::<Vec<u8>>();
+    let writing = tokio::spawn(async move {
+        while let Some(bytes) = outgoing.recv().await {
+            if writer.write_all(&bytes).await.is_err() {
+                break;
+            }
+        }
+    });
+    let mut client = Client {
+        subscriptions: Subscriptions::new(out.clone()),
+        session: None,
+    };
+    let ctx = &daemon.ctx;
+    let mut reader = BufReader::new(reader);
+
+    while let Ok(Some(frame)) = framing::read_frame(&mut reader, daemon.max_request_bytes).await {
+        let line = match frame {
+            Frame::Line(line) => framing::decode(&line, daemon.max_request_bytes),
+            Frame::TooLong(len) => Err(framing::FrameError::TooLarge(format!(
+                "request is {} bytes, over the {}-byte limit (${})",
+                len,
+                daemon.max_request_bytes,
+                framing::MAX_REQUEST_BYTES_ENV
+            ))),
+        };
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                let response = DaemonResponse {
+                    id: "unknown".into(),
+                    result: None,
+                    error: Some(ErrorInfo {
+                        code: e.code(),
+                        message: e.to_string(),
+                        details: serde_json::Value::Null,
+                    }),
+                };
+                daemon.metrics.observe("", &response, 0.0);
+                if !send_frame(&out, &response, "unknown", false) {
+                    break;
+                }
+                continue;
+            }
+        };
+        let method = peek_method(&line);
+        let request_id = peek_request_id(&line);
+        let gzip = framing::accepts_gzip(&line);
+        let turn = match takes_turn(&line) {
+            true => Some(daemon.turn.lock().await),
+            false => None,
+        };
+        let started = ctx.stopwatch();
+
+        // Forward engine events published while this request runs as
+        // progress frames ahead of the final response. Events from another
+        // client's turn are not this request's progress.
+        let progress = turn.is_some().then(|| {
+            let (out, request_id) = (out.clone(), request_id.clone());
+            ctx.events().subscribe(move |ev| {
+                let frame = DaemonProgress {
+                    id: request_id.clone(),
+                    progress: ev.clone(),
+                };
+                send_frame(&out, &frame, &request_id, gzip);
+            })
+        });
+        let response = handle_request(&line, &daemon, &mut client).await;
+        if let Some(progress) = progress {
+            ctx.events().unsubscribe(progress);
+        }
+        daemon
+            .metrics
+            .observe(&method, &response, started.elapsed_ms() as f64 / 1000.0);
+
+        // Blobs the result refers to go out as binary frames first, so the
+        // response arrives with its bytes.
+        let blobs = response
+            .result
+            .as_ref()
+            .and_then(|r| r.data.as_ref())
+            .map(|data| ctx.blobs().take_referenced(data))
+            .unwrap_or_default();
+        for (artifact_id, bytes) in blobs {
+            send_binary(&out, &request_id, artifact_id, bytes);
+        }
+        if !send_frame(&out, &response, &request_id, gzip) {
+            break;
+        }
+    }
+
+    client.subscriptions.clear(ctx);
+    drop(out);
+    let _ = writing.await;
+}
+
+/// Queue `frame` as one line; false once the connection's writer is gone.
+fn send_frame<T: serde::Serialize>(out: &Outbox, frame: &T, id: &str, gzip: bool) -> bool {
+    let mut json = framing::encode(frame, id, gzip);
+    json.push('\n');
+    out.send(json.into_bytes()).is_ok()
+}
+
+fn send_binary(out: &Outbox, id: &str, artifact_id: String, bytes: Vec<u8>) -> bool {
+    let header = DaemonBinary {
+        id: id.to_string(),
+        binary: BinaryHeader {
+            artifact_id,
+            length: bytes.len(),
+        },
+    };
+    send_frame(out, &header, id, false) && out.send(bytes).is_ok()
+}
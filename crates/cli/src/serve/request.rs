@@ -0,0 +1,205 @@
+//! Parsing and answering one request.
+
+use super::client::Client;
+use super::Daemon;
+use engine::framing;
+use engine::types::*;
+
+/// Best-effort extraction of the request id, used to tag progress frames
+/// before the request itself has been fully parsed.
+pub(super) fn peek_request_id(line: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| v.get("id").and_then(|id| id.as_str()).map(String::from))
+        .unwrap_or_else(|| "unknown".into())
+}
+
+/// Whether a request waits for its turn. Subscriptions, the `hello`
+/// handshake and `call` `ping` – what health checks send – touch no shared
+/// state, so they are answered even while another client's request runs.
+pub(super) fn takes_turn(line: &str) -> bool {
+    let Ok(req) = serde_json::from_str::<serde_json::Value>(line) else {
+        return true;
+    };
+    match req["method"].as_str() {
+        Some("subscribe" | "unsubscribe" | "hello") => false,
+        Some("call") => req["params"]["cmd"] != "ping",
+        _ => true,
+    }
+}
+
+pub(super) fn peek_method(line: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| v.get("method").and_then(|m| m.as_str()).map(String::from))
+        .unwrap_or_default()
+}
+
+pub(super) async fn handle_request(
+    line: &str,
+    daemon: &Daemon,
+    client: &mut Client,
+) -> DaemonResponse {
+    let (ctx, registry, probes) = (&daemon.ctx, &daemon.registry, &daemon.probes);
+    let req: DaemonRequest = match serde_json::from_str(line) {
+        Ok(r) => r,
+        Err(e) => {
+            return DaemonResponse {
+                id: "unknown".into(),
+                result: None,
+                error: Some(ErrorInfo {
+                    code: ErrorCode::InvalidInput,
+                    message: format!("invalid JSON request: {}", e),
+                    details: serde_json::Value::Null,
+                }),
+            };
+        }
+    };
+
+    // The handshake and connection state, outside any session's grants
+    // (subscriptions check their topics).
+    let r = match req.method.as_str() {
+        "hello" => Some(engine::protocol::run_hello(
+            &req.params,
+            ctx,
+            registry,
+            probes,
+        )),
+        "subscribe" => {
+            let gzip = req.accept_encoding.as_deref() == Some(framing::GZIP);
+            Some(
+                client
+                    .subscriptions
+                    .subscribe(&req.params, gzip, daemon, client.session.as_ref()),
+            )
+        }
+        "unsubscribe" => Some(client.subscriptions.unsubscribe(&req.params, ctx)),
+        "open_session" => Some(client.open_session(&req.params, daemon)),
+        "close_session" => Some(client.close_session(ctx)),
+        _ => None,
+    };
+    if let Some(r) = r {
+        return DaemonResponse {
+            id: req.id,
+            result: Some(r),
+            error: None,
+        };
+    }
+
+    let action = match req.method.as_str() {
+        "call" => format!("call:{}", req.params["cmd"].as_str().unwrap_or("")),
+        "probe" => format!("probe:{}", req.params["target"].as_str().unwrap_or("")),
+        other => other.to_string(),
+    };
+    let _entered = match &client.session {
+        Some(session) if !session.permits(&action) => {
+            let r = result_err(
+                &req.method,
+                &action,
+                &ctx.new_run_id(),
+                0,
+                ErrorCode::PermissionDenied,
+                format!("session {} has no grant for {}", session.id, action),
+            );
+            return DaemonResponse {
+                id: req.id,
+                result: Some(r),
+                error: None,
+            };
+        }
+        // Outside a turn, so it must not touch the process environment.
+        Some(_) if !takes_turn(line) => None,
+        Some(session) => match session.enter(ctx) {
+            Ok(entered) => Some(entered),
+            Err(e) => {
+                let r = result_err(
+                    &req.method,
+                    &action,
+                    &ctx.new_run_id(),
+                    0,
+                    ErrorCode::IoError,
+                    format!("cannot enter session {}: {}", session.id, e),
+                );
+                return DaemonResponse {
+                    id: req.id,
+                    result: Some(r),
+                    error: None,
+                };
+            }
+        },
+        None => None,
+    };
+
+    let result = match req.method.as_str() {
+        "call" => {
+            let cmd_name = req.params.get("cmd").and_then(|v| v.as_str()).unwrap_or("");
+            let args = req
+                .params
+                .get("args")
+                .cloned()
+                .unwrap_or(serde_json::Value::Object(Default::default()));
+            registry.execute_limited(cmd_name, args, ctx)
+        }
+        "probe" => {
+            let target = req
+                .params
+                .get("target")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let args = req
+                .params
+                .get("args")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            probes.run(target, args, ctx).await
+        }
+        "doctor" => engine::doctor::run_doctor(ctx),
+        "compatibility" => engine::compat::run_compatibility(&ctx.compat_rules, ctx, probes).await,
+        "metrics" => {
+            let mut r = result_ok("metrics", "prometheus", &ctx.new_run_id(), 0);
+            r.data = Some(serde_json::json!({ "text": daemon.metrics.render() }));
+            return DaemonResponse {
+                id: req.id,
+                result: Some(r),
+                error: None,
+            };
+        }
+        "llm_complete" => engine::llm::run_complete(req.params, ctx).await,
+        "llm_stream" => engine::llm::run_stream(req.params, ctx).await,
+        "env_set" => match client.session.as_mut() {
+            Some(session) => session.env_set(req.params, ctx),
+            None => engine::env::run_env_set(req.params, ctx),
+        },
+        "run_scenario" => {
+            engine::scenario::run_request(
+                req.params,
+                ctx,
+                registry,
+                probes,
+                client.session.as_ref(),
+            )
+            .await
+        }
+        "ready_check" => engine::readiness::run_ready_check(ctx, registry, probes).await,
+        "update_check" => engine::updates::run_check(req.params, ctx).await,
+        "update_download" => engine::updates::run_download(req.params, ctx).await,
+        other => {
+            return DaemonResponse {
+                id: req.id,
+                result: None,
+                error: Some(ErrorInfo {
+                    code: ErrorCode::InvalidInput,
+                    message: format!("unknown method: {}", other),
+                    details: serde_json::json!({ "methods": engine::protocol::METHODS }),
+                }),
+            };
+        }
+    };
+
+    engine::history::record_results(ctx, "daemon", None, std::slice::from_ref(&result));
+    DaemonResponse {
+        id: req.id,
+        result: Some(result),
+        error: None,
+    }
+}
@@ -0,0 +1,43 @@
+//! `run-scenario --interactive` with answers piped into stdin.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_piped_answers_run_then_abort() {
+    let dir = tempfile::tempdir().unwrap();
+    let scenario = dir.path().join("two.yaml");
+    std::fs::write(
+        &scenario,
+        "name: two\nsteps:\n  - call: ping\n  - call: ping\n",
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_appctl"))
+        .args(["run-scenario", "--interactive", "--json"])
+        .arg(&scenario)
+        .env("APP__DATA_DIR", dir.path().join("data"))
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"run\nabort\n")
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success(), "{:?}", out);
+
+    let prompts = String::from_utf8_lossy(&out.stderr);
+    assert!(prompts.contains("--- Step 1/2: ping ---"), "{}", prompts);
+    assert!(prompts.contains("Run this step? [run/skip/back/abort]"));
+    let result: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let steps = result["step_results"].as_array().unwrap();
+    assert_eq!(steps.len(), 1, "only the first step ran");
+    assert_eq!(steps[0]["status"], "pass");
+    assert_eq!(result["overall_status"], "skip");
+}
@@ -0,0 +1,105 @@
+//! Binary results kept out of the JSON.
+//!
+//! A command asked for raw bytes (`read_file` with `"encoding": "binary"`,
+//! `export_diagnostics` with `"inline": true`) puts them in the context's
+//! [`BlobStore`] and returns a [`BlobRef`] – an `artifact_id` with the
+//! length and hash – instead of base64 in `data`. The daemon then takes
+//! every blob its response references and sends it as a length-prefixed
+//! binary frame ahead of the response (see [`crate::framing`]). In-process
+//! callers have no such channel and should ask for base64.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Most bytes held at once; the oldest blobs are dropped past it.
+pub const MAX_STORED_BYTES: usize = 256 * 1024 * 1024;
+
+/// What a result carries in place of the bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobRef {
+    pub artifact_id: String,
+    pub length: usize,
+    pub sha256: String,
+}
+
+#[derive(Default)]
+pub struct BlobStore {
+    blobs: Mutex<VecDeque<(String, Vec<u8>)>>,
+    next: AtomicU64,
+}
+
+impl BlobStore {
+    /// Keep `bytes` until taken.
+    pub fn put(&self, bytes: Vec<u8>) -> BlobRef {
+        let id = format!("blob-{}", self.next.fetch_add(1, Ordering::Relaxed) + 1);
+        let blob = BlobRef {
+            artifact_id: id.clone(),
+            length: bytes.len(),
+            sha256: crate::export::hex(
+                ring::digest::digest(&ring::digest::SHA256, &bytes).as_ref(),
+            ),
+        };
+        let mut blobs = self.blobs.lock().unwrap_or_else(|e| e.into_inner());
+        blobs.push_back((id, bytes));
+        let mut total: usize = blobs.iter().map(|(_, b)| b.len()).sum();
+        while total > MAX_STORED_BYTES && blobs.len() > 1 {
+            if let Some((id, dropped)) = blobs.pop_front() {
+                tracing::warn!("dropping untaken blob {} ({} bytes)", id, dropped.len());
+                total -= dropped.len();
+            }
+        }
+        blob
+    }
+
+    /// Remove and return blob `id`.
+    pub fn take(&self, id: &str) -> Option<Vec<u8>> {
+        let mut blobs = self.blobs.lock().unwrap_or_else(|e| e.into_inner());
+        let at = blobs.iter().position(|(b, _)| b == id)?;
+        blobs.remove(at).map(|(_, bytes)| bytes)
+    }
+
+    /// Take every blob `value` refers to by `artifact_id`, in order.
+    pub fn take_referenced(&self, value: &Value) -> Vec<(String, Vec<u8>)> {
+        let mut ids = Vec::new();
+        collect_ids(value, &mut ids);
+        ids.into_iter()
+            .filter_map(|id| self.take(&id).map(|bytes| (id, bytes)))
+            .collect()
+    }
+}
+
+fn collect_ids(value: &Value, ids: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(id)) = map.get("artifact_id") {
+                ids.push(id.clone());
+            }
+            map.values().for_each(|v| collect_ids(v, ids));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_ids(v, ids)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_blobs_are_taken_once() {
+        let store = BlobStore::default();
+        let a = store.put(b"\x00\x01binary".to_vec());
+        let b = store.put(vec![7; 3]);
+        assert_eq!(a.length, 8);
+        assert_ne!(a.artifact_id, b.artifact_id);
+
+        let result = serde_json::json!({ "files": [{ "artifact_id": b.artifact_id }], "x": 1 });
+        let taken = store.take_referenced(&result);
+        assert_eq!(taken, [(b.artifact_id.clone(), vec![7; 3])]);
+        assert!(store.take_referenced(&result).is_empty());
+        assert_eq!(store.take(&a.artifact_id).unwrap(), b"\x00\x01binary");
+    }
+}
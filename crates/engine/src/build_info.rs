@@ -0,0 +1,69 @@
+//! What build of the engine is running: crate version, git commit, build
+//! date, cargo features, and rustc version, embedded by `build.rs`.
+//!
+//! Stamped on every result's `env_summary.build`, so artifacts collected
+//! from many VMs can be traced back to the binary that produced them.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildInfo {
+    pub engine_version: String,
+    /// Short commit hash, `unknown` outside a git checkout.
+    pub git_commit: String,
+    /// UTC, RFC 3339; `$SOURCE_DATE_EPOCH` when set at build time.
+    pub build_date: String,
+    pub features: Vec<String>,
+    pub rustc: String,
+}
+
+/// This binary's build info.
+pub fn current() -> BuildInfo {
+    BuildInfo {
+        engine_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("ENGINE_GIT_COMMIT").to_string(),
+        build_date: env!("ENGINE_BUILD_DATE").to_string(),
+        features: env!("ENGINE_FEATURES")
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect(),
+        rustc: env!("ENGINE_RUSTC_VERSION").to_string(),
+    }
+}
+
+/// `version_info` – the engine's build info.
+///
+/// Args: none.
+/// Returns: `{ "engine_version", "git_commit", "build_date", "features",
+/// "rustc" }`.
+pub(crate) fn cmd_version_info(_args: Value, _ctx: &AppContext) -> Result<Value, CommandError> {
+    serde_json::to_value(current()).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_is_embedded() {
+        let info = current();
+        assert_eq!(info.engine_version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_commit.is_empty());
+        assert_eq!(info.build_date.len(), "2026-01-01T00:00:00Z".len());
+        assert!(info.rustc.starts_with("rustc "), "{}", info.rustc);
+
+        let ctx = AppContext::default_headless();
+        let r = crate::commands::CommandRegistry::new().execute(
+            "version_info",
+            serde_json::json!({}),
+            &ctx,
+        );
+        assert_eq!(r.data.unwrap()["git_commit"], info.git_commit.as_str());
+        assert_eq!(r.env_summary.build, info);
+    }
+}
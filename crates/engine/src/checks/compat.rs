@@ -0,0 +1,361 @@
+//! Host compatibility tiers: one set of rules, evaluated the same way by
+//! the GUI on first launch and by `appctl compatibility` in CI.
+//!
+//! The rules come from the YAML file named by `$APP__COMPAT_RULES`, or
+//! [`builtin`] (the `filesystem` probe must pass):
+//!
+//! ```yaml
+//! required: [filesystem, display]       # failing: unsupported
+//! recommended:                          # failing: degraded
+//!   - clipboard
+//!   - probe: network
+//!     args: {timeout_ms: 2000}
+//! min_os_version:                       # older: unsupported
+//!   macos: "13.0"                       # product version (sw_vers)
+//!   linux: "5.10"                       # kernel release
+//! min_app_version: "1.2.0"              # older: unsupported
+//! ```
+//!
+//! A required probe that skips (offline, not on this platform) leaves the
+//! host degraded, since it could not be checked; a recommended one that
+//! skips is ignored. A version that cannot be determined also degrades.
+
+use crate::context::AppContext;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// YAML file with the rules.
+pub const COMPAT_RULES_ENV: &str = "APP__COMPAT_RULES";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompatRules {
+    /// Probes that must pass.
+    pub required: Vec<CompatProbe>,
+    /// Probes that should pass.
+    pub recommended: Vec<CompatProbe>,
+    /// Lowest supported OS version, by OS name (`macos`, `linux`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub min_os_version: BTreeMap<String, String>,
+    /// Lowest supported app version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_app_version: Option<String>,
+}
+
+/// The rules used without `$APP__COMPAT_RULES`.
+pub fn builtin() -> CompatRules {
+    CompatRules {
+        required: vec![CompatProbe::Name("filesystem".into())],
+        ..Default::default()
+    }
+}
+
+/// A probe name, or a probe with arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CompatProbe {
+    Name(String),
+    WithArgs {
+        probe: String,
+        #[serde(default)]
+        args: serde_json::Value,
+    },
+}
+
+impl CompatProbe {
+    fn name(&self) -> &str {
+        match self {
+            CompatProbe::Name(name) => name,
+            CompatProbe::WithArgs { probe, .. } => probe,
+        }
+    }
+
+    fn args(&self) -> serde_json::Value {
+        match self {
+            CompatProbe::Name(_) => serde_json::Value::Null,
+            CompatProbe::WithArgs { args, .. } => args.clone(),
+        }
+    }
+}
+
+/// Rules from `$APP__COMPAT_RULES`, else the built-in ones; an unreadable
+/// file is logged and ignored.
+pub fn from_env() -> CompatRules {
+    let Some(path) = std::env::var_os(COMPAT_RULES_ENV).filter(|v| !v.is_empty()) else {
+        return builtin();
+    };
+    match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|yaml| load(&yaml))
+    {
+        Ok(rules) => rules,
+        Err(e) => {
+            tracing::warn!("ignoring {}: {}", COMPAT_RULES_ENV, e);
+            builtin()
+        }
+    }
+}
+
+pub fn load(yaml: &str) -> Result<CompatRules, String> {
+    serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse compatibility rules: {}", e))
+}
+
+/// What the rules are checked against, besides probes.
+#[derive(Debug, Clone, Default)]
+pub struct HostFacts {
+    pub os: String,
+    /// The version `min_os_version` compares, if known.
+    pub os_version: Option<String>,
+    pub app_version: Option<String>,
+}
+
+impl HostFacts {
+    pub fn current() -> Self {
+        let os = current_os().to_string();
+        let os_version = match os.as_str() {
+            "linux" => crate::doctor::kernel_version(),
+            _ => crate::doctor::os_version(),
+        };
+        Self {
+            os,
+            os_version: Some(os_version).filter(|v| v != "unknown"),
+            app_version: crate::host::app_version(),
+        }
+    }
+}
+
+/// Run the probes the rules name and classify this host. The result
+/// passes unless the host is unsupported; `data` is a [`CompatReport`].
+pub async fn run_compatibility(
+    rules: &CompatRules,
+    ctx: &AppContext,
+    probes: &ProbeRegistry,
+) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    let mut checks = Vec::new();
+    let required = rules.required.iter().map(|p| (p, true));
+    let recommended = rules.recommended.iter().map(|p| (p, false));
+    for (probe, critical) in required.chain(recommended) {
+        let result = probes.run(probe.name(), probe.args(), ctx).await;
+        checks.push(SuiteProbeStatus {
+            probe: probe.name().to_string(),
+            status: result.status,
+            critical,
+        });
+    }
+
+    let report = evaluate(rules, &HostFacts::current(), checks);
+    let tier = serde_json::to_value(report.tier)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default();
+    let mut r = result_ok("compatibility", &tier, &run_id, start.elapsed_ms());
+    if report.tier == CompatTier::Unsupported {
+        r.status = Status::Fail;
+    }
+    r.data = serde_json::to_value(&report).ok();
+    r
+}
+
+/// Classify a host from its facts and probe outcomes (`critical` marks the
+/// required probes).
+pub fn evaluate(
+    rules: &CompatRules,
+    facts: &HostFacts,
+    checks: Vec<SuiteProbeStatus>,
+) -> CompatReport {
+    let mut reasons = Vec::new();
+    for check in &checks {
+        let tier = match (check.critical, check.status) {
+            (_, Status::Pass) | (false, Status::Skip) => continue,
+            (true, Status::Skip) => CompatTier::Degraded,
+            (true, _) => CompatTier::Unsupported,
+            (false, _) => CompatTier::Degraded,
+        };
+        let kind = if check.critical {
+            "required"
+        } else {
+            "recommended"
+        };
+        reasons.push(CompatReason {
+            tier,
+            rule: format!("probe:{}", check.probe),
+            message: format!(
+                "{} probe {} {}",
+                kind,
+                check.probe,
+                match check.status {
+                    Status::Skip => "was skipped",
+                    Status::Error => "errored",
+                    _ => "failed",
+                }
+            ),
+        });
+    }
+
+    if let Some(min) = rules.min_os_version.get(&facts.os) {
+        reasons.extend(check_version(
+            "os",
+            &facts.os,
+            facts.os_version.as_deref(),
+            min,
+        ));
+    }
+    if let Some(min) = &rules.min_app_version {
+        reasons.extend(check_version(
+            "app",
+            "app",
+            facts.app_version.as_deref(),
+            min,
+        ));
+    }
+
+    CompatReport {
+        tier: reasons
+            .iter()
+            .map(|r| r.tier)
+            .max()
+            .unwrap_or(CompatTier::Supported),
+        reasons,
+        os: facts.os.clone(),
+        os_version: facts.os_version.clone(),
+        app_version: facts.app_version.clone(),
+        checks,
+    }
+}
+
+fn check_version(rule: &str, what: &str, actual: Option<&str>, min: &str) -> Option<CompatReason> {
+    let rule = format!("min_{}_version", rule);
+    let Some(actual) = actual else {
+        return Some(CompatReason {
+            tier: CompatTier::Degraded,
+            rule,
+            message: format!("{} version unknown; {} or later is required", what, min),
+        });
+    };
+    match version_at_least(actual, min) {
+        Some(true) => None,
+        Some(false) => Some(CompatReason {
+            tier: CompatTier::Unsupported,
+            rule,
+            message: format!("{} {} is older than {}", what, actual, min),
+        }),
+        None => Some(CompatReason {
+            tier: CompatTier::Degraded,
+            rule,
+            message: format!("cannot compare {} version {:?} with {}", what, actual, min),
+        }),
+    }
+}
+
+/// Compare dotted versions numerically, ignoring anything after the
+/// numbers (`5.15.0-91-generic` is 5.15.0); missing parts count as 0.
+/// `None` if either has no leading number.
+fn version_at_least(actual: &str, min: &str) -> Option<bool> {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        let mut parts = Vec::new();
+        for part in v.trim().trim_start_matches('v').split('.') {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            let Ok(n) = digits.parse() else { break };
+            parts.push(n);
+            if digits.len() < part.len() {
+                break;
+            }
+        }
+        (!parts.is_empty()).then_some(parts)
+    };
+    let (mut actual, mut min) = (parse(actual)?, parse(min)?);
+    let len = actual.len().max(min.len());
+    actual.resize(len, 0);
+    min.resize(len, 0);
+    Some(actual >= min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(probe: &str, status: Status, critical: bool) -> SuiteProbeStatus {
+        SuiteProbeStatus {
+            probe: probe.into(),
+            status,
+            critical,
+        }
+    }
+
+    #[test]
+    fn test_version_at_least() {
+        assert_eq!(version_at_least("5.15.0-91-generic", "5.10"), Some(true));
+        assert_eq!(version_at_least("13", "13.0.0"), Some(true));
+        assert_eq!(version_at_least("12.7.1", "13.0"), Some(false));
+        assert_eq!(version_at_least("v1.2.0", "1.10"), Some(false));
+        assert_eq!(version_at_least("unknown", "1"), None);
+    }
+
+    #[test]
+    fn test_evaluate_takes_the_worst_reason() {
+        let rules = load(
+            "required: [filesystem]\nrecommended: [{probe: clipboard, args: {}}]\nmin_os_version: {linux: '5.10'}\n",
+        )
+        .unwrap();
+        let facts = HostFacts {
+            os: "linux".into(),
+            os_version: Some("6.1.0".into()),
+            app_version: None,
+        };
+
+        let ok = evaluate(
+            &rules,
+            &facts,
+            vec![
+                check("filesystem", Status::Pass, true),
+                check("clipboard", Status::Skip, false),
+            ],
+        );
+        assert_eq!(ok.tier, CompatTier::Supported);
+        assert!(ok.reasons.is_empty());
+
+        let degraded = evaluate(
+            &rules,
+            &facts,
+            vec![
+                check("filesystem", Status::Skip, true),
+                check("clipboard", Status::Fail, false),
+            ],
+        );
+        assert_eq!(degraded.tier, CompatTier::Degraded);
+        assert_eq!(degraded.reasons.len(), 2);
+
+        let old = HostFacts {
+            os_version: Some("4.19.0".into()),
+            ..facts
+        };
+        let unsupported = evaluate(&rules, &old, vec![check("filesystem", Status::Pass, true)]);
+        assert_eq!(unsupported.tier, CompatTier::Unsupported);
+        assert_eq!(unsupported.reasons[0].rule, "min_os_version");
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_keys() {
+        assert!(load("requird: [filesystem]\n").is_err());
+        assert_eq!(load("{}").unwrap().required, vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_run_compatibility_fails_only_when_unsupported() {
+        let ctx = AppContext::default_headless();
+        let probes = ProbeRegistry::new();
+        let r = run_compatibility(&builtin(), &ctx, &probes).await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.target, "supported");
+
+        let rules = load("required: [no-such-probe]\n").unwrap();
+        let r = run_compatibility(&rules, &ctx, &probes).await;
+        assert_eq!(r.status, Status::Fail);
+        let report: CompatReport = serde_json::from_value(r.data.unwrap()).unwrap();
+        assert_eq!(report.tier, CompatTier::Unsupported);
+    }
+}
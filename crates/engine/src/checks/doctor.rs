@@ -1,21 +1,43 @@
 //! Doctor – gather environment facts for diagnostics.
 
+use crate::context::AppContext;
 use crate::types::*;
 use std::collections::HashMap;
-use std::time::Instant;
 
 /// Run the doctor check and return a full report as a CommandResult.
-pub fn run_doctor() -> CommandResult {
-    let run_id = new_run_id();
-    let start = Instant::now();
+///
+/// Publishes `doctor:finished` (with the report) on the context's event bus
+/// so GUI diagnostics panels can update without polling.
+pub fn run_doctor(ctx: &AppContext) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
 
     let report = gather_report();
 
-    let mut r = result_ok("doctor", "env", &run_id, start.elapsed().as_millis() as u64);
+    let mut r = result_ok("doctor", "env", &run_id, start.elapsed_ms());
     r.data = Some(serde_json::to_value(&report).unwrap_or_default());
+    ctx.events().emit(
+        &run_id,
+        "doctor:finished",
+        serde_json::json!({
+            "status": r.status,
+            "duration_ms": r.timing_ms.total,
+            "report": r.data,
+        }),
+    );
     r
 }
 
+/// `doctor` – the same report as [`run_doctor`], as a registry command the
+/// UI can poll (cached for a while; see [`crate::cache`]).
+pub(crate) fn cmd_doctor(
+    _args: serde_json::Value,
+    _ctx: &AppContext,
+) -> Result<serde_json::Value, crate::commands::CommandError> {
+    serde_json::to_value(gather_report())
+        .map_err(|e| crate::commands::CommandError::Other(e.to_string()))
+}
+
 fn gather_report() -> DoctorReport {
     DoctorReport {
         os_name: os_name(),
@@ -29,6 +51,7 @@ fn gather_report() -> DoctorReport {
         session_type: session_type(),
         display_server: display_server(),
         proxy_env: collect_proxy_env(),
+        sandbox: crate::sandbox::report(),
     }
 }
 
@@ -36,7 +59,7 @@ fn os_name() -> String {
     std::env::consts::OS.to_string()
 }
 
-fn os_version() -> String {
+pub(crate) fn os_version() -> String {
     #[cfg(target_os = "macos")]
     {
         run_cmd("sw_vers", &["-productVersion"]).unwrap_or_else(|| "unknown".into())
@@ -59,7 +82,7 @@ fn os_version() -> String {
     }
 }
 
-fn kernel_version() -> String {
+pub(crate) fn kernel_version() -> String {
     run_cmd("uname", &["-r"]).unwrap_or_else(|| "unknown".into())
 }
 
@@ -155,3 +178,25 @@ fn run_cmd(cmd: &str, args: &[&str]) -> Option<String> {
         .filter(|o| o.status.success())
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doctor_publishes_report() {
+        let ctx = AppContext::default_headless();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        ctx.events()
+            .subscribe(move |ev| seen.lock().unwrap().push(ev.clone()));
+        let r = run_doctor(&ctx);
+
+        assert_eq!(r.status, Status::Pass);
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].topic, "doctor:finished");
+        assert_eq!(events[0].run_id, r.run_id);
+        assert_eq!(events[0].payload["report"]["arch"], std::env::consts::ARCH);
+    }
+}
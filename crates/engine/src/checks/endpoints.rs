@@ -0,0 +1,433 @@
+//! Network probe targets – the endpoints `probe network` checks, each with
+//! its own expectations, so a corporate allowlist blocking one host shows
+//! up as that host failing rather than "network error".
+//!
+//! Targets come from the app config (`network: endpoints:`), or from
+//! `$APP__NETWORK_ENDPOINTS`: a comma-separated URL list, or a JSON array
+//! of [`NetworkEndpoint`] objects.
+//!
+//! An optional throughput test ([`ThroughputConfig`]) adds latency
+//! percentiles and a bounded download, so a slow VM NIC fails the probe
+//! instead of passing because every request eventually answered.
+
+use crate::context::AppContext;
+use crate::traits::{CapError, HttpRequest};
+use serde::{Deserialize, Serialize};
+
+/// Environment variable overriding the configured endpoints.
+pub const ENDPOINTS_ENV: &str = "APP__NETWORK_ENDPOINTS";
+
+/// Checked when nothing is configured.
+pub const DEFAULT_ENDPOINT: &str = "https://httpbin.org/get";
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+/// One URL to GET and what its answer must look like. Without
+/// expectations any HTTP response passes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkEndpoint {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect_status: Option<u16>,
+    /// Budget for the GET itself (DNS excluded).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_latency_ms: Option<u64>,
+    /// Header the response must carry (case-insensitive name).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_header: Option<String>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl NetworkEndpoint {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            expect_status: None,
+            max_latency_ms: None,
+            require_header: None,
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+/// Network probe settings (`network:` in the app config).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkProbeConfig {
+    pub endpoints: Vec<NetworkEndpoint>,
+    /// Latency/bandwidth test; off unless configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput: Option<ThroughputConfig>,
+}
+
+impl Default for NetworkProbeConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![NetworkEndpoint::new(DEFAULT_ENDPOINT)],
+            throughput: None,
+        }
+    }
+}
+
+impl NetworkProbeConfig {
+    /// `$APP__NETWORK_ENDPOINTS` if set and valid, else the default target.
+    pub fn from_env() -> Self {
+        std::env::var(ENDPOINTS_ENV)
+            .ok()
+            .and_then(|v| match parse_endpoints(&v) {
+                Ok(endpoints) => Some(Self {
+                    endpoints,
+                    throughput: None,
+                }),
+                Err(e) => {
+                    tracing::warn!("ignoring {}: {}", ENDPOINTS_ENV, e);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A JSON array of endpoints, or comma-separated URLs.
+pub fn parse_endpoints(text: &str) -> Result<Vec<NetworkEndpoint>, String> {
+    let text = text.trim();
+    let endpoints: Vec<NetworkEndpoint> = if text.starts_with('[') {
+        serde_json::from_str(text).map_err(|e| e.to_string())?
+    } else {
+        text.split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(NetworkEndpoint::new)
+            .collect()
+    };
+    if endpoints.is_empty() {
+        return Err("no endpoints".into());
+    }
+    Ok(endpoints)
+}
+
+/// How one endpoint fared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EndpointResult {
+    pub url: String,
+    pub ok: bool,
+    pub dns_addresses: Vec<String>,
+    pub http_status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    /// Unmet expectations, or the transport failure.
+    pub problems: Vec<String>,
+    /// The request timed out (as opposed to failing outright).
+    #[serde(skip)]
+    pub timed_out: bool,
+    #[serde(skip)]
+    pub dns_ms: u64,
+}
+
+/// Resolve the endpoint's host, GET it, and check the expectations.
+pub async fn check_endpoint(ctx: &AppContext, endpoint: &NetworkEndpoint) -> EndpointResult {
+    let mut r = EndpointResult {
+        url: endpoint.url.clone(),
+        ok: false,
+        dns_addresses: Vec::new(),
+        http_status: None,
+        latency_ms: None,
+        problems: Vec::new(),
+        timed_out: false,
+        dns_ms: 0,
+    };
+    let host = match reqwest::Url::parse(&endpoint.url) {
+        Ok(url) => url.host_str().unwrap_or_default().to_string(),
+        Err(e) => {
+            r.problems.push(format!("invalid URL: {}", e));
+            return r;
+        }
+    };
+
+    let t0 = ctx.stopwatch();
+    let resolved = ctx.network().dns_resolve(&host).await;
+    r.dns_ms = t0.elapsed_ms();
+    match resolved {
+        Ok(addrs) => r.dns_addresses = addrs,
+        Err(e) => {
+            r.problems.push(format!("DNS resolution failed: {}", e));
+            return r;
+        }
+    }
+
+    let t1 = ctx.stopwatch();
+    let request = HttpRequest::get(endpoint.url.clone()).timeout_ms(endpoint.timeout_ms);
+    let resp = match ctx.network().send(request).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            r.timed_out = matches!(e, CapError::Timeout);
+            r.problems.push(format!("GET failed: {}", e));
+            return r;
+        }
+    };
+    let latency = t1.elapsed_ms();
+    r.http_status = Some(resp.status);
+    r.latency_ms = Some(latency);
+
+    if let Some(want) = endpoint.expect_status {
+        if resp.status != want {
+            r.problems
+                .push(format!("status {}, expected {}", resp.status, want));
+        }
+    }
+    if let Some(budget) = endpoint.max_latency_ms {
+        if latency > budget {
+            r.problems
+                .push(format!("took {}ms, budget {}ms", latency, budget));
+        }
+    }
+    if let Some(header) = &endpoint.require_header {
+        if resp.header(header).is_none() {
+            r.problems.push(format!("missing header {}", header));
+        }
+    }
+    r.ok = r.problems.is_empty();
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Throughput
+// ---------------------------------------------------------------------------
+
+/// Downloaded when no `download_url` is given; the server honours `bytes`.
+pub const DEFAULT_DOWNLOAD_URL: &str = "https://speed.cloudflare.com/__down?bytes=5000000";
+
+fn default_latency_samples() -> u32 {
+    10
+}
+
+fn default_download_url() -> String {
+    DEFAULT_DOWNLOAD_URL.into()
+}
+
+fn default_download_bytes() -> u64 {
+    5_000_000
+}
+
+fn default_download_timeout_ms() -> u64 {
+    30_000
+}
+
+/// Latency percentiles from repeated small GETs, and bandwidth from one
+/// bounded download, each with optional budgets. Set under `network:` in
+/// the config, or per scenario on a `probe: network` step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThroughputConfig {
+    /// Timed for latency; the first endpoint if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_url: Option<String>,
+    #[serde(default = "default_latency_samples")]
+    pub latency_samples: u32,
+    #[serde(default = "default_download_url")]
+    pub download_url: String,
+    /// Reading stops here whatever the server sends.
+    #[serde(default = "default_download_bytes")]
+    pub download_bytes: u64,
+    #[serde(default = "default_download_timeout_ms")]
+    pub download_timeout_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_p50_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_p95_ms: Option<u64>,
+    /// Minimum bandwidth in Mbit/s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_mbps: Option<f64>,
+}
+
+impl Default for ThroughputConfig {
+    fn default() -> Self {
+        Self {
+            latency_url: None,
+            latency_samples: default_latency_samples(),
+            download_url: default_download_url(),
+            download_bytes: default_download_bytes(),
+            download_timeout_ms: default_download_timeout_ms(),
+            max_p50_ms: None,
+            max_p95_ms: None,
+            min_mbps: None,
+        }
+    }
+}
+
+/// Measured latency and bandwidth.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThroughputResult {
+    pub latency_url: String,
+    /// Requests that got an answer; failed ones are left out of the
+    /// percentiles.
+    pub latency_samples: u32,
+    pub latency_failures: u32,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    pub download_url: String,
+    pub downloaded_bytes: u64,
+    pub download_ms: Option<u64>,
+    pub mbps: Option<f64>,
+    /// Budgets missed, or why a measurement could not be taken.
+    pub problems: Vec<String>,
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[u64], pct: u64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct as usize * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// Run the latency samples and the download described by `config`.
+/// `fallback_url` is timed when `latency_url` is unset.
+pub async fn measure_throughput(
+    ctx: &AppContext,
+    config: &ThroughputConfig,
+    fallback_url: &str,
+) -> ThroughputResult {
+    let latency_url = config
+        .latency_url
+        .clone()
+        .unwrap_or_else(|| fallback_url.to_string());
+    let mut latencies = Vec::new();
+    let mut last_error = None;
+    for _ in 0..config.latency_samples {
+        let t = ctx.stopwatch();
+        // Only the round trip matters; don't pull a large body per sample.
+        let request = HttpRequest::get(latency_url.clone())
+            .timeout_ms(default_timeout_ms())
+            .max_body_bytes(0);
+        match ctx.network().send(request).await {
+            Ok(_) => latencies.push(t.elapsed_ms()),
+            Err(e) => last_error = Some(e.to_string()),
+        }
+    }
+    latencies.sort_unstable();
+
+    let mut r = ThroughputResult {
+        latency_samples: latencies.len() as u32,
+        latency_failures: config.latency_samples - latencies.len() as u32,
+        p50_ms: percentile(&latencies, 50),
+        p95_ms: percentile(&latencies, 95),
+        max_ms: latencies.last().copied(),
+        latency_url,
+        download_url: config.download_url.clone(),
+        downloaded_bytes: 0,
+        download_ms: None,
+        mbps: None,
+        problems: Vec::new(),
+    };
+    if let Some(e) = last_error.filter(|_| latencies.is_empty()) {
+        r.problems
+            .push(format!("latency: every request failed: {}", e));
+    }
+    for (label, measured, budget) in [
+        ("p50", r.p50_ms, config.max_p50_ms),
+        ("p95", r.p95_ms, config.max_p95_ms),
+    ] {
+        if let (Some(ms), Some(budget)) = (measured, budget) {
+            if ms > budget {
+                r.problems
+                    .push(format!("latency {} {}ms, budget {}ms", label, ms, budget));
+            }
+        }
+    }
+
+    let last_byte = config.download_bytes.saturating_sub(1);
+    let request = HttpRequest::get(config.download_url.clone())
+        .header("Range", format!("bytes=0-{}", last_byte))
+        .timeout_ms(config.download_timeout_ms)
+        .max_body_bytes(config.download_bytes as usize);
+    let t = ctx.stopwatch();
+    match ctx.network().send(request).await {
+        Ok(resp) if resp.is_success() => {
+            let ms = t.elapsed_ms();
+            let bytes = resp.body.len() as u64;
+            // Bits per millisecond / 1000 = Mbit/s.
+            let mbps = (bytes * 8) as f64 / ms.max(1) as f64 / 1000.0;
+            r.downloaded_bytes = bytes;
+            r.download_ms = Some(ms);
+            r.mbps = Some((mbps * 100.0).round() / 100.0);
+            if let Some(min) = config.min_mbps.filter(|min| mbps < *min) {
+                r.problems.push(format!(
+                    "bandwidth {:.2} Mbit/s, minimum {} Mbit/s",
+                    mbps, min
+                ));
+            }
+        }
+        Ok(resp) => r.problems.push(format!("download: status {}", resp.status)),
+        Err(e) => r.problems.push(format!("download failed: {}", e)),
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_endpoints() {
+        let plain = parse_endpoints("https://a.test/, https://b.test/health").unwrap();
+        assert_eq!(plain.len(), 2);
+        assert_eq!(plain[1], NetworkEndpoint::new("https://b.test/health"));
+
+        let json =
+            parse_endpoints(r#"[{"url": "https://a.test/", "expect_status": 204}]"#).unwrap();
+        assert_eq!(json[0].expect_status, Some(204));
+        assert_eq!(json[0].timeout_ms, 10_000);
+
+        assert!(parse_endpoints(" , ").is_err());
+        assert!(parse_endpoints("[{}]").is_err());
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples: Vec<u64> = (1..=20).collect();
+        assert_eq!(percentile(&samples, 50), Some(10));
+        assert_eq!(percentile(&samples, 95), Some(19));
+        assert_eq!(percentile(&[7], 95), Some(7));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[tokio::test]
+    async fn test_throughput_flags_missed_budgets() {
+        use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+        use crate::traits::HttpResponse;
+
+        let network = MockNetwork::new()
+            .respond("https://api.test/", HttpResponse::default())
+            .respond(
+                "https://dl.test/",
+                HttpResponse {
+                    status: 206,
+                    headers: Vec::new(),
+                    body: vec![0; 4096],
+                },
+            );
+        let ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(network),
+            Box::new(HeadlessClipboard),
+        );
+        let config = ThroughputConfig {
+            latency_samples: 3,
+            download_url: "https://dl.test/blob".into(),
+            download_bytes: 1024,
+            min_mbps: Some(1_000_000.0),
+            ..Default::default()
+        };
+        let r = measure_throughput(&ctx, &config, "https://api.test/ping").await;
+        assert_eq!(r.latency_samples, 3);
+        assert!(r.p95_ms.is_some());
+        assert_eq!(r.downloaded_bytes, 1024);
+        assert_eq!(r.problems.len(), 1, "{:?}", r.problems);
+        assert!(r.problems[0].starts_with("bandwidth"));
+    }
+}
@@ -0,0 +1,455 @@
+//! User-facing error text: `explain_error` turns an [`ErrorCode`] and its
+//! message into a title, a description, and suggested actions, so the
+//! frontend can show a helpful dialog instead of `IO_ERROR: …`.
+//!
+//! Text is looked up by code, or by `CODE.variant` when the error is a
+//! recognizable case of it (`IO_ERROR.not_found`, `NETWORK_ERROR.dns`, …):
+//! `details.kind` names the variant, else it is guessed from the message.
+//! Each field falls back from the requested locale (`de-AT`, then `de`) to
+//! the built-in English, and from the variant to the plain code.
+//!
+//! Translations come from `error_messages:` in the app config, by locale:
+//!
+//! ```yaml
+//! error_messages:
+//!   de:
+//!     errors:
+//!       IO_ERROR.not_found:
+//!         title: Datei nicht gefunden
+//!         description: "{path} existiert nicht mehr."
+//!     actions:
+//!       retry: Erneut versuchen
+//! ```
+//!
+//! `{message}` and `{<key>}` for any string or number in `details` are
+//! filled in; unknown placeholders are left as they are.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::types::ErrorCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Locale of the built-in text.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Text for one locale; every field is optional, so a translation can
+/// cover only part of the built-in catalog.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErrorCatalog {
+    /// By `CODE` or `CODE.variant`.
+    pub errors: BTreeMap<String, ErrorText>,
+    /// Action labels, by action id.
+    pub actions: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErrorText {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Action ids, most useful first.
+    pub actions: Option<Vec<String>>,
+}
+
+/// What the frontend shows for one error.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Explanation {
+    pub code: ErrorCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+    pub title: String,
+    pub description: String,
+    pub actions: Vec<SuggestedAction>,
+    /// Locale the title came from.
+    pub locale: String,
+    /// The original message, for a "details" disclosure.
+    pub technical: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuggestedAction {
+    pub id: String,
+    pub label: String,
+}
+
+/// Built-in English text: (key, title, description, actions).
+const BUILTIN: &[(&str, &str, &str, &[&str])] = &[
+    (
+        "INVALID_INPUT",
+        "Something doesn't look right",
+        "The app couldn't use what it was given: {message}",
+        &["edit_input"],
+    ),
+    (
+        "UNSUPPORTED",
+        "Not available here",
+        "This isn't supported on this system.",
+        &["report_issue"],
+    ),
+    (
+        "UNIMPLEMENTED",
+        "Not available yet",
+        "This feature hasn't been built yet.",
+        &[],
+    ),
+    (
+        "DEPENDENCY_MISSING",
+        "Something the app needs is missing",
+        "A required component isn't installed or couldn't be found.",
+        &["install_dependency", "report_issue"],
+    ),
+    (
+        "PERMISSION_DENIED",
+        "Permission needed",
+        "The app isn't allowed to do this. You may need to grant access in your system settings.",
+        &["open_settings", "retry"],
+    ),
+    (
+        "PERMISSION_DENIED.sandbox",
+        "Blocked by the app sandbox",
+        "The app's sandbox doesn't allow this location. Choose a file through the file picker instead.",
+        &["choose_other_file"],
+    ),
+    (
+        "NETWORK_ERROR",
+        "Connection problem",
+        "The app couldn't reach the server. Check your internet connection and try again.",
+        &["check_connection", "retry"],
+    ),
+    (
+        "NETWORK_ERROR.dns",
+        "Server not found",
+        "The server's address couldn't be looked up. You may be offline, or a network filter may be blocking it.",
+        &["check_connection", "retry"],
+    ),
+    (
+        "NETWORK_ERROR.tls",
+        "Secure connection failed",
+        "The server's certificate couldn't be verified. A proxy or security software may be intercepting the connection.",
+        &["check_connection", "report_issue"],
+    ),
+    (
+        "NETWORK_ERROR.refused",
+        "Server didn't answer",
+        "The server refused the connection. It may be down; try again later.",
+        &["retry"],
+    ),
+    (
+        "IO_ERROR",
+        "Couldn't read or write a file",
+        "A file operation failed: {message}",
+        &["retry", "choose_other_file"],
+    ),
+    (
+        "IO_ERROR.not_found",
+        "File not found",
+        "The file or folder doesn't exist. It may have been moved or deleted.",
+        &["choose_other_file"],
+    ),
+    (
+        "IO_ERROR.disk_full",
+        "Disk is full",
+        "There isn't enough free space to save. Free up some space and try again.",
+        &["free_disk_space", "retry"],
+    ),
+    (
+        "IO_ERROR.read_only",
+        "Location is read-only",
+        "This location can't be written to. Choose another folder.",
+        &["choose_other_file"],
+    ),
+    (
+        "TIMEOUT",
+        "This is taking too long",
+        "The operation didn't finish in time. Try again; if it keeps happening, check your connection.",
+        &["retry", "check_connection"],
+    ),
+    (
+        "RESOURCE_EXHAUSTED",
+        "Ran out of memory",
+        "The operation used more memory than allowed and was stopped waiting for. Try a smaller input.",
+        &["edit_input", "retry"],
+    ),
+    (
+        "EXTERNAL_INTERFERENCE",
+        "Interrupted by another program",
+        "Something outside the app interfered with this operation.",
+        &["retry"],
+    ),
+    (
+        "INTERNAL_ERROR",
+        "Something went wrong",
+        "An unexpected error occurred. Please report it so it can be fixed.",
+        &["report_issue", "retry"],
+    ),
+    ("USER_SKIPPED", "Skipped", "This step was skipped.", &[]),
+];
+
+const BUILTIN_ACTIONS: &[(&str, &str)] = &[
+    ("retry", "Try again"),
+    ("edit_input", "Check your input"),
+    ("open_settings", "Open settings"),
+    ("choose_other_file", "Choose another file"),
+    ("free_disk_space", "Free up space"),
+    ("check_connection", "Check connection"),
+    ("install_dependency", "Install missing component"),
+    ("report_issue", "Report a problem"),
+];
+
+/// The built-in English catalog.
+pub fn builtin() -> ErrorCatalog {
+    ErrorCatalog {
+        errors: BUILTIN
+            .iter()
+            .map(|(key, title, description, actions)| {
+                (
+                    key.to_string(),
+                    ErrorText {
+                        title: Some(title.to_string()),
+                        description: Some(description.to_string()),
+                        actions: Some(actions.iter().map(|a| a.to_string()).collect()),
+                    },
+                )
+            })
+            .collect(),
+        actions: BUILTIN_ACTIONS
+            .iter()
+            .map(|(id, label)| (id.to_string(), label.to_string()))
+            .collect(),
+    }
+}
+
+/// The recognizable case of `code` this error is, if any.
+pub fn variant(code: ErrorCode, message: &str, details: &Value) -> Option<String> {
+    if let Some(kind) = details.get("kind").and_then(Value::as_str) {
+        return Some(kind.to_string());
+    }
+    let message = message.to_ascii_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| message.contains(n));
+    let found = match code {
+        ErrorCode::IoError if has(&["no such file", "not found"]) => "not_found",
+        ErrorCode::IoError if has(&["no space left", "disk full", "quota"]) => "disk_full",
+        ErrorCode::IoError if has(&["read-only"]) => "read_only",
+        ErrorCode::NetworkError if has(&["dns", "lookup", "resolve"]) => "dns",
+        ErrorCode::NetworkError if has(&["certificate", "tls", "ssl"]) => "tls",
+        ErrorCode::NetworkError if has(&["connection refused"]) => "refused",
+        ErrorCode::PermissionDenied if has(&["sandbox", "flatpak", "portal"]) => "sandbox",
+        _ => return None,
+    };
+    Some(found.to_string())
+}
+
+/// Explain an error in `locale`, using `catalogs` (by locale) over the
+/// built-in text.
+pub fn explain(
+    code: ErrorCode,
+    message: &str,
+    details: &Value,
+    locale: &str,
+    catalogs: &BTreeMap<String, ErrorCatalog>,
+) -> Explanation {
+    let builtin = builtin();
+    // Most specific locale first, the built-in English last.
+    let mut chain: Vec<(&str, &ErrorCatalog)> = Vec::new();
+    let language = locale.split(['-', '_']).next().unwrap_or(locale);
+    for candidate in [locale, language, DEFAULT_LOCALE] {
+        if let Some((name, catalog)) = catalogs.get_key_value(candidate) {
+            if !chain.iter().any(|(n, _)| *n == name) {
+                chain.push((name, catalog));
+            }
+        }
+    }
+    chain.push((DEFAULT_LOCALE, &builtin));
+
+    let variant = variant(code, message, details);
+    let mut keys = Vec::new();
+    if let Some(v) = &variant {
+        keys.push(format!("{}.{}", code, v));
+    }
+    keys.push(code.to_string());
+
+    // First set value for `field`, trying each key in each locale.
+    let find = |field: fn(&ErrorText) -> Option<&String>| -> Option<(&str, String)> {
+        keys.iter().find_map(|key| {
+            chain.iter().find_map(|(locale, catalog)| {
+                let text = catalog.errors.get(key)?;
+                field(text).map(|value| (*locale, value.clone()))
+            })
+        })
+    };
+    let (title_locale, title) = find(|t| t.title.as_ref()).unwrap_or_default();
+    let (_, description) = find(|t| t.description.as_ref()).unwrap_or_default();
+    let action_ids = keys
+        .iter()
+        .find_map(|key| {
+            chain
+                .iter()
+                .find_map(|(_, catalog)| catalog.errors.get(key)?.actions.clone())
+        })
+        .unwrap_or_default();
+    let actions = action_ids
+        .into_iter()
+        .map(|id| SuggestedAction {
+            label: chain
+                .iter()
+                .find_map(|(_, catalog)| catalog.actions.get(&id).cloned())
+                .unwrap_or_else(|| id.clone()),
+            id,
+        })
+        .collect();
+
+    Explanation {
+        code,
+        variant,
+        title: fill(&title, message, details),
+        description: fill(&description, message, details),
+        actions,
+        locale: title_locale.to_string(),
+        technical: message.to_string(),
+    }
+}
+
+/// Replace `{message}` and `{<detail key>}` in `template`.
+fn fill(template: &str, message: &str, details: &Value) -> String {
+    let mut out = template.replace("{message}", message);
+    if let Some(map) = details.as_object() {
+        for (key, value) in map {
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                _ => continue,
+            };
+            out = out.replace(&format!("{{{}}}", key), &text);
+        }
+    }
+    out
+}
+
+/// `explain_error` – user-facing text for an error.
+///
+/// Args: `{ "code": "IO_ERROR", "message"?: "...", "details"?: {...},
+/// "locale"?: "de-AT" }`, or `{ "error": <a result's error object>,
+/// "locale"?: ... }`.
+/// Returns: `{ "code", "variant"?, "title", "description", "actions":
+/// [{"id", "label"}], "locale", "technical" }`
+pub fn cmd_explain_error(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let source = args.get("error").unwrap_or(&args);
+    let code: ErrorCode = source
+        .get("code")
+        .cloned()
+        .ok_or_else(|| CommandError::InvalidInput("missing 'code' field".into()))
+        .and_then(|v| {
+            serde_json::from_value(v)
+                .map_err(|e| CommandError::InvalidInput(format!("invalid 'code': {}", e)))
+        })?;
+    let message = source.get("message").and_then(Value::as_str).unwrap_or("");
+    let details = source.get("details").unwrap_or(&Value::Null);
+    let locale = args
+        .get("locale")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_LOCALE);
+    let explanation = explain(code, message, details, locale, &ctx.error_catalogs);
+    serde_json::to_value(explanation).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_code_has_builtin_text() {
+        let catalog = builtin();
+        for (_, _, _, actions) in BUILTIN {
+            for action in *actions {
+                assert!(catalog.actions.contains_key(*action), "{}", action);
+            }
+        }
+        for code in [
+            ErrorCode::InvalidInput,
+            ErrorCode::Unsupported,
+            ErrorCode::Unimplemented,
+            ErrorCode::DependencyMissing,
+            ErrorCode::PermissionDenied,
+            ErrorCode::NetworkError,
+            ErrorCode::IoError,
+            ErrorCode::Timeout,
+            ErrorCode::ResourceExhausted,
+            ErrorCode::ExternalInterference,
+            ErrorCode::InternalError,
+            ErrorCode::UserSkipped,
+        ] {
+            assert!(catalog.errors.contains_key(&code.to_string()), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_variant_from_message_or_details() {
+        let e = explain(
+            ErrorCode::IoError,
+            "io: No such file or directory (os error 2)",
+            &Value::Null,
+            "en",
+            &BTreeMap::new(),
+        );
+        assert_eq!(e.variant.as_deref(), Some("not_found"));
+        assert_eq!(e.title, "File not found");
+        assert_eq!(e.actions[0].label, "Choose another file");
+
+        let e = explain(
+            ErrorCode::IoError,
+            "boom",
+            &serde_json::json!({ "kind": "disk_full" }),
+            "en",
+            &BTreeMap::new(),
+        );
+        assert_eq!(e.title, "Disk is full");
+
+        let e = explain(
+            ErrorCode::IoError,
+            "boom",
+            &Value::Null,
+            "en",
+            &BTreeMap::new(),
+        );
+        assert_eq!(e.variant, None);
+        assert_eq!(e.description, "A file operation failed: boom");
+    }
+
+    #[test]
+    fn test_translation_falls_back_per_field() {
+        let de: ErrorCatalog = serde_yaml::from_str(
+            "errors:\n  IO_ERROR.not_found:\n    title: Datei nicht gefunden\n    description: '{path} fehlt.'\nactions:\n  choose_other_file: Andere Datei wählen\n",
+        )
+        .unwrap();
+        let catalogs = BTreeMap::from([("de".to_string(), de)]);
+        let details = serde_json::json!({ "kind": "not_found", "path": "/a.txt" });
+
+        let e = explain(ErrorCode::IoError, "x", &details, "de-AT", &catalogs);
+        assert_eq!(e.locale, "de");
+        assert_eq!(e.title, "Datei nicht gefunden");
+        assert_eq!(e.description, "/a.txt fehlt.");
+        assert_eq!(e.actions[0].label, "Andere Datei wählen");
+
+        // No German text for timeouts: English, including action labels
+        // the translation does not cover.
+        let e = explain(ErrorCode::Timeout, "x", &Value::Null, "de", &catalogs);
+        assert_eq!(e.locale, "en");
+        assert_eq!(e.actions[0].label, "Try again");
+    }
+
+    #[test]
+    fn test_explain_error_command_accepts_result_errors() {
+        let ctx = AppContext::default_headless();
+        let r = crate::commands::CommandRegistry::new().execute(
+            "read_file",
+            serde_json::json!({ "path": "/definitely/not/here" }),
+            &ctx,
+        );
+        let out = cmd_explain_error(serde_json::json!({ "error": r.error }), &ctx).unwrap();
+        assert_eq!(out["variant"], "not_found");
+        assert!(cmd_explain_error(serde_json::json!({ "code": "NOPE" }), &ctx).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! First-run checks – the startup sequence the GUI runs until it passes
+//! once, and `appctl first-run` simulates headlessly.
+//!
+//! Each check is a probe; a failing `required` check fails the run, and
+//! its `remediation` link is reported so the UI can point the user at a
+//! fix. Progress goes out on the event bus, which the Tauri wrapper
+//! forwards to every window (a splash window included):
+//!
+//! - `first_run:checking` – `{index, total, probe, title}` before a check
+//! - `first_run:checked` – the [`FirstRunCheckResult`] plus `index`/`total`
+//! - `first_run:finished` – the [`FirstRunReport`]
+//!
+//! The checks come from `first_run:` in the app config, or a YAML list in
+//! `$APP__FIRST_RUN_CHECKS`, else [`builtin`]:
+//!
+//! ```yaml
+//! - probe: filesystem
+//!   title: App data folder is writable
+//!   remediation: https://example.com/help/permissions
+//! - probe: display
+//!   required: false
+//! ```
+//!
+//! A passing run is recorded in `<data_dir>/first-run.json`, along with
+//! the last report either way, so a window that opens late can catch up.
+
+use crate::context::AppContext;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// YAML file with the checks.
+pub const FIRST_RUN_CHECKS_ENV: &str = "APP__FIRST_RUN_CHECKS";
+
+/// File under [`AppContext::data_dir`] holding the last report.
+pub const STATE_FILE: &str = "first-run.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FirstRunCheck {
+    pub probe: String,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub args: serde_json::Value,
+    /// Shown instead of the probe name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Whether a failure fails the run (default true).
+    #[serde(default = "default_required")]
+    pub required: bool,
+    /// Where to send the user when the check fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// The writable filesystem is required; a display is checked but optional,
+/// so the headless simulation passes.
+pub fn builtin() -> Vec<FirstRunCheck> {
+    vec![
+        FirstRunCheck {
+            probe: "filesystem".into(),
+            args: serde_json::Value::Null,
+            title: Some("App data folder is writable".into()),
+            required: true,
+            remediation: None,
+        },
+        FirstRunCheck {
+            probe: "display".into(),
+            args: serde_json::Value::Null,
+            title: Some("Display is available".into()),
+            required: false,
+            remediation: None,
+        },
+    ]
+}
+
+/// Checks from `$APP__FIRST_RUN_CHECKS`, else `checks`; an unreadable
+/// file is logged and ignored.
+pub fn with_env_override(checks: Vec<FirstRunCheck>) -> Vec<FirstRunCheck> {
+    let Some(path) = std::env::var_os(FIRST_RUN_CHECKS_ENV).filter(|v| !v.is_empty()) else {
+        return checks;
+    };
+    match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|yaml| load(&yaml))
+    {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            tracing::warn!("ignoring {}: {}", FIRST_RUN_CHECKS_ENV, e);
+            checks
+        }
+    }
+}
+
+pub fn load(yaml: &str) -> Result<Vec<FirstRunCheck>, String> {
+    serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse first-run checks: {}", e))
+}
+
+pub fn state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATE_FILE)
+}
+
+/// The last recorded report, if any.
+pub fn last_report(ctx: &AppContext) -> Option<FirstRunReport> {
+    let bytes = ctx.fs().read_file(&state_path(&ctx.data_dir)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Whether a run has passed on this machine.
+pub fn completed(ctx: &AppContext) -> bool {
+    last_report(ctx).is_some_and(|r| r.passed)
+}
+
+/// Run `ctx.first_run_checks` in order, publish progress, and record the
+/// report. Fails if a required check did not pass or skip; `data` is the
+/// [`FirstRunReport`].
+pub async fn run_checks(ctx: &AppContext, probes: &ProbeRegistry) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    let total = ctx.first_run_checks.len();
+    let mut checks = Vec::new();
+    for (index, check) in ctx.first_run_checks.iter().enumerate() {
+        let title = check.title.clone().unwrap_or_else(|| check.probe.clone());
+        ctx.events().emit(
+            &run_id,
+            "first_run:checking",
+            serde_json::json!({
+                "index": index,
+                "total": total,
+                "probe": check.probe,
+                "title": title,
+            }),
+        );
+        let result = probes.run(&check.probe, check.args.clone(), ctx).await;
+        let failed = matches!(result.status, Status::Fail | Status::Error);
+        let outcome = FirstRunCheckResult {
+            probe: check.probe.clone(),
+            title,
+            required: check.required,
+            status: result.status,
+            message: result.error.map(|e| e.message),
+            remediation: check.remediation.clone().filter(|_| failed),
+        };
+        let mut payload = serde_json::to_value(&outcome).unwrap_or_default();
+        payload["index"] = index.into();
+        payload["total"] = total.into();
+        ctx.events().emit(&run_id, "first_run:checked", payload);
+        checks.push(outcome);
+    }
+
+    let failed: Vec<String> = checks
+        .iter()
+        .filter(|c| c.required && matches!(c.status, Status::Fail | Status::Error))
+        .map(|c| c.probe.clone())
+        .collect();
+    let report = FirstRunReport {
+        passed: failed.is_empty(),
+        checks,
+    };
+    let json = serde_json::to_vec_pretty(&report).unwrap_or_default();
+    if let Err(e) = ctx
+        .fs()
+        .write_file_atomic(&state_path(&ctx.data_dir), &json)
+    {
+        tracing::warn!("cannot record first-run report: {}", e);
+    }
+    ctx.events().emit(
+        &run_id,
+        "first_run:finished",
+        serde_json::to_value(&report).unwrap_or_default(),
+    );
+
+    let mut r = if failed.is_empty() {
+        result_ok("first-run", "checks", &run_id, start.elapsed_ms())
+    } else {
+        result_err(
+            "first-run",
+            "checks",
+            &run_id,
+            start.elapsed_ms(),
+            ErrorCode::DependencyMissing,
+            format!("required checks failed: {}", failed.join(", ")),
+        )
+    };
+    if !failed.is_empty() {
+        r.status = Status::Fail;
+    }
+    r.data = serde_json::to_value(&report).ok();
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::ProbeInfo;
+    use std::future::ready;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_load_defaults_to_required() {
+        let checks = load("- probe: filesystem\n- {probe: display, required: false}\n").unwrap();
+        assert!(checks[0].required);
+        assert!(!checks[1].required);
+        assert!(load("- {probe: x, remedy: y}\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_checks_reports_remediation_and_records_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut probes = ProbeRegistry::new();
+        probes.register(ProbeInfo::new("broken", "Always fails"), |p| {
+            Box::pin(ready(result_err(
+                "probe",
+                "broken",
+                p.run_id,
+                0,
+                ErrorCode::PermissionDenied,
+                "no access",
+            )))
+        });
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.path().to_path_buf();
+        ctx.first_run_checks = load(
+            "- {probe: filesystem, remediation: 'https://fix/fs'}\n- {probe: broken, remediation: 'https://fix/broken'}\n",
+        )
+        .unwrap();
+        let topics = Arc::new(Mutex::new(Vec::new()));
+        let seen = topics.clone();
+        ctx.events()
+            .subscribe(move |ev| seen.lock().unwrap().push(ev.topic.clone()));
+
+        assert!(!completed(&ctx));
+        let r = run_checks(&ctx, &probes).await;
+        assert_eq!(r.status, Status::Fail);
+        let report = last_report(&ctx).unwrap();
+        assert!(!report.passed);
+        assert_eq!(report.checks[0].remediation, None);
+        assert_eq!(
+            report.checks[1].remediation.as_deref(),
+            Some("https://fix/broken")
+        );
+        assert_eq!(report.checks[1].message.as_deref(), Some("no access"));
+        assert_eq!(
+            topics
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|t| t.starts_with("first_run:"))
+                .count(),
+            5
+        );
+
+        ctx.first_run_checks[1].required = false;
+        assert_eq!(run_checks(&ctx, &probes).await.status, Status::Pass);
+        assert!(completed(&ctx));
+    }
+}
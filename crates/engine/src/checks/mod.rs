@@ -0,0 +1,13 @@
+//! Environment checks and their reporting: doctor, compatibility tiers,
+//! readiness and first-run gates, probe suites, update checks, and the
+//! diagnostics bundle.
+
+pub mod compat;
+pub mod diagnostics;
+pub mod doctor;
+pub mod endpoints;
+pub mod explain;
+pub mod first_run;
+pub mod readiness;
+pub mod suites;
+pub mod updates;
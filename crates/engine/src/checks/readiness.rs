@@ -0,0 +1,220 @@
+//! Readiness gating – the sequence the GUI runs at every launch before it
+//! shows the main window, and `appctl ready-check` runs headlessly:
+//!
+//! 1. `config` – the data directory can be created and the menu spec from
+//!    the config is valid.
+//! 2. `migrations` – pending app data migrations have run.
+//! 3. `probes` – the `required` first-run checks (see [`crate::first_run`])
+//!    pass; optional ones are left to the first-run sequence.
+//!
+//! Stages run in order and stop at the first failure. Progress goes out on
+//! the event bus for a splash window:
+//!
+//! - `ready:checking` – `{index, total, stage, title}` before a stage
+//! - `ready:checked` – the [`ReadyStageResult`] plus `index`/`total`
+//! - `ready:finished` – the [`ReadyReport`]
+
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+
+/// Stage names and titles, in order.
+pub const STAGES: &[(&str, &str)] = &[
+    ("config", "Loading settings"),
+    ("migrations", "Updating app data"),
+    ("probes", "Checking system requirements"),
+];
+
+/// Run the stages, publish progress, and report. Fails at the first stage
+/// that fails; `data` is the [`ReadyReport`].
+pub async fn run_ready_check(
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    let total = STAGES.len();
+    let mut stages = Vec::new();
+    for (index, (stage, title)) in STAGES.iter().enumerate() {
+        ctx.events().emit(
+            &run_id,
+            "ready:checking",
+            serde_json::json!({ "index": index, "total": total, "stage": stage, "title": title }),
+        );
+        let started = ctx.stopwatch();
+        let outcome = match *stage {
+            "config" => check_config(ctx, registry),
+            "migrations" => run_migrations(ctx),
+            _ => check_probes(ctx, probes).await,
+        };
+        let result = ReadyStageResult {
+            stage: stage.to_string(),
+            title: title.to_string(),
+            status: if outcome.is_ok() {
+                Status::Pass
+            } else {
+                Status::Fail
+            },
+            message: outcome.unwrap_or_else(Some),
+            duration_ms: started.elapsed_ms(),
+        };
+        let mut payload = serde_json::to_value(&result).unwrap_or_default();
+        payload["index"] = index.into();
+        payload["total"] = total.into();
+        ctx.events().emit(&run_id, "ready:checked", payload);
+        let failed = result.status == Status::Fail;
+        stages.push(result);
+        if failed {
+            break;
+        }
+    }
+
+    let failed = stages.iter().find(|s| s.status == Status::Fail);
+    let report = ReadyReport {
+        ready: failed.is_none(),
+        stages: stages.clone(),
+    };
+    ctx.events().emit(
+        &run_id,
+        "ready:finished",
+        serde_json::to_value(&report).unwrap_or_default(),
+    );
+    let mut r = match failed {
+        None => result_ok("ready-check", "engine", &run_id, start.elapsed_ms()),
+        Some(stage) => {
+            let mut r = result_err(
+                "ready-check",
+                "engine",
+                &run_id,
+                start.elapsed_ms(),
+                ErrorCode::DependencyMissing,
+                format!(
+                    "{} failed: {}",
+                    stage.stage,
+                    stage.message.as_deref().unwrap_or_default()
+                ),
+            );
+            r.status = Status::Fail;
+            r
+        }
+    };
+    r.data = serde_json::to_value(&report).ok();
+    r
+}
+
+/// `Ok` with an optional note, or why the stage failed.
+type StageOutcome = Result<Option<String>, String>;
+
+fn check_config(ctx: &AppContext, registry: &CommandRegistry) -> StageOutcome {
+    ctx.fs()
+        .create_dir_all(&ctx.data_dir)
+        .map_err(|e| format!("cannot create {}: {}", ctx.data_dir.display(), e))?;
+    let problems = crate::menu::validate(&ctx.menu, &registry.list());
+    if let Some(p) = problems.first() {
+        return Err(format!(
+            "menu: {}: {} ({} problem(s))",
+            p.path,
+            p.message,
+            problems.len()
+        ));
+    }
+    Ok(None)
+}
+
+fn run_migrations(ctx: &AppContext) -> StageOutcome {
+    let report = crate::migrations::run(ctx, crate::migrations::MIGRATIONS, ctx.dry_run)
+        .map_err(|e| e.to_string())?;
+    Ok(match report.migrations.len() {
+        0 => None,
+        n => Some(format!(
+            "{} migration(s), version {} to {}",
+            n, report.from_version, report.to_version
+        )),
+    })
+}
+
+async fn check_probes(ctx: &AppContext, probes: &ProbeRegistry) -> StageOutcome {
+    let mut failed = Vec::new();
+    for check in ctx.first_run_checks.iter().filter(|c| c.required) {
+        let result = probes.run(&check.probe, check.args.clone(), ctx).await;
+        if matches!(result.status, Status::Fail | Status::Error) {
+            let title = check.title.as_deref().unwrap_or(&check.probe);
+            failed.push(match result.error {
+                Some(e) => format!("{} ({})", title, e.message),
+                None => title.to_string(),
+            });
+        }
+    }
+    if failed.is_empty() {
+        Ok(None)
+    } else {
+        Err(failed.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::ProbeInfo;
+    use std::future::ready;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_ready_check_runs_stages_in_order_and_stops_at_a_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.path().join("data");
+        let registry = CommandRegistry::new();
+        let mut probes = ProbeRegistry::new();
+        probes.register(ProbeInfo::new("broken", "Always fails"), |p| {
+            Box::pin(ready(result_err(
+                "probe",
+                "broken",
+                p.run_id,
+                0,
+                ErrorCode::PermissionDenied,
+                "no access",
+            )))
+        });
+        let topics = Arc::new(Mutex::new(Vec::new()));
+        let seen = topics.clone();
+        ctx.events()
+            .subscribe(move |ev| seen.lock().unwrap().push(ev.topic.clone()));
+
+        ctx.first_run_checks = crate::first_run::load("- probe: filesystem\n").unwrap();
+        let r = run_ready_check(&ctx, &registry, &probes).await;
+        assert_eq!(r.status, Status::Pass);
+        assert!(ctx.data_dir.is_dir());
+        assert_eq!(
+            topics
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|t| t.starts_with("ready:"))
+                .count(),
+            2 * STAGES.len() + 1
+        );
+
+        ctx.first_run_checks =
+            crate::first_run::load("- {probe: broken, title: Disk access}\n").unwrap();
+        let r = run_ready_check(&ctx, &registry, &probes).await;
+        assert_eq!(r.status, Status::Fail);
+        let report: ReadyReport = serde_json::from_value(r.data.unwrap()).unwrap();
+        assert!(!report.ready);
+        assert_eq!(
+            report.stages[2].message.as_deref(),
+            Some("Disk access (no access)")
+        );
+
+        ctx.menu = crate::menu::from_config_yaml(
+            "menu:\n  - label: File\n    items:\n      - label: Nope\n        command: nope\n",
+        )
+        .unwrap();
+        let r = run_ready_check(&ctx, &registry, &probes).await;
+        let report: ReadyReport = serde_json::from_value(r.data.unwrap()).unwrap();
+        assert_eq!(report.stages.len(), 1);
+        assert_eq!(report.stages[0].status, Status::Fail);
+    }
+}
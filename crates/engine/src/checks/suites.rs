@@ -0,0 +1,209 @@
+//! Probe suites: named sets of probes run together, such as `appctl probe
+//! all` or `appctl probe desktop`.
+//!
+//! `all`, `desktop`, and `headless-ci` are built in. `probe_suites:` in the
+//! app config, or a YAML file named by `$APP__PROBE_SUITES`, adds suites
+//! and replaces built-in ones of the same name:
+//!
+//! ```yaml
+//! kiosk:
+//!   probes: [filesystem, display, printing]
+//!   critical: [display]
+//!   args:
+//!     printing: {}
+//! ```
+//!
+//! A suite only fails when one of its `critical` probes fails or errors;
+//! the others are reported but do not change the outcome.
+
+use crate::context::AppContext;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// YAML file with suites, keyed by name.
+pub const PROBE_SUITES_ENV: &str = "APP__PROBE_SUITES";
+
+/// One named set of probes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProbeSuite {
+    /// Probes in run order; empty means every registered probe.
+    pub probes: Vec<String>,
+    /// Probes whose failure fails the suite.
+    pub critical: Vec<String>,
+    /// Arguments per probe name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, serde_json::Value>,
+}
+
+impl ProbeSuite {
+    fn of(probes: &[&str], critical: &[&str]) -> Self {
+        Self {
+            probes: probes.iter().map(|p| p.to_string()).collect(),
+            critical: critical.iter().map(|p| p.to_string()).collect(),
+            args: BTreeMap::new(),
+        }
+    }
+}
+
+/// The built-in suites.
+pub fn builtin() -> BTreeMap<String, ProbeSuite> {
+    BTreeMap::from([
+        ("all".to_string(), ProbeSuite::default()),
+        (
+            "desktop".to_string(),
+            ProbeSuite::of(
+                &[
+                    "filesystem",
+                    "network",
+                    "clipboard",
+                    "autostart",
+                    "session-events",
+                    "display",
+                    "portals",
+                    "printing",
+                    "media-devices",
+                ],
+                &["filesystem", "clipboard", "display"],
+            ),
+        ),
+        (
+            "headless-ci".to_string(),
+            ProbeSuite::of(
+                &["filesystem", "network", "interfaces", "llm"],
+                &["filesystem"],
+            ),
+        ),
+    ])
+}
+
+/// Built-in suites plus those in `$APP__PROBE_SUITES`.
+pub fn from_env() -> BTreeMap<String, ProbeSuite> {
+    with_env_overrides(builtin())
+}
+
+/// Add or replace suites from `$APP__PROBE_SUITES`, if set and valid.
+pub fn with_env_overrides(
+    mut suites: BTreeMap<String, ProbeSuite>,
+) -> BTreeMap<String, ProbeSuite> {
+    let Some(path) = std::env::var_os(PROBE_SUITES_ENV).filter(|v| !v.is_empty()) else {
+        return suites;
+    };
+    match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|yaml| load(&yaml))
+    {
+        Ok(loaded) => suites.extend(loaded),
+        Err(e) => tracing::warn!("ignoring {}: {}", PROBE_SUITES_ENV, e),
+    }
+    suites
+}
+
+/// Parse suites keyed by name.
+pub fn load(yaml: &str) -> Result<BTreeMap<String, ProbeSuite>, String> {
+    serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse probe suites: {}", e))
+}
+
+/// Run the suite `name` from `ctx.probe_suites`, or `None` if there is none.
+pub async fn run_suite(
+    name: &str,
+    ctx: &AppContext,
+    probes: &ProbeRegistry,
+) -> Option<SuiteResult> {
+    let suite = ctx.probe_suites.get(name)?;
+    let names: Vec<String> = if suite.probes.is_empty() {
+        probes.list().iter().map(|p| p.name.clone()).collect()
+    } else {
+        suite.probes.clone()
+    };
+
+    let mut summary = Vec::new();
+    let mut step_results = Vec::new();
+    let mut critical_failed = Vec::new();
+    for probe in names {
+        let args = suite.args.get(&probe).cloned().unwrap_or_default();
+        let result = probes.run(&probe, args, ctx).await;
+        let critical = suite.critical.contains(&probe);
+        if critical && matches!(result.status, Status::Fail | Status::Error) {
+            critical_failed.push(probe.clone());
+        }
+        summary.push(SuiteProbeStatus {
+            probe,
+            status: result.status,
+            critical,
+        });
+        step_results.push(result);
+    }
+
+    Some(SuiteResult {
+        name: name.to_string(),
+        overall_status: if critical_failed.is_empty() {
+            Status::Pass
+        } else {
+            Status::Fail
+        },
+        critical_failed,
+        summary,
+        step_results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::ProbeInfo;
+    use std::future::ready;
+
+    #[test]
+    fn test_load_suites() {
+        let suites = load(
+            "kiosk:\n  probes: [filesystem, display]\n  critical: [display]\n  args:\n    display: {}\n",
+        )
+        .unwrap();
+        assert_eq!(suites["kiosk"].probes, ["filesystem", "display"]);
+        assert_eq!(suites["kiosk"].critical, ["display"]);
+        assert!(load("kiosk:\n  probez: []\n").is_err());
+        assert!(builtin()["all"].probes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_critical_failures_fail_the_suite() {
+        let mut registry = ProbeRegistry::new();
+        registry.register(ProbeInfo::new("broken", "Always errors"), |p| {
+            Box::pin(ready(result_err(
+                "probe",
+                "broken",
+                p.run_id,
+                0,
+                ErrorCode::InternalError,
+                "boom",
+            )))
+        });
+        let mut ctx = AppContext::default_headless();
+        ctx.probe_suites.insert(
+            "smoke".into(),
+            ProbeSuite::of(&["filesystem", "broken"], &["filesystem"]),
+        );
+
+        let r = run_suite("smoke", &ctx, &registry).await.unwrap();
+        assert_eq!(r.overall_status, Status::Pass);
+        assert_eq!(r.summary[1].status, Status::Error);
+        assert!(!r.summary[1].critical);
+
+        ctx.probe_suites
+            .get_mut("smoke")
+            .unwrap()
+            .critical
+            .push("broken".into());
+        let r = run_suite("smoke", &ctx, &registry).await.unwrap();
+        assert_eq!(r.overall_status, Status::Fail);
+        assert_eq!(r.critical_failed, ["broken"]);
+
+        ctx.offline = true;
+        let all = run_suite("all", &ctx, &registry).await.unwrap();
+        assert_eq!(all.step_results.len(), registry.list().len());
+        assert!(run_suite("nope", &ctx, &registry).await.is_none());
+    }
+}
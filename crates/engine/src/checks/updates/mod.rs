@@ -0,0 +1,312 @@
+//! Update checks against a release manifest.
+//!
+//! The manifest uses the Tauri updater's static JSON format (`latest.json`),
+//! so the same file drives the GUI's updater plugin and these checks:
+//!
+//! ```json
+//! {
+//!   "version": "1.2.0",
+//!   "notes": "…",
+//!   "pub_date": "2026-01-01T00:00:00Z",
+//!   "platforms": {
+//!     "linux-x86_64": { "url": "https://…/app.AppImage", "signature": "<base64 minisign>" }
+//!   }
+//! }
+//! ```
+//!
+//! Everything goes through [`AppContext::network`] and [`AppContext::fs`], so
+//! `appctl update-check` can exercise the full check (and a verified
+//! download) in a VM without installing anything.
+
+mod run;
+
+pub use run::{run_check, run_download};
+
+use crate::context::AppContext;
+use crate::traits::{CapError, HttpRequest};
+use crate::types::*;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Environment variable overriding the manifest URL.
+pub const MANIFEST_URL_ENV: &str = "APP__UPDATE_MANIFEST_URL";
+/// Environment variable holding the minisign public key (base64, as in
+/// `tauri.conf.json`).
+pub const PUBKEY_ENV: &str = "APP__UPDATE_PUBKEY";
+
+/// Event topic published after a verified download.
+pub const TOPIC_DOWNLOADED: &str = "update:downloaded";
+
+const MANIFEST_TIMEOUT_MS: u64 = 15_000;
+const DOWNLOAD_TIMEOUT_MS: u64 = 600_000;
+
+/// Where to look for updates and how to trust them. The Tauri wrapper fills
+/// this from the updater plugin config and the app's package version.
+#[derive(Debug, Clone)]
+pub struct UpdateSettings {
+    pub manifest_url: Option<String>,
+    pub pubkey: Option<String>,
+    pub current_version: String,
+    /// Whether `manifest_url`/`pubkey` args may replace the configured
+    /// ones. Only `appctl update-check` sets this: webview and daemon
+    /// callers must not choose where updates come from or which key signs
+    /// them.
+    pub allow_overrides: bool,
+}
+
+impl UpdateSettings {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            manifest_url: var(MANIFEST_URL_ENV),
+            pubkey: var(PUBKEY_ENV),
+            current_version: env!("CARGO_PKG_VERSION").to_string(),
+            allow_overrides: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub pub_date: Option<String>,
+    #[serde(default)]
+    pub platforms: BTreeMap<String, PlatformRelease>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlatformRelease {
+    pub url: String,
+    pub signature: String,
+}
+
+/// Outcome of a check, returned as the command's `data`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCheck {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub platform: String,
+    /// Download URL for this platform, if the manifest lists one.
+    pub url: Option<String>,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateError {
+    #[error("{0}")]
+    Config(String),
+    #[error("invalid release manifest: {0}")]
+    Manifest(String),
+    #[error("signature verification failed: {0}")]
+    Signature(String),
+    #[error(transparent)]
+    Cap(#[from] CapError),
+}
+
+impl UpdateError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            UpdateError::Config(_) | UpdateError::Manifest(_) => ErrorCode::InvalidInput,
+            UpdateError::Signature(_) => ErrorCode::ExternalInterference,
+            UpdateError::Cap(e) => e.error_code(),
+        }
+    }
+}
+
+/// Platform key used in the manifest, matching the Tauri updater
+/// (`darwin-aarch64`, `linux-x86_64`, `windows-x86_64`, …).
+pub fn platform_key() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        other => other,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86" => "i686",
+        "arm" => "armv7",
+        other => other,
+    };
+    format!("{}-{}", os, arch)
+}
+
+fn parse_version(v: &str) -> Result<semver::Version, UpdateError> {
+    semver::Version::parse(v.trim().trim_start_matches('v'))
+        .map_err(|e| UpdateError::Manifest(format!("'{}' is not a semver version: {}", v, e)))
+}
+
+/// Whether `candidate` is a strictly newer semver version than `current`.
+pub fn is_newer(current: &str, candidate: &str) -> Result<bool, UpdateError> {
+    Ok(parse_version(candidate)? > parse_version(current)?)
+}
+
+fn base64_text(what: &str, b64: &str) -> Result<String, UpdateError> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(b64.trim())
+        .map_err(|e| UpdateError::Signature(format!("{} is not valid base64: {}", what, e)))?;
+    String::from_utf8(bytes)
+        .map_err(|_| UpdateError::Signature(format!("{} is not valid UTF-8", what)))
+}
+
+/// Verify `data` against a base64 minisign signature with a base64 minisign
+/// public key – the encodings the Tauri bundler and updater use.
+pub fn verify_signature(
+    pubkey_b64: &str,
+    signature_b64: &str,
+    data: &[u8],
+) -> Result<(), UpdateError> {
+    let pubkey = minisign_verify::PublicKey::decode(&base64_text("public key", pubkey_b64)?)
+        .map_err(|e| UpdateError::Signature(format!("bad public key: {}", e)))?;
+    let signature = minisign_verify::Signature::decode(&base64_text("signature", signature_b64)?)
+        .map_err(|e| UpdateError::Signature(format!("bad signature: {}", e)))?;
+    pubkey
+        .verify(data, &signature, true)
+        .map_err(|e| UpdateError::Signature(e.to_string()))
+}
+
+/// Apply per-call overrides (`manifest_url`, `current_version`, `pubkey`)
+/// on top of the context's settings. `manifest_url` and `pubkey` are
+/// refused unless the settings allow overrides.
+fn settings_from_args(args: &Value, ctx: &AppContext) -> Result<UpdateSettings, UpdateError> {
+    let mut settings = ctx.update_settings.clone();
+    let string = |key: &str| -> Result<Option<String>, UpdateError> {
+        match args.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => v
+                .as_str()
+                .map(|s| Some(s.to_string()))
+                .ok_or_else(|| UpdateError::Config(format!("'{}' must be a string", key))),
+        }
+    };
+    if !settings.allow_overrides {
+        if let Some(key) = ["manifest_url", "pubkey"]
+            .into_iter()
+            .find(|k| !matches!(args.get(*k), None | Some(Value::Null)))
+        {
+            return Err(CapError::PermissionDenied(format!(
+                "'{}' can only be overridden from appctl update-check",
+                key
+            ))
+            .into());
+        }
+    }
+    if let Some(url) = string("manifest_url")? {
+        settings.manifest_url = Some(url);
+    }
+    if let Some(pubkey) = string("pubkey")? {
+        settings.pubkey = Some(pubkey);
+    }
+    if let Some(version) = string("current_version")? {
+        settings.current_version = version;
+    }
+    Ok(settings)
+}
+
+async fn fetch_manifest(
+    ctx: &AppContext,
+    settings: &UpdateSettings,
+) -> Result<ReleaseManifest, UpdateError> {
+    let url = settings.manifest_url.as_deref().ok_or_else(|| {
+        UpdateError::Config(format!(
+            "no release manifest configured (pass 'manifest_url' or set {})",
+            MANIFEST_URL_ENV
+        ))
+    })?;
+    let resp = ctx
+        .network()
+        .send(HttpRequest::get(url).timeout_ms(MANIFEST_TIMEOUT_MS))
+        .await?;
+    if !resp.is_success() {
+        return Err(
+            CapError::Network(format!("manifest request returned HTTP {}", resp.status)).into(),
+        );
+    }
+    serde_json::from_slice(&resp.body).map_err(|e| UpdateError::Manifest(e.to_string()))
+}
+
+/// Fetch the manifest and compare it with the current version.
+pub async fn check(
+    ctx: &AppContext,
+    settings: &UpdateSettings,
+) -> Result<(UpdateCheck, ReleaseManifest), UpdateError> {
+    let manifest = fetch_manifest(ctx, settings).await?;
+    let platform = platform_key();
+    let check = UpdateCheck {
+        update_available: is_newer(&settings.current_version, &manifest.version)?,
+        current_version: settings.current_version.clone(),
+        latest_version: manifest.version.clone(),
+        url: manifest.platforms.get(&platform).map(|p| p.url.clone()),
+        platform,
+        notes: manifest.notes.clone(),
+        pub_date: manifest.pub_date.clone(),
+    };
+    Ok((check, manifest))
+}
+
+/// Download this platform's artifact from `manifest` and verify its
+/// signature. Returns the verified bytes; nothing is written or installed.
+pub async fn download_verified(
+    ctx: &AppContext,
+    settings: &UpdateSettings,
+    manifest: &ReleaseManifest,
+) -> Result<Vec<u8>, UpdateError> {
+    let pubkey = settings.pubkey.as_deref().ok_or_else(|| {
+        UpdateError::Config(format!(
+            "no update public key configured (pass 'pubkey' or set {})",
+            PUBKEY_ENV
+        ))
+    })?;
+    let platform = platform_key();
+    let release = manifest
+        .platforms
+        .get(&platform)
+        .ok_or_else(|| UpdateError::Manifest(format!("no release for platform {}", platform)))?;
+    let resp = ctx
+        .network()
+        .send(HttpRequest::get(&release.url).timeout_ms(DOWNLOAD_TIMEOUT_MS))
+        .await?;
+    if !resp.is_success() {
+        return Err(CapError::Network(format!("download returned HTTP {}", resp.status)).into());
+    }
+    verify_signature(pubkey, &release.signature, &resp.body)?;
+    Ok(resp.body)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Key pair and signature of b"test" from the minisign-verify test suite,
+    // wrapped in base64 the way `tauri signer` emits them.
+    pub(crate) const PUBKEY: &str = "untrusted comment: minisign public key E7620F1842B4E81F\n\
+        RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3\n";
+    pub(crate) const SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+        RUQf6LRCGA9i559r3g7V1qNyJDApGip8MfqcadIgT9CuhV3EMhHoN1mGTkUidF/z7SrlQgXdy8ofjb7bNJJylDOocrCo8KLzZwo=\n\
+        trusted comment: timestamp:1556193335\tfile:test\n\
+        y/rUw2y8/hOUYjZU71eHp/Wo1KZ40fGy2VJEDl34XMJM+TX48Ss/17u3IvIfbVR1FkZZSNCisQbuQY+bHwhEBg==\n";
+
+    pub(crate) fn b64(s: &str) -> String {
+        base64::engine::general_purpose::STANDARD.encode(s)
+    }
+
+    #[test]
+    fn test_version_comparison() {
+        assert!(is_newer("1.0.0", "v1.0.1").unwrap());
+        assert!(!is_newer("1.0.0", "1.0.0").unwrap());
+        assert!(!is_newer("1.0.0", "1.0.0-beta.1").unwrap());
+        assert!(is_newer("1.0.0-beta.1", "1.0.0").unwrap());
+        assert!(is_newer("1.0.0", "latest").is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        assert!(verify_signature(&b64(PUBKEY), &b64(SIGNATURE), b"test").is_ok());
+        let tampered = verify_signature(&b64(PUBKEY), &b64(SIGNATURE), b"tesT").unwrap_err();
+        assert_eq!(tampered.error_code(), ErrorCode::ExternalInterference);
+    }
+}
@@ -0,0 +1,233 @@
+//! The `update_check` and `update_download` entry points, producing a
+//! [`CommandResult`] like the probes.
+
+use super::{check, download_verified, settings_from_args, UpdateError, TOPIC_DOWNLOADED};
+use crate::clock::Stopwatch;
+use crate::context::AppContext;
+use crate::types::*;
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// `update_check`. Args: `{ "manifest_url"?, "current_version"? }`
+/// (`manifest_url` only when
+/// [`UpdateSettings::allow_overrides`](super::UpdateSettings::allow_overrides)
+/// is set).
+/// Returns an [`UpdateCheck`](super::UpdateCheck) as `data`.
+pub async fn run_check(args: Value, ctx: &AppContext) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    if ctx.offline {
+        return result_offline("update", "check", &run_id);
+    }
+    let start = ctx.stopwatch();
+    let outcome = match settings_from_args(&args, ctx) {
+        Ok(settings) => check(ctx, &settings).await,
+        Err(e) => Err(e),
+    };
+    match outcome {
+        Ok((check, _)) => {
+            let mut r = result_ok("update", "check", &run_id, start.elapsed_ms());
+            r.data = Some(serde_json::to_value(check).unwrap_or_default());
+            r
+        }
+        Err(e) => update_err("check", &run_id, start, e),
+    }
+}
+
+/// `update_download`. Args: `{ "manifest_url"?, "current_version"?, "pubkey"?, "dest_dir"? }`
+/// (`manifest_url`/`pubkey` only when
+/// [`UpdateSettings::allow_overrides`](super::UpdateSettings::allow_overrides)
+/// is set).
+///
+/// Downloads and verifies the artifact for this platform, then writes it to
+/// `dest_dir` (default `<data_dir>/updates`). Skips when already up to date.
+/// Returns the check plus `{ "path": "...", "size_bytes": n }`.
+pub async fn run_download(args: Value, ctx: &AppContext) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    if ctx.offline {
+        return result_offline("update", "download", &run_id);
+    }
+    let start = ctx.stopwatch();
+    let settings = match settings_from_args(&args, ctx) {
+        Ok(s) => s,
+        Err(e) => return update_err("download", &run_id, start, e),
+    };
+    let dest_dir = args
+        .get("dest_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| ctx.data_dir.join("updates"));
+
+    let (check, manifest) = match check(ctx, &settings).await {
+        Ok((check, _)) if !check.update_available => {
+            let mut r = result_skip(
+                "update",
+                "download",
+                &run_id,
+                start.elapsed_ms(),
+                format!("already up to date ({})", check.current_version),
+            );
+            r.data = Some(serde_json::to_value(check).unwrap_or_default());
+            return r;
+        }
+        Ok(v) => v,
+        Err(e) => return update_err("download", &run_id, start, e),
+    };
+    let bytes = match download_verified(ctx, &settings, &manifest).await {
+        Ok(bytes) => bytes,
+        Err(e) => return update_err("download", &run_id, start, e),
+    };
+    let file_name = check
+        .url
+        .as_deref()
+        .and_then(|u| u.split('?').next())
+        .and_then(|u| u.rsplit('/').next())
+        .filter(|n| !n.is_empty())
+        .unwrap_or("update.bin")
+        .to_string();
+    let path = dest_dir.join(file_name);
+    if let Err(e) = ctx.fs().write_file(&path, &bytes) {
+        return update_err("download", &run_id, start, e.into());
+    }
+    ctx.events().emit(
+        &run_id,
+        TOPIC_DOWNLOADED,
+        serde_json::json!({ "version": check.latest_version, "path": path.display().to_string() }),
+    );
+
+    let mut data = serde_json::to_value(&check).unwrap_or_default();
+    data["path"] = path.display().to_string().into();
+    data["size_bytes"] = bytes.len().into();
+    let mut r = result_ok("update", "download", &run_id, start.elapsed_ms());
+    r.data = Some(data);
+    r
+}
+
+fn update_err(target: &str, run_id: &str, start: Stopwatch, err: UpdateError) -> CommandResult {
+    result_err(
+        "update",
+        target,
+        run_id,
+        start.elapsed_ms(),
+        err.error_code(),
+        err.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+    use crate::traits::HttpResponse;
+    use crate::updates::tests::{b64, PUBKEY, SIGNATURE};
+    use crate::updates::{platform_key, UpdateSettings};
+
+    fn manifest(version: &str) -> Value {
+        serde_json::json!({
+            "version": version,
+            "notes": "fixes",
+            "platforms": {
+                platform_key(): { "url": "https://dl.test/app-1.2.0.tar.gz", "signature": b64(SIGNATURE) }
+            }
+        })
+    }
+
+    fn ctx_with(network: MockNetwork, data_dir: &std::path::Path) -> AppContext {
+        let mut ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(network),
+            Box::new(HeadlessClipboard),
+        );
+        ctx.update_settings = UpdateSettings {
+            manifest_url: Some("https://releases.test/latest.json".into()),
+            pubkey: Some(b64(PUBKEY)),
+            current_version: "1.0.0".into(),
+            allow_overrides: false,
+        };
+        ctx.data_dir = data_dir.to_path_buf();
+        ctx
+    }
+
+    #[tokio::test]
+    async fn test_check_and_download() {
+        let tmp = tempfile::tempdir().unwrap();
+        let network = MockNetwork::new()
+            .respond(
+                "https://releases.test/",
+                HttpResponse::from_json(200, &manifest("1.2.0")),
+            )
+            .respond(
+                "https://dl.test/",
+                HttpResponse {
+                    status: 200,
+                    body: b"test".to_vec(),
+                    ..Default::default()
+                },
+            );
+        let ctx = ctx_with(network, tmp.path());
+
+        let r = run_check(serde_json::json!({}), &ctx).await;
+        assert_eq!(r.status, Status::Pass);
+        let data = r.data.unwrap();
+        assert_eq!(data["update_available"], true);
+        assert_eq!(data["latest_version"], "1.2.0");
+
+        let r = run_check(serde_json::json!({ "current_version": "1.2.0" }), &ctx).await;
+        assert_eq!(r.data.unwrap()["update_available"], false);
+
+        let r = run_download(serde_json::json!({}), &ctx).await;
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        let path = tmp.path().join("updates/app-1.2.0.tar.gz");
+        assert_eq!(std::fs::read(path).unwrap(), b"test");
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_bad_signature() {
+        let tmp = tempfile::tempdir().unwrap();
+        let network = MockNetwork::new()
+            .respond(
+                "https://releases.test/",
+                HttpResponse::from_json(200, &manifest("1.2.0")),
+            )
+            .respond(
+                "https://dl.test/",
+                HttpResponse {
+                    status: 200,
+                    body: b"tampered".to_vec(),
+                    ..Default::default()
+                },
+            );
+        let ctx = ctx_with(network, tmp.path());
+
+        let r = run_download(serde_json::json!({}), &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::ExternalInterference);
+        assert!(!tmp.path().join("updates").exists());
+    }
+
+    #[tokio::test]
+    async fn test_overrides_rejected_unless_allowed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let network = MockNetwork::new().respond(
+            "https://evil.test/",
+            HttpResponse::from_json(200, &manifest("9.0.0")),
+        );
+        let mut ctx = ctx_with(network, tmp.path());
+        let args = serde_json::json!({ "manifest_url": "https://evil.test/latest.json" });
+        let r = run_check(args.clone(), &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::PermissionDenied);
+        let r = run_download(serde_json::json!({ "pubkey": "AAAA" }), &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::PermissionDenied);
+
+        ctx.update_settings.allow_overrides = true;
+        let r = run_check(args, &ctx).await;
+        assert_eq!(r.data.unwrap()["latest_version"], "9.0.0");
+    }
+
+    #[tokio::test]
+    async fn test_check_without_manifest_url() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut ctx = ctx_with(MockNetwork::new(), tmp.path());
+        ctx.update_settings.manifest_url = None;
+        let r = run_check(serde_json::json!({}), &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+}
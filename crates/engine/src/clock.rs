@@ -0,0 +1,150 @@
+//! [`Clock`] implementations and the [`Stopwatch`] probes and commands time
+//! their steps with.
+//!
+//! `$APP__FIXED_CLOCK=<unix seconds>` makes the default context use a
+//! [`FixedClock`], so golden-file runs report zero timings.
+
+use crate::traits::Clock;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Environment variable selecting a [`FixedClock`] at that Unix time.
+pub const FIXED_CLOCK_ENV: &str = "APP__FIXED_CLOCK";
+
+/// The OS clocks.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn monotonic(&self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a
+/// test can keep one and [`advance`](FixedClock::advance) it while the
+/// context holds another.
+#[derive(Clone)]
+pub struct FixedClock {
+    /// Offset from the starting point.
+    offset: Arc<Mutex<Duration>>,
+    start: SystemTime,
+}
+
+impl FixedClock {
+    pub fn at(start: SystemTime) -> Self {
+        Self {
+            offset: Arc::new(Mutex::new(Duration::ZERO)),
+            start,
+        }
+    }
+
+    pub fn at_unix(secs: u64) -> Self {
+        Self::at(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Move both wall-clock and monotonic time forward.
+    pub fn advance(&self, by: Duration) {
+        *self.offset.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+
+    fn offset(&self) -> Duration {
+        *self.offset.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.offset()
+    }
+
+    fn monotonic(&self) -> Duration {
+        self.offset()
+    }
+
+    fn skip(&self, by: Duration) -> bool {
+        self.advance(by);
+        true
+    }
+}
+
+/// [`FixedClock`] if `$APP__FIXED_CLOCK` holds a Unix time, else
+/// [`SystemClock`].
+pub fn from_env() -> Arc<dyn Clock> {
+    match std::env::var(FIXED_CLOCK_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+    {
+        Some(secs) => Arc::new(FixedClock::at_unix(secs)),
+        None => Arc::new(SystemClock),
+    }
+}
+
+/// Milliseconds since the Unix epoch by `clock`.
+pub fn unix_ms(clock: &dyn Clock) -> u64 {
+    clock
+        .now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Wait `duration` by `clock`: a [`FixedClock`] moves forward at once, so
+/// scenario sleeps and polls finish immediately and report the requested
+/// time.
+pub async fn sleep(clock: &dyn Clock, duration: Duration) {
+    if !clock.skip(duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Elapsed time since it was started, by a context's clock.
+#[derive(Clone)]
+pub struct Stopwatch {
+    clock: Arc<dyn Clock>,
+    origin: Duration,
+}
+
+impl Stopwatch {
+    pub fn start(clock: Arc<dyn Clock>) -> Self {
+        let origin = clock.monotonic();
+        Self { clock, origin }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.monotonic().saturating_sub(self.origin)
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.elapsed().as_millis() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_moves_only_when_advanced() {
+        let clock = FixedClock::at_unix(1_700_000_000);
+        let watch = Stopwatch::start(Arc::new(clock.clone()));
+        assert_eq!(watch.elapsed_ms(), 0);
+        assert_eq!(unix_ms(&clock), 1_700_000_000_000);
+
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(watch.elapsed_ms(), 250);
+        assert_eq!(unix_ms(&clock), 1_700_000_000_250);
+    }
+
+    #[tokio::test]
+    async fn test_sleep_advances_a_fixed_clock() {
+        let clock = FixedClock::at_unix(1_700_000_000);
+        let watch = Stopwatch::start(Arc::new(clock.clone()));
+        sleep(&clock, Duration::from_secs(3600)).await;
+        assert_eq!(watch.elapsed(), Duration::from_secs(3600));
+    }
+}
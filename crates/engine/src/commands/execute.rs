@@ -0,0 +1,377 @@
+//! Running commands: on their worker pool, through the result cache, and
+//! within their limits, with lifecycle events and dry-run handling.
+
+use super::is_dry_run;
+use super::registry::Effects;
+use super::{CommandError, CommandHandler, CommandRegistry};
+use crate::context::AppContext;
+use crate::pool::Priority;
+use crate::types::*;
+use serde_json::Value;
+use std::sync::Arc;
+
+impl CommandRegistry {
+    /// Execute a command by name and return a full CommandResult.
+    pub fn execute(&self, name: &str, args: Value, ctx: &AppContext) -> CommandResult {
+        self.execute_with(name, args, ctx, None, |handler, priority, args| {
+            self.pools.run(priority, || handler(args, ctx))
+        })
+    }
+
+    /// Like [`execute`](Self::execute), but in `priority`'s pool whatever
+    /// the command registered with; scenarios run everything as
+    /// background work.
+    pub fn execute_as(
+        &self,
+        name: &str,
+        args: Value,
+        ctx: &AppContext,
+        priority: Priority,
+    ) -> CommandResult {
+        self.execute_with(
+            name,
+            args,
+            ctx,
+            Some(priority),
+            |handler, priority, args| self.pools.run(priority, || handler(args, ctx)),
+        )
+    }
+
+    /// Like [`execute`](Self::execute), but within the command's limit from
+    /// `ctx.command_limits`: watched from a separate thread, failing with
+    /// `TIMEOUT` or `RESOURCE_EXHAUSTED` instead of blocking the caller.
+    /// Commands without a limit go straight to their pool.
+    pub fn execute_limited(&self, name: &str, args: Value, ctx: &Arc<AppContext>) -> CommandResult {
+        self.execute_within(name, args, ctx, None, None)
+    }
+
+    /// Like [`execute_limited`](Self::execute_limited), but in `priority`'s
+    /// pool and failing after `timeout_ms` at most, when given; scenarios
+    /// pass each call step's `timeout_ms`.
+    pub fn execute_within(
+        &self,
+        name: &str,
+        args: Value,
+        ctx: &Arc<AppContext>,
+        priority: Option<Priority>,
+        timeout_ms: Option<u64>,
+    ) -> CommandResult {
+        let mut limit = ctx.command_limits.for_command(self.resolve(name).0);
+        if let Some(cap) = timeout_ms {
+            limit.timeout_ms = Some(limit.timeout_ms.map_or(cap, |ms| ms.min(cap)));
+        }
+        if limit.is_unlimited() {
+            return self.execute_with(name, args, ctx, priority, |handler, priority, args| {
+                self.pools.run(priority, || handler(args, ctx))
+            });
+        }
+        self.execute_with(name, args, ctx, priority, |handler, priority, args| {
+            let pools = Arc::clone(&self.pools);
+            crate::limits::run_limited(name, ctx, limit, move |ctx| {
+                pools.run(priority, || handler(args, ctx))
+            })
+        })
+    }
+
+    fn execute_with(
+        &self,
+        name: &str,
+        args: Value,
+        ctx: &AppContext,
+        priority: Option<Priority>,
+        run: impl FnOnce(CommandHandler, Priority, Value) -> Result<Value, CommandError>,
+    ) -> CommandResult {
+        let run_id = ctx.new_run_id();
+        let start = ctx.stopwatch();
+
+        let (current, deprecation) = self.resolve(name);
+        let spec = match self.handlers.get(current) {
+            Some(spec) => spec,
+            None => {
+                crate::telemetry::record(ctx, "unknown", Some(ErrorCode::InvalidInput));
+                return result_err(
+                    "call",
+                    name,
+                    &run_id,
+                    start.elapsed_ms(),
+                    ErrorCode::InvalidInput,
+                    format!("unknown command: {}", name),
+                );
+            }
+        };
+        let priority = priority.unwrap_or(spec.priority);
+
+        ctx.events().emit(
+            &run_id,
+            "command:started",
+            serde_json::json!({ "command": name, "priority": priority }),
+        );
+        let now = ctx.clock().monotonic();
+        let cached = spec
+            .cache_ttl
+            .and_then(|_| self.cache.get(current, &args, now));
+        let outcome = if spec.effects == Effects::Mutating && is_dry_run(&args, ctx) {
+            None
+        } else if let Some((data, age)) = cached {
+            Some(Ok((data, Some(age))))
+        } else {
+            let key = spec.cache_ttl.map(|ttl| (args.clone(), ttl));
+            Some(run(spec.handler, priority, args).map(|data| {
+                if let Some((args, ttl)) = key {
+                    self.cache.put(current, &args, data.clone(), now, ttl);
+                }
+                (data, None)
+            }))
+        };
+        let mut result = match outcome {
+            None => result_skip(
+                "call",
+                name,
+                &run_id,
+                start.elapsed_ms(),
+                format!("dry run: {} has side effects and was not run", name),
+            ),
+            Some(Ok((data, age))) => {
+                let mut r = result_ok("call", name, &run_id, start.elapsed_ms());
+                r.data = Some(data);
+                r.cached_age_ms = age.map(|a| a.as_millis() as u64);
+                r
+            }
+            Some(Err(e)) => result_err(
+                "call",
+                name,
+                &run_id,
+                start.elapsed_ms(),
+                e.error_code(),
+                e.to_string(),
+            ),
+        };
+        if let Some(deprecation) = deprecation {
+            tracing::warn!("{}", deprecation.message);
+            result.deprecation = Some(deprecation);
+        }
+        // Under the name called, so it shows how much old names are used.
+        crate::telemetry::record(ctx, name, result.error.as_ref().map(|e| e.code));
+        ctx.events().emit(
+            &run_id,
+            "command:finished",
+            serde_json::json!({
+                "command": name,
+                "status": result.status,
+                "duration_ms": result.timing_ms.total,
+                "cached": result.cached_age_ms.is_some(),
+                "deprecated": result.deprecation.is_some(),
+            }),
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::*;
+    use crate::context::AppContext;
+
+    /// Outlasts every limit in the tests below by a wide margin.
+    fn cmd_sleep(_: Value, _: &AppContext) -> Result<Value, CommandError> {
+        std::thread::sleep(std::time::Duration::from_secs(30));
+        Ok(Value::Null)
+    }
+
+    fn cmd_panic(_: Value, _: &AppContext) -> Result<Value, CommandError> {
+        panic!("bug")
+    }
+
+    /// RSS that grows by 64 MiB per sample.
+    #[derive(Default)]
+    struct GrowingRss(std::sync::atomic::AtomicU64);
+
+    impl crate::traits::ResourceOps for GrowingRss {
+        fn sample(&self) -> crate::traits::CapResult<crate::traits::ResourceSample> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(crate::traits::ResourceSample {
+                process_rss_bytes: Some(n * 64 * 1024 * 1024),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_execute_limited_times_out() {
+        let mut reg = CommandRegistry::new();
+        reg.register("sleep", cmd_sleep);
+        reg.register("panic", cmd_panic);
+        let mut ctx = AppContext::default_headless();
+        ctx.command_limits = serde_json::from_value(serde_json::json!({
+            "default": { "timeout_ms": 100 },
+            "commands": { "ping": { "timeout_ms": 10000 } }
+        }))
+        .unwrap();
+        let ctx = Arc::new(ctx);
+
+        let r = reg.execute_limited("sleep", Value::Null, &ctx);
+        assert_eq!(r.error.unwrap().code, ErrorCode::Timeout);
+
+        let r = reg.execute_limited("ping", Value::Null, &ctx);
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+
+        let r = reg.execute_limited("panic", Value::Null, &ctx);
+        assert_eq!(r.error.unwrap().code, ErrorCode::InternalError);
+    }
+
+    /// Depends on the watchdog sampling RSS twice while `sleep` runs.
+    #[test]
+    #[ignore]
+    fn test_execute_limited_bounds_memory() {
+        let mut reg = CommandRegistry::new();
+        reg.register("sleep", cmd_sleep);
+        let mut ctx =
+            AppContext::default_headless().with_resources(Box::new(GrowingRss::default()));
+        ctx.command_limits.commands.insert(
+            "sleep".into(),
+            crate::limits::CommandLimit {
+                timeout_ms: Some(20000),
+                memory_mb: Some(100),
+            },
+        );
+        let r = reg.execute_limited("sleep", Value::Null, &Arc::new(ctx));
+        let error = r.error.unwrap();
+        assert_eq!(error.code, ErrorCode::ResourceExhausted);
+        assert!(
+            error.message.contains("over its 100 MiB limit"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[derive(Default, Clone)]
+    struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<EngineEvent>>>);
+
+    impl crate::events::EventSink for Recorder {
+        fn send(&self, event: &EngineEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_execute_emits_lifecycle_events() {
+        let recorder = Recorder::default();
+        let ctx = AppContext::default_headless().with_event_sink(recorder.clone());
+        let reg = CommandRegistry::new();
+        let result = reg.execute("ping", serde_json::json!({}), &ctx);
+
+        let events = recorder.0.lock().unwrap();
+        let topics: Vec<&str> = events.iter().map(|e| e.topic.as_str()).collect();
+        assert_eq!(topics, ["command:started", "command:finished"]);
+        assert!(events.iter().all(|e| e.run_id == result.run_id));
+        assert_eq!(events[1].payload["command"], "ping");
+        assert_eq!(events[1].payload["status"], "pass");
+    }
+
+    fn cmd_thread_name(_: Value, _: &AppContext) -> Result<Value, CommandError> {
+        Ok(std::thread::current().name().into())
+    }
+
+    #[test]
+    fn test_dry_run_reports_instead_of_changing_anything() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "old").unwrap();
+        let path = file.to_str().unwrap();
+        let reg = CommandRegistry::new();
+        let mut ctx = AppContext::default_headless();
+        ctx.dry_run = true;
+
+        let r = reg.execute(
+            "write_file",
+            serde_json::json!({ "path": path, "content": "new" }),
+            &ctx,
+        );
+        let data = r.data.unwrap();
+        assert_eq!(data["dry_run"], true);
+        assert_eq!(
+            (data["bytes"].as_u64(), data["exists"].as_bool()),
+            (Some(3), Some(true))
+        );
+        // Inputs are still checked.
+        let r = reg.execute(
+            "write_file",
+            serde_json::json!({ "path": path, "content": "new", "if_match_sha256": "00" }),
+            &ctx,
+        );
+        assert_eq!(r.error.unwrap().code, ErrorCode::ExternalInterference);
+
+        let r = reg.execute("delete_file", serde_json::json!({ "path": path }), &ctx);
+        assert_eq!(r.data.unwrap()["action"], "trash");
+        let missing = dir.path().join("missing").display().to_string();
+        let r = reg.execute("delete_file", serde_json::json!({ "path": missing }), &ctx);
+        assert_eq!(r.error.unwrap().code, ErrorCode::IoError);
+
+        let link = dir.path().join("b").display().to_string();
+        let r = reg.execute(
+            "create_link",
+            serde_json::json!({ "target": path, "link": link }),
+            &ctx,
+        );
+        assert_eq!(r.data.unwrap()["action"], "symlink");
+        assert!(!std::path::Path::new(&link).exists());
+
+        // Commands that can't plan a dry run are skipped.
+        let r = reg.execute(
+            "open_url",
+            serde_json::json!({ "url": "https://example.com" }),
+            &ctx,
+        );
+        assert_eq!(r.status, Status::Skip);
+        assert_eq!(ctx.opener().recorded(), Some(vec![]));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "old");
+
+        // Or per call, with the flag off.
+        ctx.dry_run = false;
+        let r = reg.execute(
+            "write_file",
+            serde_json::json!({ "path": path, "content": "new", "dry_run": true }),
+            &ctx,
+        );
+        assert_eq!(r.data.unwrap()["dry_run"], true);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "old");
+    }
+
+    #[test]
+    fn test_commands_run_on_their_priority_pool() {
+        let mut reg = CommandRegistry::new();
+        reg.register("thread", cmd_thread_name);
+        reg.register_background("bg_thread", cmd_thread_name);
+        assert_eq!(reg.priority("find_files"), Some(Priority::Background));
+        assert_eq!(reg.priority("ping"), Some(Priority::Interactive));
+        assert_eq!(reg.priority("nope"), None);
+
+        let recorder = Recorder::default();
+        let ctx = AppContext::default_headless().with_event_sink(recorder.clone());
+        let name = |r: CommandResult| r.data.unwrap().as_str().unwrap().to_string();
+        assert!(name(reg.execute("thread", Value::Null, &ctx)).starts_with("command-interactive-"));
+        assert!(
+            name(reg.execute("bg_thread", Value::Null, &ctx)).starts_with("command-background-")
+        );
+        let forced = reg.execute_as("thread", Value::Null, &ctx, Priority::Background);
+        assert!(name(forced).starts_with("command-background-"));
+
+        let events = recorder.0.lock().unwrap();
+        let started: Vec<&Value> = events
+            .iter()
+            .filter(|e| e.topic == "command:started")
+            .map(|e| &e.payload["priority"])
+            .collect();
+        assert_eq!(started, ["interactive", "background", "background"]);
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let result = reg.execute("nonexistent", serde_json::json!({}), &ctx);
+        assert_eq!(result.status, Status::Error);
+        assert_eq!(result.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+}
@@ -0,0 +1,323 @@
+//! `read_file` and `write_file`.
+
+use super::{bool_arg, is_dry_run, CommandError};
+use crate::context::AppContext;
+use serde_json::Value;
+
+/// `read_file` – read a file, or part of it, as text, base64, or a blob.
+///
+/// Args: `{ "path": "/absolute/path", "offset"?: 0, "length"?: 4096,
+/// "encoding"?: "utf-8" | "utf-16le" | "utf-16be" | "latin1" | "base64" |
+/// "binary" }`. `binary` returns an `artifact_id` in place of `content`
+/// (see [`crate::blobs`]); the daemon sends the bytes alongside.
+/// At most [`AppContext::max_read_bytes`] are returned; a longer `length`,
+/// or a file longer than that without one, is refused rather than loaded.
+/// Returns: `{ "content": "...", "encoding": "utf-8", "offset": 0,
+/// "size_bytes": 123, "lossy": false, "sha256": "..." }`; `lossy` means
+/// some bytes were not valid in the encoding and were replaced, `sha256`
+/// is of the bytes read (for `write_file`'s `if_match_sha256`).
+pub(super) fn cmd_read_file(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path_str = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+    let u64_arg = |key: &str| match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v.as_u64().map(Some).ok_or_else(|| {
+            CommandError::InvalidInput(format!("'{}' must be a non-negative integer", key))
+        }),
+    };
+    let offset = u64_arg("offset")?.unwrap_or(0);
+    let length = u64_arg("length")?;
+    let encoding = args
+        .get("encoding")
+        .and_then(|v| v.as_str())
+        .unwrap_or("utf-8");
+    if !READ_ENCODINGS.contains(&encoding) {
+        return Err(CommandError::InvalidInput(format!(
+            "unknown encoding '{}' (expected one of: {})",
+            encoding,
+            READ_ENCODINGS.join(", ")
+        )));
+    }
+
+    let limit = ctx.max_read_bytes;
+    if length.is_some_and(|n| n > limit) {
+        return Err(CommandError::InvalidInput(format!(
+            "'length' is above the {}-byte read limit",
+            limit
+        )));
+    }
+    let path = std::path::Path::new(path_str);
+    let data = ctx
+        .fs()
+        .read_range(path, offset, length.unwrap_or(limit.saturating_add(1)))
+        .map_err(|e| match e {
+            crate::traits::CapError::PermissionDenied(m) => CommandError::PermissionDenied(m),
+            crate::traits::CapError::Io(io) => CommandError::Io(io),
+            other => CommandError::Other(other.to_string()),
+        })?;
+    if data.len() as u64 > limit {
+        return Err(CommandError::InvalidInput(format!(
+            "{} is larger than the {}-byte read limit; read it in parts with 'offset' and 'length'",
+            path_str, limit
+        )));
+    }
+
+    if encoding == "binary" {
+        let (offset, size_bytes) = (offset, data.len());
+        let blob = ctx.blobs().put(data);
+        return Ok(serde_json::json!({
+            "artifact_id": blob.artifact_id,
+            "encoding": encoding,
+            "offset": offset,
+            "size_bytes": size_bytes,
+            "sha256": blob.sha256,
+        }));
+    }
+    let (content, lossy) = decode(&data, encoding);
+    Ok(serde_json::json!({
+        "content": content,
+        "encoding": encoding,
+        "offset": offset,
+        "size_bytes": data.len(),
+        "lossy": lossy,
+        "sha256": sha256_hex(&data),
+    }))
+}
+
+const READ_ENCODINGS: &[&str] = &[
+    "utf-8", "utf-16le", "utf-16be", "latin1", "base64", "binary",
+];
+
+/// `data` as text in `encoding`, and whether anything had to be replaced.
+fn decode(data: &[u8], encoding: &str) -> (String, bool) {
+    use base64::Engine as _;
+    match encoding {
+        "base64" => (
+            base64::engine::general_purpose::STANDARD.encode(data),
+            false,
+        ),
+        "latin1" => (data.iter().map(|&b| b as char).collect(), false),
+        "utf-16le" | "utf-16be" => {
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|pair| match encoding {
+                    "utf-16le" => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            let text = String::from_utf16_lossy(&units);
+            let lossy = data.len() % 2 == 1 || text.contains('\u{FFFD}');
+            (text, lossy)
+        }
+        _ => match String::from_utf8_lossy(data) {
+            std::borrow::Cow::Borrowed(text) => (text.to_string(), false),
+            std::borrow::Cow::Owned(text) => (text, true),
+        },
+    }
+}
+
+/// `write_file` – write string content to a file.
+///
+/// Args: `{ "path": "/absolute/path", "content": "hello", "fsync"?: false,
+/// "atomic"?: false, "if_match_sha256"?: "...", "expected_sha256"?: "..." }`;
+/// `fsync` flushes the file to disk before returning, `atomic` writes a
+/// synced temp file and renames it over `path`. `if_match_sha256` refuses
+/// the write (`EXTERNAL_INTERFERENCE`) unless the file still has that hash,
+/// e.g. the `sha256` from the `read_file` it was edited from; a missing
+/// file never matches. `expected_sha256` must be the hash of `content`,
+/// and the file is read back afterwards to check it landed intact.
+/// Returns: `{ "bytes_written": 5, "sha256": "2cf24d..." }`; in a dry run,
+/// after the same checks, `{ "dry_run": true, "action": "write", "path",
+/// "bytes": 5, "sha256", "exists": false }` without writing.
+pub(super) fn cmd_write_file(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path_str = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+    let content = args
+        .get("content")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'content' string field".into()))?;
+    let fsync = bool_arg(&args, "fsync")?;
+    let atomic = bool_arg(&args, "atomic")?;
+    let hash_arg = |key: &str| match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(hash)) => Ok(Some(hash.to_ascii_lowercase())),
+        Some(_) => Err(CommandError::InvalidInput(format!(
+            "'{}' must be a hex string",
+            key
+        ))),
+    };
+    let if_match = hash_arg("if_match_sha256")?;
+    let expected = hash_arg("expected_sha256")?;
+
+    let path = std::path::Path::new(path_str);
+    let data = content.as_bytes();
+    let sha256 = sha256_hex(data);
+    if expected.as_ref().is_some_and(|e| *e != sha256) {
+        return Err(CommandError::InvalidInput(
+            "'expected_sha256' does not match 'content'".into(),
+        ));
+    }
+
+    // Windows share one process; keep their check-then-write pairs apart.
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(if_match) = &if_match {
+        let current = ctx.fs().read_file(path).ok().map(|d| sha256_hex(&d));
+        if current.as_ref() != Some(if_match) {
+            return Err(CommandError::Conflict(format!(
+                "{} changed since it was read (sha256 {})",
+                path_str,
+                current.as_deref().unwrap_or("none: file missing")
+            )));
+        }
+    }
+    if is_dry_run(&args, ctx) {
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": "write",
+            "path": path_str,
+            "bytes": data.len(),
+            "sha256": sha256,
+            "exists": ctx.fs().exists(path),
+        }));
+    }
+    let written = if atomic {
+        ctx.fs().write_file_atomic(path, data)
+    } else if fsync {
+        ctx.fs().write_file_synced(path, data)
+    } else {
+        ctx.fs().write_file(path, data)
+    };
+    written.map_err(|e| match e {
+        crate::traits::CapError::PermissionDenied(m) => CommandError::PermissionDenied(m),
+        crate::traits::CapError::Io(io) => CommandError::Io(io),
+        other => CommandError::Other(other.to_string()),
+    })?;
+    if expected.is_some() {
+        let landed = sha256_hex(&ctx.fs().read_file(path)?);
+        if landed != sha256 {
+            return Err(CommandError::Conflict(format!(
+                "{} reads back with sha256 {}, not the {} written",
+                path_str, landed, sha256
+            )));
+        }
+    }
+
+    Ok(serde_json::json!({ "bytes_written": data.len(), "sha256": sha256 }))
+}
+
+/// Serializes `write_file` calls that check the current content first.
+static WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn sha256_hex(data: &[u8]) -> String {
+    crate::export::hex(ring::digest::digest(&ring::digest::SHA256, data).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::*;
+    use crate::context::AppContext;
+
+    #[test]
+    fn test_read_file_ranges_encodings_and_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("blob.bin");
+        std::fs::write(&file, b"hello\xff world").unwrap();
+        let mut ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let read = |ctx: &AppContext, extra: Value| {
+            let mut args = serde_json::json!({ "path": file.to_str().unwrap() });
+            args.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            reg.execute("read_file", args, ctx)
+        };
+
+        let data = read(&ctx, serde_json::json!({})).data.unwrap();
+        assert_eq!(data["content"], "hello\u{FFFD} world");
+        assert_eq!(data["lossy"], true);
+        let data = read(
+            &ctx,
+            serde_json::json!({ "offset": 5, "length": 2, "encoding": "base64" }),
+        )
+        .data
+        .unwrap();
+        assert_eq!(data["content"], "/yA=");
+        assert_eq!(data["size_bytes"], 2);
+
+        ctx.max_read_bytes = 8;
+        let r = read(&ctx, serde_json::json!({}));
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+        let data = read(&ctx, serde_json::json!({ "offset": 7 })).data.unwrap();
+        assert_eq!(data["content"], "world");
+        let r = read(&ctx, serde_json::json!({ "encoding": "ebcdic" }));
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn test_write_file_if_match_and_expected_sha256() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("doc.txt");
+        let path = file.to_str().unwrap();
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let write = |extra: Value| {
+            let mut args = serde_json::json!({ "path": path, "content": "mine" });
+            args.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            reg.execute("write_file", args, &ctx)
+        };
+
+        // A missing file never matches.
+        let r = write(serde_json::json!({ "if_match_sha256": "00" }));
+        assert_eq!(r.error.unwrap().code, ErrorCode::ExternalInterference);
+        std::fs::write(&file, "v1").unwrap();
+        let read = reg.execute("read_file", serde_json::json!({ "path": path }), &ctx);
+        let seen = read.data.unwrap()["sha256"].clone();
+
+        std::fs::write(&file, "someone else").unwrap();
+        let r = write(serde_json::json!({ "if_match_sha256": seen }));
+        assert_eq!(r.error.unwrap().code, ErrorCode::ExternalInterference);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "someone else");
+
+        let current = sha256_hex(b"someone else");
+        let expected = sha256_hex(b"mine");
+        let r =
+            write(serde_json::json!({ "if_match_sha256": current, "expected_sha256": expected }));
+        assert_eq!(r.data.unwrap()["sha256"], expected);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "mine");
+
+        let r = write(serde_json::json!({ "expected_sha256": current }));
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn test_read_write_file() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+
+        let tmp = std::env::temp_dir().join("engine_test_rw.txt");
+        let path_str = tmp.to_str().unwrap();
+
+        // Write
+        let w = reg.execute(
+            "write_file",
+            serde_json::json!({ "path": path_str, "content": "hello engine" }),
+            &ctx,
+        );
+        assert_eq!(w.status, Status::Pass);
+
+        // Read back
+        let r = reg.execute("read_file", serde_json::json!({ "path": path_str }), &ctx);
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.data.unwrap()["content"], "hello engine");
+
+        // Cleanup
+        let _ = std::fs::remove_file(&tmp);
+    }
+}
@@ -0,0 +1,262 @@
+//! Command registry and built-in example commands.
+//!
+//! Commands are registered by name and invoked with JSON input/output.
+
+mod execute;
+mod files;
+mod paths;
+mod registry;
+
+pub use registry::{CommandRegistry, CommandSpec};
+
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::*;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Signature for all engine commands.
+pub type CommandHandler = fn(Value, &AppContext) -> Result<Value, CommandError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+    /// Something else changed the target first (e.g. a failed `if_match_sha256`).
+    #[error("conflict: {0}")]
+    Conflict(String),
+    /// The command ran past its `timeout_ms` (see [`crate::limits`]).
+    #[error("timeout: {0}")]
+    Timeout(String),
+    /// The command grew the process past its `memory_mb`.
+    #[error("resource exhausted: {0}")]
+    ResourceExhausted(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl CommandError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            CommandError::InvalidInput(_) => ErrorCode::InvalidInput,
+            CommandError::Io(_) => ErrorCode::IoError,
+            CommandError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            CommandError::Unsupported(_) => ErrorCode::Unsupported,
+            CommandError::Conflict(_) => ErrorCode::ExternalInterference,
+            CommandError::Timeout(_) => ErrorCode::Timeout,
+            CommandError::ResourceExhausted(_) => ErrorCode::ResourceExhausted,
+            CommandError::Other(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl From<CapError> for CommandError {
+    fn from(e: CapError) -> Self {
+        match e {
+            CapError::PermissionDenied(m) => CommandError::PermissionDenied(m),
+            CapError::Io(io) => CommandError::Io(io),
+            CapError::Unsupported(m) => CommandError::Unsupported(m),
+            other => CommandError::Other(other.to_string()),
+        }
+    }
+}
+
+/// Whether the call is a dry run: `ctx.dry_run`, or `"dry_run": true` in
+/// its args.
+pub fn is_dry_run(args: &Value, ctx: &AppContext) -> bool {
+    ctx.dry_run || args.get("dry_run").and_then(Value::as_bool) == Some(true)
+}
+
+/// Register the built-in commands.
+fn register_builtins(reg: &mut CommandRegistry) {
+    reg.register("ping", cmd_ping);
+    reg.register_background("read_file", files::cmd_read_file);
+    reg.register_background("write_file", files::cmd_write_file)
+        .plans_dry_run();
+    reg.register("system_info", cmd_system_info);
+    reg.register("list_dir", paths::cmd_list_dir);
+    reg.register_background("delete_file", paths::cmd_delete_file)
+        .plans_dry_run();
+    reg.register("stat_file", paths::cmd_stat_file);
+    reg.register("create_link", paths::cmd_create_link)
+        .plans_dry_run();
+    reg.register("read_link", paths::cmd_read_link);
+    reg.register_background("find_files", crate::search::cmd_find_files);
+    reg.register_background("clipboard_watch", crate::clipboard::cmd_clipboard_watch)
+        .plans_dry_run()
+        .saves_artifact("changes");
+    reg.register("llm_estimate", cmd_llm_estimate);
+    reg.register("prompt_list", crate::prompts::cmd_prompt_list);
+    reg.register("prompt_render", crate::prompts::cmd_prompt_render);
+    reg.register("window_info", crate::windows::cmd_window_info);
+    reg.register("window_set", crate::windows::cmd_window_set)
+        .mutating();
+    reg.register("autostart_enable", crate::autostart::cmd_autostart_enable)
+        .mutating();
+    reg.register("autostart_disable", crate::autostart::cmd_autostart_disable)
+        .mutating();
+    reg.register("autostart_status", crate::autostart::cmd_autostart_status);
+    reg.register("credential_set", crate::credentials::cmd_credential_set)
+        .mutating();
+    reg.register("credential_get", crate::credentials::cmd_credential_get);
+    reg.register(
+        "credential_delete",
+        crate::credentials::cmd_credential_delete,
+    )
+    .mutating();
+    reg.register("shortcuts_list", crate::shortcuts::cmd_shortcuts_list);
+    reg.register("menu_validate", crate::menu::cmd_menu_validate);
+    reg.register("dialog_open", crate::dialogs::cmd_dialog_open);
+    reg.register("dialog_save", crate::dialogs::cmd_dialog_save);
+    reg.register("dialog_confirm", crate::dialogs::cmd_dialog_confirm);
+    reg.register("dialog_message", crate::dialogs::cmd_dialog_message);
+    reg.register("open_url", crate::opener::cmd_open_url)
+        .mutating();
+    reg.register("reveal_path", crate::opener::cmd_reveal_path)
+        .mutating();
+    reg.register("opener_log", crate::opener::cmd_opener_log);
+    reg.register_background(
+        "list_removable_media",
+        crate::devices::cmd_list_removable_media,
+    )
+    .cache_for(Duration::from_secs(5));
+    reg.register_background("doctor", crate::doctor::cmd_doctor)
+        .cache_for(Duration::from_secs(30));
+    reg.register("system_stats", crate::resources::cmd_system_stats);
+    reg.register("process_info", crate::processes::cmd_process_info);
+    reg.register("version_info", crate::build_info::cmd_version_info);
+    reg.register_background(
+        "export_diagnostics",
+        crate::diagnostics::cmd_export_diagnostics,
+    )
+    .mutating();
+    reg.register("telemetry_status", crate::telemetry::cmd_telemetry_status);
+    reg.register("telemetry_set", crate::telemetry::cmd_telemetry_set)
+        .mutating();
+    reg.register("consent_get", crate::consent::cmd_consent_get);
+    reg.register("consent_set", crate::consent::cmd_consent_set)
+        .mutating();
+    reg.register("explain_error", crate::explain::cmd_explain_error);
+    reg.register("state_get", crate::shared_state::cmd_state_get);
+    reg.register("state_set", crate::shared_state::cmd_state_set)
+        .mutating();
+    reg.register("state_subscribe", crate::shared_state::cmd_state_subscribe);
+    reg.register_background("migrate", crate::migrations::cmd_migrate)
+        .plans_dry_run();
+    reg.register("migrate_status", crate::migrations::cmd_migrate_status);
+    reg.register_background("backup_create", crate::backup::cmd_backup_create)
+        .plans_dry_run();
+    reg.register_background("backup_restore", crate::backup::cmd_backup_restore)
+        .plans_dry_run();
+    reg.register_background("backup_list", crate::backup::cmd_backup_list);
+    reg.register("profile_export", crate::profile::cmd_profile_export)
+        .plans_dry_run();
+    reg.register("profile_import", crate::profile::cmd_profile_import)
+        .plans_dry_run();
+    reg.register("queue_add", crate::queue::cmd_queue_add)
+        .mutating();
+    reg.register("queue_list", crate::queue::cmd_queue_list);
+    reg.register("queue_retry", crate::queue::cmd_queue_retry)
+        .mutating();
+}
+
+/// `ping` – returns { "pong": true }. Proves wiring works.
+fn cmd_ping(_args: Value, _ctx: &AppContext) -> Result<Value, CommandError> {
+    Ok(serde_json::json!({ "pong": true }))
+}
+
+/// `system_info` – return OS, architecture, and hostname.
+///
+/// Args: `{}` (none required)
+/// Returns: `{ "os": "macos", "arch": "aarch64", "hostname": "...", "headless": false }`
+fn cmd_system_info(_args: Value, _ctx: &AppContext) -> Result<Value, CommandError> {
+    let hostname = hostname::get()
+        .map(|h| h.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    Ok(serde_json::json!({
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "hostname": hostname,
+        "headless": crate::types::detect_headless(),
+    }))
+}
+
+fn not_found(path: &str) -> CommandError {
+    CommandError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("{} does not exist", path),
+    ))
+}
+
+/// Optional boolean flag `key`; absent or null is `false`.
+fn bool_arg(args: &Value, key: &str) -> Result<bool, CommandError> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(false),
+        Some(v) => v
+            .as_bool()
+            .ok_or_else(|| CommandError::InvalidInput(format!("'{}' must be a boolean", key))),
+    }
+}
+
+/// `llm_estimate` – count prompt tokens and estimate cost without sending.
+///
+/// Args: same as `llm_complete` (`{ "prompt": "...", "model"?, "system"?, "max_tokens"? }`)
+/// Returns: `{ "model": "...", "tokenizer": "o200k_base", "exact": true, "tokens": 9,
+///            "max_output_tokens": 1000, "context_window": 128000, "fits_context": true,
+///            "input_cost_usd": 0.0000225, "max_cost_usd": 0.0100225 }`
+fn cmd_llm_estimate(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let req = crate::llm::LlmRequest::from_args(&args).map_err(CommandError::InvalidInput)?;
+    let estimate = crate::llm::tokens::estimate(&req, ctx.llm().settings())
+        .map_err(CommandError::InvalidInput)?;
+    serde_json::to_value(estimate).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::AppContext;
+
+    #[test]
+    fn test_ping_command() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let result = reg.execute("ping", serde_json::json!({}), &ctx);
+        assert_eq!(result.status, Status::Pass);
+        assert_eq!(result.data.unwrap()["pong"], true);
+    }
+
+    #[test]
+    fn test_list_commands() {
+        let reg = CommandRegistry::new();
+        let names = reg.list();
+        assert!(names.contains(&"ping"));
+        assert!(names.contains(&"read_file"));
+        assert!(names.contains(&"write_file"));
+        assert!(names.contains(&"system_info"));
+        assert!(names.contains(&"list_dir"));
+        assert!(names.contains(&"llm_estimate"));
+        assert!(names.contains(&"prompt_list"));
+        assert!(names.contains(&"prompt_render"));
+        assert!(names.contains(&"window_info"));
+        assert!(names.contains(&"window_set"));
+    }
+
+    #[test]
+    fn test_system_info_command() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let result = reg.execute("system_info", serde_json::json!({}), &ctx);
+        assert_eq!(result.status, Status::Pass);
+        let data = result.data.unwrap();
+        assert!(data["os"].is_string());
+        assert!(data["arch"].is_string());
+        assert!(data["hostname"].is_string());
+    }
+}
@@ -0,0 +1,315 @@
+//! Directory listing, deletion, metadata, and links.
+
+use super::{bool_arg, is_dry_run, not_found, CommandError};
+use crate::context::AppContext;
+use serde_json::Value;
+
+/// `list_dir` – list entries in a directory.
+///
+/// Args: `{ "path": "/some/dir" }`
+/// Returns: `{ "entries": [{ "name": "foo.txt", "is_dir": false, "size_bytes": 42 }, ...] }`
+pub(super) fn cmd_list_dir(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path_str = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+
+    let path = std::path::Path::new(path_str);
+    let dir_entries = ctx.fs().list_dir(path).map_err(|e| match e {
+        crate::traits::CapError::PermissionDenied(m) => CommandError::PermissionDenied(m),
+        crate::traits::CapError::Io(io) => CommandError::Io(io),
+        other => CommandError::Other(other.to_string()),
+    })?;
+
+    let entries: Vec<Value> = dir_entries
+        .into_iter()
+        .map(|e| {
+            serde_json::json!({
+                "name": e.name,
+                "is_dir": e.is_dir,
+                "size_bytes": e.size_bytes,
+            })
+        })
+        .collect();
+
+    Ok(serde_json::json!({ "entries": entries }))
+}
+
+/// `delete_file` – move a file or directory to the trash, or with
+/// `permanent` remove a file for good.
+///
+/// Args: `{ "path": "/some/file", "permanent"?: false }`
+/// Returns: `{ "path": "/some/file", "trashed": true }`; in a dry run
+/// `{ "dry_run": true, "action": "trash" | "remove", "path" }`, or
+/// `IO_ERROR` if there is nothing to delete.
+pub(super) fn cmd_delete_file(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path_str = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+    let permanent = bool_arg(&args, "permanent")?;
+
+    let path = std::path::Path::new(path_str);
+    if is_dry_run(&args, ctx) {
+        if !ctx.fs().exists(path) {
+            return Err(not_found(path_str));
+        }
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": if permanent { "remove" } else { "trash" },
+            "path": path_str,
+        }));
+    }
+    if permanent {
+        ctx.fs().remove_file(path)?;
+    } else {
+        ctx.fs().trash(path)?;
+    }
+    Ok(serde_json::json!({ "path": path_str, "trashed": !permanent }))
+}
+
+/// `stat_file` – permissions, ownership, and extended attributes.
+///
+/// Args: `{ "path": "/some/file" }`
+/// Returns: `{ "path": "/some/file", "is_dir": false, "is_symlink": false,
+/// "size_bytes": 12, "readonly": false, "mode": 420, "uid": 501, "gid": 20,
+/// "modified": 1760000000, "xattrs": {"com.apple.quarantine": "0083;..."},
+/// "quarantine": {"flags": 131, "timestamp": 1760000000, "agent": "Safari",
+/// "event_id": "...", "approved": false} }`. `mode`, `uid`, and `gid` are
+/// null on Windows; `quarantine` is null without the attribute.
+pub(super) fn cmd_stat_file(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path_str = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+
+    let stat = ctx.fs().stat(std::path::Path::new(path_str))?;
+    let mut out = serde_json::to_value(&stat).map_err(|e| CommandError::Other(e.to_string()))?;
+    out["path"] = path_str.into();
+    if let (Some(q), Some(obj)) = (&stat.quarantine, out["quarantine"].as_object_mut()) {
+        obj.insert("approved".into(), q.approved().into());
+    }
+    Ok(out)
+}
+
+/// `create_link` – create a symbolic or hard link.
+///
+/// Args: `{ "target": "/some/file", "link": "/some/alias", "hard"?: false }`;
+/// a symlink's `target` may be relative to the link's directory.
+/// Returns: `{ "link": "/some/alias", "target": "/some/file", "hard": false }`;
+/// a dry run checks that `link` is free (and a hard link's `target`
+/// exists) and returns `{ "dry_run": true, "action": "symlink" |
+/// "hard_link", "link", "target" }`.
+pub(super) fn cmd_create_link(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let target = args
+        .get("target")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'target' string field".into()))?;
+    let link = args
+        .get("link")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'link' string field".into()))?;
+    let hard = bool_arg(&args, "hard")?;
+
+    let (target_path, link_path) = (std::path::Path::new(target), std::path::Path::new(link));
+    if is_dry_run(&args, ctx) {
+        if ctx.fs().exists(link_path) {
+            return Err(CommandError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", link),
+            )));
+        }
+        if hard && !ctx.fs().exists(target_path) {
+            return Err(not_found(target));
+        }
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": if hard { "hard_link" } else { "symlink" },
+            "link": link,
+            "target": target,
+        }));
+    }
+    if hard {
+        ctx.fs().hard_link(target_path, link_path)?;
+    } else {
+        ctx.fs().symlink(target_path, link_path)?;
+    }
+    Ok(serde_json::json!({ "link": link, "target": target, "hard": hard }))
+}
+
+/// `read_link` – where a symlink points.
+///
+/// Args: `{ "path": "/some/alias" }`
+/// Returns: `{ "path": "/some/alias", "target": "/some/file" }`
+pub(super) fn cmd_read_link(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path_str = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+
+    let target = ctx.fs().read_link(std::path::Path::new(path_str))?;
+    Ok(serde_json::json!({
+        "path": path_str,
+        "target": target.display().to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::*;
+    use crate::context::AppContext;
+
+    #[test]
+    fn test_delete_file_never_falls_back_to_permanent() {
+        struct NoTrash;
+        impl crate::traits::FilesystemOps for NoTrash {
+            fn read_file(&self, _: &std::path::Path) -> crate::traits::CapResult<Vec<u8>> {
+                unreachable!()
+            }
+            fn write_file(&self, _: &std::path::Path, _: &[u8]) -> crate::traits::CapResult<()> {
+                unreachable!()
+            }
+            fn remove_file(&self, path: &std::path::Path) -> crate::traits::CapResult<()> {
+                Ok(std::fs::remove_file(path)?)
+            }
+            fn create_dir_all(&self, _: &std::path::Path) -> crate::traits::CapResult<()> {
+                unreachable!()
+            }
+            fn remove_dir_all(&self, _: &std::path::Path) -> crate::traits::CapResult<()> {
+                unreachable!()
+            }
+            fn exists(&self, path: &std::path::Path) -> bool {
+                path.exists()
+            }
+            fn temp_dir(&self) -> std::path::PathBuf {
+                std::env::temp_dir()
+            }
+            fn list_dir(
+                &self,
+                _: &std::path::Path,
+            ) -> crate::traits::CapResult<Vec<crate::traits::DirEntry>> {
+                unreachable!()
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("doc.txt");
+        std::fs::write(&file, "x").unwrap();
+        let ctx = AppContext::new(
+            Box::new(NoTrash),
+            Box::new(crate::platform::MockNetwork::new()),
+            Box::new(crate::platform::HeadlessClipboard),
+        );
+        let reg = CommandRegistry::new();
+        let args = serde_json::json!({ "path": file.to_str().unwrap() });
+
+        let r = reg.execute("delete_file", args.clone(), &ctx);
+        assert_eq!(r.error.unwrap().code, ErrorCode::Unsupported);
+        assert!(file.exists());
+
+        let mut permanent = args;
+        permanent["permanent"] = true.into();
+        let r = reg.execute("delete_file", permanent, &ctx);
+        assert_eq!(r.data.unwrap()["trashed"], false);
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_stat_file_reports_mode_and_xattrs() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("download.dmg");
+        std::fs::write(&file, "x").unwrap();
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let args = serde_json::json!({ "path": file.to_str().unwrap() });
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let r = reg.execute("stat_file", args.clone(), &ctx);
+        let data = r.data.unwrap();
+        assert_eq!(data["size_bytes"], 1);
+        assert_eq!(data["quarantine"], Value::Null);
+        #[cfg(unix)]
+        assert_eq!(data["mode"], 0o640);
+
+        // Not every filesystem (tmpfs without user xattrs) can hold one.
+        #[cfg(unix)]
+        if xattr::set(&file, "user.engine", b"\xff").is_ok() {
+            let data = reg.execute("stat_file", args, &ctx).data.unwrap();
+            assert_eq!(data["xattrs"]["user.engine"], "hex:ff");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_and_read_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("original.txt");
+        std::fs::write(&file, "x").unwrap();
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let at = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+
+        let r = reg.execute(
+            "create_link",
+            serde_json::json!({ "target": "original.txt", "link": at("alias") }),
+            &ctx,
+        );
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        let r = reg.execute(
+            "read_link",
+            serde_json::json!({ "path": at("alias") }),
+            &ctx,
+        );
+        assert_eq!(r.data.unwrap()["target"], "original.txt");
+        assert_eq!(std::fs::read_to_string(at("alias")).unwrap(), "x");
+
+        let r = reg.execute(
+            "create_link",
+            serde_json::json!({ "target": at("original.txt"), "link": at("hard"), "hard": true }),
+            &ctx,
+        );
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        let r = reg.execute("read_link", serde_json::json!({ "path": at("hard") }), &ctx);
+        assert_eq!(r.error.unwrap().code, ErrorCode::IoError);
+    }
+
+    #[test]
+    fn test_quarantine_parse() {
+        let q = crate::traits::Quarantine::parse("00c1;5f1e2a3b;Safari;E1A2B3C4-0000").unwrap();
+        assert_eq!(q.flags, 0xc1);
+        assert_eq!(q.timestamp, Some(0x5f1e2a3b));
+        assert_eq!(q.agent, "Safari");
+        assert!(q.approved());
+        assert!(crate::traits::Quarantine::parse("garbage").is_none());
+    }
+
+    #[test]
+    fn test_list_dir_command() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+
+        let tmp = std::env::temp_dir();
+        let path_str = tmp.to_str().unwrap();
+        let result = reg.execute("list_dir", serde_json::json!({ "path": path_str }), &ctx);
+        assert_eq!(result.status, Status::Pass);
+        let data = result.data.unwrap();
+        assert!(data["entries"].is_array());
+    }
+
+    #[test]
+    fn test_list_dir_not_a_directory() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let result = reg.execute(
+            "list_dir",
+            serde_json::json!({ "path": "/nonexistent_dir_12345" }),
+            &ctx,
+        );
+        assert_eq!(result.status, Status::Error);
+    }
+}
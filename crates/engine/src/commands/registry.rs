@@ -0,0 +1,303 @@
+//! [`CommandRegistry`]: registration, aliases, and per-command settings.
+
+use super::CommandHandler;
+use crate::cache::ResultCache;
+use crate::pool::{CommandPools, Priority};
+use crate::types::*;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What a command changes, which decides how it behaves in a dry run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Effects {
+    None,
+    /// Skipped in a dry run.
+    Mutating,
+    /// Checks [`is_dry_run`] itself and reports what it would do.
+    PlansDryRun,
+}
+
+/// How a command was registered.
+pub struct CommandSpec {
+    pub(super) handler: CommandHandler,
+    pub(super) priority: Priority,
+    pub(super) cache_ttl: Option<Duration>,
+    pub(super) deprecated: Option<String>,
+    pub(super) effects: Effects,
+    pub(super) artifacts: Vec<&'static str>,
+}
+
+impl CommandSpec {
+    /// Has side effects; a dry run skips it rather than running it.
+    pub fn mutating(&mut self) -> &mut Self {
+        self.effects = Effects::Mutating;
+        self
+    }
+
+    /// Has side effects, but validates its input and reports what it
+    /// would do in `data` (with `"dry_run": true`) when [`is_dry_run`](super::is_dry_run).
+    pub fn plans_dry_run(&mut self) -> &mut Self {
+        self.effects = Effects::PlansDryRun;
+        self
+    }
+
+    /// Keep the command working, but flag its results with `message` (see
+    /// [`Deprecation`]).
+    pub fn deprecated(&mut self, message: &str) -> &mut Self {
+        self.deprecated = Some(message.to_string());
+        self
+    }
+
+    /// Save the `data` field `field` as a step artifact whenever a scenario
+    /// calls this command, as if the step listed it in `save_artifacts`.
+    pub fn saves_artifact(&mut self, field: &'static str) -> &mut Self {
+        self.artifacts.push(field);
+        self
+    }
+
+    /// Reuse a successful result for the same args for `ttl` (see
+    /// [`crate::cache`]). Only for commands without side effects.
+    pub fn cache_for(&mut self, ttl: Duration) -> &mut Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+}
+
+/// Handlers run on the worker pool of their [`Priority`] class (see
+/// [`crate::pool`]), never on the caller's thread.
+///
+/// Renamed commands keep working through [`alias`](Self::alias): the old
+/// name runs the new command and its results carry a [`Deprecation`].
+pub struct CommandRegistry {
+    pub(super) handlers: HashMap<String, CommandSpec>,
+    /// Old name -> current name.
+    pub(super) aliases: HashMap<String, String>,
+    pub(super) pools: Arc<CommandPools>,
+    pub(super) cache: ResultCache,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        let mut reg = Self {
+            handlers: HashMap::new(),
+            aliases: HashMap::new(),
+            pools: Arc::new(CommandPools::default()),
+            cache: ResultCache::default(),
+        };
+        super::register_builtins(&mut reg);
+        reg
+    }
+
+    /// Register an interactive command.
+    pub fn register(&mut self, name: &str, handler: CommandHandler) -> &mut CommandSpec {
+        self.insert(name, handler, Priority::Interactive)
+    }
+
+    /// Register a command that may run long enough to hold up quick calls.
+    pub fn register_background(&mut self, name: &str, handler: CommandHandler) -> &mut CommandSpec {
+        self.insert(name, handler, Priority::Background)
+    }
+
+    fn insert(
+        &mut self,
+        name: &str,
+        handler: CommandHandler,
+        priority: Priority,
+    ) -> &mut CommandSpec {
+        self.cache.invalidate(name);
+        self.aliases.remove(name);
+        let spec = CommandSpec {
+            handler,
+            priority,
+            cache_ttl: None,
+            deprecated: None,
+            effects: Effects::None,
+            artifacts: Vec::new(),
+        };
+        match self.handlers.entry(name.to_string()) {
+            Entry::Occupied(mut slot) => {
+                slot.insert(spec);
+                slot.into_mut()
+            }
+            Entry::Vacant(slot) => slot.insert(spec),
+        }
+    }
+
+    /// Make `old` run `current` from now on, flagged as deprecated. A
+    /// command registered as `old` is replaced.
+    pub fn alias(&mut self, old: &str, current: &str) {
+        self.handlers.remove(old);
+        self.aliases.insert(old.to_string(), current.to_string());
+    }
+
+    /// The command `name` runs and, if calling it by that name is
+    /// deprecated, why.
+    pub(super) fn resolve<'a>(&'a self, name: &'a str) -> (&'a str, Option<Deprecation>) {
+        let (current, alias) = match self.aliases.get(name) {
+            Some(current) => (current.as_str(), Some(current)),
+            None => (name, None),
+        };
+        let own = self
+            .handlers
+            .get(current)
+            .and_then(|s| s.deprecated.clone());
+        let deprecation = match (alias, own) {
+            (None, None) => None,
+            (Some(current), own) => Some(Deprecation {
+                command: name.to_string(),
+                replacement: Some(current.clone()),
+                message: own.unwrap_or_else(|| {
+                    format!("'{}' is deprecated; call '{}' instead", name, current)
+                }),
+            }),
+            (None, Some(message)) => Some(Deprecation {
+                command: name.to_string(),
+                replacement: None,
+                message,
+            }),
+        };
+        (current, deprecation)
+    }
+
+    /// Why calling `name` is deprecated, if it is.
+    pub fn deprecation(&self, name: &str) -> Option<Deprecation> {
+        self.resolve(name).1
+    }
+
+    /// Deprecated aliases and the commands they run, sorted.
+    pub fn aliases(&self) -> Vec<(&str, &str)> {
+        let mut aliases: Vec<(&str, &str)> = self
+            .aliases
+            .iter()
+            .map(|(old, current)| (old.as_str(), current.as_str()))
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
+    /// The class `name` was registered with, if it exists.
+    pub fn priority(&self, name: &str) -> Option<Priority> {
+        self.handlers
+            .get(self.resolve(name).0)
+            .map(|spec| spec.priority)
+    }
+
+    /// The `data` fields a scenario step calling `name` always keeps (see
+    /// [`CommandSpec::saves_artifact`]).
+    pub fn artifact_fields(&self, name: &str) -> &[&'static str] {
+        self.handlers
+            .get(self.resolve(name).0)
+            .map_or(&[], |spec| spec.artifacts.as_slice())
+    }
+
+    /// Forget `name`'s cached results so its next call runs again.
+    pub fn invalidate(&self, name: &str) -> usize {
+        self.cache.invalidate(self.resolve(name).0)
+    }
+
+    pub fn list(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.handlers.keys().map(|s| s.as_str()).collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::*;
+    use crate::context::AppContext;
+
+    static COUNTED_CALLS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+    fn cmd_counted(args: Value, _: &AppContext) -> Result<Value, CommandError> {
+        if args["fail"] == true {
+            return Err(CommandError::Other("flaky".into()));
+        }
+        Ok(COUNTED_CALLS
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+            .into())
+    }
+
+    #[test]
+    fn test_cached_commands_reuse_results_until_the_ttl() {
+        let mut reg = CommandRegistry::new();
+        reg.register("counted", cmd_counted)
+            .cache_for(Duration::from_secs(10));
+        let clock = crate::clock::FixedClock::at_unix(0);
+        let ctx = AppContext::default_headless().with_clock(Box::new(clock.clone()));
+        let call = |args: Value| reg.execute("counted", args, &ctx);
+
+        let first = call(serde_json::json!({ "a": 1 }));
+        assert_eq!(first.cached_age_ms, None);
+        clock.advance(Duration::from_secs(3));
+        let again = call(serde_json::json!({ "a": 1 }));
+        assert_eq!(again.data, first.data);
+        assert_eq!(again.cached_age_ms, Some(3000));
+        assert_ne!(call(serde_json::json!({ "a": 2 })).data, first.data);
+
+        // Failures are not cached.
+        assert_eq!(
+            call(serde_json::json!({ "fail": true })).status,
+            Status::Error
+        );
+        assert_eq!(
+            call(serde_json::json!({ "fail": true })).cached_age_ms,
+            None
+        );
+
+        clock.advance(Duration::from_secs(7));
+        assert_ne!(call(serde_json::json!({ "a": 1 })).data, first.data);
+        assert!(reg.invalidate("counted") > 0);
+        assert_eq!(call(serde_json::json!({ "a": 1 })).cached_age_ms, None);
+    }
+
+    #[test]
+    fn test_aliases_and_deprecated_commands_still_run_with_a_warning() {
+        let mut reg = CommandRegistry::new();
+        reg.register("counted", cmd_counted)
+            .cache_for(Duration::from_secs(10));
+        reg.alias("count", "counted");
+        reg.register("old_ping", cmd_ping)
+            .deprecated("old_ping goes away in 2.0; use ping");
+        let ctx = AppContext::default_headless();
+
+        let current = reg.execute("counted", Value::Null, &ctx);
+        assert_eq!(current.deprecation, None);
+        let old = reg.execute("count", Value::Null, &ctx);
+        assert_eq!(old.status, Status::Pass);
+        assert_eq!(old.command, "call");
+        assert_eq!(old.target, "count");
+        // Same command underneath, so the same cache entry.
+        assert_eq!(old.data, current.data);
+        assert_eq!(
+            old.deprecation,
+            Some(Deprecation {
+                command: "count".into(),
+                replacement: Some("counted".into()),
+                message: "'count' is deprecated; call 'counted' instead".into(),
+            })
+        );
+        assert_eq!(reg.priority("count"), Some(Priority::Interactive));
+        assert_eq!(reg.aliases(), [("count", "counted")]);
+        assert!(!reg.list().contains(&"count"));
+
+        let r = reg.execute("old_ping", Value::Null, &ctx);
+        assert_eq!(r.data.unwrap()["pong"], true);
+        let deprecation = r.deprecation.unwrap();
+        assert_eq!(deprecation.replacement, None);
+        assert_eq!(deprecation.message, "old_ping goes away in 2.0; use ping");
+
+        // Registering the old name again retires the alias.
+        reg.register("count", cmd_ping);
+        assert_eq!(reg.deprecation("count"), None);
+    }
+}
@@ -0,0 +1,151 @@
+//! The `with_*` builders swapping an [`AppContext`]'s capabilities and
+//! settings.
+
+use super::AppContext;
+use crate::diagnostics::LogBuffer;
+use crate::events::EventSink;
+use crate::llm::LlmOps;
+use crate::traits::*;
+use std::sync::Arc;
+
+impl AppContext {
+    /// Replace the network backend (e.g. a
+    /// [`crate::platform::ReqwestNetwork`] with TLS settings from the app
+    /// config).
+    pub fn with_network(mut self, network: Box<dyn NetworkOps>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Replace the LLM backend (e.g. with settings from the app config, or
+    /// a fake in tests). Defaults to
+    /// [`crate::llm::http::HttpLlm::from_env`].
+    pub fn with_llm(mut self, llm: Box<dyn LlmOps>) -> Self {
+        self.llm = llm;
+        self
+    }
+
+    /// Replace the window backend. Defaults to
+    /// [`crate::platform::HeadlessWindows`].
+    pub fn with_windows(mut self, windows: Box<dyn WindowOps>) -> Self {
+        self.windows = windows;
+        self
+    }
+
+    /// Replace the login-item backend. Defaults to
+    /// [`crate::autostart::platform_default`].
+    pub fn with_autostart(mut self, autostart: Box<dyn AutostartOps>) -> Self {
+        self.autostart = autostart;
+        self
+    }
+
+    /// Replace the global shortcut backend. Defaults to
+    /// [`crate::platform::HeadlessShortcuts`], which registers nothing.
+    pub fn with_shortcuts(mut self, shortcuts: Box<dyn ShortcutOps>) -> Self {
+        self.shortcuts = shortcuts;
+        self
+    }
+
+    /// Replace the dialog backend. Defaults to
+    /// [`crate::platform::ScriptedDialogs`], which answers from
+    /// scenario-provided responses.
+    pub fn with_dialogs(mut self, dialogs: Box<dyn DialogOps>) -> Self {
+        self.dialogs = dialogs;
+        self
+    }
+
+    /// Replace the URL/file opener. Defaults to
+    /// [`crate::platform::RecordingOpener`], which launches nothing.
+    pub fn with_opener(mut self, opener: Box<dyn OpenerOps>) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// Replace the power/session signal source. Defaults to
+    /// [`crate::session::platform_default`].
+    pub fn with_session(mut self, session: Box<dyn SessionOps>) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Replace hardware enumeration (e.g. with fixed devices in tests).
+    /// Defaults to [`crate::devices::SystemDevices`].
+    pub fn with_devices(mut self, devices: Box<dyn DeviceOps>) -> Self {
+        self.devices = devices;
+        self
+    }
+
+    /// Replace the desktop portal backend. Defaults to
+    /// [`crate::portals::platform_default`].
+    pub fn with_portals(mut self, portals: Box<dyn PortalOps>) -> Self {
+        self.portals = portals;
+        self
+    }
+
+    /// Replace resource sampling (e.g. with fixed readings in tests).
+    pub fn with_resources(mut self, resources: Box<dyn ResourceOps>) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    /// Replace interface/route/DNS discovery (e.g. with a fixed snapshot
+    /// in tests).
+    pub fn with_net_info(mut self, net_info: Box<dyn NetInfoOps>) -> Self {
+        self.net_info = net_info;
+        self
+    }
+
+    /// Replace where scenario `env:` blocks are applied (e.g. a recording
+    /// map in tests).
+    pub fn with_env(mut self, env: Box<dyn EnvOps>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Replace the OS secret store (e.g. with
+    /// [`crate::credentials::MemorySecrets`] in tests).
+    pub fn with_secrets(mut self, secrets: Box<dyn SecretStoreOps>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Issue `run_id` for the first result, then `<run_id>-<n>` (see
+    /// [`crate::ids`]).
+    pub fn with_run_id(self, run_id: impl Into<String>) -> Self {
+        self.ids.fix_run_id(run_id);
+        self
+    }
+
+    /// Derive run ids and other randomness from `seed` instead of the OS.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.ids.seed(seed);
+        self
+    }
+
+    /// Replace the time source (e.g. a [`crate::clock::FixedClock`] for
+    /// reproducible results). Defaults to [`crate::clock::from_env`].
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = Arc::from(clock);
+        self
+    }
+
+    /// Add a custom scenario step kind, replacing any with the same key.
+    pub fn with_step_handler(mut self, handler: Box<dyn StepHandler>) -> Self {
+        self.step_handlers
+            .insert(handler.key().to_string(), handler);
+        self
+    }
+
+    /// Forward every engine event to `sink` (e.g. the Tauri frontend).
+    pub fn with_event_sink(self, sink: impl EventSink + 'static) -> Self {
+        self.events.attach(Arc::new(sink));
+        self
+    }
+
+    /// Share the buffer the host's log writer fills, so diagnostics
+    /// bundles include recent logs. Without one, bundles have none.
+    pub fn with_recent_logs(mut self, logs: LogBuffer) -> Self {
+        self.recent_logs = logs;
+        self
+    }
+}
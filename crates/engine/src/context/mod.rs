@@ -0,0 +1,382 @@
+//! Application context – holds capability trait objects and config.
+
+mod builder;
+
+use crate::backup::BackupConfig;
+use crate::clock::Stopwatch;
+use crate::compat::CompatRules;
+use crate::consent::ConsentConfig;
+use crate::devices::SystemDevices;
+use crate::diagnostics::LogBuffer;
+use crate::endpoints::NetworkProbeConfig;
+use crate::events::EventBus;
+use crate::explain::ErrorCatalog;
+use crate::export::ExportConfig;
+use crate::first_run::FirstRunCheck;
+use crate::ids::IdSource;
+use crate::llm::{http::HttpLlm, LlmOps};
+use crate::menu::MenuNode;
+use crate::opener::OpenerPolicy;
+use crate::platform::{
+    HeadlessClipboard, HeadlessShortcuts, HeadlessWindows, ProcessEnv, RecordingOpener,
+    ReqwestNetwork, ScriptedDialogs, StdFilesystem, SystemClipboard,
+};
+use crate::queue::QueueConfig;
+use crate::shortcuts::ShortcutBinding;
+use crate::suites::ProbeSuite;
+use crate::telemetry::TelemetryConfig;
+use crate::traits::*;
+use crate::types::detect_headless;
+use crate::updates::UpdateSettings;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Central context passed to all engine operations.
+///
+/// Holds trait-object capabilities so callers (CLI / Tauri) can swap
+/// implementations (e.g. headless clipboard vs real clipboard).
+pub struct AppContext {
+    fs: Box<dyn FilesystemOps>,
+    network: Box<dyn NetworkOps>,
+    clipboard: Box<dyn ClipboardOps>,
+    llm: Box<dyn LlmOps>,
+    windows: Box<dyn WindowOps>,
+    autostart: Box<dyn AutostartOps>,
+    shortcuts: Box<dyn ShortcutOps>,
+    dialogs: Box<dyn DialogOps>,
+    opener: Box<dyn OpenerOps>,
+    session: Box<dyn SessionOps>,
+    devices: Box<dyn DeviceOps>,
+    portals: Box<dyn PortalOps>,
+    resources: Box<dyn ResourceOps>,
+    net_info: Box<dyn NetInfoOps>,
+    env: Box<dyn EnvOps>,
+    secrets: Box<dyn SecretStoreOps>,
+    clock: Arc<dyn Clock>,
+    ids: Arc<IdSource>,
+    events: Arc<EventBus>,
+    /// Recent log lines for diagnostics bundles (see [`crate::diagnostics`]).
+    recent_logs: LogBuffer,
+    /// Binary results waiting to be sent (see [`crate::blobs`]).
+    blobs: crate::blobs::BlobStore,
+    /// Key/value state shared by the app's windows (see
+    /// [`crate::shared_state`]).
+    shared_state: crate::shared_state::SharedState,
+    /// Custom scenario step kinds, by key.
+    step_handlers: BTreeMap<String, Box<dyn StepHandler>>,
+    /// No network access: network probes, LLM calls, and update checks
+    /// skip with [`crate::types::OFFLINE_REASON`] instead of timing out.
+    pub offline: bool,
+    /// Validate and report instead of acting: mutating commands describe
+    /// what they would do, or are skipped (see
+    /// [`crate::commands::is_dry_run`]).
+    pub dry_run: bool,
+    /// Endpoints the network probe checks (see [`crate::endpoints`]).
+    pub network_probe: NetworkProbeConfig,
+    /// Named probe suites (see [`crate::suites`]).
+    pub probe_suites: BTreeMap<String, ProbeSuite>,
+    /// Rules for the host compatibility tier (see [`crate::compat`]).
+    pub compat_rules: CompatRules,
+    /// Startup checks the GUI runs until they pass (see
+    /// [`crate::first_run`]).
+    pub first_run_checks: Vec<FirstRunCheck>,
+    /// URL that answers 204 when no captive portal is in the way (see
+    /// [`crate::interfaces`]).
+    pub captive_portal_url: String,
+    /// Root of the prompt template tree (see [`crate::prompts`]).
+    pub prompts_dir: PathBuf,
+    /// Reverse-DNS app identifier (names login items and similar entries).
+    pub app_id: String,
+    /// Human-readable app name.
+    pub app_name: String,
+    /// Global shortcuts from the app config (see [`crate::shortcuts`]).
+    pub shortcut_bindings: Vec<ShortcutBinding>,
+    /// Application menu spec from the app config (see [`crate::menu`]).
+    pub menu: Vec<MenuNode>,
+    /// URLs `open_url` may open (see [`crate::opener`]).
+    pub opener_policy: OpenerPolicy,
+    /// Directory for persisted app state (e.g. window geometry).
+    pub data_dir: PathBuf,
+    /// Run history file; `None` disables recording (see [`crate::history`]).
+    pub history_path: Option<PathBuf>,
+    /// Where `--artifacts` runs are pushed afterwards (see [`crate::export`]).
+    pub export: ExportConfig,
+    /// Release manifest, signing key, and running version for update checks.
+    pub update_settings: UpdateSettings,
+    /// Where opted-in usage counts are uploaded (see [`crate::telemetry`]).
+    pub telemetry: TelemetryConfig,
+    /// Current policy versions and consent-gated features (see
+    /// [`crate::consent`]).
+    pub consent: ConsentConfig,
+    /// Where data backups go and how many are kept (see [`crate::backup`]).
+    pub backup: BackupConfig,
+    /// Retries and retention of queued jobs (see [`crate::queue`]).
+    pub queue: QueueConfig,
+    /// Translations of user-facing error text, by locale (see
+    /// [`crate::explain`]).
+    pub error_catalogs: BTreeMap<String, ErrorCatalog>,
+    /// Most bytes `read_file` returns in one call; larger reads must pass
+    /// `offset`/`length`.
+    pub max_read_bytes: u64,
+    /// Per-command timeouts and memory limits for
+    /// [`crate::commands::CommandRegistry::execute_limited`].
+    pub command_limits: crate::limits::CommandLimits,
+}
+
+/// Identifier used outside the GUI; matches `identifier` in
+/// `tauri.conf.json`, which the Tauri wrapper applies itself.
+pub const DEFAULT_APP_ID: &str = "com.eito.tauri-app";
+pub const DEFAULT_APP_NAME: &str = "tauri-app";
+
+/// Environment variable overriding the app identifier, for apps built from
+/// this template under their own `identifier`.
+pub const APP_ID_ENV: &str = "APP__APP_ID";
+
+/// `$APP__APP_ID`, else [`DEFAULT_APP_ID`].
+pub fn app_id_from_env() -> String {
+    std::env::var(APP_ID_ENV)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_APP_ID.to_string())
+}
+
+/// Environment variable overriding the default data directory.
+pub const DATA_DIR_ENV: &str = "APP__DATA_DIR";
+
+/// `$APP__DATA_DIR`, else `./.app-data`. The Tauri wrapper replaces this
+/// with the app data dir.
+pub fn default_data_dir() -> PathBuf {
+    std::env::var(DATA_DIR_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(".app-data"))
+}
+
+/// Environment variable enabling offline mode (`1`, `true`, or `yes`).
+pub const OFFLINE_ENV: &str = "APP__OFFLINE";
+
+pub fn offline_from_env() -> bool {
+    std::env::var(OFFLINE_ENV)
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Environment variable enabling dry-run mode (`1`, `true`, or `yes`).
+pub const DRY_RUN_ENV: &str = "APP__DRY_RUN";
+
+pub fn dry_run_from_env() -> bool {
+    std::env::var(DRY_RUN_ENV)
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Environment variable overriding [`DEFAULT_MAX_READ_BYTES`].
+pub const MAX_READ_BYTES_ENV: &str = "APP__MAX_READ_BYTES";
+pub const DEFAULT_MAX_READ_BYTES: u64 = 16 * 1024 * 1024;
+
+pub fn max_read_bytes_from_env() -> u64 {
+    std::env::var(MAX_READ_BYTES_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_MAX_READ_BYTES)
+}
+
+impl AppContext {
+    pub fn new(
+        fs: Box<dyn FilesystemOps>,
+        network: Box<dyn NetworkOps>,
+        clipboard: Box<dyn ClipboardOps>,
+    ) -> Self {
+        Self {
+            fs,
+            network,
+            clipboard,
+            llm: Box::new(HttpLlm::from_env()),
+            windows: Box::new(HeadlessWindows),
+            autostart: crate::autostart::platform_default(),
+            shortcuts: Box::new(HeadlessShortcuts),
+            dialogs: Box::new(ScriptedDialogs::default()),
+            opener: Box::new(RecordingOpener::default()),
+            session: crate::session::platform_default(),
+            devices: Box::new(SystemDevices),
+            portals: crate::portals::platform_default(),
+            resources: Box::new(crate::resources::SystemResources),
+            net_info: Box::new(crate::interfaces::SystemNetInfo),
+            env: Box::new(ProcessEnv),
+            secrets: crate::credentials::platform_default(),
+            clock: crate::clock::from_env(),
+            ids: Arc::new(IdSource::from_env()),
+            events: Arc::new(EventBus::new()),
+            recent_logs: LogBuffer::default(),
+            blobs: Default::default(),
+            shared_state: Default::default(),
+            step_handlers: BTreeMap::new(),
+            offline: offline_from_env(),
+            dry_run: dry_run_from_env(),
+            network_probe: NetworkProbeConfig::from_env(),
+            probe_suites: crate::suites::from_env(),
+            compat_rules: crate::compat::from_env(),
+            first_run_checks: crate::first_run::with_env_override(crate::first_run::builtin()),
+            captive_portal_url: crate::interfaces::default_captive_portal_url(),
+            prompts_dir: crate::prompts::default_dir(),
+            app_id: app_id_from_env(),
+            app_name: DEFAULT_APP_NAME.to_string(),
+            shortcut_bindings: Vec::new(),
+            menu: Vec::new(),
+            opener_policy: OpenerPolicy::default(),
+            data_dir: default_data_dir(),
+            history_path: crate::history::default_path(),
+            export: ExportConfig::from_env(),
+            update_settings: UpdateSettings::from_env(),
+            telemetry: TelemetryConfig::from_env(),
+            consent: ConsentConfig::from_env(),
+            backup: BackupConfig::default(),
+            queue: QueueConfig::default(),
+            error_catalogs: BTreeMap::new(),
+            max_read_bytes: max_read_bytes_from_env(),
+            command_limits: crate::limits::CommandLimits::from_env(),
+        }
+    }
+
+    /// Create a context with real platform implementations, choosing the
+    /// appropriate clipboard based on headless detection.
+    pub fn default_platform() -> Self {
+        let clipboard: Box<dyn ClipboardOps> = if detect_headless() {
+            Box::new(HeadlessClipboard)
+        } else {
+            Box::new(SystemClipboard)
+        };
+        Self::new(
+            Box::new(StdFilesystem),
+            Box::new(ReqwestNetwork::new()),
+            clipboard,
+        )
+    }
+
+    /// Create a context suitable for headless / CI environments.
+    pub fn default_headless() -> Self {
+        Self::new(
+            Box::new(StdFilesystem),
+            Box::new(ReqwestNetwork::new()),
+            Box::new(HeadlessClipboard),
+        )
+    }
+
+    pub fn step_handler(&self, key: &str) -> Option<&dyn StepHandler> {
+        self.step_handlers.get(key).map(|h| h.as_ref())
+    }
+
+    pub fn fs(&self) -> &dyn FilesystemOps {
+        self.fs.as_ref()
+    }
+
+    pub fn network(&self) -> &dyn NetworkOps {
+        self.network.as_ref()
+    }
+
+    pub fn clipboard(&self) -> &dyn ClipboardOps {
+        self.clipboard.as_ref()
+    }
+
+    pub fn llm(&self) -> &dyn LlmOps {
+        self.llm.as_ref()
+    }
+
+    pub fn windows(&self) -> &dyn WindowOps {
+        self.windows.as_ref()
+    }
+
+    pub fn autostart(&self) -> &dyn AutostartOps {
+        self.autostart.as_ref()
+    }
+
+    pub fn shortcuts(&self) -> &dyn ShortcutOps {
+        self.shortcuts.as_ref()
+    }
+
+    pub fn dialogs(&self) -> &dyn DialogOps {
+        self.dialogs.as_ref()
+    }
+
+    pub fn opener(&self) -> &dyn OpenerOps {
+        self.opener.as_ref()
+    }
+
+    pub fn session(&self) -> &dyn SessionOps {
+        self.session.as_ref()
+    }
+
+    pub fn devices(&self) -> &dyn DeviceOps {
+        self.devices.as_ref()
+    }
+
+    pub fn portals(&self) -> &dyn PortalOps {
+        self.portals.as_ref()
+    }
+
+    pub fn resources(&self) -> &dyn ResourceOps {
+        self.resources.as_ref()
+    }
+
+    pub fn net_info(&self) -> &dyn NetInfoOps {
+        self.net_info.as_ref()
+    }
+
+    pub fn env(&self) -> &dyn EnvOps {
+        self.env.as_ref()
+    }
+
+    pub fn secrets(&self) -> &dyn SecretStoreOps {
+        self.secrets.as_ref()
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// A new run id (random, seeded, or derived from a fixed one).
+    pub fn new_run_id(&self) -> String {
+        self.ids.run_id()
+    }
+
+    /// Shared handle to the run id source, for issuing ids from background
+    /// threads.
+    pub fn id_source(&self) -> Arc<IdSource> {
+        self.ids.clone()
+    }
+
+    /// Random number from the context's (possibly seeded) source.
+    pub fn random_u64(&self) -> u64 {
+        self.ids.next_u64()
+    }
+
+    /// Start timing something by this context's clock.
+    pub fn stopwatch(&self) -> Stopwatch {
+        Stopwatch::start(self.clock.clone())
+    }
+
+    pub fn recent_logs(&self) -> &LogBuffer {
+        &self.recent_logs
+    }
+
+    pub fn blobs(&self) -> &crate::blobs::BlobStore {
+        &self.blobs
+    }
+
+    pub fn shared_state(&self) -> &crate::shared_state::SharedState {
+        &self.shared_state
+    }
+
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Shared handle to the event bus, for publishing from background
+    /// threads.
+    pub fn event_bus(&self) -> Arc<EventBus> {
+        self.events.clone()
+    }
+}
@@ -0,0 +1,184 @@
+//! A connection to a running `appctl serve` daemon, for tools that talk to
+//! it from another process (e.g. `appctl client ping`).
+
+use crate::protocol::{Hello, PROTOCOL_VERSION};
+use crate::types::*;
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+type Reader = BufReader<Box<dyn AsyncRead + Unpin + Send>>;
+
+pub struct DaemonClient {
+    reader: Reader,
+    writer: Box<dyn AsyncWrite + Unpin + Send>,
+    next_id: u64,
+    accept_encoding: Option<String>,
+}
+
+impl DaemonClient {
+    /// Connect to the Unix socket `socket`, or the TCP address `tcp`.
+    pub async fn connect(
+        socket: Option<&std::path::Path>,
+        tcp: Option<&str>,
+    ) -> Result<Self, String> {
+        let connect_err = |e: std::io::Error| format!("cannot connect: {}", e);
+        let (reader, writer): (
+            Box<dyn AsyncRead + Unpin + Send>,
+            Box<dyn AsyncWrite + Unpin + Send>,
+        ) = match (tcp, socket) {
+            (Some(addr), _) => {
+                let (r, w) = tokio::net::TcpStream::connect(addr)
+                    .await
+                    .map_err(connect_err)?
+                    .into_split();
+                (Box::new(r), Box::new(w))
+            }
+            #[cfg(unix)]
+            (None, Some(path)) => {
+                let (r, w) = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(connect_err)?
+                    .into_split();
+                (Box::new(r), Box::new(w))
+            }
+            _ => return Err("no usable address (Unix sockets need a Unix host)".into()),
+        };
+        Ok(Self::over(reader, writer))
+    }
+
+    fn over(
+        reader: Box<dyn AsyncRead + Unpin + Send>,
+        writer: Box<dyn AsyncWrite + Unpin + Send>,
+    ) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+            next_id: 0,
+            accept_encoding: None,
+        }
+    }
+
+    /// Ask for large frames gzipped (see [`crate::framing`]).
+    pub fn accept_gzip(mut self) -> Self {
+        self.accept_encoding = Some(crate::framing::GZIP.into());
+        self
+    }
+
+    /// The `hello` handshake, offering [`PROTOCOL_VERSION`]. `Ok(None)`
+    /// means the daemon predates the handshake and says nothing about
+    /// itself; whatever it lacks shows up as `unknown method` errors.
+    pub async fn hello(&mut self) -> Result<Option<Hello>, String> {
+        let response = self
+            .request(
+                "hello",
+                serde_json::json!({ "protocol_version": PROTOCOL_VERSION }),
+            )
+            .await?;
+        if let Some(e) = response.error {
+            if e.message.starts_with("unknown method") {
+                return Ok(None);
+            }
+            return Err(e.message);
+        }
+        let reply = response.result.ok_or("hello answered without a result")?;
+        if let Some(e) = reply.error {
+            return Err(format!("{}: {}", e.code, e.message));
+        }
+        let data = reply.data.ok_or("hello answered without data")?;
+        serde_json::from_value(data)
+            .map(Some)
+            .map_err(|e| format!("bad hello: {}", e))
+    }
+
+    /// Send one request and wait for its response. Progress, pushed
+    /// events, and binary frames arriving meanwhile are skipped.
+    pub async fn request(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<DaemonResponse, String> {
+        self.next_id += 1;
+        let id = self.next_id.to_string();
+        let line = serde_json::to_string(&DaemonRequest {
+            id: id.clone(),
+            method: method.into(),
+            params,
+            accept_encoding: self.accept_encoding.clone(),
+        })
+        .map_err(|e| e.to_string())?;
+        self.writer
+            .write_all(format!("{}\n", line).as_bytes())
+            .await
+            .map_err(|e| format!("cannot send request: {}", e))?;
+        loop {
+            let frame = next_line(&mut self.reader).await?;
+            let value: serde_json::Value =
+                serde_json::from_str(&frame).map_err(|e| format!("bad frame: {}", e))?;
+            if let Some(header) = value.get("binary") {
+                let length = header["length"].as_u64().unwrap_or(0);
+                let mut skipped = (&mut self.reader).take(length);
+                tokio::io::copy(&mut skipped, &mut tokio::io::sink())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+            if value.get("progress").is_some() || value.get("subscription").is_some() {
+                continue;
+            }
+            if value.get("id").and_then(|v| v.as_str()) != Some(id.as_str()) {
+                continue;
+            }
+            return serde_json::from_value(value).map_err(|e| format!("bad response: {}", e));
+        }
+    }
+}
+
+async fn next_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<String, String> {
+    match crate::framing::read_frame(reader, usize::MAX).await {
+        Ok(Some(crate::framing::Frame::Line(line))) => {
+            crate::framing::decode(&line, usize::MAX).map_err(|e| e.to_string())
+        }
+        Ok(_) => Err("daemon closed the connection".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+
+    #[tokio::test]
+    async fn test_request_skips_frames_until_its_response() {
+        let (ours, theirs) = tokio::io::duplex(4096);
+        let (r, w) = tokio::io::split(ours);
+        let mut client = DaemonClient::over(Box::new(r), Box::new(w));
+        let daemon = tokio::spawn(async move {
+            let (r, mut w) = tokio::io::split(theirs);
+            let mut lines = BufReader::new(r).lines();
+            let request = lines.next_line().await.unwrap().unwrap();
+            assert!(request.contains(r#""method":"hello""#), "{}", request);
+            let frames = concat!(
+                r#"{"subscription":"sub-1","event":{"run_id":"r","topic":"x","payload":null}}"#,
+                "\n",
+                r#"{"id":"1","binary":{"artifact_id":"blob-1","length":3}}"#,
+                "\n\x00\n\x01",
+                r#"{"id":"1","progress":{"run_id":"r","topic":"y","payload":null}}"#,
+                "\n",
+                r#"{"id":"1","error":{"code":"INVALID_INPUT","message":"nope"}}"#,
+                "\n",
+            );
+            w.write_all(frames.as_bytes()).await.unwrap();
+        });
+        let response = client
+            .request("hello", serde_json::Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(response.id, "1");
+        assert_eq!(response.error.unwrap().message, "nope");
+        daemon.await.unwrap();
+        assert!(client
+            .request("hello", serde_json::Value::Null)
+            .await
+            .is_err());
+    }
+}
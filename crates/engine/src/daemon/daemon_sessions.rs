@@ -0,0 +1,221 @@
+//! Daemon sessions: per-connection state for clients sharing one daemon.
+//!
+//! A client calls `open_session` to get its own variables, working
+//! directory, and permission grants (not to be confused with
+//! [`crate::session`], the OS login session). The daemon runs requests one
+//! at a time and [enters](DaemonSession::enter) the connection's session
+//! around each of them, so variables one orchestrator sets are never seen
+//! by another, and `env_set` inside a session changes only that session.
+
+use crate::context::AppContext;
+use crate::env::{env_value, EnvGuard};
+use crate::types::*;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct DaemonSession {
+    pub id: String,
+    vars: BTreeMap<String, Value>,
+    cwd: Option<PathBuf>,
+    grants: Vec<String>,
+}
+
+impl DaemonSession {
+    /// Params `{"vars": {"NAME": "value" | null}, "cwd": "/dir",
+    /// "grants": ["call:read_file", "probe:*"]}`, all optional. Without
+    /// `grants` the session may do anything.
+    pub fn open(id: String, params: &Value) -> Result<Self, String> {
+        let vars = match params.get("vars") {
+            None | Some(Value::Null) => BTreeMap::new(),
+            Some(Value::Object(vars)) => vars.clone().into_iter().collect(),
+            Some(_) => return Err("vars must be an object".into()),
+        };
+        let cwd = match params.get("cwd").and_then(Value::as_str) {
+            Some(dir) => Some(
+                std::fs::canonicalize(dir)
+                    .ok()
+                    .filter(|d| d.is_dir())
+                    .ok_or_else(|| format!("cwd {} is not a directory", dir))?,
+            ),
+            None => None,
+        };
+        let grants = match params.get("grants") {
+            None | Some(Value::Null) => vec!["*".to_string()],
+            Some(Value::Array(grants)) => grants
+                .iter()
+                .map(|g| g.as_str().map(String::from))
+                .collect::<Option<_>>()
+                .ok_or("grants must be strings")?,
+            Some(_) => return Err("grants must be an array".into()),
+        };
+        Ok(Self {
+            id,
+            vars,
+            cwd,
+            grants,
+        })
+    }
+
+    /// What `open_session` reports back; variable values are left out.
+    pub fn describe(&self) -> Value {
+        serde_json::json!({
+            "session": self.id,
+            "vars": self.vars.keys().collect::<Vec<_>>(),
+            "cwd": self.cwd,
+            "grants": self.grants,
+        })
+    }
+
+    /// Whether a grant covers `action` – `call:<command>`,
+    /// `probe:<target>`, or another method's name. Grants use the same
+    /// patterns as event subscriptions (see
+    /// [`crate::events::topic_matches`]).
+    pub fn permits(&self, action: &str) -> bool {
+        self.grants
+            .iter()
+            .any(|g| crate::events::topic_matches(g, action))
+    }
+
+    /// Apply the session's variables and working directory until the guard
+    /// is dropped.
+    pub fn enter<'a>(&self, ctx: &'a AppContext) -> std::io::Result<SessionGuard<'a>> {
+        let previous_dir = match &self.cwd {
+            Some(dir) => {
+                let previous = std::env::current_dir()?;
+                std::env::set_current_dir(dir)?;
+                Some(previous)
+            }
+            None => None,
+        };
+        Ok(SessionGuard {
+            _env: EnvGuard::apply(ctx.env(), &self.vars),
+            previous_dir,
+        })
+    }
+
+    /// `env_set` inside the session: params `{"vars": {"NAME": "value" |
+    /// null}}` update the session's variables from the next request on.
+    /// `data.previous` holds the values the session saw, as for the
+    /// daemon-wide [`crate::env::run_env_set`]; call it with the session
+    /// entered.
+    pub fn env_set(&mut self, params: Value, ctx: &AppContext) -> CommandResult {
+        let run_id = ctx.new_run_id();
+        let Some(vars) = params.get("vars").and_then(Value::as_object) else {
+            return result_err(
+                "env_set",
+                &self.id,
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                "expected params {\"vars\": {\"NAME\": \"value\" | null}}",
+            );
+        };
+        let mut previous = serde_json::Map::new();
+        for (name, value) in vars {
+            let seen = match self.vars.get(name) {
+                Some(v) => env_value(v),
+                None => ctx.env().get(name),
+            };
+            previous.insert(name.clone(), seen.map_or(Value::Null, Value::String));
+            self.vars.insert(name.clone(), value.clone());
+        }
+        let names: Vec<&str> = vars.keys().map(String::as_str).collect();
+        ctx.events().emit(
+            &run_id,
+            "config:changed",
+            serde_json::json!({ "vars": names, "session": self.id }),
+        );
+        let mut r = result_ok("env_set", &self.id, &run_id, 0);
+        r.data = Some(serde_json::json!({ "previous": previous }));
+        r
+    }
+}
+
+/// Returned by [`DaemonSession::enter`]; puts back the daemon's own
+/// variables and working directory when dropped.
+pub struct SessionGuard<'a> {
+    _env: EnvGuard<'a>,
+    previous_dir: Option<PathBuf>,
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.previous_dir {
+            let _ = std::env::set_current_dir(dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::tests::MapEnv;
+    use crate::traits::EnvOps;
+
+    #[test]
+    fn test_sessions_keep_their_variables_apart() {
+        let env = MapEnv::default();
+        env.set("PORT", Some("80"));
+        let ctx = AppContext::default_headless().with_env(Box::new(env.clone()));
+        let mut a = DaemonSession::open(
+            "sess-1".into(),
+            &serde_json::json!({ "vars": { "PORT": 8080 } }),
+        )
+        .unwrap();
+        let b = DaemonSession::open("sess-2".into(), &Value::Null).unwrap();
+
+        {
+            let _in = a.enter(&ctx).unwrap();
+            assert_eq!(env.get("PORT").as_deref(), Some("8080"));
+            let r = a.env_set(
+                serde_json::json!({ "vars": { "PORT": null, "X": "1" } }),
+                &ctx,
+            );
+            assert_eq!(
+                r.data.unwrap()["previous"],
+                serde_json::json!({ "PORT": "8080", "X": null })
+            );
+        }
+        assert_eq!(env.get("PORT").as_deref(), Some("80"));
+        {
+            let _in = a.enter(&ctx).unwrap();
+            assert_eq!(
+                (env.get("PORT"), env.get("X").as_deref()),
+                (None, Some("1"))
+            );
+        }
+        {
+            let _in = b.enter(&ctx).unwrap();
+            assert_eq!(
+                (env.get("PORT").as_deref(), env.get("X")),
+                (Some("80"), None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_grants_and_bad_params() {
+        let s = DaemonSession::open(
+            "sess-1".into(),
+            &serde_json::json!({ "grants": ["call:read_file", "probe:*"] }),
+        )
+        .unwrap();
+        assert!(s.permits("call:read_file"));
+        assert!(s.permits("probe:usb"));
+        assert!(!s.permits("call:write_file"));
+        assert!(!s.permits("env_set"));
+        assert!(DaemonSession::open("x".into(), &Value::Null)
+            .unwrap()
+            .permits("env_set"));
+
+        let err = DaemonSession::open(
+            "x".into(),
+            &serde_json::json!({ "cwd": "/definitely/not/here" }),
+        )
+        .unwrap_err();
+        assert_eq!(err, "cwd /definitely/not/here is not a directory");
+        assert!(DaemonSession::open("x".into(), &serde_json::json!({ "grants": [1] })).is_err());
+    }
+}
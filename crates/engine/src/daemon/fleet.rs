@@ -0,0 +1,285 @@
+//! Fleet runs – one scenario on several `appctl serve` daemons at once
+//! (`appctl fleet run`), merged into a matrix of step statuses per target.
+//!
+//! Targets file:
+//!
+//! ```yaml
+//! targets:
+//!   - name: macos-14
+//!     socket: /tmp/appctl-macos.sock
+//!   - name: ubuntu-24.04
+//!     tcp: 10.0.0.7:7400
+//! ```
+
+use crate::client::DaemonClient;
+use crate::scenario::{plan, TagFilter};
+use crate::types::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize)]
+struct TargetsFile {
+    targets: Vec<FleetTarget>,
+}
+
+/// Parse a targets file: at least one target, unique names, and exactly
+/// one address each.
+pub fn load_targets(yaml: &str) -> Result<Vec<FleetTarget>, String> {
+    let file: TargetsFile =
+        serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse targets YAML: {}", e))?;
+    if file.targets.is_empty() {
+        return Err("no targets".into());
+    }
+    let mut seen = std::collections::BTreeSet::new();
+    for t in &file.targets {
+        if !seen.insert(t.name.as_str()) {
+            return Err(format!("duplicate target name {:?}", t.name));
+        }
+        if t.socket.is_some() == t.tcp.is_some() {
+            return Err(format!(
+                "target {:?} needs exactly one of socket or tcp",
+                t.name
+            ));
+        }
+    }
+    Ok(file.targets)
+}
+
+/// Run `scenario` on every target concurrently. A target that cannot be
+/// reached, does not answer within `timeout`, or whose `hello` lacks
+/// `run_scenario`, counts as an error.
+pub async fn run_fleet(
+    targets: &[FleetTarget],
+    scenario: &Scenario,
+    timeout: Option<Duration>,
+) -> FleetReport {
+    let params = serde_json::json!({ "scenario": scenario });
+    let runs = targets.iter().map(|t| run_target(t, &params, timeout));
+    let results = futures_util::future::join_all(runs).await;
+    report(scenario, results)
+}
+
+async fn run_target(
+    target: &FleetTarget,
+    params: &serde_json::Value,
+    timeout: Option<Duration>,
+) -> FleetTargetResult {
+    let started = Instant::now();
+    let exchange = request(target, params);
+    let response = match timeout {
+        Some(limit) => tokio::time::timeout(limit, exchange)
+            .await
+            .unwrap_or_else(|_| Err(format!("no answer within {}s", limit.as_secs()))),
+        None => exchange.await,
+    };
+    let mut r = FleetTargetResult {
+        target: target.name.clone(),
+        os: None,
+        arch: None,
+        status: Status::Error,
+        timing_ms: started.elapsed().as_millis() as u64,
+        error: None,
+        result: None,
+    };
+    match response.and_then(scenario_result) {
+        Ok((reply, result)) => {
+            r.os = Some(reply.env_summary.os);
+            r.arch = Some(reply.env_summary.arch);
+            r.status = result.overall_status;
+            r.result = Some(result);
+        }
+        Err(e) => r.error = Some(e),
+    }
+    r
+}
+
+/// The command result a daemon answered with and the scenario result in it.
+fn scenario_result(response: DaemonResponse) -> Result<(CommandResult, ScenarioResult), String> {
+    if let Some(e) = response.error {
+        return Err(e.message);
+    }
+    let mut reply = response
+        .result
+        .ok_or("response has neither result nor error")?;
+    if let Some(e) = reply.error.take() {
+        return Err(format!("{}: {}", e.code, e.message));
+    }
+    let data = reply.data.take().ok_or("result has no scenario data")?;
+    let result = serde_json::from_value(data).map_err(|e| format!("bad scenario result: {}", e))?;
+    Ok((reply, result))
+}
+
+async fn request(
+    target: &FleetTarget,
+    params: &serde_json::Value,
+) -> Result<DaemonResponse, String> {
+    let mut client = DaemonClient::connect(target.socket.as_deref(), target.tcp.as_deref())
+        .await?
+        .accept_gzip();
+    if let Some(hello) = client.hello().await? {
+        if !hello.supports("run_scenario") {
+            return Err(format!(
+                "daemon (engine {}, protocol {}) does not support run_scenario",
+                hello.engine.engine_version, hello.protocol_version
+            ));
+        }
+    }
+    client.request("run_scenario", params.clone()).await
+}
+
+/// Merge per-target results into a report; `results` are in targets order.
+pub fn report(scenario: &Scenario, results: Vec<FleetTargetResult>) -> FleetReport {
+    let matrix = plan(scenario, &TagFilter::default())
+        .steps
+        .into_iter()
+        .map(|step| FleetMatrixRow {
+            step: step.index,
+            label: step.label,
+            statuses: results
+                .iter()
+                .map(|r| {
+                    let run = r.result.as_ref()?;
+                    run.step_results.get(step.index).map(|s| s.status)
+                })
+                .collect(),
+        })
+        .collect();
+
+    let mut by_os: BTreeMap<String, Status> = BTreeMap::new();
+    for r in &results {
+        let os = r.os.clone().unwrap_or_else(|| "unknown".into());
+        let combined = by_os.entry(os).or_insert(Status::Skip);
+        *combined = combine(*combined, r.status);
+    }
+    let overall_status = results.iter().map(|r| r.status).fold(Status::Skip, combine);
+
+    FleetReport {
+        scenario: scenario.name.clone(),
+        overall_status,
+        targets: results,
+        by_os,
+        matrix,
+    }
+}
+
+/// Fail if either failed or errored, else pass if either passed.
+fn combine(a: Status, b: Status) -> Status {
+    match (a, b) {
+        (Status::Fail | Status::Error, _) | (_, Status::Fail | Status::Error) => Status::Fail,
+        (Status::Pass, _) | (_, Status::Pass) => Status::Pass,
+        _ => Status::Skip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::context::AppContext;
+    use crate::probes::ProbeRegistry;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    #[test]
+    fn test_load_targets_validates_addresses() {
+        let ok = load_targets(
+            "targets:\n  - {name: a, socket: /tmp/a.sock}\n  - {name: b, tcp: 'h:1'}\n",
+        )
+        .unwrap();
+        assert_eq!(ok.len(), 2);
+        assert!(load_targets("targets: []").is_err());
+        assert!(load_targets("targets:\n  - {name: a}\n")
+            .unwrap_err()
+            .contains("exactly one"));
+        assert!(
+            load_targets("targets:\n  - {name: a, tcp: 'h:1'}\n  - {name: a, tcp: 'h:2'}\n")
+                .unwrap_err()
+                .contains("duplicate")
+        );
+    }
+
+    /// Answer one connection the way `appctl serve` would, leaving
+    /// `run_scenario` out of `hello` unless `scenarios`.
+    async fn fake_daemon(listener: tokio::net::TcpListener, scenarios: bool) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let ctx = Arc::new(AppContext::default_headless());
+        let (registry, probes) = (CommandRegistry::new(), ProbeRegistry::new());
+        while let Ok(Some(line)) = lines.next_line().await {
+            let req: DaemonRequest = serde_json::from_str(&line).unwrap();
+            let mut result = match req.method.as_str() {
+                "hello" => crate::protocol::run_hello(&req.params, &ctx, &registry, &probes),
+                _ => crate::scenario::run_request(req.params, &ctx, &registry, &probes, None).await,
+            };
+            if let (false, Some(data)) = (scenarios, result.data.as_mut()) {
+                data["methods"] = serde_json::json!(["hello", "call"]);
+            }
+            let progress = r#"{"id":"1","progress":{"run_id":"x","topic":"t"}}"#;
+            let response = serde_json::to_string(&DaemonResponse {
+                id: req.id,
+                result: Some(result),
+                error: None,
+            })
+            .unwrap();
+            writer
+                .write_all(format!("{}\n{}\n", progress, response).as_bytes())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_fleet_builds_matrix() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(fake_daemon(listener, true));
+        let targets = vec![
+            FleetTarget {
+                name: "up".into(),
+                socket: None,
+                tcp: Some(addr),
+            },
+            FleetTarget {
+                name: "down".into(),
+                socket: None,
+                tcp: Some("127.0.0.1:1".into()),
+            },
+        ];
+        let scenario = crate::scenario::load_scenario(
+            "name: fleet\nsteps:\n  - call: ping\n  - call: nope\n    expect_status: error\n",
+        )
+        .unwrap();
+
+        let report = run_fleet(&targets, &scenario, Some(Duration::from_secs(5))).await;
+        assert_eq!(report.targets[0].status, Status::Pass);
+        assert_eq!(report.targets[0].os.as_deref(), Some(current_os()));
+        assert_eq!(report.targets[1].status, Status::Error);
+        assert!(report.targets[1].error.is_some());
+        assert_eq!(report.overall_status, Status::Fail);
+        assert_eq!(report.by_os.get("unknown"), Some(&Status::Fail));
+        assert_eq!(report.matrix.len(), 2);
+        assert_eq!(report.matrix[0].statuses, vec![Some(Status::Pass), None]);
+        assert_eq!(report.matrix[1].statuses, vec![Some(Status::Error), None]);
+    }
+
+    #[tokio::test]
+    async fn test_run_fleet_checks_hello_for_run_scenario() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(fake_daemon(listener, false));
+        let targets = vec![FleetTarget {
+            name: "old".into(),
+            socket: None,
+            tcp: Some(addr),
+        }];
+        let scenario =
+            crate::scenario::load_scenario("name: fleet\nsteps:\n  - call: ping\n").unwrap();
+
+        let report = run_fleet(&targets, &scenario, Some(Duration::from_secs(5))).await;
+        assert_eq!(report.targets[0].status, Status::Error);
+        let error = report.targets[0].error.as_deref().unwrap();
+        assert!(error.contains("does not support run_scenario"), "{}", error);
+    }
+}
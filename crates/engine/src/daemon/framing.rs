@@ -0,0 +1,235 @@
+//! Line framing for the daemon protocol: size limits and optional gzip.
+//!
+//! Frames are newline-delimited JSON. A request may set
+//! `"accept_encoding": "gzip"`; frames for it larger than
+//! [`COMPRESS_THRESHOLD`] are then sent as a [`CompressedFrame`] – the
+//! frame's JSON, gzipped and base64-encoded so it stays one line. Clients
+//! may send requests the same way. Requests longer than the daemon's limit
+//! (before or after decompression) are refused without being buffered.
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// Environment variable overriding [`DEFAULT_MAX_REQUEST_BYTES`].
+pub const MAX_REQUEST_BYTES_ENV: &str = "APP__MAX_REQUEST_BYTES";
+pub const DEFAULT_MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+
+/// Frames up to this size are sent as plain JSON even when gzip is accepted.
+pub const COMPRESS_THRESHOLD: usize = 64 * 1024;
+
+pub const GZIP: &str = "gzip";
+
+/// `$APP__MAX_REQUEST_BYTES`, else [`DEFAULT_MAX_REQUEST_BYTES`].
+pub fn max_request_bytes_from_env() -> usize {
+    std::env::var(MAX_REQUEST_BYTES_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_REQUEST_BYTES)
+}
+
+/// A frame sent compressed: `payload` is the base64 of the gzipped JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompressedFrame {
+    pub id: String,
+    pub encoding: String,
+    pub payload: String,
+}
+
+/// One line read by [`read_frame`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Frame {
+    Line(String),
+    /// The line was longer than allowed; holds its length. Its bytes were
+    /// discarded as they arrived.
+    TooLong(usize),
+}
+
+/// Read the next line, without buffering more than `max` bytes of it.
+/// `None` at end of stream.
+pub async fn read_frame<R>(reader: &mut R, max: usize) -> std::io::Result<Option<Frame>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    let mut len = 0usize;
+    loop {
+        let chunk = reader.fill_buf().await?;
+        if chunk.is_empty() {
+            return Ok(match len {
+                0 => None,
+                _ => Some(finish(line, len, max)),
+            });
+        }
+        let (take, done) = match chunk.iter().position(|&b| b == b'\n') {
+            Some(i) => (i, true),
+            None => (chunk.len(), false),
+        };
+        len += take;
+        if len <= max {
+            line.extend_from_slice(&chunk[..take]);
+        } else {
+            line = Vec::new();
+        }
+        reader.consume(take + done as usize);
+        if done {
+            return Ok(Some(finish(line, len, max)));
+        }
+    }
+}
+
+fn finish(line: Vec<u8>, len: usize, max: usize) -> Frame {
+    if len > max {
+        return Frame::TooLong(len);
+    }
+    let mut text = String::from_utf8_lossy(&line).into_owned();
+    if text.ends_with('\r') {
+        text.pop();
+    }
+    Frame::Line(text)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FrameError {
+    #[error("{0}")]
+    TooLarge(String),
+    #[error("bad compressed frame: {0}")]
+    Invalid(String),
+}
+
+impl FrameError {
+    pub fn code(&self) -> crate::types::ErrorCode {
+        match self {
+            FrameError::TooLarge(_) => crate::types::ErrorCode::ResourceExhausted,
+            FrameError::Invalid(_) => crate::types::ErrorCode::InvalidInput,
+        }
+    }
+}
+
+/// `frame` as one line of JSON (without the newline), compressed if `gzip`,
+/// it is over [`COMPRESS_THRESHOLD`], and compressing makes it smaller
+/// (base64 content barely compresses).
+pub fn encode<T: Serialize>(frame: &T, id: &str, gzip: bool) -> String {
+    let json = serde_json::to_string(frame).unwrap_or_else(|_| "{}".into());
+    if !gzip || json.len() <= COMPRESS_THRESHOLD {
+        return json;
+    }
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    let compressed = encoder
+        .write_all(json.as_bytes())
+        .and_then(|_| encoder.finish());
+    let envelope = compressed.ok().and_then(|bytes| {
+        serde_json::to_string(&CompressedFrame {
+            id: id.to_string(),
+            encoding: GZIP.into(),
+            payload: base64::engine::general_purpose::STANDARD.encode(bytes),
+        })
+        .ok()
+    });
+    match envelope {
+        Some(envelope) if envelope.len() < json.len() => envelope,
+        _ => json,
+    }
+}
+
+/// The JSON a line carries: the line itself, or a [`CompressedFrame`]'s
+/// content, which may decompress to at most `max` bytes.
+pub fn decode(line: &str, max: usize) -> Result<String, FrameError> {
+    let Ok(frame) = serde_json::from_str::<CompressedFrame>(line) else {
+        return Ok(line.to_string());
+    };
+    if frame.encoding != GZIP {
+        return Err(FrameError::Invalid(format!(
+            "unsupported encoding {}",
+            frame.encoding
+        )));
+    }
+    let invalid = |e: &dyn std::fmt::Display| FrameError::Invalid(e.to_string());
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(frame.payload.as_bytes())
+        .map_err(|e| invalid(&e))?;
+    let mut json = Vec::new();
+    flate2::read::GzDecoder::new(bytes.as_slice())
+        .take(max as u64 + 1)
+        .read_to_end(&mut json)
+        .map_err(|e| invalid(&e))?;
+    if json.len() > max {
+        return Err(FrameError::TooLarge(format!(
+            "frame decompresses to over {} bytes",
+            max
+        )));
+    }
+    String::from_utf8(json).map_err(|e| invalid(&e))
+}
+
+/// Whether the request in `line` accepts gzip-compressed replies.
+pub fn accepts_gzip(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| v.get("accept_encoding")?.as_str().map(|e| e == GZIP))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_frame_discards_overlong_lines() {
+        let input = format!("{}\nshort\r\n{}", "x".repeat(100), "tail");
+        let mut reader = tokio::io::BufReader::with_capacity(8, input.as_bytes());
+        assert_eq!(
+            read_frame(&mut reader, 10).await.unwrap(),
+            Some(Frame::TooLong(100))
+        );
+        assert_eq!(
+            read_frame(&mut reader, 10).await.unwrap(),
+            Some(Frame::Line("short".into()))
+        );
+        assert_eq!(
+            read_frame(&mut reader, 10).await.unwrap(),
+            Some(Frame::Line("tail".into()))
+        );
+        assert_eq!(read_frame(&mut reader, 10).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_large_frames_round_trip_compressed() {
+        let big = serde_json::json!({ "id": "7", "content": "a".repeat(COMPRESS_THRESHOLD) });
+        let line = encode(&big, "7", true);
+        assert!(line.len() < COMPRESS_THRESHOLD / 10);
+        assert!(!line.contains('\n'));
+        let frame: CompressedFrame = serde_json::from_str(&line).unwrap();
+        assert_eq!((frame.id.as_str(), frame.encoding.as_str()), ("7", GZIP));
+
+        let json = decode(&line, DEFAULT_MAX_REQUEST_BYTES).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            big
+        );
+        let err = decode(&line, 1024).unwrap_err();
+        assert_eq!(err.code(), crate::types::ErrorCode::ResourceExhausted);
+        assert_eq!(err.to_string(), "frame decompresses to over 1024 bytes");
+
+        // Small frames, clients that didn't ask, and frames that don't
+        // shrink stay plain.
+        assert_eq!(encode(&big, "7", false), big.to_string());
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let noise: String = (0..COMPRESS_THRESHOLD * 2)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                char::from(b'0' + (state % 75) as u8)
+            })
+            .collect();
+        let noisy = serde_json::json!({ "content": noise });
+        assert_eq!(encode(&noisy, "8", true), noisy.to_string());
+        let small = r#"{"id":"1","method":"call","accept_encoding":"gzip"}"#;
+        assert_eq!(decode(small, 10).unwrap(), small);
+        assert!(accepts_gzip(small));
+    }
+}
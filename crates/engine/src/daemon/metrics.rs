@@ -0,0 +1,250 @@
+//! Prometheus metrics for `appctl serve`, so fleet monitoring can scrape
+//! harness daemons running inside test VMs: request counts and durations
+//! per method, error codes, and whether each probe passed last time.
+//!
+//! Rendered in the Prometheus text format by the daemon's `metrics` method
+//! and by `appctl serve --metrics-addr`.
+
+use crate::types::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Upper bounds of the request duration histogram, in seconds.
+const BUCKETS: [f64; 10] = [0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations at or below each of [`BUCKETS`].
+    counts: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    /// By (method, status).
+    requests: BTreeMap<(String, String), u64>,
+    durations: BTreeMap<String, Histogram>,
+    errors: BTreeMap<String, u64>,
+    /// Whether each probe passed on its last run.
+    probes: BTreeMap<String, bool>,
+}
+
+/// Counters for one daemon, shared by its connections and scrapers.
+pub struct DaemonMetrics {
+    counters: Mutex<Counters>,
+    /// Unix seconds when the daemon started.
+    started_at: u64,
+}
+
+impl DaemonMetrics {
+    pub fn new(started_at: u64) -> Self {
+        Self {
+            counters: Mutex::new(Counters::default()),
+            started_at,
+        }
+    }
+
+    /// Count one answered request. Requests that never reached a method
+    /// (bad JSON, unknown method) are counted under `invalid`.
+    pub fn observe(&self, method: &str, response: &DaemonResponse, seconds: f64) {
+        let (method, status, code) = match (&response.result, &response.error) {
+            (Some(r), _) => (
+                method,
+                status_label(r.status),
+                r.error.as_ref().map(|e| e.code),
+            ),
+            (None, e) => ("invalid", "error", e.as_ref().map(|e| e.code)),
+        };
+        let mut c = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        *c.requests
+            .entry((method.to_string(), status.to_string()))
+            .or_default() += 1;
+        let h = c.durations.entry(method.to_string()).or_default();
+        for (count, bound) in h.counts.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+        h.sum += seconds;
+        h.count += 1;
+        if let Some(code) = code {
+            *c.errors.entry(code.to_string()).or_default() += 1;
+        }
+
+        // Unknown probe names are input errors, not probe outcomes.
+        if let Some(r) = response.result.as_ref().filter(|r| r.command == "probe") {
+            match (r.status, code) {
+                (_, Some(ErrorCode::InvalidInput)) | (Status::Skip, _) => {}
+                (status, _) => {
+                    c.probes.insert(r.target.clone(), status == Status::Pass);
+                }
+            }
+        }
+    }
+
+    /// Everything in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let c = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        let header = |out: &mut String, name: &str, kind: &str, help: &str| {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} {}", name, help, name, kind);
+        };
+
+        header(
+            &mut out,
+            "appctl_start_time_seconds",
+            "gauge",
+            "Unix time the daemon started.",
+        );
+        let _ = writeln!(out, "appctl_start_time_seconds {}", self.started_at);
+
+        header(
+            &mut out,
+            "appctl_requests_total",
+            "counter",
+            "Requests answered, by method and result status.",
+        );
+        for ((method, status), n) in &c.requests {
+            let _ = writeln!(
+                out,
+                "appctl_requests_total{{method=\"{}\",status=\"{}\"}} {}",
+                escape(method),
+                status,
+                n
+            );
+        }
+
+        header(
+            &mut out,
+            "appctl_request_duration_seconds",
+            "histogram",
+            "Time to answer a request, by method.",
+        );
+        for (method, h) in &c.durations {
+            let method = escape(method);
+            for (bound, n) in BUCKETS.iter().zip(h.counts) {
+                let _ = writeln!(
+                    out,
+                    "appctl_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    method, bound, n
+                );
+            }
+            let _ = writeln!(
+                out,
+                "appctl_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}\n\
+                 appctl_request_duration_seconds_sum{{method=\"{}\"}} {}\n\
+                 appctl_request_duration_seconds_count{{method=\"{}\"}} {}",
+                method, h.count, method, h.sum, method, h.count
+            );
+        }
+
+        header(
+            &mut out,
+            "appctl_errors_total",
+            "counter",
+            "Error results, by error code.",
+        );
+        for (code, n) in &c.errors {
+            let _ = writeln!(out, "appctl_errors_total{{code=\"{}\"}} {}", code, n);
+        }
+
+        header(
+            &mut out,
+            "appctl_probe_passing",
+            "gauge",
+            "1 if the probe passed on its last run, 0 if it failed or errored.",
+        );
+        for (probe, passing) in &c.probes {
+            let _ = writeln!(
+                out,
+                "appctl_probe_passing{{probe=\"{}\"}} {}",
+                escape(probe),
+                u8::from(*passing)
+            );
+        }
+        out
+    }
+}
+
+fn status_label(status: Status) -> &'static str {
+    match status {
+        Status::Pass => "pass",
+        Status::Fail => "fail",
+        Status::Skip => "skip",
+        Status::Error => "error",
+    }
+}
+
+/// Escape a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(result: CommandResult) -> DaemonResponse {
+        DaemonResponse {
+            id: "1".into(),
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_render_counts_requests_errors_and_probes() {
+        let m = DaemonMetrics::new(1_700_000_000);
+        m.observe("call", &response(result_ok("call", "ping", "r1", 0)), 0.002);
+        m.observe("call", &response(result_ok("call", "ping", "r2", 0)), 0.3);
+        let usb_failed = result_err("probe", "usb", "r3", 0, ErrorCode::IoError, "gone");
+        m.observe("probe", &response(result_ok("probe", "usb", "r4", 0)), 0.01);
+        m.observe("probe", &response(usb_failed), 0.01);
+        let unknown = result_err("probe", "nope", "r5", 0, ErrorCode::InvalidInput, "?");
+        m.observe("probe", &response(unknown), 0.0);
+        m.observe(
+            "bogus",
+            &DaemonResponse {
+                id: "2".into(),
+                result: None,
+                error: Some(ErrorInfo {
+                    code: ErrorCode::InvalidInput,
+                    message: "unknown method".into(),
+                    details: serde_json::Value::Null,
+                }),
+            },
+            0.0,
+        );
+
+        let text = m.render();
+        for line in [
+            "appctl_start_time_seconds 1700000000",
+            "appctl_requests_total{method=\"call\",status=\"pass\"} 2",
+            "appctl_requests_total{method=\"probe\",status=\"error\"} 2",
+            "appctl_requests_total{method=\"invalid\",status=\"error\"} 1",
+            "appctl_request_duration_seconds_bucket{method=\"call\",le=\"0.005\"} 1",
+            "appctl_request_duration_seconds_bucket{method=\"call\",le=\"0.5\"} 2",
+            "appctl_request_duration_seconds_count{method=\"call\"} 2",
+            "appctl_errors_total{code=\"INVALID_INPUT\"} 2",
+            "appctl_errors_total{code=\"IO_ERROR\"} 1",
+            "appctl_probe_passing{probe=\"usb\"} 0",
+            "# TYPE appctl_request_duration_seconds histogram",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing {line:?} in\n{text}"
+            );
+        }
+        assert!(!text.contains("probe=\"nope\""));
+    }
+
+    #[test]
+    fn test_escape_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -0,0 +1,9 @@
+//! The `appctl serve` daemon's protocol and its clients: framing, the
+//! handshake, per-connection sessions, metrics, and fleet runs.
+
+pub mod client;
+pub mod daemon_sessions;
+pub mod fleet;
+pub mod framing;
+pub mod metrics;
+pub mod protocol;
@@ -0,0 +1,177 @@
+//! Daemon protocol handshake.
+//!
+//! A client sends `hello` first, with the newest protocol version it speaks
+//! (`{"protocol_version": 2}`), to learn what it is talking to: the version
+//! both sides will use, the engine build, the optional protocol features,
+//! and the methods, commands, and probes the daemon has. It can then skip
+//! or work around what is missing instead of finding out from an
+//! `unknown method` error halfway through.
+
+use crate::build_info::BuildInfo;
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+
+/// Bumped when a change to the frames would break existing clients.
+/// 2: `hello` negotiates the version and lists methods, commands, and probes.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The oldest client protocol version the daemon still answers.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional protocol features, as named in `hello`.
+pub const CAPABILITIES: &[&str] = &[
+    // Compressed frames (see `crate::framing`).
+    "gzip",
+    // Length-prefixed blobs ahead of a response (see `crate::blobs`).
+    "binary_frames",
+    "progress",
+    "subscribe",
+    "sessions",
+];
+
+/// Every method `appctl serve` answers.
+pub const METHODS: &[&str] = &[
+    "hello",
+    "call",
+    "probe",
+    "doctor",
+    "compatibility",
+    "metrics",
+    "env_set",
+    "subscribe",
+    "unsubscribe",
+    "open_session",
+    "close_session",
+    "run_scenario",
+    "ready_check",
+    "llm_complete",
+    "llm_stream",
+    "update_check",
+    "update_download",
+];
+
+/// The `data` of a `hello` result.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hello {
+    /// The version both sides use: the lower of the client's and the
+    /// daemon's.
+    pub protocol_version: u32,
+    pub min_protocol_version: u32,
+    pub engine: BuildInfo,
+    pub capabilities: Vec<String>,
+    pub methods: Vec<String>,
+    pub commands: Vec<String>,
+    pub probes: Vec<String>,
+}
+
+impl Hello {
+    /// Whether the daemon answers `method`. Version 1 daemons did not list
+    /// their methods, so against those this is always true.
+    pub fn supports(&self, method: &str) -> bool {
+        self.methods.is_empty() || self.methods.iter().any(|m| m == method)
+    }
+
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+/// The version to use with a client that speaks up to `client`, or why
+/// there is none.
+pub fn negotiate(client: u32) -> Result<u32, String> {
+    if client < MIN_PROTOCOL_VERSION {
+        return Err(format!(
+            "protocol version {} is older than the oldest this daemon speaks ({})",
+            client, MIN_PROTOCOL_VERSION
+        ));
+    }
+    Ok(client.min(PROTOCOL_VERSION))
+}
+
+/// Daemon method `hello`: params `{"protocol_version": N}`, the newest
+/// version the client speaks (this daemon's own if absent).
+pub fn run_hello(
+    params: &serde_json::Value,
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let client = params["protocol_version"]
+        .as_u64()
+        .map_or(PROTOCOL_VERSION, |v| v.min(u32::MAX as u64) as u32);
+    let protocol_version = match negotiate(client) {
+        Ok(v) => v,
+        Err(e) => return result_err("hello", "daemon", &run_id, 0, ErrorCode::Unsupported, e),
+    };
+    let hello = Hello {
+        protocol_version,
+        min_protocol_version: MIN_PROTOCOL_VERSION,
+        engine: crate::build_info::current(),
+        capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        methods: METHODS.iter().map(|m| m.to_string()).collect(),
+        commands: registry.list().into_iter().map(String::from).collect(),
+        probes: probes.list().into_iter().map(|p| p.name.clone()).collect(),
+    };
+    let mut r = result_ok("hello", "daemon", &run_id, 0);
+    r.data = serde_json::to_value(hello).ok();
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(params: serde_json::Value) -> CommandResult {
+        run_hello(
+            &params,
+            &AppContext::default_headless(),
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+    }
+
+    #[test]
+    fn test_hello_reports_version_and_capabilities() {
+        let data = hello(serde_json::Value::Null).data.unwrap();
+        assert_eq!(data["protocol_version"], PROTOCOL_VERSION);
+        assert_eq!(data["engine"]["engine_version"], env!("CARGO_PKG_VERSION"));
+        let hello: Hello = serde_json::from_value(data).unwrap();
+        assert!(hello.has_capability("subscribe"));
+        assert!(hello.supports("run_scenario"));
+        assert!(!hello.supports("teleport"));
+        assert!(hello.commands.iter().any(|c| c == "ping"));
+        assert!(hello.probes.iter().any(|p| p == "filesystem"));
+    }
+
+    #[test]
+    fn test_hello_negotiates_the_lower_version() {
+        let data = hello(serde_json::json!({ "protocol_version": 1 }))
+            .data
+            .unwrap();
+        assert_eq!(data["protocol_version"], 1);
+        let data = hello(serde_json::json!({ "protocol_version": 99 }))
+            .data
+            .unwrap();
+        assert_eq!(data["protocol_version"], PROTOCOL_VERSION);
+
+        let r = hello(serde_json::json!({ "protocol_version": 0 }));
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.error.unwrap().code, ErrorCode::Unsupported);
+    }
+
+    #[test]
+    fn test_version_one_hello_supports_everything() {
+        let v1: Hello = serde_json::from_value(serde_json::json!({
+            "protocol_version": 1,
+            "capabilities": ["gzip"],
+        }))
+        .unwrap();
+        assert!(v1.supports("run_scenario"));
+        assert!(!v1.has_capability("sessions"));
+    }
+}
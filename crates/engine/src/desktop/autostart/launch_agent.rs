@@ -0,0 +1,98 @@
+//! macOS: a LaunchAgent plist in `~/Library/LaunchAgents/<app_id>.plist`.
+
+use super::{check_app_id, read_optional, remove_optional, write_entry};
+use crate::traits::{AutostartEntry, AutostartOps, AutostartStatus, CapResult};
+use std::path::PathBuf;
+
+/// `<dir>/<app_id>.plist` with `RunAtLoad`; launchd picks it up at the next
+/// login.
+pub struct LaunchAgent {
+    dir: PathBuf,
+}
+
+impl LaunchAgent {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, app_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.plist", app_id))
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub(super) fn launch_agent_plist(entry: &AutostartEntry) -> String {
+    let mut program = format!(
+        "        <string>{}</string>\n",
+        xml_escape(&entry.exec.to_string_lossy())
+    );
+    for arg in &entry.args {
+        program.push_str(&format!("        <string>{}</string>\n", xml_escape(arg)));
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        xml_escape(&entry.app_id),
+        program
+    )
+}
+
+/// First `ProgramArguments` string of a plist written by [`launch_agent_plist`].
+fn launch_agent_exec(plist: &str) -> Option<PathBuf> {
+    let rest = &plist[plist.find("<key>ProgramArguments</key>")?..];
+    let start = rest.find("<string>")? + "<string>".len();
+    let end = rest[start..].find("</string>")? + start;
+    Some(PathBuf::from(xml_unescape(&rest[start..end])))
+}
+
+impl AutostartOps for LaunchAgent {
+    fn enable(&self, entry: &AutostartEntry) -> CapResult<AutostartStatus> {
+        check_app_id(&entry.app_id)?;
+        write_entry(&self.path(&entry.app_id), &launch_agent_plist(entry))?;
+        self.status(&entry.app_id)
+    }
+
+    fn disable(&self, app_id: &str) -> CapResult<AutostartStatus> {
+        check_app_id(app_id)?;
+        remove_optional(&self.path(app_id))?;
+        self.status(app_id)
+    }
+
+    fn status(&self, app_id: &str) -> CapResult<AutostartStatus> {
+        check_app_id(app_id)?;
+        let path = self.path(app_id);
+        let exec = read_optional(&path)?.and_then(|plist| launch_agent_exec(&plist));
+        Ok(AutostartStatus {
+            enabled: exec.is_some(),
+            mechanism: "launch_agent".into(),
+            path,
+            exec,
+        })
+    }
+}
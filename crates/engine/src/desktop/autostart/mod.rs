@@ -0,0 +1,280 @@
+//! Launch at login – [`AutostartOps`] implementations and commands.
+//!
+//! - macOS: a LaunchAgent plist in `~/Library/LaunchAgents/<app_id>.plist`
+//! - Linux: an XDG autostart entry in `$XDG_CONFIG_HOME/autostart/<app_id>.desktop`,
+//!   or (with `APP__AUTOSTART=systemd`) a systemd user unit enabled for
+//!   `default.target`
+//!
+//! Every implementation reads its entry back from disk for `status`, so the
+//! `autostart` probe can check that an entry was really installed rather
+//! than trusting the write.
+
+mod launch_agent;
+mod systemd;
+mod xdg;
+
+pub use launch_agent::LaunchAgent;
+pub use systemd::SystemdUser;
+pub use xdg::XdgAutostart;
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{AutostartEntry, AutostartOps, AutostartStatus, CapError, CapResult};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Environment variable choosing the Linux mechanism: `xdg` (default) or
+/// `systemd`.
+pub const MECHANISM_ENV: &str = "APP__AUTOSTART";
+
+/// Pick the implementation for this OS (and [`MECHANISM_ENV`] on Linux).
+pub fn platform_default() -> Box<dyn AutostartOps> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Box::new(UnsupportedAutostart("HOME is not set".into()));
+    };
+    if cfg!(target_os = "macos") {
+        return Box::new(LaunchAgent::new(home.join("Library/LaunchAgents")));
+    }
+    if !cfg!(target_os = "linux") {
+        return Box::new(UnsupportedAutostart(format!(
+            "autostart not implemented for {}",
+            std::env::consts::OS
+        )));
+    }
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home.join(".config"));
+    match std::env::var(MECHANISM_ENV).as_deref() {
+        Ok("systemd") => Box::new(SystemdUser::new(config_home.join("systemd/user"))),
+        Ok("xdg") | Ok("") | Err(_) => Box::new(XdgAutostart::new(config_home.join("autostart"))),
+        Ok(other) => Box::new(UnsupportedAutostart(format!(
+            "unknown {} mechanism '{}' (expected xdg or systemd)",
+            MECHANISM_ENV, other
+        ))),
+    }
+}
+
+fn check_app_id(app_id: &str) -> CapResult<()> {
+    let valid = !app_id.is_empty()
+        && app_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(CapError::Other(format!("invalid app id '{}'", app_id)))
+    }
+}
+
+fn read_optional(path: &Path) -> CapResult<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => Ok(Some(s)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn remove_optional(path: &Path) -> CapResult<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn write_entry(path: &Path, contents: &str) -> CapResult<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// First argument of a command line where arguments may be double-quoted
+/// with backslash escapes (desktop `Exec=`, systemd `ExecStart=`).
+fn first_arg(line: &str) -> Option<String> {
+    let line = line.trim_start();
+    let Some(quoted) = line.strip_prefix('"') else {
+        return line.split_whitespace().next().map(String::from);
+    };
+    let mut out = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?),
+            '"' => return Some(out),
+            c => out.push(c),
+        }
+    }
+    None
+}
+
+fn quote_arg(arg: &str, escape: &[char]) -> String {
+    let mut out = String::from("\"");
+    for c in arg.chars() {
+        if escape.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+// ===========================================================================
+// Unsupported
+// ===========================================================================
+
+/// Used where no mechanism is available; every call is UNSUPPORTED.
+pub struct UnsupportedAutostart(pub String);
+
+impl AutostartOps for UnsupportedAutostart {
+    fn enable(&self, _entry: &AutostartEntry) -> CapResult<AutostartStatus> {
+        Err(CapError::Unsupported(self.0.clone()))
+    }
+    fn disable(&self, _app_id: &str) -> CapResult<AutostartStatus> {
+        Err(CapError::Unsupported(self.0.clone()))
+    }
+    fn status(&self, _app_id: &str) -> CapResult<AutostartStatus> {
+        Err(CapError::Unsupported(self.0.clone()))
+    }
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+fn status_json(status: AutostartStatus) -> Value {
+    serde_json::to_value(status).unwrap_or_default()
+}
+
+/// `autostart_enable` – launch this app at login.
+///
+/// Args: `{ "exec"?: "/path/to/app", "args"?: ["--minimized"] }` (default:
+/// the running executable, no args)
+/// Returns: `{ "enabled": true, "mechanism": "xdg", "path": "...", "exec": "..." }`
+pub(crate) fn cmd_autostart_enable(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let exec = match args.get("exec") {
+        None | Some(Value::Null) => std::env::current_exe()?,
+        Some(v) => v
+            .as_str()
+            .map(PathBuf::from)
+            .ok_or_else(|| CommandError::InvalidInput("'exec' must be a string".into()))?,
+    };
+    let extra: Vec<String> = match args.get("args") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(v) => serde_json::from_value(v.clone())
+            .map_err(|_| CommandError::InvalidInput("'args' must be an array of strings".into()))?,
+    };
+    let entry = AutostartEntry {
+        app_id: ctx.app_id.clone(),
+        name: ctx.app_name.clone(),
+        exec,
+        args: extra,
+    };
+    Ok(status_json(ctx.autostart().enable(&entry)?))
+}
+
+/// `autostart_disable` – remove the login entry (no-op if absent).
+///
+/// Args: `{}`
+/// Returns: the resulting status, as for `autostart_status`.
+pub(crate) fn cmd_autostart_disable(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    Ok(status_json(ctx.autostart().disable(&ctx.app_id)?))
+}
+
+/// `autostart_status` – whether a login entry is installed for this app.
+///
+/// Args: `{}`
+/// Returns: `{ "enabled": false, "mechanism": "xdg", "path": "...", "exec": null }`
+pub(crate) fn cmd_autostart_status(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    Ok(status_json(ctx.autostart().status(&ctx.app_id)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::types::{ErrorCode, Status};
+    use launch_agent::launch_agent_plist;
+    use systemd::systemd_unit;
+    use xdg::{desktop_entry, desktop_exec};
+
+    fn entry(exec: &str, args: &[&str]) -> AutostartEntry {
+        AutostartEntry {
+            app_id: "com.example.test".into(),
+            name: "Test App".into(),
+            exec: PathBuf::from(exec),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_each_mechanism_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backends: Vec<Box<dyn AutostartOps>> = vec![
+            Box::new(LaunchAgent::new(tmp.path().join("agents"))),
+            Box::new(XdgAutostart::new(tmp.path().join("autostart"))),
+            Box::new(SystemdUser::new(tmp.path().join("systemd"))),
+        ];
+        let e = entry("/opt/My App/bin/app", &["--flag", "100%"]);
+        for ops in backends {
+            assert!(!ops.status(&e.app_id).unwrap().enabled);
+
+            let status = ops.enable(&e).unwrap();
+            assert!(status.enabled, "{}", status.mechanism);
+            assert!(status.path.exists());
+            assert_eq!(status.exec.as_deref(), Some(e.exec.as_path()));
+
+            let status = ops.disable(&e.app_id).unwrap();
+            assert!(!status.enabled);
+            assert!(!status.path.exists());
+            // Disabling again is fine
+            assert!(ops.disable(&e.app_id).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_entry_formats() {
+        let e = entry("/usr/bin/app", &["a \"b\""]);
+        assert!(launch_agent_plist(&e).contains("<string>a &quot;b&quot;</string>"));
+        assert!(desktop_entry(&e).contains(r#"Exec="/usr/bin/app" "a \\"b\\"""#));
+        assert!(systemd_unit(&e).contains("WantedBy=default.target"));
+        assert_eq!(
+            desktop_exec("[Desktop Entry]\nExec=/bin/x\nHidden=true\n"),
+            None
+        );
+        assert!(check_app_id("../escape").is_err());
+    }
+
+    #[test]
+    fn test_autostart_commands() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = AppContext::default_headless()
+            .with_autostart(Box::new(XdgAutostart::new(tmp.path().to_path_buf())));
+        let reg = CommandRegistry::new();
+
+        let r = reg.execute(
+            "autostart_enable",
+            serde_json::json!({ "exec": "/usr/bin/app", "args": ["--hidden"] }),
+            &ctx,
+        );
+        let data = r.data.unwrap();
+        assert_eq!(data["enabled"], true);
+        assert_eq!(data["exec"], "/usr/bin/app");
+
+        let r = reg.execute("autostart_status", serde_json::json!({}), &ctx);
+        assert_eq!(r.data.unwrap()["mechanism"], "xdg");
+
+        let r = reg.execute("autostart_disable", serde_json::json!({}), &ctx);
+        assert_eq!(r.data.unwrap()["enabled"], false);
+
+        let bad = reg.execute("autostart_enable", serde_json::json!({ "args": "x" }), &ctx);
+        assert_eq!(bad.error.unwrap().code, ErrorCode::InvalidInput);
+
+        let ctx = ctx.with_autostart(Box::new(UnsupportedAutostart("nope".into())));
+        let r = reg.execute("autostart_status", serde_json::json!({}), &ctx);
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.error.unwrap().code, ErrorCode::Unsupported);
+    }
+}
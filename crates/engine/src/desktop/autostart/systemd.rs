@@ -0,0 +1,100 @@
+//! Linux: a systemd user unit enabled for `default.target`.
+
+use super::{check_app_id, first_arg, quote_arg, read_optional, remove_optional, write_entry};
+use crate::traits::{AutostartEntry, AutostartOps, AutostartStatus, CapError, CapResult};
+use std::path::{Path, PathBuf};
+
+/// `<dir>/<app_id>.service`, enabled by linking it into
+/// `<dir>/default.target.wants/` – what `systemctl --user enable` does,
+/// without needing a running user manager. Takes effect at the next login.
+pub struct SystemdUser {
+    dir: PathBuf,
+}
+
+impl SystemdUser {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn unit(&self, app_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.service", app_id))
+    }
+
+    fn wants_link(&self, app_id: &str) -> PathBuf {
+        self.dir
+            .join("default.target.wants")
+            .join(format!("{}.service", app_id))
+    }
+}
+
+pub(super) fn systemd_unit(entry: &AutostartEntry) -> String {
+    let exec: Vec<String> = std::iter::once(entry.exec.to_string_lossy().into_owned())
+        .chain(entry.args.iter().cloned())
+        .map(|a| {
+            quote_arg(&a, &['"', '\\'])
+                .replace('%', "%%")
+                .replace('$', "$$")
+        })
+        .collect();
+    format!(
+        "[Unit]\nDescription={}\n\n[Service]\nType=simple\nExecStart={}\n\n[Install]\nWantedBy=default.target\n",
+        entry.name.replace('\n', " "),
+        exec.join(" ")
+    )
+}
+
+fn systemd_exec(unit: &str) -> Option<PathBuf> {
+    unit.lines()
+        .find_map(|l| l.strip_prefix("ExecStart="))
+        .and_then(|v| first_arg(&v.replace("%%", "%").replace("$$", "$")))
+        .map(PathBuf::from)
+}
+
+impl AutostartOps for SystemdUser {
+    fn enable(&self, entry: &AutostartEntry) -> CapResult<AutostartStatus> {
+        check_app_id(&entry.app_id)?;
+        let unit = self.unit(&entry.app_id);
+        write_entry(&unit, &systemd_unit(entry))?;
+        let link = self.wants_link(&entry.app_id);
+        if std::fs::symlink_metadata(&link).is_err() {
+            std::fs::create_dir_all(self.dir.join("default.target.wants"))?;
+            link_unit(&unit, &link)?;
+        }
+        self.status(&entry.app_id)
+    }
+
+    fn disable(&self, app_id: &str) -> CapResult<AutostartStatus> {
+        check_app_id(app_id)?;
+        remove_optional(&self.wants_link(app_id))?;
+        remove_optional(&self.unit(app_id))?;
+        self.status(app_id)
+    }
+
+    fn status(&self, app_id: &str) -> CapResult<AutostartStatus> {
+        check_app_id(app_id)?;
+        let path = self.unit(app_id);
+        let linked = std::fs::symlink_metadata(self.wants_link(app_id)).is_ok();
+        let exec = match read_optional(&path)? {
+            Some(unit) if linked => systemd_exec(&unit),
+            _ => None,
+        };
+        Ok(AutostartStatus {
+            enabled: exec.is_some(),
+            mechanism: "systemd".into(),
+            path,
+            exec,
+        })
+    }
+}
+
+#[cfg(unix)]
+fn link_unit(unit: &Path, link: &Path) -> CapResult<()> {
+    std::os::unix::fs::symlink(unit, link).map_err(CapError::from)
+}
+
+#[cfg(not(unix))]
+fn link_unit(_unit: &Path, _link: &Path) -> CapResult<()> {
+    Err(CapError::Unsupported(
+        "systemd units need a Unix host".into(),
+    ))
+}
@@ -0,0 +1,78 @@
+//! Linux: an XDG autostart entry in `$XDG_CONFIG_HOME/autostart/<app_id>.desktop`.
+
+use super::{check_app_id, first_arg, quote_arg, read_optional, remove_optional, write_entry};
+use crate::traits::{AutostartEntry, AutostartOps, AutostartStatus, CapResult};
+use std::path::PathBuf;
+
+/// `<dir>/<app_id>.desktop`, started by the desktop session at login.
+pub struct XdgAutostart {
+    dir: PathBuf,
+}
+
+impl XdgAutostart {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, app_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.desktop", app_id))
+    }
+}
+
+/// One `Exec=` argument: quoted per the desktop entry spec, with `%` doubled
+/// (field codes) and backslashes doubled again for the string value.
+fn desktop_exec_arg(arg: &str) -> String {
+    let quoted = quote_arg(arg, &['"', '`', '$', '\\']);
+    quoted.replace('\\', "\\\\").replace('%', "%%")
+}
+
+pub(super) fn desktop_entry(entry: &AutostartEntry) -> String {
+    let exec: Vec<String> = std::iter::once(entry.exec.to_string_lossy().into_owned())
+        .chain(entry.args.iter().cloned())
+        .map(|a| desktop_exec_arg(&a))
+        .collect();
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={}\nX-GNOME-Autostart-enabled=true\n",
+        entry.name.replace('\n', " "),
+        exec.join(" ")
+    )
+}
+
+/// Program from the `Exec=` line, unless the entry is marked `Hidden`.
+pub(super) fn desktop_exec(contents: &str) -> Option<PathBuf> {
+    let mut exec = None;
+    for line in contents.lines() {
+        match line.split_once('=') {
+            Some(("Hidden", v)) if v.trim() == "true" => return None,
+            Some(("Exec", v)) => exec = first_arg(&v.replace("\\\\", "\\").replace("%%", "%")),
+            _ => {}
+        }
+    }
+    exec.map(PathBuf::from)
+}
+
+impl AutostartOps for XdgAutostart {
+    fn enable(&self, entry: &AutostartEntry) -> CapResult<AutostartStatus> {
+        check_app_id(&entry.app_id)?;
+        write_entry(&self.path(&entry.app_id), &desktop_entry(entry))?;
+        self.status(&entry.app_id)
+    }
+
+    fn disable(&self, app_id: &str) -> CapResult<AutostartStatus> {
+        check_app_id(app_id)?;
+        remove_optional(&self.path(app_id))?;
+        self.status(app_id)
+    }
+
+    fn status(&self, app_id: &str) -> CapResult<AutostartStatus> {
+        check_app_id(app_id)?;
+        let path = self.path(app_id);
+        let exec = read_optional(&path)?.and_then(|d| desktop_exec(&d));
+        Ok(AutostartStatus {
+            enabled: exec.is_some(),
+            mechanism: "xdg".into(),
+            path,
+            exec,
+        })
+    }
+}
@@ -0,0 +1,283 @@
+//! `clipboard_watch`: poll the clipboard for a while and report every
+//! change, to test copy/paste integration and to catch clipboard managers
+//! rewriting what the app copied.
+//!
+//! Changes are detected through [`ClipboardOps::change_count`] where the
+//! platform has one (macOS), else by comparing the text and format list
+//! between polls. Only a hash and the length of the text are reported
+//! unless `include_text` is set, since clipboards often hold passwords.
+//! With `--artifacts`, the change list lands in the run's `result.json`,
+//! and a scenario step also saves it as `steps/<n>/changes.json`.
+
+use crate::commands::{is_dry_run, CommandError};
+use crate::context::AppContext;
+use crate::traits::ClipboardOps;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Longest watch a single call may ask for.
+pub const MAX_WATCH_MS: u64 = 5 * 60 * 1000;
+/// Shortest poll interval.
+pub const MIN_INTERVAL_MS: u64 = 50;
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WatchArgs {
+    duration_ms: u64,
+    interval_ms: u64,
+    write_marker: bool,
+    include_text: bool,
+}
+
+impl Default for WatchArgs {
+    fn default() -> Self {
+        Self {
+            duration_ms: 5000,
+            interval_ms: 250,
+            write_marker: false,
+            include_text: false,
+        }
+    }
+}
+
+/// What one poll saw.
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    change_count: Option<u64>,
+    text: Option<String>,
+    formats: Option<Vec<String>>,
+}
+
+fn snapshot(clipboard: &dyn ClipboardOps, by_count: bool) -> Snapshot {
+    Snapshot {
+        change_count: if by_count {
+            clipboard.change_count().ok()
+        } else {
+            None
+        },
+        text: clipboard.read_text().ok(),
+        formats: clipboard.available_formats().ok(),
+    }
+}
+
+impl Snapshot {
+    fn differs_from(&self, previous: &Snapshot) -> bool {
+        match (self.change_count, previous.change_count) {
+            (Some(now), Some(before)) => now != before,
+            _ => self.text != previous.text || self.formats != previous.formats,
+        }
+    }
+
+    fn describe(&self, at_ms: u64, include_text: bool) -> Value {
+        let text = self.text.as_deref();
+        let mut change = serde_json::json!({
+            "at_ms": at_ms,
+            "change_count": self.change_count,
+            "formats": self.formats,
+            "text_length": text.map(|t| t.chars().count()),
+            "text_sha256": text.map(|t| crate::export::hex(
+                ring::digest::digest(&ring::digest::SHA256, t.as_bytes()).as_ref(),
+            )),
+        });
+        if include_text {
+            change["text"] = text.into();
+        }
+        change
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// `clipboard_watch` – record clipboard changes for `duration_ms`.
+///
+/// Args: `{ "duration_ms"?: 5000, "interval_ms"?: 250, "write_marker"?: false,
+/// "include_text"?: false }`. With `write_marker` a unique text is copied
+/// first and any later change fails the call with `EXTERNAL_INTERFERENCE`:
+/// nothing else should have touched the clipboard. In a dry run that
+/// variant copies nothing and only reports the planned watch.
+/// Returns: `{ "method": "change_count" | "content", "duration_ms": 5000,
+/// "interval_ms": 250, "initial": {...}, "changes": [{"at_ms": 750,
+/// "change_count": 42, "formats": ["public.utf8-plain-text"],
+/// "text_length": 5, "text_sha256": "..."}] }`
+pub fn cmd_clipboard_watch(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let dry_run = is_dry_run(&args, ctx);
+    let args: WatchArgs = match args {
+        Value::Null => WatchArgs::default(),
+        mut args => {
+            if let Some(obj) = args.as_object_mut() {
+                obj.remove("dry_run");
+            }
+            serde_json::from_value(args).map_err(|e| CommandError::InvalidInput(e.to_string()))?
+        }
+    };
+    if args.duration_ms > MAX_WATCH_MS {
+        return Err(CommandError::InvalidInput(format!(
+            "'duration_ms' is above the {} ms maximum",
+            MAX_WATCH_MS
+        )));
+    }
+    let interval_ms = args.interval_ms.max(MIN_INTERVAL_MS);
+    if args.write_marker && dry_run {
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": "write_marker",
+            "duration_ms": args.duration_ms,
+            "interval_ms": interval_ms,
+        }));
+    }
+    let clipboard = ctx.clipboard();
+
+    if args.write_marker {
+        let marker = format!("appctl_clipboard_watch_{:016x}", ctx.random_u64());
+        clipboard.write_text(&marker)?;
+    }
+    let by_count = clipboard.change_count().is_ok();
+    if !by_count {
+        // Without a counter, at least the text must be readable.
+        clipboard.read_text()?;
+    }
+    let initial = snapshot(clipboard, by_count);
+
+    let mut previous = initial.clone();
+    let mut changes = Vec::new();
+    for poll in 1..=args.duration_ms / interval_ms {
+        std::thread::sleep(Duration::from_millis(interval_ms));
+        let now = snapshot(clipboard, by_count);
+        if now.differs_from(&previous) {
+            changes.push(now.describe(poll * interval_ms, args.include_text));
+            previous = now;
+        }
+    }
+
+    if args.write_marker && !changes.is_empty() {
+        return Err(CommandError::Conflict(format!(
+            "clipboard changed {} time(s) after the marker was copied, first at {} ms \
+             (formats: {}); a clipboard manager or another app is rewriting it",
+            changes.len(),
+            changes[0]["at_ms"],
+            changes[0]["formats"]
+        )));
+    }
+    Ok(serde_json::json!({
+        "method": if by_count { "change_count" } else { "content" },
+        "duration_ms": args.duration_ms,
+        "interval_ms": interval_ms,
+        "initial": initial.describe(0, args.include_text),
+        "changes": changes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+    use crate::traits::{CapError, CapResult};
+    use std::sync::{Arc, Mutex};
+
+    /// Text plus change counter, shared with the test thread that plays
+    /// "another app".
+    #[derive(Clone, Default)]
+    struct SharedClipboard {
+        state: Arc<Mutex<(String, u64)>>,
+        counts: bool,
+    }
+
+    impl ClipboardOps for SharedClipboard {
+        fn read_text(&self) -> CapResult<String> {
+            Ok(self.state.lock().unwrap().0.clone())
+        }
+        fn write_text(&self, text: &str) -> CapResult<()> {
+            let mut state = self.state.lock().unwrap();
+            *state = (text.to_string(), state.1 + 1);
+            Ok(())
+        }
+        fn change_count(&self) -> CapResult<u64> {
+            match self.counts {
+                true => Ok(self.state.lock().unwrap().1),
+                false => Err(CapError::Unsupported("no counter".into())),
+            }
+        }
+    }
+
+    /// Watch `clipboard` for 300 ms while `change` runs 100 ms in.
+    fn watch_while(
+        clipboard: SharedClipboard,
+        args: Value,
+        change: impl FnOnce(&SharedClipboard) + Send + 'static,
+    ) -> Result<Value, CommandError> {
+        let other = clipboard.clone();
+        let ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(MockNetwork::new()),
+            Box::new(clipboard),
+        );
+        let changer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            change(&other);
+        });
+        let mut full = serde_json::json!({ "duration_ms": 300, "interval_ms": 50 });
+        full.as_object_mut()
+            .unwrap()
+            .extend(args.as_object().unwrap().clone());
+        let out = cmd_clipboard_watch(full, &ctx);
+        changer.join().unwrap();
+        out
+    }
+
+    #[test]
+    fn test_watch_reports_content_changes_without_text() {
+        let out = watch_while(SharedClipboard::default(), serde_json::json!({}), |c| {
+            c.write_text("hunter2").unwrap()
+        })
+        .unwrap();
+        assert_eq!(out["method"], "content");
+        let changes = out["changes"].as_array().unwrap();
+        assert_eq!(changes.len(), 1, "{:?}", changes);
+        assert_eq!(changes[0]["text_length"], 7);
+        assert!(changes[0].get("text").is_none());
+    }
+
+    #[test]
+    fn test_watch_with_marker_flags_interference() {
+        // Same text, but the counter moved: something re-owned the clipboard.
+        let clipboard = SharedClipboard {
+            counts: true,
+            ..Default::default()
+        };
+        let r = watch_while(
+            clipboard,
+            serde_json::json!({ "write_marker": true }),
+            |c| c.state.lock().unwrap().1 += 1,
+        );
+        assert!(matches!(r, Err(CommandError::Conflict(_))), "{:?}", r);
+
+        let headless = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(MockNetwork::new()),
+            Box::new(HeadlessClipboard),
+        );
+        let r = cmd_clipboard_watch(Value::Null, &headless);
+        assert!(matches!(r, Err(CommandError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_marker_is_not_copied_in_a_dry_run() {
+        let clipboard = SharedClipboard::default();
+        let ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(MockNetwork::new()),
+            Box::new(clipboard.clone()),
+        );
+        let out = cmd_clipboard_watch(
+            serde_json::json!({ "write_marker": true, "dry_run": true }),
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(out["dry_run"], true);
+        assert_eq!(out["action"], "write_marker");
+        assert_eq!(*clipboard.state.lock().unwrap(), (String::new(), 0));
+    }
+}
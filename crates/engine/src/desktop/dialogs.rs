@@ -0,0 +1,127 @@
+//! Dialog commands – file open/save, confirmation, and message dialogs via
+//! [`AppContext::dialogs`].
+//!
+//! The GUI shows native dialogs; the CLI uses
+//! [`crate::platform::ScriptedDialogs`], which answers from the `dialogs:`
+//! list of the scenario step being run:
+//!
+//! ```yaml
+//! steps:
+//!   - call: dialog_open
+//!     dialogs:
+//!       - open: /tmp/input.txt
+//! ```
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::FileDialogOptions;
+use serde_json::Value;
+
+fn options(args: &Value) -> Result<FileDialogOptions, CommandError> {
+    match args {
+        Value::Null => Ok(FileDialogOptions::default()),
+        args => serde_json::from_value(args.clone())
+            .map_err(|e| CommandError::InvalidInput(format!("invalid dialog options: {}", e))),
+    }
+}
+
+fn text_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str, CommandError> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(""),
+        Some(v) => v
+            .as_str()
+            .ok_or_else(|| CommandError::InvalidInput(format!("'{}' must be a string", key))),
+    }
+}
+
+fn message_arg(args: &Value) -> Result<&str, CommandError> {
+    match text_arg(args, "message")? {
+        "" => Err(CommandError::InvalidInput(
+            "missing 'message' string field".into(),
+        )),
+        message => Ok(message),
+    }
+}
+
+/// `dialog_open` – ask the user to pick an existing file.
+///
+/// Args: `{ "title"?, "directory"?, "filters"?: [{ "name": "Text", "extensions": ["txt"] }] }`
+/// Returns: `{ "path": "/picked/file" | null, "cancelled": false }`
+pub(crate) fn cmd_dialog_open(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path = ctx.dialogs().open_file(&options(&args)?)?;
+    Ok(serde_json::json!({ "cancelled": path.is_none(), "path": path }))
+}
+
+/// `dialog_save` – ask the user where to save a file.
+///
+/// Args: `{ "title"?, "directory"?, "file_name"?, "filters"? }`
+/// Returns: `{ "path": "/chosen/file" | null, "cancelled": false }`
+pub(crate) fn cmd_dialog_save(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path = ctx.dialogs().save_file(&options(&args)?)?;
+    Ok(serde_json::json!({ "cancelled": path.is_none(), "path": path }))
+}
+
+/// `dialog_confirm` – yes/no question.
+///
+/// Args: `{ "title"?, "message": "Delete 3 files?" }`
+/// Returns: `{ "confirmed": true }`
+pub(crate) fn cmd_dialog_confirm(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let confirmed = ctx
+        .dialogs()
+        .confirm(text_arg(&args, "title")?, message_arg(&args)?)?;
+    Ok(serde_json::json!({ "confirmed": confirmed }))
+}
+
+/// `dialog_message` – informational message the user dismisses.
+///
+/// Args: `{ "title"?, "message": "Export finished" }`
+/// Returns: `{ "acknowledged": true }`
+pub(crate) fn cmd_dialog_message(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    ctx.dialogs()
+        .message(text_arg(&args, "title")?, message_arg(&args)?)?;
+    Ok(serde_json::json!({ "acknowledged": true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::CommandRegistry;
+    use crate::context::AppContext;
+    use crate::traits::DialogAnswer;
+    use crate::types::{ErrorCode, Status};
+
+    #[test]
+    fn test_dialog_commands_use_scripted_answers() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let call = |cmd: &str, args| reg.execute(cmd, args, &ctx);
+
+        ctx.dialogs()
+            .script(vec![
+                DialogAnswer::Open(Some("/tmp/in.txt".into())),
+                DialogAnswer::Save(None),
+                DialogAnswer::Confirm(true),
+                DialogAnswer::Message,
+                DialogAnswer::Confirm(false),
+            ])
+            .unwrap();
+
+        let r = call("dialog_open", serde_json::json!({ "title": "Pick" }));
+        assert_eq!(r.data.unwrap()["path"], "/tmp/in.txt");
+        let r = call("dialog_save", serde_json::json!({}));
+        assert_eq!(r.data.unwrap()["cancelled"], true);
+        let r = call("dialog_confirm", serde_json::json!({ "message": "Sure?" }));
+        assert_eq!(r.data.unwrap()["confirmed"], true);
+        let r = call("dialog_message", serde_json::json!({ "message": "Done" }));
+        assert_eq!(r.status, Status::Pass);
+
+        // Wrong kind of dialog for the next answer
+        let r = call("dialog_open", serde_json::json!({}));
+        assert_eq!(r.error.unwrap().code, ErrorCode::InternalError);
+        // Nothing left
+        let r = call("dialog_confirm", serde_json::json!({ "message": "Again?" }));
+        assert_eq!(r.error.unwrap().code, ErrorCode::Unsupported);
+
+        let r = call("dialog_confirm", serde_json::json!({}));
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+}
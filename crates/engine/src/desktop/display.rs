@@ -0,0 +1,243 @@
+//! Display server facts for the `display` probe: Wayland vs X11, the
+//! Wayland compositor's advertised globals, and whether XWayland is there
+//! to fall back to.
+//!
+//! The compositor is asked directly over the Wayland wire protocol (a
+//! `wl_registry` listing followed by a `wl_display.sync` round trip), so no
+//! client library or `wayland-info` binary is needed.
+
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Global advertising xdg-decoration, i.e. server-side decorations.
+pub const DECORATION_GLOBAL: &str = "zxdg_decoration_manager_v1";
+
+/// How long to wait for the compositor's registry.
+const WAYLAND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What the session looks like from this process.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DisplayFacts {
+    /// `XDG_SESSION_TYPE` (`wayland`, `x11`, `tty`).
+    pub session_type: Option<String>,
+    /// `XDG_CURRENT_DESKTOP` (e.g. `GNOME`, `KDE`, `sway`).
+    pub desktop: Option<String>,
+    pub wayland_display: Option<String>,
+    pub x11_display: Option<String>,
+    /// Interfaces the compositor advertises; `Err` if it could not be
+    /// reached. `None` without a Wayland display.
+    #[serde(skip)]
+    pub wayland_globals: Option<Result<Vec<String>, String>>,
+    /// An X server (XWayland under Wayland) accepts connections.
+    pub xwayland: bool,
+}
+
+/// Collect facts about the current session. `None` off Linux.
+pub fn gather() -> Option<DisplayFacts> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+    let wayland_display = var("WAYLAND_DISPLAY");
+    let x11_display = var("DISPLAY");
+    let wayland_globals = wayland_display.as_deref().map(|name| {
+        let socket = wayland_socket(name, var("XDG_RUNTIME_DIR").as_deref());
+        wayland_globals(&socket).map_err(|e| format!("{}: {}", socket.display(), e))
+    });
+    Some(DisplayFacts {
+        session_type: var("XDG_SESSION_TYPE"),
+        desktop: var("XDG_CURRENT_DESKTOP"),
+        xwayland: x11_display.as_deref().is_some_and(x_server_present),
+        wayland_display,
+        x11_display,
+        wayland_globals,
+    })
+}
+
+/// `WAYLAND_DISPLAY` is a socket name relative to `XDG_RUNTIME_DIR`, or an
+/// absolute path.
+fn wayland_socket(name: &str, runtime_dir: Option<&str>) -> PathBuf {
+    let path = Path::new(name);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    Path::new(runtime_dir.unwrap_or("/run/user/0")).join(path)
+}
+
+/// A local `DISPLAY` (`:0`, `:1.0`) needs its socket in `/tmp/.X11-unix`;
+/// remote ones are taken on trust.
+fn x_server_present(display: &str) -> bool {
+    match display.strip_prefix(':') {
+        Some(rest) => {
+            let number = rest.split('.').next().unwrap_or(rest);
+            Path::new(&format!("/tmp/.X11-unix/X{}", number)).exists()
+        }
+        None => true,
+    }
+}
+
+#[cfg(unix)]
+fn wayland_globals(socket: &Path) -> std::io::Result<Vec<String>> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(WAYLAND_TIMEOUT))?;
+    stream.write_all(&registry_request())?;
+
+    let mut buf = Vec::new();
+    let mut globals = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "compositor closed the connection",
+            ));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let (consumed, done) = decode_events(&buf, &mut globals)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        buf.drain(..consumed);
+        if done {
+            return Ok(globals);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn wayland_globals(_socket: &Path) -> std::io::Result<Vec<String>> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+// Object ids: 1 is wl_display, then the ids we allocate in the request.
+const DISPLAY_ID: u32 = 1;
+const REGISTRY_ID: u32 = 2;
+const CALLBACK_ID: u32 = 3;
+
+/// `wl_display.get_registry(2)` then `wl_display.sync(3)`.
+fn registry_request() -> Vec<u8> {
+    let message = |opcode: u32, new_id: u32| [DISPLAY_ID, (12 << 16) | opcode, new_id];
+    message(1, REGISTRY_ID)
+        .into_iter()
+        .chain(message(0, CALLBACK_ID))
+        .flat_map(u32::to_ne_bytes)
+        .collect()
+}
+
+/// Decode whole events from `buf`, collecting `wl_registry.global`
+/// interface names. Returns the bytes consumed and whether the sync
+/// callback fired (every global has been announced by then).
+fn decode_events(buf: &[u8], globals: &mut Vec<String>) -> Result<(usize, bool), String> {
+    let word = |at: usize| u32::from_ne_bytes(buf[at..at + 4].try_into().unwrap());
+    let mut at = 0;
+    while buf.len() >= at + 8 {
+        let object = word(at);
+        let size = (word(at + 4) >> 16) as usize;
+        let opcode = word(at + 4) & 0xffff;
+        if size < 8 {
+            return Err(format!("malformed event of {} bytes", size));
+        }
+        if buf.len() < at + size {
+            break;
+        }
+        let args = &buf[at + 8..at + size];
+        match (object, opcode) {
+            (REGISTRY_ID, 0) => globals.push(wire_string(args, 4)?),
+            (CALLBACK_ID, 0) => return Ok((at + size, true)),
+            (DISPLAY_ID, 0) => {
+                return Err(format!("protocol error: {}", wire_string(args, 8)?));
+            }
+            _ => {}
+        }
+        at += size;
+    }
+    Ok((at, false))
+}
+
+/// A wire-format string at byte `offset`: u32 length (with the NUL), bytes.
+fn wire_string(args: &[u8], offset: usize) -> Result<String, String> {
+    let len = args
+        .get(offset..offset + 4)
+        .map(|b| u32::from_ne_bytes(b.try_into().unwrap()) as usize)
+        .ok_or("truncated string")?;
+    let bytes = args
+        .get(offset + 4..offset + 4 + len)
+        .ok_or("truncated string")?;
+    Ok(String::from_utf8_lossy(bytes.strip_suffix(&[0]).unwrap_or(bytes)).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(object: u32, opcode: u32, args: &[u8]) -> Vec<u8> {
+        let size = (8 + args.len()) as u32;
+        [object, (size << 16) | opcode]
+            .into_iter()
+            .flat_map(u32::to_ne_bytes)
+            .chain(args.iter().copied())
+            .collect()
+    }
+
+    fn global(name: u32, interface: &str, version: u32) -> Vec<u8> {
+        let mut s = interface.as_bytes().to_vec();
+        s.push(0);
+        let len = s.len() as u32;
+        s.resize(s.len().div_ceil(4) * 4, 0);
+        let mut args = name.to_ne_bytes().to_vec();
+        args.extend(len.to_ne_bytes());
+        args.extend(s);
+        args.extend(version.to_ne_bytes());
+        event(REGISTRY_ID, 0, &args)
+    }
+
+    #[test]
+    fn test_decode_registry_events() {
+        let mut stream = global(1, "wl_compositor", 6);
+        stream.extend(global(2, DECORATION_GLOBAL, 1));
+        stream.extend(event(DISPLAY_ID, 1, &3u32.to_ne_bytes())); // delete_id
+        let done = event(CALLBACK_ID, 0, &0u32.to_ne_bytes());
+        stream.extend(&done[..6]);
+
+        let mut globals = Vec::new();
+        let (consumed, finished) = decode_events(&stream, &mut globals).unwrap();
+        assert!(!finished);
+        assert_eq!(globals, vec!["wl_compositor", DECORATION_GLOBAL]);
+
+        let mut rest = stream[consumed..].to_vec();
+        rest.extend(&done[6..]);
+        assert_eq!(decode_events(&rest, &mut globals).unwrap(), (12, true));
+        assert_eq!(registry_request().len(), 24);
+    }
+
+    #[test]
+    fn test_wayland_globals_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("wayland-test");
+        let listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            let mut request = [0u8; 24];
+            conn.read_exact(&mut request).unwrap();
+            assert_eq!(request.to_vec(), registry_request());
+            conn.write_all(&global(1, "xdg_wm_base", 5)).unwrap();
+            conn.write_all(&event(CALLBACK_ID, 0, &0u32.to_ne_bytes()))
+                .unwrap();
+        });
+        assert_eq!(wayland_globals(&socket).unwrap(), vec!["xdg_wm_base"]);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_wayland_socket_path() {
+        assert_eq!(
+            wayland_socket("wayland-0", Some("/run/user/1000")),
+            PathBuf::from("/run/user/1000/wayland-0")
+        );
+        assert_eq!(
+            wayland_socket("/tmp/wl", Some("/run/user/1000")),
+            PathBuf::from("/tmp/wl")
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Application menu built from a declarative spec.
+//!
+//! The `menu` list in the app config describes the menu bar; the GUI wrapper
+//! renders it natively and sends item activations back through [`dispatch`],
+//! so every menu action is an ordinary engine command:
+//!
+//! ```yaml
+//! menu:
+//!   - label: File
+//!     items:
+//!       - label: Ping engine
+//!         command: ping
+//!         accelerator: CmdOrCtrl+Shift+P
+//!       - separator: true
+//!       - predefined: quit
+//! ```
+//!
+//! Each node is exactly one of: a submenu (`label` + `items`), an item
+//! (`label` + `command`, optional `id`, `args`, `accelerator`, `enabled`),
+//! a `separator`, or a `predefined` native item. Top-level nodes must be
+//! submenus. [`validate`] lints a spec without a GUI (`menu_validate`).
+
+use crate::commands::{CommandError, CommandRegistry};
+use crate::context::AppContext;
+use crate::types::CommandResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Native items the wrapper knows how to create.
+pub const PREDEFINED: [&str; 16] = [
+    "about",
+    "services",
+    "hide",
+    "hide_others",
+    "show_all",
+    "quit",
+    "close_window",
+    "minimize",
+    "maximize",
+    "fullscreen",
+    "undo",
+    "redo",
+    "cut",
+    "copy",
+    "paste",
+    "select_all",
+];
+
+/// One node of the spec; see the module docs for the allowed shapes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuNode {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<MenuNode>>,
+    /// Menu event id; defaults to `command`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accelerator: Option<String>,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub separator: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predefined: Option<String>,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// What a node is, once its fields have been checked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuKind<'a> {
+    Submenu {
+        label: &'a str,
+        items: &'a [MenuNode],
+    },
+    Item {
+        id: &'a str,
+        label: &'a str,
+        command: &'a str,
+    },
+    Separator,
+    Predefined(&'a str),
+}
+
+impl MenuNode {
+    /// Classify the node, rejecting mixed or incomplete shapes.
+    pub fn kind(&self) -> Result<MenuKind<'_>, String> {
+        let shapes = [
+            self.items.is_some(),
+            self.command.is_some(),
+            self.separator,
+            self.predefined.is_some(),
+        ];
+        if shapes.iter().filter(|s| **s).count() != 1 {
+            return Err(
+                "must be exactly one of: submenu (items), item (command), separator, predefined"
+                    .into(),
+            );
+        }
+        let label = || {
+            self.label
+                .as_deref()
+                .filter(|l| !l.trim().is_empty())
+                .ok_or_else(|| "missing 'label'".to_string())
+        };
+        if let Some(ref items) = self.items {
+            return Ok(MenuKind::Submenu {
+                label: label()?,
+                items,
+            });
+        }
+        if let Some(ref command) = self.command {
+            return Ok(MenuKind::Item {
+                id: self.id.as_deref().unwrap_or(command),
+                label: label()?,
+                command,
+            });
+        }
+        if self.separator {
+            return Ok(MenuKind::Separator);
+        }
+        Ok(MenuKind::Predefined(
+            self.predefined.as_deref().unwrap_or_default(),
+        ))
+    }
+}
+
+/// A spec problem, located by the labels leading to it (e.g. `File > Ping`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MenuProblem {
+    pub path: String,
+    pub message: String,
+}
+
+/// Lint `spec` against `known_commands`. Empty means it can be rendered.
+pub fn validate(spec: &[MenuNode], known_commands: &[&str]) -> Vec<MenuProblem> {
+    let mut problems = Vec::new();
+    let mut ids = HashMap::new();
+    for (i, node) in spec.iter().enumerate() {
+        let path = node_path("", node, i);
+        match node.kind() {
+            Ok(MenuKind::Submenu { .. }) | Err(_) => {}
+            Ok(_) => problems.push(MenuProblem {
+                path: path.clone(),
+                message: "top-level entries must be submenus".into(),
+            }),
+        }
+        check_node(node, &path, known_commands, &mut ids, &mut problems);
+    }
+    problems
+}
+
+fn node_path(parent: &str, node: &MenuNode, index: usize) -> String {
+    let name = node
+        .label
+        .clone()
+        .or_else(|| node.predefined.clone())
+        .unwrap_or_else(|| format!("#{}", index));
+    if parent.is_empty() {
+        name
+    } else {
+        format!("{} > {}", parent, name)
+    }
+}
+
+fn check_node(
+    node: &MenuNode,
+    path: &str,
+    known_commands: &[&str],
+    ids: &mut HashMap<String, String>,
+    problems: &mut Vec<MenuProblem>,
+) {
+    let mut problem = |message: String| {
+        problems.push(MenuProblem {
+            path: path.to_string(),
+            message,
+        })
+    };
+    match node.kind() {
+        Err(e) => problem(e),
+        Ok(MenuKind::Submenu { items, .. }) => {
+            if items.is_empty() {
+                problem("submenu has no items".into());
+            }
+            for (i, child) in items.iter().enumerate() {
+                let child_path = node_path(path, child, i);
+                check_node(child, &child_path, known_commands, ids, problems);
+            }
+        }
+        Ok(MenuKind::Item { id, command, .. }) => {
+            if !known_commands.contains(&command) {
+                problem(format!("unknown command '{}'", command));
+            }
+            if let Some(ref keys) = node.accelerator {
+                if let Err(e) = crate::shortcuts::normalize(keys) {
+                    problem(format!("invalid accelerator: {}", e));
+                }
+            }
+            if !(node.args.is_null() || node.args.is_object()) {
+                problem("'args' must be a mapping".into());
+            }
+            if let Some(first) = ids.get(id) {
+                problem(format!("duplicate id '{}' (first used at {})", id, first));
+            } else {
+                ids.insert(id.to_string(), path.to_string());
+            }
+        }
+        Ok(MenuKind::Separator) => {}
+        Ok(MenuKind::Predefined(name)) => {
+            if !PREDEFINED.contains(&name) {
+                problem(format!(
+                    "unknown predefined item '{}' (expected one of: {})",
+                    name,
+                    PREDEFINED.join(", ")
+                ));
+            }
+        }
+    }
+}
+
+fn find_item<'a>(nodes: &'a [MenuNode], id: &str) -> Option<&'a MenuNode> {
+    nodes.iter().find_map(|node| match node.kind() {
+        Ok(MenuKind::Submenu { items, .. }) => find_item(items, id),
+        Ok(MenuKind::Item { id: item_id, .. }) if item_id == id => Some(node),
+        _ => None,
+    })
+}
+
+/// Run the command behind menu item `id`. `None` if no item has that id
+/// (e.g. a predefined item, which the OS handles itself).
+pub fn dispatch(id: &str, ctx: &AppContext, registry: &CommandRegistry) -> Option<CommandResult> {
+    let node = find_item(&ctx.menu, id)?;
+    let command = node.command.as_deref()?;
+    let args = match node.args {
+        Value::Null => Value::Object(Default::default()),
+        ref args => args.clone(),
+    };
+    Some(registry.execute(command, args, ctx))
+}
+
+/// Read the `menu` list from a config document (YAML or JSON; missing = none).
+pub fn from_config_yaml(yaml: &str) -> Result<Vec<MenuNode>, String> {
+    #[derive(Deserialize)]
+    struct Section {
+        #[serde(default)]
+        menu: Vec<MenuNode>,
+    }
+    serde_yaml::from_str::<Section>(yaml)
+        .map(|s| s.menu)
+        .map_err(|e| format!("invalid menu spec: {}", e))
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+/// `menu_validate` – lint a menu spec. Fails with INVALID_INPUT listing every
+/// problem, so `appctl call menu_validate` exits non-zero on a bad spec.
+///
+/// Args: `{ "config"?: "path/to/global_config.yaml" }` (default: the
+/// context's menu)
+/// Returns: `{ "valid": true, "menus": 2, "items": 5 }`
+pub(crate) fn cmd_menu_validate(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let from_file = match args.get("config") {
+        None | Some(Value::Null) => None,
+        Some(v) => {
+            let path = v
+                .as_str()
+                .ok_or_else(|| CommandError::InvalidInput("'config' must be a string".into()))?;
+            let text = ctx.fs().read_file(std::path::Path::new(path))?;
+            Some(
+                from_config_yaml(&String::from_utf8_lossy(&text))
+                    .map_err(CommandError::InvalidInput)?,
+            )
+        }
+    };
+    let spec = from_file.as_ref().unwrap_or(&ctx.menu);
+    let problems = validate(spec, &CommandRegistry::new().list());
+    if !problems.is_empty() {
+        let listed: Vec<String> = problems
+            .iter()
+            .map(|p| format!("{}: {}", p.path, p.message))
+            .collect();
+        return Err(CommandError::InvalidInput(format!(
+            "menu spec has {} problem(s): {}",
+            problems.len(),
+            listed.join("; ")
+        )));
+    }
+
+    fn count_items(nodes: &[MenuNode]) -> usize {
+        nodes
+            .iter()
+            .map(|n| match n.kind() {
+                Ok(MenuKind::Submenu { items, .. }) => count_items(items),
+                Ok(MenuKind::Item { .. }) => 1,
+                _ => 0,
+            })
+            .sum()
+    }
+    Ok(serde_json::json!({
+        "valid": true,
+        "menus": spec.len(),
+        "items": count_items(spec),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ErrorCode, Status};
+
+    const SPEC: &str = r#"
+menu:
+  - label: File
+    items:
+      - label: Ping engine
+        command: ping
+        accelerator: CmdOrCtrl+Shift+P
+      - label: More
+        items:
+          - label: System info
+            id: more.info
+            command: system_info
+      - separator: true
+      - predefined: quit
+  - label: Edit
+    items:
+      - predefined: copy
+"#;
+
+    #[test]
+    fn test_valid_spec_and_dispatch() {
+        let mut ctx = AppContext::default_headless();
+        ctx.menu = from_config_yaml(SPEC).unwrap();
+        let registry = CommandRegistry::new();
+        assert!(validate(&ctx.menu, &registry.list()).is_empty());
+
+        let r = dispatch("ping", &ctx, &registry).unwrap();
+        assert_eq!(r.data.unwrap()["pong"], true);
+        let r = dispatch("more.info", &ctx, &registry).unwrap();
+        assert_eq!(r.status, Status::Pass);
+        assert!(dispatch("quit", &ctx, &registry).is_none());
+
+        let r = registry.execute("menu_validate", serde_json::json!({}), &ctx);
+        let data = r.data.unwrap();
+        assert_eq!(data["menus"], 2);
+        assert_eq!(data["items"], 2);
+    }
+
+    #[test]
+    fn test_validate_reports_problems() {
+        let spec = from_config_yaml(
+            r#"
+menu:
+  - label: File
+    items:
+      - label: A
+        command: ping
+      - label: B
+        command: ping
+      - label: C
+        command: nope
+        accelerator: Ctrl+Nope
+      - label: D
+        command: ping
+        predefined: quit
+      - predefined: reboot
+  - separator: true
+"#,
+        )
+        .unwrap();
+        let problems = validate(&spec, &["ping"]);
+        let messages: Vec<String> = problems
+            .iter()
+            .map(|p| format!("{}: {}", p.path, p.message))
+            .collect();
+        assert!(messages[0].starts_with("File > B: duplicate id 'ping'"));
+        assert_eq!(messages[1], "File > C: unknown command 'nope'");
+        assert!(messages[2].starts_with("File > C: invalid accelerator"));
+        assert!(messages[3].starts_with("File > D: must be exactly one of"));
+        assert!(messages[4].starts_with("File > reboot: unknown predefined item"));
+        assert_eq!(messages[5], "#1: top-level entries must be submenus");
+        assert!(from_config_yaml("menu:\n  - label: X\n    colour: red\n").is_err());
+    }
+
+    #[test]
+    fn test_menu_validate_from_config_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "menu:\n  - label: File\n    items:\n      - label: X\n        command: nope\n",
+        )
+        .unwrap();
+        let r = CommandRegistry::new().execute(
+            "menu_validate",
+            serde_json::json!({ "config": path }),
+            &AppContext::default_headless(),
+        );
+        let err = r.error.unwrap();
+        assert_eq!(err.code, ErrorCode::InvalidInput);
+        assert!(err.message.contains("File > X: unknown command 'nope'"));
+    }
+}
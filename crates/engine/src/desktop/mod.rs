@@ -0,0 +1,14 @@
+//! Desktop integration: clipboard, dialogs, menus, shortcuts, windows,
+//! launch at login, and the other services a GUI app talks to.
+
+pub mod autostart;
+pub mod clipboard;
+pub mod dialogs;
+pub mod display;
+pub mod menu;
+pub mod opener;
+pub mod portals;
+pub mod shared_state;
+pub mod shortcuts;
+pub mod trash;
+pub mod windows;
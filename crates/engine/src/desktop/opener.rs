@@ -0,0 +1,224 @@
+//! Opening URLs and revealing files, gated by an allowlist.
+//!
+//! Every open goes through [`open_url`] / `reveal_path` here rather than the
+//! frontend talking to the OS directly, so each attempt is checked against
+//! the `opener` policy from the app config and logged:
+//!
+//! ```yaml
+//! opener:
+//!   allow:
+//!     - "mailto:"                      # any URL with this scheme
+//!     - "https://github.com/Miyamura80/" # host plus path prefix
+//!     - "https://*.example.com"        # example.com and its subdomains
+//! ```
+//!
+//! The GUI launches through the opener plugin; the CLI uses
+//! [`crate::platform::RecordingOpener`], which records the intent for
+//! `opener_log` instead of launching anything.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::OpenIntent;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// URLs the app may open. Entries are `scheme:` (whole scheme) or
+/// `scheme://host[:port][/path-prefix]`, where a host of `*.domain` also
+/// matches `domain` itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenerPolicy {
+    pub allow: Vec<String>,
+}
+
+impl Default for OpenerPolicy {
+    fn default() -> Self {
+        Self {
+            allow: vec!["https:".into(), "mailto:".into()],
+        }
+    }
+}
+
+impl OpenerPolicy {
+    /// Parse `url` and check it against the allowlist.
+    pub fn check(&self, url: &str) -> Result<Url, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("invalid URL '{}': {}", url, e))?;
+        if self.allow.iter().any(|entry| entry_matches(entry, &parsed)) {
+            Ok(parsed)
+        } else {
+            Err(format!("'{}' is not in the opener allowlist", url))
+        }
+    }
+}
+
+fn entry_matches(entry: &str, url: &Url) -> bool {
+    let Some((scheme, rest)) = entry.split_once("://") else {
+        return entry
+            .strip_suffix(':')
+            .is_some_and(|s| s.eq_ignore_ascii_case(url.scheme()));
+    };
+    if !scheme.eq_ignore_ascii_case(url.scheme()) {
+        return false;
+    }
+    let (authority, path_prefix) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    let (host_pattern, port) = match authority.rsplit_once(':') {
+        Some((h, p)) => match p.parse::<u16>() {
+            Ok(p) => (h, Some(p)),
+            Err(_) => return false,
+        },
+        None => (authority, None),
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host_pattern = host_pattern.to_ascii_lowercase();
+    let host_ok = match host_pattern.strip_prefix("*.") {
+        Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+        None => host == host_pattern,
+    };
+    host_ok
+        && port.is_none_or(|p| url.port_or_known_default() == Some(p))
+        && url.path().starts_with(path_prefix)
+}
+
+/// Check `url` against the context's policy, then open it.
+pub fn open_url(ctx: &AppContext, url: &str) -> Result<Url, CommandError> {
+    let parsed = ctx.opener_policy.check(url).map_err(|reason| {
+        tracing::warn!(target: "opener", url, "blocked: {}", reason);
+        CommandError::PermissionDenied(reason)
+    })?;
+    ctx.opener().open_url(parsed.as_str())?;
+    tracing::info!(target: "opener", url = parsed.as_str(), "opened");
+    Ok(parsed)
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+/// `open_url` – open a URL in its default handler, if the policy allows it.
+///
+/// Args: `{ "url": "https://github.com" }`
+/// Returns: `{ "url": "https://github.com/", "launched": true }` (`launched`
+/// is false when the intent was only recorded)
+pub(crate) fn cmd_open_url(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let url = args
+        .get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'url' string field".into()))?;
+    let parsed = open_url(ctx, url)?;
+    Ok(serde_json::json!({
+        "url": parsed.as_str(),
+        "launched": ctx.opener().recorded().is_none(),
+    }))
+}
+
+/// `reveal_path` – show an existing file or directory in the file manager.
+///
+/// Args: `{ "path": "/absolute/path" }`
+/// Returns: `{ "path": "/absolute/path", "launched": true }`
+pub(crate) fn cmd_reveal_path(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'path' string field".into()))?;
+    if !ctx.fs().exists(Path::new(path)) {
+        return Err(CommandError::InvalidInput(format!(
+            "'{}' does not exist",
+            path
+        )));
+    }
+    ctx.opener().reveal_path(Path::new(path))?;
+    tracing::info!(target: "opener", path, "revealed");
+    Ok(serde_json::json!({
+        "path": path,
+        "launched": ctx.opener().recorded().is_none(),
+    }))
+}
+
+/// `opener_log` – intents recorded by the headless opener, oldest first.
+///
+/// Returns: `{ "intents": [{ "kind": "url", "target": "https://github.com/" }] }`
+pub(crate) fn cmd_opener_log(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let intents: Vec<OpenIntent> = ctx.opener().recorded().ok_or_else(|| {
+        CommandError::Unsupported("this opener launches directly and keeps no log".into())
+    })?;
+    Ok(serde_json::json!({ "intents": intents }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::types::ErrorCode;
+
+    #[test]
+    fn test_policy_entries() {
+        let policy = OpenerPolicy {
+            allow: vec![
+                "mailto:".into(),
+                "https://github.com/Miyamura80/".into(),
+                "https://*.example.com".into(),
+                "http://localhost:1420".into(),
+            ],
+        };
+        for ok in [
+            "mailto:dev@example.com",
+            "https://github.com/Miyamura80/Tauri-Template",
+            "https://example.com/docs",
+            "https://api.EXAMPLE.com",
+            "http://localhost:1420/index.html",
+        ] {
+            assert!(policy.check(ok).is_ok(), "{} should be allowed", ok);
+        }
+        for denied in [
+            "https://github.com/someone-else",
+            "https://github.com/Miyamura80/../someone-else",
+            "https://notexample.com",
+            "https://example.com.evil.test",
+            "http://example.com",
+            "http://localhost:8080",
+            "file:///etc/passwd",
+            "not a url",
+        ] {
+            assert!(policy.check(denied).is_err(), "{} should be denied", denied);
+        }
+    }
+
+    #[test]
+    fn test_open_commands_record_intents_headlessly() {
+        let ctx = AppContext::default_headless();
+        let reg = CommandRegistry::new();
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_str().unwrap();
+
+        let r = reg.execute(
+            "open_url",
+            serde_json::json!({ "url": "https://github.com" }),
+            &ctx,
+        );
+        assert_eq!(r.data.unwrap()["launched"], false);
+        let r = reg.execute(
+            "open_url",
+            serde_json::json!({ "url": "file:///etc/passwd" }),
+            &ctx,
+        );
+        assert_eq!(r.error.unwrap().code, ErrorCode::PermissionDenied);
+        let r = reg.execute("reveal_path", serde_json::json!({ "path": dir }), &ctx);
+        assert!(r.error.is_none());
+
+        let r = reg.execute("opener_log", serde_json::json!({}), &ctx);
+        assert_eq!(
+            r.data.unwrap()["intents"],
+            serde_json::json!([
+                { "kind": "url", "target": "https://github.com/" },
+                { "kind": "reveal", "target": dir },
+            ])
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! XDG desktop portals – the D-Bus services Flatpak and Wayland apps use
+//! for file dialogs, notifications, screenshots, and so on.
+//!
+//! The `portals` probe checks that the session bus is reachable and that
+//! each portal in [`KEY_PORTALS`] answers a `version` property read on
+//! `org.freedesktop.portal.Desktop`. Linux only; other platforms skip.
+
+use crate::traits::{CapError, CapResult, PortalOps, PortalStatus};
+
+/// Portals a typical app needs.
+pub const KEY_PORTALS: &[&str] = &["FileChooser", "Notification", "Screenshot"];
+
+/// How long a single portal may take to answer. Activating
+/// xdg-desktop-portal can hang for D-Bus's 25 s default when a backend is
+/// broken, which is the failure we want to report rather than wait out.
+pub const PORTAL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The backend for this OS.
+pub fn platform_default() -> Box<dyn PortalOps> {
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(DbusPortals)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Box::new(UnsupportedPortals)
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use dbus::DbusPortals;
+
+#[cfg(target_os = "linux")]
+mod dbus {
+    use super::*;
+    use zbus::blocking::{connection, Proxy};
+    use zbus::zvariant::OwnedValue;
+
+    const PORTAL: &str = "org.freedesktop.portal.Desktop";
+    const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+    const PROPERTIES: &str = "org.freedesktop.DBus.Properties";
+
+    /// Portals on the session bus.
+    pub struct DbusPortals;
+
+    impl PortalOps for DbusPortals {
+        fn check(&self, names: &[&str]) -> CapResult<Vec<PortalStatus>> {
+            let conn = connection::Builder::session()
+                .and_then(|b| b.method_timeout(PORTAL_TIMEOUT).build())
+                .map_err(|e| {
+                    CapError::DependencyMissing(format!("session D-Bus unreachable: {}", e))
+                })?;
+            let props = Proxy::new(&conn, PORTAL, PORTAL_PATH, PROPERTIES)
+                .map_err(|e| CapError::Other(format!("portal proxy: {}", e)))?;
+            Ok(names
+                .iter()
+                .map(|name| {
+                    let interface = format!("org.freedesktop.portal.{}", name);
+                    let answer = props
+                        .call::<_, _, OwnedValue>("Get", &(interface.as_str(), "version"))
+                        .map_err(|e| e.to_string())
+                        .and_then(|v| u32::try_from(v).map_err(|e| e.to_string()));
+                    PortalStatus {
+                        name: name.to_string(),
+                        version: answer.as_ref().ok().copied(),
+                        error: answer.err(),
+                    }
+                })
+                .collect())
+        }
+    }
+}
+
+/// Backend for platforms without XDG portals.
+pub struct UnsupportedPortals;
+
+impl PortalOps for UnsupportedPortals {
+    fn check(&self, _names: &[&str]) -> CapResult<Vec<PortalStatus>> {
+        Err(CapError::Unsupported(
+            "XDG desktop portals only exist on Linux".into(),
+        ))
+    }
+}
@@ -0,0 +1,246 @@
+//! Shared app state for multi-window apps: a key/value store in the
+//! context that every window reads and writes through `state_get` /
+//! `state_set`, with each change published as a `state:changed` event
+//! (`{ "key", "value", "version", "source" }`). The Tauri layer forwards
+//! events to every window, so windows stay in sync without talking to each
+//! other.
+//!
+//! Every change bumps one store-wide `version`, and each entry remembers
+//! the version that last changed it. A window loads a snapshot with
+//! `state_subscribe`, then applies events newer than the snapshot's
+//! version; `state_set` with `if_version` only writes if nobody changed
+//! the key since the writer read it.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::events::topic_matches;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+pub const TOPIC_CHANGED: &str = "state:changed";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateEntry {
+    pub value: Value,
+    /// Store version of the change that set it.
+    pub version: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    entries: BTreeMap<String, StateEntry>,
+    version: u64,
+}
+
+#[derive(Default)]
+pub struct SharedState {
+    inner: Mutex<Entries>,
+}
+
+impl SharedState {
+    pub fn get(&self, key: &str) -> Option<StateEntry> {
+        self.lock().entries.get(key).cloned()
+    }
+
+    /// Entries whose keys match any of `patterns` (`*` suffixes as in
+    /// [`topic_matches`]; empty means all), and the store version.
+    pub fn snapshot(&self, patterns: &[String]) -> (BTreeMap<String, StateEntry>, u64) {
+        let inner = self.lock();
+        let entries = inner
+            .entries
+            .iter()
+            .filter(|(k, _)| patterns.is_empty() || patterns.iter().any(|p| topic_matches(p, k)))
+            .map(|(k, e)| (k.clone(), e.clone()))
+            .collect();
+        (entries, inner.version)
+    }
+
+    /// Set `key` (`null` removes it) and return the previous entry. With
+    /// `if_version`, fails with the key's current version (0 if unset)
+    /// unless that is what it still is.
+    pub fn set(
+        &self,
+        key: &str,
+        value: Value,
+        if_version: Option<u64>,
+    ) -> Result<(u64, Option<StateEntry>), u64> {
+        let mut inner = self.lock();
+        let current = inner.entries.get(key).map_or(0, |e| e.version);
+        if if_version.is_some_and(|v| v != current) {
+            return Err(current);
+        }
+        inner.version += 1;
+        let version = inner.version;
+        let previous = match value {
+            Value::Null => inner.entries.remove(key),
+            value => inner
+                .entries
+                .insert(key.to_string(), StateEntry { value, version }),
+        };
+        Ok((version, previous))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn key_arg(args: &Value) -> Result<&str, CommandError> {
+    args.get("key")
+        .and_then(Value::as_str)
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| CommandError::InvalidInput("'key' must be a non-empty string".into()))
+}
+
+fn keys_arg(args: &Value) -> Result<Vec<String>, CommandError> {
+    match args.get("keys") {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(keys) => serde_json::from_value(keys.clone()).map_err(|_| {
+            CommandError::InvalidInput("'keys' must be a list of key patterns".into())
+        }),
+    }
+}
+
+/// `state_get` – read shared state.
+///
+/// Args: `{ "key"?: "theme" }`.
+/// Returns: `{ "key", "value", "version" }` for one key (`null` value and
+/// version 0 if unset), or `{ "entries": {key: {value, version}}, "version" }`
+/// without `key`.
+pub(crate) fn cmd_state_get(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let state = ctx.shared_state();
+    if args.get("key").is_none() {
+        let (entries, version) = state.snapshot(&[]);
+        return Ok(serde_json::json!({ "entries": entries, "version": version }));
+    }
+    let key = key_arg(&args)?;
+    let entry = state.get(key);
+    Ok(serde_json::json!({
+        "key": key,
+        "value": entry.as_ref().map_or(Value::Null, |e| e.value.clone()),
+        "version": entry.map_or(0, |e| e.version),
+    }))
+}
+
+/// `state_set` – change shared state and tell every window.
+///
+/// Args: `{ "key": "theme", "value": "dark" (null removes), "if_version"?: 3,
+/// "source"?: "settings" }`; `source` (e.g. the writing window's label)
+/// is passed on in the event so the writer can skip its own echo.
+/// Returns: `{ "key", "value", "version", "previous" }`. A stale
+/// `if_version` fails with a conflict naming the current version.
+pub(crate) fn cmd_state_set(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let key = key_arg(&args)?;
+    let value = args.get("value").cloned().unwrap_or(Value::Null);
+    let if_version = match args.get("if_version") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(v.as_u64().ok_or_else(|| {
+            CommandError::InvalidInput("'if_version' must be a non-negative integer".into())
+        })?),
+    };
+    let source = args.get("source").cloned().unwrap_or(Value::Null);
+    let (version, previous) = ctx
+        .shared_state()
+        .set(key, value.clone(), if_version)
+        .map_err(|current| {
+            CommandError::Conflict(format!(
+                "'{}' is at version {}, not {}",
+                key,
+                current,
+                if_version.unwrap_or_default()
+            ))
+        })?;
+    ctx.events().emit(
+        &ctx.new_run_id(),
+        TOPIC_CHANGED,
+        serde_json::json!({ "key": key, "value": value, "version": version, "source": source }),
+    );
+    Ok(serde_json::json!({
+        "key": key,
+        "value": value,
+        "version": version,
+        "previous": previous.map_or(Value::Null, |e| e.value),
+    }))
+}
+
+/// `state_subscribe` – the starting point for following shared state.
+///
+/// Args: `{ "keys"?: ["theme", "editor.*"] }` (all keys if absent).
+/// Returns: `{ "topic": "state:changed", "keys", "entries", "version" }`:
+/// the current entries, after which the caller applies `state:changed`
+/// events for matching keys with a higher version (daemon clients
+/// `subscribe` to the topic).
+pub(crate) fn cmd_state_subscribe(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let keys = keys_arg(&args)?;
+    let (entries, version) = ctx.shared_state().snapshot(&keys);
+    Ok(serde_json::json!({
+        "topic": TOPIC_CHANGED,
+        "keys": keys,
+        "entries": entries,
+        "version": version,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_set_publishes_changes_and_checks_versions() {
+        let ctx = AppContext::default_headless();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        ctx.events()
+            .subscribe(move |e| sink.lock().unwrap().push(e.clone()));
+
+        let set = cmd_state_set(
+            serde_json::json!({ "key": "theme", "value": "dark", "source": "main" }),
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(set["version"], 1);
+        assert_eq!(set["previous"], Value::Null);
+        let stale = cmd_state_set(
+            serde_json::json!({ "key": "theme", "value": "light", "if_version": 0 }),
+            &ctx,
+        );
+        assert!(matches!(stale, Err(CommandError::Conflict(_))));
+        cmd_state_set(
+            serde_json::json!({ "key": "editor.font", "value": 14, "if_version": 0 }),
+            &ctx,
+        )
+        .unwrap();
+
+        let events = seen.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].topic, TOPIC_CHANGED);
+        assert_eq!(events[0].payload["source"], "main");
+        assert_eq!(events[1].payload["version"], 2);
+
+        let got = cmd_state_get(serde_json::json!({ "key": "theme" }), &ctx).unwrap();
+        assert_eq!(
+            (got["value"].clone(), got["version"].clone()),
+            ("dark".into(), 1.into())
+        );
+        let sub = cmd_state_subscribe(serde_json::json!({ "keys": ["editor.*"] }), &ctx).unwrap();
+        assert_eq!(sub["version"], 2);
+        assert_eq!(sub["entries"]["editor.font"]["value"], 14);
+        assert!(sub["entries"].get("theme").is_none());
+    }
+
+    #[test]
+    fn test_null_removes_a_key() {
+        let ctx = AppContext::default_headless();
+        cmd_state_set(serde_json::json!({ "key": "k", "value": [1] }), &ctx).unwrap();
+        let removed =
+            cmd_state_set(serde_json::json!({ "key": "k", "value": null }), &ctx).unwrap();
+        assert_eq!(removed["previous"], serde_json::json!([1]));
+        let all = cmd_state_get(Value::Null, &ctx).unwrap();
+        assert_eq!(all["entries"], serde_json::json!({}));
+        assert_eq!(all["version"], 2);
+        assert!(cmd_state_set(serde_json::json!({ "value": 1 }), &ctx).is_err());
+    }
+}
@@ -0,0 +1,451 @@
+//! Global keyboard shortcuts bound to engine commands.
+//!
+//! Bindings come from the `shortcuts` list in the app config:
+//!
+//! ```yaml
+//! shortcuts:
+//!   - keys: CmdOrCtrl+Shift+Space
+//!     command: ping
+//!     args: {}
+//! ```
+//!
+//! [`validate`] checks every binding (accelerator syntax, known command,
+//! duplicates, OS-reserved combinations) without touching the OS, which is
+//! all the headless CLI does. The GUI wrapper calls [`register_all`], which
+//! registers the valid ones through [`AppContext::shortcuts`], and routes
+//! presses back through [`dispatch`].
+
+use crate::commands::{CommandError, CommandRegistry};
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::CommandResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One configured shortcut.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShortcutBinding {
+    /// Accelerator, e.g. `CmdOrCtrl+Shift+P`.
+    pub keys: String,
+    /// Engine command run when the keys are pressed.
+    pub command: String,
+    #[serde(default = "empty_args")]
+    pub args: Value,
+}
+
+fn empty_args() -> Value {
+    Value::Object(Default::default())
+}
+
+/// Validation (and, in the GUI, registration) outcome for one binding.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutReport {
+    pub keys: String,
+    /// Platform-resolved accelerator; `None` if it did not parse.
+    pub normalized: Option<String>,
+    pub command: String,
+    pub problems: Vec<String>,
+    pub registered: bool,
+}
+
+impl ShortcutReport {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Accelerators
+// ---------------------------------------------------------------------------
+
+/// Modifiers in canonical order.
+const MODIFIERS: [&str; 4] = ["Ctrl", "Alt", "Shift", "Super"];
+
+fn modifier(token: &str) -> Option<&'static str> {
+    let primary = if cfg!(target_os = "macos") {
+        "Super"
+    } else {
+        "Ctrl"
+    };
+    Some(match token.to_ascii_lowercase().as_str() {
+        "cmdorctrl" | "commandorcontrol" | "cmdorcontrol" | "commandorctrl" => primary,
+        "ctrl" | "control" => "Ctrl",
+        "alt" | "option" => "Alt",
+        "shift" => "Shift",
+        "super" | "cmd" | "command" | "meta" => "Super",
+        _ => return None,
+    })
+}
+
+fn key(token: &str) -> Option<String> {
+    const NAMED: [&str; 22] = [
+        "Space",
+        "Enter",
+        "Tab",
+        "Escape",
+        "Backspace",
+        "Delete",
+        "Insert",
+        "Home",
+        "End",
+        "PageUp",
+        "PageDown",
+        "Up",
+        "Down",
+        "Left",
+        "Right",
+        "Comma",
+        "Period",
+        "Slash",
+        "Minus",
+        "Equal",
+        "Semicolon",
+        "Backquote",
+    ];
+    let mut chars = token.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (c.is_ascii_alphanumeric() || ",./;'[]\\-=`".contains(c))
+            .then(|| c.to_ascii_uppercase().to_string());
+    }
+    if let Some(n) = token
+        .strip_prefix(['F', 'f'])
+        .and_then(|n| n.parse::<u8>().ok())
+    {
+        return (1..=24).contains(&n).then(|| format!("F{}", n));
+    }
+    let alias = match token.to_ascii_lowercase().as_str() {
+        "return" => "Enter",
+        "esc" => "Escape",
+        "del" => "Delete",
+        "arrowup" => "Up",
+        "arrowdown" => "Down",
+        "arrowleft" => "Left",
+        "arrowright" => "Right",
+        _ => "",
+    };
+    if !alias.is_empty() {
+        return Some(alias.to_string());
+    }
+    NAMED
+        .iter()
+        .find(|n| n.eq_ignore_ascii_case(token))
+        .map(|n| n.to_string())
+}
+
+/// Resolve `keys` for this platform into canonical form:
+/// `Ctrl+Alt+Shift+Super+<Key>`, modifiers in that order.
+pub fn normalize(keys: &str) -> Result<String, String> {
+    let tokens: Vec<&str> = keys.split('+').map(str::trim).collect();
+    let (key_token, modifier_tokens) = tokens
+        .split_last()
+        .filter(|(k, _)| !k.is_empty())
+        .ok_or_else(|| format!("'{}' has no key", keys))?;
+    let mut mods = Vec::new();
+    for token in modifier_tokens {
+        let m =
+            modifier(token).ok_or_else(|| format!("unknown modifier '{}' in '{}'", token, keys))?;
+        if mods.contains(&m) {
+            return Err(format!("modifier '{}' repeated in '{}'", m, keys));
+        }
+        mods.push(m);
+    }
+    let key = key(key_token).ok_or_else(|| format!("unknown key '{}' in '{}'", key_token, keys))?;
+    if mods.is_empty() && !key.starts_with('F') {
+        return Err(format!(
+            "'{}' needs a modifier (a bare key would swallow normal typing)",
+            keys
+        ));
+    }
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|m| mods.contains(m))
+        .map(|m| m.to_string())
+        .collect();
+    parts.push(key);
+    Ok(parts.join("+"))
+}
+
+/// Combinations the OS (or desktop) keeps for itself on this platform.
+fn reserved() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &[
+            "Super+Tab",
+            "Super+Space",
+            "Super+Q",
+            "Super+H",
+            "Super+M",
+            "Ctrl+Super+Q",
+            "Shift+Super+3",
+            "Shift+Super+4",
+            "Shift+Super+5",
+            "Alt+Super+Escape",
+        ]
+    } else {
+        &[
+            "Alt+Tab",
+            "Alt+F4",
+            "Ctrl+Alt+Delete",
+            "Ctrl+Alt+T",
+            "Super+L",
+            "Super+D",
+        ]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Validation, registration, dispatch
+// ---------------------------------------------------------------------------
+
+/// Check every binding against `known_commands`, each other, and the OS
+/// reserved list. Nothing is registered.
+pub fn validate(bindings: &[ShortcutBinding], known_commands: &[&str]) -> Vec<ShortcutReport> {
+    let mut first_use: HashMap<String, usize> = HashMap::new();
+    bindings
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let mut problems = Vec::new();
+            let normalized = match normalize(&b.keys) {
+                Ok(n) => Some(n),
+                Err(e) => {
+                    problems.push(format!("invalid accelerator: {}", e));
+                    None
+                }
+            };
+            if !known_commands.contains(&b.command.as_str()) {
+                problems.push(format!("unknown command '{}'", b.command));
+            }
+            if let Some(ref n) = normalized {
+                if reserved().contains(&n.as_str()) {
+                    problems.push(format!("{} is reserved by the OS", n));
+                }
+                if let Some(&j) = first_use.get(n) {
+                    problems.push(format!(
+                        "conflicts with shortcut #{} ('{}' -> {})",
+                        j, bindings[j].keys, bindings[j].command
+                    ));
+                } else {
+                    first_use.insert(n.clone(), i);
+                }
+            }
+            ShortcutReport {
+                keys: b.keys.clone(),
+                normalized,
+                command: b.command.clone(),
+                problems,
+                registered: false,
+            }
+        })
+        .collect()
+}
+
+/// Validate the context's bindings and register the valid ones. The OS may
+/// still refuse a combination (typically because another app holds it);
+/// that is recorded as a conflict on the report.
+pub fn register_all(ctx: &AppContext, registry: &CommandRegistry) -> Vec<ShortcutReport> {
+    let mut reports = validate(&ctx.shortcut_bindings, &registry.list());
+    if let Err(e) = ctx.shortcuts().unregister_all() {
+        tracing::warn!("failed to clear global shortcuts: {}", e);
+    }
+    for report in reports.iter_mut().filter(|r| r.is_valid()) {
+        let Some(ref accelerator) = report.normalized else {
+            continue;
+        };
+        match ctx.shortcuts().register(accelerator) {
+            Ok(()) => report.registered = true,
+            Err(CapError::Unsupported(_)) => {}
+            Err(e) => report
+                .problems
+                .push(format!("registration failed (held by another app?): {}", e)),
+        }
+    }
+    reports
+}
+
+/// Run the command bound to `accelerator` (normalized, as passed to
+/// [`crate::traits::ShortcutOps::register`]). `None` if nothing is bound.
+pub fn dispatch(
+    accelerator: &str,
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+) -> Option<CommandResult> {
+    let binding = ctx
+        .shortcut_bindings
+        .iter()
+        .find(|b| normalize(&b.keys).as_deref() == Ok(accelerator))?;
+    Some(registry.execute(&binding.command, binding.args.clone(), ctx))
+}
+
+/// Read the `shortcuts` list from a config YAML document (missing = none).
+pub fn from_config_yaml(yaml: &str) -> Result<Vec<ShortcutBinding>, String> {
+    #[derive(Deserialize)]
+    struct Section {
+        #[serde(default)]
+        shortcuts: Vec<ShortcutBinding>,
+    }
+    serde_yaml::from_str::<Section>(yaml)
+        .map(|s| s.shortcuts)
+        .map_err(|e| format!("invalid shortcuts config: {}", e))
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+/// `shortcuts_list` – configured shortcuts with validation results.
+///
+/// Args: `{ "config"?: "path/to/global_config.yaml" }` (default: the
+/// context's bindings)
+/// Returns: `{ "valid": true, "shortcuts": [{ "keys": "CmdOrCtrl+Shift+P",
+///            "normalized": "Ctrl+Shift+P", "command": "ping",
+///            "problems": [], "registered": false }] }`
+pub(crate) fn cmd_shortcuts_list(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let from_file = match args.get("config") {
+        None | Some(Value::Null) => None,
+        Some(v) => {
+            let path = v
+                .as_str()
+                .ok_or_else(|| CommandError::InvalidInput("'config' must be a string".into()))?;
+            let yaml = ctx.fs().read_file(std::path::Path::new(path))?;
+            Some(
+                from_config_yaml(&String::from_utf8_lossy(&yaml))
+                    .map_err(CommandError::InvalidInput)?,
+            )
+        }
+    };
+    let bindings = from_file.as_ref().unwrap_or(&ctx.shortcut_bindings);
+    let registered = ctx.shortcuts().registered();
+    let mut reports = validate(bindings, &CommandRegistry::new().list());
+    for report in &mut reports {
+        report.registered = report
+            .normalized
+            .as_ref()
+            .is_some_and(|n| registered.contains(n));
+    }
+    Ok(serde_json::json!({
+        "valid": reports.iter().all(ShortcutReport::is_valid),
+        "shortcuts": reports,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{CapResult, ShortcutOps};
+    use std::sync::Mutex;
+
+    fn binding(keys: &str, command: &str) -> ShortcutBinding {
+        ShortcutBinding {
+            keys: keys.into(),
+            command: command.into(),
+            args: empty_args(),
+        }
+    }
+
+    /// Registers anything except `Ctrl+Alt+K`, which "another app" holds.
+    #[derive(Default)]
+    struct FakeShortcuts(Mutex<Vec<String>>);
+
+    impl ShortcutOps for FakeShortcuts {
+        fn register(&self, accelerator: &str) -> CapResult<()> {
+            if accelerator == "Ctrl+Alt+K" {
+                return Err(CapError::Other("already registered".into()));
+            }
+            self.0.lock().unwrap().push(accelerator.to_string());
+            Ok(())
+        }
+        fn unregister_all(&self) -> CapResult<()> {
+            self.0.lock().unwrap().clear();
+            Ok(())
+        }
+        fn registered(&self) -> Vec<String> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("shift+ctrl+p").unwrap(), "Ctrl+Shift+P");
+        assert_eq!(normalize("Alt + Esc").unwrap(), "Alt+Escape");
+        assert_eq!(normalize("F5").unwrap(), "F5");
+        let primary = if cfg!(target_os = "macos") {
+            "Super"
+        } else {
+            "Ctrl"
+        };
+        assert_eq!(
+            normalize("CmdOrCtrl+Space").unwrap(),
+            format!("{}+Space", primary)
+        );
+        assert!(normalize("P").is_err());
+        assert!(normalize("Ctrl+").is_err());
+        assert!(normalize("Hyper+P").is_err());
+        assert!(normalize("Ctrl+Control+P").is_err());
+    }
+
+    #[test]
+    fn test_validate_reports_conflicts() {
+        let reports = validate(
+            &[
+                binding("Ctrl+Shift+P", "ping"),
+                binding("Shift+Control+p", "system_info"),
+                binding("Ctrl+Shift+X", "nope"),
+                binding("Alt+Tab", "ping"),
+            ],
+            &["ping", "system_info"],
+        );
+        assert!(reports[0].is_valid());
+        assert!(reports[1].problems[0].contains("conflicts with shortcut #0"));
+        assert_eq!(reports[2].problems, vec!["unknown command 'nope'"]);
+        assert_eq!(reports[3].is_valid(), cfg!(target_os = "macos"));
+    }
+
+    #[test]
+    fn test_register_and_dispatch() {
+        let mut ctx =
+            AppContext::default_headless().with_shortcuts(Box::new(FakeShortcuts::default()));
+        ctx.shortcut_bindings = vec![
+            binding("Ctrl+Shift+P", "ping"),
+            binding("Ctrl+Alt+K", "ping"),
+            binding("Ctrl+Shift+X", "nope"),
+        ];
+        let registry = CommandRegistry::new();
+
+        let reports = register_all(&ctx, &registry);
+        assert!(reports[0].registered);
+        assert!(reports[1].problems[0].contains("held by another app"));
+        assert!(!reports[2].registered);
+        assert_eq!(ctx.shortcuts().registered(), vec!["Ctrl+Shift+P"]);
+
+        let r = dispatch("Ctrl+Shift+P", &ctx, &registry).unwrap();
+        assert_eq!(r.data.unwrap()["pong"], true);
+        assert!(dispatch("Ctrl+Shift+Q", &ctx, &registry).is_none());
+
+        let list = registry.execute("shortcuts_list", serde_json::json!({}), &ctx);
+        let data = list.data.unwrap();
+        assert_eq!(data["valid"], false);
+        assert_eq!(data["shortcuts"][0]["registered"], true);
+    }
+
+    #[test]
+    fn test_shortcuts_list_from_config_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "model_name: x\nshortcuts:\n  - keys: Ctrl+Shift+P\n    command: ping\n",
+        )
+        .unwrap();
+        let ctx = AppContext::default_headless();
+        let r = CommandRegistry::new().execute(
+            "shortcuts_list",
+            serde_json::json!({ "config": path }),
+            &ctx,
+        );
+        let data = r.data.unwrap();
+        assert_eq!(data["valid"], true);
+        assert_eq!(data["shortcuts"][0]["registered"], false);
+        assert_eq!(data["shortcuts"][0]["normalized"], "Ctrl+Shift+P");
+    }
+}
@@ -0,0 +1,201 @@
+//! Moving files to the platform trash, for [`crate::traits::FilesystemOps::trash`]
+//! and `delete_file`.
+//!
+//! - macOS: Finder, through `osascript`, so "Put Back" works
+//! - Linux: `gio trash`; without gio, the freedesktop.org home trash
+//!   (`$XDG_DATA_HOME/Trash`) is written directly
+//! - Windows: the Recycle Bin, through PowerShell
+//!
+//! There is no silent fallback to a permanent delete: if nothing can take
+//! the file, the error says so and the file stays where it is.
+
+use crate::traits::{CapError, CapResult};
+use std::path::Path;
+
+/// Move `path` (a file or directory; absolute) to the trash.
+pub(crate) fn move_to_trash(path: &Path) -> CapResult<()> {
+    std::fs::symlink_metadata(path)?;
+    platform_trash(path)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_trash(path: &Path) -> CapResult<()> {
+    let quoted = path
+        .display()
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    let script = format!(
+        "tell application \"Finder\" to delete POSIX file \"{}\"",
+        quoted
+    );
+    run_tool("osascript", &["-e", &script])
+}
+
+#[cfg(target_os = "linux")]
+fn platform_trash(path: &Path) -> CapResult<()> {
+    let target = path.display().to_string();
+    match run_tool("gio", &["trash", "--", &target]) {
+        Err(CapError::DependencyMissing(_)) => {
+            let trash = dirs::data_dir()
+                .ok_or_else(|| CapError::Unsupported("no data directory for the trash".into()))?
+                .join("Trash");
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            trash_into(path, &trash, now)
+        }
+        other => other,
+    }
+}
+
+#[cfg(windows)]
+fn platform_trash(path: &Path) -> CapResult<()> {
+    let quoted = path.display().to_string().replace('\'', "''");
+    let method = if path.is_dir() {
+        "DeleteDirectory"
+    } else {
+        "DeleteFile"
+    };
+    let script = format!(
+        "Add-Type -AssemblyName Microsoft.VisualBasic; \
+         [Microsoft.VisualBasic.FileIO.FileSystem]::{}('{}', 'OnlyErrorDialogs', 'SendToRecycleBin')",
+        method, quoted
+    );
+    run_tool(
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", &script],
+    )
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn platform_trash(_path: &Path) -> CapResult<()> {
+    Err(CapError::Unsupported(
+        "no trash on this platform; delete permanently instead".into(),
+    ))
+}
+
+#[allow(dead_code)]
+fn run_tool(cmd: &str, args: &[&str]) -> CapResult<()> {
+    let output = std::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                CapError::DependencyMissing(format!("{} not found", cmd))
+            }
+            _ => CapError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(CapError::Other(format!(
+            "{} exited with {}: {}",
+            cmd,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Move `path` into a freedesktop.org trash directory: reserve
+/// `info/<name>.trashinfo` first, then rename into `files/<name>`.
+/// `deleted_at` is Unix seconds.
+#[allow(dead_code)]
+fn trash_into(path: &Path, trash: &Path, deleted_at: u64) -> CapResult<()> {
+    let files = trash.join("files");
+    let info = trash.join("info");
+    std::fs::create_dir_all(&files)?;
+    std::fs::create_dir_all(&info)?;
+    let name = path
+        .file_name()
+        .ok_or_else(|| CapError::Other(format!("cannot trash {}", path.display())))?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut n = 1;
+    let (entry, info_path, mut info_file) = loop {
+        let entry = if n == 1 {
+            name.clone()
+        } else {
+            format!("{}.{}", name, n)
+        };
+        let info_path = info.join(format!("{}.trashinfo", entry));
+        if !files.join(&entry).exists() {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(file) => break (entry, info_path, file),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        n += 1;
+    };
+
+    use std::io::Write;
+    let date = crate::history::format_time(deleted_at);
+    let written = write!(
+        info_file,
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(&path.display().to_string()),
+        date.trim_end_matches('Z')
+    );
+    let moved = written
+        .map_err(CapError::from)
+        .and_then(|_| std::fs::rename(path, files.join(&entry)).map_err(CapError::from));
+    if let Err(e) = moved {
+        let _ = std::fs::remove_file(&info_path);
+        return Err(match e {
+            CapError::Io(io) if io.raw_os_error() == Some(18) => CapError::Unsupported(format!(
+                "{} is not on the same filesystem as the trash",
+                path.display()
+            )),
+            other => other,
+        });
+    }
+    Ok(())
+}
+
+/// `Path=` value: bytes outside the unreserved set (and `/`) as `%XX`.
+#[allow(dead_code)]
+fn percent_encode(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_into_writes_info_and_avoids_collisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path().join("Trash");
+        for _ in 0..2 {
+            let doc = dir.path().join("my notes.txt");
+            std::fs::write(&doc, "hi").unwrap();
+            trash_into(&doc, &trash, 1_709_618_828).unwrap();
+            assert!(!doc.exists());
+        }
+        assert_eq!(
+            std::fs::read_to_string(trash.join("files/my notes.txt.2")).unwrap(),
+            "hi"
+        );
+        let info = std::fs::read_to_string(trash.join("info/my notes.txt.trashinfo")).unwrap();
+        assert!(info.contains("/my%20notes.txt\n"), "{}", info);
+        assert!(info.ends_with("DeletionDate=2024-03-05T06:07:08\n"));
+
+        let missing = trash_into(&dir.path().join("gone"), &trash, 0);
+        assert!(missing.is_err());
+        assert!(!trash.join("info/gone.trashinfo").exists());
+    }
+}
@@ -0,0 +1,251 @@
+//! Window geometry – persisted per window label and exposed as commands.
+//!
+//! The GUI wrapper saves each window's geometry to
+//! `<data_dir>/window-state.json` when it closes and restores it on launch.
+//! `window_info` / `window_set` go through [`AppContext::windows`], so
+//! scenarios can assert on (and change) geometry in the GUI, and get
+//! UNSUPPORTED from the headless CLI.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{CapError, FilesystemOps, WindowGeometry};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File under [`AppContext::data_dir`] holding saved geometry.
+pub const STATE_FILE: &str = "window-state.json";
+
+/// Label used when a command does not name a window.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// Saved geometry keyed by window label.
+pub type WindowStates = BTreeMap<String, WindowGeometry>;
+
+pub fn state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATE_FILE)
+}
+
+/// Load saved geometry. A missing or unreadable file yields no entries, so a
+/// corrupt state file never blocks startup.
+pub fn load(fs: &dyn FilesystemOps, data_dir: &Path) -> WindowStates {
+    let path = state_path(data_dir);
+    if !fs.exists(&path) {
+        return WindowStates::new();
+    }
+    let parsed = fs
+        .read_file(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+    match parsed {
+        Ok(states) => states,
+        Err(e) => {
+            tracing::warn!("ignoring window state {}: {}", path.display(), e);
+            WindowStates::new()
+        }
+    }
+}
+
+/// Record `current` for `label` and write the state file.
+///
+/// A maximized window keeps its previously saved position and size, so
+/// un-maximizing after a restart returns to the user's own layout.
+pub fn save(
+    fs: &dyn FilesystemOps,
+    data_dir: &Path,
+    label: &str,
+    current: WindowGeometry,
+) -> Result<(), CapError> {
+    let mut states = load(fs, data_dir);
+    let geometry = match states.get(label) {
+        Some(previous) if current.maximized => WindowGeometry {
+            maximized: true,
+            ..*previous
+        },
+        _ => current,
+    };
+    states.insert(label.to_string(), geometry);
+    let json = serde_json::to_vec_pretty(&states).map_err(|e| CapError::Other(e.to_string()))?;
+    fs.write_file_atomic(&state_path(data_dir), &json)
+}
+
+fn label_arg(args: &Value) -> Result<&str, CommandError> {
+    match args.get("label") {
+        None | Some(Value::Null) => Ok(DEFAULT_LABEL),
+        Some(v) => v
+            .as_str()
+            .ok_or_else(|| CommandError::InvalidInput("'label' must be a string".into())),
+    }
+}
+
+fn geometry_json(label: &str, geometry: &WindowGeometry) -> Value {
+    let mut out = serde_json::to_value(geometry).unwrap_or_default();
+    out["label"] = label.into();
+    out
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+/// `window_info` – current geometry of one window.
+///
+/// Args: `{ "label"?: "main" }`
+/// Returns: `{ "label": "main", "x": 10, "y": 20, "width": 800, "height": 600,
+///            "maximized": false, "windows": ["main"] }`
+pub(crate) fn cmd_window_info(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let label = label_arg(&args)?;
+    let geometry = ctx.windows().geometry(label)?;
+    let mut out = geometry_json(label, &geometry);
+    out["windows"] = ctx.windows().labels().into();
+    Ok(out)
+}
+
+/// `window_set` – move, resize, or (un)maximize a window. Omitted fields
+/// keep their current value.
+///
+/// Args: `{ "label"?: "main", "x"?, "y"?, "width"?, "height"?, "maximized"? }`
+/// Returns: the resulting geometry, as for `window_info`.
+pub(crate) fn cmd_window_set(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let label = label_arg(&args)?;
+    let mut geometry = ctx.windows().geometry(label)?;
+
+    let int = |key: &str| -> Result<Option<i64>, CommandError> {
+        match args.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => v
+                .as_i64()
+                .map(Some)
+                .ok_or_else(|| CommandError::InvalidInput(format!("'{}' must be an integer", key))),
+        }
+    };
+    let position = |key: &str| -> Result<Option<i32>, CommandError> {
+        int(key)?
+            .map(|n| {
+                i32::try_from(n)
+                    .map_err(|_| CommandError::InvalidInput(format!("'{}' out of range", key)))
+            })
+            .transpose()
+    };
+    let size = |key: &str| -> Result<Option<u32>, CommandError> {
+        int(key)?
+            .map(|n| match u32::try_from(n) {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(CommandError::InvalidInput(format!(
+                    "'{}' must be a positive integer",
+                    key
+                ))),
+            })
+            .transpose()
+    };
+
+    geometry.x = position("x")?.unwrap_or(geometry.x);
+    geometry.y = position("y")?.unwrap_or(geometry.y);
+    geometry.width = size("width")?.unwrap_or(geometry.width);
+    geometry.height = size("height")?.unwrap_or(geometry.height);
+    if let Some(v) = args.get("maximized").filter(|v| !v.is_null()) {
+        geometry.maximized = v
+            .as_bool()
+            .ok_or_else(|| CommandError::InvalidInput("'maximized' must be a boolean".into()))?;
+    }
+
+    ctx.windows().set_geometry(label, &geometry)?;
+    let applied = ctx.windows().geometry(label)?;
+    Ok(geometry_json(label, &applied))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::platform::StdFilesystem;
+    use crate::traits::{CapResult, WindowOps};
+    use crate::types::{ErrorCode, Status};
+    use std::sync::Mutex;
+
+    fn geometry(x: i32, width: u32, maximized: bool) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y: 0,
+            width,
+            height: 600,
+            maximized,
+        }
+    }
+
+    /// In-memory windows that apply whatever they are given.
+    struct FakeWindows(Mutex<WindowStates>);
+
+    impl WindowOps for FakeWindows {
+        fn labels(&self) -> Vec<String> {
+            self.0.lock().unwrap().keys().cloned().collect()
+        }
+        fn geometry(&self, label: &str) -> CapResult<WindowGeometry> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(label)
+                .copied()
+                .ok_or_else(|| CapError::Other(format!("no window '{}'", label)))
+        }
+        fn set_geometry(&self, label: &str, geometry: &WindowGeometry) -> CapResult<()> {
+            self.0.lock().unwrap().insert(label.to_string(), *geometry);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_keeps_restored_size_when_maximized() {
+        let tmp = tempfile::tempdir().unwrap();
+        let fs = StdFilesystem;
+        assert!(load(&fs, tmp.path()).is_empty());
+
+        save(&fs, tmp.path(), "main", geometry(10, 800, false)).unwrap();
+        save(&fs, tmp.path(), "main", geometry(0, 1920, true)).unwrap();
+        save(&fs, tmp.path(), "settings", geometry(5, 400, false)).unwrap();
+
+        let states = load(&fs, tmp.path());
+        assert_eq!(states["main"], geometry(10, 800, true));
+        assert_eq!(states["settings"], geometry(5, 400, false));
+
+        std::fs::write(state_path(tmp.path()), "not json").unwrap();
+        assert!(load(&fs, tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_window_commands() {
+        let windows = FakeWindows(Mutex::new(WindowStates::from([(
+            "main".to_string(),
+            geometry(10, 800, false),
+        )])));
+        let ctx = AppContext::default_headless().with_windows(Box::new(windows));
+        let reg = CommandRegistry::new();
+
+        let info = reg.execute("window_info", serde_json::json!({}), &ctx);
+        let data = info.data.unwrap();
+        assert_eq!(data["label"], "main");
+        assert_eq!(data["width"], 800);
+        assert_eq!(data["windows"], serde_json::json!(["main"]));
+
+        let set = reg.execute(
+            "window_set",
+            serde_json::json!({ "width": 1024, "maximized": true }),
+            &ctx,
+        );
+        let data = set.data.unwrap();
+        assert_eq!(data["x"], 10);
+        assert_eq!(data["width"], 1024);
+        assert_eq!(data["maximized"], true);
+
+        let bad = reg.execute("window_set", serde_json::json!({ "width": 0 }), &ctx);
+        assert_eq!(bad.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn test_window_info_headless_is_unsupported() {
+        let ctx = AppContext::default_headless();
+        let r = CommandRegistry::new().execute("window_info", serde_json::json!({}), &ctx);
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.error.unwrap().code, ErrorCode::Unsupported);
+    }
+}
@@ -0,0 +1,146 @@
+//! Environment injection: scenario `env:` blocks and the daemon's `env_set`
+//! method, both applied through [`EnvOps`] so the embedding app decides
+//! where the variables land.
+
+use crate::context::AppContext;
+use crate::traits::EnvOps;
+use crate::types::*;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The value to set for an `env:` entry: strings as is, other scalars in
+/// their YAML/JSON spelling (`8080`, `true`), and `null` to unset.
+pub fn env_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Variables applied for a scenario run; dropping the guard puts back what
+/// was there before.
+pub struct EnvGuard<'a> {
+    env: &'a dyn EnvOps,
+    previous: Vec<(String, Option<String>)>,
+}
+
+impl<'a> EnvGuard<'a> {
+    pub fn apply(env: &'a dyn EnvOps, vars: &BTreeMap<String, Value>) -> Self {
+        let previous = vars
+            .iter()
+            .map(|(name, value)| {
+                let old = env.get(name);
+                env.set(name, env_value(value).as_deref());
+                (name.clone(), old)
+            })
+            .collect();
+        Self { env, previous }
+    }
+}
+
+impl Drop for EnvGuard<'_> {
+    fn drop(&mut self) {
+        for (name, old) in self.previous.iter().rev() {
+            self.env.set(name, old.as_deref());
+        }
+    }
+}
+
+/// Daemon method `env_set`: params `{"vars": {"NAME": "value" | null}}`.
+/// Not undone automatically; `data.previous` holds the old values, which
+/// can be sent back to restore them.
+pub fn run_env_set(params: Value, ctx: &AppContext) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let Some(vars) = params.get("vars").and_then(Value::as_object) else {
+        return result_err(
+            "env_set",
+            "",
+            &run_id,
+            0,
+            ErrorCode::InvalidInput,
+            "expected params {\"vars\": {\"NAME\": \"value\" | null}}",
+        );
+    };
+    let env = ctx.env();
+    let mut previous = serde_json::Map::new();
+    for (name, value) in vars {
+        previous.insert(
+            name.clone(),
+            env.get(name).map_or(Value::Null, Value::String),
+        );
+        env.set(name, env_value(value).as_deref());
+    }
+    let names: Vec<&str> = vars.keys().map(String::as_str).collect();
+    // Names only: values may be secrets.
+    ctx.events().emit(
+        &run_id,
+        "config:changed",
+        serde_json::json!({ "vars": names }),
+    );
+    let mut r = result_ok("env_set", &names.join(","), &run_id, 0);
+    r.data = Some(serde_json::json!({ "previous": previous }));
+    r
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// An [`EnvOps`] over a shared map, so tests leave the process alone.
+    #[derive(Clone, Default)]
+    pub(crate) struct MapEnv(pub(crate) Arc<Mutex<HashMap<String, String>>>);
+
+    impl EnvOps for MapEnv {
+        fn get(&self, name: &str) -> Option<String> {
+            self.0.lock().unwrap().get(name).cloned()
+        }
+
+        fn set(&self, name: &str, value: Option<&str>) {
+            let mut vars = self.0.lock().unwrap();
+            match value {
+                Some(v) => vars.insert(name.to_string(), v.to_string()),
+                None => vars.remove(name),
+            };
+        }
+    }
+
+    #[test]
+    fn test_guard_sets_and_restores() {
+        let env = MapEnv::default();
+        env.set("KEEP", Some("old"));
+        env.set("DROP", Some("here"));
+        let vars: BTreeMap<String, Value> = serde_json::from_value(serde_json::json!({
+            "KEEP": "new", "DROP": null, "PORT": 8080
+        }))
+        .unwrap();
+        {
+            let _guard = EnvGuard::apply(&env, &vars);
+            assert_eq!(env.get("KEEP").as_deref(), Some("new"));
+            assert_eq!(env.get("DROP"), None);
+            assert_eq!(env.get("PORT").as_deref(), Some("8080"));
+        }
+        assert_eq!(env.get("KEEP").as_deref(), Some("old"));
+        assert_eq!(env.get("DROP").as_deref(), Some("here"));
+        assert_eq!(env.get("PORT"), None);
+    }
+
+    #[test]
+    fn test_env_set_reports_previous_values() {
+        let env = MapEnv::default();
+        env.set("A", Some("1"));
+        let ctx = AppContext::default_headless().with_env(Box::new(env.clone()));
+        let r = run_env_set(serde_json::json!({ "vars": { "A": "2", "B": "x" } }), &ctx);
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(
+            r.data.unwrap()["previous"],
+            serde_json::json!({ "A": "1", "B": null })
+        );
+        assert_eq!(env.get("A").as_deref(), Some("2"));
+
+        let bad = run_env_set(serde_json::json!({}), &ctx);
+        assert_eq!(bad.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+}
@@ -0,0 +1,144 @@
+//! Engine event bus – fan out progress events to interested transports.
+//!
+//! Long-running operations (e.g. LLM streaming) publish [`EngineEvent`]s
+//! here, and commands and probes announce when they start and finish.
+//! Transports plug in as an [`EventSink`] (the Tauri wrapper forwards events
+//! with `emit`, the CLI appends them to `events.jsonl`) or as plain closures
+//! (the daemon turns them into progress frames), so the engine stays free of
+//! any transport types.
+
+use crate::types::EngineEvent;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Handle returned by [`EventBus::subscribe`], used to unsubscribe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Whether `topic` is selected by `pattern`: `*` matches every topic,
+/// `prefix*` (e.g. `command:*`) every topic starting with `prefix`, and
+/// anything else only itself.
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => pattern == topic,
+    }
+}
+
+/// A transport that receives every published event.
+pub trait EventSink: Send + Sync {
+    fn send(&self, event: &EngineEvent);
+}
+
+type Subscriber = Arc<dyn Fn(&EngineEvent) + Send + Sync>;
+
+#[derive(Default)]
+pub struct EventBus {
+    next_id: AtomicU64,
+    subscribers: Mutex<Vec<(SubscriptionId, Subscriber)>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback invoked synchronously for every published event.
+    pub fn subscribe<F>(&self, f: F) -> SubscriptionId
+    where
+        F: Fn(&EngineEvent) + Send + Sync + 'static,
+    {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, Arc::new(f)));
+        id
+    }
+
+    /// Forward every published event to `sink` until unsubscribed.
+    pub fn attach(&self, sink: Arc<dyn EventSink>) -> SubscriptionId {
+        self.subscribe(move |event| sink.send(event))
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(sid, _)| *sid != id);
+    }
+
+    /// Deliver an event to every current subscriber.
+    pub fn publish(&self, event: &EngineEvent) {
+        // Snapshot the list so callbacks may (un)subscribe without deadlocking.
+        let subscribers: Vec<Subscriber> = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(_, f)| f.clone())
+            .collect();
+        for f in subscribers {
+            f(event);
+        }
+    }
+
+    /// Convenience wrapper building the event in place.
+    pub fn emit(&self, run_id: &str, topic: &str, payload: serde_json::Value) {
+        self.publish(&EngineEvent {
+            run_id: run_id.to_string(),
+            topic: topic.to_string(),
+            payload,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribe_publish_unsubscribe() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let id = bus.subscribe(move |ev| sink.lock().unwrap().push(ev.topic.clone()));
+
+        bus.emit("r1", "test:one", serde_json::Value::Null);
+        bus.unsubscribe(id);
+        bus.emit("r1", "test:two", serde_json::Value::Null);
+
+        assert_eq!(*seen.lock().unwrap(), vec!["test:one".to_string()]);
+    }
+
+    #[test]
+    fn test_topic_patterns() {
+        assert!(topic_matches("*", "probe:finished"));
+        assert!(topic_matches("command:*", "command:started"));
+        assert!(topic_matches("probe:finished", "probe:finished"));
+        assert!(!topic_matches("probe:finished", "probe:step"));
+        assert!(!topic_matches("command:*", "commands"));
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl EventSink for Recorder {
+        fn send(&self, event: &EngineEvent) {
+            self.0.lock().unwrap().push(event.topic.clone());
+        }
+    }
+
+    #[test]
+    fn test_attached_sink_receives_events() {
+        let bus = EventBus::new();
+        let sink = Arc::new(Recorder::default());
+        let id = bus.attach(sink.clone());
+
+        bus.emit("r1", "test:one", serde_json::Value::Null);
+        bus.unsubscribe(id);
+        bus.emit("r1", "test:two", serde_json::Value::Null);
+
+        assert_eq!(*sink.0.lock().unwrap(), vec!["test:one".to_string()]);
+    }
+}
@@ -0,0 +1,359 @@
+//! Result export: after a run with `--artifacts`, push its directory
+//! (`result.json`, `events.jsonl`, ...) to where a VM fleet collects them,
+//! instead of wrapper scripts copying files off every machine.
+//!
+//! Targets come from a YAML file named by `$APP__EXPORT`:
+//!
+//! ```yaml
+//! targets:
+//!   - kind: s3              # AWS, MinIO, R2, ... (path-style, SigV4)
+//!     endpoint: https://minio.lab:9000
+//!     bucket: vm-results
+//!     prefix: nightly
+//!   - kind: http            # multipart POST per file
+//!     url: https://results.lab/upload
+//!     headers: { Authorization: "Bearer ${RESULTS_TOKEN}" }
+//!   - kind: directory       # e.g. a mounted network share
+//!     path: /mnt/results
+//! attempts: 3
+//! redact_keys: [hostname]
+//! ```
+//!
+//! Each file is retried with backoff. Files are redacted first: in JSON
+//! and JSON Lines, values under keys named by `redact_keys` (secrets and
+//! tokens by default) become `[redacted]`; in other text files, so do the
+//! values of `key=value` and `key: value` pairs, and credentials in URLs.
+//! A key is named when it ends in the configured words, so `token` covers
+//! `auth_token` and `accessToken` but not `input_tokens`. Binary files are
+//! uploaded as they are. Offline mode exports nothing.
+//!
+//! [`ResultExporter`] is the extension point; [`exporters`] builds the
+//! configured ones and [`export_run`] takes any list.
+
+mod redact;
+mod targets;
+
+pub(crate) use redact::{redact_urls, redact_value};
+pub(crate) use targets::hex;
+
+use crate::context::AppContext;
+use crate::traits::*;
+use crate::types::*;
+use redact::redact_file;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use targets::{DirectoryExporter, HttpExporter, S3Exporter};
+
+/// YAML file with the export config.
+pub const EXPORT_ENV: &str = "APP__EXPORT";
+
+/// Where and how run artifacts are exported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    pub targets: Vec<ExportTarget>,
+    /// Tries per file, including the first.
+    pub attempts: u32,
+    /// Delay before the first retry; doubled for each one after.
+    pub retry_delay_ms: u64,
+    /// Keys whose values are redacted, matched case-insensitively against
+    /// the last words of a key (see [`key_matches`](redact::key_matches)).
+    pub redact_keys: Vec<String>,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            attempts: 3,
+            retry_delay_ms: 500,
+            redact_keys: [
+                "api_key",
+                "apikey",
+                "token",
+                "secret",
+                "password",
+                "authorization",
+            ]
+            .iter()
+            .map(|k| k.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl ExportConfig {
+    /// The config in `$APP__EXPORT`, or none.
+    pub fn from_env() -> Self {
+        let Some(path) = std::env::var_os(EXPORT_ENV).filter(|v| !v.is_empty()) else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|yaml| Self::load(&yaml))
+        {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("ignoring {}: {}", EXPORT_ENV, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn load(yaml: &str) -> Result<Self, String> {
+        serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse export config: {}", e))
+    }
+}
+
+/// One export destination.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ExportTarget {
+    /// Copy into `<path>/<run_id>/`.
+    Directory { path: PathBuf },
+    /// POST each file as a multipart form (`run_id`, `name`, `file`).
+    /// `${VAR}` in header values is replaced from the environment.
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+    /// PUT `<prefix>/<run_id>/<name>` into a bucket, path-style. Credentials
+    /// are read from the named environment variables.
+    S3 {
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_region")]
+        region: String,
+        #[serde(default)]
+        prefix: String,
+        #[serde(default = "default_access_key_env")]
+        access_key_env: String,
+        #[serde(default = "default_secret_key_env")]
+        secret_key_env: String,
+        #[serde(default = "default_session_token_env")]
+        session_token_env: String,
+    },
+}
+
+fn default_region() -> String {
+    "us-east-1".into()
+}
+
+fn default_access_key_env() -> String {
+    "AWS_ACCESS_KEY_ID".into()
+}
+
+fn default_secret_key_env() -> String {
+    "AWS_SECRET_ACCESS_KEY".into()
+}
+
+fn default_session_token_env() -> String {
+    "AWS_SESSION_TOKEN".into()
+}
+
+/// Exporters for `ctx.export.targets`, sending through `ctx.network()`.
+pub fn exporters(ctx: &AppContext) -> Vec<Box<dyn ResultExporter + '_>> {
+    ctx.export
+        .targets
+        .iter()
+        .map(|target| -> Box<dyn ResultExporter + '_> {
+            match target {
+                ExportTarget::Directory { path } => Box::new(DirectoryExporter { root: path }),
+                ExportTarget::Http { url, headers } => Box::new(HttpExporter { ctx, url, headers }),
+                ExportTarget::S3 { .. } => Box::new(S3Exporter { ctx, target }),
+            }
+        })
+        .collect()
+}
+
+/// What happened at one target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetReport {
+    pub target: String,
+    /// Where each exported file ended up.
+    pub files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Export every file in `run_dir` (named after its run id) to each of
+/// `exporters`. A target stops at its first file that fails all attempts;
+/// the others carry on. Nothing is exported in offline mode.
+pub async fn export_run(
+    ctx: &AppContext,
+    exporters: &[Box<dyn ResultExporter + '_>],
+    run_dir: &Path,
+) -> Result<Vec<TargetReport>, CapError> {
+    if ctx.offline {
+        return Err(CapError::Network(OFFLINE_REASON.into()));
+    }
+    let run_id = run_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| CapError::Other(format!("not a run directory: {}", run_dir.display())))?;
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(run_dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let bytes = redact_file(&name, std::fs::read(&path)?, &ctx.export.redact_keys);
+            files.push((name, bytes));
+        }
+    }
+    files.sort();
+
+    let mut reports = Vec::new();
+    for exporter in exporters {
+        let mut report = TargetReport {
+            target: exporter.describe(),
+            files: Vec::new(),
+            error: None,
+        };
+        for (name, bytes) in &files {
+            match put_with_retry(ctx, exporter.as_ref(), &run_id, name, bytes).await {
+                Ok(location) => report.files.push(location),
+                Err(e) => {
+                    report.error = Some(format!("{}: {}", name, e));
+                    break;
+                }
+            }
+        }
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+async fn put_with_retry(
+    ctx: &AppContext,
+    exporter: &dyn ResultExporter,
+    run_id: &str,
+    name: &str,
+    bytes: &[u8],
+) -> CapResult<String> {
+    let mut delay = ctx.export.retry_delay_ms;
+    let mut attempt = 1;
+    loop {
+        match exporter.put(run_id, name, bytes).await {
+            Ok(location) => return Ok(location),
+            Err(e) if attempt < ctx.export.attempts.max(1) => {
+                tracing::debug!(
+                    "export of {} to {} failed (attempt {}): {}",
+                    name,
+                    exporter.describe(),
+                    attempt,
+                    e
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// `export` result: export the run in `run_dir` to the configured targets.
+/// Skips in offline mode.
+pub async fn run_export(ctx: &AppContext, run_dir: &Path) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    let target = run_dir.display().to_string();
+    if ctx.offline {
+        return result_offline("export", &target, &run_id);
+    }
+    if ctx.export.targets.is_empty() {
+        return result_err(
+            "export",
+            &target,
+            &run_id,
+            start.elapsed_ms(),
+            ErrorCode::InvalidInput,
+            format!("no export targets configured (set ${})", EXPORT_ENV),
+        );
+    }
+    let reports = match export_run(ctx, &exporters(ctx), run_dir).await {
+        Ok(r) => r,
+        Err(e) => {
+            return result_err(
+                "export",
+                &target,
+                &run_id,
+                start.elapsed_ms(),
+                e.error_code(),
+                e.to_string(),
+            )
+        }
+    };
+
+    let failed: Vec<&TargetReport> = reports.iter().filter(|r| r.error.is_some()).collect();
+    let mut r = if failed.is_empty() {
+        result_ok("export", &target, &run_id, start.elapsed_ms())
+    } else {
+        let names: Vec<&str> = failed.iter().map(|r| r.target.as_str()).collect();
+        result_err(
+            "export",
+            &target,
+            &run_id,
+            start.elapsed_ms(),
+            ErrorCode::NetworkError,
+            format!("export failed for {}", names.join(", ")),
+        )
+    };
+    r.data = Some(serde_json::json!({ "targets": reports }));
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+
+    #[tokio::test]
+    async fn test_export_retries_and_reports_per_target() {
+        let tmp = tempfile::tempdir().unwrap();
+        let run_dir = tmp.path().join("run-1");
+        std::fs::create_dir_all(&run_dir).unwrap();
+        std::fs::write(run_dir.join("result.json"), r#"{"secret":"s"}"#).unwrap();
+
+        let network = MockNetwork::new()
+            .fail("https://up.example/", "connection reset")
+            .respond(
+                "https://up.example/",
+                HttpResponse::from_json(201, &serde_json::json!({})),
+            )
+            .respond(
+                "https://down.example/",
+                HttpResponse::from_json(500, &serde_json::json!({})),
+            );
+        let mut ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(network),
+            Box::new(HeadlessClipboard),
+        );
+        ctx.export = ExportConfig::load(&format!(
+            "targets:\n  - kind: http\n    url: https://up.example/\n  - kind: http\n    url: https://down.example/\n  - kind: directory\n    path: {}\nattempts: 2\nretry_delay_ms: 1\n",
+            tmp.path().join("share").display()
+        ))
+        .unwrap();
+
+        let r = run_export(&ctx, &run_dir).await;
+        assert_eq!(r.status, Status::Error);
+        let targets = &r.data.as_ref().unwrap()["targets"];
+        assert_eq!(targets[0]["files"].as_array().unwrap().len(), 1);
+        assert!(targets[1]["error"].as_str().unwrap().contains("HTTP 500"));
+        let copied = std::fs::read_to_string(tmp.path().join("share/run-1/result.json")).unwrap();
+        assert_eq!(copied, r#"{"secret":"[redacted]"}"#);
+
+        ctx.offline = true;
+        let r = run_export(&ctx, &run_dir).await;
+        assert_eq!(r.status, Status::Skip);
+        assert!(export_run(&ctx, &exporters(&ctx), &run_dir).await.is_err());
+
+        assert!(ExportConfig::load("targets:\n  - kind: ftp\n").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Redaction of exported files: JSON values under named keys, `key=value`
+//! pairs in text, and credentials in URLs.
+
+pub(super) fn redact_file(name: &str, bytes: Vec<u8>, keys: &[String]) -> Vec<u8> {
+    if keys.is_empty() {
+        return bytes;
+    }
+    let redact_json = |text: &str| -> Option<String> {
+        let mut value: serde_json::Value = serde_json::from_str(text).ok()?;
+        redact_value(&mut value, keys);
+        Some(value.to_string())
+    };
+    let Ok(text) = std::str::from_utf8(&bytes) else {
+        return bytes;
+    };
+    if name.ends_with(".json") {
+        if let Some(out) = redact_json(text) {
+            return out.into_bytes();
+        }
+    }
+    let jsonl = name.ends_with(".jsonl");
+    text.split('\n')
+        .map(|line| match redact_json(line).filter(|_| jsonl) {
+            Some(out) => out,
+            None => redact_line(line, keys),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into_bytes()
+}
+
+pub(crate) fn redact_value(value: &mut serde_json::Value, keys: &[String]) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if !v.is_null() && key_matches(k, keys) {
+                    *v = "[redacted]".into();
+                } else {
+                    redact_value(v, keys);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|v| redact_value(v, keys)),
+        _ => {}
+    }
+}
+
+/// Whether `key` is named by one of `keys`: its last words, split at
+/// punctuation and camelCase humps, are all of a configured key's words.
+/// `token` matches `token`, `auth_token`, and `accessToken`, but not
+/// `input_tokens` or `token_count`.
+pub(crate) fn key_matches(key: &str, keys: &[String]) -> bool {
+    let words = key_words(key);
+    keys.iter().any(|k| {
+        let wanted = key_words(k);
+        !wanted.is_empty() && words.ends_with(&wanted)
+    })
+}
+
+fn key_words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut after_lower = false;
+    for c in key.chars() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            after_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && after_lower {
+            words.push(std::mem::take(&mut word));
+        }
+        after_lower = !c.is_uppercase();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// `line` with URL credentials and the values of `key=value` and
+/// `key: value` pairs whose key matches `keys` replaced. A `key:` value
+/// runs to the end of the line (e.g. `Authorization: Bearer ...`), a
+/// `key=` one to the next space, `&`, `,`, or `;`; quoted values to their
+/// closing quote.
+fn redact_line(line: &str, keys: &[String]) -> String {
+    let line = redact_urls(line);
+    let is_key_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+    let mut out = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while let Some(found) = line[i..].find(['=', ':']) {
+        let sep = i + found;
+        i = sep + 1;
+        let before = line[..sep].trim_end();
+        let key_end = before.strip_suffix(['"', '\'']).unwrap_or(before).len();
+        if key_end <= copied {
+            continue;
+        }
+        let key_start = line[copied..key_end]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_key_char(*c))
+            .map_or(copied, |(p, c)| copied + p + c.len_utf8());
+        if key_start == key_end || !key_matches(&line[key_start..key_end], keys) {
+            continue;
+        }
+        let value = sep + 1 + (line[sep + 1..].len() - line[sep + 1..].trim_start().len());
+        let rest = &line[value..];
+        let (start, end) = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => (
+                value + 1,
+                rest[1..].find(q).map_or(line.len(), |p| value + 1 + p),
+            ),
+            _ if line[sep..].starts_with(':') => {
+                (value, rest.find('\r').map_or(line.len(), |p| value + p))
+            }
+            _ => (
+                value,
+                rest.find(|c: char| c.is_whitespace() || matches!(c, '&' | ',' | ';'))
+                    .map_or(line.len(), |p| value + p),
+            ),
+        };
+        if start >= end {
+            continue;
+        }
+        out.push_str(&line[copied..start]);
+        out.push_str("[redacted]");
+        copied = end;
+        i = end;
+    }
+    out.push_str(&line[copied..]);
+    out
+}
+
+/// `text` with the user-info part of any URL (`user:pass@`) replaced.
+pub(crate) fn redact_urls(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find("://") {
+        let (head, tail) = rest.split_at(i + 3);
+        out.push_str(head);
+        let authority_end = tail
+            .find(|c: char| c == '/' || c == '?' || c == '#' || c.is_whitespace() || c == '"')
+            .unwrap_or(tail.len());
+        match tail[..authority_end].rfind('@') {
+            Some(at) => {
+                out.push_str("[redacted]");
+                rest = &tail[at..];
+            }
+            None => rest = tail,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExportConfig;
+
+    #[test]
+    fn test_redaction() {
+        let keys = ExportConfig::default().redact_keys;
+        let json = br#"{"data":{"api_key":"sk-1","items":[{"Auth_Token":"t"}],"token_count":null},"ok":1}"#;
+        let out = String::from_utf8(redact_file("result.json", json.to_vec(), &keys)).unwrap();
+        assert!(!out.contains("sk-1") && !out.contains("\"t\""));
+        assert!(out.contains("\"token_count\":null"));
+
+        let usage =
+            br#"{"input_tokens":12,"output_tokens":3,"accessToken":"a","openaiApiKey":"k"}"#;
+        let out = String::from_utf8(redact_file("usage.json", usage.to_vec(), &keys)).unwrap();
+        assert_eq!(
+            out,
+            r#"{"accessToken":"[redacted]","input_tokens":12,"openaiApiKey":"[redacted]","output_tokens":3}"#
+        );
+
+        let lines = b"{\"password\":\"p\"}\nnot json\n";
+        let out = String::from_utf8(redact_file("events.jsonl", lines.to_vec(), &keys)).unwrap();
+        assert_eq!(out, "{\"password\":\"[redacted]\"}\nnot json\n");
+
+        let log = "GET /x?token=abc&page=2 via https://bob:pw@proxy:3128/\r\nAuthorization: Bearer xyz\r\ninput_tokens=5 db_password = \"p w\"\n";
+        let out =
+            String::from_utf8(redact_file("log.txt", log.as_bytes().to_vec(), &keys)).unwrap();
+        assert_eq!(
+            out,
+            "GET /x?token=[redacted]&page=2 via https://[redacted]@proxy:3128/\r\nAuthorization: [redacted]\r\ninput_tokens=5 db_password = \"[redacted]\"\n"
+        );
+        let binary = vec![0xff, 0xfe, b't'];
+        assert_eq!(redact_file("shot.png", binary.clone(), &keys), binary);
+    }
+}
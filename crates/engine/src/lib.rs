@@ -4,16 +4,48 @@
 //! traits. It does NOT depend on Tauri runtime types, so it can be used
 //! by both the GUI wrapper and the headless CLI test harness.
 
+pub mod blobs;
+pub mod build_info;
+pub mod clock;
 pub mod commands;
 pub mod context;
-pub mod doctor;
+pub mod env;
+pub mod events;
+pub mod export;
+pub mod ids;
+pub mod limits;
+pub mod llm;
 pub mod platform;
+pub mod pool;
 pub mod probes;
-pub mod scenario;
+pub mod prompts;
+pub mod search;
+pub mod telemetry;
 pub mod traits;
 pub mod types;
 
+// Grouped into folders; each module keeps its `engine::<module>` path.
+mod checks;
+mod daemon;
+mod desktop;
+mod storage;
+mod system;
+mod testing;
+
+pub use checks::{
+    compat, diagnostics, doctor, endpoints, explain, first_run, readiness, suites, updates,
+};
+pub use daemon::{client, daemon_sessions, fleet, framing, metrics, protocol};
+pub use desktop::{
+    autostart, clipboard, dialogs, display, menu, opener, portals, shared_state, shortcuts, trash,
+    windows,
+};
+pub use storage::{backup, cache, consent, credentials, history, migrations, profile, queue, zip};
+pub use system::{devices, host, interfaces, processes, resources, sandbox, session, tls};
+pub use testing::{dataset, http_fixture, scenario, scenario_dir};
+
 // Re-exports for convenience
 pub use commands::CommandRegistry;
 pub use context::AppContext;
+pub use probes::ProbeRegistry;
 pub use types::{CommandResult, ErrorCode, ErrorInfo, Status};
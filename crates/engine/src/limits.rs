@@ -0,0 +1,168 @@
+//! Per-command timeouts and soft memory limits, so one buggy or runaway
+//! handler cannot hang the GUI's invoke pipeline or the daemon.
+//!
+//! [`crate::commands::CommandRegistry::execute_limited`] hands a command
+//! with a limit to its worker pool from a watcher thread and waits on it:
+//! past `timeout_ms` the call fails with `TIMEOUT`, and once the process's
+//! resident memory has grown by more than `memory_mb` since the command
+//! started, with `RESOURCE_EXHAUSTED`. Threads cannot be killed, so the
+//! handler keeps running (and holding its pool thread) in the background
+//! and its eventual result is dropped. The
+//! memory limit is soft: it measures the whole process, so concurrent
+//! commands count against each other.
+//!
+//! Limits come from `command_limits:` in the GUI config or
+//! `$APP__COMMAND_LIMITS` (the same JSON), e.g.
+//! `{"default": {"timeout_ms": 30000}, "commands": {"find_files":
+//! {"timeout_ms": 120000, "memory_mb": 512}}}`.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Environment variable holding [`CommandLimits`] as JSON; replaces the
+/// config's `command_limits`.
+pub const COMMAND_LIMITS_ENV: &str = "APP__COMMAND_LIMITS";
+
+/// How often the watchdog checks the deadline and memory.
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Limits for one command; `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandLimit {
+    pub timeout_ms: Option<u64>,
+    /// Most the process may grow while the command runs, in MiB.
+    pub memory_mb: Option<u64>,
+}
+
+impl CommandLimit {
+    pub fn is_unlimited(&self) -> bool {
+        self.timeout_ms.is_none() && self.memory_mb.is_none()
+    }
+}
+
+/// `default` applies to every command; entries in `commands` override it
+/// field by field.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandLimits {
+    pub default: CommandLimit,
+    pub commands: BTreeMap<String, CommandLimit>,
+}
+
+impl CommandLimits {
+    /// The limit `command` runs with.
+    pub fn for_command(&self, command: &str) -> CommandLimit {
+        let own = self.commands.get(command).copied().unwrap_or_default();
+        CommandLimit {
+            timeout_ms: own.timeout_ms.or(self.default.timeout_ms),
+            memory_mb: own.memory_mb.or(self.default.memory_mb),
+        }
+    }
+
+    /// `$APP__COMMAND_LIMITS` if set and valid, else no limits.
+    pub fn from_env() -> Self {
+        std::env::var(COMMAND_LIMITS_ENV)
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .and_then(|v| match serde_json::from_str(&v) {
+                Ok(limits) => Some(limits),
+                Err(e) => {
+                    tracing::warn!("ignoring {}: {}", COMMAND_LIMITS_ENV, e);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn process_rss(ctx: &AppContext) -> Option<u64> {
+    ctx.resources().sample().ok()?.process_rss_bytes
+}
+
+/// Run `job` (a command's handler) on a watcher thread and wait for it
+/// within `limit`.
+pub fn run_limited(
+    name: &str,
+    ctx: &Arc<AppContext>,
+    limit: CommandLimit,
+    job: impl FnOnce(&AppContext) -> Result<Value, CommandError> + Send + 'static,
+) -> Result<Value, CommandError> {
+    let (tx, rx) = mpsc::channel();
+    let worker_ctx = Arc::clone(ctx);
+    std::thread::Builder::new()
+        .name(format!("command-{}", name))
+        .spawn(move || {
+            let _ = tx.send(job(&worker_ctx));
+        })?;
+
+    let started = Instant::now();
+    let baseline = limit.memory_mb.and_then(|_| process_rss(ctx));
+    loop {
+        let wait = match limit.timeout_ms {
+            Some(ms) => Duration::from_millis(ms)
+                .saturating_sub(started.elapsed())
+                .min(WATCHDOG_INTERVAL),
+            None => WATCHDOG_INTERVAL,
+        };
+        match rx.recv_timeout(wait) {
+            Ok(result) => return result,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(CommandError::Other(format!("{} panicked", name)));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        if let Some(ms) = limit.timeout_ms {
+            if started.elapsed() >= Duration::from_millis(ms) {
+                return Err(CommandError::Timeout(format!(
+                    "{} did not finish within {} ms; it keeps running in the background",
+                    name, ms
+                )));
+            }
+        }
+        if let (Some(mb), Some(before)) = (limit.memory_mb, baseline) {
+            let grown = process_rss(ctx).unwrap_or(before).saturating_sub(before);
+            if grown > mb * 1024 * 1024 {
+                return Err(CommandError::ResourceExhausted(format!(
+                    "{} grew the process by {} MiB, over its {} MiB limit; \
+                     it keeps running in the background",
+                    name,
+                    grown / (1024 * 1024),
+                    mb
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_command_limits_override_the_default() {
+        let limits: CommandLimits = serde_json::from_value(serde_json::json!({
+            "default": { "timeout_ms": 30000 },
+            "commands": { "find_files": { "timeout_ms": 120000, "memory_mb": 512 },
+                          "ping": { "memory_mb": 8 } }
+        }))
+        .unwrap();
+        assert_eq!(
+            limits.for_command("find_files"),
+            CommandLimit {
+                timeout_ms: Some(120000),
+                memory_mb: Some(512)
+            }
+        );
+        assert_eq!(limits.for_command("ping").timeout_ms, Some(30000));
+        assert_eq!(limits.for_command("other").memory_mb, None);
+        assert!(CommandLimits::default().for_command("x").is_unlimited());
+        assert!(serde_json::from_value::<CommandLimits>(serde_json::json!({ "x": 1 })).is_err());
+    }
+}
@@ -0,0 +1,500 @@
+//! [`HttpLlm`] – talks to provider REST APIs via reqwest.
+//!
+//! Three wire formats are covered: OpenAI-compatible chat completions
+//! (OpenAI, Groq, Perplexity), Anthropic messages, and Gemini
+//! `generateContent`. Streaming uses each provider's SSE variant.
+
+use super::*;
+use crate::traits::{CapError, CapResult};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_MS: u64 = 120_000;
+const CHECK_TIMEOUT_MS: u64 = 10_000;
+/// Anthropic requires `max_tokens`; used when neither request nor settings set it.
+const ANTHROPIC_FALLBACK_MAX_TOKENS: u32 = 4096;
+
+pub struct HttpLlm {
+    settings: LlmSettings,
+}
+
+impl HttpLlm {
+    pub fn new(settings: LlmSettings) -> Self {
+        Self { settings }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(LlmSettings::from_env())
+    }
+
+    fn key(&self, provider: LlmProvider) -> CapResult<&str> {
+        self.settings.api_key(provider).ok_or_else(|| {
+            CapError::DependencyMissing(format!(
+                "no API key for {} (set {})",
+                provider.as_str(),
+                provider.api_key_env()
+            ))
+        })
+    }
+
+    async fn send(&self, req: &LlmRequest, stream: bool) -> CapResult<(String, reqwest::Response)> {
+        let model = req
+            .model
+            .clone()
+            .unwrap_or_else(|| self.settings.default_model.clone());
+        let (provider, name) = split_model(&model).map_err(CapError::Other)?;
+        let key = self.key(provider)?;
+
+        let body = build_body(provider, name, req, &self.settings, stream);
+        let url = endpoint(provider, name, stream);
+        let client = http_client(REQUEST_TIMEOUT_MS)?;
+        let builder = authorize(provider, client.post(&url), key).json(&body);
+        let resp = send_checked(provider, builder).await?;
+        Ok((model, resp))
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmOps for HttpLlm {
+    async fn complete(&self, req: &LlmRequest) -> CapResult<LlmResponse> {
+        let (model, resp) = self.send(req, false).await?;
+        let provider = split_model(&model).map_err(CapError::Other)?.0;
+        let json: Value = resp
+            .json()
+            .await
+            .map_err(|e| CapError::Network(format!("decoding response: {}", e)))?;
+        let chunk = parse_chunk(provider, &json);
+        Ok(LlmResponse {
+            model,
+            content: chunk.text.unwrap_or_default(),
+            finish_reason: chunk.finish_reason,
+            usage: chunk.usage,
+        })
+    }
+
+    async fn stream(&self, req: &LlmRequest, on_delta: &mut OnDelta<'_>) -> CapResult<LlmResponse> {
+        let (model, mut resp) = self.send(req, true).await?;
+        let provider = split_model(&model).map_err(CapError::Other)?.0;
+
+        let mut out = LlmResponse {
+            model,
+            content: String::new(),
+            finish_reason: None,
+            usage: None,
+        };
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            let bytes = resp.chunk().await.map_err(|e| {
+                if e.is_timeout() {
+                    CapError::Timeout
+                } else {
+                    CapError::Network(format!("reading stream: {}", e))
+                }
+            })?;
+            let Some(bytes) = bytes else { break };
+            buf.extend_from_slice(&bytes);
+
+            // SSE events are newline-delimited `data: {...}` lines.
+            while let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buf.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                if let Some(chunk) = parse_sse_line(provider, line.trim_end()) {
+                    apply_chunk(&mut out, chunk, on_delta);
+                }
+            }
+        }
+        let tail = String::from_utf8_lossy(&buf);
+        if let Some(chunk) = parse_sse_line(provider, tail.trim_end()) {
+            apply_chunk(&mut out, chunk, on_delta);
+        }
+        Ok(out)
+    }
+
+    fn settings(&self) -> Option<&LlmSettings> {
+        Some(&self.settings)
+    }
+
+    fn configured_providers(&self) -> Vec<LlmProvider> {
+        LlmProvider::ALL
+            .into_iter()
+            .filter(|p| self.settings.api_key(*p).is_some())
+            .collect()
+    }
+
+    /// Authenticated model listing – verifies reachability and the key
+    /// without spending tokens. Perplexity has no listing endpoint, so it
+    /// gets a one-token completion instead.
+    async fn check_provider(&self, provider: LlmProvider) -> CapResult<()> {
+        let key = self.key(provider)?;
+        let client = http_client(CHECK_TIMEOUT_MS)?;
+        let builder = match provider {
+            LlmProvider::Openai => client.get("https://api.openai.com/v1/models"),
+            LlmProvider::Groq => client.get("https://api.groq.com/openai/v1/models"),
+            LlmProvider::Anthropic => client.get("https://api.anthropic.com/v1/models"),
+            LlmProvider::Gemini => {
+                client.get("https://generativelanguage.googleapis.com/v1beta/models")
+            }
+            LlmProvider::Perplexity => {
+                client
+                    .post(endpoint(provider, "sonar", false))
+                    .json(&serde_json::json!({
+                        "model": "sonar",
+                        "messages": [{ "role": "user", "content": "ping" }],
+                        "max_tokens": 1,
+                    }))
+            }
+        };
+        send_checked(provider, authorize(provider, builder, key)).await?;
+        Ok(())
+    }
+}
+
+fn http_client(timeout_ms: u64) -> CapResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .build()
+        .map_err(|e| CapError::Network(format!("failed to build HTTP client: {}", e)))
+}
+
+fn authorize(
+    provider: LlmProvider,
+    builder: reqwest::RequestBuilder,
+    key: &str,
+) -> reqwest::RequestBuilder {
+    match provider {
+        LlmProvider::Anthropic => builder
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        LlmProvider::Gemini => builder.header("x-goog-api-key", key),
+        _ => builder.bearer_auth(key),
+    }
+}
+
+/// Send a request and turn non-2xx statuses into classified errors.
+async fn send_checked(
+    provider: LlmProvider,
+    builder: reqwest::RequestBuilder,
+) -> CapResult<reqwest::Response> {
+    let resp = builder.send().await.map_err(|e| {
+        if e.is_timeout() {
+            CapError::Timeout
+        } else {
+            CapError::Network(format!("{} request failed: {}", provider.as_str(), e))
+        }
+    })?;
+
+    let status = resp.status().as_u16();
+    if !resp.status().is_success() {
+        let text = resp.text().await.unwrap_or_default();
+        let snippet: String = text.chars().take(512).collect();
+        return Err(status_error(provider, status, &snippet));
+    }
+    Ok(resp)
+}
+
+fn apply_chunk(out: &mut LlmResponse, chunk: Chunk, on_delta: &mut OnDelta<'_>) {
+    if let Some(text) = chunk.text.filter(|t| !t.is_empty()) {
+        on_delta(&text);
+        out.content.push_str(&text);
+    }
+    if chunk.finish_reason.is_some() {
+        out.finish_reason = chunk.finish_reason;
+    }
+    if let Some(u) = chunk.usage {
+        // Anthropic reports input and output tokens in separate events.
+        let acc = out.usage.get_or_insert_with(LlmUsage::default);
+        acc.input_tokens = acc.input_tokens.max(u.input_tokens);
+        acc.output_tokens = acc.output_tokens.max(u.output_tokens);
+    }
+}
+
+fn status_error(provider: LlmProvider, status: u16, body: &str) -> CapError {
+    let name = provider.as_str();
+    match status {
+        401 | 403 => CapError::PermissionDenied(format!("{} rejected the API key: {}", name, body)),
+        429 => CapError::RateLimited(format!(
+            "{} rate limited or quota exceeded (HTTP 429): {}",
+            name, body
+        )),
+        _ => CapError::Network(format!("{} returned HTTP {}: {}", name, status, body)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wire formats
+// ---------------------------------------------------------------------------
+
+fn endpoint(provider: LlmProvider, model: &str, stream: bool) -> String {
+    match provider {
+        LlmProvider::Openai => "https://api.openai.com/v1/chat/completions".into(),
+        LlmProvider::Groq => "https://api.groq.com/openai/v1/chat/completions".into(),
+        LlmProvider::Perplexity => "https://api.perplexity.ai/chat/completions".into(),
+        LlmProvider::Anthropic => "https://api.anthropic.com/v1/messages".into(),
+        LlmProvider::Gemini if stream => format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{model}:streamGenerateContent?alt=sse"
+        ),
+        LlmProvider::Gemini => format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{model}:generateContent"
+        ),
+    }
+}
+
+fn build_body(
+    provider: LlmProvider,
+    model: &str,
+    req: &LlmRequest,
+    settings: &LlmSettings,
+    stream: bool,
+) -> Value {
+    let temperature = req.temperature.or(settings.default_temperature);
+    let max_tokens = req.max_tokens.or(settings.default_max_tokens);
+    let system: Vec<&str> = req
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.as_str())
+        .collect();
+    let turns = req.messages.iter().filter(|m| m.role != "system");
+
+    let mut body = match provider {
+        LlmProvider::Anthropic => {
+            let mut b = serde_json::json!({
+                "model": model,
+                "max_tokens": max_tokens.unwrap_or(ANTHROPIC_FALLBACK_MAX_TOKENS),
+                "messages": turns
+                    .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
+                    .collect::<Vec<_>>(),
+                "stream": stream,
+            });
+            if !system.is_empty() {
+                b["system"] = Value::String(system.join("\n\n"));
+            }
+            b
+        }
+        LlmProvider::Gemini => {
+            let mut b = serde_json::json!({
+                "contents": turns
+                    .map(|m| {
+                        let role = if m.role == "assistant" { "model" } else { "user" };
+                        serde_json::json!({ "role": role, "parts": [{ "text": m.content }] })
+                    })
+                    .collect::<Vec<_>>(),
+                "generationConfig": {},
+            });
+            if !system.is_empty() {
+                b["systemInstruction"] =
+                    serde_json::json!({ "parts": [{ "text": system.join("\n\n") }] });
+            }
+            if let Some(t) = temperature {
+                b["generationConfig"]["temperature"] = serde_json::json!(t);
+            }
+            if let Some(m) = max_tokens {
+                b["generationConfig"]["maxOutputTokens"] = serde_json::json!(m);
+            }
+            return b;
+        }
+        _ => serde_json::json!({
+            "model": model,
+            "messages": req.messages,
+            "stream": stream,
+        }),
+    };
+    if let Some(t) = temperature {
+        body["temperature"] = serde_json::json!(t);
+    }
+    if provider != LlmProvider::Anthropic {
+        if let Some(m) = max_tokens {
+            body["max_tokens"] = serde_json::json!(m);
+        }
+    }
+    body
+}
+
+/// Text fragment plus metadata extracted from one response body or SSE event.
+#[derive(Debug, Default, PartialEq)]
+struct Chunk {
+    text: Option<String>,
+    finish_reason: Option<String>,
+    usage: Option<LlmUsage>,
+}
+
+fn parse_sse_line(provider: LlmProvider, line: &str) -> Option<Chunk> {
+    let data = line.strip_prefix("data:")?.trim();
+    if data.is_empty() || data == "[DONE]" {
+        return None;
+    }
+    let json: Value = serde_json::from_str(data).ok()?;
+    Some(parse_chunk(provider, &json))
+}
+
+/// Parse either a full (non-streaming) response or a single streamed event.
+fn parse_chunk(provider: LlmProvider, json: &Value) -> Chunk {
+    let u64_at = |v: &Value, ptr: &str| v.pointer(ptr).and_then(Value::as_u64);
+    match provider {
+        LlmProvider::Anthropic => {
+            let text = match json.get("type").and_then(Value::as_str) {
+                Some("content_block_delta") => json
+                    .pointer("/delta/text")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                // Full message body
+                _ => json.get("content").and_then(Value::as_array).map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(|p| p.get("text").and_then(Value::as_str))
+                        .collect::<String>()
+                }),
+            };
+            let finish_reason = json
+                .get("stop_reason")
+                .or_else(|| json.pointer("/delta/stop_reason"))
+                .and_then(Value::as_str)
+                .map(String::from);
+            let usage_src = json.pointer("/message/usage").or_else(|| json.get("usage"));
+            let usage = usage_src.map(|u| LlmUsage {
+                input_tokens: u64_at(u, "/input_tokens").unwrap_or(0),
+                output_tokens: u64_at(u, "/output_tokens").unwrap_or(0),
+            });
+            Chunk {
+                text,
+                finish_reason,
+                usage,
+            }
+        }
+        LlmProvider::Gemini => {
+            let candidate = json.pointer("/candidates/0");
+            let text = candidate
+                .and_then(|c| c.pointer("/content/parts"))
+                .and_then(Value::as_array)
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(|p| p.get("text").and_then(Value::as_str))
+                        .collect::<String>()
+                });
+            let finish_reason = candidate
+                .and_then(|c| c.get("finishReason"))
+                .and_then(Value::as_str)
+                .map(String::from);
+            let usage = json.get("usageMetadata").map(|u| LlmUsage {
+                input_tokens: u64_at(u, "/promptTokenCount").unwrap_or(0),
+                output_tokens: u64_at(u, "/candidatesTokenCount").unwrap_or(0),
+            });
+            Chunk {
+                text,
+                finish_reason,
+                usage,
+            }
+        }
+        _ => {
+            let choice = json.pointer("/choices/0");
+            let text = choice
+                .and_then(|c| {
+                    c.pointer("/delta/content")
+                        .or_else(|| c.pointer("/message/content"))
+                })
+                .and_then(Value::as_str)
+                .map(String::from);
+            let finish_reason = choice
+                .and_then(|c| c.get("finish_reason"))
+                .and_then(Value::as_str)
+                .map(String::from);
+            let usage = json
+                .get("usage")
+                .filter(|u| !u.is_null())
+                .map(|u| LlmUsage {
+                    input_tokens: u64_at(u, "/prompt_tokens").unwrap_or(0),
+                    output_tokens: u64_at(u, "/completion_tokens").unwrap_or(0),
+                });
+            Chunk {
+                text,
+                finish_reason,
+                usage,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_openai_sse() {
+        let c = parse_sse_line(
+            LlmProvider::Openai,
+            r#"data: {"choices":[{"delta":{"content":"Hel"},"finish_reason":null}]}"#,
+        )
+        .unwrap();
+        assert_eq!(c.text.as_deref(), Some("Hel"));
+        assert!(parse_sse_line(LlmProvider::Openai, "data: [DONE]").is_none());
+        assert!(parse_sse_line(LlmProvider::Openai, ": keep-alive").is_none());
+    }
+
+    #[test]
+    fn test_parse_anthropic_sse() {
+        let delta = parse_sse_line(
+            LlmProvider::Anthropic,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#,
+        )
+        .unwrap();
+        assert_eq!(delta.text.as_deref(), Some("lo"));
+
+        let end = parse_sse_line(
+            LlmProvider::Anthropic,
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":7}}"#,
+        )
+        .unwrap();
+        assert_eq!(end.finish_reason.as_deref(), Some("end_turn"));
+        assert_eq!(end.usage.unwrap().output_tokens, 7);
+    }
+
+    #[test]
+    fn test_parse_gemini_full_response() {
+        let json = serde_json::json!({
+            "candidates": [{ "content": { "parts": [{ "text": "a" }, { "text": "b" }] }, "finishReason": "STOP" }],
+            "usageMetadata": { "promptTokenCount": 3, "candidatesTokenCount": 2 }
+        });
+        let c = parse_chunk(LlmProvider::Gemini, &json);
+        assert_eq!(c.text.as_deref(), Some("ab"));
+        assert_eq!(c.finish_reason.as_deref(), Some("STOP"));
+        assert_eq!(
+            c.usage,
+            Some(LlmUsage {
+                input_tokens: 3,
+                output_tokens: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_body_splits_system_for_anthropic() {
+        let req = LlmRequest::from_args(&serde_json::json!({
+            "prompt": "hi",
+            "system": "be brief",
+        }))
+        .unwrap();
+        let body = build_body(
+            LlmProvider::Anthropic,
+            "claude",
+            &req,
+            &LlmSettings::default(),
+            true,
+        );
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["max_tokens"], ANTHROPIC_FALLBACK_MAX_TOKENS);
+    }
+
+    #[tokio::test]
+    async fn test_missing_api_key_is_dependency_missing() {
+        let llm = HttpLlm::new(LlmSettings::default());
+        let req = LlmRequest::from_args(&serde_json::json!({ "prompt": "hi" })).unwrap();
+        match llm.complete(&req).await {
+            Err(CapError::DependencyMissing(msg)) => assert!(msg.contains("APP__GEMINI_API_KEY")),
+            other => panic!(
+                "expected DependencyMissing, got {:?}",
+                other.map(|r| r.content)
+            ),
+        }
+    }
+}
@@ -0,0 +1,430 @@
+//! LLM access – provider-agnostic chat completions, blocking and streaming.
+//!
+//! Models are addressed as `provider/model` (e.g. `gemini/gemini-3-flash-preview`),
+//! matching `default_llm.default_model` in `global_config.yaml`. The real
+//! implementation lives in [`http::HttpLlm`]; tests inject their own [`LlmOps`].
+
+pub mod http;
+pub mod tokens;
+
+use crate::clock::Stopwatch;
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Fallback when neither the request nor `LlmSettings` names a model.
+pub const DEFAULT_MODEL: &str = "gemini/gemini-3-flash-preview";
+
+// ---------------------------------------------------------------------------
+// Providers and settings
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProvider {
+    Openai,
+    Anthropic,
+    Groq,
+    Perplexity,
+    Gemini,
+}
+
+impl LlmProvider {
+    pub const ALL: [LlmProvider; 5] = [
+        LlmProvider::Openai,
+        LlmProvider::Anthropic,
+        LlmProvider::Groq,
+        LlmProvider::Perplexity,
+        LlmProvider::Gemini,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmProvider::Openai => "openai",
+            LlmProvider::Anthropic => "anthropic",
+            LlmProvider::Groq => "groq",
+            LlmProvider::Perplexity => "perplexity",
+            LlmProvider::Gemini => "gemini",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+
+    /// Environment variable holding this provider's API key (same names the
+    /// Tauri config loader reads).
+    pub fn api_key_env(&self) -> &'static str {
+        match self {
+            LlmProvider::Openai => "APP__OPENAI_API_KEY",
+            LlmProvider::Anthropic => "APP__ANTHROPIC_API_KEY",
+            LlmProvider::Groq => "APP__GROQ_API_KEY",
+            LlmProvider::Perplexity => "APP__PERPLEXITY_API_KEY",
+            LlmProvider::Gemini => "APP__GEMINI_API_KEY",
+        }
+    }
+}
+
+/// Split a `provider/model` identifier into its parts.
+pub fn split_model(model: &str) -> Result<(LlmProvider, &str), String> {
+    let (prefix, name) = model
+        .split_once('/')
+        .ok_or_else(|| format!("model '{}' must be of the form provider/model", model))?;
+    let provider =
+        LlmProvider::parse(prefix).ok_or_else(|| format!("unknown LLM provider: {}", prefix))?;
+    Ok((provider, name))
+}
+
+/// API keys and defaults used by [`http::HttpLlm`].
+///
+/// The engine does not read `global_config.yaml`; the Tauri wrapper fills
+/// this from its config, while the CLI uses [`LlmSettings::from_env`].
+#[derive(Debug, Clone)]
+pub struct LlmSettings {
+    pub default_model: String,
+    pub default_temperature: Option<f32>,
+    pub default_max_tokens: Option<u32>,
+    pub api_keys: HashMap<LlmProvider, String>,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            default_model: DEFAULT_MODEL.to_string(),
+            default_temperature: None,
+            default_max_tokens: None,
+            api_keys: HashMap::new(),
+        }
+    }
+}
+
+impl LlmSettings {
+    /// Read API keys and defaults from `APP__*` environment variables.
+    pub fn from_env() -> Self {
+        let mut settings = Self::default();
+        for provider in LlmProvider::ALL {
+            if let Ok(key) = std::env::var(provider.api_key_env()) {
+                if !key.trim().is_empty() {
+                    settings.api_keys.insert(provider, key);
+                }
+            }
+        }
+        if let Ok(model) = std::env::var("APP__DEFAULT_LLM__DEFAULT_MODEL") {
+            settings.default_model = model;
+        }
+        settings.default_temperature = std::env::var("APP__DEFAULT_LLM__DEFAULT_TEMPERATURE")
+            .ok()
+            .and_then(|v| v.parse().ok());
+        settings.default_max_tokens = std::env::var("APP__DEFAULT_LLM__DEFAULT_MAX_TOKENS")
+            .ok()
+            .and_then(|v| v.parse().ok());
+        settings
+    }
+
+    pub fn api_key(&self, provider: LlmProvider) -> Option<&str> {
+        self.api_keys.get(&provider).map(String::as_str)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Request / response types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmMessage {
+    /// `system`, `user`, or `assistant`.
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmRequest {
+    /// `provider/model`; falls back to `LlmSettings::default_model`.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<LlmMessage>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+impl LlmRequest {
+    /// Parse command args. Accepts either `messages` or a `prompt`
+    /// shorthand (plus optional `system`).
+    pub fn from_args(args: &Value) -> Result<Self, String> {
+        let mut req: LlmRequest = serde_json::from_value(args.clone())
+            .map_err(|e| format!("invalid LLM request: {}", e))?;
+        if let Some(system) = args.get("system").and_then(|v| v.as_str()) {
+            req.messages.insert(
+                0,
+                LlmMessage {
+                    role: "system".into(),
+                    content: system.into(),
+                },
+            );
+        }
+        if let Some(prompt) = args.get("prompt").and_then(|v| v.as_str()) {
+            req.messages.push(LlmMessage {
+                role: "user".into(),
+                content: prompt.into(),
+            });
+        }
+        if !req.messages.iter().any(|m| m.role != "system") {
+            return Err("missing 'prompt' string or non-empty 'messages' array".into());
+        }
+        Ok(req)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmResponse {
+    pub model: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<LlmUsage>,
+}
+
+// ---------------------------------------------------------------------------
+// Capability trait
+// ---------------------------------------------------------------------------
+
+/// Callback receiving each streamed text fragment.
+pub type OnDelta<'a> = dyn FnMut(&str) + Send + 'a;
+
+#[async_trait::async_trait]
+pub trait LlmOps: Send + Sync {
+    /// Run a completion and return the full response.
+    async fn complete(&self, req: &LlmRequest) -> crate::traits::CapResult<LlmResponse>;
+
+    /// Run a completion, calling `on_delta` for every text fragment as it
+    /// arrives. The returned response carries the concatenated content.
+    async fn stream(
+        &self,
+        req: &LlmRequest,
+        on_delta: &mut OnDelta<'_>,
+    ) -> crate::traits::CapResult<LlmResponse>;
+
+    /// Defaults (model, max tokens) applied to requests, when known.
+    fn settings(&self) -> Option<&LlmSettings> {
+        None
+    }
+
+    /// Providers that have credentials available.
+    fn configured_providers(&self) -> Vec<LlmProvider> {
+        Vec::new()
+    }
+
+    /// Cheap authenticated request proving the provider is reachable and
+    /// accepts the configured key. Used by the `llm` probe.
+    async fn check_provider(&self, provider: LlmProvider) -> crate::traits::CapResult<()> {
+        Err(CapError::Unsupported(format!(
+            "connectivity check not implemented for {}",
+            provider.as_str()
+        )))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Entry points (CommandResult-producing, like probes)
+// ---------------------------------------------------------------------------
+
+/// Event topic for each streamed text fragment.
+pub const TOPIC_DELTA: &str = "llm:delta";
+/// Event topic published once the stream has finished (success or error).
+pub const TOPIC_DONE: &str = "llm:done";
+
+/// Blocking completion. Args: `{ "prompt": "...", "model"?, "system"?, ... }`
+pub async fn run_complete(args: Value, ctx: &AppContext) -> CommandResult {
+    let run_id = args
+        .get("run_id")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| ctx.new_run_id());
+    let start = ctx.stopwatch();
+    if ctx.offline {
+        return result_offline("llm", "complete", &run_id);
+    }
+
+    let req = match LlmRequest::from_args(&args) {
+        Ok(r) => r,
+        Err(e) => return result_err("llm", "complete", &run_id, 0, ErrorCode::InvalidInput, e),
+    };
+
+    match ctx.llm().complete(&req).await {
+        Ok(resp) => llm_ok("complete", &run_id, start, &resp),
+        Err(e) => llm_err("complete", &run_id, start, e),
+    }
+}
+
+/// Streaming completion. Publishes `llm:delta` events (payload
+/// `{ "index": n, "text": "..." }`) on the context's event bus, then
+/// `llm:done`. Callers may pass `run_id` in args to correlate events
+/// before the final result arrives.
+pub async fn run_stream(args: Value, ctx: &AppContext) -> CommandResult {
+    let run_id = args
+        .get("run_id")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| ctx.new_run_id());
+    let start = ctx.stopwatch();
+
+    let req = match LlmRequest::from_args(&args) {
+        Ok(r) => r,
+        Err(e) => return result_err("llm", "stream", &run_id, 0, ErrorCode::InvalidInput, e),
+    };
+
+    let events = ctx.events();
+    if ctx.offline {
+        let result = result_offline("llm", "stream", &run_id);
+        events.emit(
+            &run_id,
+            TOPIC_DONE,
+            serde_json::json!({ "status": result.status, "deltas": 0 }),
+        );
+        return result;
+    }
+    let mut index = 0u64;
+    let mut on_delta = |text: &str| {
+        events.emit(
+            &run_id,
+            TOPIC_DELTA,
+            serde_json::json!({ "index": index, "text": text }),
+        );
+        index += 1;
+    };
+
+    let outcome = ctx.llm().stream(&req, &mut on_delta).await;
+    let result = match outcome {
+        Ok(resp) => llm_ok("stream", &run_id, start, &resp),
+        Err(e) => llm_err("stream", &run_id, start, e),
+    };
+    events.emit(
+        &run_id,
+        TOPIC_DONE,
+        serde_json::json!({ "status": result.status, "deltas": index }),
+    );
+    result
+}
+
+fn llm_ok(target: &str, run_id: &str, start: Stopwatch, resp: &LlmResponse) -> CommandResult {
+    let mut r = result_ok("llm", target, run_id, start.elapsed_ms());
+    r.data = Some(serde_json::to_value(resp).unwrap_or_default());
+    r
+}
+
+fn llm_err(target: &str, run_id: &str, start: Stopwatch, err: CapError) -> CommandResult {
+    result_err(
+        "llm",
+        target,
+        run_id,
+        start.elapsed_ms(),
+        err.error_code(),
+        err.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::CapResult;
+    use std::sync::{Arc, Mutex};
+
+    /// Streams the prompt back word by word.
+    struct EchoLlm;
+
+    #[async_trait::async_trait]
+    impl LlmOps for EchoLlm {
+        async fn complete(&self, req: &LlmRequest) -> CapResult<LlmResponse> {
+            Ok(LlmResponse {
+                model: "echo/test".into(),
+                content: req.messages.last().unwrap().content.clone(),
+                finish_reason: Some("stop".into()),
+                usage: None,
+            })
+        }
+
+        async fn stream(
+            &self,
+            req: &LlmRequest,
+            on_delta: &mut OnDelta<'_>,
+        ) -> CapResult<LlmResponse> {
+            let resp = self.complete(req).await?;
+            for word in resp.content.split_inclusive(' ') {
+                on_delta(word);
+            }
+            Ok(resp)
+        }
+    }
+
+    #[test]
+    fn test_split_model() {
+        let (p, name) = split_model("gemini/gemini-3-flash-preview").unwrap();
+        assert_eq!(p, LlmProvider::Gemini);
+        assert_eq!(name, "gemini-3-flash-preview");
+        assert!(split_model("gpt-4").is_err());
+        assert!(split_model("acme/model").is_err());
+    }
+
+    #[test]
+    fn test_request_from_args_prompt_shorthand() {
+        let req = LlmRequest::from_args(&serde_json::json!({
+            "prompt": "hi",
+            "system": "be brief",
+        }))
+        .unwrap();
+        assert_eq!(req.messages.len(), 2);
+        assert_eq!(req.messages[0].role, "system");
+        assert_eq!(req.messages[1].content, "hi");
+
+        assert!(LlmRequest::from_args(&serde_json::json!({})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_stream_publishes_deltas() {
+        let ctx = AppContext::default_headless().with_llm(Box::new(EchoLlm));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        ctx.events()
+            .subscribe(move |ev| sink.lock().unwrap().push(ev.clone()));
+
+        let r = run_stream(
+            serde_json::json!({ "prompt": "one two three", "run_id": "fixed" }),
+            &ctx,
+        )
+        .await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.run_id, "fixed");
+        assert_eq!(r.data.unwrap()["content"], "one two three");
+
+        let events = seen.lock().unwrap();
+        let deltas: Vec<&str> = events
+            .iter()
+            .filter(|e| e.topic == TOPIC_DELTA)
+            .map(|e| e.payload["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(deltas, vec!["one ", "two ", "three"]);
+        assert_eq!(events.last().unwrap().topic, TOPIC_DONE);
+        assert!(events.iter().all(|e| e.run_id == "fixed"));
+    }
+
+    #[tokio::test]
+    async fn test_run_complete_invalid_input() {
+        let ctx = AppContext::default_headless().with_llm(Box::new(EchoLlm));
+        let r = run_complete(serde_json::json!({ "messages": [] }), &ctx).await;
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+}
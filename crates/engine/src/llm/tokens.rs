@@ -0,0 +1,237 @@
+//! Token counting and cost estimation.
+//!
+//! OpenAI models are counted exactly with their tiktoken encoding. Other
+//! providers do not publish a local tokenizer, so their prompts are counted
+//! with `o200k_base` and reported as approximate. Prices are list prices in
+//! USD per million tokens and only cover the models in [`MODEL_PRICES`].
+
+use super::{split_model, LlmProvider, LlmRequest, LlmSettings, DEFAULT_MODEL};
+use serde::Serialize;
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+/// Chat framing overhead per message and for priming the reply
+/// (OpenAI cookbook values; close enough for other providers).
+const TOKENS_PER_MESSAGE: u64 = 3;
+const TOKENS_REPLY_PRIMING: u64 = 3;
+
+/// Pricing and context window for a family of models.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ModelPrice {
+    pub provider: LlmProvider,
+    /// Matched against the model name by longest prefix.
+    pub prefix: &'static str,
+    pub input_usd_per_mtok: f64,
+    pub output_usd_per_mtok: f64,
+    pub context_window: u64,
+}
+
+const fn price(
+    provider: LlmProvider,
+    prefix: &'static str,
+    input_usd_per_mtok: f64,
+    output_usd_per_mtok: f64,
+    context_window: u64,
+) -> ModelPrice {
+    ModelPrice {
+        provider,
+        prefix,
+        input_usd_per_mtok,
+        output_usd_per_mtok,
+        context_window,
+    }
+}
+
+pub const MODEL_PRICES: &[ModelPrice] = &[
+    price(LlmProvider::Openai, "gpt-5", 1.25, 10.0, 400_000),
+    price(LlmProvider::Openai, "gpt-5-mini", 0.25, 2.0, 400_000),
+    price(LlmProvider::Openai, "gpt-4.1", 2.0, 8.0, 1_047_576),
+    price(LlmProvider::Openai, "gpt-4.1-mini", 0.4, 1.6, 1_047_576),
+    price(LlmProvider::Openai, "gpt-4o", 2.5, 10.0, 128_000),
+    price(LlmProvider::Openai, "gpt-4o-mini", 0.15, 0.6, 128_000),
+    price(LlmProvider::Anthropic, "claude-opus-4", 15.0, 75.0, 200_000),
+    price(
+        LlmProvider::Anthropic,
+        "claude-opus-4-5",
+        5.0,
+        25.0,
+        200_000,
+    ),
+    price(
+        LlmProvider::Anthropic,
+        "claude-sonnet-4",
+        3.0,
+        15.0,
+        200_000,
+    ),
+    price(
+        LlmProvider::Anthropic,
+        "claude-haiku-4-5",
+        1.0,
+        5.0,
+        200_000,
+    ),
+    price(LlmProvider::Gemini, "gemini-2.5-pro", 1.25, 10.0, 1_048_576),
+    price(LlmProvider::Gemini, "gemini-2.5-flash", 0.3, 2.5, 1_048_576),
+    price(LlmProvider::Gemini, "gemini-3-flash", 0.5, 3.0, 1_048_576),
+    price(LlmProvider::Groq, "llama-3.3-70b", 0.59, 0.79, 131_072),
+    price(LlmProvider::Perplexity, "sonar", 1.0, 1.0, 128_000),
+];
+
+/// Look up pricing for a model name (without the provider prefix).
+pub fn model_price(provider: LlmProvider, name: &str) -> Option<&'static ModelPrice> {
+    MODEL_PRICES
+        .iter()
+        .filter(|p| p.provider == provider && name.starts_with(p.prefix))
+        .max_by_key(|p| p.prefix.len())
+}
+
+/// Result of counting a piece of text or a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenCount {
+    /// Encoding used, e.g. `o200k_base`.
+    pub tokenizer: &'static str,
+    /// `false` when the provider's own tokenizer is not available locally.
+    pub exact: bool,
+    pub tokens: u64,
+}
+
+fn encoding(provider: LlmProvider, name: &str) -> (&'static str, &'static CoreBPE, bool) {
+    let known = match provider {
+        LlmProvider::Openai => get_tokenizer(name),
+        _ => None,
+    };
+    match known {
+        Some(Tokenizer::Cl100kBase) => ("cl100k_base", tiktoken_rs::cl100k_base_singleton(), true),
+        Some(Tokenizer::O200kBase) => ("o200k_base", tiktoken_rs::o200k_base_singleton(), true),
+        Some(Tokenizer::O200kHarmony) => (
+            "o200k_harmony",
+            tiktoken_rs::o200k_harmony_singleton(),
+            true,
+        ),
+        _ => ("o200k_base", tiktoken_rs::o200k_base_singleton(), false),
+    }
+}
+
+/// Count the tokens in `text` for `provider/model`.
+pub fn count_text(model: &str, text: &str) -> Result<TokenCount, String> {
+    let (provider, name) = split_model(model)?;
+    let (tokenizer, bpe, exact) = encoding(provider, name);
+    Ok(TokenCount {
+        tokenizer,
+        exact,
+        tokens: bpe.encode_with_special_tokens(text).len() as u64,
+    })
+}
+
+/// Count the prompt tokens a chat request will consume, including framing.
+pub fn count_request(model: &str, req: &LlmRequest) -> Result<TokenCount, String> {
+    let (provider, name) = split_model(model)?;
+    let (tokenizer, bpe, exact) = encoding(provider, name);
+    let content: u64 = req
+        .messages
+        .iter()
+        .map(|m| {
+            TOKENS_PER_MESSAGE
+                + bpe.encode_with_special_tokens(&m.role).len() as u64
+                + bpe.encode_with_special_tokens(&m.content).len() as u64
+        })
+        .sum();
+    Ok(TokenCount {
+        tokenizer,
+        exact,
+        tokens: content + TOKENS_REPLY_PRIMING,
+    })
+}
+
+/// Token counts and cost bounds for a request, before it is sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct Estimate {
+    pub model: String,
+    #[serde(flatten)]
+    pub input: TokenCount,
+    /// Output budget: the request's `max_tokens`, else the settings default.
+    pub max_output_tokens: Option<u64>,
+    pub context_window: Option<u64>,
+    /// `false` when input plus output budget cannot fit the context window.
+    pub fits_context: Option<bool>,
+    pub input_cost_usd: Option<f64>,
+    /// Input cost plus the cost of generating the full output budget.
+    pub max_cost_usd: Option<f64>,
+}
+
+/// Estimate tokens and cost for `req`, resolving defaults from `settings`.
+pub fn estimate(req: &LlmRequest, settings: Option<&LlmSettings>) -> Result<Estimate, String> {
+    let model = req
+        .model
+        .clone()
+        .or_else(|| settings.map(|s| s.default_model.clone()))
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let input = count_request(&model, req)?;
+    let (provider, name) = split_model(&model)?;
+    let price = model_price(provider, name);
+
+    let max_output_tokens = req
+        .max_tokens
+        .or_else(|| settings.and_then(|s| s.default_max_tokens))
+        .map(u64::from);
+    let context_window = price.map(|p| p.context_window);
+    let fits_context = context_window.map(|w| input.tokens + max_output_tokens.unwrap_or(0) <= w);
+    let input_cost_usd = price.map(|p| input.tokens as f64 * p.input_usd_per_mtok / 1e6);
+    let max_cost_usd = match (price, input_cost_usd, max_output_tokens) {
+        (Some(p), Some(cost), Some(out)) => Some(cost + out as f64 * p.output_usd_per_mtok / 1e6),
+        _ => None,
+    };
+
+    Ok(Estimate {
+        model,
+        input,
+        max_output_tokens,
+        context_window,
+        fits_context,
+        input_cost_usd,
+        max_cost_usd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_text_openai_is_exact() {
+        let c = count_text("openai/gpt-4o", "hello world").unwrap();
+        assert_eq!(c.tokenizer, "o200k_base");
+        assert!(c.exact);
+        assert_eq!(c.tokens, 2);
+
+        let approx = count_text("anthropic/claude-sonnet-4-5", "hello world").unwrap();
+        assert!(!approx.exact);
+        assert_eq!(approx.tokens, 2);
+    }
+
+    #[test]
+    fn test_model_price_longest_prefix() {
+        let p = model_price(LlmProvider::Openai, "gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(p.prefix, "gpt-4o-mini");
+        assert!(model_price(LlmProvider::Openai, "davinci").is_none());
+    }
+
+    #[test]
+    fn test_estimate_uses_settings_defaults() {
+        let req = LlmRequest::from_args(&serde_json::json!({ "prompt": "hello world" })).unwrap();
+        let settings = LlmSettings {
+            default_model: "openai/gpt-4o".into(),
+            default_max_tokens: Some(1000),
+            ..Default::default()
+        };
+        let e = estimate(&req, Some(&settings)).unwrap();
+        assert_eq!(e.model, "openai/gpt-4o");
+        // 3 framing + "user" + "hello world" + 3 reply priming
+        assert_eq!(e.input.tokens, 9);
+        assert_eq!(e.max_output_tokens, Some(1000));
+        assert_eq!(e.fits_context, Some(true));
+        let max = e.max_cost_usd.unwrap();
+        assert!((max - (9.0 * 2.5 + 1000.0 * 10.0) / 1e6).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,229 @@
+//! [`SystemClipboard`] through the platform tools, and
+//! [`HeadlessClipboard`] for machines without one.
+
+use crate::traits::*;
+
+/// System clipboard using platform CLI tools.
+///
+/// - macOS: pbcopy / pbpaste
+/// - Linux: xclip / xsel / wl-copy+wl-paste
+pub struct SystemClipboard;
+
+impl ClipboardOps for SystemClipboard {
+    fn read_text(&self) -> CapResult<String> {
+        #[cfg(target_os = "macos")]
+        {
+            run_clipboard_cmd("pbpaste", &[])
+        }
+        #[cfg(target_os = "linux")]
+        {
+            linux_clipboard_read(ClipboardSelection::Clipboard)
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            Err(CapError::Unsupported(
+                "clipboard not implemented for this OS".into(),
+            ))
+        }
+    }
+
+    fn write_text(&self, text: &str) -> CapResult<()> {
+        #[cfg(target_os = "macos")]
+        {
+            run_clipboard_write("pbcopy", &[], text)
+        }
+        #[cfg(target_os = "linux")]
+        {
+            linux_clipboard_write(ClipboardSelection::Clipboard, text)
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            let _ = text;
+            Err(CapError::Unsupported(
+                "clipboard not implemented for this OS".into(),
+            ))
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_selection(&self, selection: ClipboardSelection) -> CapResult<String> {
+        linux_clipboard_read(selection)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn write_selection(&self, selection: ClipboardSelection, text: &str) -> CapResult<()> {
+        linux_clipboard_write(selection, text)
+    }
+
+    fn available_formats(&self) -> CapResult<Vec<String>> {
+        #[cfg(target_os = "macos")]
+        {
+            // "«class utf8», 12, «class ut16», 26, string, 12"
+            let info = run_clipboard_cmd("osascript", &["-e", "clipboard info"])?;
+            Ok(info
+                .trim()
+                .split(", ")
+                .step_by(2)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect())
+        }
+        #[cfg(target_os = "linux")]
+        {
+            let listed = run_clipboard_cmd("wl-paste", &["--list-types"]).or_else(|_| {
+                run_clipboard_cmd("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"])
+            });
+            match listed {
+                Ok(out) => Ok(out.lines().map(|l| l.trim().to_string()).collect()),
+                Err(CapError::DependencyMissing(_)) => Err(CapError::DependencyMissing(
+                    "neither wl-paste nor xclip found".into(),
+                )),
+                Err(e) => Err(e),
+            }
+        }
+        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+        {
+            Err(CapError::Unsupported(
+                "clipboard not implemented for this OS".into(),
+            ))
+        }
+    }
+
+    fn change_count(&self) -> CapResult<u64> {
+        #[cfg(target_os = "macos")]
+        {
+            let out = run_clipboard_cmd(
+                "osascript",
+                &[
+                    "-l",
+                    "JavaScript",
+                    "-e",
+                    "ObjC.import('AppKit'); $.NSPasteboard.generalPasteboard.changeCount",
+                ],
+            )?;
+            out.trim()
+                .parse()
+                .map_err(|_| CapError::Other(format!("unexpected changeCount: {}", out.trim())))
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            Err(CapError::Unsupported(
+                "no clipboard change count on this OS".into(),
+            ))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_clipboard_read(selection: ClipboardSelection) -> CapResult<String> {
+    let (xclip, xsel, wayland) = linux_selection_flags(selection);
+    // Try xclip first, then xsel, then wl-paste
+    if let Ok(out) = run_clipboard_cmd("xclip", &["-selection", xclip, "-o"]) {
+        return Ok(out);
+    }
+    if let Ok(out) = run_clipboard_cmd("xsel", &[xsel, "--output"]) {
+        return Ok(out);
+    }
+    if let Ok(out) = run_clipboard_cmd("wl-paste", wayland) {
+        return Ok(out);
+    }
+    Err(CapError::DependencyMissing(
+        "none of xclip, xsel, or wl-paste found".into(),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn linux_clipboard_write(selection: ClipboardSelection, text: &str) -> CapResult<()> {
+    let (xclip, xsel, wayland) = linux_selection_flags(selection);
+    if run_clipboard_write("xclip", &["-selection", xclip], text).is_ok() {
+        return Ok(());
+    }
+    if run_clipboard_write("xsel", &[xsel, "--input"], text).is_ok() {
+        return Ok(());
+    }
+    if run_clipboard_write("wl-copy", wayland, text).is_ok() {
+        return Ok(());
+    }
+    Err(CapError::DependencyMissing(
+        "none of xclip, xsel, or wl-copy found".into(),
+    ))
+}
+
+/// The selection's name for xclip, flag for xsel, and args for wl-paste /
+/// wl-copy.
+#[cfg(target_os = "linux")]
+fn linux_selection_flags(
+    selection: ClipboardSelection,
+) -> (&'static str, &'static str, &'static [&'static str]) {
+    match selection {
+        ClipboardSelection::Clipboard => ("clipboard", "--clipboard", &[]),
+        ClipboardSelection::Primary => ("primary", "--primary", &["--primary"]),
+    }
+}
+
+#[allow(dead_code)]
+fn run_clipboard_cmd(cmd: &str, args: &[&str]) -> CapResult<String> {
+    let output = std::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                CapError::DependencyMissing(format!("{} not found", cmd))
+            } else {
+                CapError::Io(e)
+            }
+        })?;
+
+    if !output.status.success() {
+        return Err(CapError::Other(format!(
+            "{} exited with {}",
+            cmd, output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[allow(dead_code)]
+fn run_clipboard_write(cmd: &str, args: &[&str], text: &str) -> CapResult<()> {
+    use std::io::Write;
+    let mut child = std::process::Command::new(cmd)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                CapError::DependencyMissing(format!("{} not found", cmd))
+            } else {
+                CapError::Io(e)
+            }
+        })?;
+
+    if let Some(ref mut stdin) = child.stdin {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(CapError::Other(format!("{} exited with {}", cmd, status)));
+    }
+    Ok(())
+}
+
+// ===========================================================================
+// Headless clipboard – returns SKIP / UNSUPPORTED cleanly
+// ===========================================================================
+
+/// Clipboard stub for headless environments. Never panics.
+pub struct HeadlessClipboard;
+
+impl ClipboardOps for HeadlessClipboard {
+    fn read_text(&self) -> CapResult<String> {
+        Err(CapError::Unsupported(
+            "clipboard unavailable in headless environment".into(),
+        ))
+    }
+    fn write_text(&self, _text: &str) -> CapResult<()> {
+        Err(CapError::Unsupported(
+            "clipboard unavailable in headless environment".into(),
+        ))
+    }
+}
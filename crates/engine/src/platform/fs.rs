@@ -0,0 +1,361 @@
+//! [`StdFilesystem`]: the filesystem through `std::fs`.
+
+use crate::traits::*;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub struct StdFilesystem;
+
+impl FilesystemOps for StdFilesystem {
+    fn read_file(&self, path: &Path) -> CapResult<Vec<u8>> {
+        std::fs::read(path).map_err(|e| read_error(path, e))
+    }
+
+    fn read_range(&self, path: &Path, offset: u64, length: u64) -> CapResult<Vec<u8>> {
+        use std::io::{Read, Seek};
+        let mut file = std::fs::File::open(path).map_err(|e| read_error(path, e))?;
+        file.seek(std::io::SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        file.take(length).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn write_file(&self, path: &Path, data: &[u8]) -> CapResult<()> {
+        create_parent(path)?;
+        std::fs::write(path, data).map_err(|e| write_error(path, e))
+    }
+
+    fn write_file_synced(&self, path: &Path, data: &[u8]) -> CapResult<()> {
+        use std::io::Write;
+        create_parent(path)?;
+        let mut file = std::fs::File::create(path).map_err(|e| write_error(path, e))?;
+        file.write_all(data)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| write_error(path, e))
+    }
+
+    fn write_file_atomic(&self, path: &Path, data: &[u8]) -> CapResult<()> {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static TEMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+        create_parent(path)?;
+        let name = path
+            .file_name()
+            .ok_or_else(|| CapError::Other(format!("cannot write {}", path.display())))?;
+        let tmp = path.with_file_name(format!(
+            ".{}.{}-{}.tmp",
+            name.to_string_lossy(),
+            std::process::id(),
+            TEMP_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written = self
+            .write_file_synced(&tmp, data)
+            .and_then(|_| std::fs::rename(&tmp, path).map_err(|e| write_error(path, e)));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+            return written;
+        }
+        // Persist the rename itself; directories cannot be opened for sync on Windows.
+        #[cfg(unix)]
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::File::open(parent)
+                .and_then(|dir| dir.sync_all())
+                .map_err(|e| write_error(path, e))?;
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> CapResult<()> {
+        std::fs::remove_file(path).map_err(CapError::Io)
+    }
+
+    fn trash(&self, path: &Path) -> CapResult<()> {
+        crate::trash::move_to_trash(&std::path::absolute(path)?)
+    }
+
+    fn lock(&self, path: &Path, mode: LockMode, timeout: Duration) -> CapResult<FileLock> {
+        create_parent(path)?;
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| write_error(path, e))?;
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let attempt = match mode {
+                LockMode::Shared => file.try_lock_shared(),
+                LockMode::Exclusive => file.try_lock(),
+            };
+            match attempt {
+                Ok(()) => return Ok(FileLock::new(path.to_path_buf(), mode, Some(file))),
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    let now = std::time::Instant::now();
+                    if now >= deadline {
+                        return Err(CapError::Timeout);
+                    }
+                    std::thread::sleep((deadline - now).min(Duration::from_millis(25)));
+                }
+                Err(std::fs::TryLockError::Error(e))
+                    if e.kind() == std::io::ErrorKind::Unsupported
+                        || (ENOLCK.is_some() && e.raw_os_error() == ENOLCK) =>
+                {
+                    return Err(CapError::Unsupported(format!(
+                        "cannot lock {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+                Err(std::fs::TryLockError::Error(e)) => return Err(CapError::Io(e)),
+            }
+        }
+    }
+
+    fn unlock(&self, lock: FileLock) -> CapResult<()> {
+        match lock.handle() {
+            Some(file) => file.unlock().map_err(CapError::Io),
+            None => Ok(()),
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> CapResult<()> {
+        std::fs::create_dir_all(path).map_err(CapError::Io)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> CapResult<()> {
+        std::fs::remove_dir_all(path).map_err(CapError::Io)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn temp_dir(&self) -> PathBuf {
+        std::env::temp_dir()
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> CapResult<()> {
+        #[cfg(unix)]
+        let made = std::os::unix::fs::symlink(target, link);
+        #[cfg(windows)]
+        let made = {
+            // Relative targets resolve against the link's directory.
+            let resolved = link
+                .parent()
+                .map_or(target.to_path_buf(), |p| p.join(target));
+            if resolved.is_dir() {
+                std::os::windows::fs::symlink_dir(target, link)
+            } else {
+                std::os::windows::fs::symlink_file(target, link)
+            }
+        };
+        #[cfg(not(any(unix, windows)))]
+        let made: std::io::Result<()> = Err(std::io::ErrorKind::Unsupported.into());
+        made.map_err(|e| link_error(link, e))
+    }
+
+    fn hard_link(&self, original: &Path, link: &Path) -> CapResult<()> {
+        std::fs::hard_link(original, link).map_err(|e| link_error(link, e))
+    }
+
+    fn read_link(&self, path: &Path) -> CapResult<PathBuf> {
+        std::fs::read_link(path).map_err(|e| read_error(path, e))
+    }
+
+    fn stat(&self, path: &Path) -> CapResult<FileMetadata> {
+        let meta = std::fs::metadata(path).map_err(|e| read_error(path, e))?;
+        let is_symlink = std::fs::symlink_metadata(path)?.file_type().is_symlink();
+        let mut stat = FileMetadata {
+            is_dir: meta.is_dir(),
+            is_symlink,
+            size_bytes: meta.len(),
+            readonly: meta.permissions().readonly(),
+            modified: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            ..Default::default()
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            stat.mode = Some(meta.mode() & 0o7777);
+            stat.uid = Some(meta.uid());
+            stat.gid = Some(meta.gid());
+            stat.xattrs = read_xattrs(path)?;
+        }
+        stat.quarantine = stat
+            .xattrs
+            .get(Quarantine::XATTR)
+            .and_then(|v| Quarantine::parse(v));
+        Ok(stat)
+    }
+
+    fn list_dir(&self, path: &Path) -> CapResult<Vec<DirEntry>> {
+        let read_dir = std::fs::read_dir(path)?;
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let entry = entry?;
+            // Skip entries whose metadata is transiently unavailable
+            let Ok(meta) = entry.metadata() else { continue };
+            entries.push(DirEntry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                is_dir: meta.is_dir(),
+                size_bytes: meta.len(),
+            });
+        }
+        Ok(entries)
+    }
+
+    fn walk(
+        &self,
+        root: &Path,
+        max_depth: Option<usize>,
+        visit: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> CapResult<()> {
+        std_walk(root, 1, max_depth, visit)
+            .map(|_| ())
+            .map_err(|e| read_error(root, e))
+    }
+}
+
+fn std_walk(
+    dir: &Path,
+    depth: usize,
+    max_depth: Option<usize>,
+    visit: &mut dyn FnMut(&WalkEntry) -> bool,
+) -> std::io::Result<bool> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(Result::ok).collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let walked = WalkEntry {
+            path: entry.path(),
+            depth,
+            is_dir: file_type.is_dir(),
+            is_symlink: file_type.is_symlink(),
+            size_bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+        };
+        if !visit(&walked) {
+            return Ok(false);
+        }
+        if walked.is_dir && max_depth.is_none_or(|max| depth < max) {
+            match std_walk(&walked.path, depth + 1, max_depth, visit) {
+                Ok(true) => {}
+                Ok(false) => return Ok(false),
+                Err(e) => tracing::debug!("skipping {}: {}", walked.path.display(), e),
+            }
+        }
+    }
+    Ok(true)
+}
+
+fn create_parent(path: &Path) -> CapResult<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    Ok(())
+}
+
+/// `ENOLCK`: no lock service behind the mount, as on NFS without lockd.
+#[cfg(target_os = "linux")]
+const ENOLCK: Option<i32> = Some(37);
+#[cfg(target_os = "macos")]
+const ENOLCK: Option<i32> = Some(77);
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const ENOLCK: Option<i32> = None;
+
+fn read_error(path: &Path, e: std::io::Error) -> CapError {
+    match e.kind() {
+        std::io::ErrorKind::PermissionDenied => {
+            CapError::PermissionDenied(format!("cannot read {}: {}", path.display(), e))
+        }
+        _ => CapError::Io(e),
+    }
+}
+
+/// Extended attributes of `path`; none where the filesystem has no xattrs.
+#[cfg(unix)]
+fn read_xattrs(path: &Path) -> CapResult<std::collections::BTreeMap<String, String>> {
+    let mut xattrs = std::collections::BTreeMap::new();
+    if !xattr::SUPPORTED_PLATFORM {
+        return Ok(xattrs);
+    }
+    let names = match xattr::list(path) {
+        Ok(names) => names,
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => return Ok(xattrs),
+        Err(e) => return Err(read_error(path, e)),
+    };
+    for name in names {
+        let Some(value) = xattr::get(path, &name).map_err(|e| read_error(path, e))? else {
+            continue;
+        };
+        let value = match String::from_utf8(value) {
+            Ok(text) => text,
+            Err(e) => format!("hex:{}", crate::export::hex(e.as_bytes())),
+        };
+        xattrs.insert(name.to_string_lossy().into_owned(), value);
+    }
+    Ok(xattrs)
+}
+
+fn link_error(link: &Path, e: std::io::Error) -> CapError {
+    // ERROR_PRIVILEGE_NOT_HELD: symlinks need Developer Mode or elevation.
+    #[cfg(windows)]
+    if e.raw_os_error() == Some(1314) {
+        return CapError::PermissionDenied(format!(
+            "cannot link {}: symlinks need Developer Mode or administrator rights",
+            link.display()
+        ));
+    }
+    match e.kind() {
+        std::io::ErrorKind::Unsupported => {
+            CapError::Unsupported(format!("cannot link {}: {}", link.display(), e))
+        }
+        _ => write_error(link, e),
+    }
+}
+
+fn write_error(path: &Path, e: std::io::Error) -> CapError {
+    match e.kind() {
+        std::io::ErrorKind::PermissionDenied => {
+            CapError::PermissionDenied(format!("cannot write {}: {}", path.display(), e))
+        }
+        _ => CapError::Io(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_write_replaces_without_leaving_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/settings.json");
+        StdFilesystem
+            .write_file_atomic(&path, b"{\"v\":1}")
+            .unwrap();
+        StdFilesystem
+            .write_file_atomic(&path, b"{\"v\":2}")
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"v\":2}");
+        let names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["settings.json"]);
+
+        // A failed rename (target is a directory) keeps the original and cleans up.
+        let blocked = dir.path().join("blocked");
+        std::fs::create_dir_all(blocked.join("inner")).unwrap();
+        assert!(StdFilesystem.write_file_atomic(&blocked, b"x").is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,148 @@
+//! Stand-ins for desktop capabilities in headless runs and tests.
+
+use crate::traits::*;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// ===========================================================================
+// Headless windows – no window server
+// ===========================================================================
+
+/// Window stub for the CLI and headless environments. The GUI wrapper
+/// supplies a real implementation.
+pub struct HeadlessWindows;
+
+impl WindowOps for HeadlessWindows {
+    fn labels(&self) -> Vec<String> {
+        Vec::new()
+    }
+    fn geometry(&self, _label: &str) -> CapResult<WindowGeometry> {
+        Err(CapError::Unsupported("no windows in headless mode".into()))
+    }
+    fn set_geometry(&self, _label: &str, _geometry: &WindowGeometry) -> CapResult<()> {
+        Err(CapError::Unsupported("no windows in headless mode".into()))
+    }
+}
+
+// ===========================================================================
+// Headless shortcuts – validate only
+// ===========================================================================
+
+/// Shortcut stub for the CLI: nothing is registered, so `shortcuts_list`
+/// only validates the configured mapping.
+pub struct HeadlessShortcuts;
+
+impl ShortcutOps for HeadlessShortcuts {
+    fn register(&self, _accelerator: &str) -> CapResult<()> {
+        Err(CapError::Unsupported(
+            "global shortcuts unavailable in headless mode".into(),
+        ))
+    }
+    fn unregister_all(&self) -> CapResult<()> {
+        Ok(())
+    }
+    fn registered(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// ===========================================================================
+// Scripted dialogs – answers queued by scenarios
+// ===========================================================================
+
+/// Dialogs answered from a queue of [`DialogAnswer`]s (a scenario step's
+/// `dialogs:` list), so dialog-dependent flows run headlessly. A dialog with
+/// no queued answer is UNSUPPORTED.
+#[derive(Default)]
+pub struct ScriptedDialogs {
+    answers: Mutex<VecDeque<DialogAnswer>>,
+}
+
+impl ScriptedDialogs {
+    fn next(&self, kind: &str) -> CapResult<DialogAnswer> {
+        self.answers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop_front()
+            .ok_or_else(|| CapError::Unsupported(format!("no scripted answer for {} dialog", kind)))
+    }
+}
+
+fn unexpected_answer(answer: &DialogAnswer, kind: &str) -> CapError {
+    CapError::Other(format!(
+        "scripted answer is '{}' but a {} dialog was shown",
+        answer.kind(),
+        kind
+    ))
+}
+
+impl DialogOps for ScriptedDialogs {
+    fn open_file(&self, _options: &FileDialogOptions) -> CapResult<Option<PathBuf>> {
+        match self.next("open")? {
+            DialogAnswer::Open(path) => Ok(path),
+            other => Err(unexpected_answer(&other, "open")),
+        }
+    }
+    fn save_file(&self, _options: &FileDialogOptions) -> CapResult<Option<PathBuf>> {
+        match self.next("save")? {
+            DialogAnswer::Save(path) => Ok(path),
+            other => Err(unexpected_answer(&other, "save")),
+        }
+    }
+    fn confirm(&self, _title: &str, _message: &str) -> CapResult<bool> {
+        match self.next("confirm")? {
+            DialogAnswer::Confirm(yes) => Ok(yes),
+            other => Err(unexpected_answer(&other, "confirm")),
+        }
+    }
+    fn message(&self, _title: &str, _message: &str) -> CapResult<()> {
+        match self.next("message")? {
+            DialogAnswer::Message => Ok(()),
+            other => Err(unexpected_answer(&other, "message")),
+        }
+    }
+    fn script(&self, answers: Vec<DialogAnswer>) -> CapResult<()> {
+        *self.answers.lock().unwrap_or_else(|e| e.into_inner()) = answers.into();
+        Ok(())
+    }
+}
+
+// ===========================================================================
+// Recording opener – logs what would have been opened
+// ===========================================================================
+
+/// Records open/reveal intents without launching anything, so scenarios can
+/// assert on them (`opener_log`).
+#[derive(Default)]
+pub struct RecordingOpener {
+    intents: Mutex<Vec<OpenIntent>>,
+}
+
+impl RecordingOpener {
+    fn record(&self, intent: OpenIntent) {
+        self.intents
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(intent);
+    }
+}
+
+impl OpenerOps for RecordingOpener {
+    fn open_url(&self, url: &str) -> CapResult<()> {
+        self.record(OpenIntent::Url(url.to_string()));
+        Ok(())
+    }
+    fn reveal_path(&self, path: &Path) -> CapResult<()> {
+        self.record(OpenIntent::Reveal(path.to_path_buf()));
+        Ok(())
+    }
+    fn recorded(&self) -> Option<Vec<OpenIntent>> {
+        Some(
+            self.intents
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        )
+    }
+}
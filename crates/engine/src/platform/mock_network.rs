@@ -0,0 +1,124 @@
+//! [`MockNetwork`]: scripted HTTP replies for tests, without sockets.
+
+use crate::traits::*;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Queued replies for one URL prefix; `Err` is a transport failure.
+type MockRoute = (String, VecDeque<Result<HttpResponse, String>>);
+
+/// Network stand-in for tests. Replies are queued per URL prefix and handed
+/// out in order, the last one repeating; every request is recorded.
+#[derive(Default)]
+pub struct MockNetwork {
+    routes: Mutex<Vec<MockRoute>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `response` for requests whose URL starts with `url_prefix`.
+    pub fn respond(self, url_prefix: &str, response: HttpResponse) -> Self {
+        self.push(url_prefix, Ok(response))
+    }
+
+    /// Queue a transport failure ([`CapError::Network`]) for `url_prefix`.
+    pub fn fail(self, url_prefix: &str, message: &str) -> Self {
+        self.push(url_prefix, Err(message.to_string()))
+    }
+
+    fn push(self, url_prefix: &str, reply: Result<HttpResponse, String>) -> Self {
+        {
+            let mut routes = self.routes.lock().unwrap();
+            match routes.iter_mut().find(|(prefix, _)| prefix == url_prefix) {
+                Some((_, queue)) => queue.push_back(reply),
+                None => routes.push((url_prefix.to_string(), VecDeque::from([reply]))),
+            }
+        }
+        self
+    }
+
+    /// Requests sent so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl NetworkOps for MockNetwork {
+    async fn dns_resolve(&self, _host: &str) -> CapResult<Vec<String>> {
+        Ok(vec!["127.0.0.1".into()])
+    }
+
+    async fn https_get(&self, url: &str, timeout_ms: u64) -> CapResult<(u16, String)> {
+        let resp = self
+            .send(HttpRequest::get(url).timeout_ms(timeout_ms))
+            .await?;
+        Ok((resp.status, resp.text()))
+    }
+
+    async fn send(&self, request: HttpRequest) -> CapResult<HttpResponse> {
+        let url = request.url.clone();
+        let max_body = request.max_body_bytes;
+        self.requests.lock().unwrap().push(request);
+        let mut routes = self.routes.lock().unwrap();
+        let queue = routes
+            .iter_mut()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, queue)| queue)
+            .ok_or_else(|| CapError::Network(format!("no mock route for {}", url)))?;
+        let reply = if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        };
+        let mut resp = reply
+            .expect("routes always hold at least one reply")
+            .map_err(CapError::Network)?;
+        if let Some(max) = max_body {
+            resp.body.truncate(max);
+        }
+        Ok(resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(code: u16) -> HttpResponse {
+        HttpResponse {
+            status: code,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_network_replays_in_order_and_records() {
+        let network = MockNetwork::new()
+            .respond("https://api.test/", status(429))
+            .respond("https://api.test/", status(200))
+            .respond("https://api.test/v1/images", status(201))
+            .fail("https://down.test/", "connection refused");
+
+        let send = |url: &str| network.send(HttpRequest::get(url));
+        assert_eq!(send("https://api.test/a").await.unwrap().status, 429);
+        assert_eq!(send("https://api.test/a").await.unwrap().status, 200);
+        // Last reply repeats; longest prefix wins.
+        assert_eq!(send("https://api.test/a").await.unwrap().status, 200);
+        assert_eq!(
+            send("https://api.test/v1/images").await.unwrap().status,
+            201
+        );
+        assert!(matches!(
+            send("https://down.test/").await,
+            Err(CapError::Network(_))
+        ));
+        assert!(send("https://other.test/").await.is_err());
+        assert_eq!(network.requests().len(), 6);
+    }
+}
@@ -0,0 +1,43 @@
+//! Platform-specific implementations of OS capability traits.
+//!
+//! - [`StdFilesystem`]: real std::fs operations
+//! - [`ReqwestNetwork`]: real HTTP via reqwest
+//! - [`MockNetwork`]: scripted responses for tests, no sockets
+//! - [`SystemClipboard`]: platform clipboard (pbcopy/xclip)
+//! - [`HeadlessClipboard`]: always returns UNSUPPORTED/SKIP
+//! - [`HeadlessWindows`]: no windows; geometry calls return UNSUPPORTED
+//! - [`HeadlessShortcuts`]: registers nothing; shortcuts are only validated
+//! - [`ScriptedDialogs`]: answers dialogs from scenario-provided responses
+//! - [`RecordingOpener`]: records URLs/paths to open instead of launching them
+
+mod clipboard;
+mod fs;
+mod headless;
+mod mock_network;
+mod network;
+
+pub use clipboard::{HeadlessClipboard, SystemClipboard};
+pub use fs::StdFilesystem;
+pub use headless::{HeadlessShortcuts, HeadlessWindows, RecordingOpener, ScriptedDialogs};
+pub use mock_network::MockNetwork;
+pub(crate) use network::loggable_url;
+pub use network::ReqwestNetwork;
+
+use crate::traits::*;
+
+/// The process environment, so commands, probes, and child processes all
+/// see the variables. Process-wide: concurrent runs share it.
+pub struct ProcessEnv;
+
+impl EnvOps for ProcessEnv {
+    fn get(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+
+    fn set(&self, name: &str, value: Option<&str>) {
+        match value {
+            Some(value) => std::env::set_var(name, value),
+            None => std::env::remove_var(name),
+        }
+    }
+}
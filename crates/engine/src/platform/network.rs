@@ -0,0 +1,231 @@
+//! [`ReqwestNetwork`]: real HTTP through reqwest.
+
+use crate::tls::TlsConfig;
+use crate::traits::*;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Real HTTP via reqwest. Servers are verified against the OS trust store
+/// plus any CA bundle in its [`TlsConfig`].
+pub struct ReqwestNetwork {
+    tls: TlsConfig,
+    /// Built on first use and kept so connections (and proxy settings) are
+    /// shared.
+    client: OnceLock<reqwest::Client>,
+}
+
+impl ReqwestNetwork {
+    /// TLS settings from the environment (see [`crate::tls`]).
+    pub fn new() -> Self {
+        Self::with_tls(TlsConfig::from_env())
+    }
+
+    pub fn with_tls(tls: TlsConfig) -> Self {
+        Self {
+            tls,
+            client: OnceLock::new(),
+        }
+    }
+
+    fn client_builder(&self) -> CapResult<reqwest::ClientBuilder> {
+        let loaded = crate::tls::load(&self.tls).map_err(CapError::Network)?;
+        Ok(crate::tls::apply(
+            reqwest::Client::builder(),
+            &self.tls,
+            loaded,
+        ))
+    }
+
+    fn client(&self) -> CapResult<&reqwest::Client> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+        let client = self
+            .client_builder()?
+            .build()
+            .map_err(|e| CapError::Network(format!("failed to build HTTP client: {}", e)))?;
+        Ok(self.client.get_or_init(|| client))
+    }
+}
+
+impl Default for ReqwestNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reqwest error with its causes; the top level alone ("error sending
+/// request") hides certificate and connect failures.
+fn error_chain(e: &reqwest::Error) -> String {
+    let mut out = e.to_string();
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        let text = cause.to_string();
+        if !out.ends_with(&text) {
+            out.push_str(": ");
+            out.push_str(&text);
+        }
+        source = cause.source();
+    }
+    out
+}
+
+/// URL without its query string, which may carry keys.
+pub(crate) fn loggable_url(url: &str) -> &str {
+    url.split('?').next().unwrap_or(url)
+}
+
+fn multipart_form(parts: Vec<FormPart>) -> CapResult<reqwest::multipart::Form> {
+    use reqwest::multipart::{Form, Part};
+    let mut form = Form::new();
+    for part in parts {
+        form = match part {
+            FormPart::Text { name, value } => form.text(name, value),
+            FormPart::File {
+                name,
+                file_name,
+                mime,
+                bytes,
+            } => {
+                let file = Part::bytes(bytes)
+                    .file_name(file_name)
+                    .mime_str(&mime)
+                    .map_err(|e| CapError::Other(format!("invalid mime type {}: {}", mime, e)))?;
+                form.part(name, file)
+            }
+        };
+    }
+    Ok(form)
+}
+
+#[async_trait::async_trait]
+impl NetworkOps for ReqwestNetwork {
+    async fn dns_resolve(&self, host: &str) -> CapResult<Vec<String>> {
+        use tokio::net::lookup_host;
+        let addrs: Vec<String> = lookup_host(format!("{}:443", host))
+            .await
+            .map_err(|e| CapError::Network(format!("DNS resolution failed for {}: {}", host, e)))?
+            .map(|a| a.ip().to_string())
+            .collect();
+        if addrs.is_empty() {
+            return Err(CapError::Network(format!(
+                "DNS resolution returned no addresses for {}",
+                host
+            )));
+        }
+        Ok(addrs)
+    }
+
+    async fn https_get(&self, url: &str, timeout_ms: u64) -> CapResult<(u16, String)> {
+        let client = self
+            .client_builder()?
+            .timeout(std::time::Duration::from_millis(timeout_ms))
+            .build()
+            .map_err(|e| CapError::Network(format!("failed to build HTTP client: {}", e)))?;
+
+        let resp = client.get(url).send().await.map_err(|e| {
+            if e.is_timeout() {
+                CapError::Timeout
+            } else {
+                CapError::Network(format!("HTTPS GET {}: {}", url, error_chain(&e)))
+            }
+        })?;
+
+        let status = resp.status().as_u16();
+        // Read at most 4 KiB for the snippet
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| CapError::Network(format!("reading body: {}", e)))?;
+        let snippet: String = body.chars().take(4096).collect();
+        Ok((status, snippet))
+    }
+
+    async fn send(&self, request: HttpRequest) -> CapResult<HttpResponse> {
+        let client = self.client()?;
+        let target = loggable_url(&request.url).to_string();
+        let mut builder = match request.method {
+            HttpMethod::Get => client.get(&request.url),
+            HttpMethod::Post => client.post(&request.url),
+            HttpMethod::Put => client.put(&request.url),
+        }
+        .timeout(Duration::from_millis(request.timeout_ms));
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        builder = match request.body {
+            HttpBody::Empty => builder,
+            HttpBody::Json(value) => builder.json(&value),
+            HttpBody::Multipart(parts) => builder.multipart(multipart_form(parts)?),
+            HttpBody::Bytes(bytes) => builder.body(bytes),
+        };
+
+        let mut resp = builder.send().await.map_err(|e| {
+            if e.is_timeout() {
+                CapError::Timeout
+            } else {
+                CapError::Network(format!(
+                    "{:?} {}: {}",
+                    request.method,
+                    target,
+                    error_chain(&e.without_url())
+                ))
+            }
+        })?;
+        let status = resp.status().as_u16();
+        let headers = resp
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        let body_err = |e: reqwest::Error| {
+            if e.is_timeout() {
+                CapError::Timeout
+            } else {
+                CapError::Network(format!("reading body: {}", e.without_url()))
+            }
+        };
+        let body = match request.max_body_bytes {
+            None => resp.bytes().await.map_err(body_err)?.to_vec(),
+            Some(max) => {
+                let mut body = Vec::new();
+                while body.len() < max {
+                    match resp.chunk().await.map_err(body_err)? {
+                        Some(chunk) => body.extend_from_slice(&chunk),
+                        None => break,
+                    }
+                }
+                body.truncate(max);
+                body
+            }
+        };
+        tracing::debug!("{:?} {} -> {}", request.method, target, status);
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    fn trust_store(&self) -> TrustStore {
+        crate::tls::trust_store(&self.tls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_helpers() {
+        let resp = HttpResponse::from_json(200, &serde_json::json!({"ok": true}));
+        assert!(resp.is_success());
+        assert_eq!(resp.header("Content-Type"), Some("application/json"));
+        let value: serde_json::Value = resp.json().unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(
+            loggable_url("https://x.test/p?key=secret"),
+            "https://x.test/p"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! Worker pools that command handlers run on, one per [`Priority`] class,
+//! so heavy background work (hashing large files from a scenario, a deep
+//! `find_files`) never occupies the threads quick interactive calls like
+//! `ping` or `consent_get` need.
+//!
+//! Each class has its own threads: [`INTERACTIVE_THREADS`] for
+//! interactive commands, and one per CPU but one (at most
+//! [`MAX_BACKGROUND_THREADS`]) for background ones. The pools start on
+//! first use. A command that calls another from a pool thread waits for
+//! it like any other caller; calls within the same pool run inline.
+
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Threads reserved for interactive commands.
+pub const INTERACTIVE_THREADS: usize = 2;
+/// Upper bound on background threads.
+pub const MAX_BACKGROUND_THREADS: usize = 8;
+
+/// Scheduling class of a command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Quick calls the UI waits on.
+    #[default]
+    Interactive,
+    /// File scans, hashing, and other work that may take a while.
+    Background,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Background => "background",
+        }
+    }
+}
+
+/// The per-class pools of a [`crate::commands::CommandRegistry`].
+#[derive(Default)]
+pub struct CommandPools {
+    interactive: OnceLock<Option<rayon::ThreadPool>>,
+    background: OnceLock<Option<rayon::ThreadPool>>,
+}
+
+impl CommandPools {
+    /// Run `job` on a thread of `priority`'s pool and wait for it. Runs on
+    /// the calling thread if the pool could not be started.
+    pub fn run<T: Send>(&self, priority: Priority, job: impl FnOnce() -> T + Send) -> T {
+        match self.pool(priority) {
+            Some(pool) => pool.install(job),
+            None => job(),
+        }
+    }
+
+    fn pool(&self, priority: Priority) -> Option<&rayon::ThreadPool> {
+        let (cell, threads) = match priority {
+            Priority::Interactive => (&self.interactive, INTERACTIVE_THREADS),
+            Priority::Background => (&self.background, background_threads()),
+        };
+        cell.get_or_init(|| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(move |i| format!("command-{}-{}", priority.as_str(), i))
+                .build()
+                .map_err(|e| {
+                    tracing::warn!("{} command pool: {}; running inline", priority.as_str(), e)
+                })
+                .ok()
+        })
+        .as_ref()
+    }
+}
+
+fn background_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get().saturating_sub(1))
+        .unwrap_or(1)
+        .clamp(1, MAX_BACKGROUND_THREADS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_busy_background_pool_does_not_delay_interactive_jobs() {
+        let pools = Arc::new(CommandPools::default());
+        let released = Arc::new(AtomicBool::new(false));
+        // Occupy every background thread until released.
+        let blockers: Vec<_> = (0..background_threads())
+            .map(|_| {
+                let (pools, released) = (pools.clone(), released.clone());
+                std::thread::spawn(move || {
+                    pools.run(Priority::Background, || {
+                        let deadline = Instant::now() + Duration::from_secs(5);
+                        while !released.load(Ordering::SeqCst) && Instant::now() < deadline {
+                            std::thread::sleep(Duration::from_millis(5));
+                        }
+                    })
+                })
+            })
+            .collect();
+        std::thread::sleep(Duration::from_millis(50));
+
+        let started = Instant::now();
+        let name = pools.run(Priority::Interactive, || {
+            std::thread::current().name().map(String::from)
+        });
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(name.unwrap().starts_with("command-interactive-"));
+
+        released.store(true, Ordering::SeqCst);
+        for blocker in blockers {
+            blocker.join().unwrap();
+        }
+    }
+}
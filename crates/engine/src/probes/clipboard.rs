@@ -0,0 +1,163 @@
+//! Clipboard probe.
+
+use super::{ProbeInfo, ProbeRegistry, ProbeScope};
+use crate::clock::Stopwatch;
+use crate::context::AppContext;
+use crate::traits::{CapError, ClipboardSelection};
+use crate::types::*;
+use std::collections::HashMap;
+use std::future::ready;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new(
+            "clipboard",
+            "Write and read back text, restoring the old contents",
+        )
+        .capabilities(&["clipboard"])
+        .arg(
+            "selection",
+            "\"clipboard\" (default) or \"primary\", Linux's middle-click selection",
+        ),
+        |p| {
+            let args: ClipboardArgs = match p.parse_args() {
+                Ok(args) => args,
+                Err(e) => return Box::pin(ready(p.invalid_args("clipboard", e))),
+            };
+            Box::pin(ready(probe_clipboard(
+                p.ctx,
+                p.run_id,
+                p.scope,
+                args.selection,
+            )))
+        },
+    );
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClipboardArgs {
+    #[serde(default)]
+    selection: ClipboardSelection,
+}
+
+/// Write a marker to the clipboard and read it back, restoring whatever
+/// text was there before.
+fn probe_clipboard<'a>(
+    ctx: &'a AppContext,
+    run_id: &str,
+    scope: &mut ProbeScope<'a>,
+    selection: ClipboardSelection,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+
+    // If headless, skip immediately
+    if detect_headless() {
+        return result_skip(
+            "probe",
+            "clipboard",
+            run_id,
+            start.elapsed_ms(),
+            "headless environment – no clipboard access",
+        );
+    }
+
+    let test_text = format!("engine_clipboard_probe_{:08x}", ctx.random_u64() as u32);
+    // An empty or non-text clipboard cannot be put back; leave the marker.
+    if let Ok(previous) = ctx.clipboard().read_selection(selection) {
+        scope.defer("restore_clipboard", move || {
+            ctx.clipboard().write_selection(selection, &previous)
+        });
+    }
+
+    // Step 1: write
+    let t0 = ctx.stopwatch();
+    match ctx.clipboard().write_selection(selection, &test_text) {
+        Ok(()) => {
+            steps.insert("write".into(), t0.elapsed_ms());
+        }
+        Err(e) => {
+            steps.insert("write".into(), t0.elapsed_ms());
+            return clipboard_err_result(run_id, start, steps, "write", &e);
+        }
+    }
+
+    // Step 2: read back
+    let t1 = ctx.stopwatch();
+    match ctx.clipboard().read_selection(selection) {
+        Ok(text) => {
+            steps.insert("read".into(), t1.elapsed_ms());
+            if text.trim() != test_text {
+                let mut r = result_err(
+                    "probe",
+                    "clipboard",
+                    run_id,
+                    start.elapsed_ms(),
+                    ErrorCode::ExternalInterference,
+                    "clipboard read-back does not match written text",
+                );
+                r.timing_ms.steps = steps;
+                return r;
+            }
+        }
+        Err(e) => {
+            steps.insert("read".into(), t1.elapsed_ms());
+            return clipboard_err_result(run_id, start, steps, "read", &e);
+        }
+    }
+
+    let mut r = result_ok("probe", "clipboard", run_id, start.elapsed_ms());
+    r.timing_ms.steps = steps;
+    let mut data = serde_json::json!({ "selection": selection });
+    if selection == ClipboardSelection::Clipboard {
+        if let Ok(formats) = ctx.clipboard().available_formats() {
+            data["formats"] = formats.into();
+        }
+    }
+    r.data = Some(data);
+    r
+}
+
+fn clipboard_err_result(
+    run_id: &str,
+    start: Stopwatch,
+    steps: HashMap<String, u64>,
+    failed_step: &str,
+    err: &CapError,
+) -> CommandResult {
+    let code = match err {
+        CapError::Unsupported(_) => ErrorCode::Unsupported,
+        CapError::DependencyMissing(_) => ErrorCode::DependencyMissing,
+        CapError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+        _ => ErrorCode::InternalError,
+    };
+    // For unsupported/dependency-missing, return skip rather than error
+    let status = match code {
+        ErrorCode::Unsupported | ErrorCode::DependencyMissing => Status::Skip,
+        _ => Status::Error,
+    };
+    let mut r = CommandResult {
+        run_id: run_id.to_string(),
+        command: "probe".to_string(),
+        target: "clipboard".to_string(),
+        status,
+        error: Some(ErrorInfo {
+            code,
+            message: format!("clipboard probe failed at {}: {}", failed_step, err),
+            details: serde_json::Value::Null,
+        }),
+        timing_ms: TimingInfo {
+            total: start.elapsed_ms(),
+            steps,
+        },
+        artifacts: vec![],
+        env_summary: EnvSummary::default(),
+        data: None,
+        cached_age_ms: None,
+        deprecation: None,
+    };
+    // Ensure timing is set
+    r.timing_ms.total = start.elapsed_ms();
+    r
+}
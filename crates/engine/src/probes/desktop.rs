@@ -0,0 +1,405 @@
+//! Desktop integration probes: autostart, session events, portals, and
+//! display.
+
+use super::{ProbeInfo, ProbeRegistry, ProbeScope};
+use crate::context::AppContext;
+use crate::traits::{AutostartEntry, CapError};
+use crate::types::*;
+use std::collections::HashMap;
+use std::future::ready;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new(
+            "autostart",
+            "Install, verify, and remove a throwaway login entry",
+        )
+        .capabilities(&["autostart", "fs"]),
+        |p| Box::pin(ready(probe_autostart(p.ctx, p.run_id, p.scope))),
+    );
+    reg.register(
+        ProbeInfo::new("session-events", "Subscribe to sleep/lock signals")
+            .capabilities(&["session"])
+            .platforms(&["linux"]),
+        |p| Box::pin(ready(probe_session_events(p.ctx, p.run_id))),
+    );
+    reg.register(
+        ProbeInfo::new("portals", "Check the XDG desktop portals the app relies on")
+            .capabilities(&["portals"])
+            .platforms(&["linux"]),
+        |p| Box::pin(ready(probe_portals(p.ctx, p.run_id, detect_headless()))),
+    );
+    reg.register(
+        ProbeInfo::new(
+            "display",
+            "Report monitors, scaling, and the display server",
+        )
+        .capabilities(&["display"]),
+        |p| {
+            Box::pin(ready(probe_display(
+                p.ctx,
+                p.run_id,
+                crate::display::gather(),
+            )))
+        },
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Autostart probe
+// ---------------------------------------------------------------------------
+
+/// Install a throwaway login entry, confirm it can be read back from disk
+/// pointing at this executable, then remove it again (also on failure).
+fn probe_autostart<'a>(
+    ctx: &'a AppContext,
+    run_id: &str,
+    scope: &mut ProbeScope<'a>,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+    let entry = AutostartEntry {
+        app_id: format!("{}.probe-{:08x}", ctx.app_id, ctx.random_u64() as u32),
+        name: format!("{} (probe)", ctx.app_name),
+        exec: std::env::current_exe().unwrap_or_else(|_| "/bin/true".into()),
+        args: vec!["--autostart-probe".into()],
+    };
+
+    let t0 = ctx.stopwatch();
+    let enabled = ctx.autostart().enable(&entry);
+    steps.insert("enable".into(), t0.elapsed_ms());
+    if !matches!(enabled, Err(CapError::Unsupported(_))) {
+        let app_id = entry.app_id.clone();
+        scope.defer("disable", move || {
+            let after = ctx.autostart().disable(&app_id)?;
+            if after.enabled {
+                return Err(CapError::Other(format!(
+                    "entry still enabled after disable at {}",
+                    after.path.display()
+                )));
+            }
+            Ok(())
+        });
+    }
+    let outcome = enabled.and_then(|_| {
+        let t1 = ctx.stopwatch();
+        let status = ctx.autostart().status(&entry.app_id);
+        steps.insert("verify".into(), t1.elapsed_ms());
+        status.map(|s| {
+            let on_disk = ctx.fs().exists(&s.path);
+            (s, on_disk)
+        })
+    });
+
+    let elapsed = start.elapsed_ms();
+    let mut r = match outcome {
+        Err(CapError::Unsupported(m)) => result_skip("probe", "autostart", run_id, elapsed, m),
+        Err(e) => result_err(
+            "probe",
+            "autostart",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("autostart probe failed: {}", e),
+        ),
+        Ok((status, on_disk)) if !status.enabled || !on_disk => result_err(
+            "probe",
+            "autostart",
+            run_id,
+            elapsed,
+            ErrorCode::ExternalInterference,
+            format!("entry not found after enable at {}", status.path.display()),
+        ),
+        Ok((status, _)) if status.exec.as_deref() != Some(entry.exec.as_path()) => result_err(
+            "probe",
+            "autostart",
+            run_id,
+            elapsed,
+            ErrorCode::ExternalInterference,
+            format!(
+                "installed entry launches {:?}, expected {:?}",
+                status.exec, entry.exec
+            ),
+        ),
+        Ok((status, _)) => {
+            let mut r = result_ok("probe", "autostart", run_id, elapsed);
+            r.data = Some(serde_json::json!({
+                "mechanism": status.mechanism,
+                "path": status.path,
+            }));
+            r
+        }
+    };
+    r.timing_ms.steps = steps;
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Session events probe
+// ---------------------------------------------------------------------------
+
+/// Subscribe to the OS power/session signals and drop the subscription
+/// again. Sleep or lock cannot be triggered from here, so this proves the
+/// signal source is reachable, not that it delivers.
+fn probe_session_events(ctx: &AppContext, run_id: &str) -> CommandResult {
+    let start = ctx.stopwatch();
+    let subscribed = ctx.session().check();
+    let elapsed = start.elapsed_ms();
+    let mut r = match subscribed {
+        Err(CapError::Unsupported(m)) => result_skip("probe", "session-events", run_id, elapsed, m),
+        Err(e) => result_err(
+            "probe",
+            "session-events",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("session events probe failed: {}", e),
+        ),
+        Ok(signals) => {
+            let mut r = result_ok("probe", "session-events", run_id, elapsed);
+            r.data = Some(serde_json::json!({
+                "source": ctx.session().source(),
+                "signals": signals,
+            }));
+            r
+        }
+    };
+    r.timing_ms.steps.insert("subscribe".into(), elapsed);
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Portals probe
+// ---------------------------------------------------------------------------
+
+/// Check the session bus and the key XDG desktop portals. Skips off Linux,
+/// and on headless machines without a session bus.
+fn probe_portals(ctx: &AppContext, run_id: &str, headless: bool) -> CommandResult {
+    let start = ctx.stopwatch();
+    let checked = ctx.portals().check(crate::portals::KEY_PORTALS);
+    let elapsed = start.elapsed_ms();
+    let mut r = match checked {
+        Err(CapError::Unsupported(m)) => result_skip("probe", "portals", run_id, elapsed, m),
+        Err(CapError::DependencyMissing(m)) if headless => {
+            result_skip("probe", "portals", run_id, elapsed, m)
+        }
+        Err(e) => result_err(
+            "probe",
+            "portals",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("portals probe failed: {}", e),
+        ),
+        Ok(portals) => {
+            let silent: Vec<String> = portals
+                .iter()
+                .filter(|p| p.version.is_none())
+                .map(|p| format!("{} ({})", p.name, p.error.as_deref().unwrap_or("no answer")))
+                .collect();
+            let mut r = if silent.is_empty() {
+                result_ok("probe", "portals", run_id, elapsed)
+            } else {
+                result_err(
+                    "probe",
+                    "portals",
+                    run_id,
+                    elapsed,
+                    ErrorCode::DependencyMissing,
+                    format!("portals not responding: {}", silent.join(", ")),
+                )
+            };
+            r.data = Some(serde_json::json!({ "portals": portals }));
+            r
+        }
+    };
+    r.timing_ms.steps.insert("portals".into(), elapsed);
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Display probe
+// ---------------------------------------------------------------------------
+
+/// Check for the Wayland issues that break desktop apps: no server-side
+/// decorations, no screenshot portal, no XWayland fallback. Each failed
+/// check becomes a hint in `data.hints`; only an unreachable compositor
+/// fails the probe. `facts` is `None` off Linux.
+fn probe_display(
+    ctx: &AppContext,
+    run_id: &str,
+    facts: Option<crate::display::DisplayFacts>,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let Some(facts) = facts else {
+        return result_skip(
+            "probe",
+            "display",
+            run_id,
+            0,
+            "display probe only applies to Linux",
+        );
+    };
+    if facts.wayland_display.is_none() && facts.x11_display.is_none() {
+        return result_skip(
+            "probe",
+            "display",
+            run_id,
+            0,
+            "no display server (headless)",
+        );
+    }
+
+    let mut checks = Vec::new();
+    if let Some(globals) = &facts.wayland_globals {
+        let globals = match globals {
+            Ok(globals) => globals,
+            Err(e) => {
+                return result_err(
+                    "probe",
+                    "display",
+                    run_id,
+                    start.elapsed_ms(),
+                    ErrorCode::ExternalInterference,
+                    format!("cannot reach the Wayland compositor at {}", e),
+                );
+            }
+        };
+        let screenshot = ctx
+            .portals()
+            .check(&["Screenshot"])
+            .map_err(|e| e.to_string())
+            .and_then(|p| {
+                let p = p.into_iter().next().ok_or("no answer")?;
+                p.version
+                    .ok_or(p.error.unwrap_or_else(|| "no answer".into()))
+            });
+        checks.push((
+            "server-side-decorations",
+            globals
+                .iter()
+                .any(|g| g == crate::display::DECORATION_GLOBAL),
+            "compositor draws no window decorations (e.g. GNOME); GTK falls back to \
+             client-side ones, so set `decorations: false` and draw a custom titlebar \
+             for a consistent look",
+        ));
+        checks.push((
+            "screenshot-portal",
+            screenshot.is_ok(),
+            "screenshots need xdg-desktop-portal plus a backend for this desktop \
+             (xdg-desktop-portal-gnome, -kde, -wlr, or -hyprland)",
+        ));
+        checks.push((
+            "xwayland",
+            facts.xwayland,
+            "no XWayland server, so the GDK_BACKEND=x11 workaround for WebKitGTK \
+             rendering bugs is unavailable; install xwayland",
+        ));
+    }
+
+    let elapsed = start.elapsed_ms();
+    let hints: Vec<&str> = checks
+        .iter()
+        .filter(|(_, ok, _)| !ok)
+        .map(|(_, _, hint)| *hint)
+        .collect();
+    let mut r = result_ok("probe", "display", run_id, elapsed);
+    r.data = Some(serde_json::json!({
+        "server": if facts.wayland_display.is_some() { "wayland" } else { "x11" },
+        "facts": facts,
+        "checks": checks
+            .iter()
+            .map(|(name, ok, _)| (name.to_string(), *ok))
+            .collect::<HashMap<_, _>>(),
+        "hints": hints,
+    }));
+    r.timing_ms.steps.insert("checks".into(), elapsed);
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::run_probe;
+    use crate::traits::CapResult;
+
+    #[tokio::test]
+    async fn test_autostart_probe_installs_and_cleans_up() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = AppContext::default_headless().with_autostart(Box::new(
+            crate::autostart::XdgAutostart::new(tmp.path().to_path_buf()),
+        ));
+        let r = run_probe("autostart", &ctx).await;
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        assert_eq!(r.data.unwrap()["mechanism"], "xdg");
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+
+        let ctx = ctx.with_autostart(Box::new(crate::autostart::UnsupportedAutostart(
+            "no login items here".into(),
+        )));
+        assert_eq!(run_probe("autostart", &ctx).await.status, Status::Skip);
+    }
+
+    /// Portal backend with FileChooser and Notification but no Screenshot.
+    struct PartialPortals;
+
+    impl crate::traits::PortalOps for PartialPortals {
+        fn check(&self, names: &[&str]) -> CapResult<Vec<crate::traits::PortalStatus>> {
+            Ok(names
+                .iter()
+                .map(|&name| crate::traits::PortalStatus {
+                    name: name.into(),
+                    version: (name != "Screenshot").then_some(4),
+                    error: (name == "Screenshot").then(|| "No such interface".into()),
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_portals_probe_reports_silent_portals() {
+        let ctx = AppContext::default_headless().with_portals(Box::new(PartialPortals));
+        let r = probe_portals(&ctx, "run", false);
+        let err = r.error.unwrap();
+        assert_eq!(err.code, ErrorCode::DependencyMissing);
+        assert_eq!(
+            err.message,
+            "portals not responding: Screenshot (No such interface)"
+        );
+        assert_eq!(r.data.unwrap()["portals"][0]["version"], 4);
+
+        let ctx = ctx.with_portals(Box::new(crate::portals::UnsupportedPortals));
+        assert_eq!(probe_portals(&ctx, "run", false).status, Status::Skip);
+    }
+
+    #[test]
+    fn test_display_probe_hints() {
+        use crate::display::DisplayFacts;
+
+        let ctx = AppContext::default_headless().with_portals(Box::new(PartialPortals));
+        let wayland = DisplayFacts {
+            wayland_display: Some("wayland-0".into()),
+            wayland_globals: Some(Ok(vec!["wl_compositor".into()])),
+            xwayland: true,
+            ..Default::default()
+        };
+        let r = probe_display(&ctx, "run", Some(wayland.clone()));
+        assert_eq!(r.status, Status::Pass);
+        let data = r.data.unwrap();
+        assert_eq!(data["server"], "wayland");
+        assert_eq!(data["checks"]["server-side-decorations"], false);
+        assert_eq!(data["checks"]["screenshot-portal"], false);
+        assert_eq!(data["checks"]["xwayland"], true);
+        assert_eq!(data["hints"].as_array().unwrap().len(), 2);
+
+        let unreachable = DisplayFacts {
+            wayland_globals: Some(Err("/run/user/1000/wayland-0: refused".into())),
+            ..wayland
+        };
+        let r = probe_display(&ctx, "run", Some(unreachable));
+        assert_eq!(r.error.unwrap().code, ErrorCode::ExternalInterference);
+
+        let r = probe_display(&ctx, "run", Some(DisplayFacts::default()));
+        assert_eq!(r.status, Status::Skip);
+    }
+}
@@ -0,0 +1,306 @@
+//! Device probes: USB, printing, and media devices.
+
+use super::{ProbeInfo, ProbeRegistry};
+use crate::context::AppContext;
+use crate::traits::{CapError, MediaKind, MediaPermission};
+use crate::types::*;
+use std::collections::HashMap;
+use std::future::ready;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new("usb", "List USB devices and removable disks")
+            .capabilities(&["devices"])
+            .arg(
+                "expected",
+                "vendor:product ids that must be present (default $APP__USB_EXPECT)",
+            ),
+        |p| {
+            let args: UsbArgs = match p.parse_args() {
+                Ok(args) => args,
+                Err(e) => return Box::pin(ready(p.invalid_args("usb", e))),
+            };
+            let expected = args
+                .expected
+                .unwrap_or_else(crate::devices::expected_usb_devices);
+            Box::pin(ready(probe_usb(p.ctx, p.run_id, &expected)))
+        },
+    );
+    reg.register(
+        ProbeInfo::new("printing", "Check the print service and its queues")
+            .capabilities(&["devices"]),
+        |p| Box::pin(ready(probe_printing(p.ctx, p.run_id, detect_headless()))),
+    );
+    reg.register(
+        ProbeInfo::new("media-devices", "List cameras, microphones, and speakers")
+            .capabilities(&["devices"])
+            .arg(
+                "required",
+                "kinds that must be present and allowed (default $APP__MEDIA_REQUIRE)",
+            ),
+        |p| {
+            let args: MediaArgs = match p.parse_args() {
+                Ok(args) => args,
+                Err(e) => return Box::pin(ready(p.invalid_args("media-devices", e))),
+            };
+            let required = args
+                .required
+                .unwrap_or_else(crate::devices::required_media_kinds);
+            Box::pin(ready(probe_media_devices(p.ctx, p.run_id, &required)))
+        },
+    );
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UsbArgs {
+    expected: Option<Vec<String>>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MediaArgs {
+    required: Option<Vec<MediaKind>>,
+}
+
+// ---------------------------------------------------------------------------
+// USB probe
+// ---------------------------------------------------------------------------
+
+/// Enumerate USB devices and removable disks, failing if any `expected`
+/// `vendor:product` id is missing. Skips only when neither list is
+/// available on this OS.
+fn probe_usb(ctx: &AppContext, run_id: &str, expected: &[String]) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+
+    let t0 = ctx.stopwatch();
+    let usb = ctx.devices().usb_devices();
+    steps.insert("usb_devices".into(), t0.elapsed_ms());
+    let t1 = ctx.stopwatch();
+    let media = ctx.devices().removable_media();
+    steps.insert("removable_media".into(), t1.elapsed_ms());
+
+    let elapsed = start.elapsed_ms();
+    let mut r = match (usb, media) {
+        (Err(CapError::Unsupported(m)), Err(CapError::Unsupported(_))) => {
+            result_skip("probe", "usb", run_id, elapsed, m)
+        }
+        (Err(e), _) | (_, Err(e)) if !matches!(e, CapError::Unsupported(_)) => result_err(
+            "probe",
+            "usb",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("usb probe failed: {}", e),
+        ),
+        (usb, media) => {
+            let usb = usb.ok();
+            let found: Vec<String> = usb.iter().flatten().map(crate::devices::usb_id).collect();
+            let missing: Vec<&String> = expected.iter().filter(|id| !found.contains(id)).collect();
+            let data = serde_json::json!({
+                "usb_bus": usb.is_some(),
+                "usb_devices": usb.unwrap_or_default(),
+                "removable_media": media.unwrap_or_default(),
+                "expected": expected,
+            });
+            let mut r = if missing.is_empty() {
+                result_ok("probe", "usb", run_id, elapsed)
+            } else {
+                result_err(
+                    "probe",
+                    "usb",
+                    run_id,
+                    elapsed,
+                    ErrorCode::ExternalInterference,
+                    format!("expected USB devices not visible: {:?}", missing),
+                )
+            };
+            r.data = Some(data);
+            r
+        }
+    };
+    r.timing_ms.steps = steps;
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Printing probe
+// ---------------------------------------------------------------------------
+
+/// Check the print scheduler answers and list its printers. Machines
+/// without CUPS skip, as do headless ones whose scheduler is stopped.
+fn probe_printing(ctx: &AppContext, run_id: &str, headless: bool) -> CommandResult {
+    let start = ctx.stopwatch();
+    let service = ctx.devices().print_service();
+    let elapsed = start.elapsed_ms();
+    let mut r = match service {
+        Err(CapError::Unsupported(m) | CapError::DependencyMissing(m)) => {
+            result_skip("probe", "printing", run_id, elapsed, m)
+        }
+        Err(e) => result_err(
+            "probe",
+            "printing",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("printing probe failed: {}", e),
+        ),
+        Ok(service) if !service.running && headless => result_skip(
+            "probe",
+            "printing",
+            run_id,
+            elapsed,
+            "print scheduler is not running (headless)",
+        ),
+        Ok(service) if !service.running => result_err(
+            "probe",
+            "printing",
+            run_id,
+            elapsed,
+            ErrorCode::DependencyMissing,
+            "print scheduler is not running",
+        ),
+        Ok(service) => {
+            let mut r = result_ok("probe", "printing", run_id, elapsed);
+            r.data = Some(serde_json::json!({
+                "printers": service.printers,
+                "default": service.printers.iter().find(|p| p.is_default).map(|p| &p.name),
+            }));
+            r
+        }
+    };
+    r.timing_ms.steps.insert("lpstat".into(), elapsed);
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Media devices probe
+// ---------------------------------------------------------------------------
+
+/// List cameras and microphones with their permission state, without
+/// capturing. Each `required` kind must have a device and must not be
+/// denied or restricted; not-yet-asked passes, as the app can still prompt.
+fn probe_media_devices(ctx: &AppContext, run_id: &str, required: &[MediaKind]) -> CommandResult {
+    let start = ctx.stopwatch();
+    let inventory = ctx.devices().media_devices();
+    let elapsed = start.elapsed_ms();
+    let mut r = match inventory {
+        Err(CapError::Unsupported(m)) => result_skip("probe", "media-devices", run_id, elapsed, m),
+        Err(e) => result_err(
+            "probe",
+            "media-devices",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("media devices probe failed: {}", e),
+        ),
+        Ok(inventory) => {
+            let label = |v: serde_json::Result<serde_json::Value>| {
+                v.ok()
+                    .and_then(|v| v.as_str().map(String::from))
+                    .unwrap_or_default()
+            };
+            let problem = required.iter().find_map(|&kind| {
+                let name = label(serde_json::to_value(kind));
+                if !inventory.devices.iter().any(|d| d.kind == kind) {
+                    return Some((ErrorCode::DependencyMissing, format!("no {} found", name)));
+                }
+                match inventory.permission(kind) {
+                    p @ (MediaPermission::Denied | MediaPermission::Restricted) => Some((
+                        ErrorCode::PermissionDenied,
+                        format!("{} access is {}", name, label(serde_json::to_value(p))),
+                    )),
+                    _ => None,
+                }
+            });
+            let mut r = match problem {
+                None => result_ok("probe", "media-devices", run_id, elapsed),
+                Some((code, message)) => {
+                    result_err("probe", "media-devices", run_id, elapsed, code, message)
+                }
+            };
+            r.data = serde_json::to_value(&inventory).ok();
+            r
+        }
+    };
+    r.timing_ms.steps.insert("enumerate".into(), elapsed);
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::CapResult;
+
+    struct OneStick;
+
+    impl crate::traits::DeviceOps for OneStick {
+        fn usb_devices(&self) -> CapResult<Vec<crate::traits::UsbDevice>> {
+            Ok(vec![crate::traits::UsbDevice {
+                vendor_id: "0781".into(),
+                product_id: "5581".into(),
+                manufacturer: None,
+                product: Some("Ultra".into()),
+                location: "1-1".into(),
+            }])
+        }
+        fn removable_media(&self) -> CapResult<Vec<crate::traits::RemovableMedia>> {
+            Err(CapError::Unsupported("no lsblk here".into()))
+        }
+        fn print_service(&self) -> CapResult<crate::traits::PrintService> {
+            Ok(crate::traits::PrintService {
+                running: false,
+                printers: vec![],
+            })
+        }
+        fn media_devices(&self) -> CapResult<crate::traits::MediaInventory> {
+            Ok(crate::traits::MediaInventory {
+                devices: vec![crate::traits::MediaDevice {
+                    kind: MediaKind::Camera,
+                    name: "Integrated Camera".into(),
+                    id: "/dev/video0".into(),
+                    permission: MediaPermission::Denied,
+                }],
+                camera: MediaPermission::Denied,
+                microphone: MediaPermission::Unknown,
+            })
+        }
+    }
+
+    #[test]
+    fn test_usb_probe_checks_expected_devices() {
+        let ctx = AppContext::default_headless().with_devices(Box::new(OneStick));
+        let r = probe_usb(&ctx, "run", &["0781:5581".into()]);
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        let data = r.data.unwrap();
+        assert_eq!(data["usb_devices"][0]["product"], "Ultra");
+        assert_eq!(data["removable_media"], serde_json::json!([]));
+
+        let r = probe_usb(&ctx, "run", &["0781:5581".into(), "046d:c52b".into()]);
+        assert_eq!(r.error.unwrap().code, ErrorCode::ExternalInterference);
+    }
+
+    #[test]
+    fn test_printing_probe_skips_stopped_scheduler_when_headless() {
+        let ctx = AppContext::default_headless().with_devices(Box::new(OneStick));
+        assert_eq!(probe_printing(&ctx, "run", true).status, Status::Skip);
+        let r = probe_printing(&ctx, "run", false);
+        assert_eq!(r.error.unwrap().code, ErrorCode::DependencyMissing);
+    }
+
+    #[test]
+    fn test_media_devices_probe_checks_required_kinds() {
+        let ctx = AppContext::default_headless().with_devices(Box::new(OneStick));
+        let r = probe_media_devices(&ctx, "run", &[]);
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.data.unwrap()["camera"], "denied");
+
+        let r = probe_media_devices(&ctx, "run", &[MediaKind::Camera]);
+        let err = r.error.unwrap();
+        assert_eq!(err.code, ErrorCode::PermissionDenied);
+        assert_eq!(err.message, "camera access is denied");
+        let r = probe_media_devices(&ctx, "run", &[MediaKind::Microphone]);
+        assert_eq!(r.error.unwrap().code, ErrorCode::DependencyMissing);
+    }
+}
@@ -0,0 +1,375 @@
+//! Filesystem and file-locking probes.
+
+use super::{ProbeInfo, ProbeRegistry, ProbeScope};
+use crate::clock::Stopwatch;
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::*;
+use std::collections::HashMap;
+use std::future::ready;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new(
+            "filesystem",
+            "Create, write, read back, and remove a temp dir",
+        )
+        .capabilities(&["fs"]),
+        |p| Box::pin(ready(probe_filesystem(p.ctx, p.run_id, p.scope))),
+    );
+    reg.register(
+        ProbeInfo::new(
+            "file-locking",
+            "Take, contend for, and release an advisory lock file",
+        )
+        .capabilities(&["fs"])
+        .arg(
+            "dir",
+            "directory to test instead of the temp dir, e.g. a shared mount",
+        ),
+        |p| {
+            let args: FileLockingArgs = match p.parse_args() {
+                Ok(args) => args,
+                Err(e) => return Box::pin(ready(p.invalid_args("file-locking", e))),
+            };
+            Box::pin(ready(probe_file_locking(
+                p.ctx, p.run_id, p.scope, args.dir,
+            )))
+        },
+    );
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileLockingArgs {
+    dir: Option<std::path::PathBuf>,
+}
+
+// ---------------------------------------------------------------------------
+// Filesystem probe
+// ---------------------------------------------------------------------------
+
+fn probe_filesystem<'a>(
+    ctx: &'a AppContext,
+    run_id: &str,
+    scope: &mut ProbeScope<'a>,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+
+    let tmp_dir = ctx
+        .fs()
+        .temp_dir()
+        .join(format!("engine_probe_{:08x}", ctx.random_u64() as u32));
+
+    // Step 1: create temp directory (a failed create may still leave parts)
+    let cleanup_dir = tmp_dir.clone();
+    scope.defer("remove_temp_dir", move || {
+        if ctx.fs().exists(&cleanup_dir) {
+            ctx.fs().remove_dir_all(&cleanup_dir)
+        } else {
+            Ok(())
+        }
+    });
+    let t0 = ctx.stopwatch();
+    if let Err(e) = ctx.fs().create_dir_all(&tmp_dir) {
+        return probe_fs_err("filesystem", run_id, start, steps, "create_dir", e);
+    }
+    steps.insert("create_dir".into(), t0.elapsed_ms());
+
+    // Step 2: write a test file
+    let test_file = tmp_dir.join("probe_test.txt");
+    let payload = b"engine filesystem probe";
+    let t1 = ctx.stopwatch();
+    if let Err(e) = ctx.fs().write_file(&test_file, payload) {
+        return probe_fs_err("filesystem", run_id, start, steps, "write_file", e);
+    }
+    steps.insert("write_file".into(), t1.elapsed_ms());
+
+    // Step 3: read it back and verify
+    let t2 = ctx.stopwatch();
+    match ctx.fs().read_file(&test_file) {
+        Ok(data) if data != payload => {
+            let mut r = result_err(
+                "probe",
+                "filesystem",
+                run_id,
+                start.elapsed_ms(),
+                ErrorCode::ExternalInterference,
+                "read-back data does not match written data",
+            );
+            r.timing_ms.steps = steps;
+            return r;
+        }
+        Ok(_) => {}
+        Err(e) => return probe_fs_err("filesystem", run_id, start, steps, "read_file", e),
+    }
+    steps.insert("read_verify".into(), t2.elapsed_ms());
+
+    // Step 4: link round-trips. Containers, sandboxes, and Windows without
+    // Developer Mode differ here, so failures are reported, not fatal.
+    let t3 = ctx.stopwatch();
+    let links = serde_json::json!({
+        "symlink": link_round_trip(ctx, &test_file, &tmp_dir.join("probe_symlink"), false),
+        "hard_link": link_round_trip(ctx, &test_file, &tmp_dir.join("probe_hardlink"), true),
+    });
+    steps.insert("links".into(), t3.elapsed_ms());
+
+    let mut r = result_ok("probe", "filesystem", run_id, start.elapsed_ms());
+    r.timing_ms.steps = steps;
+    r.data = Some(serde_json::json!({
+        "temp_dir_used": tmp_dir.display().to_string(),
+        "links": links,
+    }));
+    r
+}
+
+/// Link `file` at `link`, then read it back through the link: `"ok"` or
+/// what went wrong.
+fn link_round_trip(
+    ctx: &AppContext,
+    file: &std::path::Path,
+    link: &std::path::Path,
+    hard: bool,
+) -> String {
+    let made = if hard {
+        ctx.fs().hard_link(file, link)
+    } else {
+        ctx.fs()
+            .symlink(file, link)
+            .and_then(|_| match ctx.fs().read_link(link) {
+                Ok(target) if target == file => Ok(()),
+                Ok(target) => Err(CapError::Other(format!(
+                    "link points to {}",
+                    target.display()
+                ))),
+                Err(e) => Err(e),
+            })
+    };
+    let checked = made.and_then(|_| match ctx.fs().read_file(link) {
+        Ok(data) if data == ctx.fs().read_file(file)? => Ok(()),
+        Ok(_) => Err(CapError::Other("content differs through the link".into())),
+        Err(e) => Err(e),
+    });
+    match checked {
+        Ok(()) => "ok".into(),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn probe_fs_err(
+    probe: &str,
+    run_id: &str,
+    start: Stopwatch,
+    steps: HashMap<String, u64>,
+    failed_step: &str,
+    err: CapError,
+) -> CommandResult {
+    let mut r = result_err(
+        "probe",
+        probe,
+        run_id,
+        start.elapsed_ms(),
+        err.error_code(),
+        format!("{} probe failed at {}: {}", probe, failed_step, err),
+    );
+    r.timing_ms.steps = steps;
+    r
+}
+
+// ---------------------------------------------------------------------------
+// File-locking probe
+// ---------------------------------------------------------------------------
+
+/// How long the probe waits for its own first lock.
+const LOCK_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Lock a file exclusively, check that a second handle is refused, then
+/// release and lock again. Network and VM shares (NFS, SMB, virtiofs,
+/// 9p) often accept the calls but enforce nothing, or refuse them.
+///
+/// The contending handle belongs to this process, so filesystems that
+/// only track locks per process (NFS mapped onto POSIX locks) fail here
+/// too.
+fn probe_file_locking<'a>(
+    ctx: &'a AppContext,
+    run_id: &str,
+    scope: &mut ProbeScope<'a>,
+    dir: Option<std::path::PathBuf>,
+) -> CommandResult {
+    use crate::traits::LockMode;
+
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+    let dir = dir.unwrap_or_else(|| ctx.fs().temp_dir());
+    let lock_path = dir.join(format!(
+        "engine_lock_probe_{:08x}.lock",
+        ctx.random_u64() as u32
+    ));
+    let cleanup_path = lock_path.clone();
+    scope.defer("remove_lock_file", move || {
+        if ctx.fs().exists(&cleanup_path) {
+            ctx.fs().remove_file(&cleanup_path)
+        } else {
+            Ok(())
+        }
+    });
+
+    // Step 1: take the lock
+    let t0 = ctx.stopwatch();
+    let held = match ctx
+        .fs()
+        .lock(&lock_path, LockMode::Exclusive, LOCK_PROBE_TIMEOUT)
+    {
+        Ok(lock) => lock,
+        Err(e) => return probe_fs_err("file-locking", run_id, start, steps, "lock", e),
+    };
+    steps.insert("lock".into(), t0.elapsed_ms());
+
+    // Step 2: a second handle must not get it
+    let t1 = ctx.stopwatch();
+    match ctx
+        .fs()
+        .lock(&lock_path, LockMode::Shared, std::time::Duration::ZERO)
+    {
+        Err(CapError::Timeout) => {}
+        Ok(_) => {
+            let mut r = result_err(
+                "probe",
+                "file-locking",
+                run_id,
+                start.elapsed_ms(),
+                ErrorCode::Unsupported,
+                format!(
+                    "locks are not enforced in {}: a second handle locked a held file",
+                    dir.display()
+                ),
+            );
+            r.timing_ms.steps = steps;
+            return r;
+        }
+        Err(e) => return probe_fs_err("file-locking", run_id, start, steps, "contend", e),
+    }
+    steps.insert("contend".into(), t1.elapsed_ms());
+
+    // Step 3: release, after which it can be taken again
+    let t2 = ctx.stopwatch();
+    if let Err(e) = ctx.fs().unlock(held) {
+        return probe_fs_err("file-locking", run_id, start, steps, "unlock", e);
+    }
+    match ctx
+        .fs()
+        .lock(&lock_path, LockMode::Exclusive, LOCK_PROBE_TIMEOUT)
+    {
+        Ok(lock) => {
+            if let Err(e) = ctx.fs().unlock(lock) {
+                return probe_fs_err("file-locking", run_id, start, steps, "unlock", e);
+            }
+        }
+        Err(e) => return probe_fs_err("file-locking", run_id, start, steps, "relock", e),
+    }
+    steps.insert("relock".into(), t2.elapsed_ms());
+
+    let mut r = result_ok("probe", "file-locking", run_id, start.elapsed_ms());
+    r.timing_ms.steps = steps;
+    r.data = Some(serde_json::json!({
+        "dir": dir.display().to_string(),
+    }));
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::{run_probe, ProbeRegistry};
+    use crate::traits::CapResult;
+
+    #[tokio::test]
+    async fn test_fixed_clock_zeroes_timings() {
+        let ctx = AppContext::default_headless()
+            .with_clock(Box::new(crate::clock::FixedClock::at_unix(1_700_000_000)));
+        let r = run_probe("filesystem", &ctx).await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.timing_ms.total, 0);
+        assert_eq!(r.timing_ms.steps.len(), 5);
+        assert!(r.timing_ms.steps.values().all(|&ms| ms == 0));
+        #[cfg(unix)]
+        assert_eq!(
+            r.data.unwrap()["links"],
+            serde_json::json!({ "symlink": "ok", "hard_link": "ok" })
+        );
+    }
+
+    /// Real files under `root`, but reads fail.
+    struct UnreadableFs(std::path::PathBuf);
+
+    impl crate::traits::FilesystemOps for UnreadableFs {
+        fn read_file(&self, path: &std::path::Path) -> CapResult<Vec<u8>> {
+            Err(CapError::PermissionDenied(path.display().to_string()))
+        }
+        fn write_file(&self, path: &std::path::Path, data: &[u8]) -> CapResult<()> {
+            crate::platform::StdFilesystem.write_file(path, data)
+        }
+        fn remove_file(&self, path: &std::path::Path) -> CapResult<()> {
+            crate::platform::StdFilesystem.remove_file(path)
+        }
+        fn create_dir_all(&self, path: &std::path::Path) -> CapResult<()> {
+            crate::platform::StdFilesystem.create_dir_all(path)
+        }
+        fn remove_dir_all(&self, path: &std::path::Path) -> CapResult<()> {
+            crate::platform::StdFilesystem.remove_dir_all(path)
+        }
+        fn exists(&self, path: &std::path::Path) -> bool {
+            path.exists()
+        }
+        fn temp_dir(&self) -> std::path::PathBuf {
+            self.0.clone()
+        }
+        fn list_dir(&self, path: &std::path::Path) -> CapResult<Vec<crate::traits::DirEntry>> {
+            crate::platform::StdFilesystem.list_dir(path)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_probe_cleans_up_after_failure() {
+        use crate::platform::{HeadlessClipboard, MockNetwork};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = AppContext::new(
+            Box::new(UnreadableFs(tmp.path().to_path_buf())),
+            Box::new(MockNetwork::new()),
+            Box::new(HeadlessClipboard),
+        );
+        let r = run_probe("filesystem", &ctx).await;
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.error.unwrap().code, ErrorCode::PermissionDenied);
+        assert!(r.timing_ms.steps.contains_key("write_file"));
+        assert!(r.timing_ms.steps.contains_key("cleanup.remove_temp_dir"));
+        assert_eq!(r.data.unwrap()["cleanup"]["remove_temp_dir"], "ok");
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_file_locking_probe_contends_and_cleans_up() {
+        use crate::platform::{HeadlessClipboard, MockNetwork};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = AppContext::default_headless();
+        let args = serde_json::json!({ "dir": tmp.path() });
+        let r = ProbeRegistry::new()
+            .run("file-locking", args.clone(), &ctx)
+            .await;
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        assert!(r.timing_ms.steps.contains_key("contend"));
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+
+        // A backend without locking reports it rather than passing.
+        let ctx = AppContext::new(
+            Box::new(UnreadableFs(tmp.path().to_path_buf())),
+            Box::new(MockNetwork::new()),
+            Box::new(HeadlessClipboard),
+        );
+        let r = ProbeRegistry::new().run("file-locking", args, &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::Unsupported);
+    }
+}
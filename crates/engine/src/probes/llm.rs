@@ -0,0 +1,168 @@
+//! LLM probe: reach each provider that has a key configured.
+
+use super::{ProbeInfo, ProbeRegistry};
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::*;
+use std::collections::HashMap;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new("llm", "Reach each LLM provider with a configured key")
+            .capabilities(&["llm", "network"]),
+        |p| Box::pin(probe_llm(p.ctx, p.run_id)),
+    );
+}
+
+/// Check every provider with a configured API key. Each provider gets its own
+/// entry in `data.providers` (reachability, latency, auth/quota failures);
+/// the probe errors with the first failing provider's code.
+async fn probe_llm(ctx: &AppContext, run_id: &str) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+
+    let providers = ctx.llm().configured_providers();
+    if providers.is_empty() {
+        return result_skip(
+            "probe",
+            "llm",
+            run_id,
+            start.elapsed_ms(),
+            "no LLM API keys configured (set APP__<PROVIDER>_API_KEY)",
+        );
+    }
+
+    let mut reports = Vec::new();
+    let mut first_failure: Option<(ErrorCode, String)> = None;
+    for provider in providers {
+        let t0 = ctx.stopwatch();
+        let outcome = ctx.llm().check_provider(provider).await;
+        let latency_ms = t0.elapsed_ms();
+        steps.insert(provider.as_str().to_string(), latency_ms);
+        ctx.events().emit(
+            run_id,
+            "probe:step",
+            serde_json::json!({
+                "probe": "llm",
+                "step": provider,
+                "ok": outcome.is_ok(),
+                "duration_ms": latency_ms,
+            }),
+        );
+
+        let report = match outcome {
+            Ok(()) => serde_json::json!({
+                "provider": provider,
+                "reachable": true,
+                "authenticated": true,
+                "rate_limited": false,
+                "latency_ms": latency_ms,
+            }),
+            Err(e) => {
+                // Auth and quota failures still prove the endpoint answered.
+                let reachable =
+                    matches!(e, CapError::PermissionDenied(_) | CapError::RateLimited(_));
+                let report = serde_json::json!({
+                    "provider": provider,
+                    "reachable": reachable,
+                    "authenticated": matches!(e, CapError::RateLimited(_)),
+                    "rate_limited": matches!(e, CapError::RateLimited(_)),
+                    "latency_ms": latency_ms,
+                    "error": { "code": e.error_code(), "message": e.to_string() },
+                });
+                first_failure
+                    .get_or_insert((e.error_code(), format!("{}: {}", provider.as_str(), e)));
+                report
+            }
+        };
+        reports.push(report);
+    }
+
+    let total = start.elapsed_ms();
+    let mut r = match first_failure {
+        None => result_ok("probe", "llm", run_id, total),
+        Some((code, message)) => result_err("probe", "llm", run_id, total, code, message),
+    };
+    r.timing_ms.steps = steps;
+    r.data = Some(serde_json::json!({ "providers": reports }));
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{LlmOps, LlmProvider, LlmRequest, LlmResponse, OnDelta};
+    use crate::probes::run_probe;
+    use crate::traits::CapResult;
+
+    /// OpenAI answers, Gemini is over quota.
+    struct FlakyProviders;
+
+    #[async_trait::async_trait]
+    impl LlmOps for FlakyProviders {
+        async fn complete(&self, _req: &LlmRequest) -> CapResult<LlmResponse> {
+            Err(CapError::Unsupported("not used".into()))
+        }
+        async fn stream(
+            &self,
+            _req: &LlmRequest,
+            _on_delta: &mut OnDelta<'_>,
+        ) -> CapResult<LlmResponse> {
+            Err(CapError::Unsupported("not used".into()))
+        }
+        fn configured_providers(&self) -> Vec<LlmProvider> {
+            vec![LlmProvider::Openai, LlmProvider::Gemini]
+        }
+        async fn check_provider(&self, provider: LlmProvider) -> CapResult<()> {
+            match provider {
+                LlmProvider::Gemini => Err(CapError::RateLimited("HTTP 429".into())),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_probe_reports_per_provider() {
+        let ctx = AppContext::default_headless().with_llm(Box::new(FlakyProviders));
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        ctx.events()
+            .subscribe(move |ev| seen.lock().unwrap().push(ev.clone()));
+        let r = run_probe("llm", &ctx).await;
+
+        let events = events.lock().unwrap();
+        let topics: Vec<&str> = events.iter().map(|e| e.topic.as_str()).collect();
+        assert_eq!(
+            topics,
+            [
+                "probe:started",
+                "probe:step",
+                "probe:step",
+                "probe:finished"
+            ]
+        );
+        assert!(events.iter().all(|e| e.run_id == r.run_id));
+        assert_eq!(events[2].payload["step"], "gemini");
+        assert_eq!(events[2].payload["ok"], false);
+        assert_eq!(events[3].payload["status"], "error");
+
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.error.unwrap().code, ErrorCode::NetworkError);
+
+        let providers = r.data.unwrap()["providers"].as_array().unwrap().clone();
+        assert_eq!(providers.len(), 2);
+        assert_eq!(providers[0]["provider"], "openai");
+        assert_eq!(providers[0]["reachable"], true);
+        assert_eq!(providers[1]["provider"], "gemini");
+        assert_eq!(providers[1]["rate_limited"], true);
+        assert!(r.timing_ms.steps.contains_key("gemini"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_probe_skips_without_keys() {
+        let ctx = AppContext::default_headless()
+            .with_llm(Box::new(crate::llm::http::HttpLlm::new(Default::default())));
+        let r = run_probe("llm", &ctx).await;
+        assert_eq!(r.status, Status::Skip);
+    }
+}
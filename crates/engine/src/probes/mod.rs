@@ -0,0 +1,317 @@
+//! Targeted capability probes – filesystem, file-locking, network, clipboard,
+//! llm, autostart, session-events, usb, printing, media-devices, portals,
+//! display, interfaces.
+//!
+//! Probes live in a [`ProbeRegistry`], like commands in a
+//! [`crate::commands::CommandRegistry`]; apps register their own next to
+//! the built-in ones, which live in one submodule per probe family.
+
+mod clipboard;
+mod desktop;
+mod devices;
+mod fs;
+mod llm;
+mod network;
+mod scope;
+mod secrets;
+
+pub use scope::ProbeScope;
+
+use crate::context::AppContext;
+use crate::types::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Run a built-in probe by name with no arguments.
+pub async fn run_probe(name: &str, ctx: &AppContext) -> CommandResult {
+    ProbeRegistry::new().run(name, Value::Null, ctx).await
+}
+
+// ---------------------------------------------------------------------------
+// Registry
+// ---------------------------------------------------------------------------
+
+/// What a probe is run with.
+pub struct ProbeInput<'s, 'a: 's> {
+    pub ctx: &'a AppContext,
+    pub run_id: &'s str,
+    /// Probe-specific arguments; `null` when the caller passed none.
+    pub args: &'s Value,
+    pub scope: &'s mut ProbeScope<'a>,
+}
+
+impl ProbeInput<'_, '_> {
+    /// `args` as `T`, with `null` meaning `T::default()`.
+    pub fn parse_args<T: DeserializeOwned + Default>(&self) -> Result<T, serde_json::Error> {
+        parse_args(self.args)
+    }
+
+    /// The `INVALID_INPUT` result for arguments the probe cannot use.
+    pub fn invalid_args(&self, probe: &str, err: impl std::fmt::Display) -> CommandResult {
+        result_err(
+            "probe",
+            probe,
+            self.run_id,
+            0,
+            ErrorCode::InvalidInput,
+            format!("invalid {} probe args: {}", probe, err),
+        )
+    }
+}
+
+fn parse_args<T: DeserializeOwned + Default>(args: &Value) -> Result<T, serde_json::Error> {
+    match args {
+        Value::Null => Ok(T::default()),
+        args => T::deserialize(args),
+    }
+}
+
+pub type ProbeFuture<'s> = Pin<Box<dyn Future<Output = CommandResult> + Send + 's>>;
+
+/// Signature for all probes. Synchronous ones return
+/// `Box::pin(std::future::ready(result))`.
+pub type ProbeHandler = for<'s, 'a> fn(ProbeInput<'s, 'a>) -> ProbeFuture<'s>;
+
+/// What `appctl probe --list` shows about a probe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProbeInfo {
+    pub name: String,
+    pub description: String,
+    /// Context capabilities it exercises (`fs`, `network`, ...). Probes
+    /// needing `network` are skipped in offline mode.
+    pub capabilities: Vec<String>,
+    /// `std::env::consts::OS` values it runs on; empty means all. Elsewhere
+    /// it is skipped.
+    pub platforms: Vec<String>,
+    /// Accepted arguments and what they do.
+    pub args: BTreeMap<String, String>,
+}
+
+impl ProbeInfo {
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            capabilities: Vec::new(),
+            platforms: Vec::new(),
+            args: BTreeMap::new(),
+        }
+    }
+
+    pub fn capabilities(mut self, capabilities: &[&str]) -> Self {
+        self.capabilities = capabilities.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub fn platforms(mut self, platforms: &[&str]) -> Self {
+        self.platforms = platforms.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    pub fn arg(mut self, name: &str, description: &str) -> Self {
+        self.args.insert(name.to_string(), description.to_string());
+        self
+    }
+
+    pub fn needs_network(&self) -> bool {
+        self.capabilities.iter().any(|c| c == "network")
+    }
+
+    pub fn runs_here(&self) -> bool {
+        self.platforms.is_empty() || self.platforms.iter().any(|p| p == std::env::consts::OS)
+    }
+}
+
+pub struct ProbeRegistry {
+    probes: HashMap<String, (ProbeInfo, ProbeHandler)>,
+}
+
+impl ProbeRegistry {
+    pub fn new() -> Self {
+        let mut reg = Self {
+            probes: HashMap::new(),
+        };
+        fs::register(&mut reg);
+        network::register(&mut reg);
+        clipboard::register(&mut reg);
+        llm::register(&mut reg);
+        desktop::register(&mut reg);
+        secrets::register(&mut reg);
+        devices::register(&mut reg);
+        reg
+    }
+
+    pub fn register(&mut self, info: ProbeInfo, handler: ProbeHandler) {
+        self.probes.insert(info.name.clone(), (info, handler));
+    }
+
+    pub fn list(&self) -> Vec<&ProbeInfo> {
+        let mut infos: Vec<&ProbeInfo> = self.probes.values().map(|(info, _)| info).collect();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProbeInfo> {
+        self.probes.get(name).map(|(info, _)| info)
+    }
+
+    /// Run a probe by name and return a full CommandResult.
+    ///
+    /// Known probes publish `probe:started` / `probe:finished` events (and
+    /// `probe:step` for each LLM provider checked) on the context's event
+    /// bus. Cleanups a probe deferred on its [`ProbeScope`] run before
+    /// `probe:finished`.
+    pub async fn run(&self, name: &str, args: Value, ctx: &AppContext) -> CommandResult {
+        let run_id = ctx.new_run_id();
+        let Some((info, handler)) = self.probes.get(name) else {
+            let names: Vec<&str> = self.list().iter().map(|i| i.name.as_str()).collect();
+            return result_err(
+                "probe",
+                name,
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                format!("unknown probe: {} (available: {})", name, names.join(", ")),
+            );
+        };
+
+        ctx.events().emit(
+            &run_id,
+            "probe:started",
+            serde_json::json!({ "probe": name }),
+        );
+        let result = if ctx.offline && info.needs_network() {
+            result_offline("probe", name, &run_id)
+        } else if !info.runs_here() {
+            result_skip(
+                "probe",
+                name,
+                &run_id,
+                0,
+                format!("{} probe runs only on {}", name, info.platforms.join(", ")),
+            )
+        } else {
+            let mut scope = ProbeScope::new(ctx);
+            let result = handler(ProbeInput {
+                ctx,
+                run_id: &run_id,
+                args: &args,
+                scope: &mut scope,
+            })
+            .await;
+            scope.finish(result)
+        };
+        ctx.events().emit(
+            &run_id,
+            "probe:finished",
+            serde_json::json!({
+                "probe": name,
+                "status": result.status,
+                "duration_ms": result.timing_ms.total,
+            }),
+        );
+        result
+    }
+}
+
+impl Default for ProbeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::{run_probe, ProbeRegistry};
+
+    use std::future::ready;
+
+    #[tokio::test]
+    async fn test_registry_runs_custom_probes_with_args() {
+        let ctx = AppContext::default_headless();
+        let mut registry = ProbeRegistry::new();
+        registry.register(
+            ProbeInfo::new("echo", "Pass if args.ok is true")
+                .capabilities(&["fs"])
+                .arg("ok", "whether to pass"),
+            |p| {
+                let r = if p.args["ok"] == true {
+                    result_ok("probe", "echo", p.run_id, 0)
+                } else {
+                    result_err(
+                        "probe",
+                        "echo",
+                        p.run_id,
+                        0,
+                        ErrorCode::InvalidInput,
+                        "not ok",
+                    )
+                };
+                Box::pin(ready(r))
+            },
+        );
+        registry.register(
+            ProbeInfo::new("elsewhere", "Never runs here").platforms(&["plan9"]),
+            |p| Box::pin(ready(result_ok("probe", "elsewhere", p.run_id, 0))),
+        );
+
+        let r = registry
+            .run("echo", serde_json::json!({ "ok": true }), &ctx)
+            .await;
+        assert_eq!(r.status, Status::Pass);
+        let r = registry.run("echo", Value::Null, &ctx).await;
+        assert_eq!(r.status, Status::Error);
+        let r = registry.run("elsewhere", Value::Null, &ctx).await;
+        assert_eq!(r.status, Status::Skip);
+        assert_eq!(
+            r.error.unwrap().message,
+            "elsewhere probe runs only on plan9"
+        );
+
+        let r = registry.run("nope", Value::Null, &ctx).await;
+        let message = r.error.unwrap().message;
+        assert!(message.starts_with("unknown probe: nope (available: autostart, clipboard,"));
+        assert!(message.contains("echo, elsewhere, file-locking, filesystem"));
+        assert_eq!(registry.get("echo").unwrap().args["ok"], "whether to pass");
+
+        let r = registry
+            .run(
+                "clipboard",
+                serde_json::json!({ "selection": "secondary" }),
+                &ctx,
+            )
+            .await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+        let r = registry
+            .run("usb", serde_json::json!({ "expect": [] }), &ctx)
+            .await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn test_offline_skips_network_probes() {
+        let mut ctx = AppContext::default_headless();
+        ctx.offline = true;
+        let registry = ProbeRegistry::new();
+        let network: Vec<&str> = registry
+            .list()
+            .into_iter()
+            .filter(|p| p.needs_network())
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(network, ["interfaces", "llm", "network"]);
+        for name in network {
+            let r = run_probe(name, &ctx).await;
+            assert_eq!(r.status, Status::Skip, "{name}");
+            assert_eq!(r.error.unwrap().message, OFFLINE_REASON);
+        }
+        assert_eq!(run_probe("filesystem", &ctx).await.status, Status::Pass);
+        let r = crate::updates::run_check(serde_json::json!({}), &ctx).await;
+        assert_eq!(r.status, Status::Skip);
+    }
+}
@@ -0,0 +1,383 @@
+//! Network probes: endpoint DNS and HTTPS checks, and the host's
+//! interfaces, route, DNS, and captive portal.
+
+use super::{parse_args, ProbeInfo, ProbeInput, ProbeRegistry};
+use crate::context::AppContext;
+use crate::endpoints::NetworkProbeConfig;
+use crate::types::*;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new(
+            "network",
+            "DNS and HTTPS per configured endpoint, optionally latency and bandwidth",
+        )
+        .capabilities(&["network"])
+        .arg(
+            "endpoints",
+            "endpoints to check instead of the configured ones",
+        )
+        .arg("throughput", "latency/bandwidth settings and budgets"),
+        |p| {
+            Box::pin(async move {
+                let config = match network_config(&p) {
+                    Ok(config) => config,
+                    Err(e) => return p.invalid_args("network", e),
+                };
+                probe_network(p.ctx, p.run_id, &config).await
+            })
+        },
+    );
+    reg.register(
+        ProbeInfo::new(
+            "interfaces",
+            "Interfaces, default route, DNS, and captive portal detection",
+        )
+        .capabilities(&["net_info", "network"]),
+        |p| Box::pin(probe_interfaces(p.ctx, p.run_id)),
+    );
+}
+
+/// The context's network probe settings with `args` fields laid over them.
+fn network_config(p: &ProbeInput<'_, '_>) -> Result<NetworkProbeConfig, String> {
+    let mut config = serde_json::to_value(&p.ctx.network_probe).unwrap_or_default();
+    match (p.args, &mut config) {
+        (Value::Null, _) => {}
+        (Value::Object(args), Value::Object(config)) => {
+            config.extend(args.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        _ => return Err("expected an object".into()),
+    }
+    parse_args(&config).map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Network probe
+// ---------------------------------------------------------------------------
+
+async fn probe_network(
+    ctx: &AppContext,
+    run_id: &str,
+    config: &NetworkProbeConfig,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+    let mut results = Vec::new();
+    for (i, endpoint) in config.endpoints.iter().enumerate() {
+        let r = crate::endpoints::check_endpoint(ctx, endpoint).await;
+        steps.insert(format!("endpoint[{}].dns_resolve", i), r.dns_ms);
+        if let Some(latency) = r.latency_ms {
+            steps.insert(format!("endpoint[{}].https_get", i), latency);
+        }
+        results.push(r);
+    }
+    let throughput = match (&config.throughput, config.endpoints.first()) {
+        (Some(throughput), Some(first)) => {
+            let t = ctx.stopwatch();
+            let r = crate::endpoints::measure_throughput(ctx, throughput, &first.url).await;
+            steps.insert("throughput".into(), t.elapsed_ms());
+            Some(r)
+        }
+        _ => None,
+    };
+
+    let elapsed = start.elapsed_ms();
+    let failed: Vec<_> = results.iter().filter(|r| !r.ok).collect();
+    let mut failures = Vec::new();
+    if !failed.is_empty() {
+        let summary: Vec<String> = failed
+            .iter()
+            .map(|r| format!("{} ({})", r.url, r.problems.join("; ")))
+            .collect();
+        failures.push(format!(
+            "{}/{} endpoints failed: {}",
+            failed.len(),
+            results.len(),
+            summary.join(", ")
+        ));
+    }
+    if let Some(t) = throughput.as_ref().filter(|t| !t.problems.is_empty()) {
+        failures.push(format!("throughput: {}", t.problems.join("; ")));
+    }
+    let trust_store = ctx.network().trust_store();
+    let rejected_cert = failed
+        .iter()
+        .flat_map(|r| &r.problems)
+        .any(|p| p.contains("certificate") || p.contains("UnknownIssuer"));
+    if let Some(e) = &trust_store.error {
+        failures.push(format!("TLS settings unusable: {}", e));
+    } else if rejected_cert && trust_store.ca_bundle.is_none() {
+        failures.push(format!(
+            "server certificate rejected by the system trust store; behind a private CA, set ${} or tls.ca_bundle",
+            crate::tls::CA_BUNDLE_ENV
+        ));
+    }
+    let mut r = if results.is_empty() {
+        result_err(
+            "probe",
+            "network",
+            run_id,
+            elapsed,
+            ErrorCode::InvalidInput,
+            "no network probe endpoints configured",
+        )
+    } else if failures.is_empty() {
+        result_ok("probe", "network", run_id, elapsed)
+    } else {
+        let code = if !failed.is_empty() && failed.iter().all(|r| r.timed_out) {
+            ErrorCode::Timeout
+        } else {
+            ErrorCode::NetworkError
+        };
+        result_err(
+            "probe",
+            "network",
+            run_id,
+            elapsed,
+            code,
+            failures.join("; "),
+        )
+    };
+    r.timing_ms.steps = steps;
+    r.data = Some(serde_json::json!({
+        "endpoints": results,
+        "throughput": throughput,
+        "trust_store": trust_store,
+        "proxy_env": collect_proxy_env(),
+    }));
+    r
+}
+
+fn collect_proxy_env() -> HashMap<String, String> {
+    let keys = [
+        "HTTP_PROXY",
+        "http_proxy",
+        "HTTPS_PROXY",
+        "https_proxy",
+        "NO_PROXY",
+        "no_proxy",
+    ];
+    let mut out = HashMap::new();
+    for k in keys {
+        if let Ok(v) = std::env::var(k) {
+            out.insert(k.to_string(), v);
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Interfaces probe
+// ---------------------------------------------------------------------------
+
+/// List interfaces, the default route, and per-interface DNS, then tell
+/// "no network" apart from "captive portal" with a 204 check.
+async fn probe_interfaces(ctx: &AppContext, run_id: &str) -> CommandResult {
+    use crate::interfaces::ConnectivityState;
+
+    let start = ctx.stopwatch();
+    let snapshot = ctx.net_info().snapshot();
+    let t_snapshot = start.elapsed_ms();
+    let t0 = ctx.stopwatch();
+    let connectivity = crate::interfaces::check_connectivity(ctx).await;
+    let t_connectivity = t0.elapsed_ms();
+    let elapsed = start.elapsed_ms();
+
+    let (snapshot, snapshot_error) = match snapshot {
+        Ok(s) => (Some(s), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    let default_route = snapshot.as_ref().and_then(|s| s.default_route.clone());
+    let mut r = match connectivity.state {
+        ConnectivityState::Online => result_ok("probe", "interfaces", run_id, elapsed),
+        ConnectivityState::CaptivePortal => result_err(
+            "probe",
+            "interfaces",
+            run_id,
+            elapsed,
+            ErrorCode::ExternalInterference,
+            format!(
+                "captive portal: {} answered HTTP {} instead of 204{}",
+                connectivity.url,
+                connectivity.http_status.unwrap_or_default(),
+                connectivity
+                    .location
+                    .as_deref()
+                    .map(|l| format!(" (sign in at {})", l))
+                    .unwrap_or_default()
+            ),
+        ),
+        ConnectivityState::Offline => result_err(
+            "probe",
+            "interfaces",
+            run_id,
+            elapsed,
+            ErrorCode::NetworkError,
+            match (&snapshot, &default_route) {
+                (Some(_), None) => "no network: no default route".to_string(),
+                _ => format!(
+                    "no network: {}",
+                    connectivity.error.as_deref().unwrap_or("request failed")
+                ),
+            },
+        ),
+    };
+    r.data = Some(serde_json::json!({
+        "interfaces": snapshot.as_ref().map(|s| &s.interfaces),
+        "default_route": default_route,
+        "dns": snapshot.as_ref().map(|s| &s.dns),
+        "dns_differs_per_interface": snapshot.as_ref().map(|s| s.dns_differs_per_interface()),
+        "snapshot_error": snapshot_error,
+        "connectivity": connectivity,
+    }));
+    r.timing_ms.steps.insert("snapshot".into(), t_snapshot);
+    r.timing_ms
+        .steps
+        .insert("connectivity".into(), t_connectivity);
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::{run_probe, ProbeRegistry};
+    use crate::traits::CapResult;
+
+    #[tokio::test]
+    async fn test_network_probe_against_mock() {
+        use crate::endpoints::NetworkEndpoint;
+        use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+        use crate::traits::HttpResponse;
+
+        let network = MockNetwork::new().respond(
+            "https://example.test/",
+            HttpResponse {
+                status: 204,
+                ..Default::default()
+            },
+        );
+        let mut ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(network),
+            Box::new(HeadlessClipboard),
+        );
+        ctx.network_probe.endpoints = vec![NetworkEndpoint::new("https://example.test/get")];
+        let r = run_probe("network", &ctx).await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.data.unwrap()["endpoints"][0]["http_status"], 204);
+
+        // Args replace the configured endpoints for one run.
+        let args = serde_json::json!({ "endpoints": [{ "url": "https://unrouted.test/" }] });
+        let r = ProbeRegistry::new().run("network", args, &ctx).await;
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(r.data.unwrap()["endpoints"].as_array().unwrap().len(), 1);
+
+        ctx.network_probe
+            .endpoints
+            .push(NetworkEndpoint::new("https://unrouted.test/"));
+        ctx.network_probe.endpoints.push(NetworkEndpoint {
+            expect_status: Some(200),
+            require_header: Some("X-Allowed".into()),
+            ..NetworkEndpoint::new("https://example.test/health")
+        });
+        let r = run_probe("network", &ctx).await;
+        assert_eq!(r.status, Status::Error);
+        let data = r.data.unwrap();
+        assert_eq!(data["endpoints"][0]["ok"], true);
+        assert_eq!(data["endpoints"][1]["ok"], false);
+        assert_eq!(
+            data["endpoints"][2]["problems"],
+            serde_json::json!(["status 204, expected 200", "missing header X-Allowed"])
+        );
+        assert!(r
+            .error
+            .unwrap()
+            .message
+            .starts_with("2/3 endpoints failed: https://unrouted.test/"));
+    }
+
+    #[tokio::test]
+    async fn test_network_probe_hints_at_private_ca() {
+        use crate::endpoints::NetworkEndpoint;
+        use crate::platform::MockNetwork;
+
+        let network = MockNetwork::new().fail(
+            "https://intranet.test/",
+            "Get https://intranet.test/: error sending request: invalid peer certificate: UnknownIssuer",
+        );
+        let mut ctx = AppContext::default_headless().with_network(Box::new(network));
+        ctx.network_probe.endpoints = vec![NetworkEndpoint::new("https://intranet.test/")];
+        let r = run_probe("network", &ctx).await;
+        assert_eq!(r.data.unwrap()["trust_store"]["roots"], "none");
+        let message = r.error.unwrap().message;
+        assert!(
+            message.contains("rejected by the system trust store"),
+            "{message}"
+        );
+        assert!(message.contains(crate::tls::CA_BUNDLE_ENV));
+    }
+
+    /// Wi-Fi up with a default route through 192.168.1.1.
+    struct HotelWifi;
+
+    impl crate::traits::NetInfoOps for HotelWifi {
+        fn snapshot(&self) -> CapResult<crate::traits::NetSnapshot> {
+            Ok(crate::traits::NetSnapshot {
+                default_route: Some(crate::traits::DefaultRoute {
+                    gateway: "192.168.1.1".into(),
+                    interface: "wlan0".into(),
+                }),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interfaces_probe_detects_captive_portal() {
+        use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+        use crate::traits::HttpResponse;
+
+        let network = MockNetwork::new()
+            .respond(
+                "http://check.test/204",
+                HttpResponse {
+                    status: 204,
+                    ..Default::default()
+                },
+            )
+            .respond(
+                "http://check.test/portal",
+                HttpResponse {
+                    status: 302,
+                    headers: vec![("Location".into(), "http://login.hotel.test/".into())],
+                    body: Vec::new(),
+                },
+            );
+        let mut ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(network),
+            Box::new(HeadlessClipboard),
+        )
+        .with_net_info(Box::new(HotelWifi));
+
+        ctx.captive_portal_url = "http://check.test/204".into();
+        let r = probe_interfaces(&ctx, "run").await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.data.unwrap()["default_route"]["gateway"], "192.168.1.1");
+
+        ctx.captive_portal_url = "http://check.test/portal".into();
+        let r = probe_interfaces(&ctx, "run").await;
+        let err = r.error.unwrap();
+        assert_eq!(err.code, ErrorCode::ExternalInterference);
+        assert!(err
+            .message
+            .ends_with("(sign in at http://login.hotel.test/)"));
+
+        ctx.captive_portal_url = "http://unrouted.test/".into();
+        let r = probe_interfaces(&ctx, "run").await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::NetworkError);
+        assert_eq!(r.data.unwrap()["connectivity"]["state"], "offline");
+    }
+}
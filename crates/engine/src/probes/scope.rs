@@ -0,0 +1,130 @@
+//! [`ProbeScope`]: cleanups a probe defers, run and recorded when it ends.
+
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::*;
+
+type Cleanup<'a> = Box<dyn FnOnce() -> Result<(), CapError> + Send + 'a>;
+
+/// Cleanup actions for one probe run.
+///
+/// Probes that leave something behind (temp dirs, clipboard contents, login
+/// entries) [`defer`](Self::defer) its removal as soon as it exists, then
+/// return from wherever they fail. [`finish`](Self::finish) runs the actions
+/// newest first and records each as a `cleanup.<name>` step plus a
+/// `data.cleanup.<name>` status; a failed cleanup turns a pass into an error.
+/// Actions still pending when the scope is dropped (a panicking probe) run
+/// then, unrecorded.
+pub struct ProbeScope<'a> {
+    ctx: &'a AppContext,
+    cleanups: Vec<(String, Cleanup<'a>)>,
+}
+
+impl<'a> ProbeScope<'a> {
+    pub fn new(ctx: &'a AppContext) -> Self {
+        Self {
+            ctx,
+            cleanups: Vec::new(),
+        }
+    }
+
+    /// Register `action` to run when the probe ends, however it ends.
+    pub fn defer(
+        &mut self,
+        name: impl Into<String>,
+        action: impl FnOnce() -> Result<(), CapError> + Send + 'a,
+    ) {
+        self.cleanups.push((name.into(), Box::new(action)));
+    }
+
+    /// Run the pending cleanups and fold their outcome into `result`.
+    pub fn finish(mut self, mut result: CommandResult) -> CommandResult {
+        if self.cleanups.is_empty() {
+            return result;
+        }
+        let mut statuses = serde_json::Map::new();
+        let mut failures = Vec::new();
+        while let Some((name, action)) = self.cleanups.pop() {
+            let t = self.ctx.stopwatch();
+            let outcome = action();
+            let spent = t.elapsed_ms();
+            result.timing_ms.total += spent;
+            result
+                .timing_ms
+                .steps
+                .insert(format!("cleanup.{}", name), spent);
+            let status = match outcome {
+                Ok(()) => "ok".to_string(),
+                Err(e) => {
+                    let message = format!("cleanup {} failed: {}", name, e);
+                    failures.push((e.error_code(), message.clone()));
+                    message
+                }
+            };
+            statuses.insert(name, status.into());
+        }
+
+        match result.data.get_or_insert_with(|| serde_json::json!({})) {
+            serde_json::Value::Object(data) => {
+                data.insert("cleanup".into(), statuses.into());
+            }
+            _ => tracing::debug!("probe data is not an object; cleanup statuses dropped"),
+        }
+        if let (Status::Pass, Some((code, message))) = (&result.status, failures.first()) {
+            result.status = Status::Error;
+            result.error = Some(ErrorInfo {
+                code: *code,
+                message: format!("{} probe left state behind: {}", result.target, message),
+                details: serde_json::Value::Null,
+            });
+        }
+        result
+    }
+}
+
+impl Drop for ProbeScope<'_> {
+    fn drop(&mut self) {
+        while let Some((name, action)) = self.cleanups.pop() {
+            if let Err(e) = action() {
+                tracing::warn!("probe cleanup {} failed: {}", name, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_scope_failed_cleanup_fails_the_probe() {
+        let ctx = AppContext::default_headless();
+        let ran = std::sync::Mutex::new(Vec::new());
+        let mut scope = ProbeScope::new(&ctx);
+        scope.defer("first", || {
+            ran.lock().unwrap().push("first");
+            Ok(())
+        });
+        scope.defer("second", || {
+            ran.lock().unwrap().push("second");
+            Err(CapError::Other("still there".into()))
+        });
+        let r = scope.finish(result_ok("probe", "demo", "run", 0));
+        assert_eq!(*ran.lock().unwrap(), ["second", "first"]);
+        assert_eq!(r.status, Status::Error);
+        assert_eq!(
+            r.error.unwrap().message,
+            "demo probe left state behind: cleanup second failed: still there"
+        );
+        assert_eq!(r.data.unwrap()["cleanup"]["first"], "ok");
+
+        let dropped = std::sync::atomic::AtomicBool::new(false);
+        let mut scope = ProbeScope::new(&ctx);
+        scope.defer("on_drop", || {
+            dropped.store(true, std::sync::atomic::Ordering::Relaxed);
+            Ok(())
+        });
+        drop(scope);
+        assert!(dropped.into_inner());
+    }
+}
@@ -0,0 +1,367 @@
+//! Secret store probes: credentials round trip and keychain diagnosis.
+
+use super::{ProbeInfo, ProbeRegistry, ProbeScope};
+use crate::context::AppContext;
+use crate::traits::CapError;
+use crate::types::*;
+use std::collections::HashMap;
+use std::future::ready;
+
+pub(super) fn register(reg: &mut ProbeRegistry) {
+    reg.register(
+        ProbeInfo::new(
+            "credentials",
+            "Store, read back, and delete a secret under the app's service name",
+        )
+        .capabilities(&["secrets"]),
+        |p| {
+            Box::pin(ready(probe_credentials(
+                p.ctx,
+                p.run_id,
+                p.scope,
+                detect_headless(),
+            )))
+        },
+    );
+    reg.register(
+        ProbeInfo::new(
+            "keychain",
+            "Check the secret store is served and unlocked; write, read, and delete a dummy secret",
+        )
+        .capabilities(&["secrets"]),
+        |p| Box::pin(ready(probe_keychain(p.ctx, p.run_id, p.scope))),
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Credentials probe
+// ---------------------------------------------------------------------------
+
+/// Store a random secret under the app's service name, read it back, and
+/// delete it again (also on failure). Each store call is a separate
+/// `security` / `secret-tool` / PowerShell process, so a matching read-back
+/// means another process – the GUI – sees what appctl provisions. Skips
+/// where there is no store, and on headless machines without one.
+fn probe_credentials<'a>(
+    ctx: &'a AppContext,
+    run_id: &str,
+    scope: &mut ProbeScope<'a>,
+    headless: bool,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+    let account = format!("probe/appctl-{:08x}", ctx.random_u64() as u32);
+    let secret = format!("{:016x}", ctx.random_u64());
+
+    let t0 = ctx.stopwatch();
+    let stored = ctx.secrets().set(&ctx.app_id, &account, &secret);
+    steps.insert("set".into(), t0.elapsed_ms());
+    if stored.is_ok() {
+        let account = account.clone();
+        scope.defer("delete", move || {
+            match ctx.secrets().delete(&ctx.app_id, &account)? {
+                true => Ok(()),
+                false => Err(CapError::Other(format!(
+                    "{} vanished before delete",
+                    account
+                ))),
+            }
+        });
+    }
+    let outcome = stored.and_then(|_| {
+        let t1 = ctx.stopwatch();
+        let read = ctx.secrets().get(&ctx.app_id, &account);
+        steps.insert("get".into(), t1.elapsed_ms());
+        read
+    });
+
+    let elapsed = start.elapsed_ms();
+    let mut r = match outcome {
+        Err(CapError::Unsupported(m)) => result_skip("probe", "credentials", run_id, elapsed, m),
+        Err(CapError::DependencyMissing(m)) if headless => {
+            result_skip("probe", "credentials", run_id, elapsed, m)
+        }
+        Err(e) => result_err(
+            "probe",
+            "credentials",
+            run_id,
+            elapsed,
+            e.error_code(),
+            format!("credentials probe failed for {}: {}", ctx.app_id, e),
+        ),
+        Ok(read) if read.as_deref() != Some(secret.as_str()) => result_err(
+            "probe",
+            "credentials",
+            run_id,
+            elapsed,
+            ErrorCode::ExternalInterference,
+            format!(
+                "{} returned {} for {} right after storing it",
+                ctx.secrets().backend(),
+                if read.is_some() {
+                    "a different secret"
+                } else {
+                    "nothing"
+                },
+                account
+            ),
+        ),
+        Ok(_) => result_ok("probe", "credentials", run_id, elapsed),
+    };
+    r.data = Some(serde_json::json!({
+        "backend": ctx.secrets().backend(),
+        "service": ctx.app_id,
+        "account": account,
+    }));
+    r.timing_ms.steps = steps;
+    r
+}
+
+// ---------------------------------------------------------------------------
+// Keychain probe
+// ---------------------------------------------------------------------------
+
+/// Check the secret store stack itself: who serves it and whether its
+/// default store is locked, then write, read, and delete a dummy secret
+/// under a service of its own. A missing Secret Service provider, a
+/// locked keyring, or no login keychain fails with a hint in
+/// `data.hints`; only platforms without a store skip.
+fn probe_keychain<'a>(
+    ctx: &'a AppContext,
+    run_id: &str,
+    scope: &mut ProbeScope<'a>,
+) -> CommandResult {
+    let start = ctx.stopwatch();
+    let mut steps = HashMap::new();
+    let store = ctx.secrets();
+    let service = format!("{}.keychain-probe", ctx.app_id);
+    let account = format!("dummy-{:08x}", ctx.random_u64() as u32);
+    let secret = format!("{:016x}", ctx.random_u64());
+    let mut hints: Vec<String> = Vec::new();
+
+    let t0 = ctx.stopwatch();
+    let status = match store.status() {
+        Ok(status) => Some(status),
+        Err(CapError::Unsupported(_)) => None,
+        Err(e) => {
+            let mut r = result_err(
+                "probe",
+                "keychain",
+                run_id,
+                start.elapsed_ms(),
+                e.error_code(),
+                format!("keychain probe failed: {}", e),
+            );
+            r.timing_ms.steps.insert("status".into(), t0.elapsed_ms());
+            r.data = Some(serde_json::json!({ "backend": store.backend() }));
+            return r;
+        }
+    };
+    steps.insert("status".into(), t0.elapsed_ms());
+    let locked = status.as_ref().and_then(|s| s.locked) == Some(true);
+    if locked {
+        hints.push(format!(
+            "the default store{} is locked; unlock it (log in graphically, or \
+             `gnome-keyring-daemon --unlock` in headless sessions) before the app starts",
+            status
+                .as_ref()
+                .and_then(|s| s.default_store.as_deref())
+                .map(|name| format!(" '{}'", name))
+                .unwrap_or_default()
+        ));
+    }
+    if status
+        .as_ref()
+        .is_some_and(|s| s.provider.is_some() && s.default_store.is_none())
+    {
+        hints.push(
+            "no default collection yet: the first write prompts to create one, \
+             which fails without a desktop session"
+                .into(),
+        );
+    }
+
+    let t1 = ctx.stopwatch();
+    let stored = store.set(&service, &account, &secret);
+    steps.insert("set".into(), t1.elapsed_ms());
+    if stored.is_ok() {
+        let (service, account) = (service.clone(), account.clone());
+        scope.defer("delete", move || {
+            ctx.secrets().delete(&service, &account).map(|_| ())
+        });
+    }
+    let outcome = stored.and_then(|_| {
+        let t2 = ctx.stopwatch();
+        let read = store.get(&service, &account);
+        steps.insert("get".into(), t2.elapsed_ms());
+        let t3 = ctx.stopwatch();
+        let deleted = store.delete(&service, &account);
+        steps.insert("delete".into(), t3.elapsed_ms());
+        let gone = store.get(&service, &account);
+        Ok((read?, deleted?, gone?))
+    });
+    if let Err(CapError::DependencyMissing(m)) = &outcome {
+        if m.contains("secret-tool") {
+            hints.push("install secret-tool (libsecret-tools / libsecret)".into());
+        }
+    }
+
+    let elapsed = start.elapsed_ms();
+    let mut r = match outcome {
+        Err(CapError::Unsupported(m)) => result_skip("probe", "keychain", run_id, elapsed, m),
+        Err(e) => result_err(
+            "probe",
+            "keychain",
+            run_id,
+            elapsed,
+            if locked {
+                ErrorCode::PermissionDenied
+            } else {
+                e.error_code()
+            },
+            format!("keychain probe failed: {}", e),
+        ),
+        Ok((read, _, _)) if read.as_deref() != Some(secret.as_str()) => result_err(
+            "probe",
+            "keychain",
+            run_id,
+            elapsed,
+            ErrorCode::ExternalInterference,
+            format!(
+                "{} returned {} right after storing a secret",
+                store.backend(),
+                if read.is_some() {
+                    "a different secret"
+                } else {
+                    "nothing"
+                }
+            ),
+        ),
+        Ok((_, deleted, gone)) if !deleted || gone.is_some() => result_err(
+            "probe",
+            "keychain",
+            run_id,
+            elapsed,
+            ErrorCode::ExternalInterference,
+            format!("{} did not delete the dummy secret", store.backend()),
+        ),
+        Ok(_) => result_ok("probe", "keychain", run_id, elapsed),
+    };
+    r.timing_ms.steps = steps;
+    r.data = Some(serde_json::json!({
+        "backend": store.backend(),
+        "provider": status.as_ref().and_then(|s| s.provider.clone()),
+        "default_store": status.as_ref().and_then(|s| s.default_store.clone()),
+        "locked": status.as_ref().and_then(|s| s.locked),
+        "service": service,
+        "hints": hints,
+    }));
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::probes::run_probe;
+    use crate::traits::CapResult;
+
+    #[tokio::test]
+    async fn test_credentials_probe_round_trips_and_cleans_up() {
+        use crate::credentials::MemorySecrets;
+        use crate::traits::SecretStoreOps;
+
+        let store = std::sync::Arc::new(MemorySecrets::default());
+        struct Shared(std::sync::Arc<MemorySecrets>);
+        impl SecretStoreOps for Shared {
+            fn backend(&self) -> &'static str {
+                self.0.backend()
+            }
+            fn get(&self, service: &str, account: &str) -> CapResult<Option<String>> {
+                self.0.get(service, account)
+            }
+            fn set(&self, service: &str, account: &str, secret: &str) -> CapResult<()> {
+                self.0.set(service, account, secret)
+            }
+            fn delete(&self, service: &str, account: &str) -> CapResult<bool> {
+                self.0.delete(service, account)
+            }
+        }
+        let ctx = AppContext::default_headless().with_secrets(Box::new(Shared(store.clone())));
+        let r = run_probe("credentials", &ctx).await;
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        let data = r.data.unwrap();
+        assert_eq!(data["service"], ctx.app_id.as_str());
+        let account = data["account"].as_str().unwrap();
+        assert!(account.starts_with("probe/"));
+        assert_eq!(data["cleanup"]["delete"], "ok");
+        assert_eq!(store.get(&ctx.app_id, account).unwrap(), None);
+    }
+
+    /// A keyring whose default collection is locked, or that nothing
+    /// serves when `missing`.
+    struct LockedKeyring {
+        missing: bool,
+    }
+
+    impl crate::traits::SecretStoreOps for LockedKeyring {
+        fn backend(&self) -> &'static str {
+            "secret-service"
+        }
+        fn get(&self, _: &str, _: &str) -> CapResult<Option<String>> {
+            Ok(None)
+        }
+        fn set(&self, _: &str, _: &str, _: &str) -> CapResult<()> {
+            Err(CapError::Other(
+                "Cannot create an item in a locked collection".into(),
+            ))
+        }
+        fn delete(&self, _: &str, _: &str) -> CapResult<bool> {
+            Ok(false)
+        }
+        fn status(&self) -> CapResult<crate::traits::SecretStoreStatus> {
+            if self.missing {
+                return Err(CapError::DependencyMissing("no Secret Service".into()));
+            }
+            Ok(crate::traits::SecretStoreStatus {
+                provider: Some("gnome-keyring-d".into()),
+                default_store: Some("Login".into()),
+                locked: Some(true),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keychain_probe_diagnoses_the_store() {
+        let ctx = AppContext::default_headless()
+            .with_secrets(Box::new(crate::credentials::MemorySecrets::default()));
+        let r = run_probe("keychain", &ctx).await;
+        assert_eq!(r.status, Status::Pass, "{:?}", r.error);
+        assert!(r.timing_ms.steps.contains_key("delete"));
+
+        let ctx = ctx.with_secrets(Box::new(LockedKeyring { missing: false }));
+        let r = run_probe("keychain", &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::PermissionDenied);
+        let data = r.data.unwrap();
+        assert_eq!(data["locked"], true);
+        assert!(data["hints"][0]
+            .as_str()
+            .unwrap()
+            .contains("'Login' is locked"));
+
+        let ctx = ctx.with_secrets(Box::new(LockedKeyring { missing: true }));
+        let r = run_probe("keychain", &ctx).await;
+        assert_eq!(r.error.unwrap().code, ErrorCode::DependencyMissing);
+        assert!(!r.timing_ms.steps.contains_key("set"));
+    }
+
+    #[tokio::test]
+    async fn test_credentials_probe_names_the_service_on_failure() {
+        let mut ctx =
+            AppContext::default_headless().with_secrets(Box::new(LockedKeyring { missing: false }));
+        ctx.app_id = "com.example.other".into();
+        let r = run_probe("credentials", &ctx).await;
+        assert_eq!(r.status, Status::Error);
+        assert!(r.error.unwrap().message.contains("com.example.other"));
+        assert_eq!(r.data.unwrap()["service"], "com.example.other");
+    }
+}
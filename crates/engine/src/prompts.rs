@@ -0,0 +1,361 @@
+//! Prompt templates – versioned text files with `{{ variable }}` interpolation.
+//!
+//! Layout under [`AppContext::prompts_dir`]:
+//!
+//! ```text
+//! prompts/
+//!   summarize/
+//!     v1.md
+//!     v2.md      <- latest, used when no version is requested
+//!   greeting/
+//!     v1.txt
+//! ```
+//!
+//! Files are read through [`FilesystemOps`], so tests and headless runs can
+//! point the context at any directory.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{CapError, FilesystemOps};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the default prompts directory.
+pub const PROMPTS_DIR_ENV: &str = "APP__PROMPTS_DIR";
+
+const EXTENSIONS: [&str; 2] = ["md", "txt"];
+
+/// `$APP__PROMPTS_DIR`, else `./prompts`. The Tauri wrapper replaces this
+/// with `<app config dir>/prompts`.
+pub fn default_dir() -> PathBuf {
+    std::env::var(PROMPTS_DIR_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("prompts"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PromptInfo {
+    pub name: String,
+    /// Ascending.
+    pub versions: Vec<u32>,
+    pub latest: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenderedPrompt {
+    pub name: String,
+    pub version: u32,
+    pub text: String,
+    /// Variables referenced by the template, in order of first use.
+    pub variables: Vec<String>,
+}
+
+fn fs_err(e: CapError) -> CommandError {
+    match e {
+        CapError::PermissionDenied(m) => CommandError::PermissionDenied(m),
+        CapError::Io(io) => CommandError::Io(io),
+        other => CommandError::Other(other.to_string()),
+    }
+}
+
+/// Parse `v<N>.<ext>` into `N`.
+fn parse_version(file_name: &str) -> Option<u32> {
+    let (stem, ext) = file_name.rsplit_once('.')?;
+    if !EXTENSIONS.contains(&ext) {
+        return None;
+    }
+    stem.strip_prefix('v')?.parse().ok()
+}
+
+fn validate_name(name: &str) -> Result<(), CommandError> {
+    let ok = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if ok {
+        Ok(())
+    } else {
+        Err(CommandError::InvalidInput(format!(
+            "invalid prompt name '{}' (use letters, digits, '-' and '_')",
+            name
+        )))
+    }
+}
+
+/// Versions available for one template, keyed to their file paths.
+fn versions(fs: &dyn FilesystemOps, dir: &Path) -> Result<Vec<(u32, PathBuf)>, CommandError> {
+    let mut found: Vec<(u32, PathBuf)> = fs
+        .list_dir(dir)
+        .map_err(fs_err)?
+        .into_iter()
+        .filter(|e| !e.is_dir)
+        .filter_map(|e| parse_version(&e.name).map(|v| (v, dir.join(&e.name))))
+        .collect();
+    found.sort_by_key(|(v, _)| *v);
+    Ok(found)
+}
+
+/// List every template with at least one version. A missing prompts
+/// directory is treated as empty.
+pub fn list(fs: &dyn FilesystemOps, root: &Path) -> Result<Vec<PromptInfo>, CommandError> {
+    if !fs.exists(root) {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    for entry in fs.list_dir(root).map_err(fs_err)? {
+        if !entry.is_dir || validate_name(&entry.name).is_err() {
+            continue;
+        }
+        let versions: Vec<u32> = versions(fs, &root.join(&entry.name))?
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect();
+        if let Some(&latest) = versions.last() {
+            out.push(PromptInfo {
+                name: entry.name,
+                versions,
+                latest,
+            });
+        }
+    }
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(out)
+}
+
+/// Load a template's source, defaulting to the latest version.
+pub fn load(
+    fs: &dyn FilesystemOps,
+    root: &Path,
+    name: &str,
+    version: Option<u32>,
+) -> Result<(u32, String), CommandError> {
+    validate_name(name)?;
+    let dir = root.join(name);
+    if !fs.exists(&dir) {
+        return Err(CommandError::InvalidInput(format!(
+            "unknown prompt: {} (looked in {})",
+            name,
+            root.display()
+        )));
+    }
+    let available = versions(fs, &dir)?;
+    let (v, path) = match version {
+        Some(want) => available.into_iter().find(|(v, _)| *v == want),
+        None => available.into_iter().last(),
+    }
+    .ok_or_else(|| match version {
+        Some(v) => CommandError::InvalidInput(format!("prompt {} has no version {}", name, v)),
+        None => CommandError::InvalidInput(format!("prompt {} has no versions", name)),
+    })?;
+    let bytes = fs.read_file(&path).map_err(fs_err)?;
+    Ok((v, String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// Split a template into literal text and `{{ name }}` placeholders.
+enum Piece<'a> {
+    Text(&'a str),
+    Var(&'a str),
+}
+
+fn pieces(template: &str) -> Result<Vec<Piece<'_>>, CommandError> {
+    let mut out = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push(Piece::Text(&rest[..open]));
+        let after = &rest[open + 2..];
+        let close = after
+            .find("}}")
+            .ok_or_else(|| CommandError::InvalidInput("unclosed '{{' in template".into()))?;
+        let var = after[..close].trim();
+        if var.is_empty() {
+            return Err(CommandError::InvalidInput(
+                "empty '{{ }}' in template".into(),
+            ));
+        }
+        out.push(Piece::Var(var));
+        rest = &after[close + 2..];
+    }
+    out.push(Piece::Text(rest));
+    Ok(out)
+}
+
+/// Variables referenced by `template`, in order of first use.
+pub fn variables(template: &str) -> Result<Vec<String>, CommandError> {
+    let mut seen: Vec<String> = Vec::new();
+    for piece in pieces(template)? {
+        if let Piece::Var(v) = piece {
+            if !seen.iter().any(|s| s == v) {
+                seen.push(v.to_string());
+            }
+        }
+    }
+    Ok(seen)
+}
+
+/// Substitute `vars` into `template`. Strings are inserted verbatim, other
+/// JSON values in their compact form. Every referenced variable must be
+/// supplied; extra variables are ignored.
+pub fn render_template(template: &str, vars: &Map<String, Value>) -> Result<String, CommandError> {
+    let pieces = pieces(template)?;
+    let missing: Vec<&str> = pieces
+        .iter()
+        .filter_map(|p| match p {
+            Piece::Var(v) if !vars.contains_key(*v) => Some(*v),
+            _ => None,
+        })
+        .collect();
+    if !missing.is_empty() {
+        return Err(CommandError::InvalidInput(format!(
+            "missing template variables: {}",
+            missing.join(", ")
+        )));
+    }
+    let mut out = String::with_capacity(template.len());
+    for piece in pieces {
+        match piece {
+            Piece::Text(t) => out.push_str(t),
+            Piece::Var(v) => match &vars[v] {
+                Value::String(s) => out.push_str(s),
+                other => out.push_str(&other.to_string()),
+            },
+        }
+    }
+    Ok(out)
+}
+
+/// Load and render a template from the context's prompts directory.
+pub fn render(
+    ctx: &AppContext,
+    name: &str,
+    version: Option<u32>,
+    vars: &Map<String, Value>,
+) -> Result<RenderedPrompt, CommandError> {
+    let (version, source) = load(ctx.fs(), &ctx.prompts_dir, name, version)?;
+    Ok(RenderedPrompt {
+        name: name.to_string(),
+        version,
+        variables: variables(&source)?,
+        text: render_template(&source, vars)?,
+    })
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+/// `prompt_list` – list templates and their versions.
+///
+/// Args: `{}` (none required)
+/// Returns: `{ "dir": "...", "prompts": [{ "name": "summarize", "versions": [1, 2], "latest": 2 }] }`
+pub(crate) fn cmd_prompt_list(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let prompts = list(ctx.fs(), &ctx.prompts_dir)?;
+    Ok(serde_json::json!({
+        "dir": ctx.prompts_dir.display().to_string(),
+        "prompts": prompts,
+    }))
+}
+
+/// `prompt_render` – render a template with variables.
+///
+/// Args: `{ "name": "summarize", "version"?: 2, "vars"?: { "text": "..." } }`
+/// Returns: `{ "name": "summarize", "version": 2, "text": "...", "variables": ["text"] }`
+pub(crate) fn cmd_prompt_render(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| CommandError::InvalidInput("missing 'name' string field".into()))?;
+    let version = match args.get("version") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(
+            v.as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(|| CommandError::InvalidInput("'version' must be a number".into()))?,
+        ),
+    };
+    let vars = match args.get("vars") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(m)) => m.clone(),
+        Some(_) => {
+            return Err(CommandError::InvalidInput(
+                "'vars' must be an object".into(),
+            ))
+        }
+    };
+    let rendered = render(ctx, name, version, &vars)?;
+    serde_json::to_value(rendered).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::types::{ErrorCode, Status};
+
+    fn ctx_with_prompts() -> (tempfile::TempDir, AppContext) {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("greeting");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("v1.md"), "Hi {{name}}").unwrap();
+        std::fs::write(dir.join("v2.md"), "Hello {{ name }}, you are {{age}}.").unwrap();
+        std::fs::write(dir.join("notes.md"), "ignored").unwrap();
+        let mut ctx = AppContext::default_headless();
+        ctx.prompts_dir = tmp.path().to_path_buf();
+        (tmp, ctx)
+    }
+
+    #[test]
+    fn test_render_template() {
+        let vars = serde_json::json!({ "name": "Ada", "n": 3 });
+        let out = render_template("{{name}} x{{ n }}", vars.as_object().unwrap()).unwrap();
+        assert_eq!(out, "Ada x3");
+
+        let err = render_template("{{a}} {{b}}", &Map::new()).unwrap_err();
+        assert!(err.to_string().contains("a, b"));
+        assert!(render_template("{{oops", &Map::new()).is_err());
+    }
+
+    #[test]
+    fn test_list_and_render_versions() {
+        let (_tmp, ctx) = ctx_with_prompts();
+        let reg = CommandRegistry::new();
+
+        let listed = reg.execute("prompt_list", serde_json::json!({}), &ctx);
+        assert_eq!(listed.status, Status::Pass);
+        let prompts = &listed.data.unwrap()["prompts"];
+        assert_eq!(prompts[0]["name"], "greeting");
+        assert_eq!(prompts[0]["versions"], serde_json::json!([1, 2]));
+
+        let latest = reg.execute(
+            "prompt_render",
+            serde_json::json!({ "name": "greeting", "vars": { "name": "Ada", "age": 36 } }),
+            &ctx,
+        );
+        let data = latest.data.unwrap();
+        assert_eq!(data["version"], 2);
+        assert_eq!(data["text"], "Hello Ada, you are 36.");
+
+        let v1 = reg.execute(
+            "prompt_render",
+            serde_json::json!({ "name": "greeting", "version": 1, "vars": { "name": "Ada" } }),
+            &ctx,
+        );
+        assert_eq!(v1.data.unwrap()["text"], "Hi Ada");
+
+        let bad = reg.execute(
+            "prompt_render",
+            serde_json::json!({ "name": "../etc" }),
+            &ctx,
+        );
+        assert_eq!(bad.error.unwrap().code, ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn test_list_missing_dir_is_empty() {
+        let mut ctx = AppContext::default_headless();
+        ctx.prompts_dir = PathBuf::from("/nonexistent_prompts_12345");
+        assert!(list(ctx.fs(), &ctx.prompts_dir).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! `find_files`: locate files under a directory by glob pattern and,
+//! optionally, by content, through [`crate::traits::FilesystemOps::walk`] –
+//! so the frontend and scenarios never shell out to `find` or `grep`.
+//!
+//! Patterns use glob syntax (`*`, `?`, `[abc]`, `**`) against the path
+//! relative to the root with `/` separators; a pattern without `/` matches
+//! the file name at any depth, so `*.md` and `**/*.md` are the same.
+//! Content search skips binary files (a NUL byte in the first
+//! [`BINARY_SNIFF_BYTES`]) and files over [`AppContext::max_read_bytes`].
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Results returned when `max_results` is not given.
+pub const DEFAULT_MAX_RESULTS: usize = 1000;
+/// Matching lines reported per file.
+pub const MAX_MATCHES_PER_FILE: usize = 20;
+/// Bytes inspected for NUL when deciding whether a file is binary.
+pub const BINARY_SNIFF_BYTES: usize = 8192;
+/// Longest line text reported for a content match, in characters.
+const MAX_LINE_CHARS: usize = 200;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FindArgs {
+    root: PathBuf,
+    #[serde(default)]
+    pattern: Option<String>,
+    #[serde(default)]
+    contains: Option<String>,
+    #[serde(default)]
+    case_insensitive: bool,
+    #[serde(default)]
+    include_dirs: bool,
+    #[serde(default)]
+    max_depth: Option<usize>,
+    #[serde(default)]
+    max_results: Option<usize>,
+}
+
+/// Whether `bytes` look like a binary file rather than text.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// `relative` as `/`-separated text, for pattern matching and output.
+fn slash_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Lines of `text` containing `needle` (already lowercased when
+/// `case_insensitive`), 1-based.
+fn matching_lines(text: &str, needle: &str, case_insensitive: bool) -> Vec<Value> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            if case_insensitive {
+                line.to_lowercase().contains(needle)
+            } else {
+                line.contains(needle)
+            }
+        })
+        .take(MAX_MATCHES_PER_FILE)
+        .map(|(i, line)| {
+            let text: String = line.trim_end().chars().take(MAX_LINE_CHARS).collect();
+            serde_json::json!({ "line": i + 1, "text": text })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// `find_files` – files under `root` matching a glob and/or containing text.
+///
+/// Args: `{ "root": "/some/dir", "pattern"?: "**/*.md", "contains"?: "TODO",
+/// "case_insensitive"?: false, "include_dirs"?: false, "max_depth"?: null,
+/// "max_results"?: 1000 }`
+/// Returns: `{ "root": "/some/dir", "files": [{"path": "docs/a.md",
+/// "size_bytes": 120, "is_dir": false, "matches": [{"line": 3, "text":
+/// "TODO: ..."}]}], "scanned": 42, "truncated": false, "skipped_binary": 1,
+/// "skipped_large": 0 }`; `matches` is present only with `contains`, and
+/// `truncated` means `max_results` was reached.
+pub fn cmd_find_files(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let args: FindArgs =
+        serde_json::from_value(args).map_err(|e| CommandError::InvalidInput(e.to_string()))?;
+    let pattern = args
+        .pattern
+        .as_deref()
+        .map(glob::Pattern::new)
+        .transpose()
+        .map_err(|e| CommandError::InvalidInput(format!("invalid 'pattern': {}", e)))?;
+    let by_name = args.pattern.as_deref().is_some_and(|p| !p.contains('/'));
+    let options = glob::MatchOptions {
+        case_sensitive: !args.case_insensitive,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let needle = args.contains.as_deref().map(|n| {
+        if args.case_insensitive {
+            n.to_lowercase()
+        } else {
+            n.to_string()
+        }
+    });
+    let max_results = args.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+
+    let mut files = Vec::new();
+    let (mut scanned, mut truncated) = (0u64, false);
+    let (mut skipped_binary, mut skipped_large) = (0u64, 0u64);
+    ctx.fs().walk(&args.root, args.max_depth, &mut |entry| {
+        scanned += 1;
+        if entry.is_dir && !args.include_dirs {
+            return true;
+        }
+        let relative = slash_path(entry.path.strip_prefix(&args.root).unwrap_or(&entry.path));
+        if let Some(pattern) = &pattern {
+            let subject = if by_name {
+                relative.rsplit('/').next().unwrap_or(&relative)
+            } else {
+                relative.as_str()
+            };
+            if !pattern.matches_with(subject, options) {
+                return true;
+            }
+        }
+        let mut found = serde_json::json!({
+            "path": relative,
+            "size_bytes": entry.size_bytes,
+            "is_dir": entry.is_dir,
+        });
+        if let Some(needle) = &needle {
+            if entry.is_dir {
+                return true;
+            }
+            if entry.size_bytes > ctx.max_read_bytes {
+                skipped_large += 1;
+                return true;
+            }
+            let bytes = match ctx.fs().read_file(&entry.path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::debug!("find_files skipping {}: {}", entry.path.display(), e);
+                    return true;
+                }
+            };
+            if is_binary(&bytes) {
+                skipped_binary += 1;
+                return true;
+            }
+            let matches = matching_lines(
+                &String::from_utf8_lossy(&bytes),
+                needle,
+                args.case_insensitive,
+            );
+            if matches.is_empty() {
+                return true;
+            }
+            found["matches"] = matches.into();
+        }
+        if files.len() == max_results {
+            truncated = true;
+            return false;
+        }
+        files.push(found);
+        true
+    })?;
+
+    Ok(serde_json::json!({
+        "root": args.root.display().to_string(),
+        "files": files,
+        "scanned": scanned,
+        "truncated": truncated,
+        "skipped_binary": skipped_binary,
+        "skipped_large": skipped_large,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, bytes).unwrap();
+        };
+        write("README.md", b"intro\nTODO: write docs\n");
+        write("docs/guide.md", b"nothing here\n");
+        write("docs/deep/notes.md", b"todo later\n");
+        write("src/main.rs", b"// TODO: main\nfn main() {}\n");
+        write("assets/logo.png", b"\x89PNG\0\0TODO");
+        dir
+    }
+
+    fn find(dir: &Path, args: Value) -> Value {
+        let mut full = serde_json::json!({ "root": dir });
+        full.as_object_mut()
+            .unwrap()
+            .extend(args.as_object().unwrap().clone());
+        cmd_find_files(full, &AppContext::default_headless()).unwrap()
+    }
+
+    fn paths(out: &Value) -> Vec<&str> {
+        out["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_files_by_glob() {
+        let dir = tree();
+        let out = find(dir.path(), serde_json::json!({ "pattern": "*.md" }));
+        assert_eq!(
+            paths(&out),
+            ["README.md", "docs/deep/notes.md", "docs/guide.md"]
+        );
+        let out = find(dir.path(), serde_json::json!({ "pattern": "docs/*.md" }));
+        assert_eq!(paths(&out), ["docs/guide.md"]);
+        let out = find(
+            dir.path(),
+            serde_json::json!({ "pattern": "*.md", "max_depth": 2, "max_results": 1 }),
+        );
+        assert_eq!(paths(&out), ["README.md"]);
+        assert_eq!(out["truncated"], true);
+        let bad = cmd_find_files(
+            serde_json::json!({ "root": dir.path(), "pattern": "[" }),
+            &AppContext::default_headless(),
+        );
+        assert!(matches!(bad, Err(CommandError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_find_files_by_content_skips_binary() {
+        let dir = tree();
+        let out = find(dir.path(), serde_json::json!({ "contains": "TODO" }));
+        assert_eq!(paths(&out), ["README.md", "src/main.rs"]);
+        assert_eq!(out["files"][0]["matches"][0]["line"], 2);
+        assert_eq!(out["skipped_binary"], 1);
+
+        let out = find(
+            dir.path(),
+            serde_json::json!({ "contains": "todo", "case_insensitive": true, "pattern": "*.md" }),
+        );
+        assert_eq!(paths(&out), ["README.md", "docs/deep/notes.md"]);
+    }
+}
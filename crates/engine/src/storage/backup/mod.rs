@@ -0,0 +1,368 @@
+//! Backups of the app data directory – settings, state files, caches, and
+//! anything else the app keeps there – as one zip with a
+//! `manifest.json` listing every file with its size and SHA-256. The
+//! backend of an "Export my data" button and of restoring it.
+//!
+//! Backups go to `backup.dir` (`<data_dir>/backups` by default, which is
+//! itself left out), named `<app_name>-backup-<YYYYMMDD>-<HHMMSS>.zip`;
+//! after each one only the newest `backup.keep` stay:
+//!
+//! ```yaml
+//! backup:
+//!   dir: /path/to/backups
+//!   keep: 5
+//!   exclude: ["*.sock", "*.lock", "updates/*", "history.jsonl"]
+//! ```
+//!
+//! A restore checks every hash before touching anything, refuses data
+//! from a newer build (see [`crate::migrations`]), saves the current data
+//! as a `pre-restore` backup (which prunes nothing, so the backup being
+//! restored stays), then replaces the data directory's files
+//! with the backup's and runs the migrations the backup predates.
+
+mod restore;
+
+pub use restore::{open, restore};
+
+use crate::commands::{is_dry_run, CommandError};
+use crate::context::AppContext;
+use crate::export::hex;
+use crate::history::format_time;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Bumped when a change to the archive layout would break older builds.
+pub const FORMAT: u32 = 1;
+
+/// The archive entry describing the rest.
+pub const MANIFEST: &str = "manifest.json";
+
+/// Where backups go and how many are kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    /// Backup directory; `<data_dir>/backups` if unset.
+    pub dir: Option<PathBuf>,
+    /// Backups kept in `dir` after a new one is made; 0 keeps all.
+    pub keep: usize,
+    /// Glob patterns, relative to the data directory, of files left out.
+    pub exclude: Vec<String>,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            keep: 5,
+            // Sockets, locks, downloaded updates, and the run history,
+            // which every call appends to.
+            exclude: ["*.sock", "*.lock", "updates/*", "history.jsonl"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupManifest {
+    pub format: u32,
+    pub created_at: String,
+    pub app_id: String,
+    pub app_name: String,
+    pub app_version: Option<String>,
+    /// The data version (see [`crate::migrations`]) when it was made.
+    pub data_version: u32,
+    pub label: Option<String>,
+    pub files: Vec<BackupFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Relative to the data directory, `/`-separated.
+    pub path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+/// Where backups go.
+pub fn backup_dir(ctx: &AppContext) -> PathBuf {
+    ctx.backup
+        .dir
+        .clone()
+        .unwrap_or_else(|| ctx.data_dir.join("backups"))
+}
+
+fn sha256(data: &[u8]) -> String {
+    hex(ring::digest::digest(&ring::digest::SHA256, data).as_ref())
+}
+
+fn now_secs(ctx: &AppContext) -> u64 {
+    ctx.clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The data directory's files that belong in a backup, as `/`-separated
+/// relative paths, sorted.
+fn data_files(ctx: &AppContext) -> Result<Vec<String>, CommandError> {
+    let root = &ctx.data_dir;
+    if !ctx.fs().exists(root) {
+        return Ok(Vec::new());
+    }
+    let backups = backup_dir(ctx);
+    let exclude: Vec<glob::Pattern> = ctx
+        .backup
+        .exclude
+        .iter()
+        .map(|p| glob::Pattern::new(p))
+        .collect::<Result<_, _>>()
+        .map_err(|e| CommandError::InvalidInput(format!("backup.exclude: {}", e)))?;
+    let mut files = Vec::new();
+    ctx.fs().walk(root, None, &mut |entry| {
+        if entry.is_dir || entry.is_symlink || entry.path.starts_with(&backups) {
+            return true;
+        }
+        let Ok(relative) = entry.path.strip_prefix(root) else {
+            return true;
+        };
+        let relative = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if !exclude.iter().any(|p| p.matches(&relative)) {
+            files.push(relative);
+        }
+        true
+    })?;
+    files.sort();
+    Ok(files)
+}
+
+/// Back up the data directory to `dest_dir` (the backup directory if
+/// `None`, where retention then applies). In a dry run, lists the files
+/// instead.
+pub fn create(
+    ctx: &AppContext,
+    dest_dir: Option<&Path>,
+    label: Option<&str>,
+    dry_run: bool,
+) -> Result<Value, CommandError> {
+    archive(ctx, dest_dir, label, dry_run, dest_dir.is_none())
+}
+
+/// [`create`], pruning the backup directory afterwards only if `prune`.
+fn archive(
+    ctx: &AppContext,
+    dest_dir: Option<&Path>,
+    label: Option<&str>,
+    dry_run: bool,
+    prune: bool,
+) -> Result<Value, CommandError> {
+    let files = data_files(ctx)?;
+    let dir = dest_dir.map_or_else(|| backup_dir(ctx), Path::to_path_buf);
+    if dry_run {
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": "backup",
+            "dir": dir.display().to_string(),
+            "files": files,
+        }));
+    }
+
+    let now = now_secs(ctx);
+    let mut manifest = BackupManifest {
+        format: FORMAT,
+        created_at: format_time(now),
+        app_id: ctx.app_id.clone(),
+        app_name: ctx.app_name.clone(),
+        app_version: crate::host::app_version(),
+        data_version: crate::migrations::load(ctx)?.version(),
+        label: label.map(String::from),
+        files: Vec::new(),
+    };
+    let mut entries = Vec::new();
+    for file in files {
+        let data = ctx.fs().read_file(&ctx.data_dir.join(&file))?;
+        manifest.files.push(BackupFile {
+            path: file.clone(),
+            size_bytes: data.len() as u64,
+            sha256: sha256(&data),
+        });
+        entries.push((file, data));
+    }
+    let json =
+        serde_json::to_vec_pretty(&manifest).map_err(|e| CommandError::Other(e.to_string()))?;
+    entries.insert(0, (MANIFEST.to_string(), json));
+    let bytes = crate::zip::write(&entries, now)?;
+
+    let stamp: String = manifest
+        .created_at
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+    let base = format!("{}-backup-{}-{}", ctx.app_name, &stamp[..8], &stamp[8..]);
+    ctx.fs().create_dir_all(&dir)?;
+    let mut path = dir.join(format!("{}.zip", base));
+    for n in 2.. {
+        if !ctx.fs().exists(&path) {
+            break;
+        }
+        path = dir.join(format!("{}-{}.zip", base, n));
+    }
+    ctx.fs().write_file_atomic(&path, &bytes)?;
+    let pruned = match prune {
+        true => prune_backups(ctx, &dir)?,
+        false => Vec::new(),
+    };
+
+    Ok(serde_json::json!({
+        "path": path.display().to_string(),
+        "size_bytes": bytes.len(),
+        "sha256": sha256(&bytes),
+        "files": manifest.files.len(),
+        "data_version": manifest.data_version,
+        "pruned": pruned,
+    }))
+}
+
+/// Backups in `dir`, newest first.
+fn backups_in(ctx: &AppContext, dir: &Path) -> Result<Vec<PathBuf>, CommandError> {
+    if !ctx.fs().exists(dir) {
+        return Ok(Vec::new());
+    }
+    let prefix = format!("{}-backup-", ctx.app_name);
+    let mut names: Vec<String> = ctx
+        .fs()
+        .list_dir(dir)?
+        .into_iter()
+        .filter(|e| !e.is_dir && e.name.starts_with(&prefix) && e.name.ends_with(".zip"))
+        .map(|e| e.name)
+        .collect();
+    // `-2` suffixes for backups made in the same second sort after the
+    // first once the extension is off.
+    names.sort_by(|a, b| b.trim_end_matches(".zip").cmp(a.trim_end_matches(".zip")));
+    Ok(names.into_iter().map(|n| dir.join(n)).collect())
+}
+
+/// Delete all but the newest `backup.keep` backups in `dir`.
+fn prune_backups(ctx: &AppContext, dir: &Path) -> Result<Vec<String>, CommandError> {
+    if ctx.backup.keep == 0 {
+        return Ok(Vec::new());
+    }
+    let mut pruned = Vec::new();
+    for path in backups_in(ctx, dir)?.into_iter().skip(ctx.backup.keep) {
+        ctx.fs().remove_file(&path)?;
+        pruned.push(path.display().to_string());
+    }
+    Ok(pruned)
+}
+
+fn path_arg(args: &Value, key: &str) -> Result<Option<PathBuf>, CommandError> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_str()
+            .map(|s| Some(PathBuf::from(s)))
+            .ok_or_else(|| CommandError::InvalidInput(format!("'{}' must be a string", key))),
+    }
+}
+
+/// `backup_create` – archive the app data directory.
+///
+/// Args: `{ "dest_dir"?: "/path", "label"?: "before upgrade" }`; without
+/// `dest_dir` the backup goes to the backup directory and older backups
+/// past `backup.keep` are deleted.
+/// Returns: `{ "path", "size_bytes", "sha256", "files", "data_version",
+/// "pruned": [...] }`; a dry run returns the files it would archive.
+pub(crate) fn cmd_backup_create(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let dest_dir = path_arg(&args, "dest_dir")?;
+    let label = match args.get("label") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(
+            v.as_str()
+                .ok_or_else(|| CommandError::InvalidInput("'label' must be a string".into()))?,
+        ),
+    };
+    create(ctx, dest_dir.as_deref(), label, is_dry_run(&args, ctx))
+}
+
+/// `backup_restore` – replace the app data with a backup.
+///
+/// Args: `{ "path": "/path/app-backup-20260101-120000.zip" }`.
+/// Returns: `{ "path", "created_at", "restored": [...], "removed": [...],
+/// "safety_backup", "migrations" }`; a dry run returns the files it would
+/// `write` and `remove`. A damaged backup fails before anything changes.
+pub(crate) fn cmd_backup_restore(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let path = path_arg(&args, "path")?
+        .ok_or_else(|| CommandError::InvalidInput("'path' is required".into()))?;
+    restore(ctx, &path, is_dry_run(&args, ctx))
+}
+
+/// `backup_list` – the backups in the backup directory.
+///
+/// Returns: `{ "dir", "backups": [{path, size_bytes, created_at, label,
+/// data_version, files}] }`, newest first; an unreadable backup has an
+/// `error` instead.
+pub(crate) fn cmd_backup_list(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let dir = backup_dir(ctx);
+    let backups: Vec<Value> = backups_in(ctx, &dir)?
+        .into_iter()
+        .map(|path| {
+            let size_bytes = ctx.fs().stat(&path).map(|m| m.size_bytes).ok();
+            match open(ctx, &path) {
+                Ok((manifest, _)) => serde_json::json!({
+                    "path": path.display().to_string(),
+                    "size_bytes": size_bytes,
+                    "created_at": manifest.created_at,
+                    "label": manifest.label,
+                    "data_version": manifest.data_version,
+                    "files": manifest.files.len(),
+                }),
+                Err(e) => serde_json::json!({
+                    "path": path.display().to_string(),
+                    "size_bytes": size_bytes,
+                    "error": e.to_string(),
+                }),
+            }
+        })
+        .collect();
+    Ok(serde_json::json!({ "dir": dir.display().to_string(), "backups": backups }))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn ctx(dir: &Path) -> AppContext {
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.join("data");
+        std::fs::create_dir_all(ctx.data_dir.join("cache")).unwrap();
+        std::fs::write(ctx.data_dir.join("consent.json"), r#"{"features": {}}"#).unwrap();
+        std::fs::write(ctx.data_dir.join("cache/index.bin"), [1, 2, 3]).unwrap();
+        std::fs::write(ctx.data_dir.join("sidecar.sock"), "").unwrap();
+        ctx
+    }
+
+    #[test]
+    fn test_retention_keeps_the_newest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = ctx(dir.path());
+        ctx.backup.dir = Some(dir.path().join("backups"));
+        ctx.backup.keep = 2;
+        let paths: Vec<String> = (0..3)
+            .map(|_| {
+                create(&ctx, None, None, false).unwrap()["path"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        let kept = backups_in(&ctx, &backup_dir(&ctx)).unwrap();
+        assert_eq!(kept, [PathBuf::from(&paths[2]), PathBuf::from(&paths[1])]);
+    }
+}
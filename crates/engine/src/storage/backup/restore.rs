@@ -0,0 +1,224 @@
+//! Checking a backup against its manifest and restoring it over the data
+//! directory.
+
+use super::{archive, data_files, sha256, BackupManifest, FORMAT, MANIFEST};
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+/// A path from a manifest that stays inside the data directory.
+fn safe_relative(path: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(path);
+    let normal = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)));
+    (normal && !path.is_empty() && path != MANIFEST).then_some(relative)
+}
+
+/// Read the backup at `path` and check it against its manifest.
+pub fn open(
+    ctx: &AppContext,
+    path: &Path,
+) -> Result<(BackupManifest, crate::zip::Entries), CommandError> {
+    let invalid = |why: String| {
+        CommandError::InvalidInput(format!("{} is not a valid backup: {}", path.display(), why))
+    };
+    let bytes = ctx.fs().read_file(path)?;
+    let mut entries = crate::zip::read(&bytes).map_err(|e| invalid(e.to_string()))?;
+    let at = entries
+        .iter()
+        .position(|(name, _)| name == MANIFEST)
+        .ok_or_else(|| invalid(format!("no {}", MANIFEST)))?;
+    let (_, json) = entries.remove(at);
+    let manifest: BackupManifest =
+        serde_json::from_slice(&json).map_err(|e| invalid(format!("{}: {}", MANIFEST, e)))?;
+    if manifest.format > FORMAT {
+        return Err(CommandError::Unsupported(format!(
+            "backup format {} is newer than this build's {}; update the app",
+            manifest.format, FORMAT
+        )));
+    }
+
+    let listed: BTreeSet<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+    if let Some((name, _)) = entries
+        .iter()
+        .find(|(name, _)| !listed.contains(name.as_str()))
+    {
+        return Err(invalid(format!("{} is not in the manifest", name)));
+    }
+    for file in &manifest.files {
+        if safe_relative(&file.path).is_none() {
+            return Err(invalid(format!("unsafe path {}", file.path)));
+        }
+        let (_, data) = entries
+            .iter()
+            .find(|(name, _)| *name == file.path)
+            .ok_or_else(|| invalid(format!("{} is missing", file.path)))?;
+        if data.len() as u64 != file.size_bytes || sha256(data) != file.sha256 {
+            return Err(invalid(format!("{} does not match its hash", file.path)));
+        }
+    }
+    Ok((manifest, entries))
+}
+
+/// Replace the data directory's files with the backup at `path`. In a dry
+/// run, reports what would be written and removed.
+pub fn restore(ctx: &AppContext, path: &Path, dry_run: bool) -> Result<Value, CommandError> {
+    let (manifest, entries) = open(ctx, path)?;
+    let latest = crate::migrations::MIGRATIONS
+        .iter()
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(0);
+    if manifest.data_version > latest {
+        return Err(CommandError::Unsupported(format!(
+            "the backup's data is at version {}, newer than this build's {}; update the app",
+            manifest.data_version, latest
+        )));
+    }
+    let remove: Vec<String> = data_files(ctx)?
+        .into_iter()
+        .filter(|f| !entries.iter().any(|(name, _)| name == f))
+        .collect();
+    let write: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+    if dry_run {
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": "restore",
+            "path": path.display().to_string(),
+            "created_at": manifest.created_at,
+            "write": write,
+            "remove": remove,
+        }));
+    }
+
+    // Not pruned: the backup being restored may be the oldest one kept.
+    let safety = archive(ctx, None, Some("pre-restore"), false, false)?;
+    for (name, data) in &entries {
+        let dest = ctx.data_dir.join(safe_relative(name).unwrap_or_default());
+        if let Some(parent) = dest.parent() {
+            ctx.fs().create_dir_all(parent)?;
+        }
+        ctx.fs().write_file_atomic(&dest, data)?;
+    }
+    for name in &remove {
+        ctx.fs().remove_file(&ctx.data_dir.join(name))?;
+    }
+    let migrations = crate::migrations::run(ctx, crate::migrations::MIGRATIONS, false)?;
+
+    Ok(serde_json::json!({
+        "path": path.display().to_string(),
+        "created_at": manifest.created_at,
+        "restored": write,
+        "removed": remove,
+        "safety_backup": safety["path"],
+        "migrations": migrations.migrations.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::tests::ctx;
+    use crate::backup::{backup_dir, backups_in, cmd_backup_list, create};
+
+    fn read(ctx: &AppContext, name: &str) -> Option<Vec<u8>> {
+        std::fs::read(ctx.data_dir.join(name)).ok()
+    }
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+
+        let plan = create(&ctx, None, None, true).unwrap();
+        assert_eq!(
+            plan["files"],
+            serde_json::json!(["cache/index.bin", "consent.json"])
+        );
+        assert!(!backup_dir(&ctx).exists());
+
+        let made = create(&ctx, None, Some("first"), false).unwrap();
+        let path = PathBuf::from(made["path"].as_str().unwrap());
+        assert!(path.starts_with(ctx.data_dir.join("backups")));
+        assert_eq!(made["files"], 2);
+
+        std::fs::write(ctx.data_dir.join("consent.json"), "changed").unwrap();
+        std::fs::write(ctx.data_dir.join("new.json"), "{}").unwrap();
+        std::fs::remove_file(ctx.data_dir.join("cache/index.bin")).unwrap();
+
+        let plan = restore(&ctx, &path, true).unwrap();
+        assert_eq!(plan["remove"], serde_json::json!(["new.json"]));
+        assert_eq!(read(&ctx, "consent.json").unwrap(), b"changed");
+
+        let restored = restore(&ctx, &path, false).unwrap();
+        assert_eq!(read(&ctx, "consent.json").unwrap(), br#"{"features": {}}"#);
+        assert_eq!(read(&ctx, "cache/index.bin").unwrap(), [1, 2, 3]);
+        assert!(read(&ctx, "new.json").is_none());
+        assert!(read(&ctx, "sidecar.sock").is_some());
+
+        // The data as it was before the restore was kept.
+        let safety = PathBuf::from(restored["safety_backup"].as_str().unwrap());
+        let (manifest, _) = open(&ctx, &safety).unwrap();
+        assert_eq!(manifest.label.as_deref(), Some("pre-restore"));
+        assert!(manifest.files.iter().any(|f| f.path == "new.json"));
+
+        let listed = cmd_backup_list(Value::Null, &ctx).unwrap();
+        assert_eq!(listed["backups"].as_array().unwrap().len(), 2);
+        assert_eq!(listed["backups"][0]["label"], "pre-restore");
+    }
+
+    #[test]
+    fn test_restore_rejects_a_tampered_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let dest = dir.path().join("export");
+        let made = create(&ctx, Some(&dest), None, false).unwrap();
+        let path = PathBuf::from(made["path"].as_str().unwrap());
+        assert!(path.starts_with(&dest));
+
+        let (mut manifest, mut entries) = open(&ctx, &path).unwrap();
+        entries[0].1.push(b'!');
+        manifest.files[0].size_bytes += 1;
+        entries.insert(0, (MANIFEST.into(), serde_json::to_vec(&manifest).unwrap()));
+        std::fs::write(&path, crate::zip::write(&entries, 0).unwrap()).unwrap();
+
+        std::fs::write(ctx.data_dir.join("consent.json"), "current").unwrap();
+        let err = restore(&ctx, &path, false).unwrap_err();
+        assert!(
+            err.to_string().contains("does not match its hash"),
+            "{}",
+            err
+        );
+        assert_eq!(read(&ctx, "consent.json").unwrap(), b"current");
+        assert!(!backup_dir(&ctx).exists());
+
+        manifest.files[0].path = "../escape".into();
+        entries[0].1 = serde_json::to_vec(&manifest).unwrap();
+        std::fs::write(&path, crate::zip::write(&entries, 0).unwrap()).unwrap();
+        assert!(restore(&ctx, &path, true).is_err());
+    }
+
+    #[test]
+    fn test_restoring_the_oldest_kept_backup_keeps_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = ctx(dir.path());
+        ctx.backup.keep = 2;
+        let oldest = create(&ctx, None, None, false).unwrap();
+        let oldest = PathBuf::from(oldest["path"].as_str().unwrap());
+        create(&ctx, None, None, false).unwrap();
+
+        std::fs::write(ctx.data_dir.join("consent.json"), "changed").unwrap();
+        let restored = restore(&ctx, &oldest, false).unwrap();
+        assert_eq!(read(&ctx, "consent.json").unwrap(), br#"{"features": {}}"#);
+        assert!(oldest.exists());
+        let kept = backups_in(&ctx, &backup_dir(&ctx)).unwrap();
+        assert_eq!(kept.len(), 3);
+        assert_eq!(
+            kept[0],
+            PathBuf::from(restored["safety_backup"].as_str().unwrap())
+        );
+    }
+}
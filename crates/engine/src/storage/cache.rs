@@ -0,0 +1,102 @@
+//! Result cache for idempotent commands.
+//!
+//! A command registered with a TTL (see
+//! [`crate::commands::CommandSpec::cache_for`]) has its successful results
+//! kept, keyed by command name and a hash of its args, so a UI polling
+//! `doctor` or `list_removable_media` doesn't re-run the underlying system
+//! tools on every call. Failures are never cached. Ages are measured on the
+//! context's monotonic clock.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Most entries kept; the oldest go first once full.
+pub const MAX_ENTRIES: usize = 256;
+
+struct Entry {
+    stored_at: Duration,
+    ttl: Duration,
+    value: Value,
+}
+
+#[derive(Default)]
+pub struct ResultCache {
+    entries: Mutex<HashMap<(String, String), Entry>>,
+}
+
+/// Identifies `args` independently of how they were formatted; object keys
+/// are already sorted by `serde_json`.
+fn args_key(args: &Value) -> String {
+    let json = serde_json::to_string(args).unwrap_or_default();
+    crate::export::hex(ring::digest::digest(&ring::digest::SHA256, json.as_bytes()).as_ref())
+}
+
+impl ResultCache {
+    /// The cached result of `command` with `args` and its age at `now`, if
+    /// it hasn't expired.
+    pub fn get(&self, command: &str, args: &Value, now: Duration) -> Option<(Value, Duration)> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get(&(command.to_string(), args_key(args)))?;
+        let age = now.saturating_sub(entry.stored_at);
+        (age < entry.ttl).then(|| (entry.value.clone(), age))
+    }
+
+    pub fn put(&self, command: &str, args: &Value, value: Value, now: Duration, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, e| now.saturating_sub(e.stored_at) < e.ttl);
+        if entries.len() >= MAX_ENTRIES {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, e)| e.stored_at)
+                .map(|(k, _)| k.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            (command.to_string(), args_key(args)),
+            Entry {
+                stored_at: now,
+                ttl,
+                value,
+            },
+        );
+    }
+
+    /// Drop every cached result of `command`, e.g. after something it
+    /// reports on changed; returns how many were dropped.
+    pub fn invalidate(&self, command: &str) -> usize {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let before = entries.len();
+        entries.retain(|(name, _), _| name != command);
+        before - entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_and_are_keyed_by_args() {
+        let cache = ResultCache::default();
+        let ttl = Duration::from_secs(5);
+        let args = serde_json::json!({ "a": 1, "b": [true] });
+        cache.put("doctor", &args, Value::from(1), Duration::ZERO, ttl);
+
+        let same: Value = serde_json::from_str(r#"{"b":[true],  "a":1}"#).unwrap();
+        assert_eq!(
+            cache.get("doctor", &same, Duration::from_secs(2)),
+            Some((Value::from(1), Duration::from_secs(2)))
+        );
+        assert_eq!(cache.get("doctor", &Value::Null, Duration::ZERO), None);
+        assert_eq!(cache.get("other", &args, Duration::ZERO), None);
+        assert_eq!(cache.get("doctor", &args, ttl), None);
+
+        cache.put("doctor", &args, Value::from(2), ttl, ttl);
+        assert_eq!(cache.invalidate("doctor"), 1);
+        assert_eq!(cache.get("doctor", &args, ttl), None);
+    }
+}
@@ -0,0 +1,378 @@
+//! Consent records: which version of each policy the user accepted, which
+//! optional features they agreed to, and when – kept in
+//! `<data_dir>/consent.json` and changed only through `consent_set`.
+//!
+//! The app config names the current policy versions and the features that
+//! need consent (`consent:`):
+//!
+//! ```yaml
+//! consent:
+//!   policies:
+//!     privacy: "2026-01"    # bump to ask everyone again
+//!     terms: "3"
+//!   features: [telemetry, crash_reports]
+//! ```
+//!
+//! Every change is also appended to the record's `history`, so the app can
+//! show when consent was given or withdrawn. Features default to not
+//! granted; withdrawing `telemetry` drops whatever
+//! [`crate::telemetry`] had queued.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// File under [`AppContext::data_dir`] holding the record.
+pub const STATE_FILE: &str = "consent.json";
+
+/// JSON [`ConsentConfig`], e.g. the GUI's `consent:` section handed to its
+/// sidecar.
+pub const CONSENT_ENV: &str = "APP__CONSENT";
+
+/// Serializes read-modify-write of the record within this process.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Current policy versions and consent-gated features.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsentConfig {
+    /// Policy name to its current version.
+    pub policies: BTreeMap<String, String>,
+    /// Features that may only run with consent.
+    pub features: Vec<String>,
+}
+
+impl Default for ConsentConfig {
+    fn default() -> Self {
+        Self {
+            policies: BTreeMap::new(),
+            features: vec!["telemetry".into(), "crash_reports".into()],
+        }
+    }
+}
+
+impl ConsentConfig {
+    /// The config in `$APP__CONSENT`, or the default.
+    pub fn from_env() -> Self {
+        std::env::var(CONSENT_ENV)
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .and_then(|v| match serde_json::from_str(&v) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    tracing::warn!("ignoring {}: {}", CONSENT_ENV, e);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// What the user agreed to, as persisted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsentRecord {
+    pub policies: BTreeMap<String, PolicyAcceptance>,
+    pub features: BTreeMap<String, FeatureConsent>,
+    /// Every change, oldest first.
+    pub history: Vec<ConsentChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyAcceptance {
+    pub version: String,
+    /// Unix seconds.
+    pub accepted_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureConsent {
+    pub granted: bool,
+    /// Unix seconds of the last change.
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsentChange {
+    /// Unix seconds.
+    pub at: u64,
+    /// `policy:<name>` or `feature:<name>`.
+    pub subject: String,
+    /// The accepted version, or `granted` / `withdrawn`.
+    pub value: String,
+}
+
+/// The persisted record; missing or unreadable means nothing was agreed.
+pub fn load(ctx: &AppContext) -> ConsentRecord {
+    ctx.fs()
+        .read_file(&ctx.data_dir.join(STATE_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// Whether the user agreed to `feature`.
+pub fn granted(ctx: &AppContext, feature: &str) -> bool {
+    load(ctx).features.get(feature).is_some_and(|f| f.granted)
+}
+
+/// Configured policies whose current version has not been accepted.
+pub fn pending_policies(ctx: &AppContext, record: &ConsentRecord) -> Vec<String> {
+    ctx.consent
+        .policies
+        .iter()
+        .filter(|(name, version)| {
+            record
+                .policies
+                .get(*name)
+                .is_none_or(|accepted| &accepted.version != *version)
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Grant or withdraw consent for `feature`, recording the change.
+pub fn set_feature(ctx: &AppContext, feature: &str, granted: bool) -> Result<(), CommandError> {
+    apply(ctx, &BTreeMap::new(), &BTreeMap::from([(feature, granted)]))
+}
+
+pub(crate) fn apply(
+    ctx: &AppContext,
+    policies: &BTreeMap<&str, &str>,
+    features: &BTreeMap<&str, bool>,
+) -> Result<(), CommandError> {
+    for (name, version) in policies {
+        match ctx.consent.policies.get(*name) {
+            None => {
+                return Err(CommandError::InvalidInput(format!(
+                    "unknown policy '{}'",
+                    name
+                )))
+            }
+            Some(current) if current != version => {
+                return Err(CommandError::InvalidInput(format!(
+                    "policy '{}' is at version {}, not {}",
+                    name, current, version
+                )))
+            }
+            Some(_) => {}
+        }
+    }
+    if let Some(name) = features
+        .keys()
+        .find(|name| !ctx.consent.features.iter().any(|f| f == *name))
+    {
+        return Err(CommandError::InvalidInput(format!(
+            "unknown feature '{}'",
+            name
+        )));
+    }
+
+    let now = ctx
+        .clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let withdrew_telemetry = {
+        let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut record = load(ctx);
+        for (name, version) in policies {
+            record.policies.insert(
+                name.to_string(),
+                PolicyAcceptance {
+                    version: version.to_string(),
+                    accepted_at: now,
+                },
+            );
+            record.history.push(ConsentChange {
+                at: now,
+                subject: format!("policy:{}", name),
+                value: version.to_string(),
+            });
+        }
+        let was_granted = |record: &ConsentRecord, name: &str| {
+            record.features.get(name).is_some_and(|f| f.granted)
+        };
+        let withdrew_telemetry =
+            was_granted(&record, "telemetry") && features.get("telemetry") == Some(&false);
+        for (name, granted) in features {
+            if record.features.contains_key(*name) && was_granted(&record, name) == *granted {
+                continue;
+            }
+            record.features.insert(
+                name.to_string(),
+                FeatureConsent {
+                    granted: *granted,
+                    updated_at: now,
+                },
+            );
+            record.history.push(ConsentChange {
+                at: now,
+                subject: format!("feature:{}", name),
+                value: if *granted { "granted" } else { "withdrawn" }.into(),
+            });
+        }
+        let json =
+            serde_json::to_vec_pretty(&record).map_err(|e| CommandError::Other(e.to_string()))?;
+        ctx.fs()
+            .write_file_atomic(&ctx.data_dir.join(STATE_FILE), &json)?;
+        withdrew_telemetry
+    };
+    if withdrew_telemetry {
+        crate::telemetry::forget(ctx)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// `consent_get` – the record against the current config.
+///
+/// Returns: `{ "policies": {"privacy": {"current": "2026-01", "accepted":
+/// "2025-06", "accepted_at": 1750000000}}, "pending_policies": ["privacy"],
+/// "features": {"telemetry": {"granted": false, "updated_at": null}},
+/// "history": [...] }`
+pub fn cmd_consent_get(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let record = load(ctx);
+    let policies: BTreeMap<&String, Value> = ctx
+        .consent
+        .policies
+        .iter()
+        .map(|(name, current)| {
+            let accepted = record.policies.get(name);
+            (
+                name,
+                serde_json::json!({
+                    "current": current,
+                    "accepted": accepted.map(|a| &a.version),
+                    "accepted_at": accepted.map(|a| a.accepted_at),
+                }),
+            )
+        })
+        .collect();
+    let features: BTreeMap<&String, Value> = ctx
+        .consent
+        .features
+        .iter()
+        .map(|name| {
+            let consent = record.features.get(name);
+            (
+                name,
+                serde_json::json!({
+                    "granted": consent.is_some_and(|c| c.granted),
+                    "updated_at": consent.map(|c| c.updated_at),
+                }),
+            )
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "policies": policies,
+        "pending_policies": pending_policies(ctx, &record),
+        "features": features,
+        "history": record.history,
+    }))
+}
+
+/// `consent_set` – accept policy versions and grant or withdraw features.
+///
+/// Args: `{ "policies"?: {"privacy": "2026-01"}, "features"?: {"telemetry": true} }`;
+/// a policy must be accepted at its current version. Nothing is saved if
+/// any entry is invalid.
+/// Returns: the same as `consent_get`.
+pub fn cmd_consent_set(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let object = |key: &str| -> Result<Vec<(&str, &Value)>, CommandError> {
+        match args.get(key) {
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(Value::Object(map)) => Ok(map.iter().map(|(k, v)| (k.as_str(), v)).collect()),
+            Some(_) => Err(CommandError::InvalidInput(format!(
+                "'{}' must be an object",
+                key
+            ))),
+        }
+    };
+    let policies = object("policies")?
+        .into_iter()
+        .map(|(name, v)| match v.as_str() {
+            Some(version) => Ok((name, version)),
+            None => Err(CommandError::InvalidInput(format!(
+                "version for policy '{}' must be a string",
+                name
+            ))),
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let features = object("features")?
+        .into_iter()
+        .map(|(name, v)| match v.as_bool() {
+            Some(granted) => Ok((name, granted)),
+            None => Err(CommandError::InvalidInput(format!(
+                "consent for feature '{}' must be a boolean",
+                name
+            ))),
+        })
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    if policies.is_empty() && features.is_empty() {
+        return Err(CommandError::InvalidInput(
+            "nothing to set: pass 'policies' and/or 'features'".into(),
+        ));
+    }
+    apply(ctx, &policies, &features)?;
+    cmd_consent_get(Value::Null, ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(dir: &std::path::Path) -> AppContext {
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.to_path_buf();
+        ctx.consent.policies = BTreeMap::from([("privacy".into(), "2".into())]);
+        ctx
+    }
+
+    #[test]
+    fn test_policy_acceptance_tracks_current_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = context(dir.path());
+        let out = cmd_consent_get(Value::Null, &ctx).unwrap();
+        assert_eq!(out["pending_policies"], serde_json::json!(["privacy"]));
+
+        let stale = cmd_consent_set(serde_json::json!({ "policies": { "privacy": "1" } }), &ctx);
+        assert!(matches!(stale, Err(CommandError::InvalidInput(_))));
+        let out =
+            cmd_consent_set(serde_json::json!({ "policies": { "privacy": "2" } }), &ctx).unwrap();
+        assert_eq!(out["pending_policies"], serde_json::json!([]));
+        assert_eq!(out["policies"]["privacy"]["accepted"], "2");
+
+        ctx.consent.policies.insert("privacy".into(), "3".into());
+        assert_eq!(pending_policies(&ctx, &load(&ctx)), vec!["privacy"]);
+    }
+
+    #[test]
+    fn test_features_record_history_and_reject_unknown_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = context(dir.path());
+        assert!(!granted(&ctx, "telemetry"));
+
+        let bad = cmd_consent_set(
+            serde_json::json!({ "features": { "telemetry": true, "tracking": true } }),
+            &ctx,
+        );
+        assert!(matches!(bad, Err(CommandError::InvalidInput(_))));
+        assert!(!granted(&ctx, "telemetry"));
+
+        set_feature(&ctx, "telemetry", true).unwrap();
+        set_feature(&ctx, "telemetry", true).unwrap();
+        set_feature(&ctx, "telemetry", false).unwrap();
+        assert!(!granted(&ctx, "telemetry"));
+        let history: Vec<String> = load(&ctx).history.into_iter().map(|c| c.value).collect();
+        assert_eq!(history, ["granted", "withdrawn"]);
+        assert!(cmd_consent_set(serde_json::json!({}), &ctx).is_err());
+    }
+}
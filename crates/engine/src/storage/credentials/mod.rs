@@ -0,0 +1,246 @@
+//! Credentials shared between appctl and the GUI through the OS secret
+//! store, so a token provisioned from a terminal or CI job is the one the
+//! app reads at startup.
+//!
+//! Both sides address the store with the same service name – the app
+//! identifier, [`AppContext::app_id`] – and namespaced accounts
+//! (`<namespace>/<key>`, e.g. `llm/openai`). The GUI fills API keys missing
+//! from its config from the `llm` namespace (see [`fill_llm_keys`]).
+//!
+//! The stores are driven through their command-line front ends, which is
+//! also what the GUI uses, so access rules that differ between callers
+//! (Keychain ACLs, a locked Secret Service collection) apply equally:
+//! - macOS: `security` (login Keychain, generic passwords)
+//! - Linux: `secret-tool` (Secret Service over the session D-Bus)
+//! - Windows: PowerShell and the WinRT `PasswordVault` (Credential Locker)
+//!
+//! Secrets are passed on stdin, never on the command line.
+
+mod stores;
+
+pub use stores::{MemorySecrets, SystemSecrets};
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::llm::{LlmProvider, LlmSettings};
+use crate::traits::{CapError, CapResult, SecretStoreOps};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Namespace used when a command does not name one.
+pub const DEFAULT_NAMESPACE: &str = "default";
+/// Namespace the GUI reads LLM API keys from, keyed by provider name.
+pub const LLM_NAMESPACE: &str = "llm";
+
+/// The store account for `key` in `namespace`. Both parts must be
+/// non-empty and use only letters, digits, `.`, `_`, and `-`.
+pub fn account(namespace: &str, key: &str) -> Result<String, String> {
+    for (what, part) in [("namespace", namespace), ("key", key)] {
+        let valid = !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        if !valid {
+            return Err(format!(
+                "invalid {} '{}': use letters, digits, '.', '_', and '-'",
+                what, part
+            ));
+        }
+    }
+    Ok(format!("{}/{}", namespace, key))
+}
+
+/// The secret stored for `key` in `namespace` under this app's service.
+pub fn get_secret(ctx: &AppContext, namespace: &str, key: &str) -> CapResult<Option<String>> {
+    let account = account(namespace, key).map_err(CapError::Other)?;
+    ctx.secrets().get(&ctx.app_id, &account)
+}
+
+/// Add API keys for providers `settings` has none for from the
+/// [`LLM_NAMESPACE`] of `store`. Keys from config or the environment win.
+pub fn fill_llm_keys(settings: &mut LlmSettings, store: &dyn SecretStoreOps, service: &str) {
+    for provider in LlmProvider::ALL {
+        if settings.api_key(provider).is_some() {
+            continue;
+        }
+        let account = format!("{}/{}", LLM_NAMESPACE, provider.as_str());
+        match store.get(service, &account) {
+            Ok(Some(key)) if !key.trim().is_empty() => {
+                settings.api_keys.insert(provider, key);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::debug!(
+                    "no {} key from {}: {}",
+                    provider.as_str(),
+                    store.backend(),
+                    e
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// The secret store for this OS.
+pub fn platform_default() -> Box<dyn SecretStoreOps> {
+    Box::new(SystemSecrets)
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CredentialArgs {
+    #[serde(default)]
+    namespace: Option<String>,
+    key: String,
+    #[serde(default)]
+    secret: Option<String>,
+}
+
+impl CredentialArgs {
+    fn parse(args: Value, with_secret: bool) -> Result<(Self, String), CommandError> {
+        let args: Self =
+            serde_json::from_value(args).map_err(|e| CommandError::InvalidInput(e.to_string()))?;
+        if args.secret.is_some() != with_secret {
+            return Err(CommandError::InvalidInput(if with_secret {
+                "missing 'secret'".into()
+            } else {
+                "'secret' is only accepted by credential_set".into()
+            }));
+        }
+        let namespace = args.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE);
+        let account = account(namespace, &args.key).map_err(CommandError::InvalidInput)?;
+        Ok((args, account))
+    }
+}
+
+fn describe(ctx: &AppContext, account: &str) -> Value {
+    serde_json::json!({
+        "service": ctx.app_id,
+        "account": account,
+        "backend": ctx.secrets().backend(),
+    })
+}
+
+/// `credential_set` – store a secret where the GUI will find it.
+///
+/// Args: `{ "namespace"?: "default", "key": "openai", "secret": "..." }`
+/// Returns: `{ "service": "com.example.app", "account": "llm/openai",
+/// "backend": "keychain" }`
+pub fn cmd_credential_set(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let (args, account) = CredentialArgs::parse(args, true)?;
+    let secret = args.secret.unwrap_or_default();
+    if secret.is_empty() {
+        return Err(CommandError::InvalidInput("'secret' is empty".into()));
+    }
+    ctx.secrets().set(&ctx.app_id, &account, &secret)?;
+    Ok(describe(ctx, &account))
+}
+
+/// `credential_get` – whether a secret is stored, without revealing it
+/// (results end up in history and artifacts; `appctl credentials get
+/// --reveal` prints the value itself).
+///
+/// Args: `{ "namespace"?: "default", "key": "openai" }`
+/// Returns: `{ "service": ..., "account": ..., "backend": ..., "present": true,
+/// "length": 51, "sha256": "..." }`
+pub fn cmd_credential_get(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let (_, account) = CredentialArgs::parse(args, false)?;
+    let secret = ctx.secrets().get(&ctx.app_id, &account)?;
+    let mut out = describe(ctx, &account);
+    out["present"] = secret.is_some().into();
+    out["length"] = secret.as_ref().map(|s| s.chars().count()).into();
+    out["sha256"] = secret
+        .as_ref()
+        .map(|s| {
+            crate::export::hex(ring::digest::digest(&ring::digest::SHA256, s.as_bytes()).as_ref())
+        })
+        .into();
+    Ok(out)
+}
+
+/// `credential_delete` – remove a stored secret.
+///
+/// Args: `{ "namespace"?: "default", "key": "openai" }`
+/// Returns: `{ "service": ..., "account": ..., "backend": ..., "deleted": true }`;
+/// `deleted` is false when nothing was stored.
+pub fn cmd_credential_delete(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let (_, account) = CredentialArgs::parse(args, false)?;
+    let deleted = ctx.secrets().delete(&ctx.app_id, &account)?;
+    let mut out = describe(ctx, &account);
+    out["deleted"] = deleted.into();
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> AppContext {
+        AppContext::default_headless().with_secrets(Box::new(MemorySecrets::default()))
+    }
+
+    #[test]
+    fn test_credentials_round_trip_without_revealing() {
+        let ctx = ctx();
+        let args = serde_json::json!({ "namespace": "llm", "key": "openai", "secret": "sk-test" });
+        let out = cmd_credential_set(args, &ctx).unwrap();
+        assert_eq!(out["account"], "llm/openai");
+        assert_eq!(out["service"], ctx.app_id.as_str());
+
+        let key = serde_json::json!({ "namespace": "llm", "key": "openai" });
+        let out = cmd_credential_get(key.clone(), &ctx).unwrap();
+        assert_eq!(out["present"], true);
+        assert_eq!(out["length"], 7);
+        assert!(!out.to_string().contains("sk-test"));
+        assert_eq!(
+            get_secret(&ctx, "llm", "openai").unwrap().as_deref(),
+            Some("sk-test")
+        );
+
+        assert_eq!(
+            cmd_credential_delete(key.clone(), &ctx).unwrap()["deleted"],
+            true
+        );
+        assert_eq!(
+            cmd_credential_delete(key.clone(), &ctx).unwrap()["deleted"],
+            false
+        );
+        assert_eq!(cmd_credential_get(key, &ctx).unwrap()["present"], false);
+    }
+
+    #[test]
+    fn test_credential_args_are_validated() {
+        let ctx = ctx();
+        for args in [
+            serde_json::json!({ "key": "a/b", "secret": "x" }),
+            serde_json::json!({ "key": "", "secret": "x" }),
+            serde_json::json!({ "key": "a", "secret": "" }),
+            serde_json::json!({ "key": "a" }),
+        ] {
+            let r = cmd_credential_set(args.clone(), &ctx);
+            assert!(matches!(r, Err(CommandError::InvalidInput(_))), "{}", args);
+        }
+        let r = cmd_credential_get(serde_json::json!({ "key": "a", "secret": "x" }), &ctx);
+        assert!(matches!(r, Err(CommandError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_fill_llm_keys_prefers_configured_keys() {
+        let store = MemorySecrets::default();
+        store.set("svc", "llm/openai", "from-store").unwrap();
+        store.set("svc", "llm/groq", "from-store").unwrap();
+        let mut settings = LlmSettings::default();
+        settings
+            .api_keys
+            .insert(LlmProvider::Openai, "from-config".into());
+        fill_llm_keys(&mut settings, &store, "svc");
+        assert_eq!(settings.api_key(LlmProvider::Openai), Some("from-config"));
+        assert_eq!(settings.api_key(LlmProvider::Groq), Some("from-store"));
+        assert_eq!(settings.api_key(LlmProvider::Gemini), None);
+    }
+}
@@ -0,0 +1,433 @@
+//! The secret stores: [`SystemSecrets`] through each OS's command-line
+//! tool, and [`MemorySecrets`] kept in memory.
+
+use crate::traits::{CapError, CapResult, SecretStoreOps, SecretStoreStatus};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// The OS secret store, through its command-line tool.
+pub struct SystemSecrets;
+
+/// Secrets kept in memory (tests, and contexts that must not touch the
+/// user's keychain).
+#[derive(Default)]
+pub struct MemorySecrets {
+    entries: Mutex<BTreeMap<(String, String), String>>,
+}
+
+impl SecretStoreOps for MemorySecrets {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, service: &str, account: &str) -> CapResult<Option<String>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .get(&(service.to_string(), account.to_string()))
+            .cloned())
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> CapResult<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(
+            (service.to_string(), account.to_string()),
+            secret.to_string(),
+        );
+        Ok(())
+    }
+
+    fn delete(&self, service: &str, account: &str) -> CapResult<bool> {
+        let mut entries = self.entries.lock().unwrap();
+        Ok(entries
+            .remove(&(service.to_string(), account.to_string()))
+            .is_some())
+    }
+}
+
+/// Exit status and stdout of a store tool; a non-zero status is for the
+/// caller to interpret (usually "not found").
+#[allow(dead_code)]
+fn run_tool(
+    cmd: &str,
+    args: &[&str],
+    env: &[(&str, &str)],
+    stdin: Option<&str>,
+) -> CapResult<(Option<i32>, String, String)> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(cmd)
+        .args(args)
+        .envs(env.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                CapError::DependencyMissing(format!("{} not found", cmd))
+            }
+            _ => CapError::Io(e),
+        })?;
+    if let Some(mut pipe) = child.stdin.take() {
+        pipe.write_all(stdin.unwrap_or_default().as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    Ok((
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    ))
+}
+
+#[allow(dead_code)]
+fn tool_failed(cmd: &str, code: Option<i32>, stderr: &str) -> CapError {
+    let lower = stderr.to_lowercase();
+    if lower.contains("denied")
+        || lower.contains("not allowed")
+        || lower.contains("user interaction")
+        || lower.contains("locked")
+    {
+        return CapError::PermissionDenied(format!("{}: {}", cmd, stderr));
+    }
+    CapError::Other(format!(
+        "{} exited with {}: {}",
+        cmd,
+        code.map_or("a signal".to_string(), |c| c.to_string()),
+        stderr
+    ))
+}
+
+#[cfg(target_os = "macos")]
+impl SecretStoreOps for SystemSecrets {
+    fn backend(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn get(&self, service: &str, account: &str) -> CapResult<Option<String>> {
+        let (code, out, err) = run_tool(
+            "security",
+            &["find-generic-password", "-s", service, "-a", account, "-w"],
+            &[],
+            None,
+        )?;
+        match code {
+            Some(0) => Ok(Some(out.strip_suffix('\n').unwrap_or(&out).to_string())),
+            Some(SECURITY_NOT_FOUND) => Ok(None),
+            _ => Err(tool_failed("security", code, &err)),
+        }
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> CapResult<()> {
+        // `security -i` reads commands from stdin, keeping the secret out of
+        // the process list.
+        let line = format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            security_quote(service),
+            security_quote(account),
+            security_quote(secret)
+        );
+        let (code, _, err) = run_tool("security", &["-i"], &[], Some(&line))?;
+        // Interactive mode exits 0 even when the command fails.
+        if code != Some(0) || !err.is_empty() {
+            return Err(tool_failed("security", code, &err));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, service: &str, account: &str) -> CapResult<bool> {
+        let (code, _, err) = run_tool(
+            "security",
+            &["delete-generic-password", "-s", service, "-a", account],
+            &[],
+            None,
+        )?;
+        match code {
+            Some(0) => Ok(true),
+            Some(SECURITY_NOT_FOUND) => Ok(false),
+            _ => Err(tool_failed("security", code, &err)),
+        }
+    }
+
+    /// The default keychain. `security` has no plain "is it locked"
+    /// query, so `locked` stays unknown.
+    fn status(&self) -> CapResult<SecretStoreStatus> {
+        let (code, out, err) = run_tool("security", &["default-keychain"], &[], None)?;
+        if code != Some(0) {
+            return Err(CapError::DependencyMissing(format!(
+                "no default keychain ({}); log in through the GUI once, or create one \
+                 with `security create-keychain` and `security default-keychain -s`",
+                err
+            )));
+        }
+        Ok(SecretStoreStatus {
+            provider: Some("securityd".into()),
+            default_store: Some(out.trim().trim_matches('"').to_string()),
+            locked: None,
+        })
+    }
+}
+
+/// `security` exit status for errSecItemNotFound.
+#[cfg(target_os = "macos")]
+const SECURITY_NOT_FOUND: i32 = 44;
+
+#[cfg(target_os = "macos")]
+fn security_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(target_os = "linux")]
+impl SystemSecrets {
+    /// `secret-tool` needs the session bus; without one it tries to
+    /// autolaunch a private bus whose keyring nobody else can see.
+    fn check_session_bus() -> CapResult<()> {
+        let runtime_bus = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| std::path::Path::new(&dir).join("bus").exists())
+            .unwrap_or(false);
+        if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() && !runtime_bus {
+            return Err(CapError::DependencyMissing(
+                "no D-Bus session bus (DBUS_SESSION_BUS_ADDRESS is unset): \
+                 the Secret Service is unreachable"
+                    .into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SecretStoreOps for SystemSecrets {
+    fn backend(&self) -> &'static str {
+        "secret-service"
+    }
+
+    fn get(&self, service: &str, account: &str) -> CapResult<Option<String>> {
+        Self::check_session_bus()?;
+        let (code, out, err) = run_tool(
+            "secret-tool",
+            &["lookup", "service", service, "username", account],
+            &[],
+            None,
+        )?;
+        match code {
+            Some(0) => Ok(Some(out)),
+            // Not found is exit 1 with nothing on stderr.
+            Some(1) if err.is_empty() => Ok(None),
+            _ => Err(tool_failed("secret-tool", code, &err)),
+        }
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> CapResult<()> {
+        Self::check_session_bus()?;
+        let label = format!("--label={} ({})", account, service);
+        let (code, _, err) = run_tool(
+            "secret-tool",
+            &["store", &label, "service", service, "username", account],
+            &[],
+            Some(secret),
+        )?;
+        match code {
+            Some(0) => Ok(()),
+            _ => Err(tool_failed("secret-tool", code, &err)),
+        }
+    }
+
+    fn delete(&self, service: &str, account: &str) -> CapResult<bool> {
+        if self.get(service, account)?.is_none() {
+            return Ok(false);
+        }
+        let (code, _, err) = run_tool(
+            "secret-tool",
+            &["clear", "service", service, "username", account],
+            &[],
+            None,
+        )?;
+        match code {
+            Some(0) => Ok(true),
+            _ => Err(tool_failed("secret-tool", code, &err)),
+        }
+    }
+
+    fn status(&self) -> CapResult<SecretStoreStatus> {
+        Self::check_session_bus()?;
+        secret_service::status()
+    }
+}
+
+/// The Secret Service over D-Bus directly, for what `secret-tool` cannot
+/// tell: who provides it and whether the default collection is locked.
+#[cfg(target_os = "linux")]
+mod secret_service {
+    use super::*;
+    use zbus::blocking::{connection, Connection, Proxy};
+    use zbus::zvariant::OwnedValue;
+
+    const SECRETS: &str = "org.freedesktop.secrets";
+    const DEFAULT_COLLECTION: &str = "/org/freedesktop/secrets/aliases/default";
+    const COLLECTION: &str = "org.freedesktop.Secret.Collection";
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+    pub fn status() -> CapResult<SecretStoreStatus> {
+        let conn = connection::Builder::session()
+            .and_then(|b| b.method_timeout(TIMEOUT).build())
+            .map_err(|e| {
+                CapError::DependencyMissing(format!("session D-Bus unreachable: {}", e))
+            })?;
+        let bus = Proxy::new(
+            &conn,
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+        )
+        .map_err(|e| CapError::Other(format!("D-Bus proxy: {}", e)))?;
+        let owned: bool = bus
+            .call("NameHasOwner", &(SECRETS,))
+            .map_err(|e| CapError::Other(format!("NameHasOwner: {}", e)))?;
+        let provider = if owned {
+            bus.call::<_, _, u32>("GetConnectionUnixProcessID", &(SECRETS,))
+                .ok()
+                .and_then(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+                .map(|comm| comm.trim().to_string())
+        } else {
+            let activatable: Vec<String> = bus
+                .call("ListActivatableNames", &())
+                .map_err(|e| CapError::Other(format!("ListActivatableNames: {}", e)))?;
+            if !activatable.iter().any(|name| name == SECRETS) {
+                return Err(CapError::DependencyMissing(
+                    "nothing provides the Secret Service (org.freedesktop.secrets) on the \
+                     session bus: install and start gnome-keyring, or enable it in KeePassXC \
+                     or KWallet"
+                        .into(),
+                ));
+            }
+            None
+        };
+        let (default_store, locked) = default_collection(&conn);
+        Ok(SecretStoreStatus {
+            provider,
+            default_store,
+            locked,
+        })
+    }
+
+    /// Label and lock state of the default collection; both `None` when
+    /// there is none yet (a fresh keyring before its first prompt).
+    fn default_collection(conn: &Connection) -> (Option<String>, Option<bool>) {
+        let Ok(props) = Proxy::new(
+            conn,
+            SECRETS,
+            DEFAULT_COLLECTION,
+            "org.freedesktop.DBus.Properties",
+        ) else {
+            return (None, None);
+        };
+        let get = |name: &str| {
+            props
+                .call::<_, _, OwnedValue>("Get", &(COLLECTION, name))
+                .ok()
+        };
+        let label = get("Label").and_then(|v| String::try_from(v).ok());
+        let locked = get("Locked").and_then(|v| bool::try_from(v).ok());
+        (label, locked)
+    }
+}
+
+#[cfg(windows)]
+const VAULT: &str = "$ErrorActionPreference = 'Stop'; \
+    [void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; \
+    $vault = New-Object Windows.Security.Credentials.PasswordVault; \
+    function Find { try { $vault.Retrieve($env:APPCTL_SECRET_SERVICE, $env:APPCTL_SECRET_ACCOUNT) } catch { exit 3 } }; ";
+
+/// Exit status the vault scripts use for "no such credential".
+#[cfg(windows)]
+const VAULT_NOT_FOUND: i32 = 3;
+
+#[cfg(windows)]
+fn run_vault(
+    service: &str,
+    account: &str,
+    script: &str,
+    stdin: Option<&str>,
+) -> CapResult<(Option<i32>, String, String)> {
+    let script = format!("{}{}", VAULT, script);
+    run_tool(
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", &script],
+        &[
+            ("APPCTL_SECRET_SERVICE", service),
+            ("APPCTL_SECRET_ACCOUNT", account),
+        ],
+        stdin,
+    )
+}
+
+#[cfg(windows)]
+impl SecretStoreOps for SystemSecrets {
+    fn backend(&self) -> &'static str {
+        "credential-locker"
+    }
+
+    fn get(&self, service: &str, account: &str) -> CapResult<Option<String>> {
+        let (code, out, err) = run_vault(
+            service,
+            account,
+            "$c = Find; $c.RetrievePassword(); [Console]::Out.Write($c.Password)",
+            None,
+        )?;
+        match code {
+            Some(0) => Ok(Some(out)),
+            Some(VAULT_NOT_FOUND) => Ok(None),
+            _ => Err(tool_failed("powershell", code, &err)),
+        }
+    }
+
+    fn set(&self, service: &str, account: &str, secret: &str) -> CapResult<()> {
+        let (code, _, err) = run_vault(
+            service,
+            account,
+            "$s = [Console]::In.ReadToEnd(); \
+             $vault.Add((New-Object Windows.Security.Credentials.PasswordCredential(\
+             $env:APPCTL_SECRET_SERVICE, $env:APPCTL_SECRET_ACCOUNT, $s)))",
+            Some(secret),
+        )?;
+        match code {
+            Some(0) => Ok(()),
+            _ => Err(tool_failed("powershell", code, &err)),
+        }
+    }
+
+    fn delete(&self, service: &str, account: &str) -> CapResult<bool> {
+        let (code, _, err) = run_vault(service, account, "$vault.Remove((Find))", None)?;
+        match code {
+            Some(0) => Ok(true),
+            Some(VAULT_NOT_FOUND) => Ok(false),
+            _ => Err(tool_failed("powershell", code, &err)),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+impl SecretStoreOps for SystemSecrets {
+    fn backend(&self) -> &'static str {
+        "none"
+    }
+
+    fn get(&self, _service: &str, _account: &str) -> CapResult<Option<String>> {
+        Err(CapError::Unsupported(
+            "no secret store on this platform".into(),
+        ))
+    }
+
+    fn set(&self, _service: &str, _account: &str, _secret: &str) -> CapResult<()> {
+        Err(CapError::Unsupported(
+            "no secret store on this platform".into(),
+        ))
+    }
+
+    fn delete(&self, _service: &str, _account: &str) -> CapResult<bool> {
+        Err(CapError::Unsupported(
+            "no secret store on this platform".into(),
+        ))
+    }
+}
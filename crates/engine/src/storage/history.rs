@@ -0,0 +1,500 @@
+//! Run history: every `appctl` and daemon execution appends its
+//! [`CommandResult`] to `<data_dir>/history.jsonl`, so weeks of VM runs can
+//! be compared without external infrastructure.
+//!
+//! `$APP__HISTORY` names another file, or turns recording off with `off`.
+//! Scenario steps and suite probes are recorded one entry each, tagged with
+//! their parent (`scenario:<name>`, `suite:<name>`).
+//!
+//! [`flaky`] finds checks whose status keeps flipping between runs on the
+//! same machine, for triage.
+
+use crate::context::AppContext;
+use crate::types::{CommandResult, Status};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// History file path, or `off` to disable recording.
+pub const HISTORY_ENV: &str = "APP__HISTORY";
+
+/// `$APP__HISTORY`, else `history.jsonl` in the default data dir; `None`
+/// when recording is off.
+pub fn default_path() -> Option<PathBuf> {
+    match std::env::var(HISTORY_ENV) {
+        Ok(v)
+            if matches!(
+                v.trim().to_ascii_lowercase().as_str(),
+                "off" | "0" | "false"
+            ) =>
+        {
+            None
+        }
+        Ok(v) if !v.trim().is_empty() => Some(PathBuf::from(v.trim())),
+        _ => Some(crate::context::default_data_dir().join("history.jsonl")),
+    }
+}
+
+/// One recorded execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unix seconds when the result was recorded.
+    pub recorded_at: u64,
+    /// Who ran it: `cli` or `daemon`.
+    pub source: String,
+    /// Enclosing scenario or suite, e.g. `scenario:smoke`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    pub result: CommandResult,
+}
+
+/// Which entries to return; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub command: Option<String>,
+    pub target: Option<String>,
+    pub status: Option<Status>,
+    /// Recorded at or after this Unix time.
+    pub since: Option<u64>,
+    /// Recorded before this Unix time.
+    pub until: Option<u64>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let r = &entry.result;
+        self.command.as_ref().is_none_or(|c| *c == r.command)
+            && self.target.as_ref().is_none_or(|t| *t == r.target)
+            && self.status.is_none_or(|s| s == r.status)
+            && self.since.is_none_or(|t| entry.recorded_at >= t)
+            && self.until.is_none_or(|t| entry.recorded_at < t)
+    }
+}
+
+/// Record `results` in `ctx.history_path`, if recording is on. Failures
+/// are logged, never returned: history must not change a run's outcome.
+pub fn record_results(
+    ctx: &AppContext,
+    source: &str,
+    parent: Option<&str>,
+    results: &[CommandResult],
+) {
+    let Some(path) = &ctx.history_path else {
+        return;
+    };
+    let now = ctx
+        .clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    if let Err(e) = record(path, now, source, parent, results) {
+        tracing::warn!("failed to record history in {}: {}", path.display(), e);
+    }
+}
+
+/// Append `results` to the history file at `path`, creating it if needed.
+pub fn record(
+    path: &Path,
+    recorded_at: u64,
+    source: &str,
+    parent: Option<&str>,
+    results: &[CommandResult],
+) -> std::io::Result<()> {
+    let mut lines = String::new();
+    for result in results {
+        let entry = HistoryEntry {
+            recorded_at,
+            source: source.to_string(),
+            parent: parent.map(str::to_string),
+            result: result.clone(),
+        };
+        lines.push_str(&serde_json::to_string(&entry)?);
+        lines.push('\n');
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    // One write per batch, so concurrent appenders do not interleave lines.
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(lines.as_bytes())
+}
+
+/// Every entry in the file, oldest first. A missing file is an empty
+/// history; unreadable lines are skipped.
+pub fn load(path: &Path) -> std::io::Result<Vec<HistoryEntry>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::debug!("skipping history line: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Matching entries, newest first, at most `limit` of them.
+pub fn query(
+    path: &Path,
+    filter: &HistoryFilter,
+    limit: Option<usize>,
+) -> std::io::Result<Vec<HistoryEntry>> {
+    Ok(load(path)?
+        .into_iter()
+        .rev()
+        .filter(|e| filter.matches(e))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+/// The most recent entry for `run_id`.
+pub fn find(path: &Path, run_id: &str) -> std::io::Result<Option<HistoryEntry>> {
+    Ok(load(path)?
+        .into_iter()
+        .rev()
+        .find(|e| e.result.run_id == run_id))
+}
+
+/// Drop entries recorded before `older_than` and all but the newest
+/// `keep_last`; returns how many were removed. The file is replaced
+/// atomically.
+pub fn prune(
+    path: &Path,
+    older_than: Option<u64>,
+    keep_last: Option<usize>,
+) -> std::io::Result<usize> {
+    let entries = load(path)?;
+    let total = entries.len();
+    let mut kept: Vec<HistoryEntry> = entries
+        .into_iter()
+        .filter(|e| older_than.is_none_or(|t| e.recorded_at >= t))
+        .collect();
+    if let Some(n) = keep_last {
+        kept.drain(..kept.len().saturating_sub(n));
+    }
+    let removed = total - kept.len();
+    if removed == 0 {
+        return Ok(0);
+    }
+
+    let mut text = String::new();
+    for entry in &kept {
+        text.push_str(&serde_json::to_string(entry)?);
+        text.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp, text)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(removed)
+}
+
+/// A check whose status alternates across recent runs in one environment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakyCheck {
+    pub command: String,
+    pub target: String,
+    /// Enclosing scenario or suite, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// The environment the runs share (see [`env_fingerprint`]).
+    pub fingerprint: String,
+    /// Runs considered, at most the window size; skips are not counted.
+    pub runs: usize,
+    pub passes: usize,
+    /// Fail and error results.
+    pub failures: usize,
+    /// How often the status changed between consecutive runs.
+    pub flips: usize,
+    /// `flips / (runs - 1)`: 1.0 alternates every run, 0.0 never does.
+    pub flake_rate: f64,
+    pub last_status: Status,
+    pub last_run_id: String,
+}
+
+/// What makes two results comparable: OS, architecture, host, VM,
+/// sandbox, and whether a display was available.
+pub fn env_fingerprint(env: &crate::types::EnvSummary) -> String {
+    let mut parts = vec![
+        format!("{}-{}", env.os, env.arch),
+        env.hostname
+            .clone()
+            .unwrap_or_else(|| "unknown-host".into()),
+    ];
+    if let Some(vm) = &env.vm {
+        parts.push(format!("vm={}", vm));
+    }
+    for kind in &env.sandbox {
+        if let Ok(serde_json::Value::String(k)) = serde_json::to_value(kind) {
+            parts.push(k);
+        }
+    }
+    if env.headless {
+        parts.push("headless".into());
+    }
+    parts.join(" ")
+}
+
+/// Checks (command, target, and parent) whose pass/fail status flipped in
+/// their last `window` non-skipped runs on one environment, most flaky
+/// first. Checks with fewer than `min_runs` runs are left out.
+pub fn flaky(entries: &[HistoryEntry], window: usize, min_runs: usize) -> Vec<FlakyCheck> {
+    type Key<'e> = (&'e str, &'e str, Option<&'e str>, String);
+    let mut runs: std::collections::HashMap<Key, Vec<&HistoryEntry>> =
+        std::collections::HashMap::new();
+    for e in entries.iter().filter(|e| e.result.status != Status::Skip) {
+        let key = (
+            e.result.command.as_str(),
+            e.result.target.as_str(),
+            e.parent.as_deref(),
+            env_fingerprint(&e.result.env_summary),
+        );
+        runs.entry(key).or_default().push(e);
+    }
+
+    let mut checks: Vec<FlakyCheck> = runs
+        .into_iter()
+        .filter_map(|((command, target, parent, fingerprint), mut list)| {
+            // Stable, so same-second entries keep their file order.
+            list.sort_by_key(|e| e.recorded_at);
+            let recent = &list[list.len().saturating_sub(window)..];
+            let passed = |e: &HistoryEntry| e.result.status == Status::Pass;
+            let flips = recent
+                .windows(2)
+                .filter(|w| passed(w[0]) != passed(w[1]))
+                .count();
+            if recent.len() < min_runs.max(2) || flips == 0 {
+                return None;
+            }
+            let last = recent[recent.len() - 1];
+            let passes = recent.iter().filter(|e| passed(e)).count();
+            Some(FlakyCheck {
+                command: command.to_string(),
+                target: target.to_string(),
+                parent: parent.map(str::to_string),
+                fingerprint,
+                runs: recent.len(),
+                passes,
+                failures: recent.len() - passes,
+                flips,
+                flake_rate: flips as f64 / (recent.len() - 1) as f64,
+                last_status: last.result.status,
+                last_run_id: last.result.run_id.clone(),
+            })
+        })
+        .collect();
+    checks.sort_by(|a, b| {
+        b.flake_rate
+            .total_cmp(&a.flake_rate)
+            .then(b.runs.cmp(&a.runs))
+            .then_with(|| (&a.command, &a.target).cmp(&(&b.command, &b.target)))
+    });
+    checks
+}
+
+/// Parse a point in time for filters: `30d`, `12h`, or `45m` ago (relative
+/// to `now`), a `YYYY-MM-DD` date (UTC midnight), or Unix seconds.
+pub fn parse_time(s: &str, now: u64) -> Result<u64, String> {
+    let s = s.trim();
+    let ago = |n: &str, unit: u64| {
+        n.parse::<u64>()
+            .map(|n| now.saturating_sub(n * unit))
+            .map_err(|_| format!("invalid time: {}", s))
+    };
+    if let Some(n) = s.strip_suffix('d') {
+        return ago(n, 86_400);
+    }
+    if let Some(n) = s.strip_suffix('h') {
+        return ago(n, 3_600);
+    }
+    if let Some(n) = s.strip_suffix('m') {
+        return ago(n, 60);
+    }
+    if let [y, m, d] = s.split('-').collect::<Vec<_>>()[..] {
+        let parse = |v: &str| v.parse::<i64>().map_err(|_| format!("invalid date: {}", s));
+        let (y, m, d) = (parse(y)?, parse(m)?, parse(d)?);
+        if !(1..=12).contains(&m) || !(1..=31).contains(&d) || y < 1970 {
+            return Err(format!("invalid date: {}", s));
+        }
+        return Ok(days_from_civil(y, m, d) as u64 * 86_400);
+    }
+    s.parse().map_err(|_| {
+        format!(
+            "invalid time (use 30d, 12h, YYYY-MM-DD, or Unix seconds): {}",
+            s
+        )
+    })
+}
+
+/// `secs` since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn format_time(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant).
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// Days since the Unix epoch for a proleptic Gregorian date.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{result_err, result_ok, ErrorCode};
+
+    #[test]
+    fn test_record_query_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("history.jsonl");
+        let failed = result_err("probe", "usb", "r2", 5, ErrorCode::InternalError, "x");
+        record(
+            &path,
+            100,
+            "cli",
+            None,
+            &[result_ok("call", "ping", "r1", 1)],
+        )
+        .unwrap();
+        record(&path, 200, "daemon", Some("suite:desktop"), &[failed]).unwrap();
+        record(
+            &path,
+            300,
+            "cli",
+            None,
+            &[result_ok("probe", "usb", "r3", 1)],
+        )
+        .unwrap();
+
+        let all = query(&path, &HistoryFilter::default(), None).unwrap();
+        let ids: Vec<&str> = all.iter().map(|e| e.result.run_id.as_str()).collect();
+        assert_eq!(ids, ["r3", "r2", "r1"]);
+
+        let filter = HistoryFilter {
+            command: Some("probe".into()),
+            status: Some(Status::Error),
+            ..Default::default()
+        };
+        let errors = query(&path, &filter, None).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].parent.as_deref(), Some("suite:desktop"));
+
+        let window = HistoryFilter {
+            since: Some(150),
+            until: Some(300),
+            ..Default::default()
+        };
+        assert_eq!(query(&path, &window, None).unwrap().len(), 1);
+        assert_eq!(find(&path, "r1").unwrap().unwrap().source, "cli");
+
+        assert_eq!(prune(&path, Some(150), None).unwrap(), 1);
+        assert_eq!(prune(&path, None, Some(1)).unwrap(), 1);
+        let left = load(&path).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].result.run_id, "r3");
+    }
+
+    #[test]
+    fn test_missing_file_is_empty_history() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(&dir.path().join("none.jsonl")).unwrap().is_empty());
+        assert_eq!(
+            prune(&dir.path().join("none.jsonl"), Some(1), None).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_flaky_ranks_alternating_checks() {
+        let entry = |at: u64, target: &str, pass: bool, host: &str| {
+            let mut r = if pass {
+                result_ok("probe", target, &format!("r{}", at), 1)
+            } else {
+                result_err(
+                    "probe",
+                    target,
+                    &format!("r{}", at),
+                    1,
+                    ErrorCode::IoError,
+                    "x",
+                )
+            };
+            r.env_summary.hostname = Some(host.into());
+            HistoryEntry {
+                recorded_at: at,
+                source: "cli".into(),
+                parent: None,
+                result: r,
+            }
+        };
+        let mut entries = Vec::new();
+        for (i, pass) in [true, false, true, false, true].into_iter().enumerate() {
+            entries.push(entry(i as u64, "clipboard", pass, "vm-a"));
+        }
+        for (i, pass) in [true, true, true, false, true].into_iter().enumerate() {
+            entries.push(entry(i as u64, "network", pass, "vm-a"));
+            entries.push(entry(i as u64, "filesystem", true, "vm-a"));
+        }
+        // Same check, but split across machines: neither side flips.
+        entries.push(entry(10, "usb", true, "vm-a"));
+        entries.push(entry(11, "usb", false, "vm-b"));
+        entries.push(entry(12, "usb", false, "vm-b"));
+
+        let checks = flaky(&entries, 30, 3);
+        let targets: Vec<&str> = checks.iter().map(|c| c.target.as_str()).collect();
+        assert_eq!(targets, ["clipboard", "network"]);
+        assert_eq!(checks[0].flips, 4);
+        assert_eq!(checks[0].flake_rate, 1.0);
+        assert_eq!(checks[1].flips, 2);
+        assert_eq!(checks[1].last_run_id, "r4");
+
+        // A window of 2 only sees network's last fail -> pass.
+        let recent = flaky(&entries, 2, 2);
+        assert!(recent.iter().any(|c| c.target == "network"));
+        assert!(flaky(&entries, 30, 6).is_empty());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = 1_000_000;
+        assert_eq!(parse_time("1d", now), Ok(now - 86_400));
+        assert_eq!(parse_time("2h", now), Ok(now - 7_200));
+        assert_eq!(parse_time("2024-03-01", now), Ok(1_709_251_200));
+        assert_eq!(parse_time("12345", now), Ok(12_345));
+        assert!(parse_time("yesterday", now).is_err());
+        assert!(parse_time("2024-13-01", now).is_err());
+        assert_eq!(format_time(1_709_251_200), "2024-03-01T00:00:00Z");
+    }
+}
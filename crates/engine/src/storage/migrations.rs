@@ -0,0 +1,390 @@
+//! Versioned migrations of the app data directory – settings format
+//! changes, files moving to a new cache layout – run in version order by
+//! the GUI's readiness sequence at every launch (see [`crate::readiness`])
+//! and by the `migrate` command. Applied versions are recorded in
+//! `<data_dir>/migrations.json`, so each runs once per data directory.
+//!
+//! A migration is a function over a [`Migrator`], whose helpers only
+//! report what they would do in a dry run:
+//!
+//! ```text
+//! Migration {
+//!     version: 1,
+//!     name: "move window state under state/",
+//!     run: |m| m.move_file("window-state.json", "state/window-state.json"),
+//! }
+//! ```
+//!
+//! Migrations must do nothing on a fresh data directory (the helpers skip
+//! missing files), since they also run on first launch. Data recorded by a
+//! newer build (a version this build does not know) fails the run rather
+//! than being used as-is.
+
+use crate::commands::{is_dry_run, CommandError};
+use crate::context::AppContext;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File under [`AppContext::data_dir`] holding the applied versions.
+pub const STATE_FILE: &str = "migrations.json";
+
+/// This build's migrations, in version order. Never renumber or remove a
+/// released one; add a new version instead.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Serializes runs within this process.
+static RUN_LOCK: Mutex<()> = Mutex::new(());
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub run: fn(&mut Migrator) -> Result<(), CommandError>,
+}
+
+/// A migration that has run, as persisted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    /// Unix seconds.
+    pub applied_at: u64,
+    /// What it changed.
+    pub actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MigrationRecord {
+    /// Oldest first.
+    pub applied: Vec<AppliedMigration>,
+}
+
+impl MigrationRecord {
+    /// The highest applied version, 0 if none.
+    pub fn version(&self) -> u32 {
+        self.applied.iter().map(|m| m.version).max().unwrap_or(0)
+    }
+}
+
+/// What a run did, or would do in a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    /// The data version before the run.
+    pub from_version: u32,
+    /// The data version after it (what it would be in a dry run).
+    pub to_version: u32,
+    /// The migrations run (or due), in order, with `applied_at` 0 in a dry
+    /// run.
+    pub migrations: Vec<AppliedMigration>,
+}
+
+/// The data directory as a migration sees it. Paths are relative to
+/// [`AppContext::data_dir`].
+pub struct Migrator<'a> {
+    ctx: &'a AppContext,
+    dry_run: bool,
+    actions: Vec<String>,
+}
+
+impl Migrator<'_> {
+    pub fn ctx(&self) -> &AppContext {
+        self.ctx
+    }
+
+    /// Whether changes must only be described (see [`Self::action`]).
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.ctx.data_dir.join(relative)
+    }
+
+    /// Record a change made (or, in a dry run, planned) by hand.
+    pub fn action(&mut self, action: impl Into<String>) {
+        self.actions.push(action.into());
+    }
+
+    /// Move the file `from` to `to`, creating `to`'s directory. Nothing to
+    /// do if `from` is missing; a conflict if both exist.
+    pub fn move_file(&mut self, from: &str, to: &str) -> Result<(), CommandError> {
+        let (source, dest) = (self.path(from), self.path(to));
+        let fs = self.ctx.fs();
+        if !fs.exists(&source) {
+            return Ok(());
+        }
+        if fs.exists(&dest) {
+            return Err(CommandError::Conflict(format!(
+                "cannot move {} to {}: it already exists",
+                from, to
+            )));
+        }
+        if !self.dry_run {
+            let data = fs.read_file(&source)?;
+            if let Some(parent) = dest.parent() {
+                fs.create_dir_all(parent)?;
+            }
+            fs.write_file_atomic(&dest, &data)?;
+            fs.remove_file(&source)?;
+        }
+        self.action(format!("move {} to {}", from, to));
+        Ok(())
+    }
+
+    /// Rewrite the JSON file `file` with `update`, which returns whether it
+    /// changed anything. Nothing to do if the file is missing.
+    pub fn update_json(
+        &mut self,
+        file: &str,
+        update: impl FnOnce(&mut Value) -> bool,
+    ) -> Result<(), CommandError> {
+        let path = self.path(file);
+        let fs = self.ctx.fs();
+        if !fs.exists(&path) {
+            return Ok(());
+        }
+        let mut value: Value = serde_json::from_slice(&fs.read_file(&path)?)
+            .map_err(|e| CommandError::InvalidInput(format!("{}: {}", file, e)))?;
+        if !update(&mut value) {
+            return Ok(());
+        }
+        if !self.dry_run {
+            let json = serde_json::to_vec_pretty(&value)
+                .map_err(|e| CommandError::Other(e.to_string()))?;
+            fs.write_file_atomic(&path, &json)?;
+        }
+        self.action(format!("update {}", file));
+        Ok(())
+    }
+
+    /// Remove the file `file` if it exists.
+    pub fn remove_file(&mut self, file: &str) -> Result<(), CommandError> {
+        let path = self.path(file);
+        if !self.ctx.fs().exists(&path) {
+            return Ok(());
+        }
+        if !self.dry_run {
+            self.ctx.fs().remove_file(&path)?;
+        }
+        self.action(format!("remove {}", file));
+        Ok(())
+    }
+}
+
+fn state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STATE_FILE)
+}
+
+/// The persisted record; missing means nothing has run. An unreadable
+/// record is an error, since guessing could run a migration twice.
+pub fn load(ctx: &AppContext) -> Result<MigrationRecord, CommandError> {
+    let path = state_path(&ctx.data_dir);
+    if !ctx.fs().exists(&path) {
+        return Ok(MigrationRecord::default());
+    }
+    serde_json::from_slice(&ctx.fs().read_file(&path)?)
+        .map_err(|e| CommandError::Other(format!("{}: {}", STATE_FILE, e)))
+}
+
+fn save(ctx: &AppContext, record: &MigrationRecord) -> Result<(), CommandError> {
+    let json = serde_json::to_vec_pretty(record).map_err(|e| CommandError::Other(e.to_string()))?;
+    ctx.fs().create_dir_all(&ctx.data_dir)?;
+    ctx.fs()
+        .write_file_atomic(&state_path(&ctx.data_dir), &json)?;
+    Ok(())
+}
+
+/// Run the `migrations` not yet applied to the data directory, in version
+/// order, recording each as it succeeds. Stops at the first failure, with
+/// the ones before it kept.
+pub fn run(
+    ctx: &AppContext,
+    migrations: &[Migration],
+    dry_run: bool,
+) -> Result<MigrationReport, CommandError> {
+    let _guard = RUN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut record = load(ctx)?;
+    let known = migrations.iter().map(|m| m.version).max().unwrap_or(0);
+    if record.version() > known {
+        return Err(CommandError::Unsupported(format!(
+            "the app data is at version {}, newer than this build's {}; update the app",
+            record.version(),
+            known
+        )));
+    }
+
+    let mut pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| !record.applied.iter().any(|a| a.version == m.version))
+        .collect();
+    pending.sort_by_key(|m| m.version);
+    let mut report = MigrationReport {
+        dry_run,
+        from_version: record.version(),
+        to_version: record.version(),
+        migrations: Vec::new(),
+    };
+    for migration in pending {
+        let mut migrator = Migrator {
+            ctx,
+            dry_run,
+            actions: Vec::new(),
+        };
+        (migration.run)(&mut migrator).map_err(|e| {
+            CommandError::Other(format!(
+                "migration {} ({}) failed: {}",
+                migration.version, migration.name, e
+            ))
+        })?;
+        let applied = AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+            applied_at: if dry_run { 0 } else { now_secs(ctx) },
+            actions: migrator.actions,
+        };
+        if !dry_run {
+            record.applied.push(applied.clone());
+            save(ctx, &record)?;
+        }
+        report.to_version = report.to_version.max(migration.version);
+        report.migrations.push(applied);
+    }
+    Ok(report)
+}
+
+fn now_secs(ctx: &AppContext) -> u64 {
+    ctx.clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `migrate` – bring the app data directory up to this build's version.
+///
+/// Args: `{ "dry_run"?: true }`.
+/// Returns: `{ "dry_run", "from_version", "to_version", "migrations":
+/// [{version, name, applied_at, actions}] }`, listing what is due instead
+/// of running it in a dry run.
+pub(crate) fn cmd_migrate(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let report = run(ctx, MIGRATIONS, is_dry_run(&args, ctx))?;
+    serde_json::to_value(report).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+/// `migrate_status` – the data version and the migrations applied to it.
+///
+/// Returns: `{ "version", "latest", "applied": [...], "pending": [{version,
+/// name}] }`.
+pub(crate) fn cmd_migrate_status(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let record = load(ctx)?;
+    let pending: Vec<Value> = MIGRATIONS
+        .iter()
+        .filter(|m| !record.applied.iter().any(|a| a.version == m.version))
+        .map(|m| serde_json::json!({ "version": m.version, "name": m.name }))
+        .collect();
+    Ok(serde_json::json!({
+        "version": record.version(),
+        "latest": MIGRATIONS.iter().map(|m| m.version).max().unwrap_or(0),
+        "applied": record.applied,
+        "pending": pending,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "move window state",
+            run: |m| m.move_file("window-state.json", "state/window-state.json"),
+        },
+        Migration {
+            version: 2,
+            name: "rename window width",
+            run: |m| {
+                m.update_json("state/window-state.json", |v| {
+                    match v["main"].as_object_mut().and_then(|o| o.remove("w")) {
+                        Some(w) => {
+                            v["main"]["width"] = w;
+                            true
+                        }
+                        None => false,
+                    }
+                })
+            },
+        },
+    ];
+
+    fn ctx(dir: &Path) -> AppContext {
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.to_path_buf();
+        ctx
+    }
+
+    #[test]
+    fn test_runs_pending_migrations_once_and_dry_run_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        std::fs::write(
+            dir.path().join("window-state.json"),
+            r#"{"main": {"w": 800}}"#,
+        )
+        .unwrap();
+
+        let plan = run(&ctx, TEST_MIGRATIONS, true).unwrap();
+        assert_eq!((plan.from_version, plan.to_version), (0, 2));
+        assert_eq!(
+            plan.migrations[0].actions,
+            ["move window-state.json to state/window-state.json"]
+        );
+        assert!(dir.path().join("window-state.json").exists());
+        assert!(!dir.path().join(STATE_FILE).exists());
+
+        let report = run(&ctx, TEST_MIGRATIONS, false).unwrap();
+        assert_eq!(report.migrations.len(), 2);
+        let moved: Value = serde_json::from_slice(
+            &std::fs::read(dir.path().join("state/window-state.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(moved["main"]["width"], 800);
+        assert_eq!(load(&ctx).unwrap().version(), 2);
+
+        let again = run(&ctx, TEST_MIGRATIONS, false).unwrap();
+        assert!(again.migrations.is_empty());
+        assert_eq!(again.from_version, 2);
+    }
+
+    #[test]
+    fn test_fresh_data_dir_and_newer_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let report = run(&ctx, TEST_MIGRATIONS, false).unwrap();
+        assert!(report.migrations.iter().all(|m| m.actions.is_empty()));
+
+        let err = run(&ctx, &TEST_MIGRATIONS[..1], false).unwrap_err();
+        assert!(matches!(err, CommandError::Unsupported(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_failure_keeps_earlier_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        std::fs::write(dir.path().join("window-state.json"), "{}").unwrap();
+        std::fs::create_dir(dir.path().join("state")).unwrap();
+        std::fs::write(dir.path().join("state/window-state.json"), "not json").unwrap();
+
+        let err = run(&ctx, TEST_MIGRATIONS, false).unwrap_err();
+        assert!(err.to_string().contains("migration 1"), "{}", err);
+        assert_eq!(load(&ctx).unwrap().version(), 0);
+
+        std::fs::remove_file(dir.path().join("window-state.json")).unwrap();
+        let err = run(&ctx, TEST_MIGRATIONS, false).unwrap_err();
+        assert!(err.to_string().contains("migration 2"), "{}", err);
+        assert_eq!(load(&ctx).unwrap().version(), 1);
+    }
+}
@@ -0,0 +1,12 @@
+//! What the engine keeps in the app data directory: history, consents,
+//! credentials, profiles, the task queue, caches, migrations, and backups.
+
+pub mod backup;
+pub mod cache;
+pub mod consent;
+pub mod credentials;
+pub mod history;
+pub mod migrations;
+pub mod profile;
+pub mod queue;
+pub mod zip;
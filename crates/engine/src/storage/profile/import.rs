@@ -0,0 +1,245 @@
+//! Importing a profile: verified, compared setting by setting, and applied
+//! unless a conflict says to keep the local value.
+
+use super::Profile;
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{AutostartEntry, WindowGeometry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// What to do with a setting whose local value differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnConflict {
+    Overwrite,
+    Keep,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Settings set (or, in a dry run, to be set).
+    pub applied: Vec<String>,
+    /// Settings already at the profile's value.
+    pub unchanged: Vec<String>,
+    /// Settings whose local value differed, with what was done about it.
+    pub conflicts: Vec<Value>,
+    /// Settings this machine cannot take, and why.
+    pub skipped: Vec<Value>,
+}
+
+impl ImportReport {
+    /// Sort one setting into the report; whether to apply it.
+    fn compare<T: PartialEq + Serialize>(
+        &mut self,
+        name: String,
+        current: Option<&T>,
+        incoming: &T,
+        on_conflict: OnConflict,
+    ) -> bool {
+        match current {
+            Some(current) if current == incoming => {
+                self.unchanged.push(name);
+                false
+            }
+            Some(current) => {
+                let apply = on_conflict == OnConflict::Overwrite;
+                self.conflicts.push(serde_json::json!({
+                    "setting": name,
+                    "current": current,
+                    "incoming": incoming,
+                    "resolution": if apply { "overwritten" } else { "kept" },
+                }));
+                if apply {
+                    self.applied.push(name);
+                }
+                apply
+            }
+            None => {
+                self.applied.push(name);
+                true
+            }
+        }
+    }
+
+    fn skip(&mut self, name: String, reason: impl Into<String>) {
+        self.skipped
+            .push(serde_json::json!({ "setting": name, "reason": reason.into() }));
+    }
+}
+
+/// Apply a verified profile's settings.
+pub fn import(
+    ctx: &AppContext,
+    profile: &Profile,
+    on_conflict: OnConflict,
+    dry_run: bool,
+) -> Result<ImportReport, CommandError> {
+    let mut report = ImportReport {
+        dry_run,
+        ..ImportReport::default()
+    };
+
+    if let Some(consent) = &profile.settings.consent {
+        let record = crate::consent::load(ctx);
+        let mut features = BTreeMap::new();
+        for (name, granted) in &consent.features {
+            let setting = format!("consent.features.{}", name);
+            if !ctx.consent.features.contains(name) {
+                report.skip(setting, "not a consent feature in this app");
+            } else if report.compare(
+                setting,
+                record.features.get(name).map(|f| &f.granted),
+                granted,
+                on_conflict,
+            ) {
+                features.insert(name.as_str(), *granted);
+            }
+        }
+        let mut policies = BTreeMap::new();
+        for (name, version) in &consent.policies {
+            let setting = format!("consent.policies.{}", name);
+            match ctx.consent.policies.get(name) {
+                None => report.skip(setting, "not a policy in this app"),
+                Some(current) if current != version => report.skip(
+                    setting,
+                    format!("accepted at {}; this app asks for {}", version, current),
+                ),
+                Some(_) => {
+                    let accepted = record.policies.get(name).map(|p| &p.version);
+                    // Acceptance of the current version is never taken back.
+                    if report.compare(
+                        setting,
+                        accepted.filter(|v| *v == version),
+                        version,
+                        on_conflict,
+                    ) {
+                        policies.insert(name.as_str(), version.as_str());
+                    }
+                }
+            }
+        }
+        if !dry_run && (!features.is_empty() || !policies.is_empty()) {
+            crate::consent::apply(ctx, &policies, &features)?;
+        }
+    }
+
+    if let Some(windows) = &profile.settings.windows {
+        let mut states = crate::windows::load(ctx.fs(), &ctx.data_dir);
+        let mut changed = false;
+        for (label, geometry) in windows {
+            let current: Option<&WindowGeometry> = states.get(label);
+            if report.compare(format!("windows.{}", label), current, geometry, on_conflict) {
+                states.insert(label.clone(), *geometry);
+                changed = true;
+            }
+        }
+        if changed && !dry_run {
+            let json = serde_json::to_vec_pretty(&states)
+                .map_err(|e| CommandError::Other(e.to_string()))?;
+            ctx.fs().create_dir_all(&ctx.data_dir)?;
+            ctx.fs()
+                .write_file_atomic(&crate::windows::state_path(&ctx.data_dir), &json)?;
+        }
+    }
+
+    if let Some(enabled) = profile.settings.autostart {
+        match ctx.autostart().status(&ctx.app_id) {
+            Err(e) => report.skip("autostart".into(), e.to_string()),
+            Ok(status) => {
+                // Off is the default, so only an installed entry is a local choice.
+                let current = status.enabled.then_some(&status.enabled);
+                let apply = report.compare("autostart".into(), current, &enabled, on_conflict)
+                    && status.enabled != enabled;
+                if apply && !dry_run {
+                    if enabled {
+                        ctx.autostart().enable(&AutostartEntry {
+                            app_id: ctx.app_id.clone(),
+                            name: ctx.app_name.clone(),
+                            exec: std::env::current_exe()?,
+                            args: Vec::new(),
+                        })?;
+                    } else {
+                        ctx.autostart().disable(&ctx.app_id)?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::format_time;
+    use crate::profile::tests::{ctx, geometry};
+    use crate::profile::{export, ConsentSettings, ProfileSettings, FORMAT};
+
+    #[test]
+    fn test_export_then_import_reports_conflicts() {
+        let source = tempfile::tempdir().unwrap();
+        let source_ctx = ctx(source.path());
+        crate::consent::set_feature(&source_ctx, "telemetry", true).unwrap();
+        crate::consent::apply(
+            &source_ctx,
+            &BTreeMap::from([("privacy", "2")]),
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        crate::windows::save(source_ctx.fs(), source.path(), "main", geometry(800)).unwrap();
+        let profile = export(&source_ctx, &["consent".into(), "windows".into()]).unwrap();
+        assert!(profile.settings.autostart.is_none());
+
+        let target = tempfile::tempdir().unwrap();
+        let target_ctx = ctx(target.path());
+        crate::windows::save(target_ctx.fs(), target.path(), "main", geometry(1024)).unwrap();
+        crate::windows::save(target_ctx.fs(), target.path(), "tools", geometry(300)).unwrap();
+
+        let plan = import(&target_ctx, &profile, OnConflict::Keep, true).unwrap();
+        assert_eq!(
+            plan.applied,
+            ["consent.features.telemetry", "consent.policies.privacy"]
+        );
+        assert_eq!(plan.conflicts[0]["setting"], "windows.main");
+        assert_eq!(plan.conflicts[0]["resolution"], "kept");
+        assert!(!crate::consent::granted(&target_ctx, "telemetry"));
+
+        let report = import(&target_ctx, &profile, OnConflict::Overwrite, false).unwrap();
+        assert_eq!(report.conflicts[0]["current"]["width"], 1024);
+        assert!(crate::consent::granted(&target_ctx, "telemetry"));
+        let states = crate::windows::load(target_ctx.fs(), target.path());
+        assert_eq!(states["main"].width, 800);
+        assert_eq!(states["tools"].width, 300);
+
+        let again = import(&target_ctx, &profile, OnConflict::Overwrite, false).unwrap();
+        assert!(again.applied.is_empty() && again.conflicts.is_empty());
+        assert_eq!(again.unchanged.len(), 3);
+    }
+
+    #[test]
+    fn test_import_skips_what_this_app_cannot_take() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let profile = Profile {
+            format: FORMAT,
+            app_id: ctx.app_id.clone(),
+            created_at: format_time(0),
+            app_version: None,
+            settings: ProfileSettings {
+                consent: Some(ConsentSettings {
+                    features: BTreeMap::from([("location".into(), true)]),
+                    policies: BTreeMap::from([("privacy".into(), "1".into())]),
+                }),
+                ..ProfileSettings::default()
+            },
+            signature: None,
+        };
+        let report = import(&ctx, &profile, OnConflict::Overwrite, false).unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(report.skipped[1]["setting"], "consent.policies.privacy");
+    }
+}
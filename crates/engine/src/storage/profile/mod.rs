@@ -0,0 +1,398 @@
+//! Settings profiles: the user's own settings as one signed JSON file,
+//! exported on one machine and imported on others – e.g. to provision a
+//! fleet of VMs with a preconfigured app.
+//!
+//! A profile holds these sections, each optional:
+//!
+//! - `consent` – granted features and accepted policy versions (see
+//!   [`crate::consent`])
+//! - `windows` – saved window geometry by label (see [`crate::windows`]),
+//!   applied at the GUI's next launch
+//! - `autostart` – whether the app launches at login
+//!
+//! Secrets never go into a profile. It is signed with HMAC-SHA256 under a
+//! key shared by the machines (`$APP__PROFILE_KEY`, else the `profile/
+//! signing_key` credential), so an import can tell that nobody changed it
+//! on the way:
+//!
+//! ```json
+//! {"format": 1, "app_id": "com.eito.tauri-app", "created_at": "…",
+//!  "settings": {"consent": {"features": {"telemetry": false}}, "autostart": true},
+//!  "signature": "hmac-sha256:…"}
+//! ```
+//!
+//! An import checks the schema and the signature, then compares every
+//! setting with the local one. A local value that differs is a conflict:
+//! `overwrite` (the default) replaces it, `keep` leaves it. Settings this
+//! machine cannot take (an unknown feature, a policy at another version)
+//! are skipped and reported.
+
+mod import;
+
+pub use import::{import, ImportReport, OnConflict};
+
+use crate::commands::{is_dry_run, CommandError};
+use crate::context::AppContext;
+use crate::export::hex;
+use crate::history::format_time;
+use crate::windows::WindowStates;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Environment variable holding the signing key.
+pub const PROFILE_KEY_ENV: &str = "APP__PROFILE_KEY";
+
+/// Credential (namespace, key) holding the signing key when the
+/// environment does not.
+pub const KEY_CREDENTIAL: (&str, &str) = ("profile", "signing_key");
+
+/// Bumped when a change to the profile schema would break older builds.
+pub const FORMAT: u32 = 1;
+
+const SIGNATURE_PREFIX: &str = "hmac-sha256:";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub format: u32,
+    pub app_id: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub app_version: Option<String>,
+    pub settings: ProfileSettings,
+    /// `hmac-sha256:<hex>` over the profile without it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consent: Option<ConsentSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub windows: Option<WindowStates>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autostart: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsentSettings {
+    /// Feature to whether it is granted.
+    pub features: BTreeMap<String, bool>,
+    /// Policy to the accepted version.
+    pub policies: BTreeMap<String, String>,
+}
+
+/// Every section name, in profile order.
+pub const SECTIONS: &[&str] = &["consent", "windows", "autostart"];
+
+/// The signing key, if one is set up.
+fn signing_key(ctx: &AppContext) -> Option<ring::hmac::Key> {
+    let secret = match std::env::var(PROFILE_KEY_ENV)
+        .ok()
+        .filter(|k| !k.is_empty())
+    {
+        Some(key) => Some(key),
+        None => crate::credentials::get_secret(ctx, KEY_CREDENTIAL.0, KEY_CREDENTIAL.1)
+            .unwrap_or_default(),
+    };
+    secret.map(|s| ring::hmac::Key::new(ring::hmac::HMAC_SHA256, s.as_bytes()))
+}
+
+fn no_key() -> CommandError {
+    CommandError::InvalidInput(format!(
+        "no profile signing key: set ${} or the {}/{} credential",
+        PROFILE_KEY_ENV, KEY_CREDENTIAL.0, KEY_CREDENTIAL.1
+    ))
+}
+
+/// The bytes the signature covers: the profile without its signature.
+fn signed_bytes(profile: &Profile) -> Vec<u8> {
+    let unsigned = Profile {
+        signature: None,
+        ..profile.clone()
+    };
+    serde_json::to_vec(&unsigned).unwrap_or_default()
+}
+
+/// This machine's settings, in the named `sections` (all if empty).
+pub fn export(ctx: &AppContext, sections: &[String]) -> Result<Profile, CommandError> {
+    if let Some(unknown) = sections.iter().find(|s| !SECTIONS.contains(&s.as_str())) {
+        return Err(CommandError::InvalidInput(format!(
+            "unknown section '{}'; expected one of {}",
+            unknown,
+            SECTIONS.join(", ")
+        )));
+    }
+    let wants = |section: &str| sections.is_empty() || sections.iter().any(|s| s == section);
+    let mut settings = ProfileSettings::default();
+    if wants("consent") {
+        let record = crate::consent::load(ctx);
+        settings.consent = Some(ConsentSettings {
+            features: record
+                .features
+                .iter()
+                .map(|(name, f)| (name.clone(), f.granted))
+                .collect(),
+            policies: record
+                .policies
+                .iter()
+                .map(|(name, p)| (name.clone(), p.version.clone()))
+                .collect(),
+        });
+    }
+    if wants("windows") {
+        settings.windows = Some(crate::windows::load(ctx.fs(), &ctx.data_dir));
+    }
+    if wants("autostart") {
+        // Left out where this machine has no autostart to read.
+        settings.autostart = ctx.autostart().status(&ctx.app_id).ok().map(|s| s.enabled);
+    }
+    let now = ctx
+        .clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(Profile {
+        format: FORMAT,
+        app_id: ctx.app_id.clone(),
+        created_at: format_time(now),
+        app_version: crate::host::app_version(),
+        settings,
+        signature: None,
+    })
+}
+
+/// Sign `profile` with the configured key.
+pub fn sign(ctx: &AppContext, profile: &mut Profile) -> Result<(), CommandError> {
+    let key = signing_key(ctx).ok_or_else(no_key)?;
+    let tag = ring::hmac::sign(&key, &signed_bytes(profile));
+    profile.signature = Some(format!("{}{}", SIGNATURE_PREFIX, hex(tag.as_ref())));
+    Ok(())
+}
+
+/// Parse and check a profile: its schema, format, app, and signature
+/// (required unless `allow_unsigned`).
+pub fn verify(
+    ctx: &AppContext,
+    profile: Value,
+    allow_unsigned: bool,
+) -> Result<Profile, CommandError> {
+    let format = profile.get("format").and_then(Value::as_u64).unwrap_or(0);
+    if format > FORMAT as u64 {
+        return Err(CommandError::Unsupported(format!(
+            "profile format {} is newer than this build's {}; update the app",
+            format, FORMAT
+        )));
+    }
+    let profile: Profile = serde_json::from_value(profile)
+        .map_err(|e| CommandError::InvalidInput(format!("not a valid profile: {}", e)))?;
+    if profile.app_id != ctx.app_id {
+        return Err(CommandError::InvalidInput(format!(
+            "the profile is for {}, not {}",
+            profile.app_id, ctx.app_id
+        )));
+    }
+    match &profile.signature {
+        None if allow_unsigned => {}
+        None => {
+            return Err(CommandError::PermissionDenied(
+                "the profile is not signed (pass allow_unsigned to import it anyway)".into(),
+            ))
+        }
+        Some(signature) => {
+            let key = signing_key(ctx).ok_or_else(no_key)?;
+            let tag = signature
+                .strip_prefix(SIGNATURE_PREFIX)
+                .filter(|t| t.len() % 2 == 0 && t.bytes().all(|b| b.is_ascii_hexdigit()))
+                .map(|t| {
+                    (0..t.len())
+                        .step_by(2)
+                        .map(|i| u8::from_str_radix(&t[i..i + 2], 16).unwrap_or(0))
+                        .collect::<Vec<u8>>()
+                })
+                .unwrap_or_default();
+            ring::hmac::verify(&key, &signed_bytes(&profile), &tag).map_err(|_| {
+                CommandError::PermissionDenied(
+                    "the profile's signature does not match; it was changed or signed with another key"
+                        .into(),
+                )
+            })?;
+        }
+    }
+    Ok(profile)
+}
+
+fn path_arg(args: &Value) -> Result<Option<PathBuf>, CommandError> {
+    match args.get("path") {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_str()
+            .map(|s| Some(PathBuf::from(s)))
+            .ok_or_else(|| CommandError::InvalidInput("'path' must be a string".into())),
+    }
+}
+
+fn bool_arg(args: &Value, key: &str) -> Result<bool, CommandError> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(false),
+        Some(v) => v
+            .as_bool()
+            .ok_or_else(|| CommandError::InvalidInput(format!("'{}' must be a boolean", key))),
+    }
+}
+
+/// `profile_export` – the user's settings as a signed profile.
+///
+/// Args: `{ "path"?: "profile.json", "sections"?: ["consent", "windows",
+/// "autostart"], "unsigned"?: false }`; without a signing key only an
+/// `unsigned` export works.
+/// Returns: `{ "path" }` when written to `path`, else `{ "profile" }`; a
+/// dry run reports the write it would make.
+pub(crate) fn cmd_profile_export(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let sections: Vec<String> = match args.get("sections") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(v) => serde_json::from_value(v.clone()).map_err(|_| {
+            CommandError::InvalidInput("'sections' must be a list of section names".into())
+        })?,
+    };
+    let mut profile = export(ctx, &sections)?;
+    if !bool_arg(&args, "unsigned")? {
+        sign(ctx, &mut profile)?;
+    }
+    let Some(path) = path_arg(&args)? else {
+        return Ok(serde_json::json!({ "profile": profile }));
+    };
+    let json =
+        serde_json::to_vec_pretty(&profile).map_err(|e| CommandError::Other(e.to_string()))?;
+    if is_dry_run(&args, ctx) {
+        return Ok(serde_json::json!({
+            "dry_run": true,
+            "action": "write",
+            "path": path.display().to_string(),
+            "bytes": json.len(),
+            "signed": profile.signature.is_some(),
+            "exists": ctx.fs().exists(&path),
+        }));
+    }
+    ctx.fs().write_file_atomic(&path, &json)?;
+    Ok(serde_json::json!({
+        "path": path.display().to_string(),
+        "signed": profile.signature.is_some(),
+    }))
+}
+
+/// `profile_import` – apply a profile's settings.
+///
+/// Args: `{ "path": "profile.json" | "profile": {...}, "on_conflict"?:
+/// "overwrite" | "keep", "allow_unsigned"?: false }`.
+/// Returns: `{ "dry_run", "applied": [...], "unchanged": [...], "conflicts":
+/// [{setting, current, incoming, resolution}], "skipped": [{setting,
+/// reason}] }`; a dry run reports without changing anything. A profile
+/// that fails its schema or signature check changes nothing.
+pub(crate) fn cmd_profile_import(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let profile = match (path_arg(&args)?, args.get("profile")) {
+        (Some(path), _) => serde_json::from_slice(&ctx.fs().read_file(&path)?).map_err(|e| {
+            CommandError::InvalidInput(format!("{} is not JSON: {}", path.display(), e))
+        })?,
+        (None, Some(profile)) if !profile.is_null() => profile.clone(),
+        _ => {
+            return Err(CommandError::InvalidInput(
+                "pass 'path' or 'profile'".into(),
+            ))
+        }
+    };
+    let on_conflict = match args.get("on_conflict") {
+        None | Some(Value::Null) => OnConflict::Overwrite,
+        Some(v) => serde_json::from_value(v.clone()).map_err(|_| {
+            CommandError::InvalidInput("'on_conflict' must be 'overwrite' or 'keep'".into())
+        })?,
+    };
+    let profile = verify(ctx, profile, bool_arg(&args, "allow_unsigned")?)?;
+    let report = import(ctx, &profile, on_conflict, is_dry_run(&args, ctx))?;
+    serde_json::to_value(report).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::traits::WindowGeometry;
+
+    pub(crate) fn ctx(dir: &std::path::Path) -> AppContext {
+        let mut ctx = AppContext::default_headless()
+            .with_secrets(Box::new(crate::credentials::MemorySecrets::default()));
+        ctx.data_dir = dir.to_path_buf();
+        ctx.consent.policies = BTreeMap::from([("privacy".into(), "2".into())]);
+        ctx
+    }
+
+    pub(crate) fn geometry(width: u32) -> WindowGeometry {
+        WindowGeometry {
+            x: 0,
+            y: 0,
+            width,
+            height: 600,
+            maximized: false,
+        }
+    }
+
+    pub(crate) fn set_key(ctx: &AppContext, key: &str) {
+        let account = crate::credentials::account(KEY_CREDENTIAL.0, KEY_CREDENTIAL.1).unwrap();
+        ctx.secrets().set(&ctx.app_id, &account, key).unwrap();
+    }
+
+    #[test]
+    fn test_export_dry_run_plans_the_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let path = dir.path().join("profile.json");
+        let args = serde_json::json!({ "path": path, "unsigned": true, "dry_run": true });
+        let r = crate::commands::CommandRegistry::new().execute("profile_export", args, &ctx);
+        let data = r.data.unwrap();
+        assert_eq!(data["dry_run"], true);
+        assert_eq!(data["path"], path.display().to_string());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_verify_checks_schema_app_and_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let profile = export(&ctx, &["consent".into()]).unwrap();
+        let value = |p: &Profile| serde_json::to_value(p).unwrap();
+
+        assert!(matches!(
+            verify(&ctx, value(&profile), false),
+            Err(CommandError::PermissionDenied(_))
+        ));
+        assert!(verify(&ctx, value(&profile), true).is_ok());
+
+        let mut bad = value(&profile);
+        bad["settings"]["theme"] = "dark".into();
+        let err = verify(&ctx, bad, true).unwrap_err();
+        assert!(err.to_string().contains("unknown field"), "{}", err);
+        let mut other = value(&profile);
+        other["app_id"] = "com.example.other".into();
+        assert!(verify(&ctx, other, true).is_err());
+
+        let mut signed = profile;
+        assert!(sign(&ctx, &mut signed).is_err());
+        set_key(&ctx, "fleet-key");
+        sign(&ctx, &mut signed).unwrap();
+        assert!(verify(&ctx, value(&signed), false).is_ok());
+        let mut tampered = value(&signed);
+        tampered["settings"]["autostart"] = true.into();
+        assert!(matches!(
+            verify(&ctx, tampered, false),
+            Err(CommandError::PermissionDenied(_))
+        ));
+        set_key(&ctx, "another-key");
+        assert!(verify(&ctx, value(&signed), false).is_err());
+        assert!(export(&ctx, &["theme".into()]).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! Durable task queue – command invocations persisted to
+//! `<data_dir>/queue.json` and run in the background, for work such as
+//! uploads and sync that must survive the app being closed, crashing, or
+//! its VM being snapshotted and restored.
+//!
+//! `queue_add` records a job; the GUI's worker (see [`run_worker`]) runs
+//! due jobs one at a time through the command registry, oldest first, and
+//! `appctl queue run` drains them headlessly. A failed job is retried
+//! after `retry_delay_secs`, doubling each time, until `max_attempts`;
+//! then it stays `failed` until `queue_retry`. Bad arguments and unknown
+//! commands (`INVALID_INPUT`) fail at once:
+//!
+//! ```yaml
+//! queue:
+//!   max_attempts: 5
+//!   retry_delay_secs: 30
+//!   keep_done: 100
+//! ```
+//!
+//! Every change to the file happens under an advisory lock on
+//! `<data_dir>/queue.lock`, so the GUI and `appctl queue run` can share a
+//! queue. A `running` job records the process running it; one whose
+//! process has gone was interrupted and is run again, so queued commands
+//! must be safe to repeat. Progress goes out on the event bus, with the
+//! job id as the run id:
+//!
+//! - `queue:started` – the [`Job`] about to run
+//! - `queue:finished` – the [`Job`] after it ran
+
+mod worker;
+
+pub use worker::{recover, run_pending, run_worker};
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{CapError, LockMode};
+use crate::types::ErrorInfo;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// File under [`AppContext::data_dir`] holding the jobs.
+pub const STATE_FILE: &str = "queue.json";
+
+/// File under [`AppContext::data_dir`] locked while the jobs change.
+pub const LOCK_FILE: &str = "queue.lock";
+
+/// How long a change waits for another process to release the queue.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the worker looks for due jobs.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Serializes reads and writes of the queue file within this process;
+/// [`LOCK_FILE`] does so across processes.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Retry and retention settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueueConfig {
+    /// Runs of a job before it is left `failed`, unless `queue_add` says
+    /// otherwise.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles with each further one.
+    pub retry_delay_secs: u64,
+    /// `done` jobs kept for `queue_list`, newest first; 0 keeps all.
+    pub keep_done: usize,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            retry_delay_secs: 30,
+            keep_done: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+/// A queued command invocation, as persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub command: String,
+    pub args: Value,
+    pub status: JobStatus,
+    /// Runs so far, including one interrupted by a restart.
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// Earliest time (Unix seconds) a retry runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<u64>,
+    /// The command's data, once `done`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Why the last run failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+    /// The process running the job, while `running`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<JobOwner>,
+}
+
+/// A process that claimed a job. `instance` tells this process apart from
+/// an earlier one that had the same pid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobOwner {
+    pub pid: u32,
+    pub instance: String,
+}
+
+impl JobOwner {
+    fn current() -> Self {
+        static INSTANCE: OnceLock<String> = OnceLock::new();
+        Self {
+            pid: std::process::id(),
+            instance: INSTANCE
+                .get_or_init(|| uuid::Uuid::new_v4().to_string())
+                .clone(),
+        }
+    }
+
+    /// Whether the owning process may still be running the job. A pid
+    /// that cannot be looked up counts as gone.
+    fn is_alive(&self) -> bool {
+        let current = Self::current();
+        if self.pid == current.pid {
+            return self.instance == current.instance;
+        }
+        crate::processes::is_running(self.pid).unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct QueueFile {
+    /// Oldest first.
+    jobs: Vec<Job>,
+}
+
+/// The persisted jobs, oldest first; none if the file is missing.
+pub fn list(ctx: &AppContext) -> Result<Vec<Job>, CommandError> {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(load(ctx)?.jobs)
+}
+
+/// Queue `command` with `args`, to run as soon as the worker gets to it.
+pub fn enqueue(
+    ctx: &AppContext,
+    command: &str,
+    args: Value,
+    max_attempts: Option<u32>,
+) -> Result<Job, CommandError> {
+    if command.is_empty() || command.starts_with("queue_") {
+        return Err(CommandError::InvalidInput(format!(
+            "cannot queue command {:?}",
+            command
+        )));
+    }
+    let now = now_secs(ctx);
+    let job = Job {
+        id: ctx.new_run_id(),
+        command: command.to_string(),
+        args,
+        status: JobStatus::Pending,
+        attempts: 0,
+        max_attempts: max_attempts.unwrap_or(ctx.queue.max_attempts).max(1),
+        created_at: now,
+        updated_at: now,
+        not_before: None,
+        result: None,
+        error: None,
+        owner: None,
+    };
+    update(ctx, |file| {
+        file.jobs.push(job.clone());
+        Ok(())
+    })?;
+    Ok(job)
+}
+
+/// Put a `failed` or `pending` job back in line to run now, with its
+/// attempts reset.
+pub fn retry(ctx: &AppContext, id: &str) -> Result<Job, CommandError> {
+    let now = now_secs(ctx);
+    update(ctx, |file| {
+        let job = file
+            .jobs
+            .iter_mut()
+            .find(|j| j.id == id)
+            .ok_or_else(|| CommandError::InvalidInput(format!("no queued job {}", id)))?;
+        if matches!(job.status, JobStatus::Running | JobStatus::Done) {
+            return Err(CommandError::Conflict(format!(
+                "job {} is {}",
+                id,
+                status_name(job.status)
+            )));
+        }
+        job.status = JobStatus::Pending;
+        job.attempts = 0;
+        job.not_before = None;
+        job.updated_at = now;
+        Ok(job.clone())
+    })
+}
+
+fn load(ctx: &AppContext) -> Result<QueueFile, CommandError> {
+    let path = ctx.data_dir.join(STATE_FILE);
+    if !ctx.fs().exists(&path) {
+        return Ok(QueueFile::default());
+    }
+    serde_json::from_slice(&ctx.fs().read_file(&path)?)
+        .map_err(|e| CommandError::Other(format!("{}: {}", STATE_FILE, e)))
+}
+
+/// Load, change, and save the queue under both locks; nothing is written
+/// if `f` fails. Filesystems that cannot lock get the in-process lock
+/// only.
+fn update<T>(
+    ctx: &AppContext,
+    f: impl FnOnce(&mut QueueFile) -> Result<T, CommandError>,
+) -> Result<T, CommandError> {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    ctx.fs().create_dir_all(&ctx.data_dir)?;
+    let lock = match ctx.fs().lock(
+        &ctx.data_dir.join(LOCK_FILE),
+        LockMode::Exclusive,
+        LOCK_TIMEOUT,
+    ) {
+        Ok(lock) => Some(lock),
+        Err(CapError::Unsupported(e)) => {
+            tracing::debug!("queue: {}", e);
+            None
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let out = (|| {
+        let mut file = load(ctx)?;
+        let out = f(&mut file)?;
+        let json =
+            serde_json::to_vec_pretty(&file).map_err(|e| CommandError::Other(e.to_string()))?;
+        ctx.fs()
+            .write_file_atomic(&ctx.data_dir.join(STATE_FILE), &json)?;
+        Ok(out)
+    })();
+    if let Some(lock) = lock {
+        ctx.fs().unlock(lock)?;
+    }
+    out
+}
+
+fn status_name(status: JobStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn now_secs(ctx: &AppContext) -> u64 {
+    ctx.clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// `queue_add` – queue a command to run in the background, surviving
+/// restarts.
+///
+/// Args: `{ "command": "...", "args"?: {...}, "max_attempts"?: n }`.
+/// Returns: the queued job.
+pub(crate) fn cmd_queue_add(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let command = args["command"]
+        .as_str()
+        .ok_or_else(|| CommandError::InvalidInput("missing 'command'".into()))?;
+    let max_attempts = match &args["max_attempts"] {
+        Value::Null => None,
+        v => Some(
+            v.as_u64()
+                .filter(|n| (1..=u32::MAX as u64).contains(n))
+                .ok_or_else(|| {
+                    CommandError::InvalidInput("'max_attempts' must be a positive integer".into())
+                })? as u32,
+        ),
+    };
+    let command_args = match &args["args"] {
+        Value::Null => serde_json::json!({}),
+        v => v.clone(),
+    };
+    let job = enqueue(ctx, command, command_args, max_attempts)?;
+    serde_json::to_value(job).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+/// `queue_list` – queued jobs, oldest first.
+///
+/// Args: `{ "status"?: "pending" | "running" | "done" | "failed" }`.
+/// Returns: `{ "jobs": [{id, command, args, status, attempts, ...}] }`.
+pub(crate) fn cmd_queue_list(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let status: Option<JobStatus> = match &args["status"] {
+        Value::Null => None,
+        v => Some(serde_json::from_value(v.clone()).map_err(|_| {
+            CommandError::InvalidInput(format!(
+                "'status' must be pending, running, done, or failed, not {}",
+                v
+            ))
+        })?),
+    };
+    let jobs: Vec<Job> = list(ctx)?
+        .into_iter()
+        .filter(|j| status.is_none_or(|s| j.status == s))
+        .collect();
+    Ok(serde_json::json!({ "jobs": jobs }))
+}
+
+/// `queue_retry` – run a failed job again, with its attempts reset.
+///
+/// Args: `{ "id": "..." }`.
+/// Returns: the job, now pending.
+pub(crate) fn cmd_queue_retry(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let id = args["id"]
+        .as_str()
+        .ok_or_else(|| CommandError::InvalidInput("missing 'id'".into()))?;
+    let job = retry(ctx, id)?;
+    serde_json::to_value(job).map_err(|e| CommandError::Other(e.to_string()))
+}
@@ -0,0 +1,269 @@
+//! Running queued jobs: claim the oldest due one, run it, and settle it as
+//! done, retried later, or failed.
+
+use super::{now_secs, update, Job, JobOwner, JobStatus, QueueConfig, POLL_INTERVAL};
+use crate::commands::{CommandError, CommandRegistry};
+use crate::context::AppContext;
+use crate::pool::Priority;
+use crate::types::{CommandResult, ErrorCode, ErrorInfo};
+use serde_json::Value;
+
+/// Treat jobs left `running` by a process that has exited as failed runs,
+/// so they are retried (or left `failed` if out of attempts). Jobs a live
+/// process is running are left alone. Returns how many were requeued.
+/// Call once, before the first [`run_pending`].
+pub fn recover(ctx: &AppContext) -> Result<usize, CommandError> {
+    let now = now_secs(ctx);
+    let config = ctx.queue.clone();
+    update(ctx, |file| {
+        let mut interrupted = 0;
+        for job in file.jobs.iter_mut().filter(|j| {
+            j.status == JobStatus::Running && !j.owner.as_ref().is_some_and(JobOwner::is_alive)
+        }) {
+            let error = ErrorInfo {
+                code: ErrorCode::InternalError,
+                message: "interrupted by a restart".to_string(),
+                details: Value::Null,
+            };
+            settle(job, Err(error), now, &config);
+            interrupted += 1;
+        }
+        Ok(interrupted)
+    })
+}
+
+/// Run every due job, one at a time and oldest first, until none is left;
+/// returns them as they finished. Retries scheduled for later are left
+/// for a later call.
+pub async fn run_pending(
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+) -> Result<Vec<Job>, CommandError> {
+    let mut finished = Vec::new();
+    while let Some(job) = claim_next(ctx)? {
+        ctx.events().emit(
+            &job.id,
+            "queue:started",
+            serde_json::to_value(&job).unwrap_or_default(),
+        );
+        let result = registry.execute_as(&job.command, job.args.clone(), ctx, Priority::Background);
+        let job = complete(ctx, &job.id, result)?;
+        ctx.events().emit(
+            &job.id,
+            "queue:finished",
+            serde_json::to_value(&job).unwrap_or_default(),
+        );
+        finished.push(job);
+    }
+    Ok(finished)
+}
+
+/// The GUI's background worker: [`recover`], then [`run_pending`] every
+/// [`POLL_INTERVAL`], forever. Does nothing in dry-run mode, where queued
+/// commands would only be planned.
+pub async fn run_worker(ctx: &AppContext, registry: &CommandRegistry) {
+    if ctx.dry_run {
+        return;
+    }
+    match recover(ctx) {
+        Ok(0) => {}
+        Ok(n) => tracing::info!("resuming {} interrupted queue job(s)", n),
+        Err(e) => tracing::warn!("queue: {}", e),
+    }
+    loop {
+        if let Err(e) = run_pending(ctx, registry).await {
+            tracing::warn!("queue: {}", e);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Mark the oldest due `pending` job `running` by this process and count
+/// the attempt.
+fn claim_next(ctx: &AppContext) -> Result<Option<Job>, CommandError> {
+    let now = now_secs(ctx);
+    update(ctx, |file| {
+        let due = file
+            .jobs
+            .iter_mut()
+            .find(|j| j.status == JobStatus::Pending && j.not_before.is_none_or(|t| t <= now));
+        Ok(due.map(|job| {
+            job.status = JobStatus::Running;
+            job.attempts += 1;
+            job.updated_at = now;
+            job.owner = Some(JobOwner::current());
+            job.clone()
+        }))
+    })
+}
+
+/// Record how job `id`'s run went and drop the oldest `done` jobs past
+/// `keep_done`.
+fn complete(ctx: &AppContext, id: &str, result: CommandResult) -> Result<Job, CommandError> {
+    let now = now_secs(ctx);
+    let config = ctx.queue.clone();
+    update(ctx, |file| {
+        let job = file
+            .jobs
+            .iter_mut()
+            .find(|j| j.id == id)
+            .ok_or_else(|| CommandError::Other(format!("job {} left the queue", id)))?;
+        settle(job, result.error.map_or(Ok(result.data), Err), now, &config);
+        let job = job.clone();
+        if config.keep_done > 0 {
+            let done = file
+                .jobs
+                .iter()
+                .filter(|j| j.status == JobStatus::Done)
+                .count();
+            let mut excess = done.saturating_sub(config.keep_done);
+            file.jobs.retain(|j| {
+                let drop = excess > 0 && j.status == JobStatus::Done;
+                excess -= drop as usize;
+                !drop
+            });
+        }
+        Ok(job)
+    })
+}
+
+/// Move a run job on: `done`, back to `pending` with a backoff, or
+/// `failed` when out of attempts or the input was bad.
+fn settle(
+    job: &mut Job,
+    outcome: Result<Option<Value>, ErrorInfo>,
+    now: u64,
+    config: &QueueConfig,
+) {
+    job.updated_at = now;
+    job.not_before = None;
+    job.owner = None;
+    match outcome {
+        Ok(data) => {
+            job.status = JobStatus::Done;
+            job.result = data;
+            job.error = None;
+        }
+        Err(error) => {
+            job.status =
+                if error.code == ErrorCode::InvalidInput || job.attempts >= job.max_attempts {
+                    JobStatus::Failed
+                } else {
+                    let doublings = job.attempts.saturating_sub(1).min(16);
+                    job.not_before = Some(now + (config.retry_delay_secs << doublings));
+                    JobStatus::Pending
+                };
+            job.error = Some(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::{cmd_queue_list, enqueue, list, retry, LOCK_FILE};
+    use serde_json::json;
+
+    fn ctx(dir: &std::path::Path) -> AppContext {
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.to_path_buf();
+        ctx
+    }
+
+    #[tokio::test]
+    async fn test_jobs_run_in_order_and_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let registry = CommandRegistry::new();
+        let path = dir.path().join("out.txt");
+        let first = enqueue(
+            &ctx,
+            "write_file",
+            json!({ "path": path, "content": "one" }),
+            None,
+        )
+        .unwrap();
+        enqueue(&ctx, "ping", json!({}), None).unwrap();
+        assert!(enqueue(&ctx, "queue_add", json!({}), None).is_err());
+
+        let finished = run_pending(&ctx, &registry).await.unwrap();
+        assert_eq!(finished.len(), 2);
+        assert_eq!(finished[0].id, first.id);
+        assert!(finished
+            .iter()
+            .all(|j| j.status == JobStatus::Done && j.attempts == 1));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one");
+        assert!(run_pending(&ctx, &registry).await.unwrap().is_empty());
+
+        let listed = cmd_queue_list(json!({ "status": "done" }), &ctx).unwrap();
+        assert_eq!(listed["jobs"].as_array().unwrap().len(), 2);
+        assert!(cmd_queue_list(json!({ "status": "nope" }), &ctx).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failures_back_off_then_fail_and_retry_resets() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let registry = CommandRegistry::new();
+        let missing = dir.path().join("missing.txt");
+        let job = enqueue(&ctx, "read_file", json!({ "path": missing }), Some(2)).unwrap();
+        let bad = enqueue(&ctx, "no_such_command", json!({}), None).unwrap();
+
+        let finished = run_pending(&ctx, &registry).await.unwrap();
+        assert_eq!(finished.len(), 2);
+        assert_eq!(finished[0].status, JobStatus::Pending);
+        assert!(finished[0].not_before.is_some());
+        assert!(finished[0].error.is_some());
+        assert_eq!(finished[1].id, bad.id);
+        assert_eq!(finished[1].status, JobStatus::Failed);
+        // The retry is not due yet.
+        assert!(run_pending(&ctx, &registry).await.unwrap().is_empty());
+
+        retry(&ctx, &job.id).unwrap();
+        let finished = run_pending(&ctx, &registry).await.unwrap();
+        assert_eq!(finished[0].attempts, 1);
+        std::fs::write(&missing, "here").unwrap();
+        retry(&ctx, &job.id).unwrap();
+        let finished = run_pending(&ctx, &registry).await.unwrap();
+        assert_eq!(finished[0].status, JobStatus::Done);
+        assert!(matches!(
+            retry(&ctx, &job.id),
+            Err(CommandError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_recover_requeues_interrupted_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx(dir.path());
+        let once = enqueue(&ctx, "ping", json!({}), Some(1)).unwrap();
+        let twice = enqueue(&ctx, "ping", json!({}), Some(2)).unwrap();
+        let ours = enqueue(&ctx, "ping", json!({}), None).unwrap();
+        claim_next(&ctx).unwrap();
+        claim_next(&ctx).unwrap();
+        claim_next(&ctx).unwrap();
+        // The first two were claimed by an earlier process with our pid.
+        update(&ctx, |file| {
+            for job in file.jobs.iter_mut().filter(|j| j.id != ours.id) {
+                job.owner.as_mut().unwrap().instance = "exited".into();
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(recover(&ctx).unwrap(), 2);
+        let jobs = list(&ctx).unwrap();
+        assert_eq!((&jobs[0].id, jobs[0].status), (&once.id, JobStatus::Failed));
+        assert_eq!(
+            (&jobs[1].id, jobs[1].status),
+            (&twice.id, JobStatus::Pending)
+        );
+        assert_eq!(
+            jobs[1].error.as_ref().unwrap().message,
+            "interrupted by a restart"
+        );
+        assert_eq!(jobs[2].status, JobStatus::Running);
+        assert_eq!(recover(&ctx).unwrap(), 0);
+        assert!(dir.path().join(LOCK_FILE).exists());
+    }
+}
@@ -0,0 +1,185 @@
+//! Minimal zip archives for the files the engine hands to users: the
+//! diagnostics bundle (see [`crate::diagnostics`]) and data backups (see
+//! [`crate::backup`]). Entries are deflated, or stored when reading an
+//! archive another tool wrote; no zip64, so archives stay under 4 GiB.
+
+use crate::history::format_time;
+use std::io::{Error, ErrorKind, Read, Write};
+
+/// Entry names and contents, in archive order.
+pub type Entries = Vec<(String, Vec<u8>)>;
+
+/// A zip archive of deflated `files`, all stamped with `mtime` (Unix
+/// seconds, UTC).
+pub fn write<N: AsRef<str>>(files: &[(N, Vec<u8>)], mtime: u64) -> std::io::Result<Vec<u8>> {
+    let (time, date) = dos_time(mtime);
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let name = name.as_ref();
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        let crc = crc32fast::hash(data);
+        let offset = out.len() as u32;
+
+        // Fields shared by the local and central headers, from "version
+        // needed" through the extra field length.
+        let mut common = Vec::new();
+        common.extend_from_slice(&20u16.to_le_bytes()); // version needed: 2.0
+        common.extend_from_slice(&0x0800u16.to_le_bytes()); // UTF-8 names
+        common.extend_from_slice(&8u16.to_le_bytes()); // deflate
+        common.extend_from_slice(&time.to_le_bytes());
+        common.extend_from_slice(&date.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&common);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&compressed);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // made by: 2.0
+        central.extend_from_slice(&common);
+        central.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // disk numbers
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    Ok(out)
+}
+
+/// The entries of `bytes`, found through the central directory the way
+/// unzip tools do. Directory entries are skipped; a bad checksum or a
+/// truncated archive is an error.
+pub fn read(bytes: &[u8]) -> std::io::Result<Entries> {
+    let invalid = |what: &str| Error::new(ErrorKind::InvalidData, format!("not a zip: {}", what));
+    let u16_at = |i: usize| -> std::io::Result<usize> {
+        bytes
+            .get(i..i + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+            .ok_or_else(|| invalid("truncated"))
+    };
+    let u32_at = |i: usize| -> std::io::Result<usize> {
+        bytes
+            .get(i..i + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or_else(|| invalid("truncated"))
+    };
+    // The end record sits at the end, before a comment of up to 64 KiB.
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(22 + 0xffff)
+        .find(|&i| u32_at(i).ok() == Some(0x0605_4b50))
+        .ok_or_else(|| invalid("no end of central directory"))?;
+    let mut entry = u32_at(end + 16)?;
+    let mut entries = Vec::new();
+    for _ in 0..u16_at(end + 10)? {
+        if u32_at(entry)? != 0x0201_4b50 {
+            return Err(invalid("bad central directory entry"));
+        }
+        let method = u16_at(entry + 10)?;
+        let crc = u32_at(entry + 16)?;
+        let compressed_len = u32_at(entry + 20)?;
+        let size = u32_at(entry + 24)?;
+        let name_len = u16_at(entry + 28)?;
+        let skip = name_len + u16_at(entry + 30)? + u16_at(entry + 32)?;
+        let name = bytes
+            .get(entry + 46..entry + 46 + name_len)
+            .ok_or_else(|| invalid("truncated"))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        let local = u32_at(entry + 42)?;
+        if u32_at(local)? != 0x0403_4b50 {
+            return Err(invalid("bad local header"));
+        }
+        let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let raw = bytes
+            .get(start..start + compressed_len)
+            .ok_or_else(|| invalid("truncated"))?;
+        entry += 46 + skip;
+        if name.ends_with('/') {
+            continue;
+        }
+        let data = match method {
+            0 => raw.to_vec(),
+            8 => {
+                let mut data = Vec::new();
+                flate2::read::DeflateDecoder::new(raw)
+                    .take(size as u64 + 1)
+                    .read_to_end(&mut data)?;
+                data
+            }
+            other => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    format!("{}: compression method {} is not supported", name, other),
+                ))
+            }
+        };
+        if data.len() != size || crc32fast::hash(&data) as usize != crc {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: checksum mismatch", name),
+            ));
+        }
+        entries.push((name, data));
+    }
+    Ok(entries)
+}
+
+/// MS-DOS time and date fields for `secs`; clamped to 1980, where DOS
+/// time starts.
+fn dos_time(secs: u64) -> (u16, u16) {
+    let t = format_time(secs);
+    let field = |range: std::ops::Range<usize>| t[range].parse::<u16>().unwrap_or(0);
+    let year = field(0..4).max(1980);
+    let time = (field(11..13) << 11) | (field(14..16) << 5) | (field(17..19) / 2);
+    let date = ((year - 1980) << 9) | (field(5..7) << 5) | field(8..10);
+    (time, date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dos_time() {
+        // 2024-03-05T06:07:08Z
+        assert_eq!(
+            dos_time(1_709_618_828),
+            ((6 << 11) | (7 << 5) | 4, (44 << 9) | (3 << 5) | 5)
+        );
+        assert_eq!(dos_time(0).1, 1 << 5 | 1);
+    }
+
+    #[test]
+    fn test_read_what_write_wrote_and_reject_corruption() {
+        let files = [("a.json", b"{}".to_vec()), ("dir/b.txt", vec![7; 1000])];
+        let mut bytes = write(&files, 0).unwrap();
+        let entries = read(&bytes).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1], ("dir/b.txt".to_string(), vec![7; 1000]));
+
+        // Flip the stored CRC of the first entry.
+        bytes[14] ^= 0xff;
+        let central = bytes.len() - 22 - (46 + 6) - (46 + 9);
+        bytes[central + 16] ^= 0xff;
+        assert!(read(&bytes).is_err());
+        assert!(read(b"not a zip").is_err());
+    }
+}
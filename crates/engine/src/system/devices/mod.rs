@@ -0,0 +1,298 @@
+//! Attached hardware – USB devices, removable disks, and printers.
+//!
+//! [`SystemDevices`] reads `/sys/bus/usb/devices` (what udev exposes) and
+//! `lsblk` on Linux, and `system_profiler SPUSBDataType` on macOS. The `usb`
+//! probe uses it to check that passthrough devices are visible inside a VM;
+//! set `APP__USB_EXPECT=vendor:product[,...]` (hex ids, e.g. `0781:5581`) to
+//! fail the probe when a specific device is missing.
+//!
+//! Printers come from CUPS via `lpstat` (Linux and macOS) for the `printing`
+//! probe.
+//!
+//! Cameras and microphones (the `media-devices` probe) are never opened for
+//! capture. On Linux they come from `/sys/class/video4linux` and
+//! `/proc/asound/pcm`, and a non-blocking open of each device node shows
+//! whether this user may use it. On macOS they come from `system_profiler`,
+//! and the permission is the app's `AVCaptureDevice` authorization status.
+//! `APP__MEDIA_REQUIRE=camera,microphone` makes the probe fail when a
+//! required kind is missing or denied.
+
+mod parse;
+
+pub use parse::*;
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{
+    CapError, CapResult, DeviceOps, MediaDevice, MediaInventory, MediaKind, MediaPermission,
+    PrintService, RemovableMedia, UsbDevice,
+};
+use serde_json::Value;
+use std::path::Path;
+
+/// Devices the `usb` probe must find, as `vendor:product` hex pairs.
+pub const USB_EXPECT_ENV: &str = "APP__USB_EXPECT";
+
+/// `vendor:product` pairs from [`USB_EXPECT_ENV`], lowercased.
+pub fn expected_usb_devices() -> Vec<String> {
+    std::env::var(USB_EXPECT_ENV)
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Device kinds the `media-devices` probe requires.
+pub const MEDIA_REQUIRE_ENV: &str = "APP__MEDIA_REQUIRE";
+
+/// Kinds listed in [`MEDIA_REQUIRE_ENV`]; unknown names are ignored.
+pub fn required_media_kinds() -> Vec<MediaKind> {
+    std::env::var(MEDIA_REQUIRE_ENV)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|s| serde_json::from_value(Value::String(s.trim().to_string())).ok())
+        .collect()
+}
+
+/// `vendor:product` id of `device`, lowercased.
+pub fn usb_id(device: &UsbDevice) -> String {
+    format!("{}:{}", device.vendor_id, device.product_id).to_ascii_lowercase()
+}
+
+/// Hardware enumeration through the OS tools.
+pub struct SystemDevices;
+
+impl DeviceOps for SystemDevices {
+    fn usb_devices(&self) -> CapResult<Vec<UsbDevice>> {
+        #[cfg(target_os = "linux")]
+        {
+            let root = Path::new("/sys/bus/usb/devices");
+            if !root.exists() {
+                return Err(CapError::Unsupported(
+                    "no USB bus visible (/sys/bus/usb is missing)".into(),
+                ));
+            }
+            Ok(parse_sysfs_usb(root))
+        }
+        #[cfg(target_os = "macos")]
+        {
+            Ok(parse_system_profiler(&system_profiler()?).0)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            Err(CapError::Unsupported(
+                "USB enumeration is not implemented on this OS".into(),
+            ))
+        }
+    }
+
+    fn removable_media(&self) -> CapResult<Vec<RemovableMedia>> {
+        #[cfg(target_os = "linux")]
+        {
+            let json = run_tool(
+                "lsblk",
+                &[
+                    "-J",
+                    "-b",
+                    "-o",
+                    "NAME,PATH,RM,HOTPLUG,TRAN,VENDOR,MODEL,SIZE,MOUNTPOINT",
+                ],
+            )?;
+            parse_lsblk(&json)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            Ok(parse_system_profiler(&system_profiler()?).1)
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            Err(CapError::Unsupported(
+                "removable media enumeration is not implemented on this OS".into(),
+            ))
+        }
+    }
+
+    fn print_service(&self) -> CapResult<PrintService> {
+        #[cfg(unix)]
+        {
+            Ok(parse_lpstat(
+                &lpstat("-r")?,
+                &lpstat("-d")?,
+                &lpstat("-p")?,
+                &lpstat("-a")?,
+            ))
+        }
+        #[cfg(not(unix))]
+        {
+            Err(CapError::Unsupported(
+                "printer discovery needs CUPS (lpstat)".into(),
+            ))
+        }
+    }
+
+    fn media_devices(&self) -> CapResult<MediaInventory> {
+        #[cfg(target_os = "linux")]
+        {
+            let mut devices = scan_v4l(Path::new("/sys/class/video4linux"), Path::new("/dev"));
+            let pcm = std::fs::read_to_string("/proc/asound/pcm").unwrap_or_default();
+            devices.extend(parse_asound_pcm(&pcm, Path::new("/dev/snd")));
+            Ok(media_inventory(devices))
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let json = run_tool(
+                "system_profiler",
+                &["SPCameraDataType", "SPAudioDataType", "-json"],
+            )?;
+            let (camera, microphone) = av_authorization();
+            Ok(parse_macos_media(&json, camera, microphone))
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            Err(CapError::Unsupported(
+                "camera/microphone enumeration is not implemented on this OS".into(),
+            ))
+        }
+    }
+}
+
+/// `O_NONBLOCK` for this Linux ABI, so a busy sound device cannot block
+/// the permission check.
+#[cfg(target_os = "linux")]
+const O_NONBLOCK: i32 = if cfg!(any(target_arch = "mips", target_arch = "mips64")) {
+    0x80
+} else if cfg!(target_arch = "sparc64") {
+    0x4000
+} else {
+    0o4000
+};
+
+/// Whether this user may open `node` read-write. Nothing is read from it.
+fn node_access(node: &Path) -> MediaPermission {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true).write(true);
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.custom_flags(O_NONBLOCK);
+    }
+    match options.open(node) {
+        Ok(_) => MediaPermission::Granted,
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => MediaPermission::Denied,
+        Err(_) => MediaPermission::Unknown,
+    }
+}
+
+/// Granted if any device of `kind` is usable, denied if some are and none
+/// is, otherwise unknown (including no devices at all).
+fn combined_permission(devices: &[MediaDevice], kind: MediaKind) -> MediaPermission {
+    let perms: Vec<MediaPermission> = devices
+        .iter()
+        .filter(|d| d.kind == kind)
+        .map(|d| d.permission)
+        .collect();
+    if perms.contains(&MediaPermission::Granted) {
+        MediaPermission::Granted
+    } else if perms.contains(&MediaPermission::Denied) {
+        MediaPermission::Denied
+    } else {
+        MediaPermission::Unknown
+    }
+}
+
+fn media_inventory(devices: Vec<MediaDevice>) -> MediaInventory {
+    MediaInventory {
+        camera: combined_permission(&devices, MediaKind::Camera),
+        microphone: combined_permission(&devices, MediaKind::Microphone),
+        devices,
+    }
+}
+
+/// `AVCaptureDevice` authorization for video and audio, asked through
+/// JavaScript for Automation so the engine needs no Objective-C bindings.
+#[cfg(target_os = "macos")]
+fn av_authorization() -> (MediaPermission, MediaPermission) {
+    const SCRIPT: &str = "ObjC.import('AVFoundation'); \
+        [$.AVMediaTypeVideo, $.AVMediaTypeAudio] \
+        .map(t => $.AVCaptureDevice.authorizationStatusForMediaType(t)).join(',')";
+    let out = run_tool("osascript", &["-l", "JavaScript", "-e", SCRIPT]).unwrap_or_default();
+    let mut codes = out.trim().split(',').map(av_status);
+    (
+        codes.next().unwrap_or(MediaPermission::Unknown),
+        codes.next().unwrap_or(MediaPermission::Unknown),
+    )
+}
+
+/// `AVAuthorizationStatus` raw value → permission.
+pub fn av_status(code: &str) -> MediaPermission {
+    match code.trim() {
+        "0" => MediaPermission::NotDetermined,
+        "1" => MediaPermission::Restricted,
+        "2" => MediaPermission::Denied,
+        "3" => MediaPermission::Granted,
+        _ => MediaPermission::Unknown,
+    }
+}
+
+/// `lpstat <flag>` in the C locale. Its exit status is ignored: lpstat
+/// fails when there are no printers or the scheduler is down, which the
+/// parser handles from the text.
+#[cfg(unix)]
+fn lpstat(flag: &str) -> CapResult<String> {
+    let output = std::process::Command::new("lpstat")
+        .arg(flag)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                CapError::DependencyMissing("lpstat not found (CUPS is not installed)".into())
+            }
+            _ => CapError::Io(e),
+        })?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[allow(dead_code)]
+fn run_tool(cmd: &str, args: &[&str]) -> CapResult<String> {
+    let output = std::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                CapError::DependencyMissing(format!("{} not found", cmd))
+            }
+            _ => CapError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(CapError::Other(format!(
+            "{} exited with {}: {}",
+            cmd,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+fn system_profiler() -> CapResult<String> {
+    run_tool("system_profiler", &["SPUSBDataType", "-json"])
+}
+
+// ===========================================================================
+// Commands
+// ===========================================================================
+
+/// `list_removable_media` – removable and USB-attached disks.
+///
+/// Returns: `{ "media": [{ "name": "sdb", "device": "/dev/sdb",
+///            "vendor": "SanDisk", "model": "Ultra", "size_bytes": 32017047552,
+///            "transport": "usb", "mount_points": ["/media/usb"] }] }`
+pub(crate) fn cmd_list_removable_media(
+    _args: Value,
+    ctx: &AppContext,
+) -> Result<Value, CommandError> {
+    let media = ctx.devices().removable_media()?;
+    Ok(serde_json::json!({ "media": media }))
+}
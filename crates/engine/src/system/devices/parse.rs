@@ -0,0 +1,435 @@
+//! Parsers for what the OS tools and `/sys` report about devices.
+
+use super::node_access;
+use crate::traits::{
+    CapError, CapResult, MediaDevice, MediaInventory, MediaKind, MediaPermission, PrintService,
+    Printer, RemovableMedia, UsbDevice,
+};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+fn text(v: &Value) -> Option<String> {
+    v.as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(name))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// USB devices under a sysfs `devices` directory. Interfaces (`1-1:1.0`)
+/// and root hubs (`usb1`) are skipped.
+pub fn parse_sysfs_usb(root: &Path) -> Vec<UsbDevice> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<UsbDevice> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let location = entry.file_name().to_string_lossy().into_owned();
+            if location.contains(':') || location.starts_with("usb") {
+                return None;
+            }
+            let dir = entry.path();
+            Some(UsbDevice {
+                vendor_id: read_attr(&dir, "idVendor")?,
+                product_id: read_attr(&dir, "idProduct")?,
+                manufacturer: read_attr(&dir, "manufacturer"),
+                product: read_attr(&dir, "product"),
+                location,
+            })
+        })
+        .collect();
+    devices.sort_by(|a, b| a.location.cmp(&b.location));
+    devices
+}
+
+/// Removable, hotplug, or USB-attached disks from `lsblk -J -b` output.
+/// Accepts both old (`"rm": "1"`, `mountpoint`) and new (`"rm": true`,
+/// `mountpoints`) lsblk JSON.
+pub fn parse_lsblk(json: &str) -> CapResult<Vec<RemovableMedia>> {
+    let doc: Value = serde_json::from_str(json)
+        .map_err(|e| CapError::Other(format!("unexpected lsblk output: {}", e)))?;
+    let flag = |dev: &Value, key: &str| match &dev[key] {
+        Value::Bool(b) => *b,
+        Value::String(s) => s == "1",
+        Value::Number(n) => n.as_u64() == Some(1),
+        _ => false,
+    };
+    let size = |dev: &Value| match &dev["size"] {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    fn mounts(dev: &Value, out: &mut Vec<PathBuf>) {
+        let listed = dev["mountpoints"].as_array().into_iter().flatten();
+        for m in listed.chain(std::iter::once(&dev["mountpoint"])) {
+            if let Some(m) = text(m) {
+                out.push(PathBuf::from(m));
+            }
+        }
+        for child in dev["children"].as_array().into_iter().flatten() {
+            mounts(child, out);
+        }
+    }
+
+    let disks = doc["blockdevices"].as_array().into_iter().flatten();
+    Ok(disks
+        .filter(|dev| {
+            flag(dev, "rm") || flag(dev, "hotplug") || dev["tran"].as_str() == Some("usb")
+        })
+        .filter_map(|dev| {
+            let name = text(&dev["name"])?;
+            let mut mount_points = Vec::new();
+            mounts(dev, &mut mount_points);
+            mount_points.dedup();
+            Some(RemovableMedia {
+                device: text(&dev["path"]).or_else(|| Some(format!("/dev/{}", name))),
+                name,
+                vendor: text(&dev["vendor"]),
+                model: text(&dev["model"]),
+                size_bytes: size(dev),
+                transport: text(&dev["tran"]),
+                mount_points,
+            })
+        })
+        .collect())
+}
+
+/// USB devices and their disks from `system_profiler SPUSBDataType -json`.
+pub fn parse_system_profiler(json: &str) -> (Vec<UsbDevice>, Vec<RemovableMedia>) {
+    /// `"0x0781  (SanDisk Corporation)"` → `0781`.
+    fn hex_id(v: &Value) -> Option<String> {
+        let s = v.as_str()?.split_whitespace().next()?;
+        Some(s.trim_start_matches("0x").to_ascii_lowercase())
+    }
+    fn walk(item: &Value, usb: &mut Vec<UsbDevice>, media: &mut Vec<RemovableMedia>) {
+        if let (Some(vendor_id), Some(product_id)) =
+            (hex_id(&item["vendor_id"]), hex_id(&item["product_id"]))
+        {
+            usb.push(UsbDevice {
+                vendor_id,
+                product_id,
+                manufacturer: text(&item["manufacturer"]),
+                product: text(&item["_name"]),
+                location: text(&item["location_id"]).unwrap_or_default(),
+            });
+        }
+        for disk in item["Media"].as_array().into_iter().flatten() {
+            let volumes = disk["volumes"].as_array().into_iter().flatten();
+            media.push(RemovableMedia {
+                name: text(&disk["_name"]).unwrap_or_default(),
+                device: text(&disk["bsd_name"]),
+                vendor: text(&item["manufacturer"]),
+                model: text(&item["_name"]),
+                size_bytes: disk["size_in_bytes"].as_u64(),
+                transport: Some("usb".into()),
+                mount_points: volumes
+                    .filter_map(|v| text(&v["mount_point"]).map(PathBuf::from))
+                    .collect(),
+            });
+        }
+        for child in item["_items"].as_array().into_iter().flatten() {
+            walk(child, usb, media);
+        }
+    }
+
+    let (mut usb, mut media) = (Vec::new(), Vec::new());
+    if let Ok(doc) = serde_json::from_str::<Value>(json) {
+        for bus in doc["SPUSBDataType"].as_array().into_iter().flatten() {
+            walk(bus, &mut usb, &mut media);
+        }
+    }
+    (usb, media)
+}
+
+/// Print service state from `lpstat -r`, `-d`, `-p`, and `-a` output.
+pub fn parse_lpstat(
+    scheduler: &str,
+    default: &str,
+    printers: &str,
+    accepting: &str,
+) -> PrintService {
+    let default = default
+        .trim()
+        .strip_prefix("system default destination:")
+        .map(str::trim);
+    let printers = printers
+        .lines()
+        .filter_map(|line| line.strip_prefix("printer "))
+        .filter_map(|rest| {
+            let (name, status) = rest.split_once(' ')?;
+            let state = if status.starts_with("is idle") {
+                "idle"
+            } else if status.starts_with("now printing") {
+                "printing"
+            } else {
+                status.split_whitespace().next().unwrap_or("unknown")
+            };
+            Some(Printer {
+                name: name.to_string(),
+                state: state.trim_end_matches('.').to_string(),
+                accepting_jobs: accepting.lines().any(|l| {
+                    l.strip_prefix(name)
+                        .is_some_and(|r| r.starts_with(" accepting requests"))
+                }),
+                is_default: default == Some(name),
+            })
+        })
+        .collect();
+    PrintService {
+        running: scheduler.trim() == "scheduler is running",
+        printers,
+    }
+}
+
+/// Capture nodes under a sysfs `video4linux` class directory. Only index 0
+/// of each camera is kept; the others are metadata nodes.
+pub fn scan_v4l(class_dir: &Path, dev_dir: &Path) -> Vec<MediaDevice> {
+    let Ok(entries) = std::fs::read_dir(class_dir) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<MediaDevice> = entries
+        .flatten()
+        .filter(|e| read_attr(&e.path(), "index").is_none_or(|i| i == "0"))
+        .map(|e| {
+            let node = dev_dir.join(e.file_name());
+            MediaDevice {
+                kind: MediaKind::Camera,
+                name: read_attr(&e.path(), "name")
+                    .unwrap_or_else(|| e.file_name().to_string_lossy().into_owned()),
+                id: node.display().to_string(),
+                permission: node_access(&node),
+            }
+        })
+        .collect();
+    devices.sort_by(|a, b| a.id.cmp(&b.id));
+    devices
+}
+
+/// Capture-capable PCMs from `/proc/asound/pcm`, e.g.
+/// `00-00: ALC887 Analog : ALC887 Analog : playback 1 : capture 1`.
+pub fn parse_asound_pcm(pcm: &str, snd_dir: &Path) -> Vec<MediaDevice> {
+    pcm.lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':').map(str::trim);
+            let (card, device) = fields.next()?.split_once('-')?;
+            let (card, device) = (card.parse::<u32>().ok()?, device.parse::<u32>().ok()?);
+            let name = fields.next()?.to_string();
+            if !fields.any(|f| f.starts_with("capture")) {
+                return None;
+            }
+            let node = snd_dir.join(format!("pcmC{}D{}c", card, device));
+            Some(MediaDevice {
+                kind: MediaKind::Microphone,
+                name,
+                id: node.display().to_string(),
+                permission: node_access(&node),
+            })
+        })
+        .collect()
+}
+
+/// Cameras and audio inputs from `system_profiler SPCameraDataType
+/// SPAudioDataType -json`, with the given per-kind permissions.
+pub fn parse_macos_media(
+    json: &str,
+    camera: MediaPermission,
+    microphone: MediaPermission,
+) -> MediaInventory {
+    let doc: Value = serde_json::from_str(json).unwrap_or_default();
+    let mut devices = Vec::new();
+    for cam in doc["SPCameraDataType"].as_array().into_iter().flatten() {
+        let Some(name) = text(&cam["_name"]) else {
+            continue;
+        };
+        devices.push(MediaDevice {
+            kind: MediaKind::Camera,
+            id: text(&cam["spcamera_unique-id"]).unwrap_or_else(|| name.clone()),
+            name,
+            permission: camera,
+        });
+    }
+    let audio = doc["SPAudioDataType"].as_array().into_iter().flatten();
+    for dev in audio.flat_map(|group| group["_items"].as_array().into_iter().flatten()) {
+        let Some(name) = text(&dev["_name"]) else {
+            continue;
+        };
+        if dev.get("coreaudio_device_input").is_none() {
+            continue;
+        }
+        devices.push(MediaDevice {
+            kind: MediaKind::Microphone,
+            id: name.clone(),
+            name,
+            permission: microphone,
+        });
+    }
+    MediaInventory {
+        devices,
+        camera,
+        microphone,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{av_status, media_inventory, usb_id};
+
+    #[test]
+    fn test_parse_lsblk_keeps_removable_disks() {
+        let json = r#"{"blockdevices": [
+            {"name": "vda", "path": "/dev/vda", "rm": false, "hotplug": false, "tran": null,
+             "vendor": "0x1af4", "model": null, "size": 274877906944, "mountpoints": ["/"]},
+            {"name": "sdb", "path": "/dev/sdb", "rm": true, "hotplug": true, "tran": "usb",
+             "vendor": "SanDisk ", "model": "Ultra", "size": 32017047552, "mountpoints": [null],
+             "children": [{"name": "sdb1", "mountpoints": ["/media/usb"]}]},
+            {"name": "sdc", "rm": "0", "hotplug": "0", "tran": "usb", "size": "1024",
+             "mountpoint": null}
+        ]}"#;
+        let media = parse_lsblk(json).unwrap();
+        assert_eq!(media.len(), 2);
+        assert_eq!(media[0].vendor.as_deref(), Some("SanDisk"));
+        assert_eq!(media[0].mount_points, vec![PathBuf::from("/media/usb")]);
+        assert_eq!(media[1].device.as_deref(), Some("/dev/sdc"));
+        assert_eq!(media[1].size_bytes, Some(1024));
+    }
+
+    #[test]
+    fn test_parse_sysfs_usb_skips_hubs_and_interfaces() {
+        let tmp = tempfile::tempdir().unwrap();
+        let add = |name: &str, attrs: &[(&str, &str)]| {
+            let dir = tmp.path().join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            for (k, v) in attrs {
+                std::fs::write(dir.join(k), format!("{}\n", v)).unwrap();
+            }
+        };
+        add("usb1", &[("idVendor", "1d6b"), ("idProduct", "0002")]);
+        add("1-1:1.0", &[]);
+        add(
+            "1-1",
+            &[
+                ("idVendor", "0781"),
+                ("idProduct", "5581"),
+                ("product", "Ultra"),
+            ],
+        );
+
+        let devices = parse_sysfs_usb(tmp.path());
+        assert_eq!(devices.len(), 1);
+        assert_eq!(usb_id(&devices[0]), "0781:5581");
+        assert_eq!(devices[0].product.as_deref(), Some("Ultra"));
+        assert_eq!(devices[0].manufacturer, None);
+    }
+
+    #[test]
+    fn test_parse_system_profiler() {
+        let json = r#"{"SPUSBDataType": [{"_name": "USB31Bus", "_items": [{
+            "_name": "Ultra", "manufacturer": "SanDisk", "vendor_id": "0x0781  (SanDisk Corporation)",
+            "product_id": "0x5581", "location_id": "0x01100000 / 1",
+            "Media": [{"_name": "Ultra", "bsd_name": "disk4", "size_in_bytes": 32017047552,
+                       "volumes": [{"_name": "USB", "mount_point": "/Volumes/USB"}]}]
+        }]}]}"#;
+        let (usb, media) = parse_system_profiler(json);
+        assert_eq!(usb.len(), 1);
+        assert_eq!(usb_id(&usb[0]), "0781:5581");
+        assert_eq!(media[0].device.as_deref(), Some("disk4"));
+        assert_eq!(media[0].mount_points, vec![PathBuf::from("/Volumes/USB")]);
+    }
+
+    #[test]
+    fn test_parse_lpstat() {
+        let service = parse_lpstat(
+            "scheduler is running\n",
+            "system default destination: Office_HP\n",
+            "printer Office_HP is idle.  enabled since Mon 01 Jan 2024 09:00:00 AM UTC\n\
+             printer Lab now printing Lab-12.  enabled since Mon 01 Jan 2024\n\
+             printer Old disabled since Mon 01 Jan 2024 -\n\treason unknown\n",
+            "Office_HP accepting requests since Mon 01 Jan 2024\n\
+             Lab accepting requests since Mon 01 Jan 2024\n\
+             Old not accepting requests since Mon 01 Jan 2024 -\n\treason unknown\n",
+        );
+        assert!(service.running);
+        let summary: Vec<_> = service
+            .printers
+            .iter()
+            .map(|p| {
+                (
+                    p.name.as_str(),
+                    p.state.as_str(),
+                    p.accepting_jobs,
+                    p.is_default,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Office_HP", "idle", true, true),
+                ("Lab", "printing", true, false),
+                ("Old", "disabled", false, false),
+            ]
+        );
+
+        let down = parse_lpstat("scheduler is not running\n", "", "", "");
+        assert!(!down.running);
+        assert!(down.printers.is_empty());
+    }
+
+    #[test]
+    fn test_scan_v4l_and_asound() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (class, dev) = (tmp.path().join("class"), tmp.path().join("dev"));
+        for (node, index, name) in [
+            ("video0", "0", "Integrated Camera"),
+            ("video1", "1", "meta"),
+        ] {
+            std::fs::create_dir_all(class.join(node)).unwrap();
+            std::fs::write(class.join(node).join("index"), index).unwrap();
+            std::fs::write(class.join(node).join("name"), name).unwrap();
+        }
+        std::fs::create_dir_all(&dev).unwrap();
+        std::fs::write(dev.join("video0"), "").unwrap();
+
+        let cams = scan_v4l(&class, &dev);
+        assert_eq!(cams.len(), 1);
+        assert_eq!(cams[0].name, "Integrated Camera");
+        assert_eq!(cams[0].permission, MediaPermission::Granted);
+
+        let pcm = "00-00: ALC887 Analog : ALC887 Analog : playback 1 : capture 1\n\
+                   00-03: HDMI 0 : HDMI 0 : playback 1\n";
+        let mics = parse_asound_pcm(pcm, &dev);
+        assert_eq!(mics.len(), 1);
+        assert_eq!(mics[0].id, dev.join("pcmC0D0c").display().to_string());
+        // No such node here, so access cannot be determined.
+        assert_eq!(mics[0].permission, MediaPermission::Unknown);
+
+        let inventory = media_inventory(cams.into_iter().chain(mics).collect());
+        assert_eq!(inventory.camera, MediaPermission::Granted);
+        assert_eq!(inventory.microphone, MediaPermission::Unknown);
+    }
+
+    #[test]
+    fn test_parse_macos_media() {
+        let json = r#"{
+            "SPCameraDataType": [{"_name": "FaceTime HD Camera", "spcamera_unique-id": "0x1420000005ac8600"}],
+            "SPAudioDataType": [{"_name": "coreaudio_device", "_items": [
+                {"_name": "MacBook Pro Microphone", "coreaudio_device_input": 1},
+                {"_name": "MacBook Pro Speakers", "coreaudio_device_output": 2}
+            ]}]
+        }"#;
+        let inv = parse_macos_media(json, av_status("3"), av_status("0"));
+        assert_eq!(inv.devices.len(), 2);
+        assert_eq!(inv.devices[0].id, "0x1420000005ac8600");
+        assert_eq!(inv.devices[1].name, "MacBook Pro Microphone");
+        assert_eq!(inv.permission(MediaKind::Camera), MediaPermission::Granted);
+        assert_eq!(inv.microphone, MediaPermission::NotDetermined);
+    }
+}
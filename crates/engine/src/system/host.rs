@@ -0,0 +1,211 @@
+//! Which machine and app produced a result: app version, host name, VM and
+//! CI detection, and a session id, stamped on every `env_summary` so
+//! `result.json` files collected from dozens of VMs can be attributed.
+//!
+//! The host name can be replaced by a stable hash (`$APP__REDACT_HOSTNAME=1`
+//! or [`set_redact_hostname`]) when results leave the machine. The session
+//! id is random per process unless `$APP__SESSION_ID` sets it, e.g. to tie
+//! several `appctl` runs on one VM together.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// Fixed session id for this process.
+pub const SESSION_ID_ENV: &str = "APP__SESSION_ID";
+/// Report a hash instead of the host name (`1`, `true`, or `yes`).
+pub const REDACT_HOSTNAME_ENV: &str = "APP__REDACT_HOSTNAME";
+
+static APP_VERSION: OnceLock<String> = OnceLock::new();
+static REDACT_HOSTNAME: AtomicBool = AtomicBool::new(false);
+
+/// Version of the app embedding the engine (the CLI's or the GUI's). First
+/// call wins; results carry no app version until it is set.
+pub fn set_app_version(version: impl Into<String>) {
+    let _ = APP_VERSION.set(version.into());
+}
+
+pub fn app_version() -> Option<String> {
+    APP_VERSION.get().cloned()
+}
+
+pub fn set_redact_hostname(redact: bool) {
+    REDACT_HOSTNAME.store(redact, Ordering::Relaxed);
+}
+
+fn redact_from_env() -> bool {
+    std::env::var(REDACT_HOSTNAME_ENV)
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// The host name, or its `host-<hash>` stand-in when redaction is on.
+pub fn hostname() -> Option<String> {
+    static NAME: OnceLock<Option<String>> = OnceLock::new();
+    let name = NAME
+        .get_or_init(|| {
+            hostname::get()
+                .ok()
+                .map(|h| h.to_string_lossy().into_owned())
+        })
+        .as_deref()?;
+    if REDACT_HOSTNAME.load(Ordering::Relaxed) || redact_from_env() {
+        Some(redact(name))
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// A stable stand-in for `name`, so redacted results from one machine
+/// still group together (32-bit FNV-1a).
+fn redact(name: &str) -> String {
+    let hash = name.bytes().fold(0x811c_9dc5u32, |h, b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    });
+    format!("host-{:08x}", hash)
+}
+
+/// This process's session id.
+pub fn session_id() -> &'static str {
+    static ID: OnceLock<String> = OnceLock::new();
+    ID.get_or_init(|| {
+        std::env::var(SESSION_ID_ENV)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    })
+}
+
+/// The hypervisor the machine runs under (`qemu`, `vmware`, `hyper-v`, ...,
+/// or `unknown` when one is present but unnamed); `None` on bare metal.
+pub fn vm() -> Option<&'static str> {
+    static VM: OnceLock<Option<&'static str>> = OnceLock::new();
+    *VM.get_or_init(detect_vm)
+}
+
+#[cfg(target_os = "linux")]
+fn detect_vm() -> Option<&'static str> {
+    let read = |path: &str| std::fs::read_to_string(path).unwrap_or_default();
+    let hypervisor_flag = read("/proc/cpuinfo")
+        .lines()
+        .any(|l| l.starts_with("flags") && l.split_whitespace().any(|f| f == "hypervisor"));
+    classify_vm(
+        &read("/sys/class/dmi/id/sys_vendor"),
+        &read("/sys/class/dmi/id/product_name"),
+        hypervisor_flag,
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn detect_vm() -> Option<&'static str> {
+    let out = std::process::Command::new("sysctl")
+        .args(["-n", "kern.hv_vmm_present"])
+        .output()
+        .ok()?;
+    classify_vm("", "", String::from_utf8_lossy(&out.stdout).trim() == "1")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn detect_vm() -> Option<&'static str> {
+    None
+}
+
+/// Name the hypervisor from the DMI vendor and product strings.
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn classify_vm(vendor: &str, product: &str, hypervisor_flag: bool) -> Option<&'static str> {
+    let vendor = vendor.trim().to_ascii_lowercase();
+    let product = product.trim().to_ascii_lowercase();
+    let known = [
+        ("qemu", "qemu"),
+        ("kvm", "kvm"),
+        ("vmware", "vmware"),
+        ("virtualbox", "virtualbox"),
+        ("innotek", "virtualbox"),
+        ("parallels", "parallels"),
+        ("xen", "xen"),
+        ("amazon ec2", "aws"),
+        ("google compute engine", "gce"),
+    ];
+    for (needle, name) in known {
+        if vendor.contains(needle) || product.contains(needle) {
+            return Some(name);
+        }
+    }
+    if vendor.contains("microsoft") && product.contains("virtual machine") {
+        return Some("hyper-v");
+    }
+    hypervisor_flag.then_some("unknown")
+}
+
+/// The CI system the process runs under (`github-actions`, `gitlab`, ...,
+/// or `generic` when only `$CI` is set).
+pub fn ci() -> Option<&'static str> {
+    detect_ci(&|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+}
+
+fn detect_ci(env: &dyn Fn(&str) -> Option<String>) -> Option<&'static str> {
+    let known = [
+        ("GITHUB_ACTIONS", "github-actions"),
+        ("GITLAB_CI", "gitlab"),
+        ("BUILDKITE", "buildkite"),
+        ("CIRCLECI", "circleci"),
+        ("JENKINS_URL", "jenkins"),
+        ("TF_BUILD", "azure-pipelines"),
+        ("TEAMCITY_VERSION", "teamcity"),
+        ("TRAVIS", "travis"),
+    ];
+    if let Some((_, name)) = known.iter().find(|(var, _)| env(var).is_some()) {
+        return Some(name);
+    }
+    match env("CI")?.to_ascii_lowercase().as_str() {
+        "false" | "0" => None,
+        _ => Some("generic"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_vm() {
+        assert_eq!(
+            classify_vm("QEMU\n", "Standard PC (Q35)", true),
+            Some("qemu")
+        );
+        assert_eq!(
+            classify_vm("innotek GmbH", "VirtualBox", true),
+            Some("virtualbox")
+        );
+        assert_eq!(
+            classify_vm("Microsoft Corporation", "Virtual Machine", true),
+            Some("hyper-v")
+        );
+        assert_eq!(classify_vm("Dell Inc.", "XPS 13 9310", false), None);
+        assert_eq!(classify_vm("", "", true), Some("unknown"));
+    }
+
+    #[test]
+    fn test_detect_ci() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(
+            detect_ci(&env(&[("CI", "true"), ("GITHUB_ACTIONS", "true")])),
+            Some("github-actions")
+        );
+        assert_eq!(detect_ci(&env(&[("CI", "1")])), Some("generic"));
+        assert_eq!(detect_ci(&env(&[("CI", "false")])), None);
+        assert_eq!(detect_ci(&env(&[])), None);
+    }
+
+    #[test]
+    fn test_redacted_hostname_is_stable() {
+        assert_eq!(redact("build-vm-17"), redact("build-vm-17"));
+        assert_ne!(redact("build-vm-17"), redact("build-vm-18"));
+        assert!(redact("build-vm-17").starts_with("host-"));
+    }
+}
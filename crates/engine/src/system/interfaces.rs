@@ -0,0 +1,462 @@
+//! Network interfaces, default route, per-interface DNS, and captive portal
+//! detection for the `interfaces` probe.
+//!
+//! Captive portals are spotted the way OSes do it: fetch a URL that always
+//! answers `204 No Content` over plain HTTP. Anything else (typically a
+//! login page with 200, or a redirect) means something on the network is
+//! intercepting traffic – "hotel Wi-Fi", not "no network".
+//!
+//! Linux reads `ip -j addr`, `/proc/net/route`, and `resolvectl` (falling
+//! back to `/etc/resolv.conf`); macOS parses `ifconfig`, `route`, and
+//! `scutil --dns`.
+
+use crate::context::AppContext;
+use crate::traits::{
+    CapError, CapResult, DefaultRoute, DnsScope, HttpRequest, NetInfoOps, NetInterface, NetSnapshot,
+};
+use serde::Serialize;
+
+/// Environment variable overriding [`DEFAULT_CAPTIVE_PORTAL_URL`].
+pub const CAPTIVE_PORTAL_URL_ENV: &str = "APP__CAPTIVE_PORTAL_URL";
+
+/// Answers 204 with an empty body when nothing is in the way.
+pub const DEFAULT_CAPTIVE_PORTAL_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+const CONNECTIVITY_TIMEOUT_MS: u64 = 5_000;
+
+/// `$APP__CAPTIVE_PORTAL_URL`, else [`DEFAULT_CAPTIVE_PORTAL_URL`].
+pub fn default_captive_portal_url() -> String {
+    std::env::var(CAPTIVE_PORTAL_URL_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_CAPTIVE_PORTAL_URL.to_string())
+}
+
+/// Outcome of the 204 check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityState {
+    Online,
+    CaptivePortal,
+    Offline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Connectivity {
+    pub url: String,
+    pub state: ConnectivityState,
+    pub http_status: Option<u16>,
+    /// Redirect target, usually the portal's login page.
+    pub location: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Fetch `ctx.captive_portal_url` and classify the answer.
+pub async fn check_connectivity(ctx: &AppContext) -> Connectivity {
+    let url = ctx.captive_portal_url.clone();
+    let request = HttpRequest::get(url.clone()).timeout_ms(CONNECTIVITY_TIMEOUT_MS);
+    match ctx.network().send(request).await {
+        Ok(resp) => Connectivity {
+            state: if resp.status == 204 {
+                ConnectivityState::Online
+            } else {
+                ConnectivityState::CaptivePortal
+            },
+            http_status: Some(resp.status),
+            location: resp.header("location").map(String::from),
+            error: None,
+            url,
+        },
+        Err(e) => Connectivity {
+            url,
+            state: ConnectivityState::Offline,
+            http_status: None,
+            location: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// System backend
+// ---------------------------------------------------------------------------
+
+/// Interfaces and routes from the OS.
+pub struct SystemNetInfo;
+
+impl NetInfoOps for SystemNetInfo {
+    #[cfg(target_os = "linux")]
+    fn snapshot(&self) -> CapResult<NetSnapshot> {
+        let interfaces = match run_tool("ip", &["-j", "addr", "show"]) {
+            Ok(json) => parse_ip_json(&json)?,
+            Err(_) => sysfs_interfaces(),
+        };
+        let default_route = std::fs::read_to_string("/proc/net/route")
+            .ok()
+            .and_then(|t| parse_proc_route(&t));
+        let dns = match run_tool("resolvectl", &["dns"]) {
+            Ok(out) => parse_resolvectl(&out),
+            Err(_) => std::fs::read_to_string("/etc/resolv.conf")
+                .map(|t| parse_resolv_conf(&t))
+                .unwrap_or_default(),
+        };
+        Ok(NetSnapshot {
+            interfaces,
+            default_route,
+            dns,
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    fn snapshot(&self) -> CapResult<NetSnapshot> {
+        Ok(NetSnapshot {
+            interfaces: parse_ifconfig(&run_tool("ifconfig", &[])?),
+            default_route: run_tool("route", &["-n", "get", "default"])
+                .ok()
+                .and_then(|t| parse_route_get(&t)),
+            dns: run_tool("scutil", &["--dns"])
+                .map(|t| parse_scutil_dns(&t))
+                .unwrap_or_default(),
+        })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn snapshot(&self) -> CapResult<NetSnapshot> {
+        Err(CapError::Unsupported(
+            "interface listing is only implemented for Linux and macOS".into(),
+        ))
+    }
+}
+
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn run_tool(cmd: &str, args: &[&str]) -> CapResult<String> {
+    let output = std::process::Command::new(cmd)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                CapError::DependencyMissing(format!("{} not found", cmd))
+            }
+            _ => CapError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(CapError::Other(format!(
+            "{} exited with {}",
+            cmd, output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Names and link state only, for systems without iproute2.
+#[cfg(target_os = "linux")]
+fn sysfs_interfaces() -> Vec<NetInterface> {
+    let Ok(dir) = std::fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    let mut out: Vec<NetInterface> = dir
+        .flatten()
+        .map(|e| {
+            let path = e.path();
+            let read = |f: &str| {
+                std::fs::read_to_string(path.join(f))
+                    .map(|s| s.trim().to_string())
+                    .ok()
+            };
+            NetInterface {
+                name: e.file_name().to_string_lossy().into_owned(),
+                up: read("operstate").is_some_and(|s| s == "up" || s == "unknown"),
+                loopback: read("type").as_deref() == Some("772"),
+                mac: read("address").filter(|a| a != "00:00:00:00:00:00"),
+                addresses: Vec::new(),
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Parsers
+// ---------------------------------------------------------------------------
+
+/// `ip -j addr show`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_ip_json(json: &str) -> CapResult<Vec<NetInterface>> {
+    let links: Vec<serde_json::Value> = serde_json::from_str(json)
+        .map_err(|e| CapError::Other(format!("unexpected `ip -j` output: {}", e)))?;
+    Ok(links
+        .iter()
+        .map(|l| {
+            let flags: Vec<&str> = l["flags"]
+                .as_array()
+                .map(|f| f.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            NetInterface {
+                name: l["ifname"].as_str().unwrap_or_default().to_string(),
+                up: flags.contains(&"UP") && flags.contains(&"LOWER_UP"),
+                loopback: flags.contains(&"LOOPBACK"),
+                mac: l["address"]
+                    .as_str()
+                    .filter(|a| *a != "00:00:00:00:00:00")
+                    .map(String::from),
+                addresses: l["addr_info"]
+                    .as_array()
+                    .map(|addrs| {
+                        addrs
+                            .iter()
+                            .filter_map(|a| {
+                                Some(format!("{}/{}", a["local"].as_str()?, a["prefixlen"]))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// The `00000000` destination in `/proc/net/route`; addresses are
+/// little-endian hex.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_route(text: &str) -> Option<DefaultRoute> {
+    text.lines().skip(1).find_map(|line| {
+        let f: Vec<&str> = line.split_whitespace().collect();
+        if f.len() < 3 || f[1] != "00000000" {
+            return None;
+        }
+        let gw = u32::from_str_radix(f[2], 16).ok()?.to_le_bytes();
+        Some(DefaultRoute {
+            gateway: std::net::Ipv4Addr::from(gw).to_string(),
+            interface: f[0].to_string(),
+        })
+    })
+}
+
+/// `resolvectl dns`: `Global: 1.1.1.1` and `Link 3 (wlan0): 192.168.1.1`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_resolvectl(text: &str) -> Vec<DnsScope> {
+    text.lines()
+        .filter_map(|line| {
+            let (scope, servers) = line
+                .split_once(": ")
+                .or_else(|| line.strip_suffix(':').map(|s| (s, "")))?;
+            let interface = match scope.trim() {
+                "Global" => None,
+                link => Some(link.split_once('(')?.1.trim_end_matches(')').to_string()),
+            };
+            Some(DnsScope {
+                interface,
+                servers: servers.split_whitespace().map(String::from).collect(),
+            })
+        })
+        .filter(|s| !s.servers.is_empty())
+        .collect()
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_resolv_conf(text: &str) -> Vec<DnsScope> {
+    let servers: Vec<String> = text
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("nameserver"))
+        .map(|s| s.trim().to_string())
+        .collect();
+    if servers.is_empty() {
+        return Vec::new();
+    }
+    vec![DnsScope {
+        interface: None,
+        servers,
+    }]
+}
+
+/// BSD `ifconfig`: an unindented `en0: flags=8863<UP,...>` line per
+/// interface, then indented `ether`, `inet`, `inet6` lines.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ifconfig(text: &str) -> Vec<NetInterface> {
+    let mut out: Vec<NetInterface> = Vec::new();
+    for line in text.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            let Some((name, rest)) = line.split_once(": ") else {
+                continue;
+            };
+            let flags = rest
+                .split_once('<')
+                .and_then(|(_, f)| f.split_once('>'))
+                .map(|(f, _)| f)
+                .unwrap_or_default();
+            let has = |flag: &str| flags.split(',').any(|f| f == flag);
+            out.push(NetInterface {
+                name: name.to_string(),
+                up: has("UP") && has("RUNNING"),
+                loopback: has("LOOPBACK"),
+                mac: None,
+                addresses: Vec::new(),
+            });
+            continue;
+        }
+        let Some(iface) = out.last_mut() else {
+            continue;
+        };
+        let f: Vec<&str> = line.split_whitespace().collect();
+        match f.as_slice() {
+            ["ether", mac, ..] => iface.mac = Some(mac.to_string()),
+            ["inet", addr, "netmask", mask, ..] => {
+                let bits = u32::from_str_radix(mask.trim_start_matches("0x"), 16)
+                    .map(u32::count_ones)
+                    .unwrap_or(32);
+                iface.addresses.push(format!("{}/{}", addr, bits));
+            }
+            ["inet6", addr, "prefixlen", len, ..] => {
+                let addr = addr.split('%').next().unwrap_or(addr);
+                iface.addresses.push(format!("{}/{}", addr, len));
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// `route -n get default`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_route_get(text: &str) -> Option<DefaultRoute> {
+    let field = |key: &str| {
+        text.lines()
+            .find_map(|l| l.trim().strip_prefix(key)?.strip_prefix(':'))
+            .map(|v| v.trim().to_string())
+    };
+    Some(DefaultRoute {
+        gateway: field("gateway")?,
+        interface: field("interface")?,
+    })
+}
+
+/// `scutil --dns`: `resolver #N` blocks with `nameserver[i] : addr` and,
+/// for scoped resolvers, `if_index : 6 (en0)`. Merged per interface.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_scutil_dns(text: &str) -> Vec<DnsScope> {
+    let mut out: Vec<DnsScope> = Vec::new();
+    let mut flush = |servers: &mut Vec<String>, interface: &mut Option<String>| {
+        if servers.is_empty() {
+            interface.take();
+            return;
+        }
+        let servers = std::mem::take(servers);
+        let interface = interface.take();
+        match out.iter_mut().find(|s| s.interface == interface) {
+            Some(scope) => {
+                for s in servers {
+                    if !scope.servers.contains(&s) {
+                        scope.servers.push(s);
+                    }
+                }
+            }
+            None => out.push(DnsScope { interface, servers }),
+        }
+    };
+    let (mut servers, mut interface) = (Vec::new(), None);
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("resolver #") || line.starts_with("DNS configuration") {
+            flush(&mut servers, &mut interface);
+        } else if let Some((key, value)) = line.split_once(" : ") {
+            if key.trim().starts_with("nameserver[") {
+                servers.push(value.trim().to_string());
+            } else if key.trim() == "if_index" {
+                interface = value
+                    .split_once('(')
+                    .map(|(_, n)| n.trim_end_matches(')').to_string());
+            }
+        }
+    }
+    flush(&mut servers, &mut interface);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_linux_sources() {
+        let ip = r#"[{"ifname":"lo","flags":["LOOPBACK","UP","LOWER_UP"],"address":"00:00:00:00:00:00",
+            "addr_info":[{"family":"inet","local":"127.0.0.1","prefixlen":8}]},
+            {"ifname":"wlan0","flags":["BROADCAST","UP","LOWER_UP"],"address":"aa:bb:cc:dd:ee:ff",
+            "addr_info":[{"family":"inet","local":"192.168.1.5","prefixlen":24},
+                         {"family":"inet6","local":"fe80::1","prefixlen":64}]}]"#;
+        let ifaces = parse_ip_json(ip).unwrap();
+        assert!(ifaces[0].loopback && ifaces[0].mac.is_none());
+        assert_eq!(ifaces[1].addresses, vec!["192.168.1.5/24", "fe80::1/64"]);
+
+        let route = "Iface\tDestination\tGateway\tFlags\n\
+                     wlan0\t0001A8C0\t00000000\t0001\n\
+                     wlan0\t00000000\t0101A8C0\t0003\n";
+        assert_eq!(
+            parse_proc_route(route),
+            Some(DefaultRoute {
+                gateway: "192.168.1.1".into(),
+                interface: "wlan0".into()
+            })
+        );
+
+        let resolvectl = "Global: 1.1.1.1\nLink 2 (eth0):\nLink 3 (wlan0): 192.168.1.1\nLink 4 (tun0): 10.8.0.1 10.8.0.2\n";
+        let dns = parse_resolvectl(resolvectl);
+        assert_eq!(dns.len(), 3);
+        assert_eq!(dns[2].interface.as_deref(), Some("tun0"));
+        let snapshot = NetSnapshot {
+            dns,
+            ..Default::default()
+        };
+        assert!(snapshot.dns_differs_per_interface());
+        assert_eq!(
+            parse_resolv_conf("# generated\nnameserver 10.0.0.1\nsearch lan\n")[0].servers,
+            vec!["10.0.0.1"]
+        );
+    }
+
+    #[test]
+    fn test_parse_macos_sources() {
+        let ifconfig = "\
+lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384
+\tinet 127.0.0.1 netmask 0xff000000
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tether 3c:22:fb:00:11:22
+\tinet6 fe80::1c%en0 prefixlen 64 secured scopeid 0x6
+\tinet 192.168.1.20 netmask 0xffffff00 broadcast 192.168.1.255
+";
+        let ifaces = parse_ifconfig(ifconfig);
+        assert_eq!(ifaces[0].addresses, vec!["127.0.0.1/8"]);
+        assert!(ifaces[1].up && !ifaces[1].loopback);
+        assert_eq!(ifaces[1].mac.as_deref(), Some("3c:22:fb:00:11:22"));
+        assert_eq!(ifaces[1].addresses, vec!["fe80::1c/64", "192.168.1.20/24"]);
+
+        let route = "   route to: default\ndestination: default\n    gateway: 192.168.1.1\n  interface: en0\n";
+        assert_eq!(parse_route_get(route).unwrap().interface, "en0");
+
+        let scutil = "\
+DNS configuration
+
+resolver #1
+  nameserver[0] : 192.168.1.1
+  if_index : 6 (en0)
+
+resolver #2
+  domain   : local
+  options  : mdns
+
+DNS configuration (for scoped queries)
+
+resolver #1
+  nameserver[0] : 192.168.1.1
+  if_index : 6 (en0)
+
+resolver #2
+  nameserver[0] : 10.8.0.1
+  if_index : 14 (utun3)
+";
+        let dns = parse_scutil_dns(scutil);
+        assert_eq!(dns.len(), 2);
+        assert_eq!(dns[0].servers, vec!["192.168.1.1"]);
+        assert_eq!(dns[1].interface.as_deref(), Some("utun3"));
+    }
+}
@@ -0,0 +1,11 @@
+//! Facts about the machine: hardware, network interfaces, processes,
+//! resources, packaging, power and session events, and TLS settings.
+
+pub mod devices;
+pub mod host;
+pub mod interfaces;
+pub mod processes;
+pub mod resources;
+pub mod sandbox;
+pub mod session;
+pub mod tls;
@@ -0,0 +1,336 @@
+//! Self-inspection and process listing, for "two instances fighting over
+//! the socket" style reports.
+//!
+//! Linux reads `/proc`; macOS asks `ps` (no loaded-library list there).
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use serde::Serialize;
+use serde_json::Value;
+
+/// A running process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    pub name: String,
+    /// Full command line, space-joined; empty when unreadable (other users'
+    /// processes, kernel threads).
+    pub cmdline: String,
+}
+
+/// `process_info` – this process plus a filtered process list.
+///
+/// Args: `{ "filter"?: "appctl", "all"?: false }` – `filter` matches a
+/// substring of the name or command line; without it the list shows other
+/// instances of this executable; `all` lists everything.
+/// Returns: `{ "pid", "ppid", "started_at_unix", "exe", "cwd", "libraries",
+/// "filter", "processes": [{ "pid", "ppid", "name", "cmdline" }] }` –
+/// `libraries` is `null` where the platform cannot list them, and
+/// `processes` excludes this process.
+pub(crate) fn cmd_process_info(args: Value, _ctx: &AppContext) -> Result<Value, CommandError> {
+    let exe = std::env::current_exe().ok();
+    let all = args.get("all").and_then(Value::as_bool).unwrap_or(false);
+    let (filter, same_program) = match args.get("filter") {
+        None | Some(Value::Null) => (
+            exe.as_deref()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned()),
+            true,
+        ),
+        Some(Value::String(s)) => (Some(s.clone()), false),
+        Some(_) => {
+            return Err(CommandError::InvalidInput(
+                "'filter' must be a string".into(),
+            ))
+        }
+    };
+    let filter = filter.filter(|_| !all);
+
+    let pid = std::process::id();
+    let processes: Vec<ProcessEntry> = list_processes()?
+        .into_iter()
+        .filter(|p| p.pid != pid)
+        .filter(|p| match filter.as_deref() {
+            None => true,
+            Some(program) if same_program => runs_program(p, program),
+            Some(f) => p.name.contains(f) || p.cmdline.contains(f),
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "pid": pid,
+        "ppid": parent_pid(),
+        "started_at_unix": started_at_unix(),
+        "exe": exe,
+        "cwd": std::env::current_dir().ok(),
+        "libraries": loaded_libraries(),
+        "filter": filter,
+        "processes": processes,
+    }))
+}
+
+/// Whether `p` is another instance of `program` (an executable file name).
+/// Linux truncates `comm` to 15 bytes, so argv[0] is checked too.
+fn runs_program(p: &ProcessEntry, program: &str) -> bool {
+    let argv0 = p.cmdline.split(' ').next().unwrap_or_default();
+    p.name == program
+        || (program.len() > 15 && program.starts_with(&p.name) && p.name.len() == 15)
+        || argv0.rsplit('/').next() == Some(program)
+}
+
+/// Whether a process with `pid` exists; `None` where processes cannot be
+/// listed.
+pub(crate) fn is_running(pid: u32) -> Option<bool> {
+    list_processes()
+        .ok()
+        .map(|processes| processes.iter().any(|p| p.pid == pid))
+}
+
+// ---------------------------------------------------------------------------
+// Linux
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+fn parent_pid() -> Option<u32> {
+    std::fs::read_to_string("/proc/self/stat")
+        .ok()
+        .and_then(|s| stat_fields(&s)?.get(1)?.parse().ok())
+}
+
+/// Boot time plus the process's `starttime` (field 22 of `/proc/self/stat`,
+/// in USER_HZ ticks – 100 on every Linux architecture we ship for).
+#[cfg(target_os = "linux")]
+fn started_at_unix() -> Option<u64> {
+    const USER_HZ: u64 = 100;
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let ticks: u64 = stat_fields(&stat)?.get(19)?.parse().ok()?;
+    let boot: u64 = std::fs::read_to_string("/proc/stat")
+        .ok()?
+        .lines()
+        .find_map(|l| l.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(boot + ticks / USER_HZ)
+}
+
+#[cfg(target_os = "linux")]
+fn loaded_libraries() -> Option<Vec<String>> {
+    std::fs::read_to_string("/proc/self/maps")
+        .ok()
+        .map(|maps| parse_maps_libraries(&maps))
+}
+
+#[cfg(target_os = "linux")]
+fn list_processes() -> Result<Vec<ProcessEntry>, CommandError> {
+    let mut out = Vec::new();
+    for entry in std::fs::read_dir("/proc")?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+        let dir = entry.path();
+        // Processes can exit mid-scan; skip whatever vanished.
+        let Ok(name) = std::fs::read_to_string(dir.join("comm")) else {
+            continue;
+        };
+        let cmdline = std::fs::read(dir.join("cmdline"))
+            .map(|raw| {
+                raw.split(|&b| b == 0)
+                    .filter(|a| !a.is_empty())
+                    .map(|a| String::from_utf8_lossy(a).into_owned())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+        let ppid = std::fs::read_to_string(dir.join("stat"))
+            .ok()
+            .and_then(|s| stat_fields(&s)?.get(1)?.parse().ok());
+        out.push(ProcessEntry {
+            pid,
+            ppid,
+            name: name.trim_end().to_string(),
+            cmdline,
+        });
+    }
+    out.sort_by_key(|p| p.pid);
+    Ok(out)
+}
+
+/// Fields of `/proc/<pid>/stat` after the `(comm)` field, which may itself
+/// contain spaces and parens; index 0 is the state (field 3).
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn stat_fields(stat: &str) -> Option<Vec<&str>> {
+    Some(stat[stat.rfind(')')? + 1..].split_whitespace().collect())
+}
+
+/// Distinct shared objects mapped into the process, in load order.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_maps_libraries(maps: &str) -> Vec<String> {
+    let mut libs: Vec<String> = Vec::new();
+    for path in maps.lines().filter_map(|l| l.split_whitespace().nth(5)) {
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let is_lib = file.ends_with(".so") || file.contains(".so.");
+        if path.starts_with('/') && is_lib && !libs.iter().any(|l| l == path) {
+            libs.push(path.to_string());
+        }
+    }
+    libs
+}
+
+// ---------------------------------------------------------------------------
+// macOS and others
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+fn ps(args: &[&str]) -> Option<String> {
+    std::process::Command::new("ps")
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+}
+
+#[cfg(target_os = "macos")]
+fn parent_pid() -> Option<u32> {
+    ps(&["-o", "ppid=", "-p", &std::process::id().to_string()])?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// `ps` only reports elapsed seconds portably (`etime` is `[[dd-]hh:]mm:ss`).
+#[cfg(target_os = "macos")]
+fn started_at_unix() -> Option<u64> {
+    let etime = ps(&["-o", "etime=", "-p", &std::process::id().to_string()])?;
+    let (days, clock) = match etime.trim().split_once('-') {
+        Some((d, rest)) => (d.parse::<u64>().ok()?, rest.to_string()),
+        None => (0, etime.trim().to_string()),
+    };
+    let secs = clock
+        .split(':')
+        .try_fold(0u64, |acc, part| Some(acc * 60 + part.parse::<u64>().ok()?))?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some(now.saturating_sub(days * 86_400 + secs))
+}
+
+#[cfg(target_os = "macos")]
+fn loaded_libraries() -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn list_processes() -> Result<Vec<ProcessEntry>, CommandError> {
+    let out =
+        ps(&["-axo", "pid=,ppid=,comm="]).ok_or_else(|| CommandError::Other("ps failed".into()))?;
+    Ok(out
+        .lines()
+        .filter_map(|line| {
+            let mut it = line.split_whitespace();
+            let pid = it.next()?.parse().ok()?;
+            let ppid = it.next()?.parse().ok();
+            let cmdline = it.collect::<Vec<_>>().join(" ");
+            let name = cmdline.rsplit('/').next().unwrap_or(&cmdline).to_string();
+            Some(ProcessEntry {
+                pid,
+                ppid,
+                name,
+                cmdline,
+            })
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn parent_pid() -> Option<u32> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn started_at_unix() -> Option<u64> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn loaded_libraries() -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn list_processes() -> Result<Vec<ProcessEntry>, CommandError> {
+    Err(CommandError::Unsupported(
+        "process listing is only implemented for Linux and macOS".into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_fixtures() {
+        let stat = "77 (tokio (rt) 1) S 12 77 77 0 -1 0 0 0 0 0 5 3 0 0 20 0 4 0 4242";
+        let fields = stat_fields(stat).unwrap();
+        assert_eq!(fields[1], "12");
+        assert_eq!(fields[19], "4242");
+
+        let maps = "\
+5581a000-5581b000 r--p 00000000 08:01 100 /usr/bin/appctl
+7f00a000-7f00b000 r--p 00000000 08:01 200 /usr/lib/x86_64-linux-gnu/libc.so.6
+7f00b000-7f00c000 r-xp 00001000 08:01 200 /usr/lib/x86_64-linux-gnu/libc.so.6
+7f00c000-7f00d000 rw-p 00000000 00:00 0
+7f00d000-7f00e000 r--p 00000000 08:01 300 /usr/lib/libdbus-1.so
+7ffd0000-7ffd1000 rw-p 00000000 00:00 0 [stack]
+";
+        assert_eq!(
+            parse_maps_libraries(maps),
+            vec![
+                "/usr/lib/x86_64-linux-gnu/libc.so.6",
+                "/usr/lib/libdbus-1.so"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_runs_program() {
+        let entry = |name: &str, cmdline: &str| ProcessEntry {
+            pid: 2,
+            ppid: None,
+            name: name.into(),
+            cmdline: cmdline.into(),
+        };
+        assert!(runs_program(&entry("appctl", "appctl serve"), "appctl"));
+        assert!(runs_program(
+            &entry("tauri-template-", "/opt/app/tauri-template-app"),
+            "tauri-template-app"
+        ));
+        assert!(!runs_program(
+            &entry("bash", "bash -c appctl serve"),
+            "appctl"
+        ));
+    }
+
+    #[test]
+    fn test_process_info_reports_self() {
+        let ctx = AppContext::default_headless();
+        let data = cmd_process_info(serde_json::json!({ "all": true }), &ctx).unwrap();
+        assert_eq!(data["pid"], std::process::id());
+        assert!(data["filter"].is_null());
+        let pids: Vec<u64> = data["processes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|p| p["pid"].as_u64())
+            .collect();
+        assert!(!pids.contains(&(std::process::id() as u64)));
+        if cfg!(target_os = "linux") {
+            assert!(pids.contains(&1));
+            assert!(data["started_at_unix"].as_u64().unwrap() > 1_600_000_000);
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! System resource monitoring – CPU, memory, and this process's RSS and
+//! open file descriptors.
+//!
+//! `system_stats` takes two [`ResourceSample`]s `interval_ms` apart and
+//! turns the counters into rates. Scenario `resources` steps run it and
+//! compare the figures against [`ResourceLimits`], so soak tests can catch
+//! runaway memory or leaked descriptors.
+//!
+//! Linux reads `/proc`; macOS gets load, memory size, RSS, and descriptors
+//! from `sysctl`, `ps`, and `/dev/fd` (no CPU tick counters).
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{CapResult, CpuTicks, ResourceOps, ResourceSample};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Longest `interval_ms` accepted, so a typo cannot park a command for hours.
+pub const MAX_INTERVAL_MS: u64 = 60_000;
+
+fn default_interval_ms() -> u64 {
+    500
+}
+
+/// Resource usage over one sampling interval. Percentages need two
+/// samples, so they are `None` for `interval_ms: 0`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemStats {
+    pub interval_ms: u64,
+    pub pid: u32,
+    /// Machine-wide busy time, 0–100.
+    pub cpu_percent: Option<f64>,
+    /// This process, as a share of one core (can exceed 100).
+    pub process_cpu_percent: Option<f64>,
+    pub load_avg: Option<[f64; 3]>,
+    pub mem_total_bytes: Option<u64>,
+    pub mem_used_bytes: Option<u64>,
+    pub mem_used_percent: Option<f64>,
+    pub process_rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+}
+
+/// Sample `ctx`'s resources twice, `interval` apart.
+pub fn sample_stats(ctx: &AppContext, interval: Duration) -> CapResult<SystemStats> {
+    let before = ctx.resources().sample()?;
+    let after = if interval.is_zero() {
+        None
+    } else {
+        std::thread::sleep(interval);
+        Some(ctx.resources().sample()?)
+    };
+    Ok(stats(&before, after.as_ref(), interval))
+}
+
+fn stats(
+    before: &ResourceSample,
+    after: Option<&ResourceSample>,
+    interval: Duration,
+) -> SystemStats {
+    let now = after.unwrap_or(before);
+    let round = |v: f64| (v * 10.0).round() / 10.0;
+    let cpu_delta = after.and_then(|a| {
+        let (b, a) = (before.cpu?, a.cpu?);
+        Some((
+            a.busy.checked_sub(b.busy)?,
+            a.total.checked_sub(b.total)?,
+            a.cpus,
+        ))
+    });
+    let cpu_percent = cpu_delta
+        .filter(|&(_, total, _)| total > 0)
+        .map(|(busy, total, _)| round(busy as f64 * 100.0 / total as f64));
+    let process_cpu_percent = after.and_then(|a| {
+        let used = a
+            .process_cpu_ticks?
+            .checked_sub(before.process_cpu_ticks?)?;
+        let (_, total, cpus) = cpu_delta.filter(|&(_, total, _)| total > 0)?;
+        // `total` covers every CPU; scale to one core.
+        Some(round(
+            used as f64 * 100.0 * cpus.max(1) as f64 / total as f64,
+        ))
+    });
+    let mem_used_bytes = now
+        .mem_total_bytes
+        .zip(now.mem_available_bytes)
+        .map(|(total, available)| total.saturating_sub(available));
+    SystemStats {
+        interval_ms: interval.as_millis() as u64,
+        pid: std::process::id(),
+        cpu_percent,
+        process_cpu_percent,
+        load_avg: now.load_avg,
+        mem_total_bytes: now.mem_total_bytes,
+        mem_used_bytes,
+        mem_used_percent: mem_used_bytes
+            .zip(now.mem_total_bytes)
+            .filter(|&(_, total)| total > 0)
+            .map(|(used, total)| round(used as f64 * 100.0 / total as f64)),
+        process_rss_bytes: now.process_rss_bytes,
+        open_fds: now.open_fds,
+    }
+}
+
+/// `system_stats` – CPU, memory, and this process's RSS and descriptors.
+///
+/// Args: `{ "interval_ms"?: 500 }` (0 takes a single sample, without CPU
+/// percentages; at most 60000)
+/// Returns: [`SystemStats`]
+pub(crate) fn cmd_system_stats(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let interval_ms = match args.get("interval_ms") {
+        None | Some(Value::Null) => default_interval_ms(),
+        Some(v) => v.as_u64().ok_or_else(|| {
+            CommandError::InvalidInput("'interval_ms' must be a non-negative integer".into())
+        })?,
+    };
+    if interval_ms > MAX_INTERVAL_MS {
+        return Err(CommandError::InvalidInput(format!(
+            "'interval_ms' must be at most {}",
+            MAX_INTERVAL_MS
+        )));
+    }
+    let stats = sample_stats(ctx, Duration::from_millis(interval_ms))?;
+    serde_json::to_value(stats).map_err(|e| CommandError::Other(e.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Ceilings
+// ---------------------------------------------------------------------------
+
+/// Ceilings checked by a scenario `resources` step:
+///
+/// ```yaml
+/// - resources:
+///     interval_ms: 1000
+///     max_rss_mb: 400
+///     max_open_fds: 256
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceLimits {
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rss_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_fds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_process_cpu_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_mem_used_percent: Option<f64>,
+}
+
+/// Outcome of comparing [`SystemStats`] against [`ResourceLimits`].
+#[derive(Debug, Default, PartialEq)]
+pub struct LimitCheck {
+    /// One line per ceiling broken, e.g. `process_rss_bytes 612.0 MB > 400 MB`.
+    pub exceeded: Vec<String>,
+    /// Ceilings that could not be checked on this platform.
+    pub unavailable: Vec<&'static str>,
+}
+
+impl ResourceLimits {
+    pub fn check(&self, stats: &SystemStats) -> LimitCheck {
+        let mut out = LimitCheck::default();
+        let mb = |b: u64| b as f64 / (1024.0 * 1024.0);
+        let mut compare =
+            |name: &'static str, limit: Option<f64>, actual: Option<f64>, unit: &str| {
+                let Some(limit) = limit else { return };
+                match actual {
+                    None => out.unavailable.push(name),
+                    Some(actual) if actual > limit => out.exceeded.push(format!(
+                        "{} {:.1}{} > {}{}",
+                        name, actual, unit, limit, unit
+                    )),
+                    Some(_) => {}
+                }
+            };
+        compare(
+            "process_rss_bytes",
+            self.max_rss_mb.map(|m| m as f64),
+            stats.process_rss_bytes.map(mb),
+            " MB",
+        );
+        compare(
+            "open_fds",
+            self.max_open_fds.map(|n| n as f64),
+            stats.open_fds.map(|n| n as f64),
+            "",
+        );
+        compare(
+            "process_cpu_percent",
+            self.max_process_cpu_percent,
+            stats.process_cpu_percent,
+            "%",
+        );
+        compare("cpu_percent", self.max_cpu_percent, stats.cpu_percent, "%");
+        compare(
+            "mem_used_percent",
+            self.max_mem_used_percent,
+            stats.mem_used_percent,
+            "%",
+        );
+        out
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Platform sampling
+// ---------------------------------------------------------------------------
+
+/// Counters from the OS (`/proc` on Linux, `sysctl`/`ps` on macOS).
+pub struct SystemResources;
+
+impl ResourceOps for SystemResources {
+    #[cfg(target_os = "linux")]
+    fn sample(&self) -> CapResult<ResourceSample> {
+        let read = |p: &str| std::fs::read_to_string(p).ok();
+        Ok(ResourceSample {
+            cpu: read("/proc/stat").and_then(|s| parse_proc_stat(&s)),
+            process_cpu_ticks: read("/proc/self/stat").and_then(|s| parse_process_ticks(&s)),
+            load_avg: read("/proc/loadavg").and_then(|s| parse_load_avg(&s)),
+            mem_total_bytes: read("/proc/meminfo").and_then(|s| kb_field(&s, "MemTotal:")),
+            mem_available_bytes: read("/proc/meminfo").and_then(|s| kb_field(&s, "MemAvailable:")),
+            process_rss_bytes: read("/proc/self/status").and_then(|s| kb_field(&s, "VmRSS:")),
+            open_fds: count_fds("/proc/self/fd"),
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    fn sample(&self) -> CapResult<ResourceSample> {
+        let run = |cmd: &str, args: &[&str]| {
+            std::process::Command::new(cmd)
+                .args(args)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        };
+        let pid = std::process::id().to_string();
+        Ok(ResourceSample {
+            load_avg: run("sysctl", &["-n", "vm.loadavg"])
+                .and_then(|s| parse_load_avg(s.trim_matches(|c| c == '{' || c == '}'))),
+            mem_total_bytes: run("sysctl", &["-n", "hw.memsize"]).and_then(|s| s.parse().ok()),
+            process_rss_bytes: run("ps", &["-o", "rss=", "-p", &pid])
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(|kb| kb * 1024),
+            open_fds: count_fds("/dev/fd"),
+            ..Default::default()
+        })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    fn sample(&self) -> CapResult<ResourceSample> {
+        Err(crate::traits::CapError::Unsupported(
+            "resource sampling is only implemented for Linux and macOS".into(),
+        ))
+    }
+}
+
+/// Entries in a per-process descriptor directory, less the one `read_dir`
+/// itself holds open while listing.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn count_fds(dir: &str) -> Option<u64> {
+    let n = std::fs::read_dir(dir).ok()?.count() as u64;
+    Some(n.saturating_sub(1))
+}
+
+/// The aggregate `cpu` line of `/proc/stat`: user nice system idle iowait
+/// irq softirq steal (guest time is already counted in user).
+fn parse_proc_stat(text: &str) -> Option<CpuTicks> {
+    let fields: Vec<u64> = text
+        .lines()
+        .find(|l| l.starts_with("cpu "))?
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(|f| f.parse().ok())
+        .collect::<Option<_>>()?;
+    if fields.len() < 4 {
+        return None;
+    }
+    let total: u64 = fields.iter().sum();
+    let idle = fields[3] + fields.get(4).copied().unwrap_or(0);
+    let cpus = text
+        .lines()
+        .filter(|l| l.starts_with("cpu") && l.as_bytes().get(3).is_some_and(u8::is_ascii_digit))
+        .count() as u32;
+    Some(CpuTicks {
+        busy: total - idle,
+        total,
+        cpus,
+    })
+}
+
+/// utime + stime from `/proc/<pid>/stat` (fields 14 and 15; the command
+/// name in field 2 may contain spaces, so count from its closing paren).
+fn parse_process_ticks(text: &str) -> Option<u64> {
+    let rest = &text[text.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+fn parse_load_avg(text: &str) -> Option<[f64; 3]> {
+    let mut it = text.split_whitespace().map(|f| f.parse::<f64>().ok());
+    Some([it.next()??, it.next()??, it.next()??])
+}
+
+/// A `Key:   1234 kB` line from `/proc/meminfo` or `/proc/self/status`, in bytes.
+fn kb_field(text: &str, key: &str) -> Option<u64> {
+    let kb: u64 = text
+        .lines()
+        .find_map(|l| l.strip_prefix(key))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        let stat = "cpu  100 0 50 800 50 0 0 0 0 0\ncpu0 50 0 25 400 25 0 0 0 0 0\ncpu1 50 0 25 400 25 0 0 0 0 0\nintr 1\n";
+        assert_eq!(
+            parse_proc_stat(stat),
+            Some(CpuTicks {
+                busy: 150,
+                total: 1000,
+                cpus: 2
+            })
+        );
+        let self_stat = "4242 (my (odd) app) S 1 4242 4242 0 -1 4194304 100 0 0 0 30 12 0 0 20 0 4";
+        assert_eq!(parse_process_ticks(self_stat), Some(42));
+        assert_eq!(
+            parse_load_avg("0.52 0.41 0.30 1/123 4567\n"),
+            Some([0.52, 0.41, 0.30])
+        );
+        let meminfo =
+            "MemTotal:       16000000 kB\nMemFree:         1000 kB\nMemAvailable:    8000000 kB\n";
+        assert_eq!(kb_field(meminfo, "MemAvailable:"), Some(8_000_000 * 1024));
+    }
+
+    #[test]
+    fn test_stats_and_limits() {
+        let before = ResourceSample {
+            cpu: Some(CpuTicks {
+                busy: 100,
+                total: 1000,
+                cpus: 4,
+            }),
+            process_cpu_ticks: Some(10),
+            mem_total_bytes: Some(1000),
+            mem_available_bytes: Some(750),
+            ..Default::default()
+        };
+        let after = ResourceSample {
+            cpu: Some(CpuTicks {
+                busy: 300,
+                total: 1400,
+                cpus: 4,
+            }),
+            process_cpu_ticks: Some(60),
+            mem_total_bytes: Some(1000),
+            mem_available_bytes: Some(600),
+            process_rss_bytes: Some(300 * 1024 * 1024),
+            open_fds: Some(12),
+            ..Default::default()
+        };
+        let s = stats(&before, Some(&after), Duration::from_millis(1000));
+        assert_eq!(s.cpu_percent, Some(50.0));
+        assert_eq!(s.process_cpu_percent, Some(50.0));
+        assert_eq!(s.mem_used_percent, Some(40.0));
+        assert_eq!(stats(&before, None, Duration::ZERO).cpu_percent, None);
+
+        let limits: ResourceLimits =
+            serde_yaml::from_str("max_rss_mb: 256\nmax_open_fds: 64\nmax_cpu_percent: 90\n")
+                .unwrap();
+        let mut unknown = s.clone();
+        unknown.cpu_percent = None;
+        assert_eq!(
+            limits.check(&unknown),
+            LimitCheck {
+                exceeded: vec!["process_rss_bytes 300.0 MB > 256 MB".into()],
+                unavailable: vec!["cpu_percent"],
+            }
+        );
+        assert!(serde_yaml::from_str::<ResourceLimits>("max_rss: 1").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Packaging and container detection – AppImage, Flatpak, Snap, Docker, WSL.
+//!
+//! Inside these sandboxes the filesystem is remapped or filtered (a Snap's
+//! `$HOME` is `~/snap/<name>/<rev>`, a Flatpak sees only the paths it was
+//! granted), so a `filesystem` probe result only means something alongside
+//! [`report`]'s list of which host paths are actually reachable.
+//! [`kinds`] is the cheap subset stamped on every result's `env_summary`.
+
+use crate::types::{HostPathAccess, SandboxKind, SandboxReport};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Sandboxes the process runs inside. Detected once; the answer cannot
+/// change while the process is alive.
+pub fn kinds() -> &'static [SandboxKind] {
+    static KINDS: OnceLock<Vec<SandboxKind>> = OnceLock::new();
+    KINDS.get_or_init(|| detect(&env_var, &read_file).kinds)
+}
+
+/// Full report for `doctor`, including host path access checks.
+pub fn report() -> SandboxReport {
+    let mut report = detect(&env_var, &read_file);
+    report.host_paths = host_path_candidates(&report, &env_var)
+        .iter()
+        .map(|p| check_access(Path::new(p)))
+        .collect();
+    report
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn read_file(path: &str) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+/// Classify the environment from `env` and file contents (`read` returns
+/// `None` for a missing file).
+fn detect(
+    env: &dyn Fn(&str) -> Option<String>,
+    read: &dyn Fn(&str) -> Option<String>,
+) -> SandboxReport {
+    let mut r = SandboxReport::default();
+
+    if let Some(path) = env("APPIMAGE") {
+        r.kinds.push(SandboxKind::AppImage);
+        r.appimage_path = Some(path);
+        r.appimage_mount = env("APPDIR");
+    }
+
+    if let Some(info) = read("/.flatpak-info") {
+        r.kinds.push(SandboxKind::Flatpak);
+        r.flatpak_app_id = ini_value(&info, "Application", "name").or_else(|| env("FLATPAK_ID"));
+        r.flatpak_filesystems = ini_value(&info, "Context", "filesystems")
+            .map(|v| {
+                v.split(';')
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    if let Some(snap) = env("SNAP") {
+        r.kinds.push(SandboxKind::Snap);
+        r.snap_name = env("SNAP_NAME");
+        r.snap_confinement = read(&format!("{}/meta/snap.yaml", snap))
+            .and_then(|yaml| yaml_scalar(&yaml, "confinement"));
+    }
+
+    r.container_runtime = container_runtime(env, read);
+    if r.container_runtime.is_some() {
+        r.kinds.push(SandboxKind::Docker);
+    }
+
+    let osrelease = read("/proc/sys/kernel/osrelease").unwrap_or_default();
+    if env("WSL_DISTRO_NAME").is_some() || osrelease.to_lowercase().contains("microsoft") {
+        r.kinds.push(SandboxKind::Wsl);
+        r.wsl_distro = env("WSL_DISTRO_NAME");
+    }
+
+    r
+}
+
+fn container_runtime(
+    env: &dyn Fn(&str) -> Option<String>,
+    read: &dyn Fn(&str) -> Option<String>,
+) -> Option<String> {
+    if read("/.dockerenv").is_some() {
+        return Some("docker".into());
+    }
+    if read("/run/.containerenv").is_some() {
+        return Some("podman".into());
+    }
+    // Set by podman, systemd-nspawn, lxc; Flatpak sets it too but is
+    // reported on its own.
+    if let Some(name) = env("container").filter(|c| c != "flatpak") {
+        return Some(name);
+    }
+    let cgroup = read("/proc/1/cgroup").unwrap_or_default();
+    ["kubepods", "docker", "containerd", "lxc"]
+        .into_iter()
+        .find(|marker| cgroup.contains(marker))
+        .map(|marker| match marker {
+            "kubepods" => "kubernetes".to_string(),
+            other => other.to_string(),
+        })
+}
+
+/// `key=value` under `[section]` in a keyfile like `/.flatpak-info`.
+fn ini_value(text: &str, section: &str, key: &str) -> Option<String> {
+    let header = format!("[{}]", section);
+    let mut in_section = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == header;
+        } else if in_section {
+            if let Some((k, v)) = line.split_once('=') {
+                if k.trim() == key {
+                    return Some(v.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A top-level `key: value` from `snap.yaml`.
+fn yaml_scalar(text: &str, key: &str) -> Option<String> {
+    text.lines()
+        .filter_map(|l| l.strip_prefix(key)?.strip_prefix(':'))
+        .map(|v| v.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+        .next()
+}
+
+/// Host paths worth reporting: home (the real one under Snap), Downloads,
+/// temp, removable media mounts, and the Windows drive under WSL.
+fn host_path_candidates(
+    report: &SandboxReport,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Vec<String> {
+    let mut paths = Vec::new();
+    if let Some(home) = env("SNAP_REAL_HOME").or_else(|| env("HOME")) {
+        paths.push(env("XDG_DOWNLOAD_DIR").unwrap_or_else(|| format!("{}/Downloads", home)));
+        paths.insert(0, home);
+    }
+    paths.push(std::env::temp_dir().to_string_lossy().into_owned());
+    if cfg!(target_os = "linux") {
+        paths.extend(["/media".to_string(), "/mnt".to_string()]);
+    }
+    if report.kinds.contains(&SandboxKind::Wsl) {
+        paths.push("/mnt/c".into());
+    }
+    paths
+}
+
+/// Check `path` the way the app would use it: list it, then create and
+/// remove a scratch file (read-only bind mounts pass a mode-bit check).
+fn check_access(path: &Path) -> HostPathAccess {
+    let exists = path.exists();
+    let readable = exists && std::fs::read_dir(path).is_ok();
+    let writable = exists && {
+        let probe = path.join(format!(".appctl-doctor-{}", std::process::id()));
+        let created = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&probe)
+            .is_ok();
+        if created {
+            let _ = std::fs::remove_file(&probe);
+        }
+        created
+    };
+    HostPathAccess {
+        path: path.to_string_lossy().into_owned(),
+        exists,
+        readable,
+        writable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect_with(env: &[(&str, &str)], files: &[(&str, &str)]) -> SandboxReport {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let files: HashMap<String, String> = files
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        detect(&|k| env.get(k).cloned(), &|p| files.get(p).cloned())
+    }
+
+    #[test]
+    fn test_detect_packaging_formats() {
+        assert!(detect_with(&[], &[("/proc/1/cgroup", "0::/init.scope\n")])
+            .kinds
+            .is_empty());
+
+        let flatpak = detect_with(
+            &[],
+            &[(
+                "/.flatpak-info",
+                "[Application]\nname=org.example.App\n\n[Context]\nshared=network;ipc;\nfilesystems=xdg-download;home:ro;\n",
+            )],
+        );
+        assert_eq!(flatpak.kinds, vec![SandboxKind::Flatpak]);
+        assert_eq!(flatpak.flatpak_app_id.as_deref(), Some("org.example.App"));
+        assert_eq!(flatpak.flatpak_filesystems, vec!["xdg-download", "home:ro"]);
+
+        let snap = detect_with(
+            &[("SNAP", "/snap/app/12"), ("SNAP_NAME", "app")],
+            &[(
+                "/snap/app/12/meta/snap.yaml",
+                "name: app\nconfinement: classic\n",
+            )],
+        );
+        assert_eq!(snap.kinds, vec![SandboxKind::Snap]);
+        assert_eq!(snap.snap_confinement.as_deref(), Some("classic"));
+
+        let appimage = detect_with(
+            &[
+                ("APPIMAGE", "/home/u/App.AppImage"),
+                ("APPDIR", "/tmp/.mount_AppXyz"),
+            ],
+            &[],
+        );
+        assert_eq!(appimage.kinds, vec![SandboxKind::AppImage]);
+        assert_eq!(
+            appimage.appimage_mount.as_deref(),
+            Some("/tmp/.mount_AppXyz")
+        );
+    }
+
+    #[test]
+    fn test_detect_containers_and_wsl() {
+        let k8s = detect_with(&[], &[("/proc/1/cgroup", "0::/kubepods/besteffort/pod1\n")]);
+        assert_eq!(k8s.container_runtime.as_deref(), Some("kubernetes"));
+
+        let docker_in_wsl = detect_with(
+            &[],
+            &[
+                ("/.dockerenv", ""),
+                (
+                    "/proc/sys/kernel/osrelease",
+                    "5.15.153.1-microsoft-standard-WSL2\n",
+                ),
+            ],
+        );
+        assert_eq!(
+            docker_in_wsl.kinds,
+            vec![SandboxKind::Docker, SandboxKind::Wsl]
+        );
+        assert_eq!(docker_in_wsl.container_runtime.as_deref(), Some("docker"));
+
+        let flatpak_env = detect_with(&[("container", "flatpak")], &[]);
+        assert_eq!(flatpak_env.container_runtime, None);
+    }
+
+    #[test]
+    fn test_check_access() {
+        let dir = tempfile::tempdir().unwrap();
+        let ok = check_access(dir.path());
+        assert!(ok.exists && ok.readable && ok.writable);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let missing = check_access(&dir.path().join("gone"));
+        assert!(!missing.exists && !missing.readable && !missing.writable);
+    }
+}
@@ -0,0 +1,387 @@
+//! Power and session events (sleep/wake, lock/unlock, shutdown).
+//!
+//! [`forward`] starts the context's [`SessionOps`] backend and republishes
+//! each signal on the event bus as `session:<event>` (e.g. `session:wake`,
+//! payload `{ "event": "wake", "source": "logind" }`), so the Tauri frontend
+//! receives it like any other engine event and scenario runs record it in
+//! their result.
+//!
+//! On Linux the signals come from systemd-logind over the system D-Bus
+//! (`PrepareForSleep`, `PrepareForShutdown`, and the current session's
+//! `Lock` / `Unlock`). On macOS sleep and wake are read from `pmset -g log`,
+//! polled, until IOKit notifications are wired up; lock/unlock and shutdown
+//! are not reported there yet. Other platforms report UNSUPPORTED.
+
+use crate::context::AppContext;
+use crate::traits::{CapError, CapResult, SessionCallback, SessionEvent, SessionOps};
+use std::sync::{Arc, Mutex};
+
+/// Event bus topic for `event`.
+pub fn topic(event: SessionEvent) -> String {
+    format!("session:{}", event.as_str())
+}
+
+/// Publish the context's session events on its event bus until the process
+/// exits. Call once at startup.
+pub fn forward(ctx: &AppContext) -> CapResult<()> {
+    let bus = ctx.event_bus();
+    let ids = ctx.id_source();
+    let source = ctx.session().source().to_string();
+    ctx.session().watch(Box::new(move |event| {
+        tracing::info!(target: "session", source = %source, "{}", event.as_str());
+        bus.emit(
+            &ids.run_id(),
+            &topic(event),
+            serde_json::json!({ "event": event, "source": source }),
+        );
+    }))
+}
+
+/// The signal source for this OS.
+pub fn platform_default() -> Box<dyn SessionOps> {
+    #[cfg(target_os = "linux")]
+    {
+        Box::new(LogindSession)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(PmsetSession)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Box::new(UnsupportedSession(
+            "power/session events are only implemented for Linux and macOS".into(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// logind (Linux)
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+pub use logind::LogindSession;
+
+#[cfg(target_os = "linux")]
+mod logind {
+    use super::*;
+    use zbus::blocking::proxy::SignalIterator;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::message::Message;
+    use zbus::zvariant::OwnedObjectPath;
+
+    const LOGIND: &str = "org.freedesktop.login1";
+    const MANAGER_PATH: &str = "/org/freedesktop/login1";
+    const MANAGER: &str = "org.freedesktop.login1.Manager";
+    const SESSION: &str = "org.freedesktop.login1.Session";
+
+    /// systemd-logind signals on the system bus.
+    pub struct LogindSession;
+
+    fn dbus_err(e: zbus::Error) -> CapError {
+        CapError::Other(format!("logind: {}", e))
+    }
+
+    /// Map a signal to an event; `None` for ones we ignore (e.g. a
+    /// cancelled shutdown).
+    fn to_event(signal: &str, msg: &Message) -> Option<SessionEvent> {
+        let starting = || msg.body().deserialize::<bool>().ok();
+        match signal {
+            "PrepareForSleep" => Some(if starting()? {
+                SessionEvent::Sleep
+            } else {
+                SessionEvent::Wake
+            }),
+            "PrepareForShutdown" => starting()?.then_some(SessionEvent::Shutdown),
+            "Lock" => Some(SessionEvent::Lock),
+            "Unlock" => Some(SessionEvent::Unlock),
+            _ => None,
+        }
+    }
+
+    fn subscribe() -> CapResult<Vec<(&'static str, SignalIterator<'static>)>> {
+        let conn = Connection::system()
+            .map_err(|e| CapError::Unsupported(format!("no system D-Bus: {}", e)))?;
+        let manager = Proxy::new(&conn, LOGIND, MANAGER_PATH, MANAGER).map_err(dbus_err)?;
+        let mut streams = Vec::new();
+        for signal in ["PrepareForSleep", "PrepareForShutdown"] {
+            streams.push((signal, manager.receive_signal(signal).map_err(dbus_err)?));
+        }
+        // Lock/Unlock are per session; a process outside any session (e.g.
+        // a system service) only gets the manager signals.
+        match manager.call::<_, _, OwnedObjectPath>("GetSessionByPID", &std::process::id()) {
+            Ok(path) => {
+                let session =
+                    Proxy::new_owned(conn.clone(), LOGIND, path, SESSION).map_err(dbus_err)?;
+                for signal in ["Lock", "Unlock"] {
+                    streams.push((signal, session.receive_signal(signal).map_err(dbus_err)?));
+                }
+            }
+            Err(e) => tracing::debug!("no logind session for this process: {}", e),
+        }
+        Ok(streams)
+    }
+
+    impl SessionOps for LogindSession {
+        fn source(&self) -> &str {
+            "logind"
+        }
+
+        fn watch(&self, on_event: SessionCallback) -> CapResult<()> {
+            let on_event = Arc::new(on_event);
+            for (signal, stream) in subscribe()? {
+                let on_event = on_event.clone();
+                std::thread::Builder::new()
+                    .name(format!("logind-{}", signal))
+                    .spawn(move || {
+                        for msg in stream {
+                            if let Some(event) = to_event(signal, &msg) {
+                                on_event(event);
+                            }
+                        }
+                    })?;
+            }
+            Ok(())
+        }
+
+        fn check(&self) -> CapResult<Vec<String>> {
+            Ok(subscribe()?
+                .into_iter()
+                .map(|(signal, _)| signal.to_string())
+                .collect())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// pmset (macOS)
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+pub use pmset::PmsetSession;
+
+#[cfg(target_os = "macos")]
+mod pmset {
+    use super::*;
+    use std::time::Duration;
+
+    /// How often the power log is re-read. A wake is reported at most this
+    /// long after the machine comes back.
+    const POLL: Duration = Duration::from_secs(15);
+
+    /// Sleep/wake entries from `pmset -g log`.
+    pub struct PmsetSession;
+
+    fn read_log() -> CapResult<String> {
+        let out = std::process::Command::new("pmset")
+            .args(["-g", "log"])
+            .env("LC_ALL", "C")
+            .output()
+            .map_err(|e| CapError::Unsupported(format!("pmset: {}", e)))?;
+        if !out.status.success() {
+            return Err(CapError::Other(format!("pmset exited with {}", out.status)));
+        }
+        Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+    }
+
+    impl SessionOps for PmsetSession {
+        fn source(&self) -> &str {
+            "pmset"
+        }
+
+        fn watch(&self, on_event: SessionCallback) -> CapResult<()> {
+            let mut cursor = PmsetCursor::default();
+            // Everything already in the log happened before we started.
+            cursor.take(&read_log()?);
+            std::thread::Builder::new()
+                .name("pmset-log".into())
+                .spawn(move || loop {
+                    std::thread::sleep(POLL);
+                    match read_log() {
+                        Ok(log) => {
+                            for event in cursor.take(&log) {
+                                on_event(event);
+                            }
+                        }
+                        Err(e) => tracing::debug!("pmset: {}", e),
+                    }
+                })?;
+            Ok(())
+        }
+
+        fn check(&self) -> CapResult<Vec<String>> {
+            read_log()?;
+            Ok(vec!["Sleep".into(), "Wake".into()])
+        }
+    }
+}
+
+/// How far `pmset -g log` has been read: the last entry's timestamp and how
+/// many entries share it (the log has one-second resolution).
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Default)]
+struct PmsetCursor {
+    stamp: String,
+    at_stamp: usize,
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+impl PmsetCursor {
+    /// Events for the entries after the cursor, which then moves to the
+    /// end of `log`. Lines look like
+    /// `2026-01-15 08:12:34 +0100 Sleep  \tEntering Sleep state due to ...`;
+    /// `DarkWake` (maintenance wakes with the display off) is ignored.
+    fn take(&mut self, log: &str) -> Vec<SessionEvent> {
+        let mut events = Vec::new();
+        let mut last = (String::new(), 0);
+        let mut seen_at_old = 0;
+        for line in log.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(date), Some(time), Some(_zone), Some(kind)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if date.len() != 10 || date.as_bytes()[4] != b'-' {
+                continue;
+            }
+            let stamp = format!("{} {}", date, time);
+            if stamp == last.0 {
+                last.1 += 1;
+            } else {
+                last = (stamp.clone(), 1);
+            }
+            if stamp < self.stamp {
+                continue;
+            }
+            if stamp == self.stamp {
+                seen_at_old += 1;
+                if seen_at_old <= self.at_stamp {
+                    continue;
+                }
+            }
+            match kind {
+                "Sleep" => events.push(SessionEvent::Sleep),
+                "Wake" => events.push(SessionEvent::Wake),
+                _ => {}
+            }
+        }
+        if !last.0.is_empty() {
+            (self.stamp, self.at_stamp) = last;
+        }
+        events
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Other backends
+// ---------------------------------------------------------------------------
+
+/// Backend for platforms without session signal support.
+pub struct UnsupportedSession(pub String);
+
+impl SessionOps for UnsupportedSession {
+    fn source(&self) -> &str {
+        "none"
+    }
+    fn watch(&self, _on_event: SessionCallback) -> CapResult<()> {
+        Err(CapError::Unsupported(self.0.clone()))
+    }
+    fn check(&self) -> CapResult<Vec<String>> {
+        Err(CapError::Unsupported(self.0.clone()))
+    }
+}
+
+/// Events fired by hand (tests, simulations). Clones share listeners, so
+/// keep one to [`fire`](Self::fire) after handing another to the context.
+#[derive(Clone, Default)]
+pub struct ManualSession {
+    listeners: Arc<Mutex<Vec<SessionCallback>>>,
+}
+
+impl ManualSession {
+    pub fn fire(&self, event: SessionEvent) {
+        for listener in self
+            .listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            listener(event);
+        }
+    }
+}
+
+impl SessionOps for ManualSession {
+    fn source(&self) -> &str {
+        "manual"
+    }
+    fn watch(&self, on_event: SessionCallback) -> CapResult<()> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(on_event);
+        Ok(())
+    }
+    fn check(&self) -> CapResult<Vec<String>> {
+        Ok(vec!["manual".into()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_publishes_session_topics() {
+        let session = ManualSession::default();
+        let ctx = AppContext::default_headless().with_session(Box::new(session.clone()));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        ctx.events().subscribe(move |ev| {
+            sink.lock()
+                .unwrap()
+                .push((ev.topic.clone(), ev.payload["source"].clone()))
+        });
+
+        forward(&ctx).unwrap();
+        session.fire(SessionEvent::Sleep);
+        session.fire(SessionEvent::Wake);
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                ("session:sleep".to_string(), serde_json::json!("manual")),
+                ("session:wake".to_string(), serde_json::json!("manual")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pmset_cursor_reports_new_entries_once() {
+        let history = "\
+Time stamp                Domain              Message
+2026-01-15 08:12:34 +0100 Sleep               \tEntering Sleep state due to 'Idle Sleep'
+2026-01-15 09:00:01 +0100 DarkWake            \tDarkWake from Deep Idle
+2026-01-15 09:30:00 +0100 Wake                \tWake from Deep Idle
+";
+        let mut cursor = PmsetCursor::default();
+        assert_eq!(
+            cursor.take(history),
+            vec![SessionEvent::Sleep, SessionEvent::Wake]
+        );
+        assert!(cursor.take(history).is_empty());
+
+        // A sleep that failed within the same second as the last wake.
+        let later = format!(
+            "{}{}{}",
+            history,
+            "2026-01-15 09:30:00 +0100 Sleep               \tEntering Sleep state\n",
+            "2026-01-15 09:45:10 +0100 Wake                \tWake from Normal Sleep\n",
+        );
+        assert_eq!(
+            cursor.take(&later),
+            vec![SessionEvent::Sleep, SessionEvent::Wake]
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! Opt-in usage telemetry: how often each command runs and which error
+//! codes come back, counted locally and uploaded in batches.
+//!
+//! Nothing is counted unless an endpoint is configured (`telemetry:` in the
+//! app config, or `$APP__TELEMETRY_URL`) *and* the user has granted the
+//! `telemetry` feature (see [`crate::consent`]; `telemetry_set
+//! {"enabled": true}` is a shortcut). Withdrawing consent drops the pending
+//! counts and the install id.
+//!
+//! ```yaml
+//! telemetry:
+//!   endpoint: https://telemetry.example.com/v1/batches
+//!   batch_size: 50     # pending counts before a background upload
+//! ```
+//!
+//! Counts wait in `<data_dir>/telemetry.json` until [`flush`] POSTs them
+//! through [`AppContext::network`]:
+//!
+//! ```json
+//! {"install_id": "…", "app_version": "1.2.0", "os": "macos", "arch": "aarch64",
+//!  "since": 1767225600, "until": 1767229200,
+//!  "commands": {"ping": 3}, "errors": {"INVALID_INPUT": 1}}
+//! ```
+//!
+//! That is the whole payload: no arguments, messages, paths, or host
+//! names, and command names the registry does not know are counted as
+//! `unknown`. The install id is random, not derived from the machine.
+
+use crate::commands::CommandError;
+use crate::context::AppContext;
+use crate::traits::{HttpBody, HttpRequest};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Environment variable overriding the upload endpoint.
+pub const TELEMETRY_URL_ENV: &str = "APP__TELEMETRY_URL";
+
+/// File under [`AppContext::data_dir`] holding the pending counts.
+pub const STATE_FILE: &str = "telemetry.json";
+
+const UPLOAD_TIMEOUT_MS: u64 = 15_000;
+
+/// Serializes read-modify-write of the state file within this process.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where batches go (`telemetry:` in the app config).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// HTTPS URL batches are POSTed to; `None` turns telemetry off.
+    pub endpoint: Option<String>,
+    /// Pending counts that make a non-forced [`flush`] upload.
+    pub batch_size: u64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            batch_size: 50,
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// The default config with `$APP__TELEMETRY_URL` as the endpoint.
+    pub fn from_env() -> Self {
+        Self {
+            endpoint: std::env::var(TELEMETRY_URL_ENV)
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+            ..Self::default()
+        }
+    }
+}
+
+/// The counts not yet uploaded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryState {
+    /// Random id sent with every batch; created with the first count and
+    /// reset on opt-out.
+    pub install_id: Option<String>,
+    pub commands: BTreeMap<String, u64>,
+    /// Error code frequencies, by code (`INVALID_INPUT`).
+    pub errors: BTreeMap<String, u64>,
+    /// Unix seconds of the first pending count.
+    pub since: Option<u64>,
+    /// Unix seconds of the last successful upload.
+    pub last_upload: Option<u64>,
+}
+
+impl TelemetryState {
+    /// Pending command runs.
+    pub fn pending(&self) -> u64 {
+        self.commands.values().sum()
+    }
+}
+
+fn state_path(ctx: &AppContext) -> PathBuf {
+    ctx.data_dir.join(STATE_FILE)
+}
+
+/// The recorded state; missing or unreadable means nothing is pending.
+pub fn load_state(ctx: &AppContext) -> TelemetryState {
+    ctx.fs()
+        .read_file(&state_path(ctx))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(ctx: &AppContext, state: &TelemetryState) -> Result<(), CommandError> {
+    let json = serde_json::to_vec_pretty(state).map_err(|e| CommandError::Other(e.to_string()))?;
+    ctx.fs().write_file_atomic(&state_path(ctx), &json)?;
+    Ok(())
+}
+
+/// Load, change, and save the state under the process-wide lock.
+fn update<T>(
+    ctx: &AppContext,
+    change: impl FnOnce(&mut TelemetryState) -> T,
+) -> Result<T, CommandError> {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_state(ctx);
+    let out = change(&mut state);
+    save_state(ctx, &state)?;
+    Ok(out)
+}
+
+fn now(ctx: &AppContext) -> u64 {
+    ctx.clock()
+        .now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Count one command run, if telemetry is configured and opted into.
+/// `command` must be a registered name (or `unknown`).
+pub fn record(ctx: &AppContext, command: &str, error: Option<ErrorCode>) {
+    if ctx.telemetry.endpoint.is_none() {
+        return;
+    }
+    if !crate::consent::granted(ctx, "telemetry") {
+        return;
+    }
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_state(ctx);
+    state
+        .install_id
+        .get_or_insert_with(|| uuid::Uuid::new_v4().to_string());
+    *state.commands.entry(command.to_string()).or_default() += 1;
+    if let Some(code) = error {
+        *state.errors.entry(code.to_string()).or_default() += 1;
+    }
+    state.since.get_or_insert_with(|| now(ctx));
+    if let Err(e) = save_state(ctx, &state) {
+        tracing::debug!("cannot record telemetry: {}", e);
+    }
+}
+
+/// Upload the pending counts as one batch. Without `force`, waits until
+/// `batch_size` runs are pending. Skips when offline, unconfigured, or not
+/// opted in; `data` is `{"uploaded": <runs>}`.
+pub async fn flush(ctx: &AppContext, force: bool) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let start = ctx.stopwatch();
+    if ctx.offline {
+        return result_offline("telemetry", "flush", &run_id);
+    }
+    let skip =
+        |reason: &str| result_skip("telemetry", "flush", &run_id, start.elapsed_ms(), reason);
+    let Some(endpoint) = ctx.telemetry.endpoint.as_deref() else {
+        return skip("telemetry is not configured");
+    };
+    if let Err(e) = check_endpoint(endpoint) {
+        return result_err(
+            "telemetry",
+            "flush",
+            &run_id,
+            start.elapsed_ms(),
+            ErrorCode::InvalidInput,
+            e,
+        );
+    }
+    if !crate::consent::granted(ctx, "telemetry") {
+        return skip("telemetry is off");
+    }
+    let state = {
+        let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        load_state(ctx)
+    };
+
+    let pending = state.pending();
+    let mut r = result_ok("telemetry", "flush", &run_id, start.elapsed_ms());
+    if pending == 0 || (!force && pending < ctx.telemetry.batch_size) {
+        r.data = Some(serde_json::json!({ "uploaded": 0, "pending": pending }));
+        return r;
+    }
+
+    let request = HttpRequest::post(endpoint, HttpBody::Json(batch(ctx, &state)))
+        .timeout_ms(UPLOAD_TIMEOUT_MS);
+    let failure = match ctx.network().send(request).await {
+        Ok(resp) if resp.is_success() => None,
+        Ok(resp) => Some(format!("telemetry upload returned HTTP {}", resp.status)),
+        Err(e) => Some(format!("telemetry upload failed: {}", e)),
+    };
+    if let Some(message) = failure {
+        return result_err(
+            "telemetry",
+            "flush",
+            &run_id,
+            start.elapsed_ms(),
+            ErrorCode::NetworkError,
+            message,
+        );
+    }
+
+    // Runs counted while the upload was in flight stay pending.
+    let uploaded = update(ctx, |current| {
+        subtract(&mut current.commands, &state.commands);
+        subtract(&mut current.errors, &state.errors);
+        current.since = (current.pending() > 0).then(|| now(ctx));
+        current.last_upload = Some(now(ctx));
+    });
+    if let Err(e) = uploaded {
+        tracing::warn!("telemetry uploaded but not cleared: {}", e);
+    }
+    r.timing_ms.total = start.elapsed_ms();
+    r.data = Some(serde_json::json!({ "uploaded": pending, "pending": 0 }));
+    r
+}
+
+fn subtract(counts: &mut BTreeMap<String, u64>, sent: &BTreeMap<String, u64>) {
+    for (key, n) in sent {
+        if let Some(count) = counts.get_mut(key) {
+            *count = count.saturating_sub(*n);
+        }
+    }
+    counts.retain(|_, n| *n > 0);
+}
+
+/// The upload body; see the module docs for what it may contain.
+fn batch(ctx: &AppContext, state: &TelemetryState) -> Value {
+    serde_json::json!({
+        "install_id": state.install_id,
+        "app_version": crate::host::app_version(),
+        "os": current_os(),
+        "arch": std::env::consts::ARCH,
+        "since": state.since,
+        "until": now(ctx),
+        "commands": state.commands,
+        "errors": state.errors,
+    })
+}
+
+/// Batches go over HTTPS, or plain HTTP to this machine for testing.
+fn check_endpoint(endpoint: &str) -> Result<(), String> {
+    let url =
+        reqwest::Url::parse(endpoint).map_err(|e| format!("invalid telemetry endpoint: {}", e))?;
+    let local = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+    if url.scheme() == "https" || (url.scheme() == "http" && local) {
+        Ok(())
+    } else {
+        Err(format!("telemetry endpoint must use https: {}", endpoint))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// `telemetry_status` – consent, configuration, and what is pending.
+///
+/// Returns: `{ "configured": true, "enabled": false, "install_id": null,
+/// "pending": {"commands": {...}, "errors": {...}}, "last_upload": null }`
+pub fn cmd_telemetry_status(_args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let state = load_state(ctx);
+    Ok(serde_json::json!({
+        "configured": ctx.telemetry.endpoint.is_some(),
+        "enabled": crate::consent::granted(ctx, "telemetry"),
+        "install_id": state.install_id,
+        "pending": {
+            "commands": state.commands,
+            "errors": state.errors,
+        },
+        "last_upload": state.last_upload,
+    }))
+}
+
+/// Drop the pending counts and the install id (consent was withdrawn).
+pub fn forget(ctx: &AppContext) -> Result<(), CommandError> {
+    update(ctx, |state| {
+        *state = TelemetryState {
+            last_upload: state.last_upload,
+            ..Default::default()
+        };
+    })
+}
+
+/// `telemetry_set` – opt in or out; the same as setting the `telemetry`
+/// feature with `consent_set`.
+///
+/// Args: `{ "enabled": true }`. Opting out forgets the install id and
+/// drops pending counts.
+/// Returns: the same as `telemetry_status`.
+pub fn cmd_telemetry_set(args: Value, ctx: &AppContext) -> Result<Value, CommandError> {
+    let enabled = args
+        .get("enabled")
+        .and_then(Value::as_bool)
+        .ok_or_else(|| CommandError::InvalidInput("missing 'enabled' boolean field".into()))?;
+    crate::consent::set_feature(ctx, "telemetry", enabled)?;
+    cmd_telemetry_status(Value::Null, ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::platform::MockNetwork;
+    use crate::traits::{CapResult, HttpResponse, NetworkOps};
+    use std::sync::Arc;
+
+    /// Lets the test read the requests after the context owns the network.
+    struct Shared(Arc<MockNetwork>);
+
+    #[async_trait::async_trait]
+    impl NetworkOps for Shared {
+        async fn dns_resolve(&self, host: &str) -> CapResult<Vec<String>> {
+            self.0.dns_resolve(host).await
+        }
+
+        async fn https_get(&self, url: &str, timeout_ms: u64) -> CapResult<(u16, String)> {
+            self.0.https_get(url, timeout_ms).await
+        }
+
+        async fn send(&self, request: HttpRequest) -> CapResult<HttpResponse> {
+            self.0.send(request).await
+        }
+    }
+
+    const ENDPOINT: &str = "https://telemetry.test/v1/batches";
+
+    fn context(dir: &std::path::Path, network: Arc<MockNetwork>) -> AppContext {
+        let mut ctx = AppContext::default_headless().with_network(Box::new(Shared(network)));
+        ctx.data_dir = dir.to_path_buf();
+        ctx.offline = false;
+        ctx.telemetry.endpoint = Some(ENDPOINT.into());
+        ctx
+    }
+
+    #[test]
+    fn test_counts_only_after_opt_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = context(dir.path(), Arc::new(MockNetwork::new()));
+        let registry = CommandRegistry::new();
+
+        registry.execute("ping", Value::Null, &ctx);
+        assert_eq!(load_state(&ctx).pending(), 0);
+
+        let status = cmd_telemetry_set(serde_json::json!({ "enabled": true }), &ctx).unwrap();
+        assert_eq!(status["enabled"], true);
+        registry.execute("ping", Value::Null, &ctx);
+        registry.execute("read_file", serde_json::json!({}), &ctx);
+        registry.execute("secret-looking-name", Value::Null, &ctx);
+        let state = load_state(&ctx);
+        assert!(state.install_id.is_some());
+        assert_eq!(state.commands.get("ping"), Some(&1));
+        assert_eq!(state.commands.get("unknown"), Some(&1));
+        assert_eq!(state.errors.get("INVALID_INPUT"), Some(&2));
+        assert!(!serde_json::to_string(&state)
+            .unwrap()
+            .contains("secret-looking-name"));
+
+        cmd_telemetry_set(serde_json::json!({ "enabled": false }), &ctx).unwrap();
+        let state = load_state(&ctx);
+        assert_eq!(state.pending(), 0);
+        assert_eq!(state.install_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_flush_uploads_one_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let network = Arc::new(
+            MockNetwork::new()
+                .fail(ENDPOINT, "connection refused")
+                .respond(ENDPOINT, HttpResponse::from_json(204, &Value::Null)),
+        );
+        let ctx = context(dir.path(), network.clone());
+        cmd_telemetry_set(serde_json::json!({ "enabled": true }), &ctx).unwrap();
+        record(&ctx, "ping", None);
+        record(&ctx, "ping", Some(ErrorCode::Timeout));
+
+        let waiting = flush(&ctx, false).await;
+        assert_eq!(waiting.data.unwrap()["uploaded"], 0);
+        assert!(network.requests().is_empty());
+
+        assert_eq!(flush(&ctx, true).await.status, Status::Error);
+        assert_eq!(load_state(&ctx).pending(), 2);
+
+        let r = flush(&ctx, true).await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.data.unwrap()["uploaded"], 2);
+        let state = load_state(&ctx);
+        assert_eq!(state.pending(), 0);
+        assert!(state.last_upload.is_some());
+
+        let requests = network.requests();
+        let HttpBody::Json(body) = &requests[1].body else {
+            panic!("expected a JSON body");
+        };
+        assert_eq!(body["commands"]["ping"], 2);
+        assert_eq!(body["errors"]["TIMEOUT"], 1);
+        assert_eq!(body["install_id"], Value::from(state.install_id));
+        let keys: Vec<&String> = body.as_object().unwrap().keys().collect();
+        assert_eq!(
+            keys,
+            [
+                "app_version",
+                "arch",
+                "commands",
+                "errors",
+                "install_id",
+                "os",
+                "since",
+                "until"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_endpoint_requires_https() {
+        assert!(check_endpoint(ENDPOINT).is_ok());
+        assert!(check_endpoint("http://127.0.0.1:9000/t").is_ok());
+        assert!(check_endpoint("http://telemetry.test/t").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Datasets for data-driven scenario steps: a step with `data: rows.csv`
+//! runs once per row, with `${column}` in the step replaced by the row's
+//! value (see [`crate::scenario::expand_data`]).
+//!
+//! CSV files need a header row and follow RFC 4180 (quoted fields may hold
+//! commas, newlines, and `""` for a quote). JSON files hold an array of
+//! objects.
+
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// One row, by column name.
+pub type Row = Map<String, Value>;
+
+/// Read the rows of `path`, by extension (`.csv` or `.json`).
+pub fn load(path: &Path) -> Result<Vec<Row>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read dataset {}: {}", path.display(), e))?;
+    let rows = match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => parse_csv(&text),
+        Some("json") => parse_json(&text),
+        _ => Err("expected a .csv or .json file".to_string()),
+    };
+    rows.map_err(|e| format!("invalid dataset {}: {}", path.display(), e))
+}
+
+pub fn parse_json(text: &str) -> Result<Vec<Row>, String> {
+    let rows: Vec<Value> = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            Value::Object(row) => Ok(row),
+            _ => Err(format!("row {} is not an object", i)),
+        })
+        .collect()
+}
+
+/// Rows of a CSV document, keyed by the header row. Every value is a
+/// string; blank lines are ignored.
+pub fn parse_csv(text: &str) -> Result<Vec<Row>, String> {
+    let mut records = records(text)?.into_iter();
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+    records
+        .enumerate()
+        .map(|(i, fields)| {
+            if fields.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} fields, the header has {}",
+                    i + 1,
+                    fields.len(),
+                    header.len()
+                ));
+            }
+            Ok(header
+                .iter()
+                .cloned()
+                .zip(fields.into_iter().map(Value::String))
+                .collect())
+        })
+        .collect()
+}
+
+fn records(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    let mut end_record = |record: &mut Vec<String>, field: &mut String| {
+        record.push(std::mem::take(field));
+        let done = std::mem::take(record);
+        if done != [""] {
+            records.push(done);
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => end_record(&mut record, &mut field),
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".into());
+    }
+    end_record(&mut record, &mut field);
+    Ok(records)
+}
+
+/// `value` with `${column}` replaced from `row` in every string. A string
+/// that is only a placeholder takes the row value as is, so JSON numbers
+/// and booleans keep their type; unknown names are left alone.
+pub fn substitute(value: &Value, row: &Row) -> Value {
+    match value {
+        Value::String(s) => {
+            let whole = s
+                .strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .and_then(|name| row.get(name));
+            match whole {
+                Some(v) => v.clone(),
+                None => Value::String(substitute_str(s, row)),
+            }
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| substitute(v, row)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), substitute(v, row)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn substitute_str(s: &str, row: &Row) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + len];
+        out.push_str(&rest[..start]);
+        match row.get(name) {
+            Some(Value::String(v)) => out.push_str(v),
+            Some(v) => out.push_str(&v.to_string()),
+            None => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_csv_quotes_and_line_endings() {
+        let rows = parse_csv(
+            "\u{feff}name,content\r\nplain,hello\r\n\"a,b\",\"say \"\"hi\"\"\nthere\"\n\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                json!({"name": "plain", "content": "hello"}),
+                json!({"name": "a,b", "content": "say \"hi\"\nthere"}),
+            ]
+            .into_iter()
+            .map(|v| v.as_object().unwrap().clone())
+            .collect::<Vec<_>>()
+        );
+        assert!(parse_csv("a,b\n1\n").unwrap_err().contains("row 1"));
+        assert!(parse_csv("a\n\"open\n").is_err());
+        assert!(parse_csv("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_substitute_keeps_whole_placeholder_types() {
+        let row = json!({"file": "a.txt", "size": 3, "ok": true});
+        let row = row.as_object().unwrap();
+        let step = json!({
+            "call": "write_file",
+            "args": {"path": "/tmp/${file}", "size": "${size}", "flags": ["${ok}", "${nope}"]},
+        });
+        assert_eq!(
+            substitute(&step, row),
+            json!({
+                "call": "write_file",
+                "args": {"path": "/tmp/a.txt", "size": 3, "flags": [true, "${nope}"]},
+            })
+        );
+        assert_eq!(substitute_str("${size}px ${", row), "3px ${");
+    }
+
+    #[test]
+    fn test_parse_json_rows_must_be_objects() {
+        assert_eq!(parse_json(r#"[{"a": 1}]"#).unwrap().len(), 1);
+        assert!(parse_json("[1]").unwrap_err().contains("row 0"));
+    }
+}
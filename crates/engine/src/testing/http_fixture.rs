@@ -0,0 +1,372 @@
+//! Local HTTP file server for hermetic tests – serves a directory on a
+//! random `127.0.0.1` port, so network-probe and download scenarios run
+//! against fixture files instead of httpbin.org.
+//!
+//! A scenario starts one with a `serve_http_fixture` step; it serves until
+//! the scenario ends, and later steps use its address as `${<name>.url}`
+//! or `${<name>.port}` (`name` defaults to `fixture`):
+//!
+//! ```yaml
+//! steps:
+//!   - serve_http_fixture: tests/fixtures/http
+//!   - probe: network
+//!     args:
+//!       endpoints: [{url: "${fixture.url}/get.json", expect_status: 200}]
+//! ```
+//!
+//! `appctl serve-http-fixture <dir>` does the same for shell-driven tests,
+//! until interrupted. Only `GET` and `HEAD` are served, with single byte
+//! ranges (`Range: bytes=a-b`) for resumable downloads and throughput
+//! tests; `index.html` stands in for a directory, and nothing outside the
+//! directory is reachable.
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request head read before answering 431.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// A running server; dropping it stops it and closes its connections.
+pub struct HttpFixture {
+    root: PathBuf,
+    port: u16,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl HttpFixture {
+    /// Serve `root`, which must be a directory, on a free localhost port.
+    pub async fn start(root: &Path) -> std::io::Result<Self> {
+        let root = std::fs::canonicalize(root)
+            .map_err(|e| Error::new(e.kind(), format!("{}: {}", root.display(), e)))?;
+        if !root.is_dir() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{}: not a directory", root.display()),
+            ));
+        }
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let served = Arc::new(root.clone());
+        let task = tokio::spawn(async move {
+            // Dropped with this task, aborting any open connections.
+            let mut connections = tokio::task::JoinSet::new();
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            connections.spawn(handle(served.clone(), stream));
+                        }
+                        Err(e) => tracing::debug!("http fixture: accept failed: {}", e),
+                    },
+                    Some(_) = connections.join_next() => {}
+                }
+            }
+        });
+        Ok(Self { root, port, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// `http://127.0.0.1:<port>`, without a trailing slash.
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    /// The served directory, canonicalized.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Drop for HttpFixture {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Answer one request and close the connection.
+async fn handle(root: Arc<PathBuf>, mut stream: TcpStream) {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 4096];
+    let response = loop {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => head.extend_from_slice(&buf[..n]),
+        }
+        if head.windows(4).any(|w| w == b"\r\n\r\n") {
+            break respond(&root, &String::from_utf8_lossy(&head));
+        }
+        if head.len() > MAX_HEAD_BYTES {
+            break Response::error(431);
+        }
+    };
+    let _ = stream.write_all(&response.into_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    /// `HEAD`: the headers of a `GET`, without the body.
+    head_only: bool,
+}
+
+impl Response {
+    fn error(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", "text/plain".into())],
+            body: format!("{}\n", reason(status)).into_bytes(),
+            head_only: false,
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let mut out = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+        for (name, value) in &self.headers {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        out.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        ));
+        let mut out = out.into_bytes();
+        if !self.head_only {
+            out.extend_from_slice(&self.body);
+        }
+        out
+    }
+}
+
+/// The response to the request whose head is `head`, for files under
+/// `root`.
+fn respond(root: &Path, head: &str) -> Response {
+    let mut lines = head.lines();
+    let mut request = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request.next(), request.next()) else {
+        return Response::error(400);
+    };
+    if method != "GET" && method != "HEAD" {
+        let mut r = Response::error(405);
+        r.headers.push(("Allow", "GET, HEAD".into()));
+        return r;
+    }
+    let Some(mut path) = resolve(root, target) else {
+        return Response::error(404);
+    };
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    let Ok(body) = std::fs::read(&path) else {
+        return Response::error(404);
+    };
+    let range = lines
+        .filter_map(|l| l.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("range"))
+        .map(|(_, value)| value.trim());
+
+    let mut headers = vec![
+        ("Content-Type", content_type(&path).to_string()),
+        ("Accept-Ranges", "bytes".to_string()),
+    ];
+    let total = body.len();
+    let (status, body) = match range.map(|r| byte_range(r, total)) {
+        None | Some(Some(None)) => (200, body),
+        Some(Some(Some((start, end)))) => {
+            headers.push((
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, total),
+            ));
+            (206, body[start..=end].to_vec())
+        }
+        Some(None) => {
+            let mut r = Response::error(416);
+            r.headers
+                .push(("Content-Range", format!("bytes */{}", total)));
+            return r;
+        }
+    };
+    Response {
+        status,
+        headers,
+        body,
+        head_only: method == "HEAD",
+    }
+}
+
+/// The file `target` names under `root`; `None` for anything that would
+/// leave it.
+fn resolve(root: &Path, target: &str) -> Option<PathBuf> {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let mut resolved = root.to_path_buf();
+    for part in percent_decode(path)?.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            part if part.contains('\\') || Path::new(part).has_root() => return None,
+            part => resolved.push(part),
+        }
+    }
+    // Symlinks must stay inside too.
+    match std::fs::canonicalize(&resolved) {
+        Ok(real) if real.starts_with(root) => Some(real),
+        _ => None,
+    }
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// The inclusive byte range a `Range` header asks for within `total`
+/// bytes: `Some(None)` for a header this server ignores (other units,
+/// several ranges), `None` if it cannot be satisfied.
+fn byte_range(header: &str, total: usize) -> Option<Option<(usize, usize)>> {
+    let Some(spec) = header.strip_prefix("bytes=") else {
+        return Some(None);
+    };
+    if spec.contains(',') {
+        return Some(None);
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let n: usize = suffix.parse().ok()?;
+            (total.checked_sub(n.min(total))?, total.checked_sub(1)?)
+        }
+        (start, "") => (start.parse().ok()?, total.checked_sub(1)?),
+        (start, end) => {
+            let end: usize = end.parse().ok()?;
+            (start.parse().ok()?, end.min(total.checked_sub(1)?))
+        }
+    };
+    (start <= end).then_some(Some((start, end)))
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+    {
+        "json" => "application/json",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "txt" | "log" | "md" => "text/plain; charset=utf-8",
+        "csv" => "text/csv",
+        "yaml" | "yml" => "application/yaml",
+        "xml" => "application/xml",
+        "js" => "text/javascript",
+        "css" => "text/css",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "svg" => "image/svg+xml",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        206 => "Partial Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        416 => "Range Not Satisfiable",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_range() {
+        assert_eq!(byte_range("bytes=0-3", 10), Some(Some((0, 3))));
+        assert_eq!(byte_range("bytes=4-", 10), Some(Some((4, 9))));
+        assert_eq!(byte_range("bytes=-3", 10), Some(Some((7, 9))));
+        assert_eq!(byte_range("bytes=5-100", 10), Some(Some((5, 9))));
+        assert_eq!(byte_range("bytes=0-1,4-5", 10), Some(None));
+        assert_eq!(byte_range("items=0-1", 10), Some(None));
+        assert_eq!(byte_range("bytes=10-", 10), None);
+        assert_eq!(byte_range("bytes=0-0", 0), None);
+    }
+
+    /// The raw response to `method path` with extra `headers` lines.
+    async fn get(port: u16, method: &str, path: &str, headers: &str) -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await?;
+        let request = format!("{} {} HTTP/1.1\r\nHost: x\r\n{}\r\n", method, path, headers);
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn test_serves_files_and_ranges_but_nothing_outside() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("www");
+        std::fs::create_dir_all(root.join("sub dir")).unwrap();
+        std::fs::write(root.join("get.json"), r#"{"ok":true}"#).unwrap();
+        std::fs::write(root.join("sub dir/index.html"), "<p>hi</p>").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "no").unwrap();
+        let fixture = HttpFixture::start(&root).await.unwrap();
+        let port = fixture.port();
+        assert_eq!(fixture.url(), format!("http://127.0.0.1:{}", port));
+
+        let r = get(port, "GET", "/get.json?x=1", "").await.unwrap();
+        assert!(r.starts_with("HTTP/1.1 200 OK\r\n"), "{}", r);
+        assert!(r.contains("Content-Type: application/json\r\n"));
+        assert!(r.ends_with("\r\n\r\n{\"ok\":true}"), "{}", r);
+
+        let r = get(port, "GET", "/get.json", "Range: bytes=1-4\r\n")
+            .await
+            .unwrap();
+        assert!(r.starts_with("HTTP/1.1 206 "), "{}", r);
+        assert!(r.contains("Content-Range: bytes 1-4/11\r\n"));
+        assert!(r.ends_with("\r\n\r\n\"ok\""), "{}", r);
+        let r = get(port, "HEAD", "/get.json", "").await.unwrap();
+        assert!(r.contains("Content-Length: 11\r\n") && r.ends_with("\r\n\r\n"));
+
+        let r = get(port, "GET", "/sub%20dir/", "").await.unwrap();
+        assert!(r.ends_with("<p>hi</p>"), "{}", r);
+        for path in [
+            "/missing",
+            "/../secret.txt",
+            "/%2e%2e/secret.txt",
+            "/sub%20dir/..%2f..%2fsecret.txt",
+        ] {
+            let r = get(port, "GET", path, "").await.unwrap();
+            assert!(r.starts_with("HTTP/1.1 404 "), "{}: {}", path, r);
+        }
+        let r = get(port, "POST", "/get.json", "").await.unwrap();
+        assert!(r.starts_with("HTTP/1.1 405 "), "{}", r);
+
+        drop(fixture);
+        tokio::task::yield_now().await;
+        assert!(get(port, "GET", "/get.json", "").await.is_err());
+        assert!(HttpFixture::start(&root.join("get.json")).await.is_err());
+    }
+}
@@ -0,0 +1,7 @@
+//! Scripted runs for hermetic tests: scenarios, scenario directories,
+//! datasets for data-driven steps, and a local HTTP fixture server.
+
+pub mod dataset;
+pub mod http_fixture;
+pub mod scenario;
+pub mod scenario_dir;
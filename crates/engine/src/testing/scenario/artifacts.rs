@@ -0,0 +1,160 @@
+//! Step outputs saved by `save_artifacts`.
+
+use crate::context::AppContext;
+use crate::types::*;
+
+/// Read the outputs `wanted` from step `idx`, listing each in the result's
+/// `artifacts`. Ones that cannot be read are left out with a warning.
+pub(super) fn capture_artifacts(
+    wanted: &[SaveArtifact],
+    idx: usize,
+    result: &mut CommandResult,
+    ctx: &AppContext,
+) -> Vec<StepArtifact> {
+    let mut captured = Vec::new();
+    for want in wanted {
+        let read = match want {
+            SaveArtifact::Path(path) => {
+                let path = std::path::Path::new(path);
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "file".into());
+                ctx.fs()
+                    .read_file(path)
+                    .map(|bytes| (name, bytes))
+                    .map_err(|e| e.to_string())
+            }
+            SaveArtifact::Data { data } => match data_field(result.data.as_ref(), data) {
+                Some(serde_json::Value::String(text)) => {
+                    Ok((format!("{}.txt", data), text.clone().into_bytes()))
+                }
+                Some(value) => Ok((
+                    format!("{}.json", data),
+                    serde_json::to_vec_pretty(value).unwrap_or_default(),
+                )),
+                None => Err("no such field in the result data".to_string()),
+            },
+        };
+        match read {
+            Ok((name, bytes)) => {
+                let path = format!("steps/{}/{}", idx, name);
+                result.artifacts.push(path.clone());
+                captured.push(StepArtifact { path, bytes });
+            }
+            Err(e) => {
+                tracing::warn!(step = idx, artifact = ?want, error = %e, "cannot save step artifact")
+            }
+        }
+    }
+    captured
+}
+
+/// `field` (dotted, with numeric parts indexing arrays) inside `data`.
+pub(super) fn data_field<'a>(
+    data: Option<&'a serde_json::Value>,
+    field: &str,
+) -> Option<&'a serde_json::Value> {
+    field
+        .split('.')
+        .try_fold(data?, |value, key| match key.parse::<usize>() {
+            Ok(i) if value.is_array() => value.get(i),
+            _ => value.get(key),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::probes::ProbeRegistry;
+    use crate::scenario::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_save_artifacts_captures_files_and_data_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("report.txt");
+        let scenario = Scenario {
+            name: None,
+            tags: vec![],
+            on_failure: OnFailure::Continue,
+            env: Default::default(),
+            steps: vec![
+                StepSpec {
+                    save_artifacts: vec![SaveArtifact::Path(out.to_string_lossy().into_owned())],
+                    ..StepSpec::from(ScenarioStep::Call {
+                        call: "write_file".into(),
+                        args: serde_json::json!({ "path": out, "content": "hello" }),
+                        expect_status: "pass".into(),
+                        timeout_ms: 30_000,
+                        dialogs: vec![],
+                    })
+                },
+                load_scenario(
+                    "steps:\n  - call: ping\n    save_artifacts: [{data: pong}, {data: missing}]\n",
+                )
+                .unwrap()
+                .steps
+                .remove(0),
+            ],
+        };
+        let ctx = Arc::new(AppContext::default_headless());
+        let result = run_scenario(
+            &scenario,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        assert_eq!(result.overall_status, Status::Pass);
+        assert_eq!(
+            result.captured,
+            vec![
+                StepArtifact {
+                    path: "steps/0/report.txt".into(),
+                    bytes: b"hello".to_vec(),
+                },
+                StepArtifact {
+                    path: "steps/1/pong.json".into(),
+                    bytes: b"true".to_vec(),
+                },
+            ]
+        );
+        assert_eq!(result.step_results[1].artifacts, vec!["steps/1/pong.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_registered_artifact_fields_are_saved() {
+        let mut reg = CommandRegistry::new();
+        reg.register("watch", |_, _| Ok(serde_json::json!({ "changes": [] })))
+            .saves_artifact("changes");
+        let scenario =
+            load_scenario("steps:\n  - call: watch\n    save_artifacts: [{data: changes}]\n")
+                .unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(
+            result.captured,
+            vec![StepArtifact {
+                path: "steps/0/changes.json".into(),
+                bytes: b"[]".to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_data_field_walks_objects_and_arrays() {
+        let data = serde_json::json!({ "headers": { "etag": "x" }, "items": [1, 2] });
+        assert_eq!(
+            data_field(Some(&data), "headers.etag"),
+            Some(&serde_json::json!("x"))
+        );
+        assert_eq!(
+            data_field(Some(&data), "items.1"),
+            Some(&serde_json::json!(2))
+        );
+        assert_eq!(data_field(Some(&data), "items.5"), None);
+        assert_eq!(data_field(None, "headers"), None);
+    }
+}
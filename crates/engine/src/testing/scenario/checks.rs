@@ -0,0 +1,147 @@
+//! Checks on what prompt and resources steps produced.
+
+use crate::resources::{ResourceLimits, SystemStats};
+use crate::types::*;
+
+/// Compare the prompt rendered into `r` with its step's `expect` and
+/// `expect_contains`, failing `r` with the mismatch in its data.
+pub(super) fn prompt_output(
+    mut r: CommandResult,
+    expect: Option<&str>,
+    expect_contains: &[String],
+    idx: usize,
+) -> (CommandResult, bool) {
+    let text = r
+        .data
+        .as_ref()
+        .and_then(|d| d["text"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    let mismatch = match expect {
+        Some(want) if want != text => Some(format!("expected {:?}, got {:?}", want, text)),
+        _ => expect_contains
+            .iter()
+            .find(|s| !text.contains(s.as_str()))
+            .map(|s| format!("output does not contain {:?}", s)),
+    };
+    if let Some(mismatch) = mismatch {
+        tracing::warn!(step = idx, prompt = %r.target, %mismatch, "prompt output mismatch");
+        r.status = Status::Fail;
+        if let Some(data) = r.data.as_mut().and_then(|d| d.as_object_mut()) {
+            data.insert("mismatch".into(), mismatch.into());
+        }
+        return (r, false);
+    }
+    (r, true)
+}
+
+/// Hold the `system_stats` reading in `r` against the step's `resources`
+/// ceilings, listing exceeded and unavailable ones in its data.
+pub(super) fn resource_ceilings(
+    mut r: CommandResult,
+    resources: &ResourceLimits,
+    idx: usize,
+) -> (CommandResult, bool) {
+    let stats = r
+        .data
+        .clone()
+        .and_then(|d| serde_json::from_value::<SystemStats>(d).ok());
+    let Some(stats) = stats else {
+        // Unsupported platforms skip, like probes.
+        let met = r.status == Status::Skip
+            || r.error.as_ref().map(|e| e.code) == Some(ErrorCode::Unsupported);
+        if met {
+            r.status = Status::Skip;
+        }
+        return (r, met);
+    };
+    let check = resources.check(&stats);
+    if let Some(data) = r.data.as_mut().and_then(|d| d.as_object_mut()) {
+        if !check.unavailable.is_empty() {
+            data.insert("unavailable".into(), check.unavailable.clone().into());
+        }
+        if !check.exceeded.is_empty() {
+            data.insert("exceeded".into(), check.exceeded.clone().into());
+        }
+    }
+    if !check.exceeded.is_empty() {
+        tracing::warn!(step = idx, exceeded = ?check.exceeded, "resource ceiling exceeded");
+        r.status = Status::Fail;
+        return (r, false);
+    }
+    (r, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::context::AppContext;
+    use crate::probes::ProbeRegistry;
+    use crate::scenario::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_run_scenario_prompt_regression() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp.path().join("greet")).unwrap();
+        std::fs::write(tmp.path().join("greet/v1.md"), "Hello {{name}}!").unwrap();
+        let yaml = r#"
+steps:
+  - prompt: greet
+    vars: { name: Ada }
+    expect: "Hello Ada!"
+  - prompt: greet
+    vars: { name: Ada }
+    expect_contains: ["Grace"]
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let mut ctx = AppContext::default_headless();
+        ctx.prompts_dir = tmp.path().to_path_buf();
+        let ctx = Arc::new(ctx);
+        let reg = CommandRegistry::new();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Fail);
+        assert_eq!(result.step_results[0].status, Status::Pass);
+        assert_eq!(result.step_results[1].status, Status::Fail);
+        assert!(result.step_results[1].data.as_ref().unwrap()["mismatch"].is_string());
+    }
+
+    /// Fixed readings: a 300 MB process with 40 descriptors.
+    struct FixedResources;
+
+    impl crate::traits::ResourceOps for FixedResources {
+        fn sample(&self) -> crate::traits::CapResult<crate::traits::ResourceSample> {
+            Ok(crate::traits::ResourceSample {
+                process_rss_bytes: Some(300 * 1024 * 1024),
+                open_fds: Some(40),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_resource_ceilings() {
+        let yaml = r#"
+steps:
+  - resources: { interval_ms: 0, max_rss_mb: 512, max_open_fds: 64 }
+  - resources: { interval_ms: 0, max_rss_mb: 256, max_cpu_percent: 90 }
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless().with_resources(Box::new(FixedResources)));
+        let result = run_scenario(
+            &scenario,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        assert_eq!(result.overall_status, Status::Fail);
+        assert_eq!(result.step_results[0].status, Status::Pass);
+        let failed = &result.step_results[1];
+        assert_eq!(failed.status, Status::Fail);
+        let data = failed.data.as_ref().unwrap();
+        assert_eq!(data["exceeded"][0], "process_rss_bytes 300.0 MB > 256 MB");
+        assert_eq!(data["unavailable"][0], "cpu_percent");
+    }
+}
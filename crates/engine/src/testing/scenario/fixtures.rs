@@ -0,0 +1,156 @@
+//! HTTP fixtures served by `serve_http_fixture` steps.
+
+use crate::context::AppContext;
+use crate::types::*;
+use std::collections::HashMap;
+
+/// Name of a `serve_http_fixture` step's server when it has none.
+pub(super) const DEFAULT_FIXTURE: &str = "fixture";
+
+/// The HTTP fixtures a scenario's `serve_http_fixture` steps started, by
+/// name, and the `${<name>.url}`/`${<name>.port}` values later steps see.
+/// Dropping it at the end of the scenario stops the servers.
+#[derive(Default)]
+pub(super) struct Fixtures {
+    servers: HashMap<String, crate::http_fixture::HttpFixture>,
+    vars: crate::dataset::Row,
+}
+
+impl Fixtures {
+    /// Serve `dir` as `name`, replacing a server of that name (a step run
+    /// again after going back). Returns the step's data.
+    async fn start(&mut self, name: &str, dir: &str) -> std::io::Result<serde_json::Value> {
+        let server = crate::http_fixture::HttpFixture::start(std::path::Path::new(dir)).await?;
+        let data = serde_json::json!({
+            "name": name,
+            "port": server.port(),
+            "url": server.url(),
+            "root": server.root(),
+        });
+        self.vars
+            .insert(format!("{}.port", name), server.port().into());
+        self.vars
+            .insert(format!("{}.url", name), server.url().into());
+        self.servers.insert(name.to_string(), server);
+        Ok(data)
+    }
+
+    /// A `serve_http_fixture` step labelled `label`: serve `dir` as `name`.
+    pub(super) async fn serve(
+        &mut self,
+        dir: &str,
+        name: &str,
+        label: &str,
+        idx: usize,
+        ctx: &AppContext,
+    ) -> (CommandResult, bool) {
+        let run_id = ctx.new_run_id();
+        match self.start(name, dir).await {
+            Ok(data) => {
+                let mut r = result_ok("serve_http_fixture", label, &run_id, 0);
+                r.data = Some(data);
+                (r, true)
+            }
+            Err(e) => {
+                let code = match e.kind() {
+                    std::io::ErrorKind::InvalidInput => ErrorCode::InvalidInput,
+                    _ => ErrorCode::IoError,
+                };
+                let message = format!("step {}: cannot serve {}", idx, e);
+                (
+                    result_err("serve_http_fixture", label, &run_id, 0, code, message),
+                    false,
+                )
+            }
+        }
+    }
+
+    /// `spec` with the fixture placeholders filled in.
+    pub(super) fn resolve<'a>(&self, spec: &'a StepSpec) -> std::borrow::Cow<'a, StepSpec> {
+        if self.vars.is_empty() {
+            return std::borrow::Cow::Borrowed(spec);
+        }
+        serde_json::to_value(spec)
+            .ok()
+            .map(|v| crate::dataset::substitute(&v, &self.vars))
+            .and_then(|v| serde_json::from_value(v).ok())
+            .map_or(std::borrow::Cow::Borrowed(spec), std::borrow::Cow::Owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::probes::ProbeRegistry;
+    use crate::scenario::tests::EchoStep;
+    use crate::scenario::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_http_fixture_serves_for_the_scenario_and_fills_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("get.json"), "{}").unwrap();
+        let yaml = format!(
+            r#"
+steps:
+  - serve_http_fixture: "{dir}"
+  - serve_http_fixture: "{dir}"
+    name: files
+  - echo: {{ url: "${{files.url}}/get.json", port: "${{fixture.port}}" }}
+  - serve_http_fixture: "{dir}/missing"
+"#,
+            dir = dir.path().display()
+        );
+        let s = load_scenario(&yaml).unwrap();
+        assert_eq!(step_label(&s.steps[1].step), "serve_http_fixture:files");
+
+        let ctx = Arc::new(AppContext::default_headless().with_step_handler(Box::new(EchoStep)));
+        let result = run_scenario(&s, &ctx, &CommandRegistry::new(), &ProbeRegistry::new()).await;
+        let r = &result.step_results;
+        let data = |i: usize| r[i].data.clone().unwrap();
+        assert_eq!(r[0].status, Status::Pass);
+        assert_ne!(data(0)["port"], data(1)["port"]);
+        assert_eq!(
+            data(2),
+            serde_json::json!({
+                "url": format!("{}/get.json", data(1)["url"].as_str().unwrap()),
+                "port": data(0)["port"],
+            })
+        );
+        assert_eq!(r[3].error.as_ref().unwrap().code, ErrorCode::IoError);
+
+        // Stopped with the scenario.
+        tokio::task::yield_now().await;
+        let port = data(0)["port"].as_u64().unwrap() as u16;
+        assert!(tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interactive_prompt_sees_resolved_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "steps:\n  - serve_http_fixture: \"{}\"\n  - echo: {{ url: \"${{fixture.url}}\" }}\n",
+            dir.path().display()
+        );
+        let s = load_scenario(&yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless().with_step_handler(Box::new(EchoStep)));
+        let mut shown = Vec::new();
+        let result = run_scenario_interactive(
+            &s,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+            |_idx, _total, _label, step, _can_go_back| {
+                shown.push(serde_json::to_value(step).unwrap());
+                Some(StepChoice::Run)
+            },
+            |_idx, _total, _label| None,
+        )
+        .await;
+        let url = result.step_results[0].data.as_ref().unwrap()["url"].clone();
+        assert_eq!(shown[1]["echo"]["url"], url);
+    }
+}
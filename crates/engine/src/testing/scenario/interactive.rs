@@ -1,16 +1,14 @@
-//! Scenario runner – execute scripted flows from YAML files.
+//! Interactive scenario runs, where the user decides each step.
 
+use super::fixtures::Fixtures;
+use super::run::SessionRecorder;
+use super::step::{run_step, step_label, StepRun};
 use crate::commands::CommandRegistry;
 use crate::context::AppContext;
-use crate::probes;
+use crate::probes::ProbeRegistry;
 use crate::types::*;
 use std::collections::HashMap;
-use std::time::Duration;
-
-/// Load a scenario from a YAML string.
-pub fn load_scenario(yaml: &str) -> Result<Scenario, String> {
-    serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse scenario YAML: {}", e))
-}
+use std::sync::Arc;
 
 /// User choice at each interactive step.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,6 +30,8 @@ pub enum FailureChoice {
 pub(crate) struct StepOutcome {
     pub(crate) status: StepStatus,
     pub(crate) result: CommandResult,
+    pub(crate) xfail: Option<XfailStep>,
+    pub(crate) captured: Vec<StepArtifact>,
 }
 
 /// Disposition of a completed step.
@@ -42,136 +42,46 @@ pub(crate) enum StepStatus {
     Failed,
 }
 
-/// Return the label (target name) for a scenario step.
-fn step_label(step: &ScenarioStep) -> String {
-    match step {
-        ScenarioStep::Call { call, .. } => call.clone(),
-        ScenarioStep::Probe { probe } => format!("probe:{}", probe),
-    }
-}
-
-/// Execute a single scenario step and return the result plus whether the
-/// expectation was met.
-async fn execute_step(
-    step: &ScenarioStep,
-    idx: usize,
-    ctx: &AppContext,
-    registry: &CommandRegistry,
-) -> (CommandResult, bool) {
-    match step {
-        ScenarioStep::Call {
-            call,
-            args,
-            expect_status,
-            timeout_ms,
-        } => {
-            // NOTE: registry.execute() is synchronous, so the timeout can
-            // only fire between .await points - it will not preempt a
-            // long-running sync command mid-execution. This will work
-            // correctly once async command support is added. For now it
-            // still validates the timeout field and produces the right
-            // error for any command that yields (e.g. probes).
-            let deadline = Duration::from_millis(*timeout_ms);
-            let call_clone = call.clone();
-            let args_clone = args.clone();
-
-            let timeout_result = tokio::time::timeout(deadline, async {
-                registry.execute(&call_clone, args_clone, ctx)
-            })
-            .await;
-
-            let r = match timeout_result {
-                Ok(result) => result,
-                Err(_elapsed) => {
-                    let run_id = new_run_id();
-                    result_err(
-                        "call",
-                        call,
-                        &run_id,
-                        *timeout_ms,
-                        ErrorCode::Timeout,
-                        format!("step {} ('{}') timed out after {}ms", idx, call, timeout_ms),
-                    )
-                }
-            };
-
-            let actual_status = serde_json::to_value(r.status)
-                .ok()
-                .and_then(|v| v.as_str().map(String::from))
-                .unwrap_or_default();
-            let met = actual_status == *expect_status;
-            if !met {
-                tracing::warn!(
-                    step = idx,
-                    expected = %expect_status,
-                    actual = %actual_status,
-                    "scenario step status mismatch"
-                );
-            }
-            (r, met)
-        }
-        ScenarioStep::Probe { probe } => {
-            let r = probes::run_probe(probe, ctx).await;
-            let met = r.status == Status::Pass || r.status == Status::Skip;
-            (r, met)
-        }
-    }
-}
-
-/// Execute a scenario non-interactively (forward-only).
-pub async fn run_scenario(
-    scenario: &Scenario,
-    ctx: &AppContext,
-    registry: &CommandRegistry,
-) -> ScenarioResult {
-    let mut step_results = Vec::new();
-    let mut overall = Status::Pass;
-
-    for (i, step) in scenario.steps.iter().enumerate() {
-        let (result, expectation_met) = execute_step(step, i, ctx, registry).await;
-        if !expectation_met {
-            overall = Status::Fail;
-        }
-        step_results.push(result);
-    }
-
-    ScenarioResult {
-        name: scenario.name.clone(),
-        overall_status: overall,
-        step_results,
-    }
-}
-
 /// Execute a scenario interactively with go-back navigation.
 ///
-/// - `prompt_fn` is called at each step to ask the user whether to run, skip,
-///   or go back. Returns `None` to abort the scenario.
+/// - `prompt_fn` is called at each step, with the step as it will run
+///   (fixture placeholders filled in), to ask the user whether to run,
+///   skip, or go back. Returns `None` to abort the scenario.
 /// - `failure_fn` is called when a step fails, asking the user whether to
-///   continue or abort. Returns `None` to abort.
+///   continue or abort. Returns `None` to abort. Steps under
+///   `on_failure: stop` abort without asking.
 ///
 /// This keeps the engine crate free of direct terminal I/O dependencies -
 /// the CLI crate provides the real prompters.
 pub async fn run_scenario_interactive<F, G>(
     scenario: &Scenario,
-    ctx: &AppContext,
+    ctx: &Arc<AppContext>,
     registry: &CommandRegistry,
+    probes: &ProbeRegistry,
     mut prompt_fn: F,
     mut failure_fn: G,
 ) -> ScenarioResult
 where
-    F: FnMut(usize, usize, &str, bool) -> Option<StepChoice>,
+    F: FnMut(usize, usize, &str, &StepSpec, bool) -> Option<StepChoice>,
     G: FnMut(usize, usize, &str) -> Option<FailureChoice>,
 {
     let total = scenario.steps.len();
     let mut results: HashMap<usize, StepOutcome> = HashMap::new();
 
+    let mut stopped_at = None;
+    let _env = crate::env::EnvGuard::apply(ctx.env(), &scenario.env);
+    let session = SessionRecorder::start(ctx);
+    let started = ctx.stopwatch();
+    let mut fixtures = Fixtures::default();
     let mut idx = 0;
     while idx < total {
-        let step = &scenario.steps[idx];
-        let label = step_label(step);
+        session.set_step(idx);
+        let spec = &scenario.steps[idx];
+        let resolved = fixtures.resolve(spec);
+        let label = step_label(&resolved.step);
         let can_go_back = idx > 0;
 
-        let choice = match prompt_fn(idx, total, &label, can_go_back) {
+        let choice = match prompt_fn(idx, total, &label, &resolved, can_go_back) {
             Some(c) => c,
             None => break, // user aborted
         };
@@ -201,11 +111,13 @@ where
                 // This entry will be overwritten if the user later revisits
                 // this step via GoBack, or cleaned up by a GoBack from a
                 // subsequent step (which invalidates idx..total).
-                let run_id = new_run_id();
+                let run_id = ctx.new_run_id();
                 results.insert(
                     idx,
                     StepOutcome {
                         status: StepStatus::Skipped,
+                        xfail: None,
+                        captured: Vec::new(),
                         result: {
                             let mut r = result_skip("scenario", &label, &run_id, 0, "user skipped");
                             // Override the default Unsupported code - this is
@@ -223,9 +135,14 @@ where
             StepChoice::Run => {}
         }
 
-        let (result, expectation_met) = execute_step(step, idx, ctx, registry).await;
+        let StepRun {
+            result,
+            met,
+            xfail,
+            captured,
+        } = run_step(spec, idx, &started, &mut fixtures, ctx, registry, probes).await;
 
-        if !expectation_met {
+        if !met {
             // Insert the failed outcome first so failure_fn sees a
             // consistent results map if it ever inspects it.
             results.insert(
@@ -233,8 +150,15 @@ where
                 StepOutcome {
                     status: StepStatus::Failed,
                     result,
+                    xfail,
+                    captured,
                 },
             );
+            // `on_failure: stop` decides without asking.
+            if spec.on_failure.unwrap_or(scenario.on_failure) == OnFailure::Stop {
+                stopped_at = Some(idx);
+                break;
+            }
             let decision = failure_fn(idx, total, &label);
             if decision != Some(FailureChoice::Continue) {
                 break;
@@ -248,6 +172,8 @@ where
             StepOutcome {
                 status: StepStatus::Completed,
                 result,
+                xfail,
+                captured,
             },
         );
         idx += 1;
@@ -266,64 +192,32 @@ where
     };
 
     // Collect results in step order
+    let mut xfail = Vec::new();
+    let mut captured = Vec::new();
     let step_results: Vec<CommandResult> = (0..total)
-        .filter_map(|i| results.remove(&i).map(|o| o.result))
+        .filter_map(|i| results.remove(&i))
+        .map(|o| {
+            xfail.extend(o.xfail);
+            captured.extend(o.captured);
+            o.result
+        })
         .collect();
 
     ScenarioResult {
         name: scenario.name.clone(),
         overall_status: overall,
         step_results,
+        session_events: session.finish(),
+        xfail,
+        stopped_at,
+        captured,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn test_parse_scenario() {
-        let yaml = r#"
-name: basic test
-steps:
-  - call: "ping"
-    args: {}
-    expect_status: "pass"
-    timeout_ms: 5000
-  - probe: "filesystem"
-"#;
-        let s = load_scenario(yaml).expect("should parse");
-        assert_eq!(s.name, Some("basic test".into()));
-        assert_eq!(s.steps.len(), 2);
-    }
-
-    #[tokio::test]
-    async fn test_run_scenario_ping() {
-        let yaml = r#"
-steps:
-  - call: "ping"
-    args: {}
-    expect_status: "pass"
-"#;
-        let scenario = load_scenario(yaml).unwrap();
-        let ctx = AppContext::default_headless();
-        let reg = CommandRegistry::new();
-        let result = run_scenario(&scenario, &ctx, &reg).await;
-        assert_eq!(result.overall_status, Status::Pass);
-        assert_eq!(result.step_results.len(), 1);
-    }
-
-    #[test]
-    fn test_parse_scenario_minimal() {
-        let yaml = r#"
-steps:
-  - call: "read_file"
-    args:
-      path: "/tmp/nope"
-"#;
-        let s = load_scenario(yaml).expect("should parse");
-        assert_eq!(s.steps.len(), 1);
-    }
+    use crate::scenario::*;
 
     #[tokio::test]
     async fn test_interactive_go_back() {
@@ -338,6 +232,9 @@ steps:
         // Build the scenario struct directly instead of formatting a YAML
         // string, to avoid backslash-escape issues with Windows paths.
         let scenario = Scenario {
+            tags: vec![],
+            on_failure: OnFailure::Continue,
+            env: Default::default(),
             name: None,
             steps: vec![
                 ScenarioStep::Call {
@@ -345,22 +242,28 @@ steps:
                     args: serde_json::json!({ "path": tmp_str, "content": "x" }),
                     expect_status: "pass".to_string(),
                     timeout_ms: 30_000,
-                },
+                    dialogs: vec![],
+                }
+                .into(),
                 ScenarioStep::Call {
                     call: "ping".to_string(),
                     args: serde_json::json!({}),
                     expect_status: "pass".to_string(),
                     timeout_ms: 30_000,
-                },
+                    dialogs: vec![],
+                }
+                .into(),
                 ScenarioStep::Call {
                     call: "ping".to_string(),
                     args: serde_json::json!({}),
                     expect_status: "pass".to_string(),
                     timeout_ms: 30_000,
-                },
+                    dialogs: vec![],
+                }
+                .into(),
             ],
         };
-        let ctx = AppContext::default_headless();
+        let ctx = Arc::new(AppContext::default_headless());
         let reg = CommandRegistry::new();
 
         let call_count = std::cell::Cell::new(0usize);
@@ -368,7 +271,8 @@ steps:
             &scenario,
             &ctx,
             &reg,
-            |idx, _total, _label, _can_go_back| {
+            &ProbeRegistry::new(),
+            |idx, _total, _label, _step, _can_go_back| {
                 let n = call_count.get();
                 call_count.set(n + 1);
                 match n {
@@ -425,14 +329,15 @@ steps:
     expect_status: "pass"
 "#;
         let scenario = load_scenario(yaml).unwrap();
-        let ctx = AppContext::default_headless();
+        let ctx = Arc::new(AppContext::default_headless());
         let reg = CommandRegistry::new();
 
         let result = run_scenario_interactive(
             &scenario,
             &ctx,
             &reg,
-            |_idx, _total, _label, _can_go_back| Some(StepChoice::Skip),
+            &ProbeRegistry::new(),
+            |_idx, _total, _label, _step, _can_go_back| Some(StepChoice::Skip),
             |_idx, _total, _label| panic!("no failures expected"),
         )
         .await;
@@ -455,14 +360,15 @@ steps:
     expect_status: "pass"
 "#;
         let scenario = load_scenario(yaml).unwrap();
-        let ctx = AppContext::default_headless();
+        let ctx = Arc::new(AppContext::default_headless());
         let reg = CommandRegistry::new();
 
         let result = run_scenario_interactive(
             &scenario,
             &ctx,
             &reg,
-            |idx, _total, _label, _can_go_back| {
+            &ProbeRegistry::new(),
+            |idx, _total, _label, _step, _can_go_back| {
                 if idx == 0 {
                     Some(StepChoice::Run)
                 } else {
@@ -478,84 +384,6 @@ steps:
         assert_eq!(result.step_results[0].status, Status::Pass);
     }
 
-    #[tokio::test]
-    async fn test_interactive_failure_continue() {
-        // Step 0 passes, step 1 fails (expect "fail" but ping returns "pass"),
-        // user continues, step 2 passes
-        let yaml = r#"
-steps:
-  - call: "ping"
-    args: {}
-    expect_status: "pass"
-  - call: "ping"
-    args: {}
-    expect_status: "fail"
-  - call: "ping"
-    args: {}
-    expect_status: "pass"
-"#;
-        let scenario = load_scenario(yaml).unwrap();
-        let ctx = AppContext::default_headless();
-        let reg = CommandRegistry::new();
-
-        let result = run_scenario_interactive(
-            &scenario,
-            &ctx,
-            &reg,
-            |_idx, _total, _label, _can_go_back| Some(StepChoice::Run),
-            |idx, _total, _label| {
-                assert_eq!(idx, 1); // only step 1 should fail
-                Some(FailureChoice::Continue)
-            },
-        )
-        .await;
-
-        assert_eq!(result.overall_status, Status::Fail);
-        assert_eq!(result.step_results.len(), 3);
-        assert_eq!(result.step_results[0].status, Status::Pass);
-        // step 1's CommandResult.status is Pass (ping succeeded) but its expectation
-        // was "fail" - the only externally visible evidence is overall_status above.
-        assert_eq!(result.step_results[1].status, Status::Pass);
-        // step 1 failed expectation but we continued to step 2
-        assert_eq!(result.step_results[2].status, Status::Pass);
-    }
-
-    #[tokio::test]
-    async fn test_interactive_failure_abort() {
-        // Step 0 fails (expect "fail" but ping returns "pass"), user aborts
-        let yaml = r#"
-steps:
-  - call: "ping"
-    args: {}
-    expect_status: "fail"
-  - call: "ping"
-    args: {}
-    expect_status: "pass"
-"#;
-        let scenario = load_scenario(yaml).unwrap();
-        let ctx = AppContext::default_headless();
-        let reg = CommandRegistry::new();
-
-        let result = run_scenario_interactive(
-            &scenario,
-            &ctx,
-            &reg,
-            |_idx, _total, _label, _can_go_back| Some(StepChoice::Run),
-            |idx, _total, _label| {
-                assert_eq!(idx, 0);
-                Some(FailureChoice::Abort)
-            },
-        )
-        .await;
-
-        assert_eq!(result.overall_status, Status::Fail);
-        // Only step 0 recorded, step 1 never reached
-        assert_eq!(result.step_results.len(), 1);
-        // The failed step's CommandResult.status is Pass (ping executed OK)
-        // even though its expectation ("fail") was not met.
-        assert_eq!(result.step_results[0].status, Status::Pass);
-    }
-
     #[tokio::test]
     async fn test_interactive_go_back_invalidates_stale_results() {
         // Run step 0, skip step 1, go back from step 2, go back from step 1,
@@ -574,7 +402,7 @@ steps:
     expect_status: "pass"
 "#;
         let scenario = load_scenario(yaml).unwrap();
-        let ctx = AppContext::default_headless();
+        let ctx = Arc::new(AppContext::default_headless());
         let reg = CommandRegistry::new();
 
         let call_count = std::cell::Cell::new(0usize);
@@ -582,7 +410,8 @@ steps:
             &scenario,
             &ctx,
             &reg,
-            |idx, _total, _label, _can_go_back| {
+            &ProbeRegistry::new(),
+            |idx, _total, _label, _step, _can_go_back| {
                 let n = call_count.get();
                 call_count.set(n + 1);
                 match n {
@@ -618,24 +447,4 @@ steps:
         assert_eq!(result.step_results.len(), 1);
         assert_eq!(result.step_results[0].status, Status::Pass);
     }
-
-    #[tokio::test]
-    async fn test_generous_timeout_does_not_fire() {
-        // Verify the timeout_ms field is accepted without panicking and that
-        // a generous deadline (5 s) does NOT trigger a false timeout on ping.
-        let scenario = Scenario {
-            name: Some("timeout test".into()),
-            steps: vec![ScenarioStep::Call {
-                call: "ping".to_string(),
-                args: serde_json::json!({}),
-                expect_status: "pass".to_string(),
-                timeout_ms: 5_000,
-            }],
-        };
-        let ctx = AppContext::default_headless();
-        let reg = CommandRegistry::new();
-        let result = run_scenario(&scenario, &ctx, &reg).await;
-        assert_eq!(result.overall_status, Status::Pass);
-        assert_eq!(result.step_results[0].status, Status::Pass);
-    }
 }
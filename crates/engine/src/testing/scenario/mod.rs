@@ -0,0 +1,329 @@
+//! Scenario runner – execute scripted flows from YAML files.
+
+mod artifacts;
+mod checks;
+mod fixtures;
+mod interactive;
+mod request;
+mod run;
+mod step;
+mod timing;
+
+pub use interactive::{run_scenario_interactive, FailureChoice, StepChoice};
+pub use request::{actions, run_request};
+pub use run::run_scenario;
+
+use crate::types::*;
+use step::step_label;
+
+/// Load a scenario from a YAML string.
+pub fn load_scenario(yaml: &str) -> Result<Scenario, String> {
+    serde_yaml::from_str(yaml).map_err(|e| format!("failed to parse scenario YAML: {}", e))
+}
+
+/// Expand steps with a `data` file into one step per row, with `${column}`
+/// placeholders filled in. Paths are relative to `base`, the scenario
+/// file's directory.
+pub fn expand_data(scenario: Scenario, base: &std::path::Path) -> Result<Scenario, String> {
+    let mut steps = Vec::with_capacity(scenario.steps.len());
+    for (idx, spec) in scenario.steps.into_iter().enumerate() {
+        let Some(data) = &spec.data else {
+            steps.push(spec);
+            continue;
+        };
+        let rows =
+            crate::dataset::load(&base.join(data)).map_err(|e| format!("step {}: {}", idx, e))?;
+        let template = StepSpec {
+            data: None,
+            ..spec.clone()
+        };
+        let template = serde_json::to_value(&template).map_err(|e| e.to_string())?;
+        for (n, row) in rows.iter().enumerate() {
+            let step = serde_json::from_value(crate::dataset::substitute(&template, row))
+                .map_err(|e| format!("step {} row {}: {}", idx, n, e))?;
+            steps.push(step);
+        }
+    }
+    Ok(Scenario { steps, ..scenario })
+}
+
+/// Scenarios compiled into the binary, by name, for `run-scenario
+/// --builtin`.
+pub const BUILTIN: &[(&str, &str)] = &[("smoke", include_str!("../../../scenarios/smoke.yaml"))];
+
+/// The built-in scenario `name`, parsed.
+pub fn builtin(name: &str) -> Result<Scenario, String> {
+    let (_, yaml) = BUILTIN.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+        let names: Vec<&str> = BUILTIN.iter().map(|(n, _)| *n).collect();
+        format!(
+            "unknown built-in scenario: {} (available: {})",
+            name,
+            names.join(", ")
+        )
+    })?;
+    load_scenario(yaml)
+}
+
+/// Which steps `run-scenario --tags/--skip-tags` keeps, so one scenario
+/// file can serve several matrices (quick vs full, online vs offline).
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    /// Keep only steps with at least one of these; empty keeps every step.
+    pub include: Vec<String>,
+    /// Drop steps with any of these, even when included.
+    pub exclude: Vec<String>,
+}
+
+impl TagFilter {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a step tagged `tags` runs.
+    pub fn matches(&self, tags: &[&str]) -> bool {
+        let has = |wanted: &[String]| wanted.iter().any(|w| tags.contains(&w.as_str()));
+        (self.include.is_empty() || has(&self.include)) && !has(&self.exclude)
+    }
+}
+
+/// `scenario` with only the steps `filter` keeps. Each step carries its own
+/// tags plus the scenario's.
+pub fn select_steps(scenario: &Scenario, filter: &TagFilter) -> Scenario {
+    let steps = scenario
+        .steps
+        .iter()
+        .filter(|s| {
+            let tags: Vec<&str> = scenario
+                .tags
+                .iter()
+                .chain(&s.tags)
+                .map(String::as_str)
+                .collect();
+            filter.matches(&tags)
+        })
+        .cloned()
+        .collect();
+    Scenario {
+        steps,
+        ..scenario.clone()
+    }
+}
+
+/// The concrete steps `scenario` would run under `filter`, without running
+/// anything.
+pub fn plan(scenario: &Scenario, filter: &TagFilter) -> ScenarioPlan {
+    let mut run_index = 0;
+    let steps = scenario
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(index, spec)| {
+            let tags: Vec<String> = scenario.tags.iter().chain(&spec.tags).cloned().collect();
+            let tag_refs: Vec<&str> = tags.iter().map(String::as_str).collect();
+            let runs = filter.matches(&tag_refs);
+            let planned = PlannedStep {
+                index,
+                run_index: runs.then_some(run_index),
+                label: step_label(&spec.step),
+                tags,
+                skipped: (!runs).then(|| "excluded by tags".to_string()),
+                spec: spec.clone(),
+            };
+            run_index += usize::from(runs);
+            planned
+        })
+        .collect();
+    ScenarioPlan {
+        name: scenario.name.clone(),
+        env: scenario.env.clone(),
+        steps,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::commands::CommandRegistry;
+    use crate::context::AppContext;
+    use crate::probes::ProbeRegistry;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_scenario() {
+        let yaml = r#"
+name: basic test
+steps:
+  - call: "ping"
+    args: {}
+    expect_status: "pass"
+    timeout_ms: 5000
+  - probe: "filesystem"
+"#;
+        let s = load_scenario(yaml).expect("should parse");
+        assert_eq!(s.name, Some("basic test".into()));
+        assert_eq!(s.steps.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_scenario_minimal() {
+        let yaml = r#"
+steps:
+  - call: "read_file"
+    args:
+      path: "/tmp/nope"
+"#;
+        let s = load_scenario(yaml).expect("should parse");
+        assert_eq!(s.steps.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_builtin_smoke_scenario() {
+        let scenario = builtin("smoke").unwrap();
+        assert_eq!(scenario.name.as_deref(), Some("smoke"));
+        assert!(builtin("nope").unwrap_err().contains("available: smoke"));
+
+        let mut ctx = AppContext::default_headless();
+        ctx.offline = true;
+        let ctx = Arc::new(ctx);
+        let result = run_scenario(
+            &scenario,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        assert_eq!(result.overall_status, Status::Pass);
+        let targets: Vec<&str> = result
+            .step_results
+            .iter()
+            .map(|r| r.target.as_str())
+            .collect();
+        assert_eq!(targets, ["ping", "filesystem", "network", "env"]);
+        assert!(result.step_results[3].data.is_some());
+    }
+
+    #[test]
+    fn test_select_steps_by_tags() {
+        let s = load_scenario(
+            r#"
+tags: [compat]
+steps:
+  - call: ping
+    tags: [quick]
+  - probe: network
+    tags: [quick, network]
+  - doctor: true
+"#,
+        )
+        .unwrap();
+        assert_eq!(s.steps[1].tags, vec!["quick", "network"]);
+        let labels = |f: &TagFilter| -> Vec<String> {
+            select_steps(&s, f)
+                .steps
+                .iter()
+                .map(|s| step_label(&s.step))
+                .collect()
+        };
+        let filter = |include: &[&str], exclude: &[&str]| TagFilter {
+            include: include.iter().map(|t| t.to_string()).collect(),
+            exclude: exclude.iter().map(|t| t.to_string()).collect(),
+        };
+
+        assert_eq!(labels(&TagFilter::default()).len(), 3);
+        assert_eq!(
+            labels(&filter(&["quick"], &[])),
+            vec!["ping", "probe:network"]
+        );
+        assert_eq!(labels(&filter(&["quick"], &["network"])), vec!["ping"]);
+        // Scenario-level tags are inherited by every step.
+        assert_eq!(labels(&filter(&["compat"], &["quick"])), vec!["doctor"]);
+        assert!(labels(&filter(&["missing"], &[])).is_empty());
+    }
+
+    #[test]
+    fn test_plan_resolves_defaults_and_marks_filtered_steps() {
+        let s = load_scenario(
+            r#"
+name: planned
+steps:
+  - call: ping
+    tags: [slow]
+  - call: ping
+    xfail: "known"
+"#,
+        )
+        .unwrap();
+        let filter = TagFilter {
+            include: vec![],
+            exclude: vec!["slow".into()],
+        };
+        let plan = plan(&s, &filter);
+        assert_eq!(plan.name.as_deref(), Some("planned"));
+        assert_eq!(plan.steps[0].run_index, None);
+        assert_eq!(plan.steps[0].skipped.as_deref(), Some("excluded by tags"));
+        assert_eq!(plan.steps[1].run_index, Some(0));
+
+        let json = serde_json::to_value(&plan.steps[1].spec).unwrap();
+        assert_eq!(json["expect_status"], "pass");
+        assert_eq!(json["timeout_ms"], 30_000);
+        assert_eq!(json["xfail"], "known");
+    }
+
+    #[test]
+    fn test_expand_data_runs_step_per_row() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("files.csv"),
+            "name,text\na.txt,one\nb.txt,two\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("pings.json"), r#"[{"want": "pass"}]"#).unwrap();
+        let s = load_scenario(
+            r#"
+steps:
+  - call: write_file
+    args: { path: "/tmp/${name}", content: "${text}" }
+    data: files.csv
+    tags: [io]
+  - call: ping
+    expect_status: "${want}"
+    data: pings.json
+  - call: ping
+"#,
+        )
+        .unwrap();
+        let s = expand_data(s, dir.path()).unwrap();
+        assert_eq!(s.steps.len(), 4);
+        match &s.steps[1].step {
+            ScenarioStep::Call { args, .. } => {
+                assert_eq!(args["path"], "/tmp/b.txt");
+                assert_eq!(args["content"], "two");
+            }
+            other => panic!("unexpected step {:?}", other),
+        }
+        assert_eq!(s.steps[1].tags, vec!["io"]);
+        assert!(s.steps.iter().all(|step| step.data.is_none()));
+
+        let missing = load_scenario("steps:\n  - call: ping\n    data: nope.csv\n").unwrap();
+        let err = expand_data(missing, dir.path()).unwrap_err();
+        assert!(err.starts_with("step 0: cannot read dataset"), "{}", err);
+    }
+
+    pub(crate) struct EchoStep;
+
+    #[async_trait::async_trait]
+    impl crate::traits::StepHandler for EchoStep {
+        fn key(&self) -> &str {
+            "echo"
+        }
+
+        async fn run(&self, args: &serde_json::Value, ctx: &AppContext) -> CommandResult {
+            let mut r = result_ok("echo", "echo", &ctx.new_run_id(), 0);
+            if args["fail"] == true {
+                r.status = Status::Fail;
+            }
+            r.data = Some(args.clone());
+            r
+        }
+    }
+}
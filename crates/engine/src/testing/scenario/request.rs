@@ -0,0 +1,185 @@
+//! Scenarios sent to the daemon.
+
+use super::run::run_scenario;
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::daemon_sessions::DaemonSession;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use std::sync::Arc;
+
+/// What `scenario` does, as daemon session actions (see
+/// [`DaemonSession::permits`]): `call:<command>` and `probe:<target>` for
+/// its steps, including the `prompt_render` and `system_stats` calls behind
+/// prompt and resources steps, `doctor` for a doctor step,
+/// `serve_http_fixture` for a fixture server, `step:<key>` for a custom
+/// step (so only a session granted it, or `*`, may run one), and `env_set`
+/// when it sets environment variables.
+pub fn actions(scenario: &Scenario) -> Vec<String> {
+    let mut actions: Vec<String> = scenario
+        .steps
+        .iter()
+        .flat_map(|spec| match &spec.step {
+            ScenarioStep::Call { call, .. } => vec![format!("call:{}", call)],
+            ScenarioStep::Probe { probe, .. } => vec![format!("probe:{}", probe)],
+            ScenarioStep::Prompt { .. } => vec!["call:prompt_render".to_string()],
+            ScenarioStep::Resources { .. } => vec!["call:system_stats".to_string()],
+            ScenarioStep::WaitFor { wait_for } => match (&wait_for.call, &wait_for.probe) {
+                (Some(call), _) => vec![format!("call:{}", call)],
+                (None, Some(probe)) => vec![format!("probe:{}", probe)],
+                (None, None) => vec!["step:wait_for".to_string()],
+            },
+            ScenarioStep::Doctor { doctor: true } => vec!["doctor".to_string()],
+            ScenarioStep::HttpFixture { .. } => vec!["serve_http_fixture".to_string()],
+            ScenarioStep::Custom(fields) => fields.keys().map(|k| format!("step:{}", k)).collect(),
+            ScenarioStep::Doctor { doctor: false }
+            | ScenarioStep::Sleep { .. }
+            | ScenarioStep::Deadline { .. } => Vec::new(),
+        })
+        .collect();
+    if !scenario.env.is_empty() {
+        actions.push("env_set".to_string());
+    }
+    actions
+}
+
+/// Daemon method `run_scenario`: params `{"scenario": {...}}`, a scenario
+/// as JSON with its `data:` rows already expanded. The result's status is
+/// the scenario's and `data` holds the [`ScenarioResult`]. With a
+/// `session`, every step must be within its grants or nothing runs.
+pub async fn run_request(
+    params: serde_json::Value,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+    session: Option<&DaemonSession>,
+) -> CommandResult {
+    let run_id = ctx.new_run_id();
+    let started = ctx.stopwatch();
+    let scenario = params
+        .get("scenario")
+        .cloned()
+        .ok_or_else(|| "expected params {\"scenario\": {...}}".to_string())
+        .and_then(|v| serde_json::from_value::<Scenario>(v).map_err(|e| e.to_string()));
+    let scenario = match scenario {
+        Ok(s) => s,
+        Err(e) => {
+            return result_err(
+                "run_scenario",
+                "",
+                &run_id,
+                0,
+                ErrorCode::InvalidInput,
+                format!("invalid scenario: {}", e),
+            )
+        }
+    };
+    let denied = session.and_then(|session| {
+        actions(&scenario)
+            .into_iter()
+            .find(|action| !session.permits(action))
+            .map(|action| (session, action))
+    });
+    if let Some((session, action)) = denied {
+        return result_err(
+            "run_scenario",
+            scenario.name.as_deref().unwrap_or_default(),
+            &run_id,
+            0,
+            ErrorCode::PermissionDenied,
+            format!("session {} has no grant for {}", session.id, action),
+        );
+    }
+    let result = run_scenario(&scenario, ctx, registry, probes).await;
+    let name = result.name.clone().unwrap_or_default();
+    let mut r = result_ok("run_scenario", &name, &run_id, started.elapsed_ms());
+    r.status = result.overall_status;
+    r.data = serde_json::to_value(&result).ok();
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::*;
+
+    #[tokio::test]
+    async fn test_run_request_round_trips_a_serialized_scenario() {
+        let scenario = load_scenario(
+            "name: remote\non_failure: stop\nenv: {A: 1}\nsteps:\n  - call: ping\n    tags: [t]\n  - sleep_ms: 1\n",
+        )
+        .unwrap();
+        let ctx = Arc::new(
+            AppContext::default_headless().with_env(Box::new(crate::env::tests::MapEnv::default())),
+        );
+        let params = serde_json::json!({ "scenario": scenario });
+        let r = run_request(
+            params.clone(),
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+            None,
+        )
+        .await;
+        assert_eq!(r.status, Status::Pass);
+        assert_eq!(r.target, "remote");
+        let result: ScenarioResult = serde_json::from_value(r.data.unwrap()).unwrap();
+        assert_eq!(result.step_results.len(), 2);
+
+        let bad = run_request(
+            serde_json::json!({}),
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+            None,
+        )
+        .await;
+        assert_eq!(bad.error.unwrap().code, ErrorCode::InvalidInput);
+
+        let grants = serde_json::json!({ "grants": ["run_scenario", "call:ping"] });
+        let session = DaemonSession::open("s".into(), &grants).unwrap();
+        let denied = run_request(
+            params,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+            Some(&session),
+        )
+        .await;
+        let error = denied.error.unwrap();
+        assert_eq!(error.code, ErrorCode::PermissionDenied);
+        assert!(error.message.contains("env_set"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn test_restricted_session_cannot_serve_a_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "name: leak\nsteps:\n  - call: ping\n  - serve_http_fixture: {}\n  - probe: fixture_page\n",
+            dir.path().display()
+        );
+        let scenario = load_scenario(&yaml).unwrap();
+        let grants = serde_json::json!({ "grants": ["run_scenario", "call:ping"] });
+        let session = DaemonSession::open("s".into(), &grants).unwrap();
+        let r = run_request(
+            serde_json::json!({ "scenario": scenario }),
+            &Arc::new(AppContext::default_headless()),
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+            Some(&session),
+        )
+        .await;
+        let error = r.error.unwrap();
+        assert_eq!(error.code, ErrorCode::PermissionDenied);
+        assert!(
+            error.message.contains("serve_http_fixture"),
+            "{}",
+            error.message
+        );
+        assert!(r.data.is_none(), "no step ran");
+
+        let custom =
+            load_scenario("name: c\nsteps:\n  - my_step: {}\n  - prompt: greet\n").unwrap();
+        assert_eq!(actions(&custom), ["step:my_step", "call:prompt_render"]);
+    }
+}
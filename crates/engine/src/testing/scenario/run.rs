@@ -0,0 +1,327 @@
+//! Forward-only scenario runs.
+
+use super::fixtures::Fixtures;
+use super::step::run_step;
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::events::SubscriptionId;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Collects the `session:*` events published while a scenario runs, tagged
+/// with the step in progress.
+pub(super) struct SessionRecorder<'a> {
+    ctx: &'a AppContext,
+    subscription: SubscriptionId,
+    step: Arc<AtomicUsize>,
+    events: Arc<Mutex<Vec<ScenarioSessionEvent>>>,
+}
+
+impl<'a> SessionRecorder<'a> {
+    pub(super) fn start(ctx: &'a AppContext) -> Self {
+        let step = Arc::new(AtomicUsize::new(0));
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (current, sink) = (step.clone(), events.clone());
+        let subscription = ctx.events().subscribe(move |ev| {
+            if !ev.topic.starts_with("session:") {
+                return;
+            }
+            if let Ok(event) = serde_json::from_value(ev.payload["event"].clone()) {
+                sink.lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(ScenarioSessionEvent {
+                        step: current.load(Ordering::Relaxed),
+                        event,
+                    });
+            }
+        });
+        Self {
+            ctx,
+            subscription,
+            step,
+            events,
+        }
+    }
+
+    pub(super) fn set_step(&self, idx: usize) {
+        self.step.store(idx, Ordering::Relaxed);
+    }
+
+    pub(super) fn finish(self) -> Vec<ScenarioSessionEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Drop for SessionRecorder<'_> {
+    fn drop(&mut self) {
+        self.ctx.events().unsubscribe(self.subscription);
+    }
+}
+
+/// Execute a scenario non-interactively (forward-only).
+pub async fn run_scenario(
+    scenario: &Scenario,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> ScenarioResult {
+    let mut step_results = Vec::new();
+    let mut xfail = Vec::new();
+    let mut captured = Vec::new();
+    let mut overall = Status::Pass;
+    let mut stopped_at = None;
+    let _env = crate::env::EnvGuard::apply(ctx.env(), &scenario.env);
+    let session = SessionRecorder::start(ctx);
+    let started = ctx.stopwatch();
+    let mut fixtures = Fixtures::default();
+
+    for (i, spec) in scenario.steps.iter().enumerate() {
+        session.set_step(i);
+        let run = run_step(spec, i, &started, &mut fixtures, ctx, registry, probes).await;
+        step_results.push(run.result);
+        xfail.extend(run.xfail);
+        captured.extend(run.captured);
+        if !run.met {
+            overall = Status::Fail;
+            if spec.on_failure.unwrap_or(scenario.on_failure) == OnFailure::Stop {
+                tracing::warn!(step = i, "scenario stopped after failed step");
+                stopped_at = Some(i);
+                break;
+            }
+        }
+    }
+
+    ScenarioResult {
+        name: scenario.name.clone(),
+        overall_status: overall,
+        step_results,
+        session_events: session.finish(),
+        xfail,
+        stopped_at,
+        captured,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::*;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_run_scenario_ping() {
+        let yaml = r#"
+steps:
+  - call: "ping"
+    args: {}
+    expect_status: "pass"
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Pass);
+        assert_eq!(result.step_results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_records_session_events() {
+        let session = crate::session::ManualSession::default();
+        let ctx = Arc::new(AppContext::default_headless().with_session(Box::new(session.clone())));
+        crate::session::forward(&ctx).unwrap();
+        // Lock the session while the second step runs.
+        let started = std::sync::atomic::AtomicUsize::new(0);
+        ctx.events().subscribe(move |ev| {
+            if ev.topic == "command:started" && started.fetch_add(1, Ordering::Relaxed) == 1 {
+                session.fire(crate::traits::SessionEvent::Lock);
+            }
+        });
+
+        let scenario = load_scenario("steps:\n  - call: ping\n  - call: ping\n").unwrap();
+        let result = run_scenario(
+            &scenario,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        assert_eq!(
+            result.session_events,
+            vec![ScenarioSessionEvent {
+                step: 1,
+                event: crate::traits::SessionEvent::Lock,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_stop_and_step_override() {
+        let yaml = r#"
+on_failure: stop
+steps:
+  - call: ping
+    expect_status: fail
+    on_failure: continue
+  - call: ping
+    xfail: "xfail never stops a run"
+    expect_status: fail
+  - call: ping
+    expect_status: fail
+  - call: ping
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Fail);
+        assert_eq!(result.stopped_at, Some(2));
+        assert_eq!(result.step_results.len(), 3);
+
+        // Interactive runs stop too, without asking.
+        let result = run_scenario_interactive(
+            &scenario,
+            &ctx,
+            &reg,
+            &ProbeRegistry::new(),
+            |_idx, _total, _label, _step, _can_go_back| Some(StepChoice::Run),
+            |idx, _total, _label| {
+                assert_eq!(idx, 0, "only the continue step asks");
+                Some(FailureChoice::Continue)
+            },
+        )
+        .await;
+        assert_eq!(result.stopped_at, Some(2));
+        assert_eq!(result.step_results.len(), 3);
+
+        let default = load_scenario("steps:\n  - call: ping\n").unwrap();
+        assert_eq!(default.on_failure, OnFailure::Continue);
+    }
+
+    struct EnvStep(crate::env::tests::MapEnv);
+
+    #[async_trait::async_trait]
+    impl crate::traits::StepHandler for EnvStep {
+        fn key(&self) -> &str {
+            "greeting"
+        }
+
+        async fn run(&self, _args: &serde_json::Value, ctx: &AppContext) -> CommandResult {
+            use crate::traits::EnvOps;
+            let mut r = result_ok("greeting", "env", &ctx.new_run_id(), 0);
+            r.data = Some(serde_json::json!(self.0.get("APP__GREETING")));
+            r
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scenario_env_is_applied_through_env_ops_and_restored() {
+        use crate::traits::EnvOps;
+        let env = crate::env::tests::MapEnv::default();
+        env.set("APP__GREETING", Some("before"));
+        let ctx = Arc::new(
+            AppContext::default_headless()
+                .with_env(Box::new(env.clone()))
+                .with_step_handler(Box::new(EnvStep(env.clone()))),
+        );
+        let s = load_scenario(
+            r#"
+env:
+  APP__GREETING: hello
+  APP__PORT: 8080
+steps:
+  - greeting: {}
+"#,
+        )
+        .unwrap();
+        let result = run_scenario(&s, &ctx, &CommandRegistry::new(), &ProbeRegistry::new()).await;
+        assert_eq!(
+            result.step_results[0].data,
+            Some(serde_json::json!("hello"))
+        );
+        assert_eq!(env.get("APP__GREETING").as_deref(), Some("before"));
+        assert_eq!(env.get("APP__PORT"), None);
+    }
+
+    #[tokio::test]
+    async fn test_interactive_failure_continue() {
+        // Step 0 passes, step 1 fails (expect "fail" but ping returns "pass"),
+        // user continues, step 2 passes
+        let yaml = r#"
+steps:
+  - call: "ping"
+    args: {}
+    expect_status: "pass"
+  - call: "ping"
+    args: {}
+    expect_status: "fail"
+  - call: "ping"
+    args: {}
+    expect_status: "pass"
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+
+        let result = run_scenario_interactive(
+            &scenario,
+            &ctx,
+            &reg,
+            &ProbeRegistry::new(),
+            |_idx, _total, _label, _step, _can_go_back| Some(StepChoice::Run),
+            |idx, _total, _label| {
+                assert_eq!(idx, 1); // only step 1 should fail
+                Some(FailureChoice::Continue)
+            },
+        )
+        .await;
+
+        assert_eq!(result.overall_status, Status::Fail);
+        assert_eq!(result.step_results.len(), 3);
+        assert_eq!(result.step_results[0].status, Status::Pass);
+        // step 1's CommandResult.status is Pass (ping succeeded) but its expectation
+        // was "fail" - the only externally visible evidence is overall_status above.
+        assert_eq!(result.step_results[1].status, Status::Pass);
+        // step 1 failed expectation but we continued to step 2
+        assert_eq!(result.step_results[2].status, Status::Pass);
+    }
+
+    #[tokio::test]
+    async fn test_interactive_failure_abort() {
+        // Step 0 fails (expect "fail" but ping returns "pass"), user aborts
+        let yaml = r#"
+steps:
+  - call: "ping"
+    args: {}
+    expect_status: "fail"
+  - call: "ping"
+    args: {}
+    expect_status: "pass"
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+
+        let result = run_scenario_interactive(
+            &scenario,
+            &ctx,
+            &reg,
+            &ProbeRegistry::new(),
+            |_idx, _total, _label, _step, _can_go_back| Some(StepChoice::Run),
+            |idx, _total, _label| {
+                assert_eq!(idx, 0);
+                Some(FailureChoice::Abort)
+            },
+        )
+        .await;
+
+        assert_eq!(result.overall_status, Status::Fail);
+        // Only step 0 recorded, step 1 never reached
+        assert_eq!(result.step_results.len(), 1);
+        // The failed step's CommandResult.status is Pass (ping executed OK)
+        // even though its expectation ("fail") was not met.
+        assert_eq!(result.step_results[0].status, Status::Pass);
+    }
+}
@@ -0,0 +1,492 @@
+//! Running one step: dispatch on its kind, `xfail` markers, and saved
+//! artifacts.
+
+use super::artifacts::{capture_artifacts, data_field};
+use super::fixtures::{Fixtures, DEFAULT_FIXTURE};
+use super::{checks, timing};
+use crate::clock::Stopwatch;
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::pool::Priority;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use std::sync::Arc;
+
+/// Return the label (target name) for a scenario step.
+pub(super) fn step_label(step: &ScenarioStep) -> String {
+    match step {
+        ScenarioStep::Call { call, .. } => call.clone(),
+        ScenarioStep::Probe { probe, .. } => format!("probe:{}", probe),
+        ScenarioStep::Prompt { prompt, .. } => format!("prompt:{}", prompt),
+        ScenarioStep::Resources { .. } => "resources".into(),
+        ScenarioStep::Doctor { .. } => "doctor".into(),
+        ScenarioStep::Sleep { .. } => "sleep".into(),
+        ScenarioStep::Deadline { .. } => "deadline".into(),
+        ScenarioStep::HttpFixture { name, .. } => format!(
+            "serve_http_fixture:{}",
+            name.as_deref().unwrap_or(DEFAULT_FIXTURE)
+        ),
+        ScenarioStep::WaitFor { wait_for } => match (&wait_for.call, &wait_for.probe) {
+            (Some(call), _) => format!("wait_for:{}", call),
+            (None, Some(probe)) => format!("wait_for:probe:{}", probe),
+            (None, None) => "wait_for".into(),
+        },
+        ScenarioStep::Custom(fields) => match custom_key(fields) {
+            Some((key, _)) => key.to_string(),
+            None => "custom".into(),
+        },
+    }
+}
+
+/// The key and arguments of a custom step, which must have exactly one key.
+fn custom_key(
+    fields: &serde_json::Map<String, serde_json::Value>,
+) -> Option<(&str, &serde_json::Value)> {
+    let mut entries = fields.iter();
+    match (entries.next(), entries.next()) {
+        (Some((key, args)), None) => Some((key.as_str(), args)),
+        _ => None,
+    }
+}
+
+/// Built-in step keys; a step with one of these that still ended up as
+/// [`ScenarioStep::Custom`] has fields of the wrong shape.
+const BUILTIN_STEP_KEYS: [&str; 9] = [
+    "call",
+    "probe",
+    "prompt",
+    "resources",
+    "doctor",
+    "wait_for",
+    "sleep_ms",
+    "deadline_ms",
+    "serve_http_fixture",
+];
+
+/// What running one [`StepSpec`] produced.
+pub(super) struct StepRun {
+    pub(super) result: CommandResult,
+    /// Whether the step met its expectation; always true for `xfail` steps.
+    pub(super) met: bool,
+    pub(super) xfail: Option<XfailStep>,
+    pub(super) captured: Vec<StepArtifact>,
+}
+
+/// Execute `spec`, applying its `xfail` marker and capturing its
+/// `save_artifacts` plus those its command always saves.
+pub(super) async fn run_step(
+    spec: &StepSpec,
+    idx: usize,
+    started: &Stopwatch,
+    fixtures: &mut Fixtures,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> StepRun {
+    let spec = &*fixtures.resolve(spec);
+    let (mut result, met) =
+        execute_step(&spec.step, idx, started, fixtures, ctx, registry, probes).await;
+    let mut wanted = spec.save_artifacts.clone();
+    if let ScenarioStep::Call { call, .. } = &spec.step {
+        for field in registry.artifact_fields(call) {
+            let want = SaveArtifact::Data {
+                data: field.to_string(),
+            };
+            if data_field(result.data.as_ref(), field).is_some() && !wanted.contains(&want) {
+                wanted.push(want);
+            }
+        }
+    }
+    let captured = capture_artifacts(&wanted, idx, &mut result, ctx);
+    let Some(reason) = &spec.xfail else {
+        return StepRun {
+            result,
+            met,
+            xfail: None,
+            captured,
+        };
+    };
+    let outcome = if met {
+        tracing::warn!(step = idx, target = %result.target, %reason, "xfail step passed");
+        XfailOutcome::Xpass
+    } else {
+        XfailOutcome::Xfail
+    };
+    let xfail = XfailStep {
+        step: idx,
+        target: result.target.clone(),
+        reason: reason.clone(),
+        outcome,
+    };
+    StepRun {
+        result,
+        met: true,
+        xfail: Some(xfail),
+        captured,
+    }
+}
+
+/// Execute a single scenario step and return the result plus whether the
+/// expectation was met.
+async fn execute_step(
+    step: &ScenarioStep,
+    idx: usize,
+    started: &Stopwatch,
+    fixtures: &mut Fixtures,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> (CommandResult, bool) {
+    match step {
+        ScenarioStep::Call {
+            call,
+            args,
+            expect_status,
+            timeout_ms,
+            dialogs,
+        } => {
+            if !dialogs.is_empty() {
+                if let Err(e) = ctx.dialogs().script(dialogs.clone()) {
+                    let r = result_err(
+                        "call",
+                        call,
+                        &ctx.new_run_id(),
+                        0,
+                        ErrorCode::Unsupported,
+                        format!("step {} ('{}'): cannot script dialogs: {}", idx, call, e),
+                    );
+                    return (r, false);
+                }
+            }
+
+            // On a watcher thread, so a command that overruns the step's
+            // timeout fails it instead of holding up the scenario.
+            let r = registry.execute_within(
+                call,
+                args.clone(),
+                ctx,
+                Some(Priority::Background),
+                Some(*timeout_ms),
+            );
+            if !dialogs.is_empty() {
+                // Unused answers must not leak into later steps.
+                let _ = ctx.dialogs().script(Vec::new());
+            }
+
+            let actual_status = serde_json::to_value(r.status)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default();
+            let met = actual_status == *expect_status;
+            if !met {
+                tracing::warn!(
+                    step = idx,
+                    expected = %expect_status,
+                    actual = %actual_status,
+                    "scenario step status mismatch"
+                );
+            }
+            (r, met)
+        }
+        ScenarioStep::Probe {
+            probe,
+            args,
+            throughput,
+        } => {
+            let mut args = args.clone();
+            if let Some(throughput) = throughput {
+                if args.is_null() {
+                    args = serde_json::json!({});
+                }
+                if let Some(obj) = args.as_object_mut() {
+                    obj.insert(
+                        "throughput".into(),
+                        serde_json::to_value(throughput).unwrap_or_default(),
+                    );
+                }
+            }
+            let r = probes.run(probe, args, ctx).await;
+            let met = r.status == Status::Pass || r.status == Status::Skip;
+            (r, met)
+        }
+        ScenarioStep::Prompt {
+            prompt,
+            version,
+            vars,
+            expect,
+            expect_contains,
+        } => {
+            let args = serde_json::json!({ "name": prompt, "version": version, "vars": vars });
+            let mut r = registry.execute("prompt_render", args, ctx);
+            r.command = "prompt".into();
+            r.target = prompt.clone();
+            if r.status != Status::Pass {
+                return (r, false);
+            }
+
+            checks::prompt_output(r, expect.as_deref(), expect_contains, idx)
+        }
+        ScenarioStep::Resources { resources } => {
+            let args = serde_json::json!({ "interval_ms": resources.interval_ms });
+            let mut r = registry.execute("system_stats", args, ctx);
+            r.command = "resources".into();
+            r.target = "resources".into();
+            checks::resource_ceilings(r, resources, idx)
+        }
+        ScenarioStep::Doctor { doctor: true } => (crate::doctor::run_doctor(ctx), true),
+        ScenarioStep::Doctor { doctor: false } => {
+            let mut r = result_ok("doctor", "env", &ctx.new_run_id(), 0);
+            r.status = Status::Skip;
+            (r, true)
+        }
+        ScenarioStep::Sleep { sleep_ms } => timing::sleep(*sleep_ms, ctx).await,
+        ScenarioStep::Deadline { deadline_ms } => timing::deadline(*deadline_ms, started, idx, ctx),
+        ScenarioStep::WaitFor { wait_for } => {
+            timing::wait_until(wait_for, &step_label(step), idx, ctx, registry, probes).await
+        }
+        ScenarioStep::HttpFixture {
+            serve_http_fixture,
+            name,
+        } => {
+            let name = name.as_deref().unwrap_or(DEFAULT_FIXTURE);
+            fixtures
+                .serve(serve_http_fixture, name, &step_label(step), idx, ctx)
+                .await
+        }
+        ScenarioStep::Custom(fields) => {
+            let handler = custom_key(fields)
+                .and_then(|(key, args)| ctx.step_handler(key).map(|handler| (handler, args)));
+            let Some((handler, args)) = handler else {
+                let keys: Vec<&str> = fields.keys().map(String::as_str).collect();
+                let message = match keys.iter().find(|k| BUILTIN_STEP_KEYS.contains(k)) {
+                    Some(key) => format!("step {}: invalid fields for a `{}` step", idx, key),
+                    None => format!(
+                        "step {}: no step handler for keys [{}]",
+                        idx,
+                        keys.join(", ")
+                    ),
+                };
+                let r = result_err(
+                    "scenario",
+                    &step_label(step),
+                    &ctx.new_run_id(),
+                    0,
+                    ErrorCode::InvalidInput,
+                    message,
+                );
+                return (r, false);
+            };
+            let r = handler.run(args, ctx).await;
+            let met = r.status == Status::Pass || r.status == Status::Skip;
+            (r, met)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::tests::EchoStep;
+    use crate::scenario::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_scenario_network_throughput_budget() {
+        use crate::platform::{HeadlessClipboard, MockNetwork, StdFilesystem};
+        use crate::traits::HttpResponse;
+
+        let network = MockNetwork::new()
+            .respond("https://api.test/", HttpResponse::default())
+            .respond(
+                "https://dl.test/",
+                HttpResponse {
+                    status: 200,
+                    body: vec![0; 64],
+                    ..Default::default()
+                },
+            );
+        let mut ctx = AppContext::new(
+            Box::new(StdFilesystem),
+            Box::new(network),
+            Box::new(HeadlessClipboard),
+        );
+        ctx.network_probe.endpoints = vec![crate::endpoints::NetworkEndpoint::new(
+            "https://api.test/health",
+        )];
+        let ctx = Arc::new(ctx);
+        let yaml = r#"
+steps:
+  - probe: network
+  - probe: network
+    throughput: { latency_samples: 2, download_url: "https://dl.test/5mb", min_mbps: 1000 }
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let result = run_scenario(
+            &scenario,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        assert_eq!(result.step_results[0].status, Status::Pass);
+        let slow = &result.step_results[1];
+        assert_eq!(slow.status, Status::Error);
+        let throughput = &slow.data.as_ref().unwrap()["throughput"];
+        assert_eq!(throughput["latency_samples"], 2);
+        assert!(slow
+            .error
+            .as_ref()
+            .unwrap()
+            .message
+            .contains("throughput: bandwidth"));
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_scripted_dialogs() {
+        let yaml = r#"
+steps:
+  - call: dialog_confirm
+    args: { message: "Overwrite?" }
+    dialogs:
+      - confirm: true
+      - open: /tmp/unused
+  - call: dialog_open
+    expect_status: error
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Pass);
+        assert_eq!(
+            result.step_results[0].data.as_ref().unwrap()["confirmed"],
+            true
+        );
+        // The leftover answer was cleared, so step 2 has nothing to answer with.
+        assert_eq!(
+            result.step_results[1].error.as_ref().unwrap().code,
+            crate::types::ErrorCode::Unsupported
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generous_timeout_does_not_fire() {
+        // Verify the timeout_ms field is accepted without panicking and that
+        // a generous deadline (5 s) does NOT trigger a false timeout on ping.
+        let scenario = Scenario {
+            tags: vec![],
+            on_failure: OnFailure::Continue,
+            env: Default::default(),
+            name: Some("timeout test".into()),
+            steps: vec![ScenarioStep::Call {
+                call: "ping".to_string(),
+                args: serde_json::json!({}),
+                expect_status: "pass".to_string(),
+                timeout_ms: 5_000,
+                dialogs: vec![],
+            }
+            .into()],
+        };
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Pass);
+        assert_eq!(result.step_results[0].status, Status::Pass);
+    }
+
+    #[tokio::test]
+    async fn test_call_timeout_fails_a_slow_command() {
+        fn slow(
+            _: serde_json::Value,
+            _: &AppContext,
+        ) -> Result<serde_json::Value, crate::commands::CommandError> {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(serde_json::Value::Null)
+        }
+        let mut reg = CommandRegistry::new();
+        reg.register("slow", slow);
+        let scenario = load_scenario("steps:\n  - call: slow\n    timeout_ms: 100\n").unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let started = std::time::Instant::now();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let timed_out = &result.step_results[0];
+        assert_eq!(timed_out.error.as_ref().unwrap().code, ErrorCode::Timeout);
+        assert_eq!(result.overall_status, Status::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_xfail_steps_keep_scenario_green() {
+        let s = load_scenario(
+            r#"
+steps:
+  - call: no_such_command
+    xfail: "not implemented on this platform"
+  - call: ping
+    xfail: "flaky on arm64"
+  - call: ping
+    args: {}
+    expect_status: pass
+"#,
+        )
+        .unwrap();
+        let ctx = Arc::new(AppContext::default_headless());
+        let result = run_scenario(&s, &ctx, &CommandRegistry::new(), &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Pass);
+        // The step result keeps its real status.
+        assert_ne!(result.step_results[0].status, Status::Pass);
+        assert_eq!(
+            result.xfail,
+            vec![
+                XfailStep {
+                    step: 0,
+                    target: "no_such_command".into(),
+                    reason: "not implemented on this platform".into(),
+                    outcome: XfailOutcome::Xfail,
+                },
+                XfailStep {
+                    step: 1,
+                    target: "ping".into(),
+                    reason: "flaky on arm64".into(),
+                    outcome: XfailOutcome::Xpass,
+                },
+            ]
+        );
+
+        // Without the marker the same failure turns the run red.
+        let s = load_scenario("steps:\n  - call: no_such_command\n").unwrap();
+        let result = run_scenario(&s, &ctx, &CommandRegistry::new(), &ProbeRegistry::new()).await;
+        assert_eq!(result.overall_status, Status::Fail);
+        assert!(result.xfail.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_step_handlers_dispatch_by_key() {
+        let s = load_scenario(
+            r#"
+steps:
+  - echo: { message: "hi" }
+    tags: [custom]
+  - echo: { fail: true }
+    xfail: "echo was told to fail"
+  - screenshot: { window: main }
+  - call: ping
+    timeout_ms: "soon"
+"#,
+        )
+        .unwrap();
+        assert_eq!(step_label(&s.steps[0].step), "echo");
+        assert_eq!(s.steps[0].tags, vec!["custom"]);
+
+        let ctx = Arc::new(AppContext::default_headless().with_step_handler(Box::new(EchoStep)));
+        let result = run_scenario(&s, &ctx, &CommandRegistry::new(), &ProbeRegistry::new()).await;
+        let r = &result.step_results;
+        assert_eq!(r[0].status, Status::Pass);
+        assert_eq!(r[0].data, Some(serde_json::json!({ "message": "hi" })));
+        assert_eq!(result.xfail[0].outcome, XfailOutcome::Xfail);
+        let message = |i: usize| r[i].error.as_ref().unwrap().message.clone();
+        assert_eq!(message(2), "step 2: no step handler for keys [screenshot]");
+        assert_eq!(message(3), "step 3: invalid fields for a `call` step");
+        assert_eq!(result.overall_status, Status::Fail);
+    }
+}
@@ -0,0 +1,250 @@
+//! `wait_for`, `sleep_ms`, and `deadline_ms` steps.
+
+use super::artifacts::data_field;
+use crate::clock::Stopwatch;
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::pool::Priority;
+use crate::probes::ProbeRegistry;
+use crate::types::*;
+use std::time::Duration;
+
+/// Poll for a [`WaitFor`] step. The result is the last attempt's, renamed
+/// to the step and failed with `TIMEOUT` if the condition never held; its
+/// data records the attempts and the last attempt's own data.
+pub(super) async fn wait_until(
+    wait: &WaitFor,
+    label: &str,
+    idx: usize,
+    ctx: &AppContext,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> (CommandResult, bool) {
+    if wait.call.is_some() == wait.probe.is_some() {
+        let r = result_err(
+            "wait_for",
+            label,
+            &ctx.new_run_id(),
+            0,
+            ErrorCode::InvalidInput,
+            format!(
+                "step {}: wait_for needs exactly one of `call` or `probe`",
+                idx
+            ),
+        );
+        return (r, false);
+    }
+    // A fixed clock moves on with each poll's sleep, so this still times out.
+    let started = ctx.stopwatch();
+    let deadline = Duration::from_millis(wait.timeout_ms);
+    let mut attempts = 0u64;
+    loop {
+        attempts += 1;
+        let mut r = match (&wait.call, &wait.probe) {
+            (Some(call), _) => {
+                registry.execute_as(call, wait.args.clone(), ctx, Priority::Background)
+            }
+            (None, Some(probe)) => probes.run(probe, wait.args.clone(), ctx).await,
+            (None, None) => unreachable!("checked above"),
+        };
+        let status_met = serde_json::to_value(r.status)
+            .ok()
+            .and_then(|v| v.as_str().map(String::from))
+            == Some(wait.status.clone());
+        let field_met = match &wait.field {
+            None => true,
+            Some(field) => match (data_field(r.data.as_ref(), field), &wait.equals) {
+                (Some(actual), Some(want)) => actual == want,
+                (found, None) => found.is_some(),
+                (None, Some(_)) => false,
+            },
+        };
+        let met = status_met && field_met;
+        let elapsed = started.elapsed();
+        if met || elapsed >= deadline {
+            let last = r.data.take();
+            r.command = "wait_for".into();
+            r.target = label.to_string();
+            r.timing_ms.total = elapsed.as_millis() as u64;
+            r.data = Some(serde_json::json!({ "attempts": attempts, "last": last }));
+            if !met {
+                r.status = Status::Fail;
+                r.error = Some(ErrorInfo {
+                    code: ErrorCode::Timeout,
+                    message: format!(
+                        "step {}: condition not met after {} attempts in {}ms",
+                        idx, attempts, wait.timeout_ms
+                    ),
+                    details: serde_json::Value::Null,
+                });
+            }
+            return (r, met);
+        }
+        let interval = Duration::from_millis(wait.interval_ms).min(deadline - elapsed);
+        crate::clock::sleep(ctx.clock(), interval).await;
+    }
+}
+
+/// A `sleep_ms` step: sleep on the context clock, recording how long that
+/// actually took.
+pub(super) async fn sleep(sleep_ms: u64, ctx: &AppContext) -> (CommandResult, bool) {
+    let slept = ctx.stopwatch();
+    crate::clock::sleep(ctx.clock(), Duration::from_millis(sleep_ms)).await;
+    let actual_ms = slept.elapsed_ms();
+    let mut r = result_ok("sleep", "sleep", &ctx.new_run_id(), actual_ms);
+    r.data = Some(serde_json::json!({ "requested_ms": sleep_ms, "actual_ms": actual_ms }));
+    (r, true)
+}
+
+/// A `deadline_ms` step: fail once the scenario, `started` when it began,
+/// has run for longer than `deadline_ms`.
+pub(super) fn deadline(
+    deadline_ms: u64,
+    started: &Stopwatch,
+    idx: usize,
+    ctx: &AppContext,
+) -> (CommandResult, bool) {
+    let elapsed_ms = started.elapsed_ms();
+    let mut r = result_ok("deadline", "deadline", &ctx.new_run_id(), 0);
+    r.data = Some(serde_json::json!({ "budget_ms": deadline_ms, "elapsed_ms": elapsed_ms }));
+    let met = elapsed_ms <= deadline_ms;
+    if !met {
+        tracing::warn!(
+            step = idx,
+            elapsed_ms,
+            budget_ms = deadline_ms,
+            "scenario over its time budget"
+        );
+        r.status = Status::Fail;
+        r.error = Some(ErrorInfo {
+            code: ErrorCode::Timeout,
+            message: format!(
+                "step {}: {}ms elapsed, over the {}ms budget",
+                idx, elapsed_ms, deadline_ms
+            ),
+            details: serde_json::Value::Null,
+        });
+    }
+    (r, met)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_sleep_and_deadline_follow_the_context_clock() {
+        let yaml = r#"
+steps:
+  - sleep_ms: 60000
+  - deadline_ms: 30000
+"#;
+        let scenario = load_scenario(yaml).unwrap();
+        let ctx = Arc::new(
+            AppContext::default_headless()
+                .with_clock(Box::new(crate::clock::FixedClock::at_unix(1_700_000_000))),
+        );
+        let result = run_scenario(
+            &scenario,
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        let sleep = result.step_results[0].data.as_ref().unwrap();
+        assert_eq!(sleep["actual_ms"], 60000);
+        assert_eq!(result.step_results[1].status, Status::Fail);
+        assert_eq!(
+            result.step_results[1].data.as_ref().unwrap()["elapsed_ms"],
+            60000
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_polls_until_condition_or_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let ready = dir.path().join("ready");
+        let writer = {
+            let ready = ready.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(60)).await;
+                std::fs::write(ready, "up").unwrap();
+            })
+        };
+        let step = |field: &str, timeout_ms: u64| -> StepSpec {
+            ScenarioStep::WaitFor {
+                wait_for: serde_json::from_value(serde_json::json!({
+                    "call": "read_file",
+                    "args": { "path": ready },
+                    "field": field,
+                    "equals": "up",
+                    "timeout_ms": timeout_ms,
+                    "interval_ms": 10,
+                }))
+                .unwrap(),
+            }
+            .into()
+        };
+        let scenario = Scenario {
+            name: None,
+            tags: vec![],
+            on_failure: OnFailure::Continue,
+            env: Default::default(),
+            steps: vec![step("content", 5_000), step("missing", 50)],
+        };
+        let ctx = Arc::new(AppContext::default_headless());
+        let reg = CommandRegistry::new();
+        let result = run_scenario(&scenario, &ctx, &reg, &ProbeRegistry::new()).await;
+        writer.await.unwrap();
+
+        let waited = &result.step_results[0];
+        assert_eq!(waited.status, Status::Pass);
+        assert_eq!(waited.command, "wait_for");
+        assert_eq!(waited.target, "wait_for:read_file");
+        let data = waited.data.as_ref().unwrap();
+        assert!(data["attempts"].as_u64().unwrap() > 1);
+        assert_eq!(data["last"]["content"], "up");
+
+        let timed_out = &result.step_results[1];
+        assert_eq!(timed_out.status, Status::Fail);
+        assert_eq!(timed_out.error.as_ref().unwrap().code, ErrorCode::Timeout);
+        assert_eq!(result.overall_status, Status::Fail);
+
+        let both =
+            load_scenario("steps:\n  - wait_for: { call: ping, probe: filesystem }\n").unwrap();
+        let result = run_scenario(&both, &ctx, &reg, &ProbeRegistry::new()).await;
+        assert_eq!(
+            result.step_results[0].error.as_ref().unwrap().code,
+            ErrorCode::InvalidInput
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sleep_and_deadline_steps_record_timing() {
+        let s = load_scenario(
+            r#"
+steps:
+  - deadline_ms: 10000
+  - sleep_ms: 30
+  - deadline_ms: 10
+"#,
+        )
+        .unwrap();
+        assert_eq!(step_label(&s.steps[1].step), "sleep");
+        let ctx = Arc::new(AppContext::default_headless());
+        let result = run_scenario(&s, &ctx, &CommandRegistry::new(), &ProbeRegistry::new()).await;
+        let r = &result.step_results;
+
+        assert_eq!(r[0].status, Status::Pass);
+        assert_eq!(r[0].data.as_ref().unwrap()["budget_ms"], 10_000);
+        let slept = r[1].data.as_ref().unwrap();
+        assert_eq!(slept["requested_ms"], 30);
+        assert!(slept["actual_ms"].as_u64().unwrap() >= 30);
+        assert_eq!(r[2].status, Status::Fail);
+        assert_eq!(r[2].error.as_ref().unwrap().code, ErrorCode::Timeout);
+        assert!(r[2].data.as_ref().unwrap()["elapsed_ms"].as_u64().unwrap() >= 30);
+        assert_eq!(result.overall_status, Status::Fail);
+    }
+}
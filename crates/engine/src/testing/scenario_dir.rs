@@ -0,0 +1,206 @@
+//! Scenario directory runner – every scenario file in a directory, several
+//! at a time if asked, summed into one report (`appctl run-scenarios`).
+
+use crate::commands::CommandRegistry;
+use crate::context::AppContext;
+use crate::probes::ProbeRegistry;
+use crate::scenario::{expand_data, load_scenario, run_scenario, select_steps, TagFilter};
+use crate::types::*;
+use futures_util::stream::{self, StreamExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The `*.yaml` and `*.yml` files directly in `dir`, sorted by name.
+pub fn discover(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let yaml = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        );
+        if yaml && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Run every scenario in `dir`, at most `jobs` at once (0 counts as 1),
+/// keeping only the steps `filter` selects. Fails only if `dir` cannot be
+/// listed; unreadable or invalid files are reported per file.
+pub async fn run_dir(
+    dir: &Path,
+    jobs: usize,
+    filter: &TagFilter,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> std::io::Result<ScenarioDirResult> {
+    let files = discover(dir)?;
+    let scenarios: Vec<ScenarioFileResult> = stream::iter(&files)
+        .map(|path| run_file(path, filter, ctx, registry, probes))
+        .buffered(jobs.max(1))
+        .collect()
+        .await;
+
+    let mut totals = ScenarioTotals {
+        scenarios: scenarios.len(),
+        ..Default::default()
+    };
+    for s in &scenarios {
+        match s.status {
+            Status::Pass => totals.passed += 1,
+            Status::Fail => totals.failed += 1,
+            Status::Skip => totals.skipped += 1,
+            Status::Error => totals.errors += 1,
+        }
+        if let Some(r) = &s.result {
+            totals.steps += r.step_results.len();
+            for x in &r.xfail {
+                match x.outcome {
+                    XfailOutcome::Xfail => totals.xfailed += 1,
+                    XfailOutcome::Xpass => totals.xpassed += 1,
+                }
+            }
+        }
+        totals.timing_ms += s.timing_ms;
+    }
+    let overall_status = if totals.failed + totals.errors > 0 {
+        Status::Fail
+    } else if totals.passed == 0 {
+        Status::Skip
+    } else {
+        Status::Pass
+    };
+
+    Ok(ScenarioDirResult {
+        dir: dir.display().to_string(),
+        overall_status,
+        totals,
+        scenarios,
+    })
+}
+
+async fn run_file(
+    path: &Path,
+    filter: &TagFilter,
+    ctx: &Arc<AppContext>,
+    registry: &CommandRegistry,
+    probes: &ProbeRegistry,
+) -> ScenarioFileResult {
+    let started = Instant::now();
+    let file = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let loaded = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read scenario file: {}", e))
+        .and_then(|yaml| load_scenario(&yaml))
+        .and_then(|s| expand_data(s, path.parent().unwrap_or(Path::new(""))));
+
+    match loaded {
+        Ok(scenario) => {
+            let scenario = if filter.is_empty() {
+                scenario
+            } else {
+                select_steps(&scenario, filter)
+            };
+            let result = run_scenario(&scenario, ctx, registry, probes).await;
+            ScenarioFileResult {
+                file,
+                status: result.overall_status,
+                timing_ms: started.elapsed().as_millis() as u64,
+                error: None,
+                result: Some(result),
+            }
+        }
+        Err(error) => ScenarioFileResult {
+            file,
+            status: Status::Error,
+            timing_ms: started.elapsed().as_millis() as u64,
+            error: Some(error),
+            result: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_dir_aggregates_files_in_name_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, yaml: &str| std::fs::write(dir.path().join(name), yaml).unwrap();
+        write("c.yaml", "steps:\n  - call: ping\n    tags: [slow]\n");
+        write(
+            "a.yaml",
+            "name: ok\nsteps:\n  - call: ping\n  - call: ping\n",
+        );
+        write("b.yml", "steps:\n  - call: ping\n    expect_status: fail\n");
+        write("broken.yaml", "steps: 3\n");
+        write("notes.txt", "not a scenario");
+
+        let ctx = Arc::new(AppContext::default_headless());
+        let result = run_dir(
+            dir.path(),
+            2,
+            &TagFilter {
+                include: vec![],
+                exclude: vec!["slow".into()],
+            },
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await
+        .unwrap();
+
+        let files: Vec<(&str, Status)> = result
+            .scenarios
+            .iter()
+            .map(|s| (s.file.as_str(), s.status))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("a.yaml", Status::Pass),
+                ("b.yml", Status::Fail),
+                ("broken.yaml", Status::Error),
+                // Every step filtered out.
+                ("c.yaml", Status::Pass),
+            ]
+        );
+        assert!(result.scenarios[2].error.is_some());
+        assert_eq!(result.overall_status, Status::Fail);
+        assert_eq!(
+            (
+                result.totals.scenarios,
+                result.totals.passed,
+                result.totals.failed,
+                result.totals.errors,
+                result.totals.steps
+            ),
+            (4, 2, 1, 1, 3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_dir_missing_directory_is_an_error() {
+        let ctx = Arc::new(AppContext::default_headless());
+        let missing = std::path::Path::new("/nonexistent/scenarios");
+        let result = run_dir(
+            missing,
+            1,
+            &TagFilter::default(),
+            &ctx,
+            &CommandRegistry::new(),
+            &ProbeRegistry::new(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
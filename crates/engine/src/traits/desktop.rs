@@ -0,0 +1,299 @@
+//! Desktop integration: clipboard, windows, autostart, shortcuts, dialogs,
+//! the opener, session events, and portals.
+
+use super::{CapError, CapResult};
+use std::path::{Path, PathBuf};
+
+// ---------------------------------------------------------------------------
+// Clipboard operations
+// ---------------------------------------------------------------------------
+
+/// Which selection to use. `Primary` (select to copy, middle-click to
+/// paste) exists only on Linux desktops.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardSelection {
+    #[default]
+    Clipboard,
+    Primary,
+}
+
+pub trait ClipboardOps: Send + Sync {
+    fn read_text(&self) -> CapResult<String>;
+    fn write_text(&self, text: &str) -> CapResult<()>;
+    fn read_selection(&self, selection: ClipboardSelection) -> CapResult<String> {
+        match selection {
+            ClipboardSelection::Clipboard => self.read_text(),
+            ClipboardSelection::Primary => {
+                Err(CapError::Unsupported("no PRIMARY selection here".into()))
+            }
+        }
+    }
+    fn write_selection(&self, selection: ClipboardSelection, text: &str) -> CapResult<()> {
+        match selection {
+            ClipboardSelection::Clipboard => self.write_text(text),
+            ClipboardSelection::Primary => {
+                Err(CapError::Unsupported("no PRIMARY selection here".into()))
+            }
+        }
+    }
+    /// Formats the clipboard currently offers, in the platform's naming
+    /// (`public.utf8-plain-text`, `text/plain;charset=utf-8`, ...).
+    fn available_formats(&self) -> CapResult<Vec<String>> {
+        Err(CapError::Unsupported(
+            "clipboard formats not available".into(),
+        ))
+    }
+    /// A counter the platform bumps on every clipboard change, for change
+    /// monitoring without reading the content. Optional: watchers fall
+    /// back to comparing the content.
+    fn change_count(&self) -> CapResult<u64> {
+        Err(CapError::Unsupported(
+            "clipboard change count not available".into(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Window operations
+// ---------------------------------------------------------------------------
+
+/// Outer position and inner size of a window, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// Native windows, addressed by their label (e.g. `main`).
+pub trait WindowOps: Send + Sync {
+    fn labels(&self) -> Vec<String>;
+    fn geometry(&self, label: &str) -> CapResult<WindowGeometry>;
+    fn set_geometry(&self, label: &str, geometry: &WindowGeometry) -> CapResult<()>;
+}
+
+// ---------------------------------------------------------------------------
+// Autostart (launch at login)
+// ---------------------------------------------------------------------------
+
+/// What to launch when the user logs in.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AutostartEntry {
+    /// Reverse-DNS identifier; names the installed file.
+    pub app_id: String,
+    /// Human-readable name shown by the desktop's startup settings.
+    pub name: String,
+    pub exec: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Whether an entry is installed, as read back from disk.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    /// `launch_agent`, `xdg`, or `systemd`.
+    pub mechanism: String,
+    /// Where the entry lives (whether or not it exists).
+    pub path: PathBuf,
+    /// Program the installed entry launches, if enabled.
+    pub exec: Option<PathBuf>,
+}
+
+/// Login-item registration, keyed by app id.
+pub trait AutostartOps: Send + Sync {
+    fn enable(&self, entry: &AutostartEntry) -> CapResult<AutostartStatus>;
+    fn disable(&self, app_id: &str) -> CapResult<AutostartStatus>;
+    fn status(&self, app_id: &str) -> CapResult<AutostartStatus>;
+}
+
+// ---------------------------------------------------------------------------
+// Global shortcuts
+// ---------------------------------------------------------------------------
+
+/// System-wide key registration. Accelerators are passed in the normalized
+/// form produced by [`crate::shortcuts::normalize`] (e.g. `Ctrl+Shift+P`).
+pub trait ShortcutOps: Send + Sync {
+    fn register(&self, accelerator: &str) -> CapResult<()>;
+    fn unregister_all(&self) -> CapResult<()>;
+    /// Accelerators currently registered, normalized.
+    fn registered(&self) -> Vec<String>;
+}
+
+// ---------------------------------------------------------------------------
+// Dialogs
+// ---------------------------------------------------------------------------
+
+/// Named extension filter, e.g. `Images` → `["png", "jpg"]`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+/// Options shared by open and save dialogs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FileDialogOptions {
+    pub title: Option<String>,
+    /// Directory the dialog starts in.
+    pub directory: Option<PathBuf>,
+    /// Pre-filled file name (save dialogs).
+    pub file_name: Option<String>,
+    pub filters: Vec<FileFilter>,
+}
+
+/// A pre-recorded answer to the next dialog, as written in scenarios:
+/// `{ open: /path }`, `{ save: null }` (cancelled), `{ confirm: true }`, or
+/// `message`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", try_from = "serde_json::Value")]
+pub enum DialogAnswer {
+    Open(Option<PathBuf>),
+    Save(Option<PathBuf>),
+    Confirm(bool),
+    Message,
+}
+
+impl DialogAnswer {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DialogAnswer::Open(_) => "open",
+            DialogAnswer::Save(_) => "save",
+            DialogAnswer::Confirm(_) => "confirm",
+            DialogAnswer::Message => "message",
+        }
+    }
+}
+
+// serde_yaml wants `!tag` syntax for enums; going through JSON accepts the
+// plain `{ open: ... }` map form.
+impl TryFrom<serde_json::Value> for DialogAnswer {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Repr {
+            Open(Option<PathBuf>),
+            Save(Option<PathBuf>),
+            Confirm(bool),
+            Message,
+        }
+        let repr = serde_json::from_value(value).map_err(|e| format!("dialog answer: {}", e))?;
+        Ok(match repr {
+            Repr::Open(p) => DialogAnswer::Open(p),
+            Repr::Save(p) => DialogAnswer::Save(p),
+            Repr::Confirm(b) => DialogAnswer::Confirm(b),
+            Repr::Message => DialogAnswer::Message,
+        })
+    }
+}
+
+/// Native file and message dialogs. File dialogs return `None` when the
+/// user cancels.
+pub trait DialogOps: Send + Sync {
+    fn open_file(&self, options: &FileDialogOptions) -> CapResult<Option<PathBuf>>;
+    fn save_file(&self, options: &FileDialogOptions) -> CapResult<Option<PathBuf>>;
+    fn confirm(&self, title: &str, message: &str) -> CapResult<bool>;
+    fn message(&self, title: &str, message: &str) -> CapResult<()>;
+
+    /// Queue answers for the next dialogs, replacing any still pending.
+    /// Only scripted implementations support this.
+    fn script(&self, _answers: Vec<DialogAnswer>) -> CapResult<()> {
+        Err(CapError::Unsupported(
+            "dialog answers can only be scripted headlessly".into(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Opener
+// ---------------------------------------------------------------------------
+
+/// Something the app asked the OS to open.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", content = "target", rename_all = "snake_case")]
+pub enum OpenIntent {
+    Url(String),
+    Reveal(PathBuf),
+}
+
+/// Hands URLs and paths to the OS (default browser, file manager). Policy
+/// checks happen in [`crate::opener`] before these are called.
+pub trait OpenerOps: Send + Sync {
+    fn open_url(&self, url: &str) -> CapResult<()>;
+    /// Show `path` selected in the platform file manager.
+    fn reveal_path(&self, path: &Path) -> CapResult<()>;
+
+    /// Intents recorded instead of launched, oldest first. `None` for
+    /// backends that really launch.
+    fn recorded(&self) -> Option<Vec<OpenIntent>> {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Power and session events
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEvent {
+    Sleep,
+    Wake,
+    Lock,
+    Unlock,
+    Shutdown,
+}
+
+impl SessionEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionEvent::Sleep => "sleep",
+            SessionEvent::Wake => "wake",
+            SessionEvent::Lock => "lock",
+            SessionEvent::Unlock => "unlock",
+            SessionEvent::Shutdown => "shutdown",
+        }
+    }
+}
+
+pub type SessionCallback = Box<dyn Fn(SessionEvent) + Send + Sync>;
+
+/// OS power and session signals (sleep/wake, lock/unlock, shutdown).
+pub trait SessionOps: Send + Sync {
+    /// Where the signals come from, e.g. `logind`.
+    fn source(&self) -> &str;
+    /// Deliver events to `on_event` from a background thread for the rest
+    /// of the process lifetime.
+    fn watch(&self, on_event: SessionCallback) -> CapResult<()>;
+    /// Subscribe and immediately unsubscribe, returning the signals that
+    /// were subscribed to. Used by the `session-events` probe.
+    fn check(&self) -> CapResult<Vec<String>>;
+}
+
+// ---------------------------------------------------------------------------
+// Desktop portals
+// ---------------------------------------------------------------------------
+
+/// Whether one XDG desktop portal interface answered.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PortalStatus {
+    /// Short name, e.g. `FileChooser` for `org.freedesktop.portal.FileChooser`.
+    pub name: String,
+    /// Interface `version` property; `None` if the portal did not answer.
+    pub version: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// XDG desktop portals over the session D-Bus.
+pub trait PortalOps: Send + Sync {
+    /// Query each portal's version. Fails as a whole only when the session
+    /// bus itself is unreachable.
+    fn check(&self, names: &[&str]) -> CapResult<Vec<PortalStatus>>;
+}
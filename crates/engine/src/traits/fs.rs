@@ -0,0 +1,241 @@
+//! Filesystem access: [`FilesystemOps`] and what it reads and returns.
+
+use super::{CapError, CapResult};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Metadata for a single directory entry.
+pub struct DirEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size_bytes: u64,
+}
+
+/// One entry from [`FilesystemOps::walk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    pub path: PathBuf,
+    /// 1 for the root's own entries, 2 for theirs, and so on.
+    pub depth: usize,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub size_bytes: u64,
+}
+
+/// [`FilesystemOps::walk`] on top of `list_dir`, for backends without
+/// their own.
+pub fn walk_by_listing<F: FilesystemOps + ?Sized>(
+    fs: &F,
+    dir: &Path,
+    depth: usize,
+    max_depth: Option<usize>,
+    visit: &mut dyn FnMut(&WalkEntry) -> bool,
+) -> CapResult<bool> {
+    let mut entries = fs.list_dir(dir)?;
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    for entry in entries {
+        let walked = WalkEntry {
+            path: dir.join(&entry.name),
+            depth,
+            is_dir: entry.is_dir,
+            is_symlink: false,
+            size_bytes: entry.size_bytes,
+        };
+        if !visit(&walked) {
+            return Ok(false);
+        }
+        if walked.is_dir && max_depth.is_none_or(|max| depth < max) {
+            match walk_by_listing(fs, &walked.path, depth + 1, max_depth, visit) {
+                Ok(true) => {}
+                Ok(false) => return Ok(false),
+                Err(e) => tracing::debug!("skipping {}: {}", walked.path.display(), e),
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Permissions, ownership, and extended attributes of a file, from
+/// [`FilesystemOps::stat`]. Unix-only fields are `None` elsewhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FileMetadata {
+    pub is_dir: bool,
+    /// `path` itself is a symlink; the other fields describe its target.
+    pub is_symlink: bool,
+    pub size_bytes: u64,
+    pub readonly: bool,
+    /// Permission bits, e.g. `0o755`.
+    pub mode: Option<u32>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// Unix seconds.
+    pub modified: Option<u64>,
+    /// Extended attribute values: UTF-8 text as-is, anything else as
+    /// `hex:<bytes>`.
+    pub xattrs: BTreeMap<String, String>,
+    /// Parsed `com.apple.quarantine`, set on downloads until Gatekeeper
+    /// clears it.
+    pub quarantine: Option<Quarantine>,
+}
+
+/// The `com.apple.quarantine` attribute: `flags;timestamp;agent;event-id`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Quarantine {
+    /// Hex flags; `0x40` set means the user approved opening it.
+    pub flags: u32,
+    /// Unix seconds the file was quarantined.
+    pub timestamp: Option<u64>,
+    /// The app that downloaded it (`Safari`, `curl`, ...).
+    pub agent: String,
+    pub event_id: Option<String>,
+}
+
+impl Quarantine {
+    pub const XATTR: &'static str = "com.apple.quarantine";
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut fields = value.trim_end_matches('\0').split(';');
+        let flags = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let timestamp = fields.next().and_then(|t| u64::from_str_radix(t, 16).ok());
+        let agent = fields.next().unwrap_or_default().to_string();
+        let event_id = fields
+            .next()
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        Some(Self {
+            flags,
+            timestamp,
+            agent,
+            event_id,
+        })
+    }
+
+    /// The user approved opening the file past Gatekeeper.
+    pub fn approved(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+}
+
+/// Whether a [`FileLock`] excludes everyone else or only writers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+/// An advisory lock from [`FilesystemOps::lock`]; released by
+/// [`FilesystemOps::unlock`] or when dropped.
+#[derive(Debug)]
+pub struct FileLock {
+    pub path: PathBuf,
+    pub mode: LockMode,
+    handle: Option<std::fs::File>,
+}
+
+impl FileLock {
+    /// A lock held through `handle`, if the backend has one; closing the
+    /// handle releases it.
+    pub fn new(path: PathBuf, mode: LockMode, handle: Option<std::fs::File>) -> Self {
+        Self { path, mode, handle }
+    }
+
+    pub fn handle(&self) -> Option<&std::fs::File> {
+        self.handle.as_ref()
+    }
+}
+
+pub trait FilesystemOps: Send + Sync {
+    fn read_file(&self, path: &Path) -> CapResult<Vec<u8>>;
+    /// Up to `length` bytes of `path` starting at `offset`; fewer at the
+    /// end of the file.
+    fn read_range(&self, path: &Path, offset: u64, length: u64) -> CapResult<Vec<u8>> {
+        let data = self.read_file(path)?;
+        let start = (offset as usize).min(data.len());
+        let end = start.saturating_add(length as usize).min(data.len());
+        Ok(data[start..end].to_vec())
+    }
+    fn write_file(&self, path: &Path, data: &[u8]) -> CapResult<()>;
+    /// Like `write_file`, but flushed to disk (`fsync`) before returning.
+    fn write_file_synced(&self, path: &Path, data: &[u8]) -> CapResult<()> {
+        self.write_file(path, data)
+    }
+    /// Replace `path` so that a crash or power-off leaves either the old or
+    /// the new content, never a torn file: write a synced temp file next to
+    /// it, then rename it over `path`.
+    fn write_file_atomic(&self, path: &Path, data: &[u8]) -> CapResult<()> {
+        self.write_file(path, data)
+    }
+    fn remove_file(&self, path: &Path) -> CapResult<()>;
+    /// Move a file or directory to the platform trash / recycle bin, where
+    /// the user can restore it. Backends without one must not fall back to
+    /// removing it.
+    fn trash(&self, path: &Path) -> CapResult<()> {
+        Err(CapError::Unsupported(format!(
+            "no trash for {}; delete permanently instead",
+            path.display()
+        )))
+    }
+    /// Take an advisory lock on `path` (created if missing), retrying for
+    /// up to `timeout` while another handle holds it; then
+    /// [`CapError::Timeout`]. Filesystems that cannot lock return
+    /// [`CapError::Unsupported`].
+    fn lock(&self, path: &Path, mode: LockMode, timeout: Duration) -> CapResult<FileLock> {
+        let _ = (mode, timeout);
+        Err(CapError::Unsupported(format!(
+            "no file locking for {}",
+            path.display()
+        )))
+    }
+    fn unlock(&self, lock: FileLock) -> CapResult<()> {
+        drop(lock);
+        Ok(())
+    }
+    /// Create a symbolic link at `link` pointing to `target`.
+    fn symlink(&self, target: &Path, link: &Path) -> CapResult<()> {
+        let _ = target;
+        Err(CapError::Unsupported(format!(
+            "no symlinks for {}",
+            link.display()
+        )))
+    }
+    /// Create a hard link at `link` to the existing file `original`.
+    fn hard_link(&self, original: &Path, link: &Path) -> CapResult<()> {
+        let _ = original;
+        Err(CapError::Unsupported(format!(
+            "no hard links for {}",
+            link.display()
+        )))
+    }
+    /// Where the symlink at `path` points.
+    fn read_link(&self, path: &Path) -> CapResult<PathBuf> {
+        Err(CapError::Unsupported(format!(
+            "no symlinks for {}",
+            path.display()
+        )))
+    }
+    /// Metadata of `path`, following symlinks.
+    fn stat(&self, path: &Path) -> CapResult<FileMetadata> {
+        Err(CapError::Unsupported(format!(
+            "no metadata for {}",
+            path.display()
+        )))
+    }
+    fn create_dir_all(&self, path: &Path) -> CapResult<()>;
+    fn remove_dir_all(&self, path: &Path) -> CapResult<()>;
+    fn exists(&self, path: &Path) -> bool;
+    fn temp_dir(&self) -> PathBuf;
+    fn list_dir(&self, path: &Path) -> CapResult<Vec<DirEntry>>;
+    /// Visit everything under `root` depth-first in name order, at most
+    /// `max_depth` levels down. Directory symlinks are reported but not
+    /// followed, and unreadable subdirectories are skipped. `visit` returns
+    /// `false` to stop.
+    fn walk(
+        &self,
+        root: &Path,
+        max_depth: Option<usize>,
+        visit: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> CapResult<()> {
+        walk_by_listing(self, root, 1, max_depth, visit).map(|_| ())
+    }
+}
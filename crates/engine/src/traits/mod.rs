@@ -0,0 +1,128 @@
+//! OS capability traits. The engine reaches the OS only through these, so
+//! each can be swapped per platform (see [`crate::platform`]) or in tests.
+
+mod desktop;
+mod fs;
+mod network;
+mod system;
+
+pub use desktop::*;
+pub use fs::*;
+pub use network::*;
+pub use system::*;
+
+use crate::types::ErrorCode;
+
+/// Result type for trait operations that may be unsupported.
+pub type CapResult<T> = Result<T, CapError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CapError {
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+
+    #[error("dependency missing: {0}")]
+    DependencyMissing(String),
+
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("network error: {0}")]
+    Network(String),
+
+    #[error("timeout")]
+    Timeout,
+
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl CapError {
+    /// Default mapping onto the result contract's error codes.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            CapError::Unsupported(_) => ErrorCode::Unsupported,
+            CapError::DependencyMissing(_) => ErrorCode::DependencyMissing,
+            CapError::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            CapError::Io(_) => ErrorCode::IoError,
+            CapError::Network(_) | CapError::RateLimited(_) => ErrorCode::NetworkError,
+            CapError::Timeout => ErrorCode::Timeout,
+            CapError::Other(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Environment
+// ---------------------------------------------------------------------------
+
+/// Environment variables, for scenario `env:` blocks and the daemon's
+/// `env_set` method (see [`crate::env`]).
+pub trait EnvOps: Send + Sync {
+    fn get(&self, name: &str) -> Option<String>;
+
+    /// Set `name`, or remove it when `value` is `None`.
+    fn set(&self, name: &str, value: Option<&str>);
+}
+
+// ---------------------------------------------------------------------------
+// Clock
+// ---------------------------------------------------------------------------
+
+/// Time source. Result timings are read from here, so a fixed clock makes
+/// output reproducible and time-dependent logic testable.
+pub trait Clock: Send + Sync {
+    /// Wall-clock time.
+    fn now(&self) -> std::time::SystemTime;
+
+    /// Monotonic time since an arbitrary origin; only differences matter.
+    fn monotonic(&self) -> std::time::Duration;
+
+    /// Move a simulated clock forward by `by` instead of waiting for it.
+    /// Returns `false` for real clocks, where the caller has to sleep.
+    fn skip(&self, _by: std::time::Duration) -> bool {
+        false
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Result export
+// ---------------------------------------------------------------------------
+
+/// Somewhere a finished run's artifacts are pushed to (see
+/// [`crate::export`]).
+#[async_trait::async_trait]
+pub trait ResultExporter: Send + Sync {
+    /// Where files go, for reports, e.g. `s3://bucket/prefix`.
+    fn describe(&self) -> String;
+
+    /// Store file `name` of run `run_id`; returns where it ended up.
+    async fn put(&self, run_id: &str, name: &str, bytes: &[u8]) -> CapResult<String>;
+}
+
+// ---------------------------------------------------------------------------
+// Custom scenario steps
+// ---------------------------------------------------------------------------
+
+/// A scenario step kind added outside the engine, selected by its YAML key:
+/// with a handler keyed `sql`, `- sql: { query: "select 1" }` runs it with
+/// `{"query": "select 1"}`. Register with
+/// [`crate::context::AppContext::with_step_handler`]. Built-in keys
+/// (`call`, `probe`, `prompt`, `resources`, `doctor`) always win.
+#[async_trait::async_trait]
+pub trait StepHandler: Send + Sync {
+    fn key(&self) -> &str;
+
+    /// Run one step. A `pass` or `skip` result meets its expectation.
+    async fn run(
+        &self,
+        args: &serde_json::Value,
+        ctx: &crate::context::AppContext,
+    ) -> crate::types::CommandResult;
+}
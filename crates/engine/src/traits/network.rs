@@ -0,0 +1,186 @@
+//! HTTP: [`NetworkOps`], its requests and responses, and TLS trust.
+
+use super::{CapError, CapResult};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+}
+
+#[derive(Debug, Clone)]
+pub enum HttpBody {
+    Empty,
+    Json(serde_json::Value),
+    Multipart(Vec<FormPart>),
+    /// Raw bytes, sent as-is; set `Content-Type` yourself.
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub enum FormPart {
+    Text {
+        name: String,
+        value: String,
+    },
+    File {
+        name: String,
+        file_name: String,
+        mime: String,
+        bytes: Vec<u8>,
+    },
+}
+
+/// Request for [`NetworkOps::send`]. Headers may carry credentials, so
+/// implementations must never log them (or the URL query).
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: HttpBody,
+    pub timeout_ms: u64,
+    /// Stop reading the response body after this many bytes.
+    pub max_body_bytes: Option<usize>,
+}
+
+impl HttpRequest {
+    pub const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: HttpMethod::Get,
+            url: url.into(),
+            headers: Vec::new(),
+            body: HttpBody::Empty,
+            timeout_ms: Self::DEFAULT_TIMEOUT_MS,
+            max_body_bytes: None,
+        }
+    }
+
+    pub fn post(url: impl Into<String>, body: HttpBody) -> Self {
+        Self {
+            method: HttpMethod::Post,
+            body,
+            ..Self::get(url)
+        }
+    }
+
+    pub fn put(url: impl Into<String>, body: HttpBody) -> Self {
+        Self {
+            method: HttpMethod::Put,
+            body,
+            ..Self::get(url)
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("Authorization", format!("Bearer {token}"))
+    }
+
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn max_body_bytes(mut self, max: usize) -> Self {
+        self.max_body_bytes = Some(max);
+        self
+    }
+}
+
+/// Full response from [`NetworkOps::send`]; non-2xx statuses are returned,
+/// not turned into errors.
+#[derive(Debug, Clone, Default)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn from_json(status: u16, value: &serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// First header named `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> CapResult<T> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| CapError::Network(format!("decoding response: {}", e)))
+    }
+}
+
+#[async_trait::async_trait]
+pub trait NetworkOps: Send + Sync {
+    /// Resolve a hostname to at least one IP address.
+    async fn dns_resolve(&self, host: &str) -> CapResult<Vec<String>>;
+
+    /// Perform an HTTPS GET and return (status_code, body_snippet).
+    async fn https_get(&self, url: &str, timeout_ms: u64) -> CapResult<(u16, String)>;
+
+    /// Send an arbitrary request. Transport failures are errors
+    /// ([`CapError::Timeout`] / [`CapError::Network`]); HTTP statuses are not.
+    async fn send(&self, request: HttpRequest) -> CapResult<HttpResponse>;
+
+    /// What servers are verified against, for diagnostics. Stand-ins that
+    /// speak no TLS keep the default.
+    fn trust_store(&self) -> TrustStore {
+        TrustStore::default()
+    }
+}
+
+/// Roots server certificates are checked against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustRoots {
+    /// The OS trust store.
+    System,
+    /// The OS trust store plus a configured CA bundle.
+    SystemAndBundle,
+    /// Only the configured CA bundle.
+    BundleOnly,
+    /// No TLS (test doubles).
+    #[default]
+    None,
+}
+
+/// TLS trust and client identity a [`NetworkOps`] uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TrustStore {
+    pub roots: TrustRoots,
+    pub ca_bundle: Option<PathBuf>,
+    /// Certificates read from `ca_bundle`.
+    pub ca_certs: usize,
+    /// Client certificate presented for mTLS.
+    pub client_cert: Option<PathBuf>,
+    /// Why the bundle or client certificate could not be loaded; every
+    /// request fails until it is fixed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -0,0 +1,238 @@
+//! The machine around the app: attached devices, resource usage, network
+//! interfaces, and the secret store.
+
+use super::{CapError, CapResult};
+use std::path::PathBuf;
+
+// ---------------------------------------------------------------------------
+// Attached devices
+// ---------------------------------------------------------------------------
+
+/// A USB device as the OS reports it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct UsbDevice {
+    /// Hex vendor/product ids, e.g. `0781` / `5581`.
+    pub vendor_id: String,
+    pub product_id: String,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// Bus location (sysfs name on Linux, location id on macOS).
+    pub location: String,
+}
+
+/// A removable or USB-attached disk.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RemovableMedia {
+    pub name: String,
+    /// Device node, e.g. `/dev/sdb` or `disk4`.
+    pub device: Option<String>,
+    pub vendor: Option<String>,
+    pub model: Option<String>,
+    pub size_bytes: Option<u64>,
+    /// Bus it is attached through (`usb`, `sata`, ...), when known.
+    pub transport: Option<String>,
+    /// Mounted volumes on the disk or its partitions.
+    pub mount_points: Vec<PathBuf>,
+}
+
+/// A print queue known to the print service.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Printer {
+    pub name: String,
+    /// `idle`, `printing`, `disabled`, ... as reported by the service.
+    pub state: String,
+    pub accepting_jobs: bool,
+    pub is_default: bool,
+}
+
+/// Print service reachability and its configured printers.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PrintService {
+    /// Whether the scheduler (e.g. cupsd) answered.
+    pub running: bool,
+    pub printers: Vec<Printer>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Camera,
+    Microphone,
+}
+
+/// Whether this process may capture from a device kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaPermission {
+    Granted,
+    Denied,
+    /// Blocked by policy (e.g. MDM); the user cannot grant it.
+    Restricted,
+    /// The user has not been asked yet; the first capture will prompt.
+    NotDetermined,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MediaDevice {
+    pub kind: MediaKind,
+    pub name: String,
+    /// Device node (`/dev/video0`) or OS unique id.
+    pub id: String,
+    pub permission: MediaPermission,
+}
+
+/// Cameras and microphones plus the per-kind permission state.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MediaInventory {
+    pub devices: Vec<MediaDevice>,
+    pub camera: MediaPermission,
+    pub microphone: MediaPermission,
+}
+
+impl MediaInventory {
+    pub fn permission(&self, kind: MediaKind) -> MediaPermission {
+        match kind {
+            MediaKind::Camera => self.camera,
+            MediaKind::Microphone => self.microphone,
+        }
+    }
+}
+
+/// Enumeration of attached hardware.
+pub trait DeviceOps: Send + Sync {
+    fn usb_devices(&self) -> CapResult<Vec<UsbDevice>>;
+    fn removable_media(&self) -> CapResult<Vec<RemovableMedia>>;
+    fn print_service(&self) -> CapResult<PrintService>;
+    /// Cameras and microphones, without opening a capture stream.
+    fn media_devices(&self) -> CapResult<MediaInventory>;
+}
+
+// ---------------------------------------------------------------------------
+// System resources
+// ---------------------------------------------------------------------------
+
+/// Cumulative CPU time across the machine, in clock ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CpuTicks {
+    pub busy: u64,
+    pub total: u64,
+    pub cpus: u32,
+}
+
+/// One reading of machine and process resource usage. Counters are
+/// cumulative, so rates need two samples; `None` where the platform does
+/// not expose a figure.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ResourceSample {
+    pub cpu: Option<CpuTicks>,
+    /// CPU ticks this process has used (user + system).
+    pub process_cpu_ticks: Option<u64>,
+    pub load_avg: Option<[f64; 3]>,
+    pub mem_total_bytes: Option<u64>,
+    pub mem_available_bytes: Option<u64>,
+    pub process_rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+}
+
+/// Machine and process resource counters.
+pub trait ResourceOps: Send + Sync {
+    fn sample(&self) -> CapResult<ResourceSample>;
+}
+
+// ---------------------------------------------------------------------------
+// Network interfaces
+// ---------------------------------------------------------------------------
+
+/// A network interface and its addresses (CIDR notation).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NetInterface {
+    pub name: String,
+    pub up: bool,
+    pub loopback: bool,
+    pub mac: Option<String>,
+    pub addresses: Vec<String>,
+}
+
+/// Where traffic leaves the machine by default.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DefaultRoute {
+    pub gateway: String,
+    pub interface: String,
+}
+
+/// DNS servers for one interface, or the global list when `interface` is
+/// `None`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DnsScope {
+    pub interface: Option<String>,
+    pub servers: Vec<String>,
+}
+
+/// Interfaces, default route, and resolver configuration at one moment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NetSnapshot {
+    pub interfaces: Vec<NetInterface>,
+    pub default_route: Option<DefaultRoute>,
+    pub dns: Vec<DnsScope>,
+}
+
+impl NetSnapshot {
+    /// Whether interfaces resolve through different DNS servers (split DNS,
+    /// e.g. a VPN alongside Wi-Fi).
+    pub fn dns_differs_per_interface(&self) -> bool {
+        let mut per_link = self.dns.iter().filter(|s| s.interface.is_some());
+        match per_link.next() {
+            Some(first) => per_link.any(|s| s.servers != first.servers),
+            None => false,
+        }
+    }
+}
+
+/// Local network configuration.
+pub trait NetInfoOps: Send + Sync {
+    fn snapshot(&self) -> CapResult<NetSnapshot>;
+}
+
+// ---------------------------------------------------------------------------
+// Secret store
+// ---------------------------------------------------------------------------
+
+/// What the secret store's [`SecretStoreOps::status`] could find out
+/// without touching any secret.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct SecretStoreStatus {
+    /// The program serving the store, e.g. `gnome-keyring-daemon` or
+    /// `keepassxc`, when known.
+    pub provider: Option<String>,
+    /// The default collection or keychain new secrets go to.
+    pub default_store: Option<String>,
+    /// Whether that store is locked; `None` when it cannot be told.
+    pub locked: Option<bool>,
+}
+
+/// The OS credential store (Keychain, Secret Service, Credential Locker),
+/// addressed by service and account like a generic password (see
+/// [`crate::credentials`]).
+pub trait SecretStoreOps: Send + Sync {
+    /// Short name of the backing store, e.g. `"keychain"`.
+    fn backend(&self) -> &'static str;
+
+    /// The secret, or `None` if nothing is stored under this name.
+    fn get(&self, service: &str, account: &str) -> CapResult<Option<String>>;
+
+    /// Store `secret`, replacing any previous value.
+    fn set(&self, service: &str, account: &str, secret: &str) -> CapResult<()>;
+
+    /// Remove the secret; `false` if there was none.
+    fn delete(&self, service: &str, account: &str) -> CapResult<bool>;
+
+    /// Who serves the store and whether it is locked. `DependencyMissing`
+    /// when there is no store to talk to at all.
+    fn status(&self) -> CapResult<SecretStoreStatus> {
+        Err(CapError::Unsupported(format!(
+            "{} cannot report its status",
+            self.backend()
+        )))
+    }
+}
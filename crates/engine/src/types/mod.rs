@@ -1,3 +1,14 @@
+//! Shared data types: the command result contract, scenarios, reports, and
+//! the daemon protocol.
+
+mod protocol;
+mod reports;
+mod scenario;
+
+pub use protocol::*;
+pub use reports::*;
+pub use scenario::*;
+
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -20,6 +31,23 @@ pub struct CommandResult {
     /// Arbitrary command-specific payload returned on success.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data: Option<serde_json::Value>,
+    /// Set when `data` came from the result cache: how old it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_age_ms: Option<u64>,
+    /// Set when the command called is deprecated; it still ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<Deprecation>,
+}
+
+/// Warning attached to results of deprecated commands and aliases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deprecation {
+    /// The name that was called.
+    pub command: String,
+    /// What to call instead, if anything.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -50,6 +78,8 @@ pub enum ErrorCode {
     NetworkError,
     IoError,
     Timeout,
+    /// A command outgrew its memory limit (see [`crate::limits`]).
+    ResourceExhausted,
     ExternalInterference,
     InternalError,
     UserSkipped,
@@ -77,6 +107,28 @@ pub struct EnvSummary {
     pub os: String,
     pub arch: String,
     pub headless: bool,
+    /// Packaging sandboxes and containers the process runs inside; empty
+    /// for a native install.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sandbox: Vec<SandboxKind>,
+    /// The engine build that produced the result.
+    #[serde(default)]
+    pub build: crate::build_info::BuildInfo,
+    /// Version of the app (CLI or GUI) embedding the engine.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    /// Host name, or a `host-<hash>` stand-in when redacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Hypervisor the machine runs under; absent on bare metal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vm: Option<String>,
+    /// CI system the process runs under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ci: Option<String>,
+    /// Id shared by every result from this process (or `$APP__SESSION_ID`).
+    #[serde(default)]
+    pub session_id: String,
 }
 
 impl Default for EnvSummary {
@@ -85,107 +137,28 @@ impl Default for EnvSummary {
             os: current_os().to_string(),
             arch: std::env::consts::ARCH.to_string(),
             headless: detect_headless(),
+            sandbox: crate::sandbox::kinds().to_vec(),
+            build: crate::build_info::current(),
+            app_version: crate::host::app_version(),
+            hostname: crate::host::hostname(),
+            vm: crate::host::vm().map(str::to_string),
+            ci: crate::host::ci().map(str::to_string),
+            session_id: crate::host::session_id().to_string(),
         }
     }
 }
 
-// ---------------------------------------------------------------------------
-// Doctor-specific types
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DoctorReport {
-    pub os_name: String,
-    pub os_version: String,
-    pub kernel: String,
-    pub arch: String,
-    pub user_id: Option<u32>,
-    pub effective_user_id: Option<u32>,
-    pub is_admin: bool,
-    pub headless: bool,
-    pub session_type: Option<String>,
-    pub display_server: Option<String>,
-    pub proxy_env: HashMap<String, String>,
-}
-
-// ---------------------------------------------------------------------------
-// Scenario types
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Scenario {
-    #[serde(default)]
-    pub name: Option<String>,
-    pub steps: Vec<ScenarioStep>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
-pub enum ScenarioStep {
-    Call {
-        call: String,
-        #[serde(default)]
-        args: serde_json::Value,
-        #[serde(default = "default_expect_status")]
-        expect_status: String,
-        #[serde(default = "default_timeout_ms")]
-        timeout_ms: u64,
-    },
-    Probe {
-        probe: String,
-    },
-}
-
-fn default_expect_status() -> String {
-    "pass".to_string()
-}
-
-fn default_timeout_ms() -> u64 {
-    30_000
-}
-
-// ---------------------------------------------------------------------------
-// Scenario result
-// ---------------------------------------------------------------------------
-
-/// Result of a scenario run.
-///
-/// When using `run_scenario_interactive`, `step_results` may contain fewer
-/// entries than the declared scenario steps if the user aborts mid-run or
-/// a step fails and the user chooses not to continue. `overall_status` values:
-/// - `Pass` – every step was decided (run or skip) and all executed steps
-///   met expectations. Note: individual steps may have been skipped by the
-///   user - `Pass` does not guarantee every step ran.
-/// - `Skip` – user aborted before all steps were reached, OR every step was
-///   explicitly skipped.
-/// - `Fail` – at least one step failed its expectation (run may be partial if
-///   user also aborted at the failure dialog).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ScenarioResult {
-    pub name: Option<String>,
-    pub overall_status: Status,
-    pub step_results: Vec<CommandResult>,
-}
-
-// ---------------------------------------------------------------------------
-// Serve / daemon protocol
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DaemonRequest {
-    pub id: String,
-    pub method: String,
-    #[serde(default)]
-    pub params: serde_json::Value,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DaemonResponse {
-    pub id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub result: Option<CommandResult>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<ErrorInfo>,
+/// A packaging format or container that changes what the filesystem looks
+/// like from inside the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxKind {
+    AppImage,
+    Flatpak,
+    Snap,
+    /// Docker or another OCI runtime; see [`SandboxReport::container_runtime`].
+    Docker,
+    Wsl,
 }
 
 // ---------------------------------------------------------------------------
@@ -235,6 +208,8 @@ pub fn result_ok(command: &str, target: &str, run_id: &str, total_ms: u64) -> Co
         artifacts: vec![],
         env_summary: EnvSummary::default(),
         data: None,
+        cached_age_ms: None,
+        deprecation: None,
     }
 }
 
@@ -264,6 +239,8 @@ pub fn result_err(
         artifacts: vec![],
         env_summary: EnvSummary::default(),
         data: None,
+        cached_age_ms: None,
+        deprecation: None,
     }
 }
 
@@ -292,5 +269,15 @@ pub fn result_skip(
         artifacts: vec![],
         env_summary: EnvSummary::default(),
         data: None,
+        cached_age_ms: None,
+        deprecation: None,
     }
 }
+
+/// Skip reason shared by everything offline mode turns off.
+pub const OFFLINE_REASON: &str = "offline mode: network access disabled";
+
+/// Skip result for network-touching work when the context is offline.
+pub fn result_offline(command: &str, target: &str, run_id: &str) -> CommandResult {
+    result_skip(command, target, run_id, 0, OFFLINE_REASON)
+}
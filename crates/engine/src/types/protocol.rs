@@ -0,0 +1,78 @@
+//! Engine events and the serve/daemon protocol.
+
+use super::*;
+use serde::{Deserialize, Serialize};
+
+// ---------------------------------------------------------------------------
+// Engine events
+// ---------------------------------------------------------------------------
+
+/// A progress/notification event published on the engine event bus.
+///
+/// `topic` uses `:` separators (e.g. `llm:delta`) so it can double as a
+/// Tauri event name. `run_id` ties the event to the `CommandResult` that
+/// is eventually returned for the same operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineEvent {
+    pub run_id: String,
+    pub topic: String,
+    #[serde(default)]
+    pub payload: serde_json::Value,
+}
+
+// ---------------------------------------------------------------------------
+// Serve / daemon protocol
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonRequest {
+    pub id: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+    /// `"gzip"` to receive large frames compressed (see
+    /// [`crate::framing`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonResponse {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<CommandResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorInfo>,
+}
+
+/// Intermediate frame written before the final `DaemonResponse` for
+/// methods that publish events while running (e.g. `llm_stream`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonProgress {
+    pub id: String,
+    pub progress: EngineEvent,
+}
+
+/// Event pushed to a connection that called `subscribe`, at any time and
+/// independently of its requests. `subscription` is the id `subscribe`
+/// returned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonEvent {
+    pub subscription: String,
+    pub event: EngineEvent,
+}
+
+/// Header of a binary frame: exactly `binary.length` raw bytes follow the
+/// header's newline. Sent before the response whose result refers to
+/// `binary.artifact_id` (see [`crate::blobs`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonBinary {
+    pub id: String,
+    pub binary: BinaryHeader,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryHeader {
+    pub artifact_id: String,
+    pub length: usize,
+}
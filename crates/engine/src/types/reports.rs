@@ -0,0 +1,204 @@
+//! Reports from the doctor, fleet, suite, compat, first-run, and
+//! readiness checks.
+
+use super::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ---------------------------------------------------------------------------
+// Doctor-specific types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub os_name: String,
+    pub os_version: String,
+    pub kernel: String,
+    pub arch: String,
+    pub user_id: Option<u32>,
+    pub effective_user_id: Option<u32>,
+    pub is_admin: bool,
+    pub headless: bool,
+    pub session_type: Option<String>,
+    pub display_server: Option<String>,
+    pub proxy_env: HashMap<String, String>,
+    pub sandbox: SandboxReport,
+}
+
+/// Where the app is running from, and what it can reach of the host.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SandboxReport {
+    pub kinds: Vec<SandboxKind>,
+    /// The `.AppImage` file and the directory it is mounted at.
+    pub appimage_path: Option<String>,
+    pub appimage_mount: Option<String>,
+    pub flatpak_app_id: Option<String>,
+    /// `filesystems=` permissions from `/.flatpak-info` (e.g. `home`, `xdg-download:ro`).
+    pub flatpak_filesystems: Vec<String>,
+    pub snap_name: Option<String>,
+    /// `strict`, `classic`, or `devmode`.
+    pub snap_confinement: Option<String>,
+    /// `docker`, `podman`, `kubernetes`, `containerd`, `lxc`, ...
+    pub container_runtime: Option<String>,
+    pub wsl_distro: Option<String>,
+    pub host_paths: Vec<HostPathAccess>,
+}
+
+/// Whether a host path is visible, listable, and writable from here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostPathAccess {
+    pub path: String,
+    pub exists: bool,
+    pub readable: bool,
+    pub writable: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Fleet, suite, and readiness reports
+// ---------------------------------------------------------------------------
+
+/// A daemon in a fleet targets file (see [`crate::fleet`]): a name plus
+/// exactly one of a Unix `socket` path or a `tcp` `host:port`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetTarget {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<std::path::PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<String>,
+}
+
+/// One scenario run on every target of a fleet. `Fail` if any target
+/// failed or could not be reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetReport {
+    pub scenario: Option<String>,
+    pub overall_status: Status,
+    /// In targets file order.
+    pub targets: Vec<FleetTargetResult>,
+    /// Combined status of the targets on each OS.
+    pub by_os: std::collections::BTreeMap<String, Status>,
+    /// One row per scenario step, one column per target.
+    pub matrix: Vec<FleetMatrixRow>,
+}
+
+/// One target's outcome in a [`FleetReport`]; `os`/`arch` come from the
+/// daemon's reply and are absent when it could not be reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetTargetResult {
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    pub status: Status,
+    pub timing_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ScenarioResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FleetMatrixRow {
+    pub step: usize,
+    pub label: String,
+    /// Per target, in [`FleetReport::targets`] order; `None` where the step
+    /// did not run.
+    pub statuses: Vec<Option<Status>>,
+}
+
+/// Result of a probe suite (see [`crate::suites`]).
+///
+/// - `Pass` – no critical probe failed; other probes may have.
+/// - `Fail` – at least one probe in `critical_failed` failed or errored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuiteResult {
+    pub name: String,
+    pub overall_status: Status,
+    pub critical_failed: Vec<String>,
+    pub summary: Vec<SuiteProbeStatus>,
+    pub step_results: Vec<CommandResult>,
+}
+
+/// One probe's line in a [`SuiteResult`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuiteProbeStatus {
+    pub probe: String,
+    pub status: Status,
+    pub critical: bool,
+}
+
+/// How well this host is supported (see [`crate::compat`]); ordered from
+/// best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatTier {
+    Supported,
+    Degraded,
+    Unsupported,
+}
+
+/// The tier a host got, and every rule that pulled it down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatReport {
+    pub tier: CompatTier,
+    pub reasons: Vec<CompatReason>,
+    pub os: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    /// Probe outcomes; `critical` marks the required ones.
+    pub checks: Vec<SuiteProbeStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompatReason {
+    pub tier: CompatTier,
+    /// `probe:<name>`, `min_os_version`, or `min_app_version`.
+    pub rule: String,
+    pub message: String,
+}
+
+/// Outcome of the first-run checks (see [`crate::first_run`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirstRunReport {
+    /// No required check failed.
+    pub passed: bool,
+    pub checks: Vec<FirstRunCheckResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirstRunCheckResult {
+    pub probe: String,
+    pub title: String,
+    pub required: bool,
+    pub status: Status,
+    /// The probe's error message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The check's remediation link, when it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+/// Outcome of the readiness sequence (see [`crate::readiness`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadyReport {
+    /// Every stage passed; the app may show its main window.
+    pub ready: bool,
+    /// The stages that ran, up to the first failure.
+    pub stages: Vec<ReadyStageResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadyStageResult {
+    pub stage: String,
+    pub title: String,
+    pub status: Status,
+    /// Why the stage failed, or a note on a stage that had nothing to do.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub duration_ms: u64,
+}
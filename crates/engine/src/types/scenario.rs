@@ -0,0 +1,328 @@
+//! Scenario files, plans, and results.
+
+use super::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tags every step inherits, for `run-scenario --tags/--skip-tags`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// What to do after a step fails; steps can override it.
+    #[serde(default)]
+    pub on_failure: OnFailure,
+    /// Environment variables set while the scenario runs and restored
+    /// afterwards; `null` unsets one (see [`crate::env`]).
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, serde_json::Value>,
+    pub steps: Vec<StepSpec>,
+}
+
+/// Whether a scenario carries on after a failed step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnFailure {
+    /// Run the remaining steps, gathering as much as possible.
+    #[default]
+    Continue,
+    /// Stop at the first failure, e.g. before destructive steps.
+    Stop,
+}
+
+/// A scenario step plus the options any kind of step accepts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepSpec {
+    #[serde(flatten)]
+    pub step: ScenarioStep,
+    /// Tags on top of the scenario's own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Why this step is known to fail here. Its failure then leaves the
+    /// scenario green, and a pass is reported as an unexpected pass.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xfail: Option<String>,
+    /// CSV or JSON file, relative to the scenario file, to run this step
+    /// once per row of (see [`crate::dataset`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Overrides the scenario's `on_failure` when this step fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<OnFailure>,
+    /// Outputs to copy into the run's artifacts, under `steps/<index>/`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub save_artifacts: Vec<SaveArtifact>,
+}
+
+/// An output a step keeps (see [`StepSpec::save_artifacts`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SaveArtifact {
+    /// A file the step produced, e.g. a written file or a screenshot.
+    Path(String),
+    /// A field of the result's `data`, dotted for nested fields
+    /// (`body`, `headers.etag`, `items.0`). Strings are saved as text,
+    /// anything else as JSON.
+    Data { data: String },
+}
+
+/// Bytes a step saved, for the caller to write at `path` relative to the
+/// run's artifacts directory. Also listed in the step result's `artifacts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepArtifact {
+    pub path: String,
+    pub bytes: Vec<u8>,
+}
+
+impl From<ScenarioStep> for StepSpec {
+    fn from(step: ScenarioStep) -> Self {
+        Self {
+            step,
+            tags: Vec::new(),
+            xfail: None,
+            data: None,
+            on_failure: None,
+            save_artifacts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScenarioStep {
+    Call {
+        call: String,
+        #[serde(default)]
+        args: serde_json::Value,
+        #[serde(default = "default_expect_status")]
+        expect_status: String,
+        /// The step fails with `TIMEOUT` after this, or after the command's
+        /// own `command_limits` timeout if that is shorter.
+        #[serde(default = "default_timeout_ms")]
+        timeout_ms: u64,
+        /// Answers for dialogs the command shows, in order (see
+        /// [`crate::dialogs`]).
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        dialogs: Vec<crate::traits::DialogAnswer>,
+    },
+    Probe {
+        probe: String,
+        /// Probe arguments (see `appctl probe --list`).
+        #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+        args: serde_json::Value,
+        /// Throughput budgets for a `network` probe, replacing the
+        /// configured ones (see [`crate::endpoints::ThroughputConfig`]).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        throughput: Option<crate::endpoints::ThroughputConfig>,
+    },
+    /// Render a prompt template and check the output, so prompts can be
+    /// regression-tested headlessly.
+    Prompt {
+        prompt: String,
+        #[serde(default)]
+        version: Option<u32>,
+        #[serde(default)]
+        vars: serde_json::Map<String, serde_json::Value>,
+        /// Exact expected output.
+        #[serde(default)]
+        expect: Option<String>,
+        /// Substrings the output must contain.
+        #[serde(default)]
+        expect_contains: Vec<String>,
+    },
+    /// Sample system resources and fail if a ceiling is exceeded (see
+    /// [`crate::resources`]).
+    Resources {
+        resources: crate::resources::ResourceLimits,
+    },
+    /// Pause, e.g. to model a user reading the screen.
+    Sleep { sleep_ms: u64 },
+    /// Fail if the scenario has been running longer than this, so far.
+    Deadline { deadline_ms: u64 },
+    /// Re-run a command or probe until its result matches, e.g. until a
+    /// daemon answers or a file appears.
+    WaitFor { wait_for: WaitFor },
+    /// Collect the `appctl doctor` report into the results; `false` skips.
+    Doctor { doctor: bool },
+    /// Serve a directory over HTTP on a random localhost port until the
+    /// scenario ends; later steps see `${<name>.url}` and `${<name>.port}`
+    /// (see [`crate::http_fixture`]).
+    HttpFixture {
+        serve_http_fixture: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    /// Anything else: a single key naming a
+    /// [`crate::traits::StepHandler`], with its arguments as the value.
+    Custom(serde_json::Map<String, serde_json::Value>),
+}
+
+/// A [`ScenarioStep::WaitFor`] step: run `call` or `probe` every
+/// `interval_ms` until its status is `status` and, if `field` is set, that
+/// data field exists (and equals `equals`, if given), or `timeout_ms` passes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitFor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub args: serde_json::Value,
+    #[serde(default = "default_expect_status")]
+    pub status: String,
+    /// Dotted path into the result's `data`, e.g. `entries.0.name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<serde_json::Value>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+fn default_interval_ms() -> u64 {
+    500
+}
+
+fn default_expect_status() -> String {
+    "pass".to_string()
+}
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+// ---------------------------------------------------------------------------
+// Scenario result
+// ---------------------------------------------------------------------------
+
+/// Result of a scenario run.
+///
+/// When using `run_scenario_interactive`, `step_results` may contain fewer
+/// entries than the declared scenario steps if the user aborts mid-run or
+/// a step fails and the user chooses not to continue. `overall_status` values:
+/// - `Pass` – every step was decided (run or skip) and all executed steps
+///   met expectations. Note: individual steps may have been skipped by the
+///   user - `Pass` does not guarantee every step ran.
+/// - `Skip` – user aborted before all steps were reached, OR every step was
+///   explicitly skipped.
+/// - `Fail` – at least one step failed its expectation (run may be partial if
+///   user also aborted at the failure dialog).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: Option<String>,
+    pub overall_status: Status,
+    pub step_results: Vec<CommandResult>,
+    /// Power/session events seen while the scenario ran (see
+    /// [`crate::session`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub session_events: Vec<ScenarioSessionEvent>,
+    /// Steps marked `xfail` that ran, in step order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub xfail: Vec<XfailStep>,
+    /// The step that failed and stopped the run under `on_failure: stop`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<usize>,
+    /// Outputs steps asked to keep, not yet written anywhere.
+    #[serde(skip)]
+    pub captured: Vec<StepArtifact>,
+}
+
+/// How a step marked `xfail` went.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XfailStep {
+    pub step: usize,
+    pub target: String,
+    pub reason: String,
+    pub outcome: XfailOutcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum XfailOutcome {
+    /// Failed as expected.
+    Xfail,
+    /// Passed despite the marker: the bug may be fixed, so drop the marker.
+    Xpass,
+}
+
+/// What `run-scenario --dry-run` would do (see [`crate::scenario::plan`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioPlan {
+    pub name: Option<String>,
+    /// Variables the run would set.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, serde_json::Value>,
+    /// Every step in the file, in order, whether or not it would run.
+    pub steps: Vec<PlannedStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedStep {
+    /// Position in the file.
+    pub index: usize,
+    /// Position in the run, when the step would run; results and
+    /// `steps/<index>/` artifacts use this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_index: Option<usize>,
+    pub label: String,
+    /// Own tags plus the scenario's.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Why the step would not run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+    /// The step with every default filled in.
+    pub spec: StepSpec,
+}
+
+/// Result of running every scenario in a directory (see
+/// [`crate::scenario_dir`]). `Fail` if any scenario failed or could not be
+/// loaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioDirResult {
+    pub dir: String,
+    pub overall_status: Status,
+    pub totals: ScenarioTotals,
+    /// In file name order, whatever order they finished in.
+    pub scenarios: Vec<ScenarioFileResult>,
+}
+
+/// Counts across a [`ScenarioDirResult`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScenarioTotals {
+    pub scenarios: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Files that could not be read or parsed.
+    pub errors: usize,
+    pub steps: usize,
+    /// Steps marked `xfail` that failed as expected.
+    pub xfailed: usize,
+    /// Steps marked `xfail` that passed.
+    pub xpassed: usize,
+    pub timing_ms: u64,
+}
+
+/// One file's outcome in a [`ScenarioDirResult`]; `result` is absent when
+/// the file could not be loaded, and `error` says why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioFileResult {
+    pub file: String,
+    pub status: Status,
+    pub timing_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<ScenarioResult>,
+}
+
+/// A session event and the step that was running when it arrived.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScenarioSessionEvent {
+    pub step: usize,
+    pub event: crate::traits::SessionEvent,
+}
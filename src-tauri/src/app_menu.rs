@@ -0,0 +1,111 @@
+//! Native application menu rendered from the engine's menu spec (the `menu`
+//! list in the global config; see [`engine::menu`]). Item activations are
+//! dispatched back through the engine command registry.
+
+use crate::EngineState;
+use engine::menu::{MenuKind, MenuNode};
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Runtime};
+
+fn predefined<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+    label: Option<&str>,
+) -> tauri::Result<PredefinedMenuItem<R>> {
+    match name {
+        "about" => PredefinedMenuItem::about(app, label, None),
+        "services" => PredefinedMenuItem::services(app, label),
+        "hide" => PredefinedMenuItem::hide(app, label),
+        "hide_others" => PredefinedMenuItem::hide_others(app, label),
+        "show_all" => PredefinedMenuItem::show_all(app, label),
+        "quit" => PredefinedMenuItem::quit(app, label),
+        "close_window" => PredefinedMenuItem::close_window(app, label),
+        "minimize" => PredefinedMenuItem::minimize(app, label),
+        "maximize" => PredefinedMenuItem::maximize(app, label),
+        "fullscreen" => PredefinedMenuItem::fullscreen(app, label),
+        "undo" => PredefinedMenuItem::undo(app, label),
+        "redo" => PredefinedMenuItem::redo(app, label),
+        "cut" => PredefinedMenuItem::cut(app, label),
+        "copy" => PredefinedMenuItem::copy(app, label),
+        "paste" => PredefinedMenuItem::paste(app, label),
+        "select_all" => PredefinedMenuItem::select_all(app, label),
+        _ => PredefinedMenuItem::separator(app),
+    }
+}
+
+fn append_node<R: Runtime>(
+    app: &AppHandle<R>,
+    parent: &Submenu<R>,
+    node: &MenuNode,
+) -> tauri::Result<()> {
+    // Only validated specs reach here, so `kind()` cannot fail.
+    let Ok(kind) = node.kind() else {
+        return Ok(());
+    };
+    match kind {
+        MenuKind::Submenu { label, items } => parent.append(&submenu(app, label, node, items)?),
+        MenuKind::Item { id, label, .. } => parent.append(&MenuItem::with_id(
+            app,
+            id,
+            label,
+            node.enabled,
+            node.accelerator.as_deref(),
+        )?),
+        MenuKind::Separator => parent.append(&PredefinedMenuItem::separator(app)?),
+        MenuKind::Predefined(name) => parent.append(&predefined(app, name, node.label.as_deref())?),
+    }
+}
+
+fn submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    label: &str,
+    node: &MenuNode,
+    items: &[MenuNode],
+) -> tauri::Result<Submenu<R>> {
+    let menu = Submenu::new(app, label, node.enabled)?;
+    for item in items {
+        append_node(app, &menu, item)?;
+    }
+    Ok(menu)
+}
+
+/// Render the engine's menu spec as the app menu. An empty spec keeps
+/// Tauri's default menu; an invalid one is logged and skipped.
+pub fn install<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let engine = app.state::<EngineState>();
+    let spec = &engine.ctx.menu;
+    if spec.is_empty() {
+        return Ok(());
+    }
+    let problems = engine::menu::validate(spec, &engine.registry.list());
+    if !problems.is_empty() {
+        for p in &problems {
+            tracing::warn!("menu spec: {}: {}", p.path, p.message);
+        }
+        return Ok(());
+    }
+
+    let menu = Menu::new(app)?;
+    for node in spec {
+        if let Ok(MenuKind::Submenu { label, items }) = node.kind() {
+            menu.append(&submenu(app, label, node, items)?)?;
+        }
+    }
+    app.set_menu(menu)?;
+    Ok(())
+}
+
+/// Run the engine command behind an activated menu item, off the main
+/// thread the menu event arrives on (window commands post back to it).
+pub fn on_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let app = app.clone();
+    let id = event.id().as_ref().to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let engine = app.state::<EngineState>();
+        if let Some(result) = engine::menu::dispatch(&id, &engine.ctx, &engine.registry) {
+            if let Some(err) = result.error {
+                tracing::warn!("menu item '{}' failed: {} – {}", id, err.code, err.message);
+            }
+        }
+    });
+}
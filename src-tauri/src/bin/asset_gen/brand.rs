@@ -0,0 +1,325 @@
+//! Splash screens and social previews composed from existing brand assets.
+//!
+//! Nothing here calls a provider: splash screens centre the light/dark icons
+//! written by `logo` on a flat background, and the social preview crops the
+//! banner (or lays out icon + title when there is no banner yet).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::imageops::{crop_imm, overlay, resize, FilterType};
+use image::{ImageBuffer, Rgb, Rgba, RgbaImage};
+use tracing::info;
+
+use crate::chroma::parse_color;
+use crate::font;
+
+/// Splash targets: (platform, width, height, icon size as a fraction of the
+/// shorter side). Android 12+ masks the splash icon to a circle of 2/3 the
+/// canvas, so its icon stays inside that.
+pub const SPLASH_TARGETS: &[(&str, u32, u32, f32)] = &[
+    ("desktop", 1280, 800, 0.3),
+    ("ios", 2732, 2732, 0.2),
+    ("android", 1152, 1152, 0.45),
+];
+
+/// Social targets: (path relative to the workspace, width, height).
+pub const SOCIAL_TARGETS: &[(&str, u32, u32)] = &[
+    // Open Graph / Twitter card for the docs site
+    ("docs/public/og-image.png", 1200, 630),
+    // GitHub repository social preview
+    ("media/social-preview.png", 1280, 640),
+];
+
+/// Brand inputs shared by `splash` and `social`.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ThemeArgs {
+    /// Icon for light backgrounds (defaults to docs/public/icon-light.png)
+    #[arg(long)]
+    pub icon: Option<PathBuf>,
+    /// Icon for dark backgrounds (defaults to docs/public/icon-dark.png,
+    /// falling back to the light icon)
+    #[arg(long)]
+    pub icon_dark: Option<PathBuf>,
+    /// Light theme background
+    #[arg(long, default_value = "#FFFFFF", value_parser = parse_color)]
+    pub background_light: Rgb<u8>,
+    /// Dark theme background
+    #[arg(long, default_value = "#111111", value_parser = parse_color)]
+    pub background_dark: Rgb<u8>,
+}
+
+/// Light and dark icons resolved from [`ThemeArgs`].
+pub struct Icons {
+    light: RgbaImage,
+    dark: RgbaImage,
+}
+
+impl Icons {
+    pub fn load(args: &ThemeArgs, workspace: &Path) -> Result<Self> {
+        let public = workspace.join("docs").join("public");
+        let light_path = args
+            .icon
+            .clone()
+            .unwrap_or_else(|| public.join("icon-light.png"));
+        let light = open_rgba(&light_path).context("Run `make logo` first or pass --icon")?;
+        let dark_path = args
+            .icon_dark
+            .clone()
+            .unwrap_or_else(|| public.join("icon-dark.png"));
+        let dark = if args.icon_dark.is_some() || dark_path.exists() {
+            open_rgba(&dark_path)?
+        } else {
+            light.clone()
+        };
+        Ok(Self { light, dark })
+    }
+
+    pub fn themes<'a>(&'a self, args: &ThemeArgs) -> [Theme<'a>; 2] {
+        [
+            Theme {
+                name: "light",
+                icon: &self.light,
+                background: args.background_light,
+            },
+            Theme {
+                name: "dark",
+                icon: &self.dark,
+                background: args.background_dark,
+            },
+        ]
+    }
+}
+
+pub fn open_rgba(path: &Path) -> Result<RgbaImage> {
+    Ok(image::open(path)
+        .with_context(|| format!("Failed to load {}", path.display()))?
+        .to_rgba8())
+}
+
+pub fn run_splash(args: &ThemeArgs, workspace: &Path, output_dir: Option<PathBuf>) -> Result<()> {
+    let target = output_dir.unwrap_or_else(|| workspace.join("src-tauri").join("splash"));
+    let icons = Icons::load(args, workspace)?;
+    let written = write_splash_set(&icons.themes(args), &target)?;
+    info!(
+        "Wrote {} splash screens to {}",
+        written.len(),
+        target.display()
+    );
+    Ok(())
+}
+
+pub fn run_social(
+    args: &ThemeArgs,
+    workspace: &Path,
+    title: &str,
+    banner: Option<PathBuf>,
+) -> Result<()> {
+    // An explicit --banner must load; the default is used only if present.
+    let banner = match banner {
+        Some(path) => Some(open_rgba(&path)?),
+        None => {
+            let default = workspace.join("media").join("banner.png");
+            default.exists().then(|| open_rgba(&default)).transpose()?
+        }
+    };
+    if banner.is_none() {
+        info!("No banner found, composing the preview from icon and title");
+    }
+    let icons = Icons::load(args, workspace)?;
+    let [light, _] = icons.themes(args);
+    for path in write_social_set(workspace, title, &light, banner.as_ref())? {
+        info!("Saved social preview at {}", path.display());
+    }
+    Ok(())
+}
+
+/// Icon and background for one colour scheme.
+pub struct Theme<'a> {
+    pub name: &'a str,
+    pub icon: &'a RgbaImage,
+    pub background: Rgb<u8>,
+}
+
+fn opaque(color: Rgb<u8>) -> Rgba<u8> {
+    let [r, g, b] = color.0;
+    Rgba([r, g, b, 255])
+}
+
+/// Readable text colour for a background.
+fn ink_for(background: Rgb<u8>) -> Rgba<u8> {
+    let [r, g, b] = background.0.map(u32::from);
+    if (299 * r + 587 * g + 114 * b) / 1000 > 128 {
+        Rgba([28, 28, 36, 255])
+    } else {
+        Rgba([240, 240, 244, 255])
+    }
+}
+
+/// Fit `image` inside a `size` square, keeping its aspect ratio.
+fn fit(image: &RgbaImage, size: u32) -> RgbaImage {
+    let scale = size as f32 / image.width().max(image.height()) as f32;
+    let w = ((image.width() as f32 * scale).round() as u32).max(1);
+    let h = ((image.height() as f32 * scale).round() as u32).max(1);
+    resize(image, w, h, FilterType::Lanczos3)
+}
+
+/// Scale and centre-crop `image` to exactly fill `width` x `height`.
+pub fn cover(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let scale = (width as f32 / image.width() as f32).max(height as f32 / image.height() as f32);
+    let w = ((image.width() as f32 * scale).ceil() as u32).max(width);
+    let h = ((image.height() as f32 * scale).ceil() as u32).max(height);
+    let scaled = resize(image, w, h, FilterType::Lanczos3);
+    crop_imm(&scaled, (w - width) / 2, (h - height) / 2, width, height).to_image()
+}
+
+pub fn splash(icon: &RgbaImage, width: u32, height: u32, fraction: f32, bg: Rgb<u8>) -> RgbaImage {
+    let mut canvas = ImageBuffer::from_pixel(width, height, opaque(bg));
+    let icon = fit(icon, (width.min(height) as f32 * fraction).round() as u32);
+    let x = (width - icon.width()) / 2;
+    let y = (height - icon.height()) / 2;
+    overlay(&mut canvas, &icon, i64::from(x), i64::from(y));
+    canvas
+}
+
+/// Write `<platform>-<theme>.png` for every splash target and theme.
+pub fn write_splash_set(themes: &[Theme<'_>], out_dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    let mut written = Vec::new();
+    for (platform, width, height, fraction) in SPLASH_TARGETS {
+        for theme in themes {
+            let image = splash(theme.icon, *width, *height, *fraction, theme.background);
+            let path = out_dir.join(format!("{platform}-{}.png", theme.name));
+            image
+                .save(&path)
+                .with_context(|| format!("Failed to save {}", path.display()))?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+/// Social card: the banner cropped to fit, or icon + title on `theme`.
+pub fn social_preview(
+    title: &str,
+    theme: &Theme<'_>,
+    banner: Option<&RgbaImage>,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    if let Some(banner) = banner {
+        return cover(banner, width, height);
+    }
+    let mut canvas = ImageBuffer::from_pixel(width, height, opaque(theme.background));
+    let margin = height / 8;
+    let icon = fit(theme.icon, height - 2 * margin);
+    overlay(
+        &mut canvas,
+        &icon,
+        i64::from(margin),
+        i64::from((height - icon.height()) / 2),
+    );
+
+    let text_x = margin * 2 + icon.width();
+    let text = title.to_uppercase();
+    let scale = font::fit_scale(&text, width.saturating_sub(text_x + margin), height / 5);
+    let (_, text_h) = font::text_size(&text, scale);
+    font::draw_text(
+        &mut canvas,
+        &text,
+        text_x,
+        (height - text_h) / 2,
+        scale,
+        ink_for(theme.background),
+    );
+    canvas
+}
+
+/// Write every [`SOCIAL_TARGETS`] image under `workspace`.
+pub fn write_social_set(
+    workspace: &Path,
+    title: &str,
+    theme: &Theme<'_>,
+    banner: Option<&RgbaImage>,
+) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for (relative, width, height) in SOCIAL_TARGETS {
+        let path = workspace.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        social_preview(title, theme, banner, *width, *height)
+            .save(&path)
+            .with_context(|| format!("Failed to save {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+    const BLACK: Rgb<u8> = Rgb([17, 17, 17]);
+
+    fn icon() -> RgbaImage {
+        ImageBuffer::from_pixel(64, 64, Rgba([200, 30, 30, 255]))
+    }
+
+    #[test]
+    fn splash_centres_icon_on_background() {
+        let image = splash(&icon(), 400, 200, 0.5, WHITE);
+        assert_eq!(image.dimensions(), (400, 200));
+        assert_eq!(*image.get_pixel(0, 0), opaque(WHITE));
+        assert_eq!(*image.get_pixel(200, 100), Rgba([200, 30, 30, 255]));
+        // Icon is 100px wide, so 140px from the centre is background.
+        assert_eq!(*image.get_pixel(340, 100), opaque(WHITE));
+    }
+
+    #[test]
+    fn writes_every_platform_and_theme() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("asset-gen-splash-{}", std::process::id()));
+        let icon = icon();
+        let themes = [
+            Theme {
+                name: "light",
+                icon: &icon,
+                background: WHITE,
+            },
+            Theme {
+                name: "dark",
+                icon: &icon,
+                background: BLACK,
+            },
+        ];
+        let written = write_splash_set(&themes, &dir)?;
+        assert_eq!(written.len(), SPLASH_TARGETS.len() * 2);
+        let dark = image::open(dir.join("desktop-dark.png"))?.to_rgba8();
+        assert_eq!(dark.dimensions(), (1280, 800));
+        assert_eq!(*dark.get_pixel(0, 0), opaque(BLACK));
+        std::fs::remove_dir_all(&dir).ok();
+        Ok(())
+    }
+
+    #[test]
+    fn social_preview_crops_banner_or_composes_title() {
+        let icon = icon();
+        let theme = Theme {
+            name: "light",
+            icon: &icon,
+            background: WHITE,
+        };
+        let banner = ImageBuffer::from_pixel(1920, 1080, Rgba([1, 2, 3, 255]));
+        let cropped = social_preview("Demo", &theme, Some(&banner), 1200, 630);
+        assert_eq!(cropped.dimensions(), (1200, 630));
+        assert_eq!(*cropped.get_pixel(600, 315), Rgba([1, 2, 3, 255]));
+
+        let composed = social_preview("Demo", &theme, None, 1200, 630);
+        assert_eq!(composed.dimensions(), (1200, 630));
+        let ink = ink_for(WHITE);
+        assert!(composed.pixels().any(|p| *p == ink), "title drawn");
+    }
+}
@@ -0,0 +1,145 @@
+//! Per-pipeline stage cache so a failed run can be resumed.
+//!
+//! Every provider call (description, each generated image) is written to
+//! `.cache/asset_gen/<pipeline>/` as soon as it succeeds, next to a
+//! `run.json` recording the inputs. With `--resume`, stages whose output is
+//! already cached for the same inputs are loaded instead of regenerated.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use serde_json::Value;
+use tracing::{info, warn};
+
+const MANIFEST: &str = "run.json";
+
+pub struct StageCache {
+    dir: PathBuf,
+    resume: bool,
+}
+
+impl StageCache {
+    /// Open the cache for `pipeline`. Without `resume` (or when the cached run
+    /// was for different inputs) previous stages are discarded.
+    pub fn open(workspace: &Path, pipeline: &str, inputs: &Value, resume: bool) -> Result<Self> {
+        Self::open_in(
+            workspace.join(".cache").join("asset_gen").join(pipeline),
+            inputs,
+            resume,
+        )
+    }
+
+    fn open_in(dir: PathBuf, inputs: &Value, resume: bool) -> Result<Self> {
+        let manifest = dir.join(MANIFEST);
+        let resume = resume && {
+            let cached = std::fs::read_to_string(&manifest)
+                .ok()
+                .and_then(|s| serde_json::from_str::<Value>(&s).ok());
+            match cached {
+                Some(cached) if cached == *inputs => true,
+                Some(_) => {
+                    warn!("Cached run used different inputs; starting over");
+                    false
+                }
+                None => {
+                    info!("Nothing to resume in {}; starting over", dir.display());
+                    false
+                }
+            }
+        };
+        if !resume && dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to clear {}", dir.display()))?;
+        }
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        std::fs::write(&manifest, serde_json::to_vec_pretty(inputs)?)
+            .with_context(|| format!("Failed to write {}", manifest.display()))?;
+        Ok(Self { dir, resume })
+    }
+
+    /// Cached text for `stage`, or the result of `run` (then cached).
+    pub async fn text<F, Fut>(&self, stage: &str, run: F) -> Result<String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let path = self.dir.join(format!("{stage}.txt"));
+        if self.resume && path.exists() {
+            info!("Resuming: reusing cached {stage}");
+            return std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()));
+        }
+        let text = run().await?;
+        std::fs::write(&path, &text)
+            .with_context(|| format!("Failed to cache {}", path.display()))?;
+        Ok(text)
+    }
+
+    /// Cached image for `stage`, or the result of `run` (then cached as PNG).
+    pub async fn image<F, Fut>(&self, stage: &str, run: F) -> Result<DynamicImage>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<DynamicImage>>,
+    {
+        let path = self.dir.join(format!("{stage}.png"));
+        if self.resume && path.exists() {
+            info!("Resuming: reusing cached {stage}");
+            return image::open(&path)
+                .with_context(|| format!("Failed to load {}", path.display()));
+        }
+        let image = run().await?;
+        image
+            .save(&path)
+            .with_context(|| format!("Failed to cache {}", path.display()))?;
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn resume_skips_completed_stages_only_for_same_inputs() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("asset-gen-cache-{}", std::process::id()));
+        let inputs = json!({ "title": "Demo" });
+
+        // First run: description succeeds, image stage fails.
+        let cache = StageCache::open_in(dir.clone(), &inputs, false)?;
+        cache
+            .text("description", || async { Ok("first".into()) })
+            .await?;
+        let failed = cache
+            .image("wordmark", || async { Err(anyhow!("HTTP 503")) })
+            .await;
+        assert!(failed.is_err());
+
+        // Resumed run reuses the description and only runs the failed stage.
+        let cache = StageCache::open_in(dir.clone(), &inputs, true)?;
+        let text = cache
+            .text("description", || async { panic!("should be cached") })
+            .await?;
+        assert_eq!(text, "first");
+        let image = cache
+            .image("wordmark", || async {
+                Ok(DynamicImage::ImageRgba8(image::RgbaImage::new(3, 2)))
+            })
+            .await?;
+        assert_eq!(image.width(), 3);
+
+        // Different inputs invalidate the cache.
+        let cache = StageCache::open_in(dir.clone(), &json!({ "title": "Other" }), true)?;
+        let text = cache
+            .text("description", || async { Ok("second".into()) })
+            .await?;
+        assert_eq!(text, "second");
+
+        std::fs::remove_dir_all(&dir).ok();
+        Ok(())
+    }
+}
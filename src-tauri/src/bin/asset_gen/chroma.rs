@@ -0,0 +1,249 @@
+//! Chroma keying for generated art on a solid background.
+//!
+//! Distances are measured in the CbCr plane (YCbCr without luma), so shading
+//! and highlights on the key colour still key out. Pixels within `tolerance`
+//! of the key become transparent; the next `feather` units form a soft edge
+//! where the key colour is unmixed from the pixel. Finally the key's dominant
+//! channel is clamped to the others (despill) so anti-aliased edges and
+//! reflected light don't keep a halo; `keep` colours are exempt from both.
+
+use anyhow::{anyhow, Result};
+use image::{Rgb, RgbaImage};
+
+pub const DEFAULT_TOLERANCE: f32 = 60.0;
+pub const DEFAULT_FEATHER: f32 = 40.0;
+
+/// Parse `#RRGGBB` / `RRGGBB`.
+pub fn parse_color(value: &str) -> Result<Rgb<u8>> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return Err(anyhow!("Expected a colour like #00FF00, got {value:?}"));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| anyhow!("Expected a colour like #00FF00, got {value:?}"))
+    };
+    Ok(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ChromaArgs {
+    /// Background colour to key out
+    #[arg(long, default_value = "#00FF00", value_parser = parse_color)]
+    pub key_color: Rgb<u8>,
+    /// Chroma distance from the key that is fully removed
+    #[arg(long, default_value_t = DEFAULT_TOLERANCE)]
+    pub tolerance: f32,
+    /// Width of the soft edge beyond the tolerance (0 for a hard cut)
+    #[arg(long, default_value_t = DEFAULT_FEATHER)]
+    pub feather: f32,
+    /// Artwork colour that must never be keyed or despilled (repeatable)
+    #[arg(long = "keep-color", value_parser = parse_color)]
+    pub keep_colors: Vec<Rgb<u8>>,
+}
+
+impl ChromaArgs {
+    pub fn key(&self) -> ChromaKey {
+        ChromaKey {
+            key: self.key_color,
+            tolerance: self.tolerance,
+            feather: self.feather,
+            keep: self.keep_colors.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChromaKey {
+    pub key: Rgb<u8>,
+    pub tolerance: f32,
+    pub feather: f32,
+    /// Colours protected from keying: a pixel within `tolerance` of one of
+    /// these, and closer to it than to the key, is left untouched.
+    pub keep: Vec<Rgb<u8>>,
+}
+
+impl Default for ChromaKey {
+    fn default() -> Self {
+        Self {
+            key: Rgb([0, 255, 0]),
+            tolerance: DEFAULT_TOLERANCE,
+            feather: DEFAULT_FEATHER,
+            keep: Vec::new(),
+        }
+    }
+}
+
+type Chroma = (f32, f32);
+
+/// BT.601 Cb/Cr, centred on zero.
+fn chroma([r, g, b]: [u8; 3]) -> Chroma {
+    let (r, g, b) = (f32::from(r), f32::from(g), f32::from(b));
+    (
+        -0.168_736 * r - 0.331_264 * g + 0.5 * b,
+        0.5 * r - 0.418_688 * g - 0.081_312 * b,
+    )
+}
+
+fn distance(a: Chroma, b: Chroma) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Channel that strictly dominates the key colour, if any.
+fn dominant_channel(key: [u8; 3]) -> Option<usize> {
+    let max = (0..3).max_by_key(|&i| key[i])?;
+    (0..3)
+        .filter(|&i| i != max)
+        .all(|i| key[i] < key[max])
+        .then_some(max)
+}
+
+impl ChromaKey {
+    /// Foreground coverage for a pixel at chroma distance `d` from the key.
+    fn coverage(&self, d: f32) -> f32 {
+        if self.feather <= 0.0 {
+            return if d <= self.tolerance { 0.0 } else { 1.0 };
+        }
+        ((d - self.tolerance) / self.feather).clamp(0.0, 1.0)
+    }
+
+    pub fn apply(&self, image: &mut RgbaImage) {
+        let key = chroma(self.key.0);
+        let keep: Vec<Chroma> = self.keep.iter().map(|c| chroma(c.0)).collect();
+        let dominant = dominant_channel(self.key.0);
+
+        for pixel in image.pixels_mut() {
+            let [r, g, b, a] = pixel.0;
+            let c = chroma([r, g, b]);
+            let d = distance(c, key);
+            if keep.iter().any(|k| {
+                let near = distance(c, *k);
+                near <= self.tolerance && near < d
+            }) {
+                continue;
+            }
+            let coverage = self.coverage(d);
+            if coverage <= 0.0 {
+                pixel.0 = [0, 0, 0, 0];
+                continue;
+            }
+
+            let mut rgb = [f32::from(r), f32::from(g), f32::from(b)];
+            if coverage < 1.0 {
+                // Edge pixel = coverage * fg + (1 - coverage) * key; recover fg.
+                for (channel, k) in rgb.iter_mut().zip(self.key.0) {
+                    *channel = (*channel - (1.0 - coverage) * f32::from(k)) / coverage;
+                }
+            }
+            if let Some(dom) = dominant {
+                let others = (0..3)
+                    .filter(|&i| i != dom)
+                    .map(|i| rgb[i])
+                    .fold(f32::MIN, f32::max);
+                rgb[dom] = rgb[dom].min(others);
+            }
+
+            let [r, g, b] = rgb.map(|v| v.round().clamp(0.0, 255.0) as u8);
+            pixel.0 = [r, g, b, (f32::from(a) * coverage).round() as u8];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::imageops::{resize, FilterType};
+    use image::{ImageBuffer, Rgba};
+    use std::path::PathBuf;
+
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const INK: Rgba<u8> = Rgba([28, 28, 36, 255]);
+
+    /// Ink disc on green, drawn at 4x and downsampled for anti-aliased edges.
+    fn antialiased_disc(size: u32) -> RgbaImage {
+        let big = size * 4;
+        let radius = big as f32 / 3.0;
+        let centre = big as f32 / 2.0;
+        let canvas = ImageBuffer::from_fn(big, big, |x, y| {
+            let (dx, dy) = (x as f32 - centre, y as f32 - centre);
+            if dx.hypot(dy) <= radius {
+                INK
+            } else {
+                GREEN
+            }
+        });
+        resize(&canvas, size, size, FilterType::Triangle)
+    }
+
+    fn golden_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/bin/asset_gen/testdata")
+            .join(name)
+    }
+
+    #[test]
+    fn parses_hex_colours() {
+        assert_eq!(parse_color("#00FF00").unwrap(), Rgb([0, 255, 0]));
+        assert_eq!(parse_color("1a2B3c").unwrap(), Rgb([0x1a, 0x2b, 0x3c]));
+        assert!(parse_color("#0F0").is_err());
+        assert!(parse_color("#GG0000").is_err());
+    }
+
+    #[test]
+    fn keys_background_and_keeps_foreground() {
+        let mut image = ImageBuffer::from_fn(3, 1, |x, _| match x {
+            0 => GREEN,
+            1 => Rgba([40, 200, 30, 255]), // shaded green
+            _ => INK,
+        });
+        ChromaKey::default().apply(&mut image);
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        assert_eq!(image.get_pixel(1, 0)[3], 0);
+        assert_eq!(*image.get_pixel(2, 0), INK);
+    }
+
+    #[test]
+    fn keep_colors_are_protected() {
+        let leaf = Rgba([20, 230, 40, 255]);
+        let mut image = ImageBuffer::from_fn(2, 1, |x, _| if x == 0 { leaf } else { GREEN });
+        let key = ChromaKey {
+            keep: vec![Rgb([20, 230, 40])],
+            ..ChromaKey::default()
+        };
+        key.apply(&mut image);
+        assert_eq!(*image.get_pixel(0, 0), leaf);
+        assert_eq!(image.get_pixel(1, 0)[3], 0, "background still keyed");
+    }
+
+    #[test]
+    fn antialiased_edges_have_no_green_halo() {
+        let mut image = antialiased_disc(32);
+        ChromaKey::default().apply(&mut image);
+        let edge_pixels = image.pixels().filter(|p| p[3] > 0 && p[3] < 255).count();
+        assert!(edge_pixels > 0, "expected a feathered edge");
+        for p in image.pixels().filter(|p| p[3] > 0) {
+            assert!(p[1] <= p[0].max(p[2]), "green spill in {p:?}");
+        }
+    }
+
+    /// Compares against a checked-in PNG; run with `UPDATE_GOLDEN=1` to
+    /// regenerate after an intentional change.
+    #[test]
+    fn matches_golden_image() {
+        let mut image = antialiased_disc(32);
+        ChromaKey::default().apply(&mut image);
+
+        let path = golden_path("chroma_disc.png");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            image.save(&path).unwrap();
+        }
+        let golden = image::open(&path)
+            .unwrap_or_else(|e| panic!("missing golden {}: {e}", path.display()))
+            .to_rgba8();
+        assert_eq!(image.dimensions(), golden.dimensions());
+        assert!(
+            image.as_raw() == golden.as_raw(),
+            "output differs from golden"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Gemini `generateContent` client used for descriptions and images.
+
+use crate::provider::{description_prompt, encode_png, AssetKind, ImageProvider, ImageRequest};
+use crate::retry::{send_with_backoff, Backoff};
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use engine::traits::{HttpBody, HttpRequest, NetworkOps};
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri_app_lib::config;
+
+const API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+const IMAGE_MODEL: &str = "gemini-3-pro-image-preview";
+/// Image generation routinely takes over a minute.
+const REQUEST_TIMEOUT_MS: u64 = 180_000;
+
+pub struct GeminiClient {
+    network: Arc<dyn NetworkOps>,
+    api_key: String,
+    text_model: String,
+    backoff: Backoff,
+}
+
+impl GeminiClient {
+    pub fn new(network: Arc<dyn NetworkOps>) -> Result<Self> {
+        let cfg = config::get_config();
+        let api_key = cfg
+            .gemini_api_key()
+            .ok_or_else(|| anyhow!("Missing APP__GEMINI_API_KEY"))?
+            .to_string();
+        // Strip provider prefix (e.g. "gemini/gemini-3-flash-preview" -> "gemini-3-flash-preview")
+        let text_model = cfg
+            .model_name
+            .rsplit_once('/')
+            .map(|(_, name): (&str, &str)| name.to_string())
+            .unwrap_or_else(|| cfg.model_name.clone());
+        Ok(Self {
+            network,
+            api_key,
+            text_model,
+            backoff: Backoff::from_config(),
+        })
+    }
+
+    async fn generate_text(&self, model: &str, prompt: &str) -> Result<String> {
+        let request = GenerateContentRequest::new_text(prompt);
+        let response = self.send_request(model, &request).await?;
+        extract_text(&response).ok_or_else(|| anyhow!("No text returned from Gemini"))
+    }
+
+    async fn generate_image(&self, model: &str, prompt: &str) -> Result<DynamicImage> {
+        let request = GenerateContentRequest::new_image(prompt);
+        let response = self.send_request(model, &request).await?;
+        extract_first_image(&response).ok_or_else(|| anyhow!("No image returned from Gemini"))
+    }
+
+    async fn generate_image_from_reference(
+        &self,
+        model: &str,
+        prompt: &str,
+        reference: &RgbaImage,
+    ) -> Result<DynamicImage> {
+        let inline = inline_image_from_rgba(reference)?;
+        let request = GenerateContentRequest::new_image_with_ref(prompt, inline);
+        let response = self.send_request(model, &request).await?;
+        extract_first_image(&response)
+            .ok_or_else(|| anyhow!("No inline image returned from Gemini"))
+    }
+
+    async fn send_request(
+        &self,
+        model: &str,
+        payload: &GenerateContentRequest,
+    ) -> Result<GenerateContentResponse> {
+        let url = format!("{API_BASE}/models/{model}:generateContent");
+        let body = serde_json::to_value(payload).context("Failed to encode Gemini request")?;
+        let response = send_with_backoff(self.network.as_ref(), "Gemini", self.backoff, || {
+            Ok(HttpRequest::post(&url, HttpBody::Json(body.clone()))
+                .header("x-goog-api-key", &self.api_key)
+                .timeout_ms(REQUEST_TIMEOUT_MS))
+        })
+        .await?;
+        response
+            .json::<GenerateContentResponse>()
+            .context("Failed to decode Gemini response")
+    }
+}
+
+#[async_trait::async_trait]
+impl ImageProvider for GeminiClient {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    async fn describe(
+        &self,
+        kind: AssetKind,
+        title: &str,
+        suggestion: Option<&str>,
+    ) -> Result<String> {
+        let prompt = description_prompt(kind, title, suggestion);
+        self.generate_text(&self.text_model, &prompt).await
+    }
+
+    async fn generate(&self, request: &ImageRequest<'_>) -> Result<DynamicImage> {
+        match request.reference {
+            Some(reference) => {
+                self.generate_image_from_reference(IMAGE_MODEL, request.prompt, reference)
+                    .await
+            }
+            None => self.generate_image(IMAGE_MODEL, request.prompt).await,
+        }
+    }
+}
+
+fn inline_image_from_rgba(image: &RgbaImage) -> Result<InlineImage> {
+    Ok(InlineImage {
+        mime_type: "image/png".into(),
+        data: general_purpose::STANDARD.encode(encode_png(image)?),
+    })
+}
+
+fn extract_text(response: &GenerateContentResponse) -> Option<String> {
+    response
+        .candidates
+        .iter()
+        .flat_map(|candidate| candidate.content.parts.iter())
+        .filter_map(|part| part.text.clone())
+        .next()
+}
+
+fn extract_first_image(response: &GenerateContentResponse) -> Option<DynamicImage> {
+    for candidate in &response.candidates {
+        for part in &candidate.content.parts {
+            if let Some(data) = &part.inline_data {
+                if data.mime_type.starts_with("image/") {
+                    if let Ok(bytes) = general_purpose::STANDARD.decode(&data.data) {
+                        if let Ok(img) = image::load_from_memory(&bytes) {
+                            return Some(img);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateContentRequest {
+    contents: Vec<RequestContent>,
+    generation_config: GenerationConfig,
+}
+
+impl GenerateContentRequest {
+    fn new_text(prompt: &str) -> Self {
+        Self {
+            contents: vec![RequestContent {
+                parts: vec![RequestPart::Text {
+                    text: prompt.into(),
+                }],
+            }],
+            generation_config: GenerationConfig {
+                response_modalities: vec!["TEXT".into()],
+            },
+        }
+    }
+
+    fn new_image(prompt: &str) -> Self {
+        Self {
+            contents: vec![RequestContent {
+                parts: vec![RequestPart::Text {
+                    text: prompt.into(),
+                }],
+            }],
+            generation_config: GenerationConfig {
+                response_modalities: vec!["IMAGE".into(), "TEXT".into()],
+            },
+        }
+    }
+
+    fn new_image_with_ref(prompt: &str, inline: InlineImage) -> Self {
+        Self {
+            contents: vec![RequestContent {
+                parts: vec![
+                    RequestPart::Text {
+                        text: prompt.into(),
+                    },
+                    RequestPart::InlineData {
+                        inline_data: inline,
+                    },
+                ],
+            }],
+            generation_config: GenerationConfig {
+                response_modalities: vec!["IMAGE".into(), "TEXT".into()],
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RequestContent {
+    parts: Vec<RequestPart>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RequestPart {
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: InlineImage,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    response_modalities: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineImage {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct GenerateContentResponse {
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Deserialize)]
+struct Candidate {
+    content: Content,
+}
+
+#[derive(Deserialize)]
+struct Content {
+    parts: Vec<ContentPart>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContentPart {
+    text: Option<String>,
+    inline_data: Option<InlineData>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::platform::MockNetwork;
+    use engine::traits::HttpResponse;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn generates_image_through_mock_network_after_rate_limit() {
+        let png = encode_png(&RgbaImage::new(2, 2)).unwrap();
+        let reply = json!({ "candidates": [{ "content": { "parts": [{
+            "inlineData": { "mimeType": "image/png", "data": general_purpose::STANDARD.encode(png) }
+        }] } }] });
+        let network = Arc::new(
+            MockNetwork::new()
+                .respond(API_BASE, HttpResponse::from_json(429, &json!({})))
+                .respond(API_BASE, HttpResponse::from_json(200, &reply)),
+        );
+        let client = GeminiClient {
+            network: network.clone(),
+            api_key: "test-key".into(),
+            text_model: "gemini-test".into(),
+            backoff: Backoff {
+                max_attempts: 2,
+                min_wait: Duration::ZERO,
+                max_wait: Duration::ZERO,
+            },
+        };
+
+        let request = ImageRequest {
+            kind: AssetKind::Icon,
+            title: "Demo",
+            prompt: "an icon",
+            reference: None,
+        };
+        let image = client.generate(&request).await.unwrap();
+        assert_eq!(image.width(), 2);
+
+        let sent = network.requests();
+        assert_eq!(sent.len(), 2);
+        assert!(sent[1].url.contains(IMAGE_MODEL));
+        assert!(sent[1]
+            .headers
+            .contains(&("x-goog-api-key".into(), "test-key".into())));
+    }
+}
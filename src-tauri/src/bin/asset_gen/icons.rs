@@ -0,0 +1,252 @@
+//! Full Tauri icon set from one square image – the same files `tauri icon`
+//! writes (desktop PNGs, Windows Store logos, ICO, ICNS, Android mipmaps,
+//! iOS AppIcons), without needing the Tauri CLI.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use image::codecs::ico::{IcoEncoder, IcoFrame};
+use image::imageops::{overlay, resize, FilterType};
+use image::{ColorType, ImageBuffer, Rgba, RgbaImage};
+
+use crate::provider::encode_png;
+
+/// Smallest source that covers every output without upscaling.
+pub const RECOMMENDED_SOURCE_SIZE: u32 = 1024;
+
+const PNG_ICONS: &[(&str, u32)] = &[
+    ("32x32.png", 32),
+    ("64x64.png", 64),
+    ("128x128.png", 128),
+    ("128x128@2x.png", 256),
+    ("icon.png", 512),
+    // Windows Store
+    ("Square30x30Logo.png", 30),
+    ("Square44x44Logo.png", 44),
+    ("Square71x71Logo.png", 71),
+    ("Square89x89Logo.png", 89),
+    ("Square107x107Logo.png", 107),
+    ("Square142x142Logo.png", 142),
+    ("Square150x150Logo.png", 150),
+    ("Square284x284Logo.png", 284),
+    ("Square310x310Logo.png", 310),
+    ("StoreLogo.png", 50),
+    // iOS
+    ("ios/AppIcon-20x20@1x.png", 20),
+    ("ios/AppIcon-20x20@2x.png", 40),
+    ("ios/AppIcon-20x20@2x-1.png", 40),
+    ("ios/AppIcon-20x20@3x.png", 60),
+    ("ios/AppIcon-29x29@1x.png", 29),
+    ("ios/AppIcon-29x29@2x.png", 58),
+    ("ios/AppIcon-29x29@2x-1.png", 58),
+    ("ios/AppIcon-29x29@3x.png", 87),
+    ("ios/AppIcon-40x40@1x.png", 40),
+    ("ios/AppIcon-40x40@2x.png", 80),
+    ("ios/AppIcon-40x40@2x-1.png", 80),
+    ("ios/AppIcon-40x40@3x.png", 120),
+    ("ios/AppIcon-60x60@2x.png", 120),
+    ("ios/AppIcon-60x60@3x.png", 180),
+    ("ios/AppIcon-76x76@1x.png", 76),
+    ("ios/AppIcon-76x76@2x.png", 152),
+    ("ios/AppIcon-83.5x83.5@2x.png", 167),
+    ("ios/AppIcon-512@2x.png", 1024),
+];
+
+/// Android density buckets: (dir, launcher size, adaptive foreground size).
+const ANDROID_MIPMAPS: &[(&str, u32, u32)] = &[
+    ("mipmap-mdpi", 48, 108),
+    ("mipmap-hdpi", 72, 162),
+    ("mipmap-xhdpi", 96, 216),
+    ("mipmap-xxhdpi", 144, 324),
+    ("mipmap-xxxhdpi", 192, 432),
+];
+
+const ICO_SIZES: [u32; 6] = [16, 24, 32, 48, 64, 256];
+
+/// PNG-payload ICNS entries understood by macOS 10.7+.
+const ICNS_ENTRIES: [(&[u8; 4], u32); 10] = [
+    (b"icp4", 16),
+    (b"icp5", 32),
+    (b"ic11", 32),
+    (b"icp6", 64),
+    (b"ic12", 64),
+    (b"ic07", 128),
+    (b"ic08", 256),
+    (b"ic13", 256),
+    (b"ic09", 512),
+    (b"ic10", 1024),
+];
+
+/// Resizes the source once per distinct size.
+struct Sizes<'a> {
+    source: &'a RgbaImage,
+    cache: HashMap<u32, RgbaImage>,
+}
+
+impl<'a> Sizes<'a> {
+    fn new(source: &'a RgbaImage) -> Self {
+        Self {
+            source,
+            cache: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, size: u32) -> &RgbaImage {
+        let source = self.source;
+        self.cache
+            .entry(size)
+            .or_insert_with(|| resize(source, size, size, FilterType::Lanczos3))
+    }
+}
+
+/// Write every icon into `out_dir` and return the paths written.
+pub fn write_icon_set(source: &RgbaImage, out_dir: &Path) -> Result<Vec<PathBuf>> {
+    if source.width() != source.height() {
+        bail!(
+            "Icon source must be square, got {}x{}",
+            source.width(),
+            source.height()
+        );
+    }
+    let mut sizes = Sizes::new(source);
+    let mut written = Vec::new();
+
+    for (name, size) in PNG_ICONS {
+        let path = out_dir.join(name);
+        save(sizes.get(*size), &path)?;
+        written.push(path);
+    }
+
+    for (dir, launcher, foreground) in ANDROID_MIPMAPS {
+        let dir = out_dir.join("android").join(dir);
+        let icon = sizes.get(*launcher).clone();
+        let outputs = [
+            ("ic_launcher.png", icon.clone()),
+            ("ic_launcher_round.png", circle_mask(&icon)),
+            (
+                "ic_launcher_foreground.png",
+                adaptive_foreground(&mut sizes, *foreground),
+            ),
+        ];
+        for (name, image) in outputs {
+            let path = dir.join(name);
+            save(&image, &path)?;
+            written.push(path);
+        }
+    }
+
+    let ico_path = out_dir.join("icon.ico");
+    write_ico(&mut sizes, &ico_path)?;
+    written.push(ico_path);
+
+    let icns_path = out_dir.join("icon.icns");
+    std::fs::write(&icns_path, build_icns(&mut sizes)?)
+        .with_context(|| format!("Failed to write {}", icns_path.display()))?;
+    written.push(icns_path);
+
+    Ok(written)
+}
+
+fn save(image: &RgbaImage, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    image
+        .save(path)
+        .with_context(|| format!("Failed to save PNG at {}", path.display()))
+}
+
+/// Clear everything outside the inscribed circle.
+fn circle_mask(image: &RgbaImage) -> RgbaImage {
+    let mut out = image.clone();
+    let radius = image.width() as f32 / 2.0;
+    for (x, y, pixel) in out.enumerate_pixels_mut() {
+        let dx = x as f32 + 0.5 - radius;
+        let dy = y as f32 + 0.5 - radius;
+        if dx * dx + dy * dy > radius * radius {
+            pixel.0[3] = 0;
+        }
+    }
+    out
+}
+
+/// Android adaptive icons crop to the inner 66/108 of the layer, so the
+/// artwork is scaled into that safe zone on a transparent canvas.
+fn adaptive_foreground(sizes: &mut Sizes<'_>, size: u32) -> RgbaImage {
+    let inner = size * 66 / 108;
+    let mut canvas = ImageBuffer::from_pixel(size, size, Rgba([0, 0, 0, 0]));
+    let offset = i64::from((size - inner) / 2);
+    overlay(&mut canvas, sizes.get(inner), offset, offset);
+    canvas
+}
+
+fn write_ico(sizes: &mut Sizes<'_>, path: &Path) -> Result<()> {
+    let frames = ICO_SIZES
+        .iter()
+        .map(|&size| {
+            let image = sizes.get(size);
+            IcoFrame::as_png(image.as_raw(), size, size, ColorType::Rgba8.into())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to encode ICO frames")?;
+    let file = File::create(path)
+        .with_context(|| format!("Failed to open ICO file at {}", path.display()))?;
+    IcoEncoder::new(file)
+        .encode_images(&frames)
+        .with_context(|| format!("Failed to write ICO at {}", path.display()))
+}
+
+/// ICNS container: `icns` + total length, then (type, length, PNG) entries.
+fn build_icns(sizes: &mut Sizes<'_>) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    for (kind, size) in ICNS_ENTRIES {
+        let png = encode_png(sizes.get(size))?;
+        body.extend_from_slice(kind);
+        body.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+        body.extend_from_slice(&png);
+    }
+    let mut out = Vec::with_capacity(body.len() + 8);
+    out.extend_from_slice(b"icns");
+    out.extend_from_slice(&(body.len() as u32 + 8).to_be_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_complete_icon_set() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("asset-gen-icons-{}", std::process::id()));
+        let source = ImageBuffer::from_pixel(64, 64, Rgba([10, 20, 200, 255]));
+        let written = write_icon_set(&source, &dir)?;
+
+        assert_eq!(
+            written.len(),
+            PNG_ICONS.len() + ANDROID_MIPMAPS.len() * 3 + 2
+        );
+        assert_eq!(image::open(dir.join("128x128@2x.png"))?.width(), 256);
+        let round = image::open(dir.join("android/mipmap-mdpi/ic_launcher_round.png"))?;
+        assert_eq!(round.to_rgba8().get_pixel(0, 0)[3], 0);
+
+        let icns = std::fs::read(dir.join("icon.icns"))?;
+        assert_eq!(&icns[..4], b"icns");
+        assert_eq!(
+            u32::from_be_bytes(icns[4..8].try_into()?) as usize,
+            icns.len()
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_non_square_source() {
+        let source = ImageBuffer::from_pixel(10, 20, Rgba([0, 0, 0, 255]));
+        assert!(write_icon_set(&source, Path::new("/nonexistent")).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+//! Local provider – uses pre-made images instead of generating them.
+//!
+//! The directory (`asset_gen.local_dir`, relative to the repo root) holds
+//! `wordmark.png`, `icon.png` and `banner.png`; the pipelines then derive
+//! every other size and format from them.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use image::DynamicImage;
+use tauri_app_lib::config;
+
+use crate::provider::{AssetKind, ImageProvider, ImageRequest};
+
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+pub struct LocalProvider {
+    dir: PathBuf,
+}
+
+impl LocalProvider {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn from_config() -> Result<Self> {
+        let configured = Path::new(&config::get_config().asset_gen.local_dir);
+        let dir = if configured.is_absolute() {
+            configured.to_path_buf()
+        } else {
+            crate::workspace_root()?.join(configured)
+        };
+        Ok(Self::new(dir))
+    }
+
+    fn source_path(&self, kind: AssetKind) -> Result<PathBuf> {
+        EXTENSIONS
+            .iter()
+            .map(|ext| self.dir.join(format!("{}.{ext}", kind.as_str())))
+            .find(|p| p.exists())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Local provider: no {}.png in {} (expected wordmark.png, icon.png, banner.png)",
+                    kind.as_str(),
+                    self.dir.display()
+                )
+            })
+    }
+}
+
+#[async_trait::async_trait]
+impl ImageProvider for LocalProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn describe(
+        &self,
+        kind: AssetKind,
+        title: &str,
+        _suggestion: Option<&str>,
+    ) -> Result<String> {
+        Ok(format!("Pre-made {} for {title}", kind.as_str()))
+    }
+
+    async fn generate(&self, request: &ImageRequest<'_>) -> Result<DynamicImage> {
+        let path = self.source_path(request.kind)?;
+        image::open(&path).with_context(|| format!("Failed to load {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn loads_images_by_kind() {
+        let dir = std::env::temp_dir().join(format!("asset-gen-local-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        image::RgbaImage::new(4, 4)
+            .save(dir.join("icon.png"))
+            .unwrap();
+        let provider = LocalProvider::new(dir.clone());
+
+        let request = |kind| ImageRequest {
+            kind,
+            title: "Demo",
+            prompt: "",
+            reference: None,
+        };
+        let icon = provider.generate(&request(AssetKind::Icon)).await.unwrap();
+        assert_eq!(icon.width(), 4);
+        assert!(provider
+            .generate(&request(AssetKind::Banner))
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,223 @@
+mod brand;
+mod cache;
+mod chroma;
+mod font;
+mod gemini;
+mod icons;
+mod local;
+mod offline;
+mod openai;
+mod pipelines;
+mod provider;
+mod retry;
+mod vector;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand};
+use engine::platform::ReqwestNetwork;
+use serde_json::Value;
+use tauri_app_lib::logging;
+use tracing::{error, info, warn};
+
+use brand::ThemeArgs;
+use chroma::ChromaArgs;
+use provider::{ImageProvider, ProviderKind};
+
+#[derive(Parser)]
+#[command(author, version, about = "Legacy asset generator replacement", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Image provider (defaults to `asset_gen.provider` in global_config.yaml)
+    #[arg(long, global = true, value_enum)]
+    provider: Option<ProviderKind>,
+    /// Generate deterministic placeholder art locally; same as `--provider mock`
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Continue from the last successful stage of an interrupted run
+    #[arg(long, global = true)]
+    resume: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the project logo, icons, and favicon
+    Logo {
+        /// Project name used in prompts
+        #[arg(long)]
+        project_name: Option<String>,
+        /// Optional creative suggestion for the wordmark
+        #[arg(long)]
+        suggestion: Option<String>,
+        /// Where to write assets (defaults to docs/public)
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        #[command(flatten)]
+        chroma: ChromaArgs,
+        /// Also trace SVG versions of the wordmarks and icons
+        #[arg(long)]
+        svg: bool,
+    },
+    /// Generate the hero banner image
+    Banner {
+        /// Title/text that belongs on the banner
+        #[arg(long)]
+        title: Option<String>,
+        /// Optional guiding suggestion for the description
+        #[arg(long)]
+        suggestion: Option<String>,
+        /// Output directory for the banner (defaults to media/)
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Path to an icon/logo image to incorporate into the banner.
+        /// If omitted, falls back to docs/public/icon-light.png when it exists.
+        #[arg(long)]
+        icon: Option<PathBuf>,
+    },
+    /// Generate the full Tauri icon set from a square image
+    Icons {
+        /// Square source image (1024x1024 or larger recommended)
+        source: PathBuf,
+        /// Where to write icons (defaults to src-tauri/icons)
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Generate light/dark splash screens for desktop, iOS and Android
+    Splash {
+        #[command(flatten)]
+        theme: ThemeArgs,
+        /// Where to write splash screens (defaults to src-tauri/splash)
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Generate Open Graph and GitHub social preview images
+    Social {
+        /// Title for the preview when composing it without a banner
+        #[arg(long)]
+        title: Option<String>,
+        /// Banner to crop from (defaults to media/banner.png when it exists)
+        #[arg(long)]
+        banner: Option<PathBuf>,
+        #[command(flatten)]
+        theme: ThemeArgs,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Install ring as the rustls crypto provider (reqwest needs this with rustls-no-provider)
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    logging::init_logging();
+    let cli = Cli::parse();
+    let provider = || -> Result<Box<dyn ImageProvider>> {
+        let client = ProviderKind::resolve(cli.provider, cli.offline)?
+            .build(Arc::new(ReqwestNetwork::new()))?;
+        info!("Using {} image provider", client.name());
+        Ok(client)
+    };
+
+    let resume = cli.resume;
+
+    let result = match cli.command {
+        Command::Logo {
+            project_name,
+            suggestion,
+            output_dir,
+            chroma,
+            svg,
+        } => {
+            let client = provider()?;
+            pipelines::run_logo(
+                project_name,
+                suggestion,
+                output_dir,
+                &chroma.key(),
+                svg,
+                client.as_ref(),
+                resume,
+            )
+            .await
+        }
+        Command::Banner {
+            title,
+            suggestion,
+            output_dir,
+            icon,
+        } => {
+            let client = provider()?;
+            pipelines::run_banner(title, suggestion, output_dir, icon, client.as_ref(), resume)
+                .await
+        }
+        Command::Icons { source, output_dir } => return run_icons(&source, output_dir),
+        Command::Splash { theme, output_dir } => {
+            return brand::run_splash(&theme, &workspace_root()?, output_dir)
+        }
+        Command::Social {
+            title,
+            banner,
+            theme,
+        } => {
+            let workspace = workspace_root()?;
+            let title = project_title(&workspace, title).await;
+            return brand::run_social(&theme, &workspace, &title, banner);
+        }
+    };
+    if result.is_err() {
+        error!("Completed stages are cached; rerun with --resume to continue");
+    }
+    result
+}
+
+fn run_icons(source: &Path, output_dir: Option<PathBuf>) -> Result<()> {
+    let target = match output_dir {
+        Some(dir) => dir,
+        None => workspace_root()?.join("src-tauri").join("icons"),
+    };
+    let image = image::open(source)
+        .with_context(|| format!("Failed to load {}", source.display()))?
+        .to_rgba8();
+    if image.width().min(image.height()) < icons::RECOMMENDED_SOURCE_SIZE {
+        warn!(
+            "Source is {}x{}; larger icons will be upscaled (1024x1024 recommended)",
+            image.width(),
+            image.height()
+        );
+    }
+    let written = icons::write_icon_set(&image, &target)?;
+    info!("Wrote {} icons to {}", written.len(), target.display());
+    Ok(())
+}
+
+fn workspace_root() -> Result<PathBuf> {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    Path::new(manifest_dir)
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| anyhow!("Unable to determine workspace root"))
+}
+
+/// Explicit title, else the package.json name, else the template name.
+async fn project_title(workspace: &Path, explicit: Option<String>) -> String {
+    match explicit {
+        Some(title) => title,
+        None => read_project_name(workspace)
+            .await
+            .unwrap_or_else(|_| "Tauri-Template".into()),
+    }
+}
+
+async fn read_project_name(workspace: &Path) -> Result<String> {
+    let package_json = workspace.join("package.json");
+    let data = tokio::fs::read_to_string(&package_json)
+        .await
+        .with_context(|| format!("Failed to read {}", package_json.display()))?;
+    let json: Value = serde_json::from_str(&data).context("Invalid package.json")?;
+    json.get("name")
+        .and_then(|value| value.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow!("package.json does not declare a name"))
+}
@@ -0,0 +1,196 @@
+//! Offline generator – deterministic placeholder art, no API key or network.
+//!
+//! Produces the same shapes the pipelines expect from Gemini (a lime-green
+//! keyed wordmark, a keyed square icon, a 16:9 banner) so contributors and
+//! CI can exercise every downstream step. Output depends only on the title.
+
+use anyhow::Result;
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+
+use crate::font;
+use crate::provider::{AssetKind, ImageProvider, ImageRequest};
+
+const GREENSCREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+const INK: Rgba<u8> = Rgba([28, 28, 36, 255]);
+const PAPER: Rgba<u8> = Rgba([244, 240, 230, 255]);
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+pub const WORDMARK_SIZE: (u32, u32) = (3200, 800);
+pub const ICON_SIZE: u32 = 1024;
+pub const BANNER_SIZE: (u32, u32) = (1920, 1080);
+
+pub struct OfflineGenerator;
+
+impl OfflineGenerator {
+    pub fn describe_wordmark(&self, title: &str, suggestion: Option<&str>) -> String {
+        with_suggestion(
+            format!("Offline placeholder wordmark for {title}"),
+            suggestion,
+        )
+    }
+
+    pub fn describe_banner(&self, title: &str, suggestion: Option<&str>) -> String {
+        with_suggestion(
+            format!("Offline placeholder banner for {title}"),
+            suggestion,
+        )
+    }
+
+    /// 4:1 wordmark on lime green: badge on the left, title on the right.
+    pub fn wordmark(&self, title: &str) -> DynamicImage {
+        let (w, h) = WORDMARK_SIZE;
+        let mut canvas = ImageBuffer::from_pixel(w, h, GREENSCREEN);
+        let badge = h * 3 / 4;
+        let margin = (h - badge) / 2;
+        draw_badge(&mut canvas, title, margin, margin, badge);
+
+        let text_x = margin * 2 + badge;
+        let text = title.to_uppercase();
+        let scale = font::fit_scale(&text, w - text_x - margin, badge / 2);
+        let (_, text_h) = font::text_size(&text, scale);
+        font::draw_text(&mut canvas, &text, text_x, (h - text_h) / 2, scale, INK);
+        DynamicImage::ImageRgba8(canvas)
+    }
+
+    /// Square icon on lime green – the badge from [`Self::wordmark`] alone.
+    pub fn icon(&self, title: &str) -> DynamicImage {
+        let mut canvas = ImageBuffer::from_pixel(ICON_SIZE, ICON_SIZE, GREENSCREEN);
+        let badge = ICON_SIZE * 3 / 4;
+        let offset = (ICON_SIZE - badge) / 2;
+        draw_badge(&mut canvas, title, offset, offset, badge);
+        DynamicImage::ImageRgba8(canvas)
+    }
+
+    /// 16:9 banner: title across the top, the icon (or badge) centred below.
+    pub fn banner(&self, title: &str, icon: Option<&RgbaImage>) -> DynamicImage {
+        let (w, h) = BANNER_SIZE;
+        let mut canvas = ImageBuffer::from_pixel(w, h, PAPER);
+
+        let text = title.to_uppercase();
+        let scale = font::fit_scale(&text, w * 4 / 5, h / 8);
+        let (text_w, _) = font::text_size(&text, scale);
+        font::draw_text(&mut canvas, &text, (w - text_w) / 2, h / 10, scale, INK);
+
+        let size = h / 2;
+        let (x, y) = ((w - size) / 2, h * 3 / 10);
+        match icon {
+            Some(icon) => {
+                let scaled = image::imageops::resize(
+                    icon,
+                    size,
+                    size,
+                    image::imageops::FilterType::Triangle,
+                );
+                image::imageops::overlay(&mut canvas, &scaled, x as i64, y as i64);
+            }
+            None => draw_badge(&mut canvas, title, x, y, size),
+        }
+        DynamicImage::ImageRgba8(canvas)
+    }
+}
+
+#[async_trait::async_trait]
+impl ImageProvider for OfflineGenerator {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn describe(
+        &self,
+        kind: AssetKind,
+        title: &str,
+        suggestion: Option<&str>,
+    ) -> Result<String> {
+        Ok(match kind {
+            AssetKind::Banner => self.describe_banner(title, suggestion),
+            AssetKind::Wordmark | AssetKind::Icon => self.describe_wordmark(title, suggestion),
+        })
+    }
+
+    async fn generate(&self, request: &ImageRequest<'_>) -> Result<DynamicImage> {
+        Ok(match request.kind {
+            AssetKind::Wordmark => self.wordmark(request.title),
+            AssetKind::Icon => self.icon(request.title),
+            AssetKind::Banner => self.banner(request.title, request.reference),
+        })
+    }
+}
+
+fn with_suggestion(base: String, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(s) if !s.trim().is_empty() => format!("{base} ({})", s.trim()),
+        _ => base,
+    }
+}
+
+/// FNV-1a, so colours are stable across runs and platforms.
+fn hash(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |h, b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Dark, never green-dominant accent so chroma keying leaves it intact.
+fn accent(title: &str) -> Rgba<u8> {
+    let h = hash(title);
+    let r = 40 + (h & 0x4F) as u8;
+    let b = 80 + ((h >> 8) & 0x4F) as u8;
+    let g = 30 + ((h >> 16) & 0x1F) as u8;
+    Rgba([r, g, b, 255])
+}
+
+/// Filled circle in the title's accent colour with its initial in white.
+fn draw_badge(canvas: &mut RgbaImage, title: &str, x: u32, y: u32, size: u32) {
+    let color = accent(title);
+    let radius = size as f32 / 2.0;
+    for dy in 0..size {
+        for dx in 0..size {
+            let fx = dx as f32 + 0.5 - radius;
+            let fy = dy as f32 + 0.5 - radius;
+            if fx * fx + fy * fy <= radius * radius {
+                canvas.put_pixel(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    let initial: String = title
+        .chars()
+        .find(|c| c.is_alphanumeric())
+        .unwrap_or('?')
+        .to_string();
+    let scale = font::fit_scale(&initial, size / 2, size / 2);
+    let (glyph_w, glyph_h) = font::text_size(&initial, scale);
+    font::draw_text(
+        canvas,
+        &initial,
+        x + (size - glyph_w) / 2,
+        y + (size - glyph_h) / 2,
+        scale,
+        WHITE,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_output_is_deterministic() {
+        let a = OfflineGenerator.wordmark("Tauri-Template").to_rgba8();
+        let b = OfflineGenerator.wordmark("Tauri-Template").to_rgba8();
+        assert_eq!(a.dimensions(), WORDMARK_SIZE);
+        assert_eq!(a.as_raw(), b.as_raw());
+        assert_ne!(
+            a.as_raw(),
+            OfflineGenerator.wordmark("Other").to_rgba8().as_raw()
+        );
+    }
+
+    #[test]
+    fn offline_wordmark_is_keyable() {
+        let image = OfflineGenerator.wordmark("Demo").to_rgba8();
+        assert_eq!(*image.get_pixel(0, 0), GREENSCREEN);
+        let centre = image.get_pixel(WORDMARK_SIZE.1 / 2, WORDMARK_SIZE.1 / 2);
+        assert_ne!(*centre, GREENSCREEN);
+    }
+}
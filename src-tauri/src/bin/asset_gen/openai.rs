@@ -0,0 +1,174 @@
+//! OpenAI provider – chat completions for descriptions, the Images API for art.
+
+use crate::provider::{description_prompt, encode_png, AssetKind, ImageProvider, ImageRequest};
+use crate::retry::{send_with_backoff, Backoff};
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use engine::traits::{FormPart, HttpBody, HttpRequest, NetworkOps};
+use image::DynamicImage;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tauri_app_lib::config;
+
+const API_BASE: &str = "https://api.openai.com/v1";
+const IMAGE_MODEL: &str = "gpt-image-1";
+/// Image generation routinely takes over a minute.
+const REQUEST_TIMEOUT_MS: u64 = 180_000;
+/// Used for descriptions unless `model_name` already points at OpenAI.
+const FALLBACK_TEXT_MODEL: &str = "gpt-4.1-mini";
+
+pub struct OpenAiClient {
+    network: Arc<dyn NetworkOps>,
+    api_key: String,
+    text_model: String,
+    backoff: Backoff,
+}
+
+impl OpenAiClient {
+    pub fn new(network: Arc<dyn NetworkOps>) -> Result<Self> {
+        let cfg = config::get_config();
+        let api_key = cfg
+            .openai_api_key()
+            .ok_or_else(|| anyhow!("Missing APP__OPENAI_API_KEY"))?
+            .to_string();
+        let text_model = cfg
+            .model_name
+            .strip_prefix("openai/")
+            .unwrap_or(FALLBACK_TEXT_MODEL)
+            .to_string();
+        Ok(Self {
+            network,
+            api_key,
+            text_model,
+            backoff: Backoff::from_config(),
+        })
+    }
+
+    async fn send(&self, request: HttpRequest) -> Result<Value> {
+        let response = send_with_backoff(self.network.as_ref(), "OpenAI", self.backoff, || {
+            Ok(request
+                .clone()
+                .bearer(&self.api_key)
+                .timeout_ms(REQUEST_TIMEOUT_MS))
+        })
+        .await?;
+        response
+            .json::<Value>()
+            .context("Failed to decode OpenAI response")
+    }
+}
+
+/// Closest supported `gpt-image-1` size for each asset.
+fn image_size(kind: AssetKind) -> &'static str {
+    match kind {
+        AssetKind::Icon => "1024x1024",
+        AssetKind::Wordmark | AssetKind::Banner => "1536x1024",
+    }
+}
+
+fn text_part(name: &str, value: &str) -> FormPart {
+    FormPart::Text {
+        name: name.into(),
+        value: value.into(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ImagesResponse {
+    data: Vec<ImageData>,
+}
+
+#[derive(Deserialize)]
+struct ImageData {
+    b64_json: Option<String>,
+}
+
+fn decode_images_response(value: Value) -> Result<DynamicImage> {
+    let response: ImagesResponse =
+        serde_json::from_value(value).context("Unexpected OpenAI Images response")?;
+    let encoded = response
+        .data
+        .into_iter()
+        .find_map(|d| d.b64_json)
+        .ok_or_else(|| anyhow!("No image returned from OpenAI"))?;
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .context("Invalid base64 image from OpenAI")?;
+    image::load_from_memory(&bytes).context("Failed to decode OpenAI image")
+}
+
+#[async_trait::async_trait]
+impl ImageProvider for OpenAiClient {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn describe(
+        &self,
+        kind: AssetKind,
+        title: &str,
+        suggestion: Option<&str>,
+    ) -> Result<String> {
+        let body = json!({
+            "model": self.text_model,
+            "messages": [{ "role": "user", "content": description_prompt(kind, title, suggestion) }],
+        });
+        let value = self
+            .send(HttpRequest::post(
+                format!("{API_BASE}/chat/completions"),
+                HttpBody::Json(body),
+            ))
+            .await?;
+        value["choices"][0]["message"]["content"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| anyhow!("No text returned from OpenAI"))
+    }
+
+    async fn generate(&self, request: &ImageRequest<'_>) -> Result<DynamicImage> {
+        let size = image_size(request.kind);
+        let http_request = match request.reference {
+            Some(reference) => HttpRequest::post(
+                format!("{API_BASE}/images/edits"),
+                HttpBody::Multipart(vec![
+                    text_part("model", IMAGE_MODEL),
+                    text_part("prompt", request.prompt),
+                    text_part("size", size),
+                    FormPart::File {
+                        name: "image[]".into(),
+                        file_name: "reference.png".into(),
+                        mime: "image/png".into(),
+                        bytes: encode_png(reference)?,
+                    },
+                ]),
+            ),
+            None => HttpRequest::post(
+                format!("{API_BASE}/images/generations"),
+                HttpBody::Json(json!({
+                    "model": IMAGE_MODEL,
+                    "prompt": request.prompt,
+                    "size": size,
+                    "n": 1,
+                })),
+            ),
+        };
+        let value = self.send(http_request).await?;
+        decode_images_response(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_b64_image_response() {
+        let png = encode_png(&image::RgbaImage::new(2, 3)).unwrap();
+        let value = json!({ "data": [{ "b64_json": general_purpose::STANDARD.encode(png) }] });
+        let image = decode_images_response(value).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 3));
+
+        assert!(decode_images_response(json!({ "data": [] })).is_err());
+    }
+}
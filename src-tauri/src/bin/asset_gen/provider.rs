@@ -0,0 +1,130 @@
+//! Image-generation providers behind a common trait.
+//!
+//! Pipelines describe *what* they need ([`ImageRequest`]); each provider
+//! decides how to produce it. Prompt-driven providers (Gemini, OpenAI) use
+//! the prompt and reference image, while the local and mock providers key off
+//! [`AssetKind`] and the title.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use engine::traits::NetworkOps;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, DynamicImage, ImageEncoder, RgbaImage};
+use tauri_app_lib::config;
+
+use crate::gemini::GeminiClient;
+use crate::local::LocalProvider;
+use crate::offline::OfflineGenerator;
+use crate::openai::OpenAiClient;
+
+/// Which asset a request is for; determines aspect ratio and file names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    /// 4:1 horizontal logo with icon and text on a lime-green key.
+    Wordmark,
+    /// Square icon on a lime-green key.
+    Icon,
+    /// 16:9 hero banner.
+    Banner,
+}
+
+impl AssetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetKind::Wordmark => "wordmark",
+            AssetKind::Icon => "icon",
+            AssetKind::Banner => "banner",
+        }
+    }
+}
+
+pub struct ImageRequest<'a> {
+    pub kind: AssetKind,
+    pub title: &'a str,
+    pub prompt: &'a str,
+    /// Image the result should be derived from (e.g. the wordmark when
+    /// extracting the icon, or the icon when composing the banner).
+    pub reference: Option<&'a RgbaImage>,
+}
+
+#[async_trait::async_trait]
+pub trait ImageProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Short creative description used to enrich the image prompt.
+    async fn describe(
+        &self,
+        kind: AssetKind,
+        title: &str,
+        suggestion: Option<&str>,
+    ) -> Result<String>;
+
+    async fn generate(&self, request: &ImageRequest<'_>) -> Result<DynamicImage>;
+}
+
+/// Text prompt asking a language model for a creative description.
+pub fn description_prompt(kind: AssetKind, title: &str, suggestion: Option<&str>) -> String {
+    let suggestion = suggestion.unwrap_or("");
+    match kind {
+        AssetKind::Banner => {
+            format!("Describe a Japanese-style banner featuring the text '{title}'. {suggestion}")
+        }
+        AssetKind::Wordmark | AssetKind::Icon => format!(
+            "Create a concise, creative description of a modern horizontal wordmark for '{title}'. {suggestion}"
+        ),
+    }
+}
+
+/// Encode an image as PNG bytes for upload.
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    PngEncoder::new(Cursor::new(&mut buffer))
+        .write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ColorType::Rgba8.into(),
+        )
+        .context("Failed to encode reference image")?;
+    Ok(buffer)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProviderKind {
+    /// Gemini image models (needs APP__GEMINI_API_KEY)
+    Gemini,
+    /// OpenAI Images API (needs APP__OPENAI_API_KEY)
+    Openai,
+    /// Pre-made images from a directory (wordmark.png, icon.png, banner.png)
+    Local,
+    /// Deterministic placeholders, no key or network
+    Mock,
+}
+
+impl ProviderKind {
+    /// Resolve the `--provider` flag, falling back to `asset_gen.provider`.
+    pub fn resolve(flag: Option<ProviderKind>, offline: bool) -> Result<Self> {
+        if offline {
+            return Ok(ProviderKind::Mock);
+        }
+        if let Some(kind) = flag {
+            return Ok(kind);
+        }
+        let configured = &config::get_config().asset_gen.provider;
+        ProviderKind::from_str(configured, true)
+            .map_err(|_| anyhow!("Unknown asset_gen.provider in config: {configured}"))
+    }
+
+    /// Build the provider; API-backed ones send through `network`.
+    pub fn build(self, network: Arc<dyn NetworkOps>) -> Result<Box<dyn ImageProvider>> {
+        Ok(match self {
+            ProviderKind::Gemini => Box::new(GeminiClient::new(network)?),
+            ProviderKind::Openai => Box::new(OpenAiClient::new(network)?),
+            ProviderKind::Local => Box::new(LocalProvider::from_config()?),
+            ProviderKind::Mock => Box::new(OfflineGenerator),
+        })
+    }
+}
@@ -0,0 +1,260 @@
+//! Retry with exponential backoff for provider API calls.
+//!
+//! Attempts and wait bounds come from `llm_config.retry`. Only transient
+//! failures are retried: HTTP 429/5xx (honouring `Retry-After`) and
+//! connection errors or timeouts reported by the engine's `NetworkOps`.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+use engine::traits::{CapError, HttpRequest, HttpResponse, NetworkOps};
+use tauri_app_lib::config;
+use tracing::warn;
+
+/// Non-success HTTP response from a provider API.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub provider: &'static str,
+    pub status: u16,
+    pub retry_after: Option<Duration>,
+}
+
+impl HttpStatusError {
+    /// Build from a failed response, logging its body.
+    pub fn from_response(provider: &'static str, response: &HttpResponse) -> Self {
+        let retry_after = response
+            .header("retry-after")
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        tracing::error!(
+            "{provider} returned {}: {}",
+            response.status,
+            response.text()
+        );
+        Self {
+            provider,
+            status: response.status,
+            retry_after,
+        }
+    }
+
+    fn is_transient(&self) -> bool {
+        self.status == 429 || (500..600).contains(&self.status)
+    }
+}
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} request failed with HTTP {}",
+            self.provider, self.status
+        )
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub max_attempts: u32,
+    pub min_wait: Duration,
+    pub max_wait: Duration,
+}
+
+impl Backoff {
+    pub fn from_config() -> Self {
+        let retry = &config::get_config().llm_config.retry;
+        Self {
+            max_attempts: retry.max_attempts.max(1) as u32,
+            min_wait: Duration::from_secs(retry.min_wait_seconds.max(0) as u64),
+            max_wait: Duration::from_secs(retry.max_wait_seconds.max(0) as u64),
+        }
+    }
+
+    /// Wait before retry number `attempt` (1-based): min * 2^(attempt-1), capped.
+    fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let exponential = self
+            .min_wait
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(self.max_wait);
+        retry_after.map_or(exponential, |hint| hint.min(self.max_wait))
+    }
+}
+
+/// Whether `err` is worth retrying, plus any server-provided wait hint.
+fn transient(err: &anyhow::Error) -> Option<Option<Duration>> {
+    for cause in err.chain() {
+        if let Some(status) = cause.downcast_ref::<HttpStatusError>() {
+            return status.is_transient().then_some(status.retry_after);
+        }
+        if let Some(e) = cause.downcast_ref::<CapError>() {
+            return matches!(e, CapError::Timeout | CapError::Network(_)).then_some(None);
+        }
+    }
+    None
+}
+
+/// Run `call` until it succeeds, fails permanently, or attempts run out.
+pub async fn with_backoff<T, F, Fut>(label: &str, backoff: Backoff, mut call: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < backoff.max_attempts => {
+                let Some(hint) = transient(&err) else {
+                    return Err(err);
+                };
+                let wait = backoff.delay(attempt, hint);
+                warn!(
+                    "{label} failed (attempt {attempt}/{}): {err:#}; retrying in {:?}",
+                    backoff.max_attempts, wait
+                );
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Send the request built by `build` (rebuilt per attempt), retrying
+/// transient failures; non-2xx responses become [`HttpStatusError`].
+pub async fn send_with_backoff<F>(
+    network: &dyn NetworkOps,
+    provider: &'static str,
+    backoff: Backoff,
+    build: F,
+) -> Result<HttpResponse>
+where
+    F: Fn() -> Result<HttpRequest>,
+{
+    let label = format!("{provider} request");
+    with_backoff(&label, backoff, || async {
+        let response = network.send(build()?).await?;
+        if !response.is_success() {
+            return Err(HttpStatusError::from_response(provider, &response).into());
+        }
+        Ok(response)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const FAST: Backoff = Backoff {
+        max_attempts: 3,
+        min_wait: Duration::ZERO,
+        max_wait: Duration::ZERO,
+    };
+
+    fn status(status: u16) -> anyhow::Error {
+        HttpStatusError {
+            provider: "Test",
+            status,
+            retry_after: None,
+        }
+        .into()
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_until_success() {
+        let calls = AtomicU32::new(0);
+        let result = with_backoff("test", FAST, || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(status(429)),
+                1 => Err(status(503)),
+                _ => Ok("done"),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_permanent_errors_and_exhaustion() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = with_backoff("test", FAST, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(status(400))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<()> = with_backoff("test", FAST, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(status(500))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), FAST.max_attempts);
+    }
+
+    #[tokio::test]
+    async fn sends_through_network_ops_with_retries() {
+        use engine::platform::MockNetwork;
+
+        let network = MockNetwork::new()
+            .fail("https://api.test/", "connection reset")
+            .respond(
+                "https://api.test/",
+                HttpResponse {
+                    status: 503,
+                    headers: vec![("Retry-After".into(), "0".into())],
+                    body: Vec::new(),
+                },
+            )
+            .respond(
+                "https://api.test/",
+                HttpResponse::from_json(200, &"ok".into()),
+            );
+        let build = || Ok(HttpRequest::get("https://api.test/x"));
+
+        let response = send_with_backoff(&network, "Test", FAST, build)
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(network.requests().len(), 3);
+
+        let denied = MockNetwork::new().respond(
+            "https://api.test/",
+            HttpResponse {
+                status: 401,
+                ..Default::default()
+            },
+        );
+        let err = send_with_backoff(&denied, "Test", FAST, build)
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<HttpStatusError>().unwrap().status, 401);
+        assert_eq!(denied.requests().len(), 1);
+    }
+
+    #[test]
+    fn delay_grows_and_respects_cap_and_hint() {
+        let backoff = Backoff {
+            max_attempts: 5,
+            min_wait: Duration::from_secs(1),
+            max_wait: Duration::from_secs(5),
+        };
+        assert_eq!(backoff.delay(1, None), Duration::from_secs(1));
+        assert_eq!(backoff.delay(3, None), Duration::from_secs(4));
+        assert_eq!(backoff.delay(4, None), Duration::from_secs(5));
+        assert_eq!(
+            backoff.delay(1, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Raster-to-SVG tracing (vtracer) for resolution-independent logos.
+//!
+//! Input should already be keyed: fully transparent pixels are left out of
+//! the SVG, so the result has a transparent background like the PNG.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use image::RgbaImage;
+use vtracer::{ColorImage, Config, Preset};
+
+/// Trace `image` into an SVG document.
+pub fn to_svg(image: &RgbaImage) -> Result<String> {
+    let (width, height) = image.dimensions();
+    let input = ColorImage {
+        pixels: image.as_raw().clone(),
+        width: width as usize,
+        height: height as usize,
+    };
+    let svg = vtracer::convert(input, Config::from_preset(Preset::Poster))
+        .map_err(|e| anyhow!("Vectorization failed: {e}"))?
+        .to_string();
+    // vtracer only sets width/height; a viewBox lets the SVG scale with CSS.
+    Ok(svg.replacen(
+        "<svg ",
+        &format!(r#"<svg viewBox="0 0 {width} {height}" "#),
+        1,
+    ))
+}
+
+pub fn write_svg(image: &RgbaImage, path: &Path) -> Result<()> {
+    std::fs::write(path, to_svg(image)?)
+        .with_context(|| format!("Failed to save SVG at {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgba};
+
+    #[test]
+    fn traces_shapes_and_skips_transparent_background() {
+        let image = ImageBuffer::from_fn(40, 20, |x, y| {
+            if (10..30).contains(&x) && (5..15).contains(&y) {
+                Rgba([200, 30, 30, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let svg = to_svg(&image).unwrap();
+        assert!(svg.contains(r#"viewBox="0 0 40 20""#));
+        assert!(svg.contains("<path"));
+        assert!(svg.to_uppercase().contains("#C81E1E"), "fill colour kept");
+        assert!(!svg.contains("#000000"), "transparent background traced");
+    }
+}
@@ -0,0 +1,397 @@
+//! Tauri commands – thin wrappers that delegate to the engine.
+
+use crate::global_config::{self, FrontendConfig};
+use crate::startup::reveal_main_window;
+use crate::{sidecar, EngineState};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+#[tauri::command]
+fn greet(name: &str, engine: State<'_, EngineState>) -> String {
+    // Simple greeting – delegates to engine ping to prove wiring
+    let result = engine
+        .registry
+        .execute("ping", serde_json::json!({}), &engine.ctx);
+    format!(
+        "Hello, {}! You've been greeted from Rust! (engine status: {:?})",
+        name, result.status
+    )
+}
+
+#[tauri::command]
+fn get_app_config() -> &'static FrontendConfig {
+    global_config::get_frontend_config()
+}
+
+/// Run the engine command `cmd` on a blocking thread. Handlers wait for
+/// the engine's worker pools, and window commands post back to the event
+/// loop, so a sync command (which Tauri runs on the main thread) would
+/// stall the UI or deadlock.
+async fn execute_blocking<R: Runtime>(
+    app: &AppHandle<R>,
+    cmd: &'static str,
+    args: serde_json::Value,
+) -> serde_json::Value {
+    let worker = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let engine = worker.state::<EngineState>();
+        engine.registry.execute(cmd, args, &engine.ctx)
+    })
+    .await;
+    let result = result.unwrap_or_else(|e| {
+        engine::types::result_err(
+            cmd,
+            "",
+            &app.state::<EngineState>().ctx.new_run_id(),
+            0,
+            engine::types::ErrorCode::InternalError,
+            format!("{} did not finish: {}", cmd, e),
+        )
+    });
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Geometry of a window (`{ "label"?: "main" }`), as the engine's
+/// `window_info` command; scenarios can assert on the result.
+#[tauri::command]
+async fn window_info<R: Runtime>(app: AppHandle<R>, args: serde_json::Value) -> serde_json::Value {
+    execute_blocking(&app, "window_info", args).await
+}
+
+/// Move, resize, or (un)maximize a window, as the engine's `window_set`.
+#[tauri::command]
+async fn window_set<R: Runtime>(app: AppHandle<R>, args: serde_json::Value) -> serde_json::Value {
+    execute_blocking(&app, "window_set", args).await
+}
+
+/// Read shared state, as the engine's `state_get` (see
+/// `engine::shared_state`).
+#[tauri::command]
+async fn state_get<R: Runtime>(app: AppHandle<R>, args: serde_json::Value) -> serde_json::Value {
+    execute_blocking(&app, "state_get", args).await
+}
+
+/// Change shared state, as the engine's `state_set`, with the calling
+/// window's label as `source` unless one is given. Every window then gets
+/// the `state:changed` event.
+#[tauri::command]
+async fn state_set<R: Runtime>(
+    app: AppHandle<R>,
+    window: tauri::WebviewWindow<R>,
+    mut args: serde_json::Value,
+) -> serde_json::Value {
+    if let Some(args) = args.as_object_mut() {
+        args.entry("source")
+            .or_insert_with(|| window.label().into());
+    }
+    execute_blocking(&app, "state_set", args).await
+}
+
+/// Snapshot of the shared state keys a window follows, as the engine's
+/// `state_subscribe`; apply `state:changed` events newer than its
+/// `version` afterwards.
+#[tauri::command]
+async fn state_subscribe<R: Runtime>(
+    app: AppHandle<R>,
+    args: serde_json::Value,
+) -> serde_json::Value {
+    execute_blocking(&app, "state_subscribe", args).await
+}
+
+/// Generic command invocation – call any engine command by name, within
+/// its `command_limits`. In sidecar mode the `sidecar.commands` go to the
+/// sidecar, and run here only while it is not connected; a request the
+/// sidecar received but never answered comes back as an error.
+#[tauri::command]
+async fn engine_call<R: Runtime>(
+    app: AppHandle<R>,
+    cmd: String,
+    args: serde_json::Value,
+) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    if let Some(sidecar) = engine.sidecar.as_ref().filter(|s| s.routes(&cmd, &args)) {
+        match sidecar.call(&cmd, args.clone()).await {
+            Ok(result) => return serde_json::to_value(&result).unwrap_or_default(),
+            Err(sidecar::CallError::Unavailable(e)) => {
+                tracing::debug!("sidecar unavailable, running {} in-process: {}", cmd, e)
+            }
+            Err(sidecar::CallError::Failed(e)) => {
+                let result = engine::types::result_err(
+                    &cmd,
+                    "sidecar",
+                    &engine.ctx.new_run_id(),
+                    0,
+                    engine::types::ErrorCode::IoError,
+                    format!("sidecar did not answer: {}", e),
+                );
+                return serde_json::to_value(&result).unwrap_or_default();
+            }
+        }
+    }
+    let result = engine.registry.execute_limited(&cmd, args, &engine.ctx);
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// List all available engine commands.
+#[tauri::command]
+fn engine_list_commands(engine: State<'_, EngineState>) -> Vec<String> {
+    engine
+        .registry
+        .list()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Run a probe suite (`all`, `desktop`, `headless-ci`, or one from
+/// `probe_suites:`); `null` for an unknown suite.
+#[tauri::command]
+async fn engine_probe_suite<R: Runtime>(app: AppHandle<R>, suite: String) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result = engine::suites::run_suite(&suite, &engine.ctx, &engine.probes).await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// List the registered probes with their capabilities, platforms, and
+/// arguments.
+#[tauri::command]
+fn engine_list_probes(engine: State<'_, EngineState>) -> Vec<engine::probes::ProbeInfo> {
+    engine.probes.list().into_iter().cloned().collect()
+}
+
+// Async commands take the (owned) app handle rather than a borrowed
+// `State`, which Tauri only allows for async commands returning `Result`.
+
+/// Run a capability probe (`filesystem`, `network`, `clipboard`, ... – see
+/// `engine_list_probes`), with optional probe-specific `args`.
+/// Progress arrives as `probe:started` / `probe:step` / `probe:finished`
+/// events carrying the same `run_id` as the returned result.
+#[tauri::command]
+async fn engine_probe<R: Runtime>(
+    app: AppHandle<R>,
+    target: String,
+    args: Option<serde_json::Value>,
+) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let args = args.unwrap_or_default();
+    let result = engine.probes.run(&target, args, &engine.ctx).await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Collect the same environment report as `appctl doctor`; also published
+/// as a `doctor:finished` event. The report shells out to system tools, so
+/// it runs on a blocking thread rather than the main thread.
+#[tauri::command]
+async fn engine_doctor<R: Runtime>(app: AppHandle<R>) -> serde_json::Value {
+    let ctx = app.state::<EngineState>().ctx.clone();
+    let worker = ctx.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || engine::doctor::run_doctor(&worker)).await;
+    let result = result.unwrap_or_else(|e| {
+        engine::types::result_err(
+            "doctor",
+            "env",
+            &ctx.new_run_id(),
+            0,
+            engine::types::ErrorCode::InternalError,
+            format!("doctor did not finish: {}", e),
+        )
+    });
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Backend of the "Export diagnostics" button: zip the doctor report,
+/// recent redacted logs, config fingerprint, and last run results into the
+/// Downloads folder; `data.path` is the file to show the user.
+#[tauri::command]
+async fn engine_export_diagnostics<R: Runtime>(app: AppHandle<R>) -> serde_json::Value {
+    execute_blocking(&app, "export_diagnostics", serde_json::json!({})).await
+}
+
+/// Classify this host against the compatibility rules, as `appctl
+/// compatibility`; the frontend runs it on first launch and reads
+/// `data.tier` and `data.reasons`.
+#[tauri::command]
+async fn engine_compatibility<R: Runtime>(app: AppHandle<R>) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result =
+        engine::compat::run_compatibility(&engine.ctx.compat_rules, &engine.ctx, &engine.probes)
+            .await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Run the first-run checks again (e.g. a splash window's Retry button);
+/// progress arrives as `first_run:*` events.
+#[tauri::command]
+async fn engine_first_run<R: Runtime>(app: AppHandle<R>) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result = engine::first_run::run_checks(&engine.ctx, &engine.probes).await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Run the readiness sequence again (e.g. the splash window's Retry
+/// button); progress arrives as `ready:*` events, and the main window is
+/// shown once it passes.
+#[tauri::command]
+async fn engine_ready_check<R: Runtime>(app: AppHandle<R>) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result =
+        engine::readiness::run_ready_check(&engine.ctx, &engine.registry, &engine.probes).await;
+    if result.status == engine::types::Status::Pass {
+        reveal_main_window(&app);
+    }
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// The last first-run report, for a window that opened after the startup
+/// sequence finished; `null` before the first run.
+#[tauri::command]
+fn engine_first_run_report(engine: State<'_, EngineState>) -> serde_json::Value {
+    serde_json::to_value(engine::first_run::last_report(&engine.ctx)).unwrap_or_default()
+}
+
+/// Blocking LLM completion using the configured default model.
+#[tauri::command]
+async fn llm_complete<R: Runtime>(app: AppHandle<R>, args: serde_json::Value) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result = engine::llm::run_complete(args, &engine.ctx).await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Streaming LLM completion. Text fragments arrive as `llm:delta` events
+/// (pass a `run_id` in args to correlate them); the full result is returned
+/// once the stream ends.
+#[tauri::command]
+async fn llm_stream<R: Runtime>(app: AppHandle<R>, args: serde_json::Value) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result = engine::llm::run_stream(args, &engine.ctx).await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Check the release manifest, as `appctl update-check`.
+#[tauri::command]
+async fn update_check<R: Runtime>(app: AppHandle<R>, args: serde_json::Value) -> serde_json::Value {
+    let engine = app.state::<EngineState>();
+    let result = engine::updates::run_check(args, &engine.ctx).await;
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Download and verify the update for this platform (`update:downloaded`
+/// fires when it lands). With `{ "install": true }` the engine only checks,
+/// and the updater plugin downloads, verifies against its own configured
+/// key, and installs the update itself.
+#[tauri::command]
+async fn update_download<R: Runtime>(
+    app: AppHandle<R>,
+    args: serde_json::Value,
+) -> serde_json::Value {
+    let install = args
+        .get("install")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let engine = app.state::<EngineState>();
+    if !install {
+        let result = engine::updates::run_download(args, &engine.ctx).await;
+        return serde_json::to_value(&result).unwrap_or_default();
+    }
+    let mut result = engine::updates::run_check(args, &engine.ctx).await;
+    let available = result
+        .data
+        .as_ref()
+        .is_some_and(|d| d["update_available"] == true);
+    if result.status == engine::types::Status::Pass && available {
+        let installed = install_update(&app).await;
+        if let Some(data) = result.data.as_mut() {
+            data["installed"] = installed.is_ok().into();
+        }
+        if let Err(e) = installed {
+            tracing::warn!("update install failed: {}", e);
+            result.status = engine::types::Status::Error;
+            result.error = Some(engine::types::ErrorInfo {
+                code: engine::types::ErrorCode::InternalError,
+                message: format!("install failed: {}", e),
+                details: serde_json::Value::Null,
+            });
+        }
+    }
+    serde_json::to_value(&result).unwrap_or_default()
+}
+
+/// Let the updater plugin fetch, verify and install the pending update.
+async fn install_update<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    let update = app
+        .updater()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "updater plugin reports no pending update".to_string())?;
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Invoke handler for every engine-backed command. Requires [`EngineState`]
+/// to be managed on the app.
+pub fn invoke_handler<R: Runtime>() -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static
+{
+    tauri::generate_handler![
+        greet,
+        get_app_config,
+        engine_call,
+        engine_list_commands,
+        engine_list_probes,
+        engine_probe,
+        engine_probe_suite,
+        engine_doctor,
+        engine_compatibility,
+        engine_first_run,
+        engine_first_run_report,
+        engine_ready_check,
+        engine_export_diagnostics,
+        window_info,
+        window_set,
+        state_get,
+        state_set,
+        state_subscribe,
+        llm_complete,
+        llm_stream,
+        update_check,
+        update_download,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine::AppContext;
+    use tauri::test::{mock_builder, mock_context, noop_assets};
+
+    #[test]
+    fn test_commands_use_managed_engine_state() {
+        let app = mock_builder()
+            .manage(EngineState::new(AppContext::default_headless()))
+            .invoke_handler(invoke_handler())
+            .build(mock_context(noop_assets()))
+            .expect("failed to build mock app");
+
+        let result = tauri::async_runtime::block_on(engine_call(
+            app.handle().clone(),
+            "ping".into(),
+            serde_json::json!({}),
+        ));
+        assert_eq!(result["status"], "pass");
+        assert_eq!(result["data"]["pong"], true);
+        assert!(engine_list_commands(app.state()).contains(&"ping".to_string()));
+        let state = tauri::async_runtime::block_on(state_subscribe(
+            app.handle().clone(),
+            serde_json::json!({}),
+        ));
+        assert_eq!(state["data"]["topic"], "state:changed");
+        assert!(engine_list_probes(app.state())
+            .iter()
+            .any(|p| p.name == "filesystem"));
+    }
+}
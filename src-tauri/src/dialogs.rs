@@ -0,0 +1,66 @@
+//! [`DialogOps`] backed by the dialog plugin, so `dialog_*` commands show
+//! native dialogs. Engine commands run off the main thread, which is what
+//! the plugin's blocking calls require.
+
+use engine::traits::{CapError, CapResult, DialogOps, FileDialogOptions};
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder, FilePath, MessageDialogButtons};
+
+pub struct TauriDialogs<R: Runtime>(pub AppHandle<R>);
+
+impl<R: Runtime> TauriDialogs<R> {
+    fn file_dialog(&self, options: &FileDialogOptions) -> FileDialogBuilder<R> {
+        let mut builder = self.0.dialog().file();
+        if let Some(title) = &options.title {
+            builder = builder.set_title(title);
+        }
+        if let Some(directory) = &options.directory {
+            builder = builder.set_directory(directory);
+        }
+        if let Some(name) = &options.file_name {
+            builder = builder.set_file_name(name);
+        }
+        for filter in &options.filters {
+            let extensions: Vec<&str> = filter.extensions.iter().map(String::as_str).collect();
+            builder = builder.add_filter(&filter.name, &extensions);
+        }
+        builder
+    }
+}
+
+fn into_path(picked: Option<FilePath>) -> CapResult<Option<PathBuf>> {
+    picked
+        .map(|p| p.into_path().map_err(|e| CapError::Other(e.to_string())))
+        .transpose()
+}
+
+impl<R: Runtime> DialogOps for TauriDialogs<R> {
+    fn open_file(&self, options: &FileDialogOptions) -> CapResult<Option<PathBuf>> {
+        into_path(self.file_dialog(options).blocking_pick_file())
+    }
+
+    fn save_file(&self, options: &FileDialogOptions) -> CapResult<Option<PathBuf>> {
+        into_path(self.file_dialog(options).blocking_save_file())
+    }
+
+    fn confirm(&self, title: &str, message: &str) -> CapResult<bool> {
+        Ok(self
+            .0
+            .dialog()
+            .message(message)
+            .title(title)
+            .buttons(MessageDialogButtons::OkCancel)
+            .blocking_show())
+    }
+
+    fn message(&self, title: &str, message: &str) -> CapResult<()> {
+        self.0
+            .dialog()
+            .message(message)
+            .title(title)
+            .buttons(MessageDialogButtons::Ok)
+            .blocking_show();
+        Ok(())
+    }
+}
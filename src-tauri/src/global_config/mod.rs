@@ -1,3 +1,7 @@
+mod sections;
+
+pub use sections::*;
+
 use config::{Config, ConfigError, Environment, File};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -16,6 +20,67 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     #[serde(default)]
     pub features: HashMap<String, bool>,
+    #[serde(default)]
+    pub asset_gen: AssetGenConfig,
+    /// Global shortcuts bound to engine commands (see `engine::shortcuts`).
+    #[serde(default)]
+    pub shortcuts: Vec<engine::shortcuts::ShortcutBinding>,
+    /// Application menu spec (see `engine::menu`); empty keeps Tauri's default.
+    #[serde(default)]
+    pub menu: Vec<engine::menu::MenuNode>,
+    /// URLs the app may open (see `engine::opener`).
+    #[serde(default)]
+    pub opener: engine::opener::OpenerPolicy,
+    /// Endpoints the network probe checks (see `engine::endpoints`).
+    #[serde(default)]
+    pub network: engine::endpoints::NetworkProbeConfig,
+    /// Skip network-touching commands and probes (`$APP__OFFLINE` wins).
+    #[serde(default)]
+    pub offline: bool,
+    /// CA bundle and client certificate for HTTP (see `engine::tls`).
+    #[serde(default)]
+    pub tls: engine::tls::TlsConfig,
+    /// Named probe suites, added to the built-in ones (see `engine::suites`).
+    #[serde(default)]
+    pub probe_suites: std::collections::BTreeMap<String, engine::suites::ProbeSuite>,
+    /// Startup checks; empty uses the engine's built-in ones (see
+    /// `engine::first_run`).
+    #[serde(default)]
+    pub first_run: Vec<engine::first_run::FirstRunCheck>,
+    /// Opt-in usage telemetry upload (see `engine::telemetry`); off without
+    /// an endpoint.
+    #[serde(default)]
+    pub telemetry: engine::telemetry::TelemetryConfig,
+    /// Current policy versions and consent-gated features (see
+    /// `engine::consent`).
+    #[serde(default)]
+    pub consent: engine::consent::ConsentConfig,
+    /// Data backup directory, retention, and exclusions (see
+    /// `engine::backup`).
+    #[serde(default)]
+    pub backup: engine::backup::BackupConfig,
+    /// Retries and retention of queued jobs (see `engine::queue`).
+    #[serde(default)]
+    pub queue: engine::queue::QueueConfig,
+    /// Translated user-facing error text, by locale (see `engine::explain`).
+    #[serde(default)]
+    pub error_messages: std::collections::BTreeMap<String, engine::explain::ErrorCatalog>,
+    /// Most bytes `read_file` returns at once; `$APP__MAX_READ_BYTES` wins
+    /// (see `engine::context`).
+    #[serde(default)]
+    pub max_read_bytes: Option<u64>,
+    /// Per-command timeouts and soft memory limits for `engine_call`;
+    /// `$APP__COMMAND_LIMITS` wins (see `engine::limits`).
+    #[serde(default)]
+    pub command_limits: engine::limits::CommandLimits,
+    /// Report a hash instead of the host name in results; so does
+    /// `$APP__REDACT_HOSTNAME=1` (see `engine::host`).
+    #[serde(default)]
+    pub redact_hostname: bool,
+    /// Run heavy commands in an `appctl serve` child process (see
+    /// `crate::sidecar`).
+    #[serde(default)]
+    pub sidecar: crate::sidecar::SidecarConfig,
 
     // Environment variables (optional in config file, usually injected)
     #[serde(skip_serializing)]
@@ -46,6 +111,31 @@ impl AppConfig {
     pub fn gemini_api_key(&self) -> Option<&str> {
         self.gemini_api_key.as_deref()
     }
+
+    /// Build the engine's LLM settings (default model + API keys) from config.
+    pub fn llm_settings(&self) -> engine::llm::LlmSettings {
+        use engine::llm::{LlmProvider, LlmSettings};
+
+        let mut settings = LlmSettings {
+            default_model: self.default_llm.default_model.clone(),
+            default_temperature: Some(self.default_llm.default_temperature),
+            default_max_tokens: u32::try_from(self.default_llm.default_max_tokens).ok(),
+            ..LlmSettings::default()
+        };
+        let keys = [
+            (LlmProvider::Openai, self.openai_api_key()),
+            (LlmProvider::Anthropic, self.anthropic_api_key()),
+            (LlmProvider::Groq, self.groq_api_key()),
+            (LlmProvider::Perplexity, self.perplexity_api_key()),
+            (LlmProvider::Gemini, self.gemini_api_key()),
+        ];
+        for (provider, key) in keys {
+            if let Some(key) = key.filter(|k| !k.trim().is_empty()) {
+                settings.api_keys.insert(provider, key.to_string());
+            }
+        }
+        settings
+    }
 }
 
 /// A sanitized version of the configuration intended for exposure to the frontend.
@@ -79,90 +169,6 @@ fn default_dev_env() -> String {
     "dev".to_string()
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct ExampleParent {
-    pub example_child: String,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct DefaultLlm {
-    pub default_model: String,
-    pub fallback_model: Option<String>,
-    pub default_temperature: f32,
-    pub default_max_tokens: i32,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct LlmConfig {
-    pub cache_enabled: bool,
-    pub retry: RetryConfig,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct RetryConfig {
-    pub max_attempts: i32,
-    pub min_wait_seconds: i32,
-    pub max_wait_seconds: i32,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct LoggingConfig {
-    pub verbose: bool,
-    pub format: LoggingFormatConfig,
-    pub levels: LoggingLevelsConfig,
-    #[serde(default)]
-    pub redaction: RedactionConfig,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct LoggingFormatConfig {
-    pub show_time: bool,
-    pub show_session_id: bool,
-    pub location: LoggingLocationConfig,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct LoggingLocationConfig {
-    pub enabled: bool,
-    pub show_file: bool,
-    pub show_function: bool,
-    pub show_line: bool,
-    pub show_for_info: bool,
-    pub show_for_debug: bool,
-    pub show_for_warning: bool,
-    pub show_for_error: bool,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct LoggingLevelsConfig {
-    pub debug: bool,
-    pub info: bool,
-    pub warning: bool,
-    pub error: bool,
-    pub critical: bool,
-}
-
-#[derive(Debug, Deserialize, Serialize, Default, Clone)]
-pub struct RedactionConfig {
-    #[serde(default = "true_default")]
-    pub enabled: bool,
-    #[serde(default = "true_default")]
-    pub use_default_pii: bool,
-    #[serde(default)]
-    pub patterns: Vec<RedactionPattern>,
-}
-
-fn true_default() -> bool {
-    true
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct RedactionPattern {
-    pub name: String,
-    pub regex: String,
-    pub placeholder: String,
-}
-
 #[derive(Clone, Copy)]
 struct ConfigStorage {
     app: &'static AppConfig,
@@ -416,6 +422,23 @@ mod tests {
                 redaction: RedactionConfig::default(),
             },
             features: HashMap::new(),
+            asset_gen: AssetGenConfig::default(),
+            shortcuts: Vec::new(),
+            menu: Vec::new(),
+            opener: Default::default(),
+            network: Default::default(),
+            offline: false,
+            tls: Default::default(),
+            probe_suites: Default::default(),
+            first_run: Vec::new(),
+            telemetry: Default::default(),
+            consent: Default::default(),
+            backup: Default::default(),
+            queue: Default::default(),
+            error_messages: Default::default(),
+            max_read_bytes: None,
+            command_limits: Default::default(),
+            redact_hostname: false,
             openai_api_key: Some("secret-key".to_string()),
             anthropic_api_key: None,
             groq_api_key: None,
@@ -0,0 +1,116 @@
+//! Config sections nested in [`AppConfig`](super::AppConfig): LLM
+//! defaults, asset generation, logging, and redaction.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ExampleParent {
+    pub example_child: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DefaultLlm {
+    pub default_model: String,
+    pub fallback_model: Option<String>,
+    pub default_temperature: f32,
+    pub default_max_tokens: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LlmConfig {
+    pub cache_enabled: bool,
+    pub retry: RetryConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RetryConfig {
+    pub max_attempts: i32,
+    pub min_wait_seconds: i32,
+    pub max_wait_seconds: i32,
+}
+
+/// Settings for the `asset-gen` binary.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AssetGenConfig {
+    /// `gemini`, `openai`, `local`, or `mock`.
+    #[serde(default = "default_asset_gen_provider")]
+    pub provider: String,
+    /// Source images for the `local` provider, relative to the repo root.
+    #[serde(default = "default_asset_gen_local_dir")]
+    pub local_dir: String,
+}
+
+impl Default for AssetGenConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_asset_gen_provider(),
+            local_dir: default_asset_gen_local_dir(),
+        }
+    }
+}
+
+fn default_asset_gen_provider() -> String {
+    "gemini".to_string()
+}
+
+fn default_asset_gen_local_dir() -> String {
+    "media/source".to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoggingConfig {
+    pub verbose: bool,
+    pub format: LoggingFormatConfig,
+    pub levels: LoggingLevelsConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoggingFormatConfig {
+    pub show_time: bool,
+    pub show_session_id: bool,
+    pub location: LoggingLocationConfig,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoggingLocationConfig {
+    pub enabled: bool,
+    pub show_file: bool,
+    pub show_function: bool,
+    pub show_line: bool,
+    pub show_for_info: bool,
+    pub show_for_debug: bool,
+    pub show_for_warning: bool,
+    pub show_for_error: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoggingLevelsConfig {
+    pub debug: bool,
+    pub info: bool,
+    pub warning: bool,
+    pub error: bool,
+    pub critical: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct RedactionConfig {
+    #[serde(default = "true_default")]
+    pub enabled: bool,
+    #[serde(default = "true_default")]
+    pub use_default_pii: bool,
+    #[serde(default)]
+    pub patterns: Vec<RedactionPattern>,
+}
+
+fn true_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RedactionPattern {
+    pub name: String,
+    pub regex: String,
+    pub placeholder: String,
+}
@@ -1,61 +1,162 @@
+mod app_menu;
+mod commands;
+mod dialogs;
 pub mod global_config;
 pub mod logging;
+mod opener;
+mod shortcuts;
+pub mod sidecar;
+mod startup;
+mod window_state;
 
+pub use commands::invoke_handler;
 pub use global_config as config;
 
-use global_config::FrontendConfig;
 // ---------------------------------------------------------------------------
 // Engine integration
 // ---------------------------------------------------------------------------
 
-use engine::{AppContext, CommandRegistry};
-use std::sync::OnceLock;
+use engine::events::EventSink;
+use engine::llm::http::HttpLlm;
+use engine::platform::ReqwestNetwork;
+use engine::types::EngineEvent;
+use engine::{AppContext, CommandRegistry, ProbeRegistry};
+use startup::{
+    build_sidecar, start_first_run, start_queue, start_readiness, start_sidecar, start_telemetry,
+};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 
-static ENGINE_CTX: OnceLock<AppContext> = OnceLock::new();
-static ENGINE_REGISTRY: OnceLock<CommandRegistry> = OnceLock::new();
-
-fn engine_ctx() -> &'static AppContext {
-    ENGINE_CTX.get_or_init(AppContext::default_platform)
+/// Engine context, command registry, and probe registry shared by every
+/// Tauri command.
+///
+/// Built in `setup()` and handed to Tauri as managed state, so tests can
+/// manage one wrapping a mock context instead.
+pub struct EngineState {
+    /// Shared with command workers, which outlive a call that hit its
+    /// `command_limits` timeout.
+    pub ctx: Arc<AppContext>,
+    pub registry: CommandRegistry,
+    pub probes: ProbeRegistry,
+    /// `appctl serve` child that runs the heavy commands, in sidecar mode.
+    pub sidecar: Option<Arc<sidecar::Sidecar>>,
 }
 
-fn engine_registry() -> &'static CommandRegistry {
-    ENGINE_REGISTRY.get_or_init(CommandRegistry::new)
+impl EngineState {
+    pub fn new(ctx: AppContext) -> Self {
+        Self {
+            ctx: Arc::new(ctx),
+            registry: CommandRegistry::new(),
+            probes: ProbeRegistry::new(),
+            sidecar: None,
+        }
+    }
 }
 
-// ---------------------------------------------------------------------------
-// Tauri commands – thin wrappers that delegate to engine
-// ---------------------------------------------------------------------------
-
-#[tauri::command]
-fn greet(name: &str) -> String {
-    // Simple greeting – delegates to engine ping to prove wiring
-    let result = engine_registry().execute("ping", serde_json::json!({}), engine_ctx());
-    format!(
-        "Hello, {}! You've been greeted from Rust! (engine status: {:?})",
-        name, result.status
-    )
-}
+/// Forwards engine events to the frontend, using the topic as the Tauri
+/// event name (e.g. `llm:delta`, `probe:finished`).
+struct TauriEventSink<R: Runtime>(AppHandle<R>);
 
-#[tauri::command]
-fn get_app_config() -> &'static FrontendConfig {
-    global_config::get_frontend_config()
+impl<R: Runtime> EventSink for TauriEventSink<R> {
+    fn send(&self, event: &EngineEvent) {
+        if let Err(e) = self.0.emit(&event.topic, event) {
+            tracing::warn!("failed to emit {}: {}", event.topic, e);
+        }
+    }
 }
 
-/// Generic command invocation – call any engine command by name.
-#[tauri::command]
-fn engine_call(cmd: String, args: serde_json::Value) -> serde_json::Value {
-    let result = engine_registry().execute(&cmd, args, engine_ctx());
-    serde_json::to_value(&result).unwrap_or_default()
+/// Context used by the app: LLM and TLS settings, offline mode, shortcuts,
+/// the menu spec, the opener allowlist, network probe endpoints, and probe
+/// suites from the global config, LLM API keys it lacks from the OS secret
+/// store (`appctl credentials set --namespace llm <provider>`), native
+/// windows and opener, state in the app data dir, prompt templates in the
+/// app config dir (unless overridden), every engine event forwarded to the
+/// frontend, and recent log lines kept for diagnostics bundles.
+fn build_engine_ctx<R: Runtime>(app: &AppHandle<R>) -> AppContext {
+    let config = global_config::get_config();
+    let mut llm_settings = config.llm_settings();
+    engine::credentials::fill_llm_keys(
+        &mut llm_settings,
+        &engine::credentials::SystemSecrets,
+        &app.config().identifier,
+    );
+    let llm = HttpLlm::new(llm_settings);
+    let network = ReqwestNetwork::with_tls(config.tls.clone().with_env_overrides());
+    let mut ctx = AppContext::default_platform()
+        .with_network(Box::new(network))
+        .with_llm(Box::new(llm))
+        .with_windows(Box::new(window_state::TauriWindows(app.clone())))
+        .with_opener(Box::new(opener::TauriOpener(app.clone())))
+        .with_dialogs(Box::new(dialogs::TauriDialogs(app.clone())))
+        .with_shortcuts(Box::new(shortcuts::TauriShortcuts::new(app.clone())))
+        .with_event_sink(TauriEventSink(app.clone()))
+        .with_recent_logs(logging::recent_logs());
+    ctx.shortcut_bindings = config.shortcuts.clone();
+    ctx.menu = config.menu.clone();
+    ctx.opener_policy = config.opener.clone();
+    ctx.consent = config.consent.clone();
+    ctx.backup = config.backup.clone();
+    ctx.queue = config.queue.clone();
+    ctx.error_catalogs = config.error_messages.clone();
+    if std::env::var_os(engine::context::OFFLINE_ENV).is_none() {
+        ctx.offline = config.offline;
+    }
+    if std::env::var_os(engine::endpoints::ENDPOINTS_ENV).is_none() {
+        ctx.network_probe = config.network.clone();
+    }
+    let mut suites = engine::suites::builtin();
+    suites.extend(config.probe_suites.clone());
+    ctx.probe_suites = engine::suites::with_env_overrides(suites);
+    if let Some(limit) = config.max_read_bytes {
+        if std::env::var_os(engine::context::MAX_READ_BYTES_ENV).is_none() {
+            ctx.max_read_bytes = limit;
+        }
+    }
+    if std::env::var_os(engine::limits::COMMAND_LIMITS_ENV).is_none() {
+        ctx.command_limits = config.command_limits.clone();
+    }
+    if std::env::var_os(engine::telemetry::TELEMETRY_URL_ENV).is_none() {
+        ctx.telemetry = config.telemetry.clone();
+    }
+    if !config.first_run.is_empty() {
+        ctx.first_run_checks = engine::first_run::with_env_override(config.first_run.clone());
+    }
+    engine::host::set_app_version(app.package_info().version.to_string());
+    engine::host::set_redact_hostname(config.redact_hostname);
+    ctx.app_id = app.config().identifier.clone();
+    ctx.app_name = app.package_info().name.clone();
+    if let Ok(dir) = app.path().app_data_dir() {
+        ctx.data_dir = dir;
+    }
+    apply_updater_config(app, &mut ctx.update_settings);
+    if std::env::var_os(engine::prompts::PROMPTS_DIR_ENV).is_none() {
+        if let Ok(dir) = app.path().app_config_dir() {
+            ctx.prompts_dir = dir.join("prompts");
+        }
+    }
+    ctx
 }
 
-/// List all available engine commands.
-#[tauri::command]
-fn engine_list_commands() -> Vec<String> {
-    engine_registry()
-        .list()
-        .into_iter()
-        .map(String::from)
-        .collect()
+/// Point engine update checks at the updater plugin's first endpoint and
+/// public key, and at this app's version. Env overrides win.
+fn apply_updater_config<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &mut engine::updates::UpdateSettings,
+) {
+    settings.current_version = app.package_info().version.to_string();
+    let Some(updater) = app.config().plugins.0.get("updater") else {
+        return;
+    };
+    if std::env::var_os(engine::updates::MANIFEST_URL_ENV).is_none() {
+        if let Some(url) = updater["endpoints"][0].as_str() {
+            settings.manifest_url = Some(url.to_string());
+        }
+    }
+    if std::env::var_os(engine::updates::PUBKEY_ENV).is_none() {
+        if let Some(pubkey) = updater["pubkey"].as_str() {
+            settings.pubkey = Some(pubkey.to_string());
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -72,14 +173,52 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(shortcuts::plugin())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            get_app_config,
-            engine_call,
-            engine_list_commands,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .setup(|app| {
+            let ctx = build_engine_ctx(app.handle());
+            let sidecar = build_sidecar(&ctx);
+            app.manage(EngineState {
+                sidecar,
+                ..EngineState::new(ctx)
+            });
+            window_state::restore(app.handle());
+            app_menu::install(app.handle())?;
+            let engine = app.state::<EngineState>();
+            for report in engine::shortcuts::register_all(&engine.ctx, &engine.registry) {
+                if !report.is_valid() {
+                    tracing::warn!("shortcut {}: {}", report.keys, report.problems.join("; "));
+                }
+            }
+            if let Err(e) = engine::session::forward(&engine.ctx) {
+                tracing::warn!("power/session events unavailable: {}", e);
+            }
+            start_readiness(app.handle());
+            start_queue(app.handle());
+            start_first_run(app.handle());
+            start_telemetry(app.handle());
+            start_sidecar(app.handle());
+            Ok(())
+        })
+        .on_menu_event(app_menu::on_event)
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                window_state::save(window.app_handle(), window.label());
+            }
+        })
+        .invoke_handler(invoke_handler())
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::ExitRequested { .. } => window_state::save_all(app),
+            tauri::RunEvent::Exit => {
+                window_state::save_all(app);
+                if let Some(sidecar) = app.state::<EngineState>().sidecar.clone() {
+                    tauri::async_runtime::block_on(sidecar.stop());
+                }
+            }
+            _ => {}
+        });
 }
@@ -1,10 +1,17 @@
 use crate::global_config::{get_config, AppConfig};
+use engine::diagnostics::LogBuffer;
 use regex::Regex;
 use std::io;
 use std::sync::{Arc, OnceLock};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter, Layer};
 
 static SESSION_ID: OnceLock<String> = OnceLock::new();
+static RECENT_LOGS: OnceLock<LogBuffer> = OnceLock::new();
+
+/// The last redacted log lines, for diagnostics bundles.
+pub fn recent_logs() -> LogBuffer {
+    RECENT_LOGS.get_or_init(LogBuffer::default).clone()
+}
 
 fn get_session_id() -> &'static str {
     SESSION_ID.get_or_init(|| {
@@ -22,6 +29,7 @@ struct RedactingWriter<W> {
     inner: W,
     patterns: Arc<Vec<(Regex, String)>>,
     session_id: Option<Arc<String>>,
+    recent: Option<LogBuffer>,
 }
 
 impl<W: io::Write> io::Write for RedactingWriter<W> {
@@ -43,6 +51,9 @@ impl<W: io::Write> io::Write for RedactingWriter<W> {
             }
         }
         self.inner.write_all(redacted.as_bytes())?;
+        if let Some(ref logs) = self.recent {
+            logs.push(&redacted);
+        }
         Ok(buf.len())
     }
 
@@ -54,6 +65,7 @@ impl<W: io::Write> io::Write for RedactingWriter<W> {
 struct RedactingMakeWriter {
     patterns: Arc<Vec<(Regex, String)>>,
     session_id: Option<Arc<String>>,
+    recent: LogBuffer,
 }
 
 impl<'a> fmt::MakeWriter<'a> for RedactingMakeWriter {
@@ -64,6 +76,7 @@ impl<'a> fmt::MakeWriter<'a> for RedactingMakeWriter {
             inner: io::stdout(),
             patterns: self.patterns.clone(),
             session_id: self.session_id.clone(),
+            recent: Some(self.recent.clone()),
         }
     }
 }
@@ -130,6 +143,7 @@ pub fn init_logging() {
     let make_writer = RedactingMakeWriter {
         patterns,
         session_id,
+        recent: recent_logs(),
     };
 
     // Use Layer::boxed() to unify the types of the if/else branches
@@ -174,6 +188,7 @@ mod tests {
             inner: buffer,
             patterns: Arc::new(patterns),
             session_id: session_id.map(|s| Arc::new(s.to_string())),
+            recent: None,
         }
     }
 
@@ -209,6 +224,17 @@ mod tests {
         assert!(!out.contains('['));
     }
 
+    #[test]
+    fn test_recent_logs_get_redacted_lines() {
+        let mut buf = Vec::new();
+        let logs = LogBuffer::new(10);
+        let patterns = vec![(Regex::new(r"sk-\w+").unwrap(), "[KEY]".to_string())];
+        let mut w = make_writer(&mut buf, patterns, Some("SESS01"));
+        w.recent = Some(logs.clone());
+        w.write_all(b"using sk-abc123\n").unwrap();
+        assert_eq!(logs.lines(), vec!["[SESS01] using [KEY]"]);
+    }
+
     #[test]
     fn test_session_id_skipped_for_whitespace_only_input() {
         let mut buf = Vec::new();
@@ -415,6 +441,7 @@ mod tests {
                 redaction: RedactionConfig::default(),
             },
             features: HashMap::new(),
+            asset_gen: AssetGenConfig::default(),
             openai_api_key: None,
             anthropic_api_key: None,
             groq_api_key: None,
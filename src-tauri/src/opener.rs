@@ -0,0 +1,26 @@
+//! [`OpenerOps`] backed by the opener plugin. The frontend has no opener
+//! permissions of its own; it goes through the engine's `open_url` /
+//! `reveal_path` commands, which apply the allowlist (see [`engine::opener`]).
+
+use engine::traits::{CapError, CapResult, OpenerOps};
+use std::path::Path;
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_opener::OpenerExt;
+
+pub struct TauriOpener<R: Runtime>(pub AppHandle<R>);
+
+impl<R: Runtime> OpenerOps for TauriOpener<R> {
+    fn open_url(&self, url: &str) -> CapResult<()> {
+        self.0
+            .opener()
+            .open_url(url, None::<&str>)
+            .map_err(|e| CapError::Other(e.to_string()))
+    }
+
+    fn reveal_path(&self, path: &Path) -> CapResult<()> {
+        self.0
+            .opener()
+            .reveal_item_in_dir(path)
+            .map_err(|e| CapError::Other(e.to_string()))
+    }
+}
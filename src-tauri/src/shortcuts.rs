@@ -0,0 +1,104 @@
+//! [`ShortcutOps`] backed by the global-shortcut plugin, and the plugin
+//! handler that runs the bound engine command when a shortcut is pressed
+//! (see [`engine::shortcuts`]).
+
+use crate::EngineState;
+use engine::traits::{CapError, CapResult, ShortcutOps};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+/// Parse an accelerator normalized by [`engine::shortcuts::normalize`]
+/// (e.g. `Ctrl+Shift+P`); the plugin reads the same modifier and key names.
+fn parse(accelerator: &str) -> CapResult<Shortcut> {
+    Shortcut::from_str(accelerator)
+        .map_err(|e| CapError::Other(format!("cannot register '{}': {}", accelerator, e)))
+}
+
+pub struct TauriShortcuts<R: Runtime> {
+    app: AppHandle<R>,
+    /// What this process registered; the plugin has no listing.
+    registered: Mutex<Vec<String>>,
+}
+
+impl<R: Runtime> TauriShortcuts<R> {
+    pub fn new(app: AppHandle<R>) -> Self {
+        Self {
+            app,
+            registered: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn list(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        self.registered.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<R: Runtime> ShortcutOps for TauriShortcuts<R> {
+    fn register(&self, accelerator: &str) -> CapResult<()> {
+        self.app
+            .global_shortcut()
+            .register(parse(accelerator)?)
+            .map_err(|e| CapError::Other(e.to_string()))?;
+        self.list().push(accelerator.to_string());
+        Ok(())
+    }
+
+    fn unregister_all(&self) -> CapResult<()> {
+        self.app
+            .global_shortcut()
+            .unregister_all()
+            .map_err(|e| CapError::Other(e.to_string()))?;
+        self.list().clear();
+        Ok(())
+    }
+
+    fn registered(&self) -> Vec<String> {
+        self.list().clone()
+    }
+}
+
+/// The global-shortcut plugin, calling [`on_shortcut`] for every press.
+pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(on_shortcut)
+        .build()
+}
+
+/// Run the command bound to `shortcut` through [`engine::shortcuts::dispatch`],
+/// off the main thread the press arrives on.
+fn on_shortcut<R: Runtime>(app: &AppHandle<R>, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let app = app.clone();
+    let id = shortcut.id();
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(engine) = app.try_state::<EngineState>() else {
+            return;
+        };
+        let Some(accelerator) = engine
+            .ctx
+            .shortcuts()
+            .registered()
+            .into_iter()
+            .find(|a| parse(a).is_ok_and(|s| s.id() == id))
+        else {
+            return;
+        };
+        if let Some(result) =
+            engine::shortcuts::dispatch(&accelerator, &engine.ctx, &engine.registry)
+        {
+            if let Some(err) = result.error {
+                tracing::warn!(
+                    "shortcut {} failed: {} – {}",
+                    accelerator,
+                    err.code,
+                    err.message
+                );
+            }
+        }
+    });
+}
@@ -0,0 +1,369 @@
+//! Sidecar mode: the app spawns `appctl serve` as a child process and sends
+//! heavy commands (`sidecar.commands`) to it over a Unix socket, so long
+//! file walks and exports don't grow or stall the GUI process.
+//!
+//! The sidecar is health-checked with `ping` every `health_interval_secs`
+//! and restarted when it exits or stops answering, up to `max_restarts`
+//! times. A ping that takes longer than the interval, or a call longer than
+//! `call_timeout_secs`, counts as not answering. While it is down, routed commands run in-process as before; a
+//! request that reached the sidecar but got no answer is reported as an
+//! error instead, since the command may already have run there.
+//!
+//! The sidecar gets the GUI's engine settings (command limits, read limit,
+//! consent config) through their `APP__*` variables and inherits the rest
+//! of the environment, e.g. `$APP__EXPORT` for redaction keys. Requests
+//! that need this process – `export_diagnostics`, which bundles its logs
+//! and config, and anything answered with a blob, which the client does
+//! not pass back – always run in-process.
+
+use engine::client::DaemonClient;
+use engine::types::CommandResult;
+use engine::AppContext;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::{Child, Command};
+
+/// How long a freshly spawned sidecar gets to answer `hello`.
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SidecarConfig {
+    pub enabled: bool,
+    /// The `appctl` binary; defaults to the one next to the app's
+    /// executable (where Tauri puts `externalBin` sidecars).
+    pub path: Option<PathBuf>,
+    /// Commands sent to the sidecar; everything else runs in-process.
+    pub commands: Vec<String>,
+    pub health_interval_secs: u64,
+    /// How long a routed call may take before the sidecar is treated as
+    /// hung and restarted.
+    pub call_timeout_secs: u64,
+    pub max_restarts: u32,
+}
+
+impl Default for SidecarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            commands: ["find_files", "read_file", "write_file"]
+                .map(String::from)
+                .to_vec(),
+            health_interval_secs: 10,
+            call_timeout_secs: 300,
+            max_restarts: 5,
+        }
+    }
+}
+
+impl SidecarConfig {
+    /// Whether this call goes to the sidecar: `cmd` is listed and neither
+    /// needs this process nor returns a blob.
+    pub fn routes(&self, cmd: &str, args: &serde_json::Value) -> bool {
+        let in_process = match cmd {
+            "export_diagnostics" => true,
+            "read_file" => args.get("encoding").and_then(|v| v.as_str()) == Some("binary"),
+            _ => false,
+        };
+        !in_process && self.commands.iter().any(|c| c == cmd)
+    }
+
+    fn health_interval(&self) -> Duration {
+        Duration::from_secs(self.health_interval_secs.max(1))
+    }
+
+    fn binary(&self) -> Result<PathBuf, String> {
+        if let Some(path) = &self.path {
+            return Ok(path.clone());
+        }
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let dir = exe.parent().ok_or("executable has no parent directory")?;
+        Ok(dir.join(format!("appctl{}", std::env::consts::EXE_SUFFIX)))
+    }
+}
+
+/// Why [`Sidecar::call`] produced no result.
+#[derive(Debug)]
+pub enum CallError {
+    /// No request was sent (the sidecar is not connected), so the caller
+    /// may run the command itself.
+    Unavailable(String),
+    /// The request was sent but no result came back. The command may have
+    /// run in the sidecar and must not be run again.
+    Failed(String),
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::Unavailable(e) | CallError::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Running {
+    child: Option<Child>,
+    client: Option<DaemonClient>,
+    restarts: u32,
+}
+
+/// One `appctl serve` child and the connection to it.
+pub struct Sidecar {
+    config: SidecarConfig,
+    socket: PathBuf,
+    data_dir: PathBuf,
+    env: Vec<(&'static str, String)>,
+    running: tokio::sync::Mutex<Running>,
+}
+
+impl Sidecar {
+    /// A sidecar listening on `socket`, keeping its state in `ctx`'s data
+    /// directory (the app's own, so both see the same history and
+    /// consents) and running under `ctx`'s settings.
+    pub fn new(config: SidecarConfig, socket: PathBuf, ctx: &AppContext) -> Self {
+        Self {
+            config,
+            socket,
+            data_dir: ctx.data_dir.clone(),
+            env: context_env(ctx),
+            running: tokio::sync::Mutex::new(Running::default()),
+        }
+    }
+
+    pub fn routes(&self, cmd: &str, args: &serde_json::Value) -> bool {
+        self.config.routes(cmd, args)
+    }
+
+    /// Spawn the daemon and wait until it answers `hello`.
+    pub async fn start(&self) -> Result<(), String> {
+        let mut running = self.running.lock().await;
+        self.spawn(&mut running).await
+    }
+
+    async fn spawn(&self, running: &mut Running) -> Result<(), String> {
+        running.client = None;
+        if let Some(mut old) = running.child.take() {
+            let _ = old.kill().await;
+        }
+        let binary = self.config.binary()?;
+        let child = Command::new(&binary)
+            .arg("serve")
+            .arg("--socket")
+            .arg(&self.socket)
+            .env(engine::context::DATA_DIR_ENV, &self.data_dir)
+            .envs(self.env.iter().map(|(k, v)| (*k, v)))
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("cannot start {}: {}", binary.display(), e))?;
+        running.child = Some(child);
+        let client = wait_ready(&self.socket).await?;
+        running.client = Some(client);
+        tracing::info!(
+            "sidecar {} serving on {}",
+            binary.display(),
+            self.socket.display()
+        );
+        Ok(())
+    }
+
+    /// Round-trip a `ping`; a sidecar that fails it, or takes longer than
+    /// the health interval, is disconnected.
+    pub async fn health_check(&self) -> Result<Duration, String> {
+        let mut running = self.running.lock().await;
+        let client = running.client.as_mut().ok_or("sidecar is not running")?;
+        let started = std::time::Instant::now();
+        let timeout = self.config.health_interval();
+        let pinged = tokio::time::timeout(
+            timeout,
+            client.request("call", serde_json::json!({ "cmd": "ping" })),
+        )
+        .await
+        .unwrap_or_else(|_| Err(format!("no answer to ping within {:?}", timeout)))
+        .and_then(|r| {
+            r.result
+                .ok_or_else(|| "ping answered without a result".into())
+        });
+        match pinged {
+            Ok(r) if r.error.is_none() => Ok(started.elapsed()),
+            Ok(r) => Err(r.error.map(|e| e.message).unwrap_or_default()),
+            Err(e) => {
+                running.client = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// Run `cmd` in the sidecar. Only [`CallError::Unavailable`] means the
+    /// caller should run the command itself. A call with no answer within
+    /// `call_timeout_secs` disconnects the sidecar, so the next health check
+    /// restarts it.
+    pub async fn call(
+        &self,
+        cmd: &str,
+        args: serde_json::Value,
+    ) -> Result<CommandResult, CallError> {
+        let mut running = self.running.lock().await;
+        let client = running
+            .client
+            .as_mut()
+            .ok_or_else(|| CallError::Unavailable("sidecar is not running".into()))?;
+        let timeout = Duration::from_secs(self.config.call_timeout_secs.max(1));
+        let response = tokio::time::timeout(
+            timeout,
+            client.request("call", serde_json::json!({ "cmd": cmd, "args": args })),
+        )
+        .await
+        .unwrap_or_else(|_| {
+            Err(format!(
+                "sidecar did not answer {} within {:?}",
+                cmd, timeout
+            ))
+        });
+        let response = match response {
+            Ok(r) => r,
+            Err(e) => {
+                running.client = None;
+                return Err(CallError::Failed(e));
+            }
+        };
+        match (response.result, response.error) {
+            (Some(result), _) => Ok(result),
+            (None, e) => Err(CallError::Failed(e.map(|e| e.message).unwrap_or_default())),
+        }
+    }
+
+    /// Health-check forever, restarting the sidecar when it has exited or
+    /// fails a check, until `max_restarts` is used up.
+    pub async fn supervise(&self) {
+        let interval = self.config.health_interval();
+        loop {
+            tokio::time::sleep(interval).await;
+            let exited = {
+                let mut running = self.running.lock().await;
+                match running.child.as_mut().map(|c| c.try_wait()) {
+                    Some(Ok(Some(status))) => Some(status.to_string()),
+                    Some(Err(e)) => Some(e.to_string()),
+                    Some(Ok(None)) => None,
+                    None => Some("not running".into()),
+                }
+            };
+            let problem = match exited {
+                Some(status) => format!("exited ({})", status),
+                None => match self.health_check().await {
+                    Ok(_) => continue,
+                    Err(e) => format!("failed its health check: {}", e),
+                },
+            };
+            let mut running = self.running.lock().await;
+            if running.restarts >= self.config.max_restarts {
+                tracing::error!(
+                    "sidecar {}; not restarting after {} restarts, running commands in-process",
+                    problem,
+                    running.restarts
+                );
+                running.client = None;
+                return;
+            }
+            running.restarts += 1;
+            tracing::warn!("sidecar {}; restarting ({})", problem, running.restarts);
+            if let Err(e) = self.spawn(&mut running).await {
+                tracing::warn!("sidecar restart failed: {}", e);
+            }
+        }
+    }
+
+    /// Stop the child, e.g. when the app exits.
+    pub async fn stop(&self) {
+        let mut running = self.running.lock().await;
+        running.client = None;
+        if let Some(mut child) = running.child.take() {
+            let _ = child.kill().await;
+        }
+        let _ = std::fs::remove_file(&self.socket);
+    }
+}
+
+/// The settings the GUI resolved from its own config, as the variables
+/// `appctl` reads them from.
+fn context_env(ctx: &AppContext) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            engine::limits::COMMAND_LIMITS_ENV,
+            serde_json::to_string(&ctx.command_limits).unwrap_or_default(),
+        ),
+        (
+            engine::context::MAX_READ_BYTES_ENV,
+            ctx.max_read_bytes.to_string(),
+        ),
+        (
+            engine::consent::CONSENT_ENV,
+            serde_json::to_string(&ctx.consent).unwrap_or_default(),
+        ),
+        (engine::context::APP_ID_ENV, ctx.app_id.clone()),
+    ]
+}
+
+/// Connect and `hello` until the daemon answers or [`READY_TIMEOUT`]
+/// passes.
+async fn wait_ready(socket: &Path) -> Result<DaemonClient, String> {
+    let deadline = tokio::time::Instant::now() + READY_TIMEOUT;
+    loop {
+        let attempt = async {
+            let mut client = DaemonClient::connect(Some(socket), None).await?;
+            client.hello().await?;
+            Ok::<_, String>(client)
+        };
+        match attempt.await {
+            Ok(client) => return Ok(client),
+            Err(e) if tokio::time::Instant::now() >= deadline => {
+                return Err(format!("sidecar did not come up: {}", e))
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_only_configured_commands() {
+        let mut config = SidecarConfig::default();
+        assert!(!config.enabled);
+        assert!(config.routes("find_files", &serde_json::json!({})));
+        assert!(!config.routes("ping", &serde_json::json!({})));
+        assert!(config.routes("read_file", &serde_json::json!({ "encoding": "base64" })));
+        assert!(!config.routes("read_file", &serde_json::json!({ "encoding": "binary" })));
+        config.commands.push("export_diagnostics".into());
+        assert!(!config.routes("export_diagnostics", &serde_json::json!({})));
+        assert!(config
+            .binary()
+            .unwrap()
+            .ends_with(format!("appctl{}", std::env::consts::EXE_SUFFIX)));
+    }
+
+    #[tokio::test]
+    async fn test_start_fails_without_a_binary() {
+        let dir = std::env::temp_dir();
+        let mut ctx = AppContext::default_headless();
+        ctx.data_dir = dir.clone();
+        let sidecar = Sidecar::new(
+            SidecarConfig {
+                path: Some(dir.join("no-such-appctl")),
+                ..SidecarConfig::default()
+            },
+            dir.join("no-such-appctl.sock"),
+            &ctx,
+        );
+        assert!(sidecar.start().await.unwrap_err().contains("cannot start"));
+        assert!(matches!(
+            sidecar.call("find_files", serde_json::json!({})).await,
+            Err(CallError::Unavailable(_))
+        ));
+    }
+}
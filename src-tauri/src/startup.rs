@@ -0,0 +1,119 @@
+//! Background work started once the app is set up: readiness gating, the
+//! task queue, first-run checks, telemetry uploads, and the sidecar.
+
+use crate::{global_config, sidecar, EngineState};
+use engine::AppContext;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Close the `splash` window and show `main`.
+pub(crate) fn reveal_main_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(main) = app.get_webview_window("main") {
+        let _ = main.show();
+        let _ = main.set_focus();
+    }
+    if let Some(splash) = app.get_webview_window("splash") {
+        let _ = splash.close();
+    }
+}
+
+/// Launch gating: if the app declares a `splash` window, keep `main`
+/// hidden (declare it with `"visible": false` to avoid a flash) until the
+/// readiness sequence passes. On failure the splash stays up with the
+/// `ready:*` events to show, and can retry with `engine_ready_check`.
+pub(crate) fn start_readiness<R: Runtime>(app: &AppHandle<R>) {
+    let gated = app.get_webview_window("splash").is_some();
+    if let (true, Some(main)) = (gated, app.get_webview_window("main")) {
+        let _ = main.hide();
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let engine = app.state::<EngineState>();
+        let result =
+            engine::readiness::run_ready_check(&engine.ctx, &engine.registry, &engine.probes).await;
+        match result.error {
+            None if gated => reveal_main_window(&app),
+            None => {}
+            Some(e) => tracing::warn!("not ready: {}", e.message),
+        }
+    });
+}
+
+/// Work through the durable task queue in the background, resuming jobs
+/// a previous run left unfinished.
+pub(crate) fn start_queue<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let engine = app.state::<EngineState>();
+        engine::queue::run_worker(&engine.ctx, &engine.registry).await;
+    });
+}
+
+/// Startup sequence: until the first-run checks have passed once, run
+/// them in the background. Failures are logged with their remediation
+/// links.
+pub(crate) fn start_first_run<R: Runtime>(app: &AppHandle<R>) {
+    if engine::first_run::completed(&app.state::<EngineState>().ctx) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let engine = app.state::<EngineState>();
+        let result = engine::first_run::run_checks(&engine.ctx, &engine.probes).await;
+        let report: Option<engine::types::FirstRunReport> =
+            result.data.and_then(|d| serde_json::from_value(d).ok());
+        for check in report.iter().flat_map(|r| &r.checks) {
+            if let Some(link) = &check.remediation {
+                tracing::warn!("first-run check {} failed; see {}", check.title, link);
+            }
+        }
+    });
+}
+
+/// Sidecar mode: the sidecar for `sidecar:` config, if enabled, with its
+/// socket and state in the app data dir.
+pub(crate) fn build_sidecar(ctx: &AppContext) -> Option<Arc<sidecar::Sidecar>> {
+    let config = global_config::get_config().sidecar.clone();
+    if !config.enabled {
+        return None;
+    }
+    let socket = ctx.data_dir.join("sidecar.sock");
+    Some(Arc::new(sidecar::Sidecar::new(config, socket, ctx)))
+}
+
+/// Start the sidecar, then keep it healthy for the app's lifetime.
+pub(crate) fn start_sidecar<R: Runtime>(app: &AppHandle<R>) {
+    let Some(sidecar) = app.state::<EngineState>().sidecar.clone() else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = sidecar.start().await {
+            tracing::warn!("sidecar: {}; running commands in-process", e);
+        }
+        sidecar.supervise().await;
+    });
+}
+
+/// How often opted-in telemetry counts are offered for upload.
+const TELEMETRY_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Upload what the last session left pending, then a batch whenever
+/// enough runs pile up. Does nothing until the user opts in.
+pub(crate) fn start_telemetry<R: Runtime>(app: &AppHandle<R>) {
+    if app.state::<EngineState>().ctx.telemetry.endpoint.is_none() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let engine = app.state::<EngineState>();
+        let mut force = true;
+        loop {
+            let result = engine::telemetry::flush(&engine.ctx, force).await;
+            if let (engine::types::Status::Error, Some(e)) = (result.status, &result.error) {
+                tracing::debug!("telemetry upload: {}", e.message);
+            }
+            force = false;
+            tokio::time::sleep(TELEMETRY_FLUSH_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,105 @@
+//! Native window geometry for the engine: the [`WindowOps`] implementation
+//! behind `window_info` / `window_set`, plus saving each window's geometry
+//! on close or app exit and restoring it on launch (see [`engine::windows`]).
+
+use crate::EngineState;
+use engine::traits::{CapError, CapResult, WindowGeometry, WindowOps};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow};
+
+pub struct TauriWindows<R: Runtime>(pub AppHandle<R>);
+
+impl<R: Runtime> TauriWindows<R> {
+    fn window(&self, label: &str) -> CapResult<WebviewWindow<R>> {
+        self.0
+            .get_webview_window(label)
+            .ok_or_else(|| CapError::Other(format!("no window labelled '{}'", label)))
+    }
+}
+
+fn tauri_err(e: tauri::Error) -> CapError {
+    CapError::Other(e.to_string())
+}
+
+fn read_geometry<R: Runtime>(window: &WebviewWindow<R>) -> CapResult<WindowGeometry> {
+    let position = window.outer_position().map_err(tauri_err)?;
+    let size = window.inner_size().map_err(tauri_err)?;
+    Ok(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().map_err(tauri_err)?,
+    })
+}
+
+fn apply_geometry<R: Runtime>(
+    window: &WebviewWindow<R>,
+    geometry: &WindowGeometry,
+) -> CapResult<()> {
+    // Size and position first, so un-maximizing later lands on them.
+    if window.is_maximized().map_err(tauri_err)? {
+        window.unmaximize().map_err(tauri_err)?;
+    }
+    window
+        .set_size(PhysicalSize::new(geometry.width, geometry.height))
+        .map_err(tauri_err)?;
+    window
+        .set_position(PhysicalPosition::new(geometry.x, geometry.y))
+        .map_err(tauri_err)?;
+    if geometry.maximized {
+        window.maximize().map_err(tauri_err)?;
+    }
+    Ok(())
+}
+
+impl<R: Runtime> WindowOps for TauriWindows<R> {
+    fn labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.0.webview_windows().into_keys().collect();
+        labels.sort();
+        labels
+    }
+
+    fn geometry(&self, label: &str) -> CapResult<WindowGeometry> {
+        read_geometry(&self.window(label)?)
+    }
+
+    fn set_geometry(&self, label: &str, geometry: &WindowGeometry) -> CapResult<()> {
+        apply_geometry(&self.window(label)?, geometry)
+    }
+}
+
+/// Apply saved geometry to every open window that has an entry.
+pub fn restore<R: Runtime>(app: &AppHandle<R>) {
+    let engine = app.state::<EngineState>();
+    let saved = engine::windows::load(engine.ctx.fs(), &engine.ctx.data_dir);
+    for (label, window) in app.webview_windows() {
+        if let Some(geometry) = saved.get(&label) {
+            if let Err(e) = apply_geometry(&window, geometry) {
+                tracing::warn!("failed to restore window '{}': {}", label, e);
+            }
+        }
+    }
+}
+
+/// Save the geometry of every open window; called when the app is about to
+/// exit, since quitting (e.g. Cmd+Q or `app.exit`) does not close windows
+/// one by one first.
+pub fn save_all<R: Runtime>(app: &AppHandle<R>) {
+    for label in app.webview_windows().into_keys() {
+        save(app, &label);
+    }
+}
+
+/// Save the geometry of `label`; called when the window is about to close.
+pub fn save<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let Some(window) = app.get_webview_window(label) else {
+        return;
+    };
+    let engine = app.state::<EngineState>();
+    let saved = read_geometry(&window).and_then(|geometry| {
+        engine::windows::save(engine.ctx.fs(), &engine.ctx.data_dir, label, geometry)
+    });
+    if let Err(e) = saved {
+        tracing::warn!("failed to save window '{}': {}", label, e);
+    }
+}